@@ -1,17 +1,31 @@
-use crate::audio::{AudioEngine, NullAudioEngine, WasapiAudioEngine};
+use crate::audio::{AudioEngine, NullAudioEngine, WasapiAudioEngine, output_device_names_match};
 use crate::config;
-use crate::core::{BrowserEntryKind, HeaderSection, LyricsMode, StatsFilterFocus, TuneCore};
+use crate::core::{
+    BrowserEntryKind, CoverArtDownloadRequest, HeaderSection, LyricsFetchRequest,
+    LyricsLeaveAction, LyricsMode, SLEEP_TIMER_OPTIONS_MINUTES, StatsFilterFocus, TuneCore,
+    arrow_keys_label, balance_label, compact_layout_label, crossfade_lead_label,
+    daily_listen_goal_label, equalizer_band_label, playback_speed_label, sleep_on_silence_label,
+    stats_completion_label, stats_grace_label, track_gap_label,
+};
 use crate::library::{self, LibraryIndex, LibraryScanEvent, LibraryScanKind, MetadataEdit};
-use crate::model::{CoverArtTemplate, Theme};
+use crate::lyrics::{self, LyricsDocument};
+use crate::model::{
+    CoverArtTemplate, FolderEnterBehavior, LastOnlineJoin, SmartPlaylist, SmartPlaylistSort, Theme,
+};
 use crate::online::{
     OnlineSession, Participant, StreamQuality, TransportCommand, TransportEnvelope,
 };
 use crate::online_net::{
-    HomeRoomDirectoryEntry, LocalAction as NetworkLocalAction, NetworkEvent, NetworkRole,
-    OnlineNetwork, StreamTrackFormat, create_home_room, list_home_rooms, resolve_home_room,
-    verify_home_server,
+    HomeRoomDirectoryEntry, HomeServerHandle, LocalAction as NetworkLocalAction, NetworkEvent,
+    NetworkRole, OnlineNetwork, StreamTrackFormat, create_home_room, list_home_rooms,
+    resolve_home_room, verify_home_server,
 };
-use crate::stats::{self, ListenSessionRecord, StatsStore};
+use crate::online_secure::RoomTransport;
+use crate::player::{
+    ListenTracker, SilenceWatch, SleepTimer, StatsIdentityHint, advance_local_queue,
+    concise_audio_error,
+};
+use crate::stats::{self, ListenEvent, StatsStore};
 use anyhow::{Context, Result};
 use arboard::Clipboard;
 use base64::Engine;
@@ -25,7 +39,7 @@ use crossterm::terminal::{
 };
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 #[cfg(target_os = "linux")]
 use std::ffi::CString;
 use std::fs;
@@ -37,35 +51,67 @@ use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver};
 #[cfg(any(windows, target_os = "linux"))]
-use std::sync::{Mutex, OnceLock};
-use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
 
 #[cfg(windows)]
 const APP_INSTANCE_MUTEX: &str = "TuneTui.SingleInstance";
-#[cfg(windows)]
 const APP_CONSOLE_TITLE: &str = "TuneTUI";
 const MAX_VOLUME: f32 = 2.5;
 const VOLUME_STEP_COARSE: f32 = 0.05;
 const VOLUME_STEP_FINE: f32 = 0.01;
 const SCRUB_SECONDS_OPTIONS: [u16; 5] = [5, 10, 15, 30, 60];
 const STATS_TOP_SONGS_COUNT_OPTIONS: [u8; 5] = [5, 8, 10, 12, 15];
-const PARTIAL_LISTEN_FLUSH_SECONDS: u32 = 10;
-const LOOP_RESTART_END_WINDOW_SECONDS: u64 = 2;
-const LOOP_RESTART_START_WINDOW_SECONDS: u64 = 5;
-const LOOP_RESTART_FALLBACK_MIN_PREVIOUS_SECONDS: u64 = 20;
+/// Listen, Plays and Skips, in the order the Stats sort row shows them.
+const STATS_SORT_OPTION_COUNT: u8 = 3;
 const ONLINE_SYNC_CORRECTION_THRESHOLD_PAUSED_MS: i64 = 100;
 const ONLINE_SYNC_CORRECTION_THRESHOLD_OPTIONS_MS: [u16; 8] =
     [100, 150, 200, 300, 400, 500, 750, 1000];
 const MAX_ONLINE_EVENTS_PER_TICK: usize = 128;
 const ONLINE_DEFAULT_HOME_SERVER_PORT: u16 = 7878;
 const ONLINE_DEFAULT_HOME_SERVER_ADDR: &str = "127.0.0.1:7878";
+const ONLINE_QUEUE_PAGE_ROWS: usize = 8;
+/// How often a listener sends its measured drift to the room; syncs arrive
+/// about once a second.
+const ONLINE_DRIFT_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+/// Most matches the Find Track panel lists; more letters narrow the rest.
+const FUZZY_FIND_LIMIT: usize = 100;
 const ONLINE_PUBLIC_HOME_SERVER_ADDR: &str = "tunetui.online";
+// The monotonic clock stops while the machine sleeps, so resume is detected from wall time.
+const RESUME_WALL_CLOCK_GAP: Duration = Duration::from_secs(30);
+/// How far each "Balance 10% left/right" entry moves the balance.
+const BALANCE_STEP: f32 = 0.1;
+/// How often the main loop checks that the audio output device is still there.
+const OUTPUT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 const HOST_ONLY_LISTENER_LOCKED_STATUS: &str = "Room is host-only. Listener playback locked";
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Default)]
 pub struct AppStartupOptions {
     pub default_home_server_addr: Option<String>,
     pub home_server_connected: bool,
+    /// Home server started alongside the app (`--host --app`). It keeps relaying
+    /// after the local user leaves a room and stops when the app exits.
+    pub local_home_server: Option<HomeServerHandle>,
+    /// Skips the Windows single-instance mutex (`--multi-instance`).
+    pub allow_multiple_instances: bool,
+    /// Logs remote sync decisions to `sync_debug.log` (`--debug-sync`).
+    pub debug_sync: bool,
+    /// Nickname from `--nickname`, saved the same way as one typed into the prompt.
+    pub online_nickname: Option<String>,
+    /// Room to join or host as soon as the app is up (`--join`, `--host-room`).
+    pub startup_join: Option<StartupJoin>,
+}
+
+/// Room requested on the command line with `--join` or `--host-room`.
+#[derive(Debug, Clone, Default)]
+pub struct StartupJoin {
+    /// Room name on the default home server, or a `host[:port]/room/NAME` link.
+    pub target: String,
+    /// From `--room-password` or `TUNETUI_ROOM_PASSWORD`. Locked rooms without
+    /// one fall back to the password prompt; a hosted room without one is open.
+    pub password: Option<String>,
+    /// Create and host the room instead of joining it (`--host-room`).
+    pub host: bool,
 }
 
 #[cfg(target_os = "linux")]
@@ -153,6 +199,36 @@ struct LibraryRuntime {
     active_scan: Option<ActiveLibraryScan>,
     next_scan_id: u64,
     index: LibraryIndex,
+    change_watch: TrackChangeWatch,
+}
+
+const TRACK_CHANGE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const TRACK_CHANGE_POLL_BATCH: usize = 256;
+const TRACK_CHANGE_DEBOUNCE: Duration = Duration::from_millis(1500);
+
+/// Walks the library index a batch at a time looking for files whose size or mtime
+/// moved, so external tag edits show up without a full rescan. The files are
+/// stat'ed on a worker thread; only the metadata reload runs on the UI thread.
+#[derive(Default)]
+struct TrackChangeWatch {
+    cursor: usize,
+    last_poll: Option<Instant>,
+    pending: HashMap<PathBuf, (Option<library::LibraryTrackFingerprint>, Instant)>,
+    sweep: Option<Receiver<TrackChangeSweep>>,
+}
+
+/// Fingerprints read by one sweep: indexed files that changed since the index
+/// was saved, and the current fingerprint of every path already pending.
+#[derive(Default)]
+struct TrackChangeSweep {
+    changed: Vec<(PathBuf, Option<library::LibraryTrackFingerprint>)>,
+    rechecked: Vec<(PathBuf, Option<library::LibraryTrackFingerprint>)>,
+}
+
+struct StreamProgress {
+    requested_path: PathBuf,
+    received_bytes: u64,
+    total_bytes: Option<u64>,
 }
 
 struct OnlineRuntime {
@@ -160,6 +236,7 @@ struct OnlineRuntime {
     local_nickname: String,
     home_server_addr: String,
     home_server_connected: bool,
+    local_home_server: Option<HomeServerHandle>,
     nickname_configured: bool,
     last_transport_seq: u64,
     join_prompt_active: bool,
@@ -186,10 +263,19 @@ struct OnlineRuntime {
     pending_join_invite_code: String,
     room_code_revealed: bool,
     host_invite_modal_active: bool,
+    host_invite_qr_only: bool,
+    /// `host_invite_code` drawn as QR rows, built once when the dialog opens.
+    host_invite_qr_rows: Option<Arc<[String]>>,
     host_invite_code: String,
     host_invite_button: HostInviteModalButton,
+    /// Typing goes to the chat draft instead of the Online tab shortcuts.
+    chat_input_active: bool,
+    chat_input: String,
+    /// Set by the host's `s`; the main loop skips on its next pass.
+    skip_requested: bool,
     streamed_track_cache: HashMap<PathBuf, PathBuf>,
     pending_stream_path: Option<PathBuf>,
+    stream_progress: Option<StreamProgress>,
     remote_logical_track: Option<PathBuf>,
     remote_track_title: Option<String>,
     remote_track_artist: Option<String>,
@@ -197,7 +283,65 @@ struct OnlineRuntime {
     remote_provider_track_id: Option<String>,
     last_remote_transport_origin: Option<String>,
     last_periodic_sync_at: Instant,
+    last_drift_report_at: Option<Instant>,
     online_playback_source: OnlinePlaybackSource,
+    sync_debug_log: Option<SyncDebugLog>,
+}
+
+/// Appends one line per remote transport command to `sync_debug.log`, so sync
+/// behavior can be pasted into a bug report.
+struct SyncDebugLog {
+    file: fs::File,
+}
+
+impl SyncDebugLog {
+    fn open() -> Result<(Self, PathBuf)> {
+        crate::config::ensure_config_dir()?;
+        let path = crate::config::sync_debug_log_path()?;
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        Ok((Self { file }, path))
+    }
+
+    fn record(&mut self, line: &str) {
+        let epoch_ms = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis());
+        let _ = writeln!(self.file, "ts_ms={epoch_ms} {line}");
+    }
+}
+
+/// Inputs and outcome of one `SetPlaybackState` correction.
+struct SyncDecision<'a> {
+    path: &'a Path,
+    remote_ms: u64,
+    delay_ms: i64,
+    target_ms: i64,
+    local_ms: i64,
+    drift_ms: i64,
+    threshold_ms: i64,
+    paused: bool,
+    seeked: bool,
+}
+
+impl SyncDecision<'_> {
+    fn log_line(&self) -> String {
+        format!(
+            "event=set_playback_state path={:?} remote_ms={} delay_ms={} target_ms={} local_ms={} drift_ms={} threshold_ms={} paused={} action={}",
+            self.path.display().to_string(),
+            self.remote_ms,
+            self.delay_ms,
+            self.target_ms,
+            self.local_ms,
+            self.drift_ms,
+            self.threshold_ms,
+            self.paused,
+            if self.seeked { "seek" } else { "none" }
+        )
+    }
 }
 
 impl OnlineRuntime {
@@ -223,6 +367,7 @@ impl OnlineRuntime {
         }
         self.clear_streamed_track_cache();
         self.pending_stream_path = None;
+        self.stream_progress = None;
         self.remote_logical_track = None;
         self.remote_track_title = None;
         self.remote_track_artist = None;
@@ -250,6 +395,8 @@ impl OnlineRuntime {
         self.join_prompt_button = JoinPromptButton::Join;
         self.room_code_revealed = false;
         self.host_invite_modal_active = false;
+        self.host_invite_qr_only = false;
+        self.host_invite_qr_rows = None;
         self.host_invite_code.clear();
         self.host_invite_button = HostInviteModalButton::Copy;
         self.online_playback_source = OnlinePlaybackSource::LocalQueue;
@@ -262,9 +409,22 @@ impl OnlineRuntime {
         Some(crate::ui::HostInviteModalView {
             invite_code: self.host_invite_code.clone(),
             copy_selected: matches!(self.host_invite_button, HostInviteModalButton::Copy),
+            qr_only: self.host_invite_qr_only,
+            qr_rows: self.host_invite_qr_rows.clone(),
+            code_revealed: self.room_code_revealed,
         })
     }
 
+    /// Copy status for the invite dialog. Leaves the code out while it is
+    /// hidden behind the QR, so it never shows up on a shared screen.
+    fn invite_copied_status(&self) -> String {
+        if self.host_invite_qr_only && !self.room_code_revealed {
+            String::from("Copied invite code")
+        } else {
+            format!("Copied invite code: {}", self.host_invite_code)
+        }
+    }
+
     fn join_prompt_view(&self) -> Option<crate::ui::JoinPromptModalView> {
         if !self.join_prompt_active {
             return None;
@@ -336,6 +496,45 @@ impl OnlineRuntime {
         })
     }
 
+    fn online_status_view(&self, core: &TuneCore) -> Option<crate::ui::OnlineStatusView> {
+        let session = core.online.session.as_ref()?;
+        let role = if session
+            .local_participant()
+            .is_some_and(|participant| participant.is_host)
+        {
+            "Host"
+        } else {
+            "Client"
+        };
+        let connection = if self.network.is_some() {
+            "Connected"
+        } else {
+            "Disconnected"
+        };
+        let server_addr = if self.home_server_addr.trim().is_empty() {
+            String::from("no server")
+        } else {
+            self.home_server_addr.clone()
+        };
+        Some(crate::ui::OnlineStatusView {
+            role: String::from(role),
+            connection: String::from(connection),
+            server_addr,
+        })
+    }
+
+    fn buffering_view(&self) -> Option<crate::ui::BufferingView> {
+        let pending = self.pending_stream_path.as_ref()?;
+        let progress = self
+            .stream_progress
+            .as_ref()
+            .filter(|progress| &progress.requested_path == pending);
+        Some(crate::ui::BufferingView {
+            received_bytes: progress.map_or(0, |progress| progress.received_bytes),
+            total_bytes: progress.and_then(|progress| progress.total_bytes),
+        })
+    }
+
     fn online_room_field_view(&self) -> Option<crate::ui::OnlineRoomFieldView> {
         if let Some(password) = self.active_room_password.as_deref() {
             return Some(crate::ui::OnlineRoomFieldView {
@@ -501,260 +700,6 @@ fn online_room_field_copy_name(kind: OnlineRoomFieldKind) -> &'static str {
     }
 }
 
-#[derive(Debug, Clone)]
-struct ActiveListenSession {
-    playback_path: PathBuf,
-    track_path: PathBuf,
-    title: String,
-    artist: Option<String>,
-    album: Option<String>,
-    provider_track_id: Option<String>,
-    started_at_epoch_seconds: i64,
-    playing_started_at: Option<Instant>,
-    listened: Duration,
-    persisted_listened_seconds: u32,
-    play_count_recorded: bool,
-    pending_same_track_restart: bool,
-    last_position: Option<Duration>,
-    duration: Option<Duration>,
-}
-
-#[derive(Debug, Default)]
-struct ListenTracker {
-    active: Option<ActiveListenSession>,
-}
-
-#[derive(Debug, Clone)]
-struct StatsIdentityHint {
-    logical_path: PathBuf,
-    title: Option<String>,
-    artist: Option<String>,
-    album: Option<String>,
-    provider_track_id: Option<String>,
-}
-
-impl ListenTracker {
-    fn reset(&mut self) {
-        self.active = None;
-    }
-
-    fn tick(
-        &mut self,
-        core: &TuneCore,
-        audio: &dyn AudioEngine,
-        stats: &mut StatsStore,
-        identity_hint: Option<&StatsIdentityHint>,
-    ) -> bool {
-        let mut wrote_event = false;
-        let current_track = audio.current_track().map(Path::to_path_buf);
-        let current_position = audio.position();
-        let crossfade_seconds = audio.crossfade_seconds();
-        let paused = audio.is_paused();
-
-        let finished = audio.is_finished();
-        let mut force_completed = finished;
-        let should_finalize = self.active.as_ref().is_some_and(|active| {
-            let track_changed = current_track.as_ref() != Some(&active.playback_path);
-            let restarted_same_track = !track_changed
-                && !finished
-                && same_track_restarted(active, current_position, paused, crossfade_seconds);
-            if restarted_same_track {
-                force_completed = true;
-            }
-            track_changed || finished || restarted_same_track
-        });
-        if should_finalize {
-            wrote_event = self.finalize_active(stats, force_completed) || wrote_event;
-        }
-
-        if current_track.is_none() || finished {
-            return wrote_event;
-        }
-
-        if self.active.is_none() {
-            let path = current_track.expect("checked some");
-            let (logical_path, provider_track_id, hint_title, hint_artist, hint_album) =
-                if let Some(hint) = identity_hint {
-                    (
-                        hint.logical_path.clone(),
-                        hint.provider_track_id.clone(),
-                        hint.title.clone(),
-                        hint.artist.clone(),
-                        hint.album.clone(),
-                    )
-                } else {
-                    (path.clone(), None, None, None, None)
-                };
-            let now = Instant::now();
-            self.active = Some(ActiveListenSession {
-                title: hint_title
-                    .or_else(|| core.title_for_path(&logical_path))
-                    .unwrap_or_else(|| {
-                        logical_path
-                            .file_stem()
-                            .and_then(|name| name.to_str())
-                            .unwrap_or("-")
-                            .to_string()
-                    }),
-                artist: hint_artist
-                    .or_else(|| core.artist_for_path(&logical_path).map(ToOwned::to_owned)),
-                album: hint_album
-                    .or_else(|| core.album_for_path(&logical_path).map(ToOwned::to_owned)),
-                playback_path: path,
-                track_path: logical_path,
-                provider_track_id,
-                started_at_epoch_seconds: stats::now_epoch_seconds(),
-                playing_started_at: (!paused).then_some(now),
-                listened: Duration::ZERO,
-                persisted_listened_seconds: 0,
-                play_count_recorded: false,
-                pending_same_track_restart: false,
-                last_position: current_position,
-                duration: audio.duration(),
-            });
-            return wrote_event;
-        }
-
-        if let Some(active) = self.active.as_mut() {
-            let queued_same_track = audio
-                .crossfade_queued_track()
-                .is_some_and(|queued| queued == active.playback_path.as_path());
-            active.pending_same_track_restart |= queued_same_track;
-            active.last_position = current_position.or(active.last_position);
-            active.duration = audio.duration().or(active.duration);
-            if paused {
-                if let Some(started) = active.playing_started_at.take() {
-                    active.listened = active.listened.saturating_add(started.elapsed());
-                }
-            } else if active.playing_started_at.is_none() {
-                active.playing_started_at = Some(Instant::now());
-            }
-        }
-
-        wrote_event = self.flush_partial(stats) || wrote_event;
-
-        wrote_event
-    }
-
-    fn finalize_active(&mut self, stats: &mut StatsStore, force_completed: bool) -> bool {
-        let mut active = match self.active.take() {
-            Some(active) => active,
-            None => return false,
-        };
-
-        if let Some(started) = active.playing_started_at.take() {
-            active.listened = active.listened.saturating_add(started.elapsed());
-        }
-
-        let total_listened_seconds = duration_to_recorded_seconds(active.listened);
-        let listened_seconds =
-            total_listened_seconds.saturating_sub(active.persisted_listened_seconds);
-        let completed = force_completed
-            || active
-                .duration
-                .zip(active.last_position)
-                .is_some_and(|(duration, position)| {
-                    position >= duration
-                        || duration.saturating_sub(position) <= Duration::from_secs(1)
-                });
-        let counted_play = crate::stats::should_count_as_play(
-            total_listened_seconds,
-            completed,
-            active.duration.map(|duration| duration.as_secs() as u32),
-        ) && !active.play_count_recorded;
-        let allow_short_listen = active.persisted_listened_seconds > 0 || counted_play;
-
-        stats.record_listen(ListenSessionRecord {
-            track_path: active.track_path,
-            title: active.title,
-            artist: active.artist,
-            album: active.album,
-            provider_track_id: active.provider_track_id,
-            started_at_epoch_seconds: active.started_at_epoch_seconds,
-            listened_seconds,
-            completed,
-            duration_seconds: active.duration.map(|duration| duration.as_secs() as u32),
-            counted_play_override: Some(counted_play),
-            allow_short_listen,
-        });
-        listened_seconds >= PARTIAL_LISTEN_FLUSH_SECONDS || counted_play || allow_short_listen
-    }
-
-    fn flush_partial(&mut self, stats: &mut StatsStore) -> bool {
-        let Some(active) = self.active.as_mut() else {
-            return false;
-        };
-
-        let mut listened = active.listened;
-        if let Some(started) = active.playing_started_at {
-            listened = listened.saturating_add(started.elapsed());
-        }
-
-        let total_seconds = duration_to_recorded_seconds(listened);
-        let delta = total_seconds.saturating_sub(active.persisted_listened_seconds);
-        let should_record_play = crate::stats::should_count_as_play(
-            total_seconds,
-            false,
-            active.duration.map(|duration| duration.as_secs() as u32),
-        ) && !active.play_count_recorded;
-
-        if delta < PARTIAL_LISTEN_FLUSH_SECONDS && !should_record_play {
-            return false;
-        }
-
-        stats.record_listen(ListenSessionRecord {
-            track_path: active.track_path.clone(),
-            title: active.title.clone(),
-            artist: active.artist.clone(),
-            album: active.album.clone(),
-            provider_track_id: active.provider_track_id.clone(),
-            started_at_epoch_seconds: active.started_at_epoch_seconds,
-            listened_seconds: delta,
-            completed: false,
-            duration_seconds: active.duration.map(|duration| duration.as_secs() as u32),
-            counted_play_override: Some(should_record_play),
-            allow_short_listen: false,
-        });
-        active.persisted_listened_seconds = active.persisted_listened_seconds.saturating_add(delta);
-        if should_record_play {
-            active.play_count_recorded = true;
-        }
-        true
-    }
-}
-
-fn same_track_restarted(
-    active: &ActiveListenSession,
-    current_position: Option<Duration>,
-    paused: bool,
-    crossfade_seconds: u16,
-) -> bool {
-    if paused || !active.pending_same_track_restart {
-        return false;
-    }
-
-    let Some(current) = current_position else {
-        return false;
-    };
-    let Some(previous) = active.last_position else {
-        return false;
-    };
-    if current >= previous {
-        return false;
-    }
-
-    let start_window = Duration::from_secs(
-        LOOP_RESTART_START_WINDOW_SECONDS.max(u64::from(crossfade_seconds).saturating_add(2)),
-    );
-    let was_near_end = active.duration.is_some_and(|duration| {
-        let end_window = Duration::from_secs(LOOP_RESTART_END_WINDOW_SECONDS);
-        previous >= duration.saturating_sub(end_window)
-    }) || previous
-        >= Duration::from_secs(LOOP_RESTART_FALLBACK_MIN_PREVIOUS_SECONDS);
-    let now_near_start = current <= start_window;
-    was_near_end && now_near_start
-}
-
 fn inferred_tunetui_config_dir(
     userprofile: Option<&str>,
     home: Option<&str>,
@@ -813,54 +758,68 @@ fn prepare_runtime_environment() {
     }
 }
 
-fn duration_to_recorded_seconds(duration: Duration) -> u32 {
-    if duration.is_zero() {
-        return 0;
-    }
-    let secs = duration.as_secs();
-    let has_subsec = duration.subsec_nanos() > 0;
-    let rounded = if has_subsec {
-        secs.saturating_add(1)
-    } else {
-        secs
-    };
-    rounded.min(u64::from(u32::MAX)) as u32
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum RootActionId {
     RemoveSelectedFromQueue,
     MoveSelectedQueueItemToNext,
+    CopyQueueAsText,
+    ImportQueueFromClipboard,
+    PlayPath,
     PlaybackSettings,
     RemoveSelectedFromPlaylist,
     RemovePlaylist,
+    CreateSmartPlaylist,
+    ImportPlaylistFile,
+    ExportM3u,
     RemoveDirectory,
     RescanLibrary,
     AudioDriverSettings,
+    SleepTimer,
     Theme,
     ClearListenHistory,
+    MergeDuplicateStats,
+    ExportStats,
     MetadataEditor,
     AudioQualityInspector,
+    FindDuplicates,
+    CycleAllSongsOrder,
     MinimizeToTray,
     ImportTxtToLyrics,
+    ShuffleSharedQueue,
+    StopLocalHomeServer,
+    ReconnectLastRoom,
     ClosePanel,
 }
 
-const ROOT_ACTIONS: [RootActionId; 15] = [
+const ROOT_ACTIONS: [RootActionId; 29] = [
     RootActionId::RemoveSelectedFromQueue,
     RootActionId::MoveSelectedQueueItemToNext,
+    RootActionId::CopyQueueAsText,
+    RootActionId::ImportQueueFromClipboard,
+    RootActionId::PlayPath,
     RootActionId::PlaybackSettings,
     RootActionId::RemoveSelectedFromPlaylist,
     RootActionId::RemovePlaylist,
+    RootActionId::CreateSmartPlaylist,
+    RootActionId::ImportPlaylistFile,
+    RootActionId::ExportM3u,
     RootActionId::RemoveDirectory,
     RootActionId::RescanLibrary,
     RootActionId::AudioDriverSettings,
+    RootActionId::SleepTimer,
     RootActionId::Theme,
     RootActionId::ClearListenHistory,
+    RootActionId::MergeDuplicateStats,
+    RootActionId::ExportStats,
     RootActionId::MetadataEditor,
     RootActionId::AudioQualityInspector,
+    RootActionId::FindDuplicates,
+    RootActionId::CycleAllSongsOrder,
     RootActionId::MinimizeToTray,
     RootActionId::ImportTxtToLyrics,
+    RootActionId::ShuffleSharedQueue,
+    RootActionId::StopLocalHomeServer,
+    RootActionId::ReconnectLastRoom,
     RootActionId::ClosePanel,
 ];
 
@@ -892,19 +851,56 @@ struct MetadataEditorState {
     title_input: String,
     artist_input: String,
     album_input: String,
+    genre_input: String,
+    year_input: String,
+    crossfade_disabled: bool,
     confirm_all_songs_cover_copy: bool,
+    confirm_tag_write: Option<usize>,
+    backup_before_tag_writes: bool,
+    /// Size of the backup folder when the editor opened; only the track editor
+    /// shows it.
+    tag_backup_bytes: u64,
 }
 
 impl MetadataEditorState {
     fn options(&self) -> Vec<String> {
-        if self.selected_track_path.is_some() {
+        if let Some(path) = self.selected_track_path.as_ref() {
             vec![
                 format!("Title: {}", self.title_input),
                 format!("Artist: {}", self.artist_input),
                 format!("Album: {}", self.album_input),
-                String::from("Save embedded tags"),
-                String::from("Clear title/artist/album tags"),
+                format!("Genre: {}", self.genre_input),
+                format!("Year: {}", self.year_input),
+                if self.confirm_tag_write == Some(5) {
+                    format!("Confirm: save tags to {}", path.display())
+                } else {
+                    String::from("Save embedded tags")
+                },
+                if self.confirm_tag_write == Some(6) {
+                    format!("Confirm: clear tags in {}", path.display())
+                } else {
+                    String::from("Clear title/artist/album tags")
+                },
                 format!("Copy now playing cover art to {}", self.copy_target_label),
+                String::from("Download cover art and embed"),
+                format!(
+                    "Crossfade: {}",
+                    if self.crossfade_disabled {
+                        "never for this track"
+                    } else {
+                        "normal"
+                    }
+                ),
+                String::from("Restore original file from backup"),
+                format!(
+                    "Back up originals before writing: {} ({} used)",
+                    if self.backup_before_tag_writes {
+                        "On"
+                    } else {
+                        "Off"
+                    },
+                    disk_usage_label(self.tag_backup_bytes)
+                ),
                 String::from("Back"),
             ]
         } else {
@@ -917,20 +913,156 @@ impl MetadataEditorState {
                 } else {
                     format!("Copy now playing cover art to {}", self.copy_target_label)
                 },
+                format!("Artist for all: {}", self.artist_input),
+                format!("Album for all: {}", self.album_input),
+                if self.confirm_tag_write == Some(3) {
+                    format!(
+                        "Confirm: write artist/album to {} tracks in {}",
+                        self.copy_target_paths.len(),
+                        self.copy_target_label
+                    )
+                } else {
+                    String::from("Apply artist/album to all")
+                },
                 String::from("Back"),
             ]
         }
     }
 
+    /// Artist/album edit for a batch target, leaving titles and blank fields untouched.
+    fn batch_metadata_edit(&self) -> Option<MetadataEdit> {
+        let field = |value: &str| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+        let edit = MetadataEdit {
+            artist: field(&self.artist_input),
+            album: field(&self.album_input),
+            ..MetadataEdit::default()
+        };
+        (edit.artist.is_some() || edit.album.is_some()).then_some(edit)
+    }
+
+    /// Fields left blank keep whatever the file already has.
     fn metadata_edit(&self) -> MetadataEdit {
+        let field = |value: &str| Some(value.trim().to_string()).filter(|value| !value.is_empty());
         MetadataEdit {
-            title: Some(self.title_input.clone()),
-            artist: Some(self.artist_input.clone()),
-            album: Some(self.album_input.clone()),
+            title: field(&self.title_input),
+            artist: field(&self.artist_input),
+            album: field(&self.album_input),
+            genre: field(&self.genre_input),
+            year: field(&self.year_input),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SmartPlaylistDraft {
+    name: String,
+    sort: SmartPlaylistSort,
+    limit_input: String,
+    artist: String,
+    genre: String,
+}
+
+impl Default for SmartPlaylistDraft {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            sort: SmartPlaylistSort::default(),
+            limit_input: String::from("25"),
+            artist: String::new(),
+            genre: String::new(),
         }
     }
 }
 
+impl SmartPlaylistDraft {
+    const CREATE_ROW: usize = 5;
+
+    fn options(&self) -> Vec<String> {
+        vec![
+            format!("Name: {}", self.name),
+            format!("Sort: {}", self.sort.label()),
+            format!("Limit: {}", self.limit_input),
+            format!("Artist contains: {}", self.artist),
+            format!("Genre contains: {}", self.genre),
+            String::from("Create smart playlist"),
+        ]
+    }
+
+    fn text_field(&mut self, row: usize) -> Option<&mut String> {
+        match row {
+            0 => Some(&mut self.name),
+            3 => Some(&mut self.artist),
+            4 => Some(&mut self.genre),
+            _ => None,
+        }
+    }
+
+    fn rule(&self) -> Option<SmartPlaylist> {
+        let limit = self
+            .limit_input
+            .parse::<usize>()
+            .ok()
+            .filter(|limit| *limit > 0)?;
+        let optional =
+            |value: &str| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+        Some(SmartPlaylist {
+            sort: self.sort,
+            limit,
+            artist: optional(&self.artist),
+            genre: optional(&self.genre),
+        })
+    }
+}
+
+/// One duplicate group open for cleanup, with the copy chosen to keep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DuplicateClusterState {
+    clusters: Vec<Vec<PathBuf>>,
+    cluster: usize,
+    keep: usize,
+    confirm_delete: bool,
+}
+
+impl DuplicateClusterState {
+    fn copies(&self) -> &[PathBuf] {
+        self.clusters
+            .get(self.cluster)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn others(&self) -> Vec<PathBuf> {
+        self.copies()
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != self.keep)
+            .map(|(_, path)| path.clone())
+            .collect()
+    }
+
+    fn options(&self) -> Vec<String> {
+        let copies = self.copies();
+        let mut options: Vec<String> = copies
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| {
+                let marker = if idx == self.keep { "[KEEP]" } else { "      " };
+                format!("{marker} {}", path.display())
+            })
+            .collect();
+        options.push(String::from("Remove other copies from library"));
+        options.push(if self.confirm_delete {
+            format!(
+                "Confirm: delete {} file(s) from disk",
+                copies.len().saturating_sub(1)
+            )
+        } else {
+            String::from("Delete other copies from disk")
+        });
+        options
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct AudioQualityPanelState {
     target_path: PathBuf,
@@ -961,18 +1093,32 @@ fn root_action_label(action: RootActionId) -> &'static str {
     match action {
         RootActionId::RemoveSelectedFromQueue => "Remove selected queue item",
         RootActionId::MoveSelectedQueueItemToNext => "Move selected queue item to next",
+        RootActionId::CopyQueueAsText => "Copy queue as text",
+        RootActionId::ImportQueueFromClipboard => "Import queue from clipboard",
+        RootActionId::PlayPath => "Play a file or folder path",
         RootActionId::PlaybackSettings => "Playback settings",
+        RootActionId::SleepTimer => "Sleep timer",
         RootActionId::RemoveSelectedFromPlaylist => "Remove selected from playlist",
         RootActionId::RemovePlaylist => "Remove playlist",
-        RootActionId::RemoveDirectory => "Remove directory",
+        RootActionId::CreateSmartPlaylist => "Create smart playlist",
+        RootActionId::ImportPlaylistFile => "Import M3U/PLS playlist",
+        RootActionId::ExportM3u => "Export queue or playlist to M3U",
+        RootActionId::RemoveDirectory => "Manage directories",
         RootActionId::RescanLibrary => "Rescan library",
         RootActionId::AudioDriverSettings => "Audio driver settings",
         RootActionId::Theme => "Theme",
         RootActionId::ClearListenHistory => "Clear listen history (backup)",
+        RootActionId::MergeDuplicateStats => "Merge duplicate stats (preview)",
+        RootActionId::ExportStats => "Export stats (CSV/JSON)",
         RootActionId::MetadataEditor => "Edit selected track metadata",
         RootActionId::AudioQualityInspector => "View audio quality + spectrograph",
+        RootActionId::FindDuplicates => "Find duplicate tracks",
+        RootActionId::CycleAllSongsOrder => "Arrange All Songs by title/year/genre",
         RootActionId::MinimizeToTray => "Minimize to tray",
         RootActionId::ImportTxtToLyrics => "Import TXT to lyrics",
+        RootActionId::ShuffleSharedQueue => "Shuffle shared queue (host)",
+        RootActionId::StopLocalHomeServer => "Stop local home server",
+        RootActionId::ReconnectLastRoom => "Reconnect to last room",
         RootActionId::ClosePanel => "Close panel",
     }
 }
@@ -995,19 +1141,34 @@ fn root_action_matches_query(action: RootActionId, query_lower: &str) -> bool {
 
 fn root_action_category(action: RootActionId) -> &'static str {
     match action {
-        RootActionId::PlaybackSettings | RootActionId::AudioDriverSettings => "Settings",
-        RootActionId::RemoveSelectedFromPlaylist | RootActionId::RemovePlaylist => "Playlist",
-        RootActionId::RemoveSelectedFromQueue | RootActionId::MoveSelectedQueueItemToNext => {
-            "Queue"
-        }
+        RootActionId::PlaybackSettings
+        | RootActionId::SleepTimer
+        | RootActionId::AudioDriverSettings => "Settings",
+        RootActionId::RemoveSelectedFromPlaylist
+        | RootActionId::RemovePlaylist
+        | RootActionId::CreateSmartPlaylist
+        | RootActionId::ImportPlaylistFile
+        | RootActionId::ExportM3u => "Playlist",
+        RootActionId::RemoveSelectedFromQueue
+        | RootActionId::MoveSelectedQueueItemToNext
+        | RootActionId::CopyQueueAsText
+        | RootActionId::ImportQueueFromClipboard
+        | RootActionId::PlayPath => "Queue",
         RootActionId::RemoveDirectory
         | RootActionId::RescanLibrary
         | RootActionId::MetadataEditor
-        | RootActionId::AudioQualityInspector => "Library",
+        | RootActionId::AudioQualityInspector
+        | RootActionId::FindDuplicates
+        | RootActionId::CycleAllSongsOrder => "Library",
         RootActionId::Theme => "Appearance",
-        RootActionId::ClearListenHistory => "Stats",
+        RootActionId::ClearListenHistory
+        | RootActionId::MergeDuplicateStats
+        | RootActionId::ExportStats => "Stats",
         RootActionId::MinimizeToTray => "Window",
         RootActionId::ImportTxtToLyrics => "Lyrics",
+        RootActionId::ShuffleSharedQueue
+        | RootActionId::StopLocalHomeServer
+        | RootActionId::ReconnectLastRoom => "Online",
         RootActionId::ClosePanel => "Actions",
     }
 }
@@ -1057,6 +1218,7 @@ fn root_visible_actions(
         "Stats",
         "Window",
         "Lyrics",
+        "Online",
         "Actions",
     ] {
         let mut header_added = false;
@@ -1180,12 +1342,18 @@ enum ActionPanelState {
     AudioOutput {
         selected: usize,
     },
+    OutputShaping {
+        selected: usize,
+    },
     PlaybackSettings {
         selected: usize,
     },
     OnlineDelaySettings {
         selected: usize,
     },
+    Equalizer {
+        selected: usize,
+    },
     ThemeSettings {
         selected: usize,
     },
@@ -1206,6 +1374,14 @@ enum ActionPanelState {
         selected: usize,
         state: AudioQualityPanelState,
     },
+    DuplicateClusters {
+        selected: usize,
+        clusters: Vec<Vec<PathBuf>>,
+    },
+    DuplicateCluster {
+        selected: usize,
+        state: DuplicateClusterState,
+    },
     AddDirectory {
         selected: usize,
         input: String,
@@ -1213,6 +1389,51 @@ enum ActionPanelState {
     RemoveDirectory {
         selected: usize,
     },
+    StatsMerge {
+        selected: usize,
+        merges: Vec<crate::stats::StatsMerge>,
+    },
+    StatsExport {
+        selected: usize,
+        path_input: String,
+    },
+    M3uExport {
+        selected: usize,
+        path_input: String,
+    },
+    PlaylistImport {
+        selected: usize,
+        path_input: String,
+    },
+    SmartPlaylistCreate {
+        selected: usize,
+        draft: SmartPlaylistDraft,
+    },
+    FolderEnter {
+        selected: usize,
+        folder: PathBuf,
+    },
+    QueueJump {
+        selected: usize,
+        input: String,
+    },
+    SleepTimer {
+        selected: usize,
+    },
+    SeekToTimestamp {
+        selected: usize,
+        input: String,
+    },
+    PlayPath {
+        selected: usize,
+        input: String,
+    },
+    FuzzyFind {
+        selected: usize,
+        query: String,
+        /// Ranked track indices, recomputed as the query changes.
+        results: Vec<usize>,
+    },
 }
 
 impl ActionPanelState {
@@ -1231,6 +1452,29 @@ impl ActionPanelState {
         !matches!(self, Self::Closed)
     }
 
+    /// Panels with a search or input field, where hjkl are typed as text
+    /// rather than used to move.
+    fn takes_text_input(&self) -> bool {
+        matches!(
+            self,
+            Self::Root { .. }
+                | Self::PlaylistCreate { .. }
+                | Self::PlaylistCreateForAdd { .. }
+                | Self::OnlineNickname { .. }
+                | Self::LyricsImportTxt { .. }
+                | Self::StatsExport { .. }
+                | Self::M3uExport { .. }
+                | Self::PlaylistImport { .. }
+                | Self::SmartPlaylistCreate { .. }
+                | Self::MetadataEditor { .. }
+                | Self::AddDirectory { .. }
+                | Self::QueueJump { .. }
+                | Self::SeekToTimestamp { .. }
+                | Self::PlayPath { .. }
+                | Self::FuzzyFind { .. }
+        )
+    }
+
     fn to_view(
         &self,
         core: &TuneCore,
@@ -1246,6 +1490,7 @@ impl ActionPanelState {
                     title: String::from("Actions"),
                     hint: String::from("Type search  Enter select  Esc close  Up/Down navigate"),
                     search_query: Some(query.clone()),
+                    drift_meter: None,
                     options: if visible_actions.is_empty() {
                         vec![String::from("(no matching actions)")]
                     } else {
@@ -1261,6 +1506,7 @@ impl ActionPanelState {
                 title: String::from("Add To Playlist"),
                 hint: String::from("Enter add/create  Backspace back"),
                 search_query: None,
+                drift_meter: None,
                 options: playlist_picker_options(core),
                 selected: *selected,
             }),
@@ -1268,6 +1514,7 @@ impl ActionPanelState {
                 title: String::from("Add Now Playing To Playlist"),
                 hint: String::from("Enter add/create  Backspace back"),
                 search_query: None,
+                drift_meter: None,
                 options: playlist_picker_options(core),
                 selected: *selected,
             }),
@@ -1275,6 +1522,7 @@ impl ActionPanelState {
                 title: String::from("Create Playlist"),
                 hint: String::from("Type name + Enter  Backspace back"),
                 search_query: None,
+                drift_meter: None,
                 options: vec![if input.is_empty() {
                     String::from("Name: ")
                 } else {
@@ -1283,11 +1531,15 @@ impl ActionPanelState {
                 selected: *selected,
             }),
             Self::PlaylistRemove { selected } => {
-                let playlists = sorted_playlist_names(core);
+                let playlists: Vec<String> = all_playlist_names(core)
+                    .into_iter()
+                    .map(|name| playlist_option_label(core, name))
+                    .collect();
                 Some(crate::ui::ActionPanelView {
                     title: String::from("Remove Playlist"),
                     hint: String::from("Enter remove  Backspace back"),
                     search_query: None,
+                    drift_meter: None,
                     options: if playlists.is_empty() {
                         vec![String::from("(no playlists)")]
                     } else {
@@ -1300,19 +1552,30 @@ impl ActionPanelState {
                 title: String::from("Audio Driver Settings"),
                 hint: String::from("Enter select  Backspace back"),
                 search_query: None,
+                drift_meter: None,
                 options: vec![
                     String::from("Reload audio driver"),
                     String::from("Select output speaker"),
+                    String::from("Output shaping"),
                     String::from("Back"),
                 ],
                 selected: *selected,
             }),
+            Self::OutputShaping { selected } => Some(crate::ui::ActionPanelView {
+                title: String::from("Output Shaping"),
+                hint: String::from("Enter apply  Backspace back"),
+                search_query: None,
+                drift_meter: None,
+                options: output_shaping_options(core),
+                selected: *selected,
+            }),
             Self::PlaylistCreateForAdd {
                 selected, input, ..
             } => Some(crate::ui::ActionPanelView {
                 title: String::from("Create Playlist"),
                 hint: String::from("Type name + Enter create/add  Backspace back"),
                 search_query: None,
+                drift_meter: None,
                 options: vec![if input.is_empty() {
                     String::from("Name: ")
                 } else {
@@ -1326,6 +1589,7 @@ impl ActionPanelState {
                     title: String::from("Output Speaker"),
                     hint: String::from("Enter apply  Backspace back"),
                     search_query: None,
+                    drift_meter: None,
                     options,
                     selected: *selected,
                 })
@@ -1334,20 +1598,43 @@ impl ActionPanelState {
                 title: String::from("Playback Settings"),
                 hint: String::from("Enter toggle/select  Backspace back"),
                 search_query: None,
+                drift_meter: None,
                 options: playback_settings_options(core),
                 selected: *selected,
             }),
             Self::OnlineDelaySettings { selected } => Some(crate::ui::ActionPanelView {
-                title: String::from("Online Delay Settings"),
+                title: String::from("Online Settings"),
                 hint: String::from("Enter apply  Backspace back"),
                 search_query: None,
+                drift_meter: core
+                    .online
+                    .session
+                    .as_ref()
+                    .filter(|session| {
+                        session
+                            .local_participant()
+                            .is_some_and(|local| !local.is_host)
+                    })
+                    .map(|session| crate::ui::SyncDriftMeter {
+                        drift_ms: session.last_sync_drift_ms,
+                        threshold_ms: core.online_sync_correction_threshold_ms,
+                    }),
                 options: online_delay_settings_options(core),
                 selected: *selected,
             }),
+            Self::Equalizer { selected } => Some(crate::ui::ActionPanelView {
+                title: String::from("Equalizer"),
+                hint: String::from("Enter raise band +3 dB  Backspace back"),
+                search_query: None,
+                drift_meter: None,
+                options: equalizer_options(core),
+                selected: *selected,
+            }),
             Self::ThemeSettings { selected } => Some(crate::ui::ActionPanelView {
                 title: String::from("Theme"),
                 hint: String::from("Enter apply  Backspace back"),
                 search_query: None,
+                drift_meter: None,
                 options: theme_options(core.theme),
                 selected: *selected,
             }),
@@ -1355,6 +1642,7 @@ impl ActionPanelState {
                 title: String::from("Online Nickname"),
                 hint: String::from("Type nickname + Enter save  Backspace back"),
                 search_query: None,
+                drift_meter: None,
                 options: vec![if input.is_empty() {
                     String::from("Nickname: ")
                 } else {
@@ -1370,6 +1658,7 @@ impl ActionPanelState {
                 title: String::from("Import TXT To Lyrics"),
                 hint: String::from("Type path/seconds then Enter on Import"),
                 search_query: None,
+                drift_meter: None,
                 options: vec![
                     if path_input.is_empty() {
                         String::from("TXT path: ")
@@ -1389,6 +1678,7 @@ impl ActionPanelState {
                 title: String::from("Edit Metadata"),
                 hint: String::from("Type fields  Enter save/select  Backspace back"),
                 search_query: None,
+                drift_meter: None,
                 options: state.options(),
                 selected: *selected,
             }),
@@ -1396,6 +1686,7 @@ impl ActionPanelState {
                 title: format!("Audio Quality / {}", state.target_title),
                 hint: String::from("Static analysis for selected song  Enter/Backspace return"),
                 search_query: None,
+                drift_meter: None,
                 options: state.options(),
                 selected: *selected,
             }),
@@ -1403,6 +1694,7 @@ impl ActionPanelState {
                 title: String::from("Add Directory"),
                 hint: String::from("Type path or Down choose folder"),
                 search_query: None,
+                drift_meter: None,
                 options: vec![
                     if input.is_empty() {
                         String::from("Path: ")
@@ -1414,30 +1706,268 @@ impl ActionPanelState {
                 selected: *selected,
             }),
             Self::RemoveDirectory { selected } => {
-                let paths = sorted_folder_paths(core);
+                let folders = sorted_folders(core);
                 Some(crate::ui::ActionPanelView {
-                    title: String::from("Remove Directory"),
-                    hint: String::from("Enter remove  Backspace back"),
+                    title: String::from("Manage Directories"),
+                    hint: String::from("Enter remove  Space enable/disable  Backspace back"),
                     search_query: None,
-                    options: if paths.is_empty() {
+                    drift_meter: None,
+                    options: if folders.is_empty() {
                         vec![String::from("(no folders)")]
                     } else {
-                        paths
+                        folders
                             .iter()
-                            .map(|path| {
-                                crate::config::sanitize_display_text(&path.display().to_string())
+                            .map(|folder| {
+                                format!(
+                                    "[{}] {}",
+                                    if folder.enabled { "on" } else { "off" },
+                                    crate::config::sanitize_display_text(
+                                        &folder.path.display().to_string()
+                                    )
+                                )
                             })
                             .collect()
                     },
                     selected: *selected,
                 })
             }
-        }
-    }
-}
-
-pub fn run() -> Result<()> {
-    run_with_startup(AppStartupOptions::default())
+            Self::StatsExport {
+                selected,
+                path_input,
+            } => Some(crate::ui::ActionPanelView {
+                title: String::from("Export Stats"),
+                hint: String::from("Type path then Enter on a format  Backspace back"),
+                search_query: None,
+                drift_meter: None,
+                options: vec![
+                    format!("Export path: {path_input}"),
+                    String::from("Export as CSV"),
+                    String::from("Export as JSON"),
+                ],
+                selected: *selected,
+            }),
+            Self::M3uExport {
+                selected,
+                path_input,
+            } => Some(crate::ui::ActionPanelView {
+                title: String::from("Export M3U"),
+                hint: String::from("Type a file or folder path then Enter on a source"),
+                search_query: None,
+                drift_meter: None,
+                options: m3u_export_options(core, path_input),
+                selected: *selected,
+            }),
+            Self::SmartPlaylistCreate { selected, draft } => Some(crate::ui::ActionPanelView {
+                title: String::from("Create Smart Playlist"),
+                hint: String::from("Type fields  Enter on Sort cycles  Backspace back"),
+                search_query: None,
+                drift_meter: None,
+                options: draft.options(),
+                selected: *selected,
+            }),
+            Self::PlaylistImport {
+                selected,
+                path_input,
+            } => Some(crate::ui::ActionPanelView {
+                title: String::from("Import Playlist"),
+                hint: String::from("Type path then Enter on Import"),
+                search_query: None,
+                drift_meter: None,
+                options: vec![
+                    format!("M3U/PLS path: {path_input}"),
+                    String::from("Import as playlist"),
+                ],
+                selected: *selected,
+            }),
+            Self::StatsMerge { selected, merges } => Some(crate::ui::ActionPanelView {
+                title: String::from("Merge Duplicate Stats"),
+                hint: String::from("Nothing changes until Enter on Apply  Backspace back"),
+                search_query: None,
+                drift_meter: None,
+                options: stats_merge_options(merges),
+                selected: *selected,
+            }),
+            Self::FolderEnter { selected, folder } => Some(crate::ui::ActionPanelView {
+                title: folder
+                    .file_name()
+                    .map(|name| crate::config::sanitize_display_text(&name.to_string_lossy()))
+                    .unwrap_or_else(|| String::from("Folder")),
+                hint: String::from("Enter choose  Backspace cancel"),
+                search_query: None,
+                drift_meter: None,
+                options: folder_enter_options(),
+                selected: *selected,
+            }),
+            Self::FuzzyFind {
+                selected,
+                query,
+                results,
+            } => Some(crate::ui::ActionPanelView {
+                title: String::from("Find Track"),
+                hint: String::from("Type search  Enter play  Alt+Enter queue  Esc close"),
+                search_query: Some(query.clone()),
+                drift_meter: None,
+                options: fuzzy_find_options(core, query, results),
+                selected: *selected,
+            }),
+            Self::QueueJump { selected, input } => Some(crate::ui::ActionPanelView {
+                title: String::from("Go To Queue Position"),
+                hint: String::from("Type a number + Enter play  Backspace back"),
+                search_query: None,
+                drift_meter: None,
+                options: vec![format!("Position: {input}")],
+                selected: *selected,
+            }),
+            Self::SleepTimer { selected } => Some(crate::ui::ActionPanelView {
+                title: String::from("Sleep Timer"),
+                hint: String::from("Enter set  Backspace back"),
+                search_query: None,
+                drift_meter: None,
+                options: sleep_timer_options(core, Instant::now()),
+                selected: *selected,
+            }),
+            Self::SeekToTimestamp { selected, input } => Some(crate::ui::ActionPanelView {
+                title: String::from("Seek To Time"),
+                hint: String::from("Type mm:ss or h:mm:ss + Enter seek  Backspace back"),
+                search_query: None,
+                drift_meter: None,
+                options: vec![format!("Time: {input}")],
+                selected: *selected,
+            }),
+            Self::DuplicateClusters { selected, clusters } => Some(crate::ui::ActionPanelView {
+                title: String::from("Duplicate Tracks"),
+                hint: String::from("Enter choose copies  Backspace back"),
+                search_query: None,
+                drift_meter: None,
+                options: duplicate_cluster_options(core, clusters),
+                selected: *selected,
+            }),
+            Self::DuplicateCluster { selected, state } => Some(crate::ui::ActionPanelView {
+                title: format!(
+                    "Duplicates / {}",
+                    duplicate_cluster_label(core, state.copies())
+                ),
+                hint: String::from("Enter keep copy or clean up  Backspace back"),
+                search_query: None,
+                drift_meter: None,
+                options: state.options(),
+                selected: *selected,
+            }),
+            Self::PlayPath { selected, input } => Some(crate::ui::ActionPanelView {
+                title: String::from("Play Path"),
+                hint: String::from(
+                    "Type or paste a file or folder path + Enter play  Backspace back",
+                ),
+                search_query: None,
+                drift_meter: None,
+                options: vec![format!("Path: {input}")],
+                selected: *selected,
+            }),
+        }
+    }
+}
+
+/// The timer lengths, then "end of track", "Turn off" (with what is set now),
+/// and "Back".
+fn sleep_timer_options(core: &TuneCore, now: Instant) -> Vec<String> {
+    let mut options: Vec<String> = SLEEP_TIMER_OPTIONS_MINUTES
+        .iter()
+        .map(|minutes| format!("{minutes} min"))
+        .collect();
+    options.push(String::from("End of current track"));
+    options.push(match core.sleep_deadline {
+        Some(deadline) => format!(
+            "Turn off (pausing in {} min)",
+            deadline
+                .saturating_duration_since(now)
+                .as_secs()
+                .div_ceil(60)
+        ),
+        None if core.sleep_after_current_track => {
+            String::from("Turn off (stopping after this track)")
+        }
+        None => String::from("Turn off"),
+    });
+    options.push(String::from("Back"));
+    options
+}
+
+fn fuzzy_find_options(core: &TuneCore, query: &str, results: &[usize]) -> Vec<String> {
+    if query.trim().is_empty() {
+        return vec![String::from(
+            "(type to search title, artist, album or file name)",
+        )];
+    }
+    if results.is_empty() {
+        return vec![String::from("(no matching tracks)")];
+    }
+    results
+        .iter()
+        .filter_map(|idx| core.tracks.get(*idx))
+        .map(|track| {
+            let label = match track.artist.as_deref() {
+                Some(artist) => format!("{} - {artist}", track.title),
+                None => track.title.clone(),
+            };
+            crate::config::sanitize_display_text(&label)
+        })
+        .collect()
+}
+
+/// Alt+Enter (or Shift+Enter where the terminal reports it) in Find Track:
+/// queues the highlighted match and keeps the panel open for the next one.
+fn queue_fuzzy_find_selection(core: &mut TuneCore, panel: &ActionPanelState) -> bool {
+    let ActionPanelState::FuzzyFind {
+        selected, results, ..
+    } = panel
+    else {
+        return false;
+    };
+    match results.get(*selected) {
+        Some(track_idx) => core.queue_library_track(*track_idx),
+        None => {
+            core.status = String::from("No matching tracks");
+            core.dirty = true;
+        }
+    }
+    true
+}
+
+fn folder_enter_options() -> Vec<String> {
+    vec![
+        String::from("Open folder"),
+        String::from("Play folder"),
+        String::from("Cancel"),
+    ]
+}
+
+fn stats_merge_options(merges: &[crate::stats::StatsMerge]) -> Vec<String> {
+    if merges.is_empty() {
+        return vec![String::from("No duplicate stats found")];
+    }
+    let mut options = vec![format!(
+        "Apply {} merge{}",
+        merges.len(),
+        if merges.len() == 1 { "" } else { "s" }
+    )];
+    options.extend(merges.iter().map(|merge| {
+        let name = match merge.artist.as_deref() {
+            Some(artist) => format!("{} - {}", merge.title, artist),
+            None => merge.title.clone(),
+        };
+        format!(
+            "{}: {} listen{} from {}",
+            crate::config::sanitize_display_text(&name),
+            merge.listens,
+            if merge.listens == 1 { "" } else { "s" },
+            crate::config::sanitize_display_text(&merge.from_key)
+        )
+    }));
+    options
+}
+
+pub fn run() -> Result<()> {
+    run_with_startup(AppStartupOptions::default())
 }
 
 fn start_full_library_scan(
@@ -1449,7 +1979,7 @@ fn start_full_library_scan(
         core,
         library_runtime,
         LibraryScanKind::FullRefresh,
-        core.folders.clone(),
+        core.enabled_folder_paths(),
         status,
     );
 }
@@ -1493,6 +2023,7 @@ fn start_library_scan(
         kind,
         roots.clone(),
         library_runtime.index.clone(),
+        core.scan_ignore_patterns.clone(),
         tx,
     );
     library_runtime.active_scan = Some(ActiveLibraryScan {
@@ -1505,15 +2036,24 @@ fn start_library_scan(
     core.dirty = true;
 }
 
-fn poll_library_scan(core: &mut TuneCore, library_runtime: &mut LibraryRuntime) {
+/// Starts a requested Play path read and drains scan events. Returns the first
+/// track of a finished Play path read for the caller to start.
+fn poll_library_scan(core: &mut TuneCore, library_runtime: &mut LibraryRuntime) -> Option<PathBuf> {
+    if let Some(path) = core.play_path_requested.take() {
+        let display = crate::config::sanitize_display_text(&path.display().to_string());
+        start_library_scan(
+            core,
+            library_runtime,
+            LibraryScanKind::PlayPath,
+            vec![path],
+            &format!("Reading {display}..."),
+        );
+    }
     loop {
-        let Some((scan_id, kind)) = library_runtime
+        let (scan_id, kind) = library_runtime
             .active_scan
             .as_ref()
-            .map(|active| (active.scan_id, active.kind))
-        else {
-            return;
-        };
+            .map(|active| (active.scan_id, active.kind))?;
 
         let event = match library_runtime
             .active_scan
@@ -1523,12 +2063,12 @@ fn poll_library_scan(core: &mut TuneCore, library_runtime: &mut LibraryRuntime)
             .try_recv()
         {
             Ok(event) => event,
-            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Empty) => return None,
             Err(mpsc::TryRecvError::Disconnected) => {
                 library_runtime.active_scan = None;
                 core.status = format!("{} failed unexpectedly", kind.label());
                 core.dirty = true;
-                return;
+                return None;
             }
         };
 
@@ -1542,7 +2082,10 @@ fn poll_library_scan(core: &mut TuneCore, library_runtime: &mut LibraryRuntime)
                 scan_id: event_scan_id,
                 kind: event_kind,
                 tracks,
-            } if event_scan_id == scan_id && event_kind == kind => {
+            } if event_scan_id == scan_id
+                && event_kind == kind
+                && kind != LibraryScanKind::PlayPath =>
+            {
                 core.upsert_library_tracks(tracks);
             }
             LibraryScanEvent::Finished {
@@ -1556,6 +2099,10 @@ fn poll_library_scan(core: &mut TuneCore, library_runtime: &mut LibraryRuntime)
                     .active_scan
                     .take()
                     .expect("active library scan should exist");
+                if kind == LibraryScanKind::PlayPath {
+                    let tracks = library::tracks_from_index(&index, &active.roots);
+                    return play_scanned_path(core, &active.roots[0], tracks);
+                }
                 apply_finished_library_scan(
                     core,
                     library_runtime,
@@ -1564,7 +2111,7 @@ fn poll_library_scan(core: &mut TuneCore, library_runtime: &mut LibraryRuntime)
                     discovered_tracks,
                     refreshed_metadata_tracks,
                 );
-                return;
+                return None;
             }
             LibraryScanEvent::Failed {
                 scan_id: event_scan_id,
@@ -1574,7 +2121,7 @@ fn poll_library_scan(core: &mut TuneCore, library_runtime: &mut LibraryRuntime)
                 library_runtime.active_scan = None;
                 core.status = format!("{} failed: {error}", kind.label());
                 core.dirty = true;
-                return;
+                return None;
             }
             _ => {}
         }
@@ -1612,6 +2159,194 @@ fn poll_selected_duration_lookup(core: &mut TuneCore, runtime: &mut DurationLook
     runtime.active = Some(DurationLookupTask { path, rx });
 }
 
+/// Probes genre and year for All Songs off the UI thread. Results arrive in
+/// batches so a large library fills in as it goes.
+fn poll_extra_tags_lookup(core: &mut TuneCore, runtime: &mut ExtraTagsLookupRuntime) {
+    if let Some(task) = runtime.active.as_ref() {
+        let mut probed = Vec::new();
+        let finished = loop {
+            match task.rx.try_recv() {
+                Ok(batch) => probed.extend(batch),
+                Err(mpsc::TryRecvError::Empty) => break false,
+                Err(mpsc::TryRecvError::Disconnected) => break true,
+            }
+        };
+        if !probed.is_empty() {
+            core.cache_extra_tags(probed);
+            core.dirty = true;
+        }
+        if !finished {
+            return;
+        }
+        runtime.active = None;
+    }
+
+    let paths = core.take_extra_tags_probe_request();
+    if paths.is_empty() {
+        return;
+    }
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for chunk in paths.chunks(EXTRA_TAGS_BATCH) {
+            let batch = chunk
+                .iter()
+                .map(|path| (path.clone(), library::extra_tags_for_path(path)))
+                .collect();
+            if tx.send(batch).is_err() {
+                return;
+            }
+        }
+    });
+    runtime.active = Some(ExtraTagsLookupTask { rx });
+}
+
+fn poll_cover_art_download(
+    core: &mut TuneCore,
+    runtime: &mut CoverArtDownloadRuntime,
+    library_runtime: &mut LibraryRuntime,
+) {
+    if let Some(task) = runtime.active.as_ref() {
+        let result = match task.rx.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Err(anyhow::anyhow!("download worker stopped"))
+            }
+        };
+        if let Some(task) = runtime.active.take() {
+            finish_cover_art_download(core, library_runtime, &task.request, result);
+        }
+    }
+
+    if runtime.active.is_some() {
+        return;
+    }
+    let Some(request) = core.cover_art_download_requested.take() else {
+        return;
+    };
+    let (artist, album) = (request.artist.clone(), request.album.clone());
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(library::fetch_cover_art(&artist, &album));
+    });
+    runtime.active = Some(CoverArtDownloadTask { request, rx });
+}
+
+fn finish_cover_art_download(
+    core: &mut TuneCore,
+    library_runtime: &mut LibraryRuntime,
+    request: &CoverArtDownloadRequest,
+    result: Result<Option<Vec<u8>>>,
+) {
+    let album_label = format!("{} - {}", request.artist, request.album);
+    let path = &request.track_path;
+    core.status = match result {
+        Ok(Some(image_data)) => {
+            let backup = core.tag_write_backup_path(path);
+            match library::write_embedded_cover_art(path, &image_data, backup.as_deref()) {
+                Ok(()) => {
+                    core.reload_track_metadata(path);
+                    sync_library_index_track_from_core(core, library_runtime, path);
+                    format!("Embedded downloaded cover art for {album_label}")
+                }
+                Err(err) => format!("Downloaded cover art could not be embedded: {err:#}"),
+            }
+        }
+        Ok(None) => format!("No cover art found online for {album_label}"),
+        Err(err) => format!("Cover art download failed: {err:#}"),
+    };
+    core.dirty = true;
+}
+
+fn poll_lyrics_fetch(core: &mut TuneCore, runtime: &mut LyricsFetchRuntime) {
+    if let Some(task) = runtime.active.as_ref() {
+        let result = match task.rx.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => Err(anyhow::anyhow!("lyrics worker stopped")),
+        };
+        if let Some(task) = runtime.active.take() {
+            core.finish_lyrics_fetch(&task.request, result);
+        }
+    }
+
+    if runtime.active.is_some() {
+        return;
+    }
+    let Some(request) = core.lyrics_fetch_requested.take() else {
+        return;
+    };
+    let (path, artist, title) = (
+        request.track_path.clone(),
+        request.artist.clone(),
+        request.title.clone(),
+    );
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let duration = library::duration_seconds(&path);
+        let _ = tx.send(lyrics::fetch_synced(&artist, &title, duration));
+    });
+    runtime.active = Some(LyricsFetchTask { request, rx });
+}
+
+/// Groups look-alike tracks off the UI thread, since telling copies apart probes
+/// the duration of every same-named file. Returns the clusters once they arrive.
+fn poll_duplicate_scan(
+    core: &mut TuneCore,
+    runtime: &mut DuplicateScanRuntime,
+) -> Option<Vec<Vec<PathBuf>>> {
+    if let Some(rx) = runtime.active.as_ref() {
+        let result = match rx.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => return None,
+            Err(mpsc::TryRecvError::Disconnected) => None,
+        };
+        runtime.active = None;
+        let Some(result) = result else {
+            core.status = String::from("Duplicate scan failed unexpectedly");
+            core.dirty = true;
+            return None;
+        };
+        for (path, duration) in &result.probed {
+            core.cache_duration_seconds_for_path(path, *duration);
+        }
+        return Some(result.clusters);
+    }
+
+    if !std::mem::take(&mut core.duplicate_scan_requested) {
+        return None;
+    }
+    let (tracks, durations) = core.duplicate_scan_input();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut probed = Vec::new();
+        let clusters = library::find_duplicates(&tracks, |path| match durations.get(path) {
+            Some(duration) => *duration,
+            None => {
+                let duration = library::duration_seconds(path);
+                probed.push((path.to_path_buf(), duration));
+                duration
+            }
+        });
+        let _ = tx.send(DuplicateScanResult { clusters, probed });
+    });
+    runtime.active = Some(rx);
+    None
+}
+
+fn open_duplicate_clusters(
+    core: &mut TuneCore,
+    panel: &mut ActionPanelState,
+    clusters: Vec<Vec<PathBuf>>,
+) {
+    core.status = duplicate_summary(&clusters);
+    *panel = ActionPanelState::DuplicateClusters {
+        selected: 0,
+        clusters,
+    };
+    core.dirty = true;
+}
+
 fn request_library_rescan(core: &mut TuneCore, library_runtime: &mut LibraryRuntime) {
     start_full_library_scan(core, library_runtime, "Rescanning library in background...");
 }
@@ -1675,55 +2410,197 @@ fn try_remove_folder_async(
     }
 }
 
-fn sync_library_index_track_from_core(
-    core: &TuneCore,
-    library_runtime: &mut LibraryRuntime,
-    path: &Path,
+fn toggle_folder_enabled(
+    core: &mut TuneCore,
+    audio: &dyn AudioEngine,
+    library_runtime: Option<&mut LibraryRuntime>,
+    root: &Path,
 ) {
-    let Some(title) = core.title_for_path(path) else {
+    if library_runtime
+        .as_ref()
+        .is_some_and(|runtime| runtime.active_scan.is_some())
+    {
+        core.status = String::from("Wait for the current library scan to finish");
+        core.dirty = true;
+        return;
+    }
+    let Some(enabled) = core.toggle_folder_enabled(root) else {
         return;
     };
-    let track = crate::model::Track {
-        path: path.to_path_buf(),
-        title,
-        artist: core.artist_for_path(path).map(str::to_string),
-        album: core.album_for_path(path).map(str::to_string),
-    };
-    library::upsert_index_entry(&mut library_runtime.index, &track);
-    let _ = config::save_library_index(&library_runtime.index);
+    auto_save_state(core, audio);
+    if enabled {
+        match library_runtime {
+            Some(library_runtime) => {
+                start_folder_import_scan(core, library_runtime, root.to_path_buf());
+            }
+            None => {
+                core.upsert_library_tracks(library::scan_folder(root, &core.scan_ignore_patterns));
+                core.status = String::from("Folder enabled");
+                core.dirty = true;
+            }
+        }
+    }
 }
 
-fn apply_finished_library_scan(
+fn poll_library_file_changes(
     core: &mut TuneCore,
     library_runtime: &mut LibraryRuntime,
-    active: ActiveLibraryScan,
-    index: LibraryIndex,
-    discovered_tracks: usize,
-    refreshed_metadata_tracks: usize,
+    now: Instant,
 ) {
-    match active.kind {
-        LibraryScanKind::FullRefresh => {
-            let tracks = library::tracks_from_index(&index, &active.roots);
-            core.replace_library_tracks(tracks);
-            library_runtime.index = index;
-        }
-        LibraryScanKind::AddFolder => {
-            for root in &active.roots {
-                library::remove_index_entries_in_folder(&mut library_runtime.index, root);
+    if let Some(rx) = library_runtime.change_watch.sweep.as_ref() {
+        match rx.try_recv() {
+            Ok(sweep) => {
+                library_runtime.change_watch.sweep = None;
+                apply_track_change_sweep(core, library_runtime, sweep, now);
+            }
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                library_runtime.change_watch.sweep = None;
             }
-            library_runtime.index.tracks.extend(index.tracks);
-            library_runtime
-                .index
-                .tracks
-                .sort_by(|a, b| a.path.cmp(&b.path));
-            library_runtime
-                .index
-                .tracks
-                .dedup_by(|a, b| a.path == b.path);
         }
     }
 
-    core.status = match config::save_library_index(&library_runtime.index) {
+    if library_runtime.active_scan.is_some()
+        || library_runtime
+            .change_watch
+            .last_poll
+            .is_some_and(|last| now.saturating_duration_since(last) < TRACK_CHANGE_POLL_INTERVAL)
+    {
+        return;
+    }
+    library_runtime.change_watch.last_poll = Some(now);
+
+    let total = library_runtime.index.tracks.len();
+    let start = library_runtime.change_watch.cursor.min(total);
+    let end = start.saturating_add(TRACK_CHANGE_POLL_BATCH).min(total);
+    library_runtime.change_watch.cursor = if end >= total { 0 } else { end };
+    let watch = &library_runtime.change_watch;
+    let batch: Vec<_> = library_runtime.index.tracks[start..end]
+        .iter()
+        .filter(|entry| {
+            !watch.pending.contains_key(&entry.path) && core.title_for_path(&entry.path).is_some()
+        })
+        .map(|entry| (entry.path.clone(), entry.fingerprint.clone()))
+        .collect();
+    let pending: Vec<_> = watch.pending.keys().cloned().collect();
+    if batch.is_empty() && pending.is_empty() {
+        return;
+    }
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut sweep = TrackChangeSweep::default();
+        for (path, indexed) in batch {
+            let fingerprint = library::track_fingerprint(&path);
+            if fingerprint.is_some() && fingerprint != indexed {
+                sweep.changed.push((path, fingerprint));
+            }
+        }
+        for path in pending {
+            let fingerprint = library::track_fingerprint(&path);
+            sweep.rechecked.push((path, fingerprint));
+        }
+        let _ = tx.send(sweep);
+    });
+    library_runtime.change_watch.sweep = Some(rx);
+}
+
+/// Waits out the debounce on changed files and reloads the ones that settled.
+fn apply_track_change_sweep(
+    core: &mut TuneCore,
+    library_runtime: &mut LibraryRuntime,
+    sweep: TrackChangeSweep,
+    now: Instant,
+) {
+    for (path, fingerprint) in sweep.rechecked {
+        let Some((seen, since)) = library_runtime.change_watch.pending.remove(&path) else {
+            continue;
+        };
+        if fingerprint.is_none() {
+            continue;
+        }
+        if fingerprint != seen {
+            library_runtime
+                .change_watch
+                .pending
+                .insert(path, (fingerprint, now));
+        } else if now.saturating_duration_since(since) < TRACK_CHANGE_DEBOUNCE {
+            library_runtime
+                .change_watch
+                .pending
+                .insert(path, (seen, since));
+        } else {
+            core.reload_track_metadata(&path);
+            sync_library_index_track_from_core(core, library_runtime, &path);
+        }
+    }
+    for (path, fingerprint) in sweep.changed {
+        library_runtime
+            .change_watch
+            .pending
+            .entry(path)
+            .or_insert((fingerprint, now));
+    }
+}
+
+fn sync_library_index_track_from_core(
+    core: &TuneCore,
+    library_runtime: &mut LibraryRuntime,
+    path: &Path,
+) {
+    if core.is_transient_track(path) {
+        return;
+    }
+    let Some(title) = core.title_for_path(path) else {
+        return;
+    };
+    let track = crate::model::Track {
+        path: path.to_path_buf(),
+        title,
+        artist: core.artist_for_path(path).map(str::to_string),
+        album: core.album_for_path(path).map(str::to_string),
+    };
+    library::upsert_index_entry(&mut library_runtime.index, &track);
+    let _ = config::save_library_index(&library_runtime.index);
+}
+
+fn apply_finished_library_scan(
+    core: &mut TuneCore,
+    library_runtime: &mut LibraryRuntime,
+    active: ActiveLibraryScan,
+    index: LibraryIndex,
+    discovered_tracks: usize,
+    refreshed_metadata_tracks: usize,
+) {
+    match active.kind {
+        LibraryScanKind::FullRefresh => {
+            let tracks = library::tracks_from_index(&index, &active.roots);
+            core.replace_library_tracks(tracks);
+            library_runtime.index = index;
+        }
+        LibraryScanKind::AddFolder => {
+            for root in &active.roots {
+                library::remove_index_entries_in_folder(&mut library_runtime.index, root);
+            }
+            library_runtime.index.tracks.extend(index.tracks);
+            library_runtime
+                .index
+                .tracks
+                .sort_by(|a, b| a.path.cmp(&b.path));
+            library_runtime
+                .index
+                .tracks
+                .dedup_by(|a, b| a.path == b.path);
+        }
+        // Queued by `poll_library_scan` and never merged into the index.
+        LibraryScanKind::PlayPath => return,
+    }
+    core.set_track_added_times(&library_runtime.index);
+
+    core.status = match config::save_library_index(&library_runtime.index) {
+        Ok(()) if active.kind == LibraryScanKind::FullRefresh => {
+            core.library_summary(Some(library_index_bytes(&library_runtime.index)))
+        }
         Ok(()) => format!(
             "{} complete: {discovered_tracks} track(s), {refreshed_metadata_tracks} refreshed",
             active.kind.label()
@@ -1736,6 +2613,65 @@ fn apply_finished_library_scan(
     core.dirty = true;
 }
 
+fn library_index_bytes(index: &LibraryIndex) -> u64 {
+    index
+        .tracks
+        .iter()
+        .filter_map(|entry| entry.fingerprint.as_ref())
+        .map(|fingerprint| fingerprint.file_size_bytes)
+        .sum()
+}
+
+/// Adds `folders` to the saved library and runs a full refresh without opening
+/// the TUI (`--scan`), so the next launch starts from a warm index. Returns the
+/// same summary line the status bar shows after a rescan.
+pub fn scan_library_headless(folders: &[PathBuf]) -> Result<String> {
+    let mut state = config::load_state()?;
+    let index = config::load_library_index().unwrap_or_default();
+    let indexed_tracks =
+        library::tracks_from_index(&index, &crate::model::enabled_folder_paths(&state.folders));
+    let mut core = TuneCore::from_persisted_with_tracks(state.clone(), indexed_tracks);
+    for folder in folders {
+        match core.resolve_folder_for_addition(folder) {
+            Ok(normalized) => core.insert_folder_reference(normalized),
+            Err("Folder already added") => {}
+            Err(message) => anyhow::bail!("{}: {message}", folder.display()),
+        }
+    }
+    let roots = core.enabled_folder_paths();
+    if roots.is_empty() {
+        anyhow::bail!("no enabled library folders to scan");
+    }
+    state.folders = core.persisted_state().folders;
+    config::save_state(&state)?;
+
+    let (tx, rx) = mpsc::channel();
+    library::spawn_library_scan(
+        1,
+        LibraryScanKind::FullRefresh,
+        roots.clone(),
+        index,
+        core.scan_ignore_patterns.clone(),
+        tx,
+    );
+    let index = loop {
+        match rx.recv() {
+            Ok(LibraryScanEvent::Finished { index, .. }) => break index,
+            Ok(LibraryScanEvent::Failed { error, .. }) => {
+                anyhow::bail!("{} failed: {error}", LibraryScanKind::FullRefresh.label())
+            }
+            Ok(_) => {}
+            Err(_) => anyhow::bail!(
+                "{} failed unexpectedly",
+                LibraryScanKind::FullRefresh.label()
+            ),
+        }
+    };
+    config::save_library_index(&index)?;
+    core.replace_library_tracks(library::tracks_from_index(&index, &roots));
+    Ok(core.library_summary(Some(library_index_bytes(&index))))
+}
+
 fn request_minimize_to_tray(core: &mut TuneCore) {
     core.status = match minimize_to_tray() {
         TrayActionOutcome::Done(status) => String::from(status),
@@ -1749,37 +2685,62 @@ enum TrayActionOutcome {
     Unavailable(String),
 }
 
-pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
+pub fn run_with_startup(mut startup: AppStartupOptions) -> Result<()> {
     prepare_runtime_environment();
 
     #[cfg(windows)]
-    let _single_instance = match ensure_single_instance() {
-        Ok(Some(guard)) => guard,
-        Ok(None) => return Ok(()),
-        Err(err) => return Err(err),
+    let _single_instance = if startup.allow_multiple_instances {
+        set_console_title(APP_CONSOLE_TITLE);
+        None
+    } else {
+        match ensure_single_instance() {
+            Ok(Some(guard)) => Some(guard),
+            Ok(None) => return Ok(()),
+            Err(err) => return Err(err),
+        }
     };
 
     let state = config::load_state()?;
     let library_index = config::load_library_index().unwrap_or_default();
-    let indexed_tracks = library::tracks_from_index(&library_index, &state.folders);
+    let indexed_tracks = library::tracks_from_index(
+        &library_index,
+        &crate::model::enabled_folder_paths(&state.folders),
+    );
     let preferred_output = state.selected_output_device.clone();
     let saved_volume = state.saved_volume;
+    let saved_muted = state.saved_muted;
     let mut core = TuneCore::from_persisted_with_tracks(state, indexed_tracks);
+    core.set_track_added_times(&library_index);
     let mut library_runtime = LibraryRuntime {
         active_scan: None,
         next_scan_id: 1,
         index: library_index,
+        change_watch: TrackChangeWatch::default(),
     };
     let mut stats_store = stats::load_stats().unwrap_or_default();
-    let mut listen_tracker = ListenTracker::default();
+    core.set_listen_history(&stats_store.events);
+    let mut listen_history_events = stats_store.events.len();
+    let mut stats_rows_cache = StatsRowsCache::default();
+    let mut listen_tracker = ListenTracker {
+        scrobble_queue: start_scrobbling(&core),
+        ..ListenTracker::default()
+    };
+    let mut silence_watch = SilenceWatch::default();
+    let mut sleep_timer = SleepTimer::default();
+    let mut window_title_shown: Option<String> = None;
 
+    let mut audio_init_error = None;
     let mut audio: Box<dyn AudioEngine> = match WasapiAudioEngine::new() {
         Ok(engine) => Box::new(engine),
-        Err(_) => Box::new(NullAudioEngine::new()),
+        Err(err) => {
+            audio_init_error = Some(err);
+            Box::new(NullAudioEngine::new())
+        }
     };
 
     apply_audio_preferences_from_core(&core, &mut *audio);
     apply_saved_volume(&mut *audio, saved_volume);
+    apply_saved_mute(&mut core, &mut *audio, saved_muted);
     apply_saved_audio_output(&mut core, &mut *audio, preferred_output);
 
     // Linux audio backends can emit ALSA underrun diagnostics directly to stderr,
@@ -1793,13 +2754,17 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    if !core.folders.is_empty() {
+    if core.folders.iter().any(|folder| folder.enabled) {
         start_full_library_scan(
             &mut core,
             &mut library_runtime,
             "Scanning library in background...",
         );
     }
+    if audio.is_silent() {
+        core.status = silent_audio_status(audio_init_error.as_ref());
+        core.dirty = true;
+    }
 
     let mut action_panel = ActionPanelState::Closed;
     let mut recent_root_actions: Vec<RootActionId> = Vec::new();
@@ -1808,6 +2773,10 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
     let mut hit_map = crate::ui::HitMap::default();
     let mut mouse_state = MouseState::default();
     let mut duration_lookup_runtime = DurationLookupRuntime { active: None };
+    let mut extra_tags_lookup_runtime = ExtraTagsLookupRuntime { active: None };
+    let mut cover_art_download_runtime = CoverArtDownloadRuntime { active: None };
+    let mut lyrics_fetch_runtime = LyricsFetchRuntime { active: None };
+    let mut duplicate_scan_runtime = DuplicateScanRuntime { active: None };
     let mut stats_enabled_last = core.stats_enabled;
     let mut online_runtime = OnlineRuntime {
         network: None,
@@ -1821,6 +2790,7 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
             .clone()
             .unwrap_or_else(|| String::from(ONLINE_DEFAULT_HOME_SERVER_ADDR)),
         home_server_connected: startup.home_server_connected,
+        local_home_server: startup.local_home_server.take(),
         nickname_configured: !core.online_nickname.trim().is_empty(),
         last_transport_seq: 0,
         join_prompt_active: false,
@@ -1847,10 +2817,16 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
         pending_join_invite_code: String::new(),
         room_code_revealed: false,
         host_invite_modal_active: false,
+        host_invite_qr_only: false,
+        host_invite_qr_rows: None,
         host_invite_code: String::new(),
         host_invite_button: HostInviteModalButton::Copy,
+        chat_input_active: false,
+        chat_input: String::new(),
+        skip_requested: false,
         streamed_track_cache: HashMap::new(),
         pending_stream_path: None,
+        stream_progress: None,
         remote_logical_track: None,
         remote_track_title: None,
         remote_track_artist: None,
@@ -1858,12 +2834,43 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
         remote_provider_track_id: None,
         last_remote_transport_origin: None,
         last_periodic_sync_at: Instant::now(),
+        last_drift_report_at: None,
         online_playback_source: OnlinePlaybackSource::LocalQueue,
+        sync_debug_log: None,
     };
+    if startup.debug_sync {
+        match SyncDebugLog::open() {
+            Ok((log, path)) => {
+                online_runtime.sync_debug_log = Some(log);
+                core.status = format!("Sync debug log: {}", path.display());
+            }
+            Err(err) => core.status = format!("Sync debug log unavailable: {err}"),
+        }
+        core.dirty = true;
+    }
+    if let Some(nickname) = startup.online_nickname.as_deref() {
+        apply_online_nickname(&mut core, &mut online_runtime, nickname);
+    }
+    let mut startup_room_task = startup
+        .startup_join
+        .as_ref()
+        .and_then(|join| start_startup_room(&mut core, &online_runtime, join));
 
     let mut pending_scrub_delta: i64 = 0;
+    let mut last_loop_wall_clock = SystemTime::now();
+    let mut output_checked_at = Instant::now();
 
     let result: Result<()> = 'app_loop: loop {
+        let loop_wall_clock = SystemTime::now();
+        if wall_clock_gap_suggests_resume(last_loop_wall_clock, loop_wall_clock) {
+            recover_audio_output_after_resume(&mut core, &mut *audio);
+        }
+        last_loop_wall_clock = loop_wall_clock;
+        if output_checked_at.elapsed() >= OUTPUT_HEALTH_CHECK_INTERVAL {
+            output_checked_at = Instant::now();
+            recover_lost_output_device(&mut core, &mut *audio);
+        }
+
         if pending_scrub_delta != 0 {
             let delta = pending_scrub_delta;
             pending_scrub_delta = 0;
@@ -1889,10 +2896,42 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
         if pump_tray_events(&mut core) {
             terminal.clear()?;
         }
-        poll_library_scan(&mut core, &mut library_runtime);
+        if let Some(path) = poll_library_scan(&mut core, &mut library_runtime) {
+            if let Err(err) = audio.play(&path) {
+                core.status = concise_audio_error(&err);
+            } else {
+                publish_current_playback_state(&core, &*audio, &online_runtime);
+            }
+            core.dirty = true;
+        }
+        poll_library_file_changes(&mut core, &mut library_runtime, Instant::now());
         poll_selected_duration_lookup(&mut core, &mut duration_lookup_runtime);
+        poll_extra_tags_lookup(&mut core, &mut extra_tags_lookup_runtime);
+        poll_startup_room(&mut core, &mut online_runtime, &mut startup_room_task);
+        poll_cover_art_download(
+            &mut core,
+            &mut cover_art_download_runtime,
+            &mut library_runtime,
+        );
+        poll_lyrics_fetch(&mut core, &mut lyrics_fetch_runtime);
+        if let Some(clusters) = poll_duplicate_scan(&mut core, &mut duplicate_scan_runtime) {
+            open_duplicate_clusters(&mut core, &mut action_panel, clusters);
+        }
         drain_online_network_events(&mut core, &mut *audio, &mut online_runtime);
+        sync_playback_speed(&core, &mut *audio);
         audio.tick();
+        if silence_watch.tick(&mut core, &mut *audio, Instant::now())
+            || sleep_timer.tick(&mut core, &mut *audio, Instant::now())
+        {
+            publish_current_playback_state(&core, &*audio, &online_runtime);
+        }
+        let title = window_title(&core, &*audio);
+        if window_title_shown.as_deref() != Some(title.as_str())
+            && (core.window_title_shows_track || window_title_shown.is_some())
+        {
+            apply_window_title(&title);
+            window_title_shown = Some(title);
+        }
         maybe_publish_online_playback_sync(&core, &*audio, &mut online_runtime);
         let stats_identity_hint = online_streaming_stats_identity(&online_runtime, &*audio);
         if core.stats_enabled
@@ -1911,6 +2950,64 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
         {
             let _ = stats::save_stats(&stats_store);
         }
+        if let Some(err) = listen_tracker
+            .scrobble_queue
+            .as_ref()
+            .and_then(|queue| queue.take_auth_error())
+        {
+            core.status = format!(
+                "Scrobbling paused: {err}. Fix listenbrainz_token in state.json and restart"
+            );
+            core.dirty = true;
+        }
+        if let Some(track_key) = core.forget_stats_track_requested.take() {
+            listen_tracker.forget_track(&stats_store, &track_key);
+            let removed = stats_store.forget_track(&track_key);
+            if let Err(err) = stats::save_stats(&stats_store) {
+                core.status = format!("Failed to forget track stats: {err}");
+            } else {
+                core.status = format!("Forgot {removed} listens for track (backup saved)");
+            }
+            core.stats_focus = StatsFilterFocus::Range(core_range_index(core.stats_range));
+            core.dirty = true;
+        }
+        if core.stats_merge_preview_requested {
+            core.stats_merge_preview_requested = false;
+            let merges = stats_store.coalesce_preview();
+            core.status = if merges.is_empty() {
+                String::from("No duplicate stats found")
+            } else {
+                format!("Found {} duplicate stats entries", merges.len())
+            };
+            action_panel = ActionPanelState::StatsMerge {
+                selected: 0,
+                merges,
+            };
+            core.dirty = true;
+        }
+        if let Some((path, format)) = core.stats_export_requested.take() {
+            let query = stats_query_for_core(&core);
+            let now = stats::now_epoch_seconds();
+            let exported = match format {
+                stats::StatsExportFormat::Csv => stats_store.export_csv(&path, &query, now),
+                stats::StatsExportFormat::Json => stats_store.export_json(&path, &query, now),
+            };
+            let display = crate::config::sanitize_display_text(&path.display().to_string());
+            core.status = match exported {
+                Ok(count) => format!("Exported {count} tracks to {display}"),
+                Err(err) => format!("Failed to export stats: {err:#}"),
+            };
+            core.dirty = true;
+        }
+        if let Some(merges) = core.coalesce_stats_requested.take() {
+            let merged = stats_store.coalesce(&merges);
+            if let Err(err) = stats::save_stats(&stats_store) {
+                core.status = format!("Failed to merge duplicate stats: {err}");
+            } else {
+                core.status = format!("Merged {merged} duplicate stats entries (backup saved)");
+            }
+            core.dirty = true;
+        }
         if core.clear_stats_requested {
             listen_tracker.reset();
             stats_store.clear_history();
@@ -1922,9 +3019,24 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
             core.clear_stats_requested = false;
             core.dirty = true;
         }
+        if stats_store.events.len() != listen_history_events {
+            listen_history_events = stats_store.events.len();
+            core.set_listen_history(&stats_store.events);
+        }
         stats_enabled_last = core.stats_enabled;
         maybe_start_online_shared_queue_if_idle(&mut core, &mut *audio, &mut online_runtime);
-        maybe_auto_advance_track(&mut core, &mut *audio, &mut online_runtime);
+        maybe_auto_advance_track(
+            &mut core,
+            &mut *audio,
+            &mut online_runtime,
+            &stats_store.events,
+        );
+        maybe_skip_online_track(
+            &mut core,
+            &mut *audio,
+            &mut online_runtime,
+            &stats_store.events,
+        );
         if core.header_section == HeaderSection::Online
             && online_runtime.join_directory_active
             && online_runtime.last_directory_refresh_at.elapsed() > Duration::from_secs(1)
@@ -1942,24 +3054,21 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
 
         if core.dirty || last_tick.elapsed() > Duration::from_millis(250) {
             terminal.draw(|frame| {
-                library_rect = crate::ui::library_rect(frame.area());
+                library_rect = crate::ui::library_rect(
+                    frame.area(),
+                    crate::ui::compact_layout_active(frame.area(), core.compact_layout),
+                    core.header_section,
+                );
                 let panel_view = action_panel.to_view(&core, &*audio, &recent_root_actions);
                 let join_prompt_modal = online_runtime.join_prompt_view();
                 let room_directory_modal = online_runtime.room_directory_view();
                 let host_invite_modal = online_runtime.host_invite_modal_view();
                 let password_prompt_modal = online_runtime.password_prompt_view();
                 let online_room_field = online_runtime.online_room_field_view();
+                let online_status = online_runtime.online_status_view(&core);
+                let buffering = online_runtime.buffering_view();
                 let stats_snapshot = (core.header_section == HeaderSection::Stats).then(|| {
-                    stats_store.query(
-                        &crate::stats::StatsQuery {
-                            range: core.stats_range,
-                            sort: core.stats_sort,
-                            artist_filter: core.stats_artist_filter.clone(),
-                            album_filter: core.stats_album_filter.clone(),
-                            search: core.stats_search.clone(),
-                        },
-                        stats::now_epoch_seconds(),
-                    )
+                    stats_store.query(&stats_query_for_core(&core), stats::now_epoch_seconds())
                 });
                 crate::ui::draw(
                     frame,
@@ -1973,7 +3082,12 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                         online_password_prompt: password_prompt_modal.as_ref(),
                         host_invite_modal: host_invite_modal.as_ref(),
                         online_room_field: online_room_field.as_ref(),
+                        online_status: online_status.as_ref(),
+                        buffering: buffering.as_ref(),
                         room_code_revealed: online_runtime.room_code_revealed,
+                        chat_input: online_runtime
+                            .chat_input_active
+                            .then_some(online_runtime.chat_input.as_str()),
                     },
                 )
             })?;
@@ -1993,6 +3107,9 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
         while event_drain_first || event::poll(Duration::ZERO)? {
             event_drain_first = false;
             let event = event::read()?;
+            if matches!(event, Event::Key(_) | Event::Mouse(_) | Event::Paste(_)) {
+                silence_watch.reset();
+            }
             if let Event::Paste(text) = &event
                 && core.header_section == HeaderSection::Online
                 && online_runtime.password_prompt_active
@@ -2018,6 +3135,29 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                 core.dirty = true;
                 continue;
             }
+            if let Event::Paste(text) = &event
+                && core.header_section == HeaderSection::Online
+                && online_runtime.chat_input_active
+            {
+                append_chat_input(&mut online_runtime, text.lines().next().unwrap_or_default());
+                core.dirty = true;
+                continue;
+            }
+            if let Event::Paste(text) = &event
+                && let ActionPanelState::PlayPath { input, .. } = &mut action_panel
+            {
+                *input = text.lines().next().unwrap_or_default().to_string();
+                core.dirty = true;
+                continue;
+            }
+            if let Event::Resize(width, height) = event {
+                // Redraw from scratch right away instead of waiting for the next
+                // tick, and drop hit targets laid out for the old size.
+                terminal.clear()?;
+                library_rect = handle_terminal_resize(&mut core, width, height);
+                hit_map = crate::ui::HitMap::default();
+                continue;
+            }
             if let Event::Mouse(mouse) = event {
                 handle_mouse_with_panel(
                     &mut core,
@@ -2042,7 +3182,23 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                 continue;
             }
 
+            if core.lyrics_leave_prompt.is_some() {
+                if handle_lyrics_leave_prompt_input(&mut core, key) == Some(LyricsLeaveAction::Quit)
+                {
+                    break 'app_loop Ok(());
+                }
+                continue;
+            }
+
             if action_panel.is_open() {
+                if key.code == KeyCode::Enter
+                    && key
+                        .modifiers
+                        .intersects(KeyModifiers::ALT | KeyModifiers::SHIFT)
+                    && queue_fuzzy_find_selection(&mut core, &action_panel)
+                {
+                    continue;
+                }
                 handle_action_panel_input_with_recent(
                     &mut core,
                     &mut *audio,
@@ -2063,6 +3219,10 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                 continue;
             }
 
+            if handle_online_chat_input(&mut core, key, &mut online_runtime) {
+                continue;
+            }
+
             if handle_quick_action_key(
                 &mut core,
                 &mut *audio,
@@ -2073,10 +3233,46 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                 continue;
             }
 
+            if key_event_matches_ctrl_char(&key, 'r') {
+                terminal.clear()?;
+                reinitialize_audio(&mut core, &mut *audio);
+                publish_current_playback_state(&core, &*audio, &online_runtime);
+                continue;
+            }
+
+            if key_event_matches_ctrl_char(&key, 'g') {
+                action_panel = ActionPanelState::QueueJump {
+                    selected: 0,
+                    input: String::new(),
+                };
+                core.dirty = true;
+                continue;
+            }
+
+            if key_event_matches_ctrl_char(&key, 'k') {
+                action_panel = ActionPanelState::FuzzyFind {
+                    selected: 0,
+                    query: String::new(),
+                    results: Vec::new(),
+                };
+                core.dirty = true;
+                continue;
+            }
+
+            if seek_to_timestamp_shortcut(&core, &key) {
+                open_seek_to_timestamp(&mut core, &*audio, &mut action_panel);
+                continue;
+            }
+
             if handle_online_inline_input(&mut core, &mut *audio, key, &mut online_runtime) {
                 continue;
             }
-            if handle_stats_inline_input(&mut core, key) {
+            let stats_rows = if core.header_section == HeaderSection::Stats {
+                stats_rows_cache.rows(&core, &stats_store)
+            } else {
+                &[]
+            };
+            if handle_stats_inline_input(&mut core, key, stats_rows) {
                 continue;
             }
             if handle_lyrics_inline_input(&mut core, &*audio, key) {
@@ -2089,6 +3285,9 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                         && ch.eq_ignore_ascii_case(&'c'))
                         || ch == '\u{3}' =>
                 {
+                    if core.request_lyrics_leave(LyricsLeaveAction::Quit) {
+                        continue;
+                    }
                     break 'app_loop Ok(());
                 }
                 KeyCode::Char(_)
@@ -2123,6 +3322,46 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                     core.refresh_browser_view();
                     core.dirty = true;
                 }
+                _ if scrub_key_direction(&core, &*audio, &key).is_some() => {
+                    if local_playback_locked_by_host_only(&core) {
+                        core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
+                        core.dirty = true;
+                        continue;
+                    }
+                    let direction = scrub_key_direction(&core, &*audio, &key).unwrap_or(0);
+                    pending_scrub_delta = pending_scrub_delta
+                        .saturating_add(direction * i64::from(core.scrub_seconds));
+                }
+                KeyCode::Char('[') | KeyCode::Char(']') => {
+                    core.scrub_seconds = if key.code == KeyCode::Char(']') {
+                        next_scrub_seconds(core.scrub_seconds)
+                    } else {
+                        previous_scrub_seconds(core.scrub_seconds)
+                    };
+                    core.status = format!("Scrub length: {}", scrub_label(core.scrub_seconds));
+                    core.dirty = true;
+                    auto_save_state(&mut core, &*audio);
+                }
+                KeyCode::Delete
+                    if core.header_section == HeaderSection::Library
+                        && !core.library_search_focused =>
+                {
+                    remove_selected_queue_item(
+                        &mut core,
+                        &mut *audio,
+                        online_runtime.network.as_ref(),
+                    );
+                    auto_save_state(&mut core, &*audio);
+                }
+                KeyCode::Up | KeyCode::Down
+                    if key.modifiers.contains(KeyModifiers::SHIFT)
+                        && core.header_section == HeaderSection::Library
+                        && !core.library_search_focused =>
+                {
+                    let delta = if key.code == KeyCode::Up { -1 } else { 1 };
+                    shift_selected_queue_item(&mut core, &online_runtime, delta);
+                    auto_save_state(&mut core, &*audio);
+                }
                 KeyCode::Down => {
                     if core.header_section == HeaderSection::Library && core.library_search_focused
                     {
@@ -2171,10 +3410,22 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                     {
                         continue;
                     }
-                    if let Some(path) = core.activate_selected() {
+                    let alternate = key.modifiers.contains(KeyModifiers::SHIFT);
+                    if !alternate && open_folder_enter_prompt(&core, &mut action_panel) {
+                        core.dirty = true;
+                        continue;
+                    }
+                    let play_once = key.modifiers.contains(KeyModifiers::ALT)
+                        && core.online.session.is_none()
+                        && core
+                            .browser_entries
+                            .get(core.selected_browser)
+                            .is_some_and(|entry| entry.kind == BrowserEntryKind::Track);
+                    if let Some(path) = core.activate_selected_with(alternate) {
                         if let Err(err) = audio.play(&path) {
                             core.status = concise_audio_error(&err);
                         } else {
+                            set_play_once(&mut core, play_once.then_some(path));
                             publish_current_playback_state(&core, &*audio, &online_runtime);
                         }
                     }
@@ -2201,6 +3452,9 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                         core.navigate_back();
                     }
                 }
+                KeyCode::Home if core.header_section == HeaderSection::Library => {
+                    core.navigate_to_breadcrumb(0);
+                }
                 KeyCode::Backspace => {
                     if core.header_section == HeaderSection::Library
                         && core.library_search_focused
@@ -2220,6 +3474,9 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                         core.navigate_back();
                     }
                 }
+                _ if library_search_mark_key(&core, &key) => {
+                    core.toggle_library_search_mark();
+                }
                 KeyCode::Char(' ') => {
                     if local_playback_locked_by_host_only(&core) {
                         core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
@@ -2236,6 +3493,33 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                     publish_current_playback_state(&core, &*audio, &online_runtime);
                     core.dirty = true;
                 }
+                KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'z') => {
+                    let area = terminal.size()?;
+                    let compact = crate::ui::compact_layout_active(
+                        ratatui::prelude::Rect::new(0, 0, area.width, area.height),
+                        core.compact_layout,
+                    );
+                    core.toggle_compact_layout(compact);
+                    auto_save_state(&mut core, &*audio);
+                }
+                KeyCode::Char('0') => toggle_mute(&mut core, &mut *audio),
+                KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'o') => {
+                    cycle_ab_loop(&mut core, &mut *audio);
+                }
+                KeyCode::Char('>') | KeyCode::Char('<') => {
+                    cycle_playback_speed(&mut core, &mut *audio, key.code == KeyCode::Char('>'));
+                }
+                KeyCode::Char('c') => copy_now_playing(&mut core, &*audio, false),
+                KeyCode::Char('C') => copy_now_playing(&mut core, &*audio, true),
+                KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'x') => {
+                    stop_playback(
+                        &mut core,
+                        &mut *audio,
+                        &online_runtime,
+                        &mut listen_tracker,
+                        &mut stats_store,
+                    );
+                }
                 KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'n') => {
                     if local_playback_locked_by_host_only(&core) {
                         core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
@@ -2266,34 +3550,16 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                         }
                     }
                 }
-                KeyCode::Char('a') | KeyCode::Char('A') => {
+                KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'m') => {
                     if local_playback_locked_by_host_only(&core) {
                         core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
                         core.dirty = true;
                         continue;
                     }
-                    pending_scrub_delta =
-                        pending_scrub_delta.saturating_sub(i64::from(core.scrub_seconds));
+                    core.cycle_repeat_mode();
+                    auto_save_state(&mut core, &*audio);
                 }
-                KeyCode::Char('d') | KeyCode::Char('D') => {
-                    if local_playback_locked_by_host_only(&core) {
-                        core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
-                        core.dirty = true;
-                        continue;
-                    }
-                    pending_scrub_delta =
-                        pending_scrub_delta.saturating_add(i64::from(core.scrub_seconds));
-                }
-                KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'m') => {
-                    if local_playback_locked_by_host_only(&core) {
-                        core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
-                        core.dirty = true;
-                        continue;
-                    }
-                    core.cycle_repeat_mode();
-                    auto_save_state(&mut core, &*audio);
-                }
-                KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'v') => {
+                KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'v') => {
                     if local_playback_locked_by_host_only(&core) {
                         core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
                         core.dirty = true;
@@ -2312,6 +3578,12 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                 KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'t') => {
                     request_minimize_to_tray(&mut core);
                 }
+                KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'g') => {
+                    toggle_loudness_normalization(&mut core, &mut *audio);
+                }
+                _ if output_cycle_key(&key) => {
+                    cycle_output_device(&mut core, &mut *audio);
+                }
                 KeyCode::Char('+') | KeyCode::Char('=') => {
                     let step = if key.code == KeyCode::Char('+')
                         || key.modifiers.contains(KeyModifiers::SHIFT)
@@ -2321,6 +3593,7 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                         VOLUME_STEP_COARSE
                     };
                     let next = (audio.volume() + step).clamp(0.0, MAX_VOLUME);
+                    audio.unmute();
                     audio.set_volume(next);
                     core.status = format!("Volume: {}%", (next * 100.0).round() as u16);
                     core.dirty = true;
@@ -2334,6 +3607,7 @@ pub fn run_with_startup(startup: AppStartupOptions) -> Result<()> {
                         VOLUME_STEP_COARSE
                     };
                     let next = (audio.volume() - step).clamp(0.0, MAX_VOLUME);
+                    audio.unmute();
                     audio.set_volume(next);
                     core.status = format!("Volume: {}%", (next * 100.0).round() as u16);
                     core.dirty = true;
@@ -2406,6 +3680,38 @@ fn ensure_single_instance() -> anyhow::Result<Option<SingleInstanceGuard>> {
     Ok(Some(SingleInstanceGuard(handle)))
 }
 
+/// `TuneTUI — Artist - Title` while a track is loaded and the setting is on, so
+/// the taskbar, alt-tab, and terminal tabs show what is playing.
+fn window_title(core: &TuneCore, audio: &dyn AudioEngine) -> String {
+    let Some(path) = audio
+        .current_track()
+        .filter(|_| core.window_title_shows_track)
+    else {
+        return String::from(APP_CONSOLE_TITLE);
+    };
+    let title = core.title_for_path(path).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("-"))
+    });
+    let track = match core.artist_for_path(path) {
+        Some(artist) => format!("{artist} - {title}"),
+        None => title,
+    };
+    let track: String = track.chars().filter(|ch| !ch.is_control()).collect();
+    format!("{APP_CONSOLE_TITLE} — {track}")
+}
+
+#[cfg(windows)]
+fn apply_window_title(title: &str) {
+    set_console_title(title);
+}
+
+#[cfg(not(windows))]
+fn apply_window_title(title: &str) {
+    let _ = execute!(stdout(), crossterm::terminal::SetTitle(title));
+}
+
 #[cfg(windows)]
 fn set_console_title(title: &str) {
     use windows_sys::Win32::System::Console::SetConsoleTitleW;
@@ -2451,50 +3757,42 @@ fn request_tray_restore() -> bool {
     unsafe { PostMessageW(hwnd, TRAY_RESTORE_MSG, 0, 0) != 0 }
 }
 
+/// Arms or clears the Alt+Enter "play once then stop" mode for the track that
+/// just started.
+fn set_play_once(core: &mut TuneCore, path: Option<PathBuf>) {
+    if let Some(path) = &path {
+        let title = core
+            .title_for_path(path)
+            .unwrap_or_else(|| path.display().to_string());
+        core.status = format!("Playing once: {title} (stops when it ends)");
+        core.dirty = true;
+    }
+    core.play_once = path;
+}
+
 fn maybe_auto_advance_track(
     core: &mut TuneCore,
     audio: &mut dyn AudioEngine,
     online_runtime: &mut OnlineRuntime,
+    history: &[ListenEvent],
 ) {
     if audio.current_track().is_none() || audio.is_paused() {
         return;
     }
 
     if core.online.session.is_some() {
-        maybe_auto_advance_online_track(core, audio, online_runtime);
-        return;
-    }
-
-    let crossfade_triggered = should_trigger_crossfade_advance(audio);
-    if crossfade_triggered && audio.crossfade_queued_track().is_some() {
-        return;
-    }
-
-    if !audio.is_finished() && !crossfade_triggered {
+        maybe_auto_advance_online_track(core, audio, online_runtime, history);
         return;
     }
 
-    if let Some(path) = core.next_track_path() {
-        let result = if crossfade_triggered {
-            audio.queue_crossfade(&path)
-        } else {
-            audio.play(&path)
-        };
-        if let Err(err) = result {
-            core.status = concise_audio_error(&err);
-            core.dirty = true;
-        }
-    } else if audio.is_finished() {
-        audio.stop();
-        core.status = String::from("Reached end of queue");
-        core.dirty = true;
-    }
+    advance_local_queue(core, audio, history);
 }
 
 fn maybe_auto_advance_online_track(
     core: &mut TuneCore,
     audio: &mut dyn AudioEngine,
     online_runtime: &mut OnlineRuntime,
+    history: &[ListenEvent],
 ) {
     if !audio.is_finished() {
         return;
@@ -2510,6 +3808,17 @@ fn maybe_auto_advance_online_track(
         return;
     }
 
+    advance_online_track(core, audio, online_runtime, history);
+}
+
+/// Moves the room on to the next shared item, or the local queue when the
+/// shared queue is empty, and publishes the new track.
+fn advance_online_track(
+    core: &mut TuneCore,
+    audio: &mut dyn AudioEngine,
+    online_runtime: &mut OnlineRuntime,
+    history: &[ListenEvent],
+) {
     let next_shared = core
         .online
         .session
@@ -2541,7 +3850,10 @@ fn maybe_auto_advance_online_track(
         return;
     }
 
-    if let Some(path) = core.next_track_path() {
+    if let Some(path) = core
+        .next_track_path()
+        .or_else(|| core.end_of_queue_track_path(history))
+    {
         match audio.play(&path) {
             Ok(()) => {
                 online_runtime.online_playback_source = OnlinePlaybackSource::LocalQueue;
@@ -2561,6 +3873,83 @@ fn maybe_auto_advance_online_track(
     }
 }
 
+/// Skips the room's track when the host asked to with `s`, or when the
+/// authority sees that a majority voted to skip what it is playing. The path
+/// check keeps a stale session from skipping the next track too.
+fn maybe_skip_online_track(
+    core: &mut TuneCore,
+    audio: &mut dyn AudioEngine,
+    online_runtime: &mut OnlineRuntime,
+    history: &[ListenEvent],
+) {
+    let forced = std::mem::take(&mut online_runtime.skip_requested);
+    let Some(session) = core.online.session.as_ref() else {
+        return;
+    };
+    if audio.current_track().is_none() {
+        return;
+    }
+    if !forced {
+        let local_is_authority = online_authority_nickname(session).is_some_and(|authority| {
+            authority.eq_ignore_ascii_case(&online_runtime.local_nickname)
+        });
+        if !local_is_authority
+            || !session.skip_vote_passed()
+            || session.current_transport_path() != audio.current_track()
+        {
+            return;
+        }
+    }
+
+    core.status = if forced {
+        String::from("Skipped by host")
+    } else {
+        format!("Skip vote passed ({})", session.skip_vote_label())
+    };
+    core.dirty = true;
+    if let Some(session) = core.online.session.as_mut() {
+        session.skip_votes.clear();
+    }
+    advance_online_track(core, audio, online_runtime, history);
+}
+
+/// `s` on the Online tab: the host skips straight away, everyone else adds a
+/// vote for the track the room is playing.
+fn request_online_skip(core: &mut TuneCore, online_runtime: &mut OnlineRuntime) {
+    core.dirty = true;
+    let Some(session) = core.online.session.as_ref() else {
+        core.status = String::from("No room connected");
+        return;
+    };
+    let Some(current) = session.current_transport_path().map(Path::to_path_buf) else {
+        core.status = String::from("Nothing playing to skip");
+        return;
+    };
+    let local_is_host = session
+        .local_participant()
+        .is_some_and(|participant| participant.is_host);
+    if local_is_host {
+        online_runtime.skip_requested = true;
+        return;
+    }
+    if session
+        .skip_votes
+        .iter()
+        .any(|voter| voter.eq_ignore_ascii_case(&online_runtime.local_nickname))
+    {
+        core.status = format!("Already voted ({})", session.skip_vote_label());
+        return;
+    }
+    let Some(network) = online_runtime.network.as_ref() else {
+        core.status = String::from("Skip votes need a connected room");
+        return;
+    };
+    network.send_local_action(NetworkLocalAction::VoteSkip {
+        expected_path: Some(current),
+    });
+    core.status = String::from("Voted to skip");
+}
+
 fn maybe_start_online_shared_queue_if_idle(
     core: &mut TuneCore,
     audio: &mut dyn AudioEngine,
@@ -2644,6 +4033,52 @@ fn play_shared_queue_now(
     }
 }
 
+/// Removes the selected queue item, telling the room when it is the shared
+/// queue. Removing the local entry that is playing stops playback instead of
+/// letting the removed track play on.
+fn remove_selected_queue_item(
+    core: &mut TuneCore,
+    audio: &mut dyn AudioEngine,
+    network: Option<&OnlineNetwork>,
+) {
+    if core.viewing_shared_queue() {
+        if let Some((index, expected_path)) = core.remove_selected_from_shared_queue()
+            && let Some(network) = network
+        {
+            network.send_local_action(NetworkLocalAction::QueueRemoveAt {
+                index,
+                expected_path: Some(expected_path),
+            });
+        }
+        return;
+    }
+    if core.remove_selected_from_local_queue() && audio.current_track().is_some() {
+        audio.stop();
+        core.clear_now_playing();
+        core.status = String::from("Removed the playing track. Stopped");
+        core.dirty = true;
+    }
+}
+
+/// Shift+Up/Down in the Library: moves the selected queue item one place,
+/// telling the room when it is the shared queue.
+fn shift_selected_queue_item(core: &mut TuneCore, online_runtime: &OnlineRuntime, delta: isize) {
+    if !core.viewing_shared_queue() {
+        core.shift_selected_local_queue_item(delta);
+        return;
+    }
+    if let Some((from_index, to_index, expected_path)) =
+        core.shift_selected_shared_queue_item(delta)
+        && let Some(network) = online_runtime.network.as_ref()
+    {
+        network.send_local_action(NetworkLocalAction::QueueMove {
+            from_index,
+            to_index,
+            expected_path: Some(expected_path),
+        });
+    }
+}
+
 fn play_selected_shared_queue_item(
     core: &mut TuneCore,
     audio: &mut dyn AudioEngine,
@@ -2749,6 +4184,21 @@ fn key_code_matches_char(code: KeyCode, expected: char) -> bool {
     matches!(code, KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&expected))
 }
 
+/// `Insert` or `Ctrl+Space` marks a Library search result. Plain `Space` is
+/// left to pause and, while the search field is focused, to typing.
+fn library_search_mark_key(core: &TuneCore, key: &KeyEvent) -> bool {
+    let mark_key = key.code == KeyCode::Insert || key_event_matches_ctrl_char(key, ' ');
+    mark_key
+        && core.header_section == HeaderSection::Library
+        && !core.library_search_query.is_empty()
+}
+
+/// Plain `s` switches outputs. `Ctrl+s` is the Online shared queue key in
+/// Library and does nothing elsewhere.
+fn output_cycle_key(key: &KeyEvent) -> bool {
+    key_code_matches_char(key.code, 's') && !key_event_matches_ctrl_char(key, 's')
+}
+
 fn key_event_matches_ctrl_char(key: &KeyEvent, expected: char) -> bool {
     key.modifiers.contains(KeyModifiers::CONTROL) && key_code_matches_char(key.code, expected)
 }
@@ -2767,6 +4217,159 @@ fn header_section_shortcut(key: KeyEvent) -> Option<HeaderSection> {
     }
 }
 
+fn cycle_playback_speed(core: &mut TuneCore, audio: &mut dyn AudioEngine, faster: bool) {
+    if core.online.session.is_some() {
+        core.status = String::from("Playback speed stays at 1x in Listen Together");
+        core.dirty = true;
+        return;
+    }
+    core.cycle_playback_speed(faster);
+    audio.set_playback_speed(core.playback_speed);
+    auto_save_state(core, &*audio);
+}
+
+fn toggle_preserve_pitch(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    core.toggle_preserve_pitch();
+    audio.set_preserve_pitch(core.preserve_pitch);
+    auto_save_state(core, &*audio);
+}
+
+/// Listen Together syncs positions in track time, so a room always plays at
+/// normal speed; the saved speed comes back on leaving.
+fn sync_playback_speed(core: &TuneCore, audio: &mut dyn AudioEngine) {
+    let speed = if core.online.session.is_some() {
+        1.0
+    } else {
+        core.playback_speed
+    };
+    if audio.playback_speed() != speed {
+        audio.set_playback_speed(speed);
+    }
+}
+
+/// One key walks the loop through its states: mark A, mark B to start
+/// looping, then clear.
+fn cycle_ab_loop(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    core.dirty = true;
+    if audio.ab_loop().is_some() {
+        audio.set_ab_loop(None);
+        core.ab_loop_start = None;
+        core.status = String::from("A-B loop cleared");
+        return;
+    }
+    if core.online.session.is_some() {
+        core.status = String::from("A-B loop is not available in Listen Together");
+        return;
+    }
+    let (Some(track), Some(position)) = (audio.current_track(), audio.position()) else {
+        core.status = String::from("Nothing playing to loop");
+        return;
+    };
+    let track = track.to_path_buf();
+    match core.ab_loop_start.take() {
+        Some((start_track, start)) if start_track == track => {
+            if position <= start {
+                core.ab_loop_start = Some((start_track, start));
+                core.status = format!("Loop end B must come after A ({})", format_timestamp(start));
+                return;
+            }
+            audio.set_ab_loop(Some((start, position)));
+            core.status = format!(
+                "A-B loop {}-{}",
+                format_timestamp(start),
+                format_timestamp(position)
+            );
+        }
+        _ => {
+            core.ab_loop_start = Some((track, position));
+            core.status = format!(
+                "Loop start A at {}; press o again to set B",
+                format_timestamp(position)
+            );
+        }
+    }
+}
+
+fn toggle_album_gain(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    core.album_gain = !core.album_gain;
+    audio.set_album_gain(core.album_gain);
+    core.status = format!(
+        "Normalization gain: {}",
+        normalization_gain_label(core.album_gain)
+    );
+    core.dirty = true;
+    auto_save_state(core, &*audio);
+}
+
+fn normalization_gain_label(album_gain: bool) -> &'static str {
+    if album_gain { "Album" } else { "Track" }
+}
+
+fn toggle_gapless(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    core.gapless = !core.gapless;
+    audio.set_gapless(core.gapless);
+    core.status = format!(
+        "Gapless playback: {}",
+        if core.gapless { "On" } else { "Off" }
+    );
+    core.dirty = true;
+    auto_save_state(core, &*audio);
+}
+
+fn toggle_prevent_clipping(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    core.prevent_clipping = !core.prevent_clipping;
+    audio.set_prevent_clipping(core.prevent_clipping);
+    core.status = format!(
+        "Prevent clipping: {}",
+        if core.prevent_clipping { "On" } else { "Off" }
+    );
+    core.dirty = true;
+    auto_save_state(core, &*audio);
+}
+
+fn toggle_loudness_normalization(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    core.loudness_normalization = !core.loudness_normalization;
+    audio.set_loudness_normalization(core.loudness_normalization);
+    core.status = format!(
+        "Loudness normalization: {}",
+        if core.loudness_normalization {
+            "On"
+        } else {
+            "Off"
+        }
+    );
+    core.dirty = true;
+    auto_save_state(core, &*audio);
+}
+
+fn stop_playback(
+    core: &mut TuneCore,
+    audio: &mut dyn AudioEngine,
+    online_runtime: &OnlineRuntime,
+    listen_tracker: &mut ListenTracker,
+    stats_store: &mut StatsStore,
+) {
+    if local_playback_locked_by_host_only(core) {
+        core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
+        core.dirty = true;
+        return;
+    }
+    if audio.current_track().is_none() {
+        core.status = String::from("Nothing is playing");
+        core.dirty = true;
+        return;
+    }
+
+    if listen_tracker.finalize_active(stats_store, false) {
+        let _ = stats::save_stats(stats_store);
+    }
+    audio.stop();
+    core.clear_now_playing();
+    publish_transport_command(core, online_runtime, TransportCommand::StopPlayback);
+    core.status = String::from("Stopped");
+    core.dirty = true;
+}
+
 fn online_tab_allows_global_shortcut(code: KeyCode) -> bool {
     matches!(
         code,
@@ -2778,14 +4381,86 @@ fn online_tab_allows_global_shortcut(code: KeyCode) -> bool {
             | KeyCode::Char('M')
             | KeyCode::Char('v')
             | KeyCode::Char('V')
+            | KeyCode::Char('x')
+            | KeyCode::Char('X')
+            | KeyCode::Char('g')
+            | KeyCode::Char('G')
+            | KeyCode::Char('0')
     )
 }
 
-fn handle_stats_inline_input(core: &mut TuneCore, key: KeyEvent) -> bool {
+/// Switches to the next output after the selected one, wrapping through the
+/// system default and skipping any device that fails to open.
+fn cycle_output_device(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    let mut choices: Vec<Option<String>> = vec![None];
+    choices.extend(audio.available_outputs().into_iter().map(Some));
+    if choices.len() < 2 {
+        core.status = String::from("No other outputs available");
+        core.dirty = true;
+        return;
+    }
+
+    let selected = audio.selected_output_device();
+    let current = choices
+        .iter()
+        .position(|choice| match (choice, &selected) {
+            (Some(choice), Some(selected)) => output_device_names_match(choice, selected),
+            (choice, selected) => choice.is_none() && selected.is_none(),
+        })
+        .unwrap_or(0);
+    let mut last_error = None;
+    for offset in 1..choices.len() {
+        let choice = &choices[(current + offset) % choices.len()];
+        match audio.set_output_device(choice.as_deref()) {
+            Ok(()) => {
+                core.status = format!(
+                    "Output: {}",
+                    audio
+                        .output_name()
+                        .unwrap_or_else(|| String::from("unknown output"))
+                );
+                core.dirty = true;
+                auto_save_state(core, &*audio);
+                return;
+            }
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    if let Some(err) = last_error {
+        core.status = format!("Output switch failed: {err}. Try Reload audio driver");
+    }
+    core.dirty = true;
+}
+
+fn handle_stats_inline_input(
+    core: &mut TuneCore,
+    key: KeyEvent,
+    rows: &[crate::stats::TrackStatsRow],
+) -> bool {
     if core.header_section != HeaderSection::Stats {
         return false;
     }
 
+    if key.code == KeyCode::Tab {
+        core.stats_forget_confirm = None;
+        if matches!(core.stats_focus, StatsFilterFocus::Row(_)) {
+            core.stats_focus = StatsFilterFocus::Range(core_range_index(core.stats_range));
+            core.status = String::from("Stats filters");
+        } else if rows.is_empty() {
+            core.status = String::from("No tracks to select");
+        } else {
+            core.stats_focus = StatsFilterFocus::Row(0);
+            core.status = String::from("Select a track, Delete to forget its stats");
+        }
+        core.dirty = true;
+        return true;
+    }
+
+    if let StatsFilterFocus::Row(index) = core.stats_focus {
+        return handle_stats_row_input(core, key, rows, index);
+    }
+
     if key.code == KeyCode::Up && key.modifiers.contains(KeyModifiers::SHIFT) {
         core.stats_scroll = 0;
         core.stats_focus = StatsFilterFocus::Range(core_range_index(core.stats_range));
@@ -2831,11 +4506,14 @@ fn handle_stats_inline_input(core: &mut TuneCore, key: KeyEvent) -> bool {
                     set_stats_range_by_index(core, next);
                 }
                 StatsFilterFocus::Sort(index) => {
-                    let next = 1_u8.saturating_sub(index.min(1));
+                    let next = (index + 1) % STATS_SORT_OPTION_COUNT;
                     core.stats_focus = StatsFilterFocus::Sort(next);
                     set_stats_sort_by_index(core, next);
                 }
-                StatsFilterFocus::Artist | StatsFilterFocus::Album | StatsFilterFocus::Search => {}
+                StatsFilterFocus::Artist
+                | StatsFilterFocus::Album
+                | StatsFilterFocus::Search
+                | StatsFilterFocus::Row(_) => {}
             }
             true
         }
@@ -2844,7 +4522,9 @@ fn handle_stats_inline_input(core: &mut TuneCore, key: KeyEvent) -> bool {
                 StatsFilterFocus::Artist => Some(&mut core.stats_artist_filter),
                 StatsFilterFocus::Album => Some(&mut core.stats_album_filter),
                 StatsFilterFocus::Search => Some(&mut core.stats_search),
-                StatsFilterFocus::Range(_) | StatsFilterFocus::Sort(_) => None,
+                StatsFilterFocus::Range(_)
+                | StatsFilterFocus::Sort(_)
+                | StatsFilterFocus::Row(_) => None,
             };
 
             if let Some(text) = target {
@@ -2868,7 +4548,9 @@ fn handle_stats_inline_input(core: &mut TuneCore, key: KeyEvent) -> bool {
                 StatsFilterFocus::Artist => Some(&mut core.stats_artist_filter),
                 StatsFilterFocus::Album => Some(&mut core.stats_album_filter),
                 StatsFilterFocus::Search => Some(&mut core.stats_search),
-                StatsFilterFocus::Range(_) | StatsFilterFocus::Sort(_) => None,
+                StatsFilterFocus::Range(_)
+                | StatsFilterFocus::Sort(_)
+                | StatsFilterFocus::Row(_) => None,
             };
 
             if let Some(text) = target {
@@ -2886,7 +4568,11 @@ fn handle_stats_inline_input(core: &mut TuneCore, key: KeyEvent) -> bool {
                 StatsFilterFocus::Artist => core.stats_artist_filter.clear(),
                 StatsFilterFocus::Album => core.stats_album_filter.clear(),
                 StatsFilterFocus::Search => core.stats_search.clear(),
-                StatsFilterFocus::Range(_) | StatsFilterFocus::Sort(_) => return false,
+                StatsFilterFocus::Range(_)
+                | StatsFilterFocus::Sort(_)
+                | StatsFilterFocus::Row(_) => {
+                    return false;
+                }
             }
             core.status = format!("{} filter cleared", core.stats_focus.label());
             core.dirty = true;
@@ -2896,32 +4582,126 @@ fn handle_stats_inline_input(core: &mut TuneCore, key: KeyEvent) -> bool {
     }
 }
 
-fn handle_lyrics_inline_input(core: &mut TuneCore, audio: &dyn AudioEngine, key: KeyEvent) -> bool {
-    if core.header_section != HeaderSection::Lyrics {
-        return false;
-    }
-
-    if key.code == KeyCode::Char('/')
-        || (header_section_shortcut(key).is_some()
-            && (core.lyrics_missing_prompt || core.lyrics_mode != LyricsMode::Edit))
-    {
-        return false;
+/// hjkl as the arrow keys they sit under, for lists with nothing to type into.
+fn vim_navigation_key(key: KeyCode) -> KeyCode {
+    match key {
+        KeyCode::Char('h') => KeyCode::Left,
+        KeyCode::Char('j') => KeyCode::Down,
+        KeyCode::Char('k') => KeyCode::Up,
+        KeyCode::Char('l') => KeyCode::Right,
+        other => other,
     }
+}
 
-    if core.lyrics_missing_prompt {
-        match key.code {
-            KeyCode::Enter => {
-                core.create_empty_lyrics_sidecar();
-                true
-            }
-            KeyCode::Esc | KeyCode::Backspace => {
-                core.decline_lyrics_creation();
-                true
-            }
-            _ => true,
-        }
-    } else {
-        if key_event_matches_ctrl_char(&key, 'e') {
+fn handle_stats_row_input(
+    core: &mut TuneCore,
+    key: KeyEvent,
+    rows: &[crate::stats::TrackStatsRow],
+    index: usize,
+) -> bool {
+    let last = rows.len().saturating_sub(1);
+    let index = index.min(last);
+    // h and l stay page shortcuts here; rows only move up and down.
+    let code = match key.code {
+        KeyCode::Char('j' | 'k') if key.modifiers.is_empty() => vim_navigation_key(key.code),
+        code => code,
+    };
+    match code {
+        KeyCode::Up | KeyCode::Down => {
+            let next = if code == KeyCode::Up {
+                index.saturating_sub(1)
+            } else {
+                (index + 1).min(last)
+            };
+            core.stats_focus = StatsFilterFocus::Row(next);
+            core.stats_forget_confirm = None;
+            core.dirty = true;
+            true
+        }
+        KeyCode::Delete => {
+            let Some(row) = rows.get(index) else {
+                core.status = String::from("No track selected");
+                core.dirty = true;
+                return true;
+            };
+            if core.stats_forget_confirm.as_deref() == Some(row.track_key.as_str()) {
+                core.stats_forget_confirm = None;
+                core.forget_stats_track_requested = Some(row.track_key.clone());
+                core.status = format!("Forgetting stats for {}...", row.title);
+            } else {
+                core.stats_forget_confirm = Some(row.track_key.clone());
+                core.status = format!(
+                    "Press Delete again to forget all stats for {} (Esc cancels)",
+                    row.title
+                );
+            }
+            core.dirty = true;
+            true
+        }
+        KeyCode::Esc if core.stats_forget_confirm.is_some() => {
+            core.stats_forget_confirm = None;
+            core.status = String::from("Forget cancelled");
+            core.dirty = true;
+            true
+        }
+        KeyCode::Left | KeyCode::Right | KeyCode::Enter | KeyCode::Backspace => true,
+        _ => false,
+    }
+}
+
+/// y saves, n discards, Esc keeps editing; other keys leave the prompt up.
+/// Returns the held action once it may go ahead.
+fn handle_lyrics_leave_prompt_input(
+    core: &mut TuneCore,
+    key: KeyEvent,
+) -> Option<LyricsLeaveAction> {
+    let resolved = match key.code {
+        KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'y') => {
+            core.resolve_lyrics_leave_prompt(true)
+        }
+        KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'n') => {
+            core.resolve_lyrics_leave_prompt(false)
+        }
+        KeyCode::Esc => {
+            core.cancel_lyrics_leave_prompt();
+            None
+        }
+        _ => None,
+    };
+    core.dirty = true;
+    resolved
+}
+
+fn handle_lyrics_inline_input(core: &mut TuneCore, audio: &dyn AudioEngine, key: KeyEvent) -> bool {
+    if core.header_section != HeaderSection::Lyrics {
+        return false;
+    }
+
+    if key.code == KeyCode::Char('/')
+        || (header_section_shortcut(key).is_some()
+            && (core.lyrics_missing_prompt || core.lyrics_mode != LyricsMode::Edit))
+    {
+        return false;
+    }
+
+    if core.lyrics_missing_prompt {
+        match key.code {
+            KeyCode::Enter => {
+                core.create_empty_lyrics_sidecar();
+                true
+            }
+            KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'f') => {
+                core.request_lyrics_fetch();
+                true
+            }
+            KeyCode::Esc | KeyCode::Backspace => {
+                core.decline_lyrics_creation();
+                true
+            }
+            _ => true,
+        }
+    } else {
+        if key_event_matches_ctrl_char(&key, 'e') {
             core.toggle_lyrics_mode();
             return true;
         }
@@ -3204,31 +4984,12 @@ fn handle_online_inline_input(
                 if key.modifiers.contains(KeyModifiers::CONTROL)
                     && ch.eq_ignore_ascii_case(&'v') =>
             {
-                match paste_invite_from_clipboard(online_runtime) {
-                    Ok(()) => {
-                        core.status = format!("Pasted input: {}", online_runtime.join_code_input);
-                    }
-                    Err(err) => {
-                        core.status = format!("Clipboard paste failed: {err}");
-                    }
-                }
-                online_runtime.join_prompt_button = JoinPromptButton::Input;
-                core.dirty = true;
+                apply_clipboard_paste(core, online_runtime);
                 return true;
             }
             KeyCode::Enter => {
                 if matches!(online_runtime.join_prompt_button, JoinPromptButton::Paste) {
-                    match paste_invite_from_clipboard(online_runtime) {
-                        Ok(()) => {
-                            core.status =
-                                format!("Pasted input: {}", online_runtime.join_code_input);
-                        }
-                        Err(err) => {
-                            core.status = format!("Clipboard paste failed: {err}");
-                        }
-                    }
-                    online_runtime.join_prompt_button = JoinPromptButton::Input;
-                    core.dirty = true;
+                    apply_clipboard_paste(core, online_runtime);
                     return true;
                 }
                 if matches!(
@@ -3401,6 +5162,12 @@ fn handle_online_inline_input(
             play_shared_queue_now(core, audio, online_runtime);
             true
         }
+        KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home
+            if core.online.session.is_some() =>
+        {
+            scroll_online_shared_queue(core, key.code);
+            true
+        }
         KeyCode::Char(_) if key_event_matches_ctrl_char(&key, 'l') => {
             if core.online.session.is_none() {
                 core.status = String::from("No room connected");
@@ -3412,7 +5179,12 @@ fn handle_online_inline_input(
             core.online_leave_room();
             if online_runtime.home_server_connected {
                 online_runtime.pending_join_server_addr = online_runtime.home_server_addr.clone();
-                load_home_room_directory(core, online_runtime, "Left room. Room directory loaded.");
+                let status = if online_runtime.local_home_server.is_some() {
+                    "Left room. Local home server still relaying. Room directory loaded."
+                } else {
+                    "Left room. Room directory loaded."
+                };
+                load_home_room_directory(core, online_runtime, status);
             }
             true
         }
@@ -3444,7 +5216,30 @@ fn handle_online_inline_input(
             core.dirty = true;
             true
         }
+        KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'c') => {
+            if core.online.session.is_none() {
+                core.status = String::from("No room connected");
+            } else {
+                online_runtime.chat_input_active = true;
+                core.status = String::from("Chat: Enter sends, Esc closes");
+            }
+            core.dirty = true;
+            true
+        }
+        KeyCode::Char(ch)
+            if ch.eq_ignore_ascii_case(&'s') && !key.modifiers.contains(KeyModifiers::CONTROL) =>
+        {
+            request_online_skip(core, online_runtime);
+            true
+        }
         KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'t') => {
+            // `t` minimizes to tray on the other pages. Here it only explains
+            // itself, so reaching for the old reveal key never hides the app.
+            core.status = String::from(ONLINE_TRAY_KEY_HINT);
+            core.dirty = true;
+            true
+        }
+        KeyCode::Char('1') => {
             let Some(session) = core.online.session.as_ref() else {
                 return true;
             };
@@ -3466,7 +5261,7 @@ fn handle_online_inline_input(
         KeyCode::Char('2') => {
             if let Some(session) = core.online.session.as_ref() {
                 let field = active_online_room_field(online_runtime, session);
-                match copy_invite_to_clipboard(&field.value) {
+                match copy_text_to_clipboard(&field.value) {
                     Ok(()) => {
                         core.status = format!("Copied {}", online_room_field_copy_name(field.kind));
                     }
@@ -3478,10 +5273,159 @@ fn handle_online_inline_input(
             }
             true
         }
+        KeyCode::Char('3') => {
+            open_room_invite_qr(core, online_runtime);
+            true
+        }
         _ => !online_tab_allows_global_shortcut(key.code),
     }
 }
 
+/// Opens the invite dialog with the room's share value as a QR code. The
+/// text stays hidden unless the room code reveal toggle is on.
+fn open_room_invite_qr(core: &mut TuneCore, online_runtime: &mut OnlineRuntime) {
+    let Some(session) = core.online.session.as_ref() else {
+        return;
+    };
+    let field = active_online_room_field(online_runtime, session);
+    online_runtime.host_invite_qr_rows = crate::ui::invite_qr_rows(&field.value).map(Arc::from);
+    online_runtime.host_invite_code = field.value;
+    online_runtime.host_invite_qr_only = true;
+    online_runtime.host_invite_button = HostInviteModalButton::Copy;
+    online_runtime.host_invite_modal_active = true;
+    core.status = format!(
+        "Showing {} as QR code",
+        online_room_field_copy_name(field.kind)
+    );
+    core.dirty = true;
+}
+
+/// Chat input mode on the Online tab. Every key goes to the draft until Esc,
+/// so typing a message never triggers a page or room shortcut.
+fn handle_online_chat_input(
+    core: &mut TuneCore,
+    key: KeyEvent,
+    online_runtime: &mut OnlineRuntime,
+) -> bool {
+    if core.header_section != HeaderSection::Online || !online_runtime.chat_input_active {
+        return false;
+    }
+    if core.online.session.is_none() {
+        online_runtime.chat_input_active = false;
+        online_runtime.chat_input.clear();
+        return false;
+    }
+    if key_event_matches_ctrl_char(&key, 'c') {
+        return false;
+    }
+    match key.code {
+        KeyCode::Esc => {
+            online_runtime.chat_input_active = false;
+            online_runtime.chat_input.clear();
+            core.status = String::from("Chat closed");
+        }
+        KeyCode::Backspace => {
+            online_runtime.chat_input.pop();
+        }
+        KeyCode::Enter => send_chat_message(core, online_runtime),
+        KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            append_chat_input(online_runtime, &ch.to_string());
+        }
+        _ => {}
+    }
+    core.dirty = true;
+    true
+}
+
+fn append_chat_input(online_runtime: &mut OnlineRuntime, text: &str) {
+    let room = crate::online::MAX_CHAT_MESSAGE_CHARS
+        .saturating_sub(online_runtime.chat_input.chars().count());
+    online_runtime
+        .chat_input
+        .extend(text.chars().filter(|ch| !ch.is_control()).take(room));
+}
+
+/// The message shows up in the log once the room echoes it back, so every
+/// participant sees the same order.
+fn send_chat_message(core: &mut TuneCore, online_runtime: &mut OnlineRuntime) {
+    let message = online_runtime.chat_input.trim().to_string();
+    if message.is_empty() {
+        return;
+    }
+    let Some(network) = &online_runtime.network else {
+        core.status = String::from("Chat needs a connected room");
+        return;
+    };
+    network.send_local_action(NetworkLocalAction::Chat { message });
+    online_runtime.chat_input.clear();
+}
+
+const ONLINE_TRAY_KEY_HINT: &str =
+    "t minimizes to tray outside Online. Use 1 to show or hide room codes";
+
+fn scroll_online_shared_queue(core: &mut TuneCore, code: KeyCode) {
+    let last = core
+        .online
+        .session
+        .as_ref()
+        .map(|session| session.shared_queue.len().saturating_sub(1))
+        .unwrap_or(0);
+    let scroll = core.online_queue_scroll.min(last);
+    core.online_queue_scroll = match code {
+        KeyCode::Up => scroll.saturating_sub(1),
+        KeyCode::Down => (scroll + 1).min(last),
+        KeyCode::PageUp => scroll.saturating_sub(ONLINE_QUEUE_PAGE_ROWS),
+        KeyCode::PageDown => (scroll + ONLINE_QUEUE_PAGE_ROWS).min(last),
+        _ => 0,
+    };
+    core.dirty = true;
+}
+
+fn stop_local_home_server(core: &mut TuneCore, online_runtime: &mut OnlineRuntime) {
+    let Some(server) = online_runtime.local_home_server.take() else {
+        core.status = String::from("No local home server running");
+        core.dirty = true;
+        return;
+    };
+    if core.online.session.is_some() {
+        online_runtime.shutdown();
+        online_runtime.last_transport_seq = 0;
+        core.online_leave_room();
+    }
+    server.shutdown();
+    online_runtime.home_server_connected = false;
+    online_runtime.join_directory_active = false;
+    online_runtime.join_directory_rooms.clear();
+    core.status = String::from("Local home server stopped");
+    core.dirty = true;
+}
+
+/// Rejoins the room from the last successful join without re-entering the
+/// invite, for when a dropped connection was not recovered automatically.
+fn reconnect_last_room(core: &mut TuneCore, online_runtime: &mut OnlineRuntime) -> bool {
+    let Some(last) = core.last_online_join.clone() else {
+        core.status = String::from("No previous room to reconnect to");
+        core.dirty = true;
+        return false;
+    };
+    let password = last.password.unwrap_or_default();
+    if join_home_room(
+        core,
+        online_runtime,
+        &last.server_addr,
+        &last.room_name,
+        &password,
+    ) {
+        core.status = format!("Reconnected to {}: {}", last.room_name, core.status);
+        core.dirty = true;
+        true
+    } else {
+        core.status = format!("Reconnect to {} failed: {}", last.room_name, core.status);
+        core.dirty = true;
+        false
+    }
+}
+
 fn next_room_mode_for_local_host(session: &OnlineSession) -> Option<crate::online::OnlineRoomMode> {
     session
         .local_participant()
@@ -3696,11 +5640,20 @@ fn handle_host_invite_modal_input(
             core.dirty = true;
             true
         }
+        KeyCode::Char('1') if online_runtime.host_invite_qr_only => {
+            online_runtime.room_code_revealed = !online_runtime.room_code_revealed;
+            core.status = if online_runtime.room_code_revealed {
+                String::from("Invite code shown")
+            } else {
+                String::from("Invite code hidden")
+            };
+            core.dirty = true;
+            true
+        }
         KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&'c') => {
-            match copy_invite_to_clipboard(&online_runtime.host_invite_code) {
+            match copy_text_to_clipboard(&online_runtime.host_invite_code) {
                 Ok(()) => {
-                    core.status =
-                        format!("Copied invite code: {}", online_runtime.host_invite_code);
+                    core.status = online_runtime.invite_copied_status();
                 }
                 Err(err) => {
                     core.status = format!("Clipboard copy failed: {err}");
@@ -3712,10 +5665,9 @@ fn handle_host_invite_modal_input(
         KeyCode::Enter => {
             match online_runtime.host_invite_button {
                 HostInviteModalButton::Copy => {
-                    match copy_invite_to_clipboard(&online_runtime.host_invite_code) {
+                    match copy_text_to_clipboard(&online_runtime.host_invite_code) {
                         Ok(()) => {
-                            core.status =
-                                format!("Copied invite code: {}", online_runtime.host_invite_code);
+                            core.status = online_runtime.invite_copied_status();
                         }
                         Err(err) => {
                             core.status = format!("Clipboard copy failed: {err}");
@@ -3766,78 +5718,255 @@ fn append_password_input(online_runtime: &mut OnlineRuntime, value: &str) {
     }
 }
 
-fn paste_invite_from_clipboard(online_runtime: &mut OnlineRuntime) -> anyhow::Result<()> {
-    let mut clipboard = Clipboard::new().context("clipboard unavailable")?;
-    let value = clipboard.get_text().context("clipboard text unavailable")?;
-    append_invite_input(online_runtime, &value);
-    Ok(())
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClipboardPaste {
+    Pasted,
+    Empty,
+    Unavailable,
 }
 
-fn copy_invite_to_clipboard(invite_code: &str) -> anyhow::Result<()> {
-    if let Ok(mut clipboard) = Clipboard::new()
-        && clipboard.set_text(invite_code.to_string()).is_ok()
-    {
-        return Ok(());
+fn paste_invite_from_clipboard(online_runtime: &mut OnlineRuntime) -> ClipboardPaste {
+    let Ok(mut clipboard) = Clipboard::new() else {
+        return ClipboardPaste::Unavailable;
+    };
+    match clipboard.get_text() {
+        Ok(value) if !value.trim().is_empty() => {
+            append_invite_input(online_runtime, &value);
+            ClipboardPaste::Pasted
+        }
+        _ => ClipboardPaste::Empty,
+    }
+}
+
+fn clipboard_paste_status(outcome: ClipboardPaste, input: &str) -> String {
+    match outcome {
+        ClipboardPaste::Pasted => format!("Pasted input: {input}"),
+        ClipboardPaste::Empty => String::from("Clipboard is empty; type the code instead"),
+        ClipboardPaste::Unavailable => {
+            String::from("No clipboard access (SSH/headless); type the code or use terminal paste")
+        }
     }
+}
 
-    copy_invite_via_osc52(invite_code)
+fn apply_clipboard_paste(core: &mut TuneCore, online_runtime: &mut OnlineRuntime) {
+    let outcome = paste_invite_from_clipboard(online_runtime);
+    core.status = clipboard_paste_status(outcome, &online_runtime.join_code_input);
+    online_runtime.join_prompt_button = JoinPromptButton::Input;
+    core.dirty = true;
 }
 
-fn copy_invite_via_osc52(invite_code: &str) -> anyhow::Result<()> {
-    let encoded = base64::engine::general_purpose::STANDARD.encode(invite_code.as_bytes());
-    let mut out = stdout();
-    if std::env::var_os("TMUX").is_some() {
-        write!(out, "\x1bPtmux;\x1b\x1b]52;c;{encoded}\x07\x1b\\")
-            .context("tmux osc52 write failed")?;
-    } else if std::env::var_os("STY").is_some() {
-        write!(out, "\x1bP\x1b]52;c;{encoded}\x07\x1b\\").context("screen osc52 write failed")?;
-    } else {
-        write!(out, "\x1b]52;c;{encoded}\x07\x1b]52;c;{encoded}\x1b\\")
-            .context("osc52 write failed")?;
+/// With `FolderEnterBehavior::Ask`, Enter on a folder asks whether to open or
+/// play it instead of acting right away.
+fn open_folder_enter_prompt(core: &TuneCore, panel: &mut ActionPanelState) -> bool {
+    if core.folder_enter_behavior != FolderEnterBehavior::Ask
+        || core.header_section != HeaderSection::Library
+    {
+        return false;
     }
-    std::io::Write::flush(&mut out).context("stdout flush failed")
+    let Some(entry) = core
+        .browser_entries
+        .get(core.selected_browser)
+        .filter(|entry| entry.kind == BrowserEntryKind::Folder)
+    else {
+        return false;
+    };
+    *panel = ActionPanelState::FolderEnter {
+        selected: 0,
+        folder: entry.path.clone(),
+    };
+    true
 }
 
-fn apply_online_nickname(core: &mut TuneCore, online_runtime: &mut OnlineRuntime, nickname: &str) {
-    let trimmed = nickname.trim();
-    if trimmed.is_empty() {
+fn copy_queue_as_text(core: &mut TuneCore) {
+    if core.queue.is_empty() {
+        core.status = String::from("Queue is empty");
+        core.dirty = true;
         return;
     }
-    let new_name = trimmed.to_string();
-    let previous = online_runtime.local_nickname.clone();
-    online_runtime.local_nickname = new_name.clone();
-    online_runtime.nickname_configured = true;
-    core.online_nickname = new_name.clone();
+    core.status = match copy_text_to_clipboard(&core.export_queue_text()) {
+        Ok(()) => format!("Copied {} queue track(s) as text", core.queue.len()),
+        Err(err) => format!("Clipboard copy failed: {err}"),
+    };
+    core.dirty = true;
+}
 
-    if let Some(session) = core.online.session.as_mut() {
-        if let Some(local) = session.local_participant_mut() {
-            local.nickname = new_name.clone();
+fn import_queue_from_clipboard(core: &mut TuneCore) {
+    let text = Clipboard::new()
+        .ok()
+        .and_then(|mut clipboard| clipboard.get_text().ok());
+    match text {
+        Some(text) if !text.trim().is_empty() => {
+            core.import_queue_text(&text);
         }
-        for item in &mut session.shared_queue {
-            if item
-                .owner_nickname
-                .as_deref()
-                .is_some_and(|owner| owner.eq_ignore_ascii_case(&previous))
-            {
-                item.owner_nickname = Some(new_name.clone());
-            }
+        Some(_) => {
+            core.status = String::from("Clipboard is empty");
+            core.dirty = true;
+        }
+        None => {
+            core.status = String::from("No clipboard access (SSH/headless)");
+            core.dirty = true;
         }
     }
+}
 
-    if let Some(network) = online_runtime.network.as_ref() {
-        network.send_local_action(NetworkLocalAction::SetNickname {
-            nickname: new_name.clone(),
-        });
-    }
-    core.status = format!("Online nickname: {new_name}");
+/// Cleans a typed or drag-and-drop pasted path: surrounding quotes, a
+/// `file://` prefix, and percent-escapes like `%20` are removed.
+fn parse_pasted_path(text: &str) -> PathBuf {
+    let trimmed = text.trim();
+    let unquoted = trimmed
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .or_else(|| {
+            trimmed
+                .strip_prefix('\'')
+                .and_then(|rest| rest.strip_suffix('\''))
+        })
+        .unwrap_or(trimmed);
+    config::file_uri_path(unquoted).unwrap_or_else(|| PathBuf::from(unquoted))
 }
 
-fn ensure_authority_port(authority: String) -> String {
-    if authority.parse::<std::net::SocketAddr>().is_ok() {
-        return authority;
+/// Asks for the file or folder at `input` to be read in the background and
+/// played without adding it to the library. Returns whether it was requested,
+/// setting a status explaining why not otherwise.
+fn play_path_input(core: &mut TuneCore, input: &str) -> bool {
+    if input.trim().is_empty() {
+        core.status = String::from("Type or paste a path to play");
+        return false;
     }
-    if authority.starts_with('[') {
-        return if authority.contains("]:") {
+    let path = parse_pasted_path(input);
+    if !path.exists() {
+        core.status = format!(
+            "Path not found: {}",
+            crate::config::sanitize_display_text(&path.display().to_string())
+        );
+        return false;
+    }
+    core.play_path_requested = Some(path);
+    true
+}
+
+/// Queues `tracks` read from the Play path `path` and returns the first one to
+/// play, or sets a status explaining why nothing can.
+fn play_scanned_path(
+    core: &mut TuneCore,
+    path: &Path,
+    tracks: Vec<crate::model::Track>,
+) -> Option<PathBuf> {
+    if tracks.is_empty() {
+        core.status = if path.is_dir() {
+            String::from("No audio files in that folder")
+        } else {
+            String::from("Not a playable audio file")
+        };
+        return None;
+    }
+    let label = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    core.play_transient_tracks(tracks, &label)
+}
+
+/// Absolute file path, or `Artist - Title` (just the title without an artist).
+/// Cue sheet tracks copy the path of the audio file they live in.
+fn now_playing_copy_text(core: &TuneCore, path: &Path, copy_path: bool) -> String {
+    if copy_path {
+        let file = library::media_file_path(path);
+        return fs::canonicalize(&file)
+            .unwrap_or(file)
+            .display()
+            .to_string();
+    }
+    let title = core.title_for_path(path).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("-"))
+    });
+    match core.artist_for_path(path) {
+        Some(artist) if !artist.trim().is_empty() => format!("{artist} - {title}"),
+        _ => title,
+    }
+}
+
+fn copy_now_playing(core: &mut TuneCore, audio: &dyn AudioEngine, copy_path: bool) {
+    let Some(path) = audio.current_track() else {
+        core.status = String::from("Nothing playing to copy");
+        core.dirty = true;
+        return;
+    };
+    let text = now_playing_copy_text(core, path, copy_path);
+    core.status = match copy_text_to_clipboard(&text) {
+        Ok(()) => format!("Copied {text}"),
+        Err(err) => format!("Clipboard copy failed: {err}"),
+    };
+    core.dirty = true;
+}
+
+fn copy_text_to_clipboard(text: &str) -> anyhow::Result<()> {
+    if let Ok(mut clipboard) = Clipboard::new()
+        && clipboard.set_text(text.to_string()).is_ok()
+    {
+        return Ok(());
+    }
+
+    copy_text_via_osc52(text)
+}
+
+fn copy_text_via_osc52(text: &str) -> anyhow::Result<()> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text.as_bytes());
+    let mut out = stdout();
+    if std::env::var_os("TMUX").is_some() {
+        write!(out, "\x1bPtmux;\x1b\x1b]52;c;{encoded}\x07\x1b\\")
+            .context("tmux osc52 write failed")?;
+    } else if std::env::var_os("STY").is_some() {
+        write!(out, "\x1bP\x1b]52;c;{encoded}\x07\x1b\\").context("screen osc52 write failed")?;
+    } else {
+        write!(out, "\x1b]52;c;{encoded}\x07\x1b]52;c;{encoded}\x1b\\")
+            .context("osc52 write failed")?;
+    }
+    std::io::Write::flush(&mut out).context("stdout flush failed")
+}
+
+fn apply_online_nickname(core: &mut TuneCore, online_runtime: &mut OnlineRuntime, nickname: &str) {
+    let trimmed = nickname.trim();
+    if trimmed.is_empty() {
+        return;
+    }
+    let new_name = trimmed.to_string();
+    let previous = online_runtime.local_nickname.clone();
+    online_runtime.local_nickname = new_name.clone();
+    online_runtime.nickname_configured = true;
+    core.online_nickname = new_name.clone();
+
+    if let Some(session) = core.online.session.as_mut() {
+        if let Some(local) = session.local_participant_mut() {
+            local.nickname = new_name.clone();
+        }
+        for item in &mut session.shared_queue {
+            if item
+                .owner_nickname
+                .as_deref()
+                .is_some_and(|owner| owner.eq_ignore_ascii_case(&previous))
+            {
+                item.owner_nickname = Some(new_name.clone());
+            }
+        }
+    }
+
+    if let Some(network) = online_runtime.network.as_ref() {
+        network.send_local_action(NetworkLocalAction::SetNickname {
+            nickname: new_name.clone(),
+        });
+    }
+    core.status = format!("Online nickname: {new_name}");
+}
+
+fn ensure_authority_port(authority: String) -> String {
+    if authority.parse::<std::net::SocketAddr>().is_ok() {
+        return authority;
+    }
+    if authority.starts_with('[') {
+        return if authority.contains("]:") {
             authority
         } else {
             format!("{authority}:{ONLINE_DEFAULT_HOME_SERVER_PORT}")
@@ -3911,15 +6040,16 @@ fn filtered_room_entries(
 }
 
 fn rewrite_room_server_addr_host(home_server_addr: &str, room_server_addr: &str) -> Option<String> {
+    let (transport, room_server_addr) = RoomTransport::parse_room_addr(room_server_addr);
     let home_host = home_server_addr.trim().rsplit_once(':')?.0.trim();
-    let room_port = room_server_addr.trim().rsplit_once(':')?.1.trim();
+    let room_port = room_server_addr.rsplit_once(':')?.1.trim();
     if home_host.is_empty() || room_port.is_empty() {
         return None;
     }
     if home_host.contains(':') && !home_host.starts_with('[') {
-        Some(format!("[{home_host}]:{room_port}"))
+        Some(transport.room_addr(&format!("[{home_host}]:{room_port}")))
     } else {
-        Some(format!("{home_host}:{room_port}"))
+        Some(transport.room_addr(&format!("{home_host}:{room_port}")))
     }
 }
 
@@ -3991,6 +6121,126 @@ fn refresh_room_directory(core: &mut TuneCore, online_runtime: &mut OnlineRuntim
     }
 }
 
+/// Splits a `--join` value into server and room. A bare room name stays on the
+/// default home server.
+fn startup_join_target(target: &str, default_server_addr: &str) -> Result<(String, String)> {
+    let target = target.trim();
+    if target.is_empty() {
+        anyhow::bail!("empty room");
+    }
+    if !target.contains('/') {
+        return Ok((default_server_addr.to_string(), target.to_string()));
+    }
+    let parsed = parse_home_link(target)?;
+    let room_name = parsed.room_name.context("link has no /room/NAME part")?;
+    Ok((parsed.server_addr, room_name))
+}
+
+/// Startup room whose server is being checked off the UI thread. `Ok(true)`
+/// means the room to join is locked.
+struct StartupRoomTask {
+    join: StartupJoin,
+    server_addr: String,
+    room_name: String,
+    rx: Receiver<Result<bool>>,
+}
+
+/// Checks the `--join` or `--host-room` server in the background so the first
+/// frame is not held up by the network.
+fn start_startup_room(
+    core: &mut TuneCore,
+    online_runtime: &OnlineRuntime,
+    join: &StartupJoin,
+) -> Option<StartupRoomTask> {
+    core.set_header_section(HeaderSection::Online);
+    core.dirty = true;
+    let flag = if join.host { "--host-room" } else { "--join" };
+    let (server_addr, room_name) =
+        match startup_join_target(&join.target, &online_runtime.home_server_addr) {
+            Ok(target) => target,
+            Err(err) => {
+                core.status = format!("Invalid {flag}: {err}");
+                return None;
+            }
+        };
+    core.status = if join.host {
+        format!("Creating room {room_name}...")
+    } else {
+        format!("Joining room {room_name}...")
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let worker_server = server_addr.clone();
+    let worker_room = room_name.clone();
+    let host = join.host;
+    std::thread::spawn(move || {
+        let checked = if host {
+            verify_home_server(&worker_server).map(|()| false)
+        } else {
+            resolve_home_room(&worker_server, &worker_room).map(|room| room.locked)
+        };
+        let _ = tx.send(checked);
+    });
+    Some(StartupRoomTask {
+        join: join.clone(),
+        server_addr,
+        room_name,
+        rx,
+    })
+}
+
+fn poll_startup_room(
+    core: &mut TuneCore,
+    online_runtime: &mut OnlineRuntime,
+    task: &mut Option<StartupRoomTask>,
+) {
+    let Some(active) = task.as_ref() else {
+        return;
+    };
+    let checked = match active.rx.try_recv() {
+        Ok(checked) => checked,
+        Err(mpsc::TryRecvError::Empty) => return,
+        Err(mpsc::TryRecvError::Disconnected) => Err(anyhow::anyhow!("lookup worker stopped")),
+    };
+    let Some(StartupRoomTask {
+        join,
+        server_addr,
+        room_name,
+        ..
+    }) = task.take()
+    else {
+        return;
+    };
+    core.dirty = true;
+    let password = join.password.unwrap_or_default();
+    match checked {
+        Err(err) if join.host => {
+            core.status = format!("Home server unavailable: {err}");
+        }
+        Err(err) => {
+            core.status = format!("Room not found or unavailable: {err}");
+        }
+        Ok(_) if join.host => {
+            online_runtime.pending_join_server_addr = server_addr;
+            online_runtime.pending_join_room_name = Some(room_name);
+            online_runtime.host_max_connections_input = String::from("8");
+            start_host_with_password(core, online_runtime, &password);
+        }
+        Ok(true) if password.is_empty() => {
+            online_runtime.pending_join_server_addr = server_addr;
+            online_runtime.pending_join_room_name = Some(room_name);
+            online_runtime.password_prompt_active = true;
+            online_runtime.password_prompt_mode = OnlinePasswordPromptMode::Join;
+            online_runtime.password_prompt_focus = PasswordPromptFocus::PasswordInput;
+            online_runtime.password_input.clear();
+            core.status = String::from("Enter room password, then Enter");
+        }
+        Ok(_) => {
+            join_home_room(core, online_runtime, &server_addr, &room_name, &password);
+        }
+    }
+}
+
 fn join_home_room(
     core: &mut TuneCore,
     online_runtime: &mut OnlineRuntime,
@@ -4058,12 +6308,23 @@ fn join_home_room(
                 .locked
                 .then(|| password.trim().to_string())
                 .filter(|value| !value.is_empty());
+            core.last_online_join = Some(LastOnlineJoin {
+                server_addr: server_addr.clone(),
+                room_name: resolved.room_name.clone(),
+                password: join_password,
+                sealed_password: None,
+            });
             online_runtime.home_server_addr = server_addr;
             online_runtime.home_server_connected = true;
             online_runtime.network = Some(network);
+            let sealed = RoomTransport::parse_room_addr(&resolved.room_server_addr).0
+                == RoomTransport::Sealed;
             core.status = format!(
-                "Connected {} ({}/{})",
-                resolved.room_name, resolved.current_connections, resolved.max_connections
+                "Connected {} ({}/{}{})",
+                resolved.room_name,
+                resolved.current_connections,
+                resolved.max_connections,
+                if sealed { ", encrypted" } else { "" }
             );
             core.dirty = true;
             true
@@ -4248,17 +6509,24 @@ fn drain_online_network_events(
                 requested_path,
                 local_temp_path,
                 format,
+                duration,
             } => {
+                online_runtime.stream_progress = None;
                 online_runtime
                     .cache_streamed_track(requested_path.clone(), local_temp_path.clone());
                 if online_runtime.pending_stream_path.as_ref() == Some(&requested_path) {
                     match audio.play(&local_temp_path) {
                         Ok(()) => {
+                            if let Some(duration) = duration {
+                                audio.announce_stream_duration(duration);
+                            }
                             online_runtime.remote_logical_track = Some(requested_path.clone());
                             let format_label = match format {
-                                StreamTrackFormat::LosslessOriginal => "Lossless original",
-                                StreamTrackFormat::BalancedOpus160kVbrStereo => {
-                                    "Balanced Opus 160k VBR stereo"
+                                StreamTrackFormat::LosslessOriginal => {
+                                    String::from("Lossless original")
+                                }
+                                StreamTrackFormat::OpusVbrStereo { bitrate_kbps } => {
+                                    format!("Opus {bitrate_kbps}k VBR stereo")
                                 }
                             };
                             core.status = format!(
@@ -4282,6 +6550,20 @@ fn drain_online_network_events(
                     online_runtime.pending_stream_path = None;
                 }
             }
+            NetworkEvent::StreamTrackProgress {
+                requested_path,
+                received_bytes,
+                total_bytes,
+            } => {
+                if online_runtime.pending_stream_path.as_ref() == Some(&requested_path) {
+                    online_runtime.stream_progress = Some(StreamProgress {
+                        requested_path,
+                        received_bytes,
+                        total_bytes,
+                    });
+                    core.dirty = true;
+                }
+            }
             NetworkEvent::SessionSync(session) => {
                 let mut session = *session;
                 let previous_quality = core.online.session.as_ref().map(|entry| entry.quality);
@@ -4390,7 +6672,9 @@ fn handle_stream_quality_change(
 fn stream_quality_label(quality: StreamQuality) -> &'static str {
     match quality {
         StreamQuality::Lossless => "Lossless",
+        StreamQuality::High => "High Opus 320k",
         StreamQuality::Balanced => "Balanced Opus 160k",
+        StreamQuality::Low => "Low Opus 96k",
     }
 }
 
@@ -4452,6 +6736,7 @@ fn normalize_local_online_participant(
         ping_ms: 30,
         manual_extra_delay_ms: 0,
         auto_ping_delay: true,
+        last_drift_ms: None,
     });
 }
 
@@ -4461,6 +6746,22 @@ fn apply_remote_transport(
     online_runtime: &mut OnlineRuntime,
     command: &TransportCommand,
 ) {
+    if let Some(log) = online_runtime.sync_debug_log.as_mut() {
+        match command {
+            TransportCommand::StopPlayback => log.record("event=stop"),
+            TransportCommand::SetPaused { paused } => {
+                log.record(&format!("event=set_paused paused={paused}"));
+            }
+            TransportCommand::PlayTrack { path, .. } => {
+                log.record(&format!(
+                    "event=play_track path={:?}",
+                    path.display().to_string()
+                ));
+            }
+            // Logged below, once the correction is decided.
+            TransportCommand::SetPlaybackState { .. } => {}
+        }
+    }
     match command {
         TransportCommand::StopPlayback => {
             audio.stop();
@@ -4538,15 +6839,32 @@ fn apply_remote_transport(
                     .unwrap_or(0)
             };
             let target_ms = (*position_ms as i64).saturating_add(remote_delay_ms);
-            let drift_ms = (target_ms - local_ms).abs();
+            let drift_ms = target_ms - local_ms;
             let seek_threshold = if *paused {
                 ONLINE_SYNC_CORRECTION_THRESHOLD_PAUSED_MS
             } else {
                 i64::from(core.online_sync_correction_threshold_ms)
             };
-            if drift_ms >= seek_threshold {
+            let seeked = drift_ms.abs() >= seek_threshold;
+            if seeked {
                 let _ = audio.seek_to(Duration::from_millis(target_ms as u64));
             }
+            if let Some(log) = online_runtime.sync_debug_log.as_mut() {
+                log.record(
+                    &SyncDecision {
+                        path,
+                        remote_ms: *position_ms,
+                        delay_ms: remote_delay_ms,
+                        target_ms,
+                        local_ms,
+                        drift_ms,
+                        threshold_ms: seek_threshold,
+                        paused: *paused,
+                        seeked,
+                    }
+                    .log_line(),
+                );
+            }
 
             if *paused {
                 audio.pause();
@@ -4555,15 +6873,32 @@ fn apply_remote_transport(
             }
 
             core.current_queue_index = core.queue_position_for_path(path);
+            let drift_ms = drift_ms.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32;
             if let Some(session) = core.online.session.as_mut() {
-                session.last_sync_drift_ms = drift_ms.min(i64::from(i32::MAX)) as i32;
+                session.last_sync_drift_ms = drift_ms;
             }
-            core.status = format!("Remote sync drift {}ms", drift_ms);
+            report_sync_drift(online_runtime, drift_ms);
+            core.status = format!("Remote sync drift {}ms", drift_ms.unsigned_abs());
             core.dirty = true;
         }
     }
 }
 
+/// Shares this listener's drift with the room, at most every
+/// `ONLINE_DRIFT_REPORT_INTERVAL`, so the host can see who is out of sync.
+fn report_sync_drift(online_runtime: &mut OnlineRuntime, drift_ms: i32) {
+    if online_runtime
+        .last_drift_report_at
+        .is_some_and(|sent| sent.elapsed() < ONLINE_DRIFT_REPORT_INTERVAL)
+    {
+        return;
+    }
+    if let Some(network) = online_runtime.network.as_ref() {
+        network.send_local_action(NetworkLocalAction::ReportDrift(drift_ms));
+        online_runtime.last_drift_report_at = Some(Instant::now());
+    }
+}
+
 fn ensure_remote_track(
     core: &mut TuneCore,
     audio: &mut dyn AudioEngine,
@@ -4662,6 +6997,60 @@ fn preferred_stream_source(
         .map(str::to_string)
 }
 
+/// ListenBrainz scrobbling, when a token is set in `state.json`.
+fn start_scrobbling(core: &TuneCore) -> Option<crate::scrobble::ScrobbleQueue> {
+    let token = core
+        .listenbrainz_token
+        .as_deref()
+        .map(str::trim)
+        .filter(|token| !token.is_empty())?;
+    let queue_path = crate::config::scrobble_queue_path().ok()?;
+    Some(crate::scrobble::ScrobbleQueue::start(
+        Box::new(crate::scrobble::ListenBrainz::new(token)),
+        queue_path,
+    ))
+}
+
+/// Top-song rows the Stats keys act on. Queried again only when the listen
+/// history, the filters or the row count change, not on every key press.
+#[derive(Default)]
+struct StatsRowsCache {
+    key: Option<(usize, u64, crate::stats::StatsQuery, u8)>,
+    rows: Vec<crate::stats::TrackStatsRow>,
+}
+
+impl StatsRowsCache {
+    fn rows(
+        &mut self,
+        core: &TuneCore,
+        stats_store: &StatsStore,
+    ) -> &[crate::stats::TrackStatsRow] {
+        let key = (
+            stats_store.events.len(),
+            stats_store.generation(),
+            stats_query_for_core(core),
+            core.stats_top_songs_count,
+        );
+        if self.key.as_ref() != Some(&key) {
+            let mut rows = stats_store.query(&key.2, stats::now_epoch_seconds()).rows;
+            rows.truncate(usize::from(core.stats_top_songs_count.max(1)));
+            self.rows = rows;
+            self.key = Some(key);
+        }
+        &self.rows
+    }
+}
+
+fn stats_query_for_core(core: &TuneCore) -> crate::stats::StatsQuery {
+    crate::stats::StatsQuery {
+        range: core.stats_range,
+        sort: core.stats_sort,
+        artist_filter: core.stats_artist_filter.clone(),
+        album_filter: core.stats_album_filter.clone(),
+        search: core.stats_search.clone(),
+    }
+}
+
 fn stats_scroll_down(core: &mut TuneCore) {
     core.stats_scroll = core.stats_scroll.saturating_add(1);
     core.dirty = true;
@@ -4685,14 +7074,19 @@ fn move_stats_focus_or_value(core: &mut TuneCore, forward: bool) -> bool {
             true
         }
         StatsFilterFocus::Sort(index) => {
-            let next = 1_u8.saturating_sub(index.min(1));
+            let next = if forward {
+                (index + 1) % STATS_SORT_OPTION_COUNT
+            } else {
+                (index + STATS_SORT_OPTION_COUNT - 1) % STATS_SORT_OPTION_COUNT
+            };
             core.stats_focus = StatsFilterFocus::Sort(next);
             set_stats_sort_by_index(core, next);
             true
         }
-        StatsFilterFocus::Artist | StatsFilterFocus::Album | StatsFilterFocus::Search => {
-            move_stats_row(core, forward)
-        }
+        StatsFilterFocus::Artist
+        | StatsFilterFocus::Album
+        | StatsFilterFocus::Search
+        | StatsFilterFocus::Row(_) => move_stats_row(core, forward),
     }
 }
 
@@ -4726,7 +7120,7 @@ fn move_stats_row(core: &mut TuneCore, forward: bool) -> bool {
                 StatsFilterFocus::Artist
             }
         }
-        StatsFilterFocus::Search => {
+        StatsFilterFocus::Search | StatsFilterFocus::Row(_) => {
             if forward {
                 StatsFilterFocus::Range(core_range_index(core.stats_range))
             } else {
@@ -4749,10 +7143,10 @@ fn set_stats_range_by_index(core: &mut TuneCore, index: u8) {
 }
 
 fn set_stats_sort_by_index(core: &mut TuneCore, index: u8) {
-    core.stats_sort = if index == 0 {
-        crate::stats::StatsSort::ListenTime
-    } else {
-        crate::stats::StatsSort::Plays
+    core.stats_sort = match index {
+        0 => crate::stats::StatsSort::ListenTime,
+        1 => crate::stats::StatsSort::Plays,
+        _ => crate::stats::StatsSort::SkipRate,
     };
     core.dirty = true;
 }
@@ -4770,29 +7164,10 @@ fn core_sort_index(sort: crate::stats::StatsSort) -> u8 {
     match sort {
         crate::stats::StatsSort::ListenTime => 0,
         crate::stats::StatsSort::Plays => 1,
+        crate::stats::StatsSort::SkipRate => 2,
     }
 }
 
-fn should_trigger_crossfade_advance(audio: &dyn AudioEngine) -> bool {
-    let crossfade_seconds = audio.crossfade_seconds();
-    if crossfade_seconds == 0 {
-        return false;
-    }
-
-    let Some(position) = audio.position() else {
-        return false;
-    };
-    let Some(duration) = audio.duration() else {
-        return false;
-    };
-    if duration <= position {
-        return false;
-    }
-
-    let remaining = duration.saturating_sub(position);
-    remaining <= Duration::from_secs(u64::from(crossfade_seconds))
-}
-
 fn scrub_current_track_by_delta(audio: &mut dyn AudioEngine, delta_seconds: i64) -> Result<()> {
     if delta_seconds == 0 {
         return Ok(());
@@ -4815,13 +7190,101 @@ fn scrub_current_track_by_delta(audio: &mut dyn AudioEngine, delta_seconds: i64)
     audio.seek_to(target)
 }
 
-fn concise_audio_error(err: &anyhow::Error) -> String {
-    let message = err.to_string();
-    let lower = message.to_ascii_lowercase();
-    if lower.contains("device") && (lower.contains("no longer") || lower.contains("unavailable")) {
-        return String::from("Audio device unavailable. Use / -> Audio driver settings -> Reload");
+/// Ctrl+t opens seek-to-timestamp everywhere except the lyrics editor, where
+/// it stamps the selected line instead.
+fn seek_to_timestamp_shortcut(core: &TuneCore, key: &KeyEvent) -> bool {
+    key_event_matches_ctrl_char(key, 't')
+        && !(core.header_section == HeaderSection::Lyrics
+            && core.lyrics_mode == LyricsMode::Edit
+            && !core.lyrics_missing_prompt)
+}
+
+fn open_seek_to_timestamp(
+    core: &mut TuneCore,
+    audio: &dyn AudioEngine,
+    panel: &mut ActionPanelState,
+) {
+    if local_playback_locked_by_host_only(core) {
+        core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
+    } else if audio.current_track().is_none() {
+        core.status = String::from("Nothing playing to seek");
+    } else {
+        *panel = ActionPanelState::SeekToTimestamp {
+            selected: 0,
+            input: String::new(),
+        };
+    }
+    core.dirty = true;
+}
+
+/// Reads `mm:ss` or `h:mm:ss`. Minutes may run past 59 in the short form so
+/// long mixes can be typed as `75:30`.
+fn parse_timestamp(input: &str) -> Option<Duration> {
+    let parts = input
+        .trim()
+        .split(':')
+        .map(|part| {
+            (!part.is_empty() && part.len() <= 3)
+                .then(|| part.parse::<u64>().ok())
+                .flatten()
+        })
+        .collect::<Option<Vec<u64>>>()?;
+    let seconds = match parts.as_slice() {
+        [minutes, seconds] if *seconds < 60 => minutes * 60 + seconds,
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => {
+            hours * 3_600 + minutes * 60 + seconds
+        }
+        _ => return None,
+    };
+    Some(Duration::from_secs(seconds))
+}
+
+fn format_timestamp(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3_600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3_600,
+            (seconds % 3_600) / 60,
+            seconds % 60
+        )
+    } else {
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Seeks to a typed timestamp, clamped to the track length. Returns whether
+/// the seek happened; otherwise `core.status` says why.
+fn seek_to_timestamp_input(core: &mut TuneCore, audio: &mut dyn AudioEngine, input: &str) -> bool {
+    if audio.current_track().is_none() {
+        core.status = String::from("Nothing playing to seek");
+        return false;
+    }
+    let Some(mut target) = parse_timestamp(input) else {
+        core.status = if input.trim().is_empty() {
+            String::from("Type a time as mm:ss or h:mm:ss")
+        } else {
+            format!("Not a time: {input} (use mm:ss or h:mm:ss)")
+        };
+        return false;
+    };
+    let mut clamped = false;
+    if let Some(duration) = audio.duration()
+        && target > duration
+    {
+        target = duration;
+        clamped = true;
+    }
+    if let Err(err) = audio.seek_to(target) {
+        core.status = format!("Seek failed: {err}");
+        return false;
     }
-    format!("Playback failed: {message}")
+    core.status = if clamped {
+        format!("Seek {} (end of track)", format_timestamp(target))
+    } else {
+        format!("Seek {}", format_timestamp(target))
+    };
+    true
 }
 
 fn save_state_with_audio(core: &mut TuneCore, audio: &dyn AudioEngine) -> Result<()> {
@@ -4853,6 +7316,7 @@ fn persisted_state_with_audio(
     let mut state = core.persisted_state();
     state.selected_output_device = audio.selected_output_device();
     state.saved_volume = audio.volume().clamp(0.0, MAX_VOLUME);
+    state.saved_muted = audio.is_muted();
     state
 }
 
@@ -4860,6 +7324,27 @@ fn apply_saved_volume(audio: &mut dyn AudioEngine, saved_volume: f32) {
     audio.set_volume(saved_volume.clamp(0.0, MAX_VOLUME));
 }
 
+/// Restores a mute left on at quit, saying so since nothing would play.
+fn apply_saved_mute(core: &mut TuneCore, audio: &mut dyn AudioEngine, saved_muted: bool) {
+    if saved_muted {
+        audio.mute();
+        core.status = String::from("Muted");
+        core.dirty = true;
+    }
+}
+
+fn toggle_mute(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    if audio.is_muted() {
+        audio.unmute();
+        core.status = format!("Unmuted at {}%", (audio.volume() * 100.0).round() as u16);
+    } else {
+        audio.mute();
+        core.status = String::from("Muted");
+    }
+    core.dirty = true;
+    auto_save_state(core, &*audio);
+}
+
 fn apply_saved_audio_output(
     core: &mut TuneCore,
     audio: &mut dyn AudioEngine,
@@ -4886,16 +7371,162 @@ fn apply_saved_audio_output(
     }
 }
 
-fn handle_mouse(core: &mut TuneCore, mouse: MouseEvent, library_rect: ratatui::prelude::Rect) {
-    let inside_library = point_in_rect(mouse.column, mouse.row, library_rect);
-    match mouse.kind {
-        MouseEventKind::ScrollDown if inside_library => match core.header_section {
-            HeaderSection::Library => core.select_next(),
-            HeaderSection::Stats => {
-                stats_scroll_down(core);
-                core.stats_focus = StatsFilterFocus::Search;
+/// Startup warning for when no output could be opened and playback falls back
+/// to the silent null engine.
+fn silent_audio_status(init_error: Option<&anyhow::Error>) -> String {
+    match init_error {
+        Some(err) => format!("Running without audio output ({err}). Playback is silent"),
+        None => String::from("Running without audio output. Playback is silent"),
+    }
+}
+
+/// Recovery shortcut for a wedged sink: reopens the output and puts volume and
+/// the playback position back where they were.
+fn reinitialize_audio(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    let volume = audio.volume();
+    let position = audio.position();
+    match audio.reload_driver() {
+        Ok(()) => {
+            audio.set_volume(volume);
+            if let Some(position) = position
+                && audio.current_track().is_some()
+            {
+                let _ = audio.seek_to(position);
             }
-            HeaderSection::Lyrics | HeaderSection::Online => {}
+            core.status = String::from("Reinitialized display and audio");
+        }
+        Err(err) => {
+            core.status = format!(
+                "Reinitialized display. Audio reload failed: {}",
+                concise_audio_error(&err)
+            );
+        }
+    }
+    core.dirty = true;
+}
+
+fn wall_clock_gap_suggests_resume(previous: SystemTime, now: SystemTime) -> bool {
+    now.duration_since(previous)
+        .is_ok_and(|gap| gap >= RESUME_WALL_CLOCK_GAP)
+}
+
+fn recover_audio_output_after_resume(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    // Without a sound device there is nothing to reopen, and the saved choice
+    // must survive for the next start that has one.
+    if audio.is_silent() {
+        return;
+    }
+    let preferred_output = audio.selected_output_device();
+    let position = audio.position();
+    let output_label = preferred_output.as_deref().unwrap_or("default output");
+
+    match audio.set_output_device(preferred_output.as_deref()) {
+        Ok(()) => {
+            core.status = format!("Resumed from sleep. Audio output restored: {output_label}");
+        }
+        Err(err) => {
+            if audio.set_output_device(None).is_err() {
+                core.status = format!(
+                    "Resumed from sleep. Audio output lost ({}). / -> Audio driver settings -> Reload",
+                    concise_audio_error(&err)
+                );
+                core.dirty = true;
+                return;
+            }
+            core.status =
+                format!("Resumed from sleep. '{output_label}' unavailable, using default output");
+        }
+    }
+
+    if let Some(position) = position
+        && audio.current_track().is_some()
+    {
+        let _ = audio.seek_to(position);
+    }
+    core.dirty = true;
+}
+
+/// Reopens the output when its device went away, such as an unplugged USB DAC,
+/// instead of playing on into nothing. A chosen device missing from the device
+/// list switches to the default output. Devices are only enumerated after the
+/// stream reported a lost device, so a healthy output costs one flag read.
+fn recover_lost_output_device(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    if audio.is_silent() || !audio.device_lost() {
+        return;
+    }
+    let selected = audio.selected_output_device();
+    let selected_missing = selected.as_deref().is_some_and(|name| {
+        !audio
+            .available_outputs()
+            .iter()
+            .any(|output| output_device_names_match(output, name))
+    });
+
+    let position = audio.position();
+    let output_label = selected.as_deref().unwrap_or("default output");
+    if !selected_missing && audio.reload_driver().is_ok() {
+        core.status = format!("Audio output lost; reopened {output_label}");
+    } else {
+        match audio.set_output_device(None) {
+            Ok(()) => {
+                core.status = format!(
+                    "'{output_label}' disconnected. Using default output / -> Audio driver settings"
+                );
+            }
+            Err(err) => {
+                core.status = format!(
+                    "'{output_label}' disconnected ({}). / -> Audio driver settings -> Reload",
+                    concise_audio_error(&err)
+                );
+                core.dirty = true;
+                return;
+            }
+        }
+    }
+
+    if let Some(position) = position
+        && audio.current_track().is_some()
+    {
+        let _ = audio.seek_to(position);
+    }
+    core.dirty = true;
+}
+
+/// Clamps selections and scroll offsets after the terminal changes size and
+/// returns the library rect for the new layout.
+fn handle_terminal_resize(core: &mut TuneCore, width: u16, height: u16) -> ratatui::prelude::Rect {
+    core.selected_browser = core
+        .selected_browser
+        .min(core.browser_entries.len().saturating_sub(1));
+    let shared_queue_len = core
+        .online
+        .session
+        .as_ref()
+        .map_or(0, |session| session.shared_queue.len());
+    core.online_queue_scroll = core
+        .online_queue_scroll
+        .min(shared_queue_len.saturating_sub(1));
+    core.dirty = true;
+
+    let area = ratatui::prelude::Rect::new(0, 0, width, height);
+    crate::ui::library_rect(
+        area,
+        crate::ui::compact_layout_active(area, core.compact_layout),
+        core.header_section,
+    )
+}
+
+fn handle_mouse(core: &mut TuneCore, mouse: MouseEvent, library_rect: ratatui::prelude::Rect) {
+    let inside_library = point_in_rect(mouse.column, mouse.row, library_rect);
+    match mouse.kind {
+        MouseEventKind::ScrollDown if inside_library => match core.header_section {
+            HeaderSection::Library => core.select_next(),
+            HeaderSection::Stats => {
+                stats_scroll_down(core);
+                core.stats_focus = StatsFilterFocus::Search;
+            }
+            HeaderSection::Online => scroll_online_shared_queue(core, KeyCode::Down),
+            HeaderSection::Lyrics => {}
         },
         MouseEventKind::ScrollUp if inside_library => match core.header_section {
             HeaderSection::Library => core.select_prev(),
@@ -4903,7 +7534,8 @@ fn handle_mouse(core: &mut TuneCore, mouse: MouseEvent, library_rect: ratatui::p
                 stats_scroll_up(core);
                 core.stats_focus = StatsFilterFocus::Search;
             }
-            HeaderSection::Lyrics | HeaderSection::Online => {}
+            HeaderSection::Online => scroll_online_shared_queue(core, KeyCode::Up),
+            HeaderSection::Lyrics => {}
         },
         _ => {}
     }
@@ -4925,6 +7557,43 @@ struct DurationLookupTask {
     rx: Receiver<Option<u32>>,
 }
 
+const EXTRA_TAGS_BATCH: usize = 200;
+
+struct ExtraTagsLookupRuntime {
+    active: Option<ExtraTagsLookupTask>,
+}
+
+struct ExtraTagsLookupTask {
+    rx: Receiver<Vec<(PathBuf, library::ExtraTags)>>,
+}
+
+struct CoverArtDownloadRuntime {
+    active: Option<CoverArtDownloadTask>,
+}
+
+struct CoverArtDownloadTask {
+    request: CoverArtDownloadRequest,
+    rx: Receiver<Result<Option<Vec<u8>>>>,
+}
+
+struct LyricsFetchRuntime {
+    active: Option<LyricsFetchTask>,
+}
+
+struct DuplicateScanRuntime {
+    active: Option<Receiver<DuplicateScanResult>>,
+}
+
+struct DuplicateScanResult {
+    clusters: Vec<Vec<PathBuf>>,
+    probed: Vec<(PathBuf, Option<u32>)>,
+}
+
+struct LyricsFetchTask {
+    request: LyricsFetchRequest,
+    rx: Receiver<Result<Option<LyricsDocument>>>,
+}
+
 #[allow(clippy::too_many_arguments)]
 fn handle_mouse_with_panel(
     core: &mut TuneCore,
@@ -5180,10 +7849,11 @@ fn apply_left_click(
             }
             core.dirty = true;
         }
+        HitTarget::ShowRoomQr => open_room_invite_qr(core, online_runtime),
         HitTarget::CopyRoomCode => {
             if let Some(session) = core.online.session.as_ref() {
                 let field = active_online_room_field(online_runtime, session);
-                match copy_invite_to_clipboard(&field.value) {
+                match copy_text_to_clipboard(&field.value) {
                     Ok(()) => {
                         core.status = format!("Copied {}", online_room_field_copy_name(field.kind));
                     }
@@ -5243,6 +7913,7 @@ fn apply_left_click(
                 VOLUME_STEP_COARSE
             };
             let next = (audio.volume() - step).clamp(0.0, MAX_VOLUME);
+            audio.unmute();
             audio.set_volume(next);
             core.status = format!("Volume: {}%", (next * 100.0).round() as u16);
             core.dirty = true;
@@ -5254,6 +7925,7 @@ fn apply_left_click(
                 VOLUME_STEP_COARSE
             };
             let next = (audio.volume() + step).clamp(0.0, MAX_VOLUME);
+            audio.unmute();
             audio.set_volume(next);
             core.status = format!("Volume: {}%", (next * 100.0).round() as u16);
             core.dirty = true;
@@ -5270,6 +7942,11 @@ fn apply_left_click(
                 core.dirty = true;
             }
         }
+        HitTarget::LibraryCrumb(depth) => {
+            if core.header_section == HeaderSection::Library {
+                core.navigate_to_breadcrumb(depth);
+            }
+        }
         HitTarget::LibraryRow(idx) => {
             if core.header_section != HeaderSection::Library {
                 return;
@@ -5325,7 +8002,7 @@ fn apply_left_click(
             core.dirty = true;
         }
         HitTarget::StatsSort(index) => {
-            let idx = (index % 2) as u8;
+            let idx = index.min(usize::from(STATS_SORT_OPTION_COUNT - 1)) as u8;
             core.stats_focus = crate::core::StatsFilterFocus::Sort(idx);
             set_stats_sort_by_index(core, idx);
             core.status = format!("Sort: {}", core.stats_sort.label());
@@ -5358,16 +8035,7 @@ fn apply_left_click(
             handle_online_inline_input(core, audio, synthetic, online_runtime);
         }
         HitTarget::JoinPromptPaste => {
-            match paste_invite_from_clipboard(online_runtime) {
-                Ok(()) => {
-                    core.status = format!("Pasted input: {}", online_runtime.join_code_input);
-                }
-                Err(err) => {
-                    core.status = format!("Clipboard paste failed: {err}");
-                }
-            }
-            online_runtime.join_prompt_button = JoinPromptButton::Input;
-            core.dirty = true;
+            apply_clipboard_paste(core, online_runtime);
         }
         HitTarget::RoomDirectorySearch => {
             online_runtime.join_directory_focus = RoomDirectoryFocus::Search;
@@ -5462,6 +8130,7 @@ fn set_volume_from_x(
         .min(bar_width.saturating_sub(1));
     let ratio = (offset as f32 + 0.5) / bar_width as f32;
     let next = (ratio * 1.0).clamp(0.0, 1.0);
+    audio.unmute();
     audio.set_volume(next);
     core.status = format!("Volume: {}%", (next * 100.0).round() as u16);
     core.dirty = true;
@@ -5478,6 +8147,11 @@ fn seek_from_x(
     if bar_width == 0 {
         return;
     }
+    if audio.seek_pending() {
+        core.status = String::from("Seek pending: stream is still downloading");
+        core.dirty = true;
+        return;
+    }
     let Some(duration) = audio.duration() else {
         return;
     };
@@ -5526,15 +8200,30 @@ fn set_action_panel_selected(panel: &mut ActionPanelState, idx: usize) {
         | ActionPanelState::PlaylistRemove { selected }
         | ActionPanelState::AudioSettings { selected }
         | ActionPanelState::AudioOutput { selected }
+        | ActionPanelState::OutputShaping { selected }
         | ActionPanelState::PlaybackSettings { selected }
         | ActionPanelState::OnlineDelaySettings { selected }
+        | ActionPanelState::Equalizer { selected }
         | ActionPanelState::ThemeSettings { selected }
         | ActionPanelState::OnlineNickname { selected, .. }
         | ActionPanelState::LyricsImportTxt { selected, .. }
         | ActionPanelState::MetadataEditor { selected, .. }
         | ActionPanelState::AudioQualityInspector { selected, .. }
+        | ActionPanelState::DuplicateClusters { selected, .. }
+        | ActionPanelState::DuplicateCluster { selected, .. }
         | ActionPanelState::AddDirectory { selected, .. }
-        | ActionPanelState::RemoveDirectory { selected } => *selected = idx,
+        | ActionPanelState::RemoveDirectory { selected }
+        | ActionPanelState::StatsMerge { selected, .. }
+        | ActionPanelState::StatsExport { selected, .. }
+        | ActionPanelState::M3uExport { selected, .. }
+        | ActionPanelState::PlaylistImport { selected, .. }
+        | ActionPanelState::SmartPlaylistCreate { selected, .. }
+        | ActionPanelState::FolderEnter { selected, .. }
+        | ActionPanelState::QueueJump { selected, .. }
+        | ActionPanelState::SeekToTimestamp { selected, .. }
+        | ActionPanelState::SleepTimer { selected }
+        | ActionPanelState::PlayPath { selected, .. }
+        | ActionPanelState::FuzzyFind { selected, .. } => *selected = idx,
         ActionPanelState::Closed => {}
     }
 }
@@ -5555,16 +8244,193 @@ fn sorted_playlist_names(core: &TuneCore) -> Vec<String> {
     names
 }
 
+/// Manual and smart playlists together, for panels that act on either.
+fn all_playlist_names(core: &TuneCore) -> Vec<String> {
+    let mut names: Vec<String> = core
+        .playlists
+        .keys()
+        .chain(core.smart_playlists.keys())
+        .cloned()
+        .collect();
+    names.sort_by_cached_key(|name| name.to_ascii_lowercase());
+    names
+}
+
+fn duplicate_cluster_label(core: &TuneCore, copies: &[PathBuf]) -> String {
+    let Some(path) = copies.first() else {
+        return String::new();
+    };
+    let title = core
+        .title_for_path(path)
+        .unwrap_or_else(|| path.display().to_string());
+    match core.artist_for_path(path) {
+        Some(artist) => format!("{artist} - {title}"),
+        None => title,
+    }
+}
+
+fn duplicate_cluster_options(core: &TuneCore, clusters: &[Vec<PathBuf>]) -> Vec<String> {
+    if clusters.is_empty() {
+        return vec![String::from("(no duplicates found)")];
+    }
+    clusters
+        .iter()
+        .map(|copies| {
+            format!(
+                "{} ({} copies)",
+                duplicate_cluster_label(core, copies),
+                copies.len()
+            )
+        })
+        .collect()
+}
+
+/// Copies that could go while keeping one of each song.
+fn reclaimable_duplicate_count(clusters: &[Vec<PathBuf>]) -> usize {
+    clusters
+        .iter()
+        .map(|copies| copies.len().saturating_sub(1))
+        .sum()
+}
+
+fn duplicate_summary(clusters: &[Vec<PathBuf>]) -> String {
+    if clusters.is_empty() {
+        return String::from("No duplicate tracks found");
+    }
+    format!(
+        "Found {} duplicated song(s); {} extra file(s) can be removed",
+        clusters.len(),
+        reclaimable_duplicate_count(clusters)
+    )
+}
+
+/// Deletes files from disk and drops them from the library and its index.
+/// Returns how many were deleted and the first error, if any.
+fn delete_duplicate_files(
+    core: &mut TuneCore,
+    library_runtime: Option<&mut LibraryRuntime>,
+    paths: &[PathBuf],
+) -> (usize, Option<String>) {
+    let mut deleted = Vec::new();
+    let mut first_error = None;
+    for path in paths {
+        let result = if library::cue_segment_for_path(path).is_some() {
+            Err(String::from("cue sheet tracks cannot be deleted"))
+        } else {
+            fs::remove_file(config::strip_windows_verbatim_prefix(path))
+                .map_err(|err| err.to_string())
+        };
+        match result {
+            Ok(()) => deleted.push(path.clone()),
+            Err(err) => {
+                if first_error.is_none() {
+                    first_error = Some(err);
+                }
+            }
+        }
+    }
+    core.remove_library_tracks(&deleted);
+    if let Some(runtime) = library_runtime
+        && !deleted.is_empty()
+    {
+        let deleted: HashSet<&Path> = deleted.iter().map(PathBuf::as_path).collect();
+        runtime
+            .index
+            .tracks
+            .retain(|entry| !deleted.contains(entry.path.as_path()));
+        let _ = config::save_library_index(&runtime.index);
+    }
+    (deleted.len(), first_error)
+}
+
+/// Drops the cleaned-up group and goes back to the list, or closes it once
+/// nothing is left.
+fn finish_duplicate_cluster(
+    core: &mut TuneCore,
+    panel: &mut ActionPanelState,
+    mut state: DuplicateClusterState,
+    done: String,
+) {
+    if state.cluster < state.clusters.len() {
+        state.clusters.remove(state.cluster);
+    }
+    core.status = format!(
+        "{done}; {} extra file(s) left",
+        reclaimable_duplicate_count(&state.clusters)
+    );
+    core.dirty = true;
+    if state.clusters.is_empty() {
+        panel.close();
+    } else {
+        *panel = ActionPanelState::DuplicateClusters {
+            selected: state.cluster.min(state.clusters.len() - 1),
+            clusters: state.clusters,
+        };
+    }
+}
+
+fn playlist_option_label(core: &TuneCore, name: String) -> String {
+    if core.is_smart_playlist(&name) {
+        format!("[SMART] {name}")
+    } else {
+        name
+    }
+}
+
+fn m3u_export_options(core: &TuneCore, path_input: &str) -> Vec<String> {
+    let mut options = vec![
+        format!("Export path: {path_input}"),
+        String::from("Export current queue"),
+    ];
+    options.extend(
+        all_playlist_names(core)
+            .into_iter()
+            .map(|name| format!("Export playlist: {}", playlist_option_label(core, name))),
+    );
+    options
+}
+
+/// Adds `.m3u` to a bare file name, or `<source>.m3u` inside a folder.
+fn m3u_export_path(path_input: &str, source_name: &str) -> PathBuf {
+    let mut path = parse_pasted_path(path_input);
+    if path.is_dir() {
+        path.push(format!("{}.m3u", sanitize_m3u_file_stem(source_name)));
+    } else if path.extension().is_none() {
+        path.set_extension("m3u");
+    }
+    path
+}
+
+fn sanitize_m3u_file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|ch| {
+            if ch.is_control() || matches!(ch, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+            {
+                '_'
+            } else {
+                ch
+            }
+        })
+        .collect();
+    let stem = stem.trim().trim_matches('.');
+    if stem.is_empty() {
+        String::from("playlist")
+    } else {
+        stem.to_string()
+    }
+}
+
 fn playlist_picker_options(core: &TuneCore) -> Vec<String> {
     let mut options = sorted_playlist_names(core);
     options.push(String::from("[+] Create new playlist"));
     options
 }
 
-fn sorted_folder_paths(core: &TuneCore) -> Vec<PathBuf> {
-    let mut paths = core.folders.clone();
-    paths.sort_by_cached_key(|path| path.to_string_lossy().to_ascii_lowercase());
-    paths
+fn sorted_folders(core: &TuneCore) -> Vec<crate::model::LibraryFolder> {
+    let mut folders = core.folders.clone();
+    folders.sort_by_cached_key(|folder| folder.path.to_string_lossy().to_ascii_lowercase());
+    folders
 }
 
 fn apply_quick_action(
@@ -5697,6 +8563,7 @@ fn metadata_editor_state_for_selection(core: &TuneCore) -> Option<MetadataEditor
         BrowserEntryKind::Track => {
             let path = entry.path;
             let metadata = library::metadata_snapshot_for_path(&path);
+            let crossfade_disabled = core.track_crossfade_disabled(&path);
             Some(MetadataEditorState {
                 selected_track_path: Some(path),
                 copy_target_label: String::from("selected track"),
@@ -5704,7 +8571,13 @@ fn metadata_editor_state_for_selection(core: &TuneCore) -> Option<MetadataEditor
                 title_input: metadata.title.unwrap_or_default(),
                 artist_input: metadata.artist.unwrap_or_default(),
                 album_input: metadata.album.unwrap_or_default(),
+                genre_input: metadata.genre.unwrap_or_default(),
+                year_input: metadata.year.unwrap_or_default(),
+                crossfade_disabled,
                 confirm_all_songs_cover_copy: false,
+                confirm_tag_write: None,
+                backup_before_tag_writes: core.backup_before_tag_writes,
+                tag_backup_bytes: core.tag_backup_disk_usage(),
             })
         }
         BrowserEntryKind::Folder => Some(MetadataEditorState {
@@ -5714,7 +8587,13 @@ fn metadata_editor_state_for_selection(core: &TuneCore) -> Option<MetadataEditor
             title_input: String::new(),
             artist_input: String::new(),
             album_input: String::new(),
+            genre_input: String::new(),
+            year_input: String::new(),
+            crossfade_disabled: false,
             confirm_all_songs_cover_copy: false,
+            confirm_tag_write: None,
+            backup_before_tag_writes: core.backup_before_tag_writes,
+            tag_backup_bytes: 0,
         }),
         BrowserEntryKind::Playlist => Some(MetadataEditorState {
             selected_track_path: None,
@@ -5723,7 +8602,13 @@ fn metadata_editor_state_for_selection(core: &TuneCore) -> Option<MetadataEditor
             title_input: String::new(),
             artist_input: String::new(),
             album_input: String::new(),
+            genre_input: String::new(),
+            year_input: String::new(),
+            crossfade_disabled: false,
             confirm_all_songs_cover_copy: false,
+            confirm_tag_write: None,
+            backup_before_tag_writes: core.backup_before_tag_writes,
+            tag_backup_bytes: 0,
         }),
         BrowserEntryKind::AllSongs => Some(MetadataEditorState {
             selected_track_path: None,
@@ -5732,7 +8617,13 @@ fn metadata_editor_state_for_selection(core: &TuneCore) -> Option<MetadataEditor
             title_input: String::new(),
             artist_input: String::new(),
             album_input: String::new(),
+            genre_input: String::new(),
+            year_input: String::new(),
+            crossfade_disabled: false,
             confirm_all_songs_cover_copy: true,
+            confirm_tag_write: None,
+            backup_before_tag_writes: core.backup_before_tag_writes,
+            tag_backup_bytes: 0,
         }),
         BrowserEntryKind::QueueLocal
         | BrowserEntryKind::QueueShared
@@ -5829,7 +8720,8 @@ fn copy_now_playing_cover_to_paths(
     let mut first_error = None;
     let mut library_runtime = library_runtime;
     for target in targets {
-        match library::write_embedded_cover_art(target, &image_data) {
+        let backup = core.tag_write_backup_path(target);
+        match library::write_embedded_cover_art(target, &image_data, backup.as_deref()) {
             Ok(()) => {
                 core.reload_track_metadata(target);
                 if let Some(runtime) = library_runtime.as_mut() {
@@ -5857,6 +8749,47 @@ fn copy_now_playing_cover_to_paths(
     core.dirty = true;
 }
 
+fn write_metadata_to_paths(
+    core: &mut TuneCore,
+    library_runtime: Option<&mut LibraryRuntime>,
+    targets: &[PathBuf],
+    edit: &MetadataEdit,
+    target_label: &str,
+) {
+    let mut written = 0usize;
+    let mut failed = 0usize;
+    let mut first_error = None;
+    let mut library_runtime = library_runtime;
+    for target in targets {
+        let backup = core.tag_write_backup_path(target);
+        match library::write_embedded_metadata(target, edit, backup.as_deref()) {
+            Ok(()) => {
+                core.reload_track_metadata(target);
+                if let Some(runtime) = library_runtime.as_mut() {
+                    sync_library_index_track_from_core(core, runtime, target);
+                }
+                written += 1;
+            }
+            Err(err) => {
+                failed += 1;
+                if first_error.is_none() {
+                    first_error = Some(err.to_string());
+                }
+            }
+        }
+    }
+
+    core.status = if failed == 0 {
+        format!("Wrote artist/album to {written} tracks in {target_label}")
+    } else {
+        format!(
+            "Wrote artist/album to {written} tracks in {target_label} ({failed} failed: {})",
+            first_error.unwrap_or_else(|| String::from("unknown error"))
+        )
+    };
+    core.dirty = true;
+}
+
 fn audio_output_options(audio: &dyn AudioEngine) -> Vec<String> {
     let selected = audio.selected_output_device();
     let outputs = audio.available_outputs();
@@ -5868,7 +8801,10 @@ fn audio_output_options(audio: &dyn AudioEngine) -> Vec<String> {
     });
 
     for output in outputs {
-        let label = if selected.as_deref() == Some(output.as_str()) {
+        let label = if selected
+            .as_deref()
+            .is_some_and(|selected| output_device_names_match(selected, &output))
+        {
             format!("* {output}")
         } else {
             output
@@ -5913,19 +8849,122 @@ fn playback_settings_options(core: &TuneCore) -> Vec<String> {
             "Missing cover fallback: {}",
             cover_template_label(core.fallback_cover_template)
         ),
-        String::from("Online sync delay settings"),
+        String::from("Online settings"),
         format!("Online nickname: {nickname}"),
+        format!("End of queue: {}", core.end_of_queue_behavior.label()),
+        format!("Keep going from: {}", core.keep_going_pool.label()),
+        format!(
+            "Crossfade only when shuffling: {}",
+            if core.crossfade_only_when_shuffling {
+                "On"
+            } else {
+                "Off"
+            }
+        ),
+        format!(
+            "Sleep on silence: {}",
+            sleep_on_silence_label(core.sleep_on_silence_minutes)
+        ),
+        format!("Enter on folder: {}", core.folder_enter_behavior.label()),
+        format!(
+            "Window title shows track: {}",
+            if core.window_title_shows_track {
+                "On"
+            } else {
+                "Off"
+            }
+        ),
+        format!("Arrow keys: {}", arrow_keys_label(core.arrow_keys_seek)),
+        format!(
+            "Gap between tracks: {}",
+            track_gap_label(core.track_gap_seconds)
+        ),
+        format!(
+            "Daily listening goal: {}",
+            daily_listen_goal_label(core.daily_listen_goal_minutes)
+        ),
+        format!(
+            "Crossfade look-ahead: {}",
+            crossfade_lead_label(core.crossfade_lead_ms)
+        ),
+        format!(
+            "Stats grace period: {}",
+            stats_grace_label(core.stats_grace_seconds)
+        ),
+        format!(
+            "Counts as completed: {}",
+            stats_completion_label(core.stats_completion_percent)
+        ),
+        format!(
+            "Prevent clipping: {}",
+            if core.prevent_clipping { "On" } else { "Off" }
+        ),
+        format!("Layout: {}", compact_layout_label(core.compact_layout)),
+        format!("Equalizer: {}", equalizer_summary(&core.equalizer_bands)),
+        format!(
+            "Gapless playback: {}",
+            if core.gapless { "On" } else { "Off" }
+        ),
+        format!(
+            "Normalization gain: {}",
+            normalization_gain_label(core.album_gain)
+        ),
+        format!(
+            "Playback speed: {}",
+            playback_speed_label(core.playback_speed)
+        ),
+        format!(
+            "Preserve pitch: {}",
+            if core.preserve_pitch { "On" } else { "Off" }
+        ),
+        format!("Crossfade curve: {}", core.crossfade_curve.label()),
+        format!("Shuffle mode: {}", core.shuffle_mode.label()),
+        format!(
+            "Shuffle avoids last: {}% of queue",
+            core.shuffle_recent_percent
+        ),
         String::from("Back"),
     ]
 }
 
-fn cover_template_label(_template: CoverArtTemplate) -> &'static str {
-    "Music Note"
+fn equalizer_summary(bands: &[(f32, f32)]) -> &'static str {
+    if bands.iter().all(|(_, gain)| *gain == 0.0) {
+        "Flat"
+    } else {
+        "Custom"
+    }
 }
 
-fn online_delay_settings_options(core: &TuneCore) -> Vec<String> {
-    let detail = core
-        .online
+fn output_shaping_options(core: &TuneCore) -> Vec<String> {
+    vec![
+        format!("Mono: {}", if core.mono_output { "On" } else { "Off" }),
+        format!("Balance: {} (Enter to center)", balance_label(core.balance)),
+        String::from("Balance 10% left"),
+        String::from("Balance 10% right"),
+        String::from("Back"),
+    ]
+}
+
+fn equalizer_options(core: &TuneCore) -> Vec<String> {
+    core.equalizer_bands
+        .iter()
+        .map(|(frequency, gain)| equalizer_band_label(*frequency, *gain))
+        .chain([String::from("Reset to flat"), String::from("Back")])
+        .collect()
+}
+
+fn apply_equalizer(core: &mut TuneCore, audio: &mut dyn AudioEngine) {
+    audio.set_equalizer(&core.equalizer_bands);
+    auto_save_state(core, &*audio);
+}
+
+fn cover_template_label(_template: CoverArtTemplate) -> &'static str {
+    "Music Note"
+}
+
+fn online_delay_settings_options(core: &TuneCore) -> Vec<String> {
+    let detail = core
+        .online
         .session
         .as_ref()
         .and_then(|session| session.local_participant())
@@ -5948,6 +8987,14 @@ fn online_delay_settings_options(core: &TuneCore) -> Vec<String> {
             "Sync correction threshold: {}ms",
             core.online_sync_correction_threshold_ms
         ),
+        format!(
+            "Remember room password (encrypted): {}",
+            if core.remember_online_password {
+                "On"
+            } else {
+                "Off"
+            }
+        ),
         format!("Back ({detail})"),
     ]
 }
@@ -6014,6 +9061,20 @@ fn theme_label(theme: Theme) -> &'static str {
     }
 }
 
+fn disk_usage_label(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
 fn crossfade_label(seconds: u16) -> String {
     if seconds == 0 {
         String::from("Off")
@@ -6049,6 +9110,32 @@ fn next_scrub_seconds(current: u16) -> u16 {
     SCRUB_SECONDS_OPTIONS[(index + 1) % SCRUB_SECONDS_OPTIONS.len()]
 }
 
+fn previous_scrub_seconds(current: u16) -> u16 {
+    let index = SCRUB_SECONDS_OPTIONS
+        .iter()
+        .position(|entry| *entry == current)
+        .unwrap_or(0);
+    SCRUB_SECONDS_OPTIONS[(index + SCRUB_SECONDS_OPTIONS.len() - 1) % SCRUB_SECONDS_OPTIONS.len()]
+}
+
+/// `-1` or `1` when `key` should seek: the configured scrub keys always, and
+/// Left/Right while a track is loaded if arrow keys are set to seek.
+fn scrub_key_direction(core: &TuneCore, audio: &dyn AudioEngine, key: &KeyEvent) -> Option<i64> {
+    match key.code {
+        KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&core.scrub_back_key) => Some(-1),
+        KeyCode::Char(ch) if ch.eq_ignore_ascii_case(&core.scrub_forward_key) => Some(1),
+        KeyCode::Left | KeyCode::Right
+            if core.arrow_keys_seek
+                && audio.current_track().is_some()
+                && !(core.header_section == HeaderSection::Library
+                    && (core.library_search_focused || !core.library_search_query.is_empty())) =>
+        {
+            Some(if key.code == KeyCode::Left { -1 } else { 1 })
+        }
+        _ => None,
+    }
+}
+
 fn next_stats_top_songs_count(current: u8) -> u8 {
     let index = STATS_TOP_SONGS_COUNT_OPTIONS
         .iter()
@@ -6077,8 +9164,18 @@ fn next_online_sync_correction_threshold_ms(current: u16) -> u16 {
 }
 
 fn apply_audio_preferences_from_core(core: &TuneCore, audio: &mut dyn AudioEngine) {
+    audio.set_prevent_clipping(core.prevent_clipping);
+    audio.set_album_gain(core.album_gain);
     audio.set_loudness_normalization(core.loudness_normalization);
     audio.set_crossfade_seconds(core.crossfade_seconds);
+    audio.set_crossfade_lead(Duration::from_millis(u64::from(core.crossfade_lead_ms)));
+    audio.set_crossfade_curve(core.crossfade_curve);
+    audio.set_equalizer(&core.equalizer_bands);
+    audio.set_gapless(core.gapless);
+    audio.set_preserve_pitch(core.preserve_pitch);
+    audio.set_playback_speed(core.playback_speed);
+    audio.set_mono(core.mono_output);
+    audio.set_balance(core.balance);
 }
 
 fn update_panel_selection(panel: &mut ActionPanelState, option_count: usize, move_next: bool) {
@@ -6107,15 +9204,30 @@ fn update_panel_selection(panel: &mut ActionPanelState, option_count: usize, mov
         | ActionPanelState::PlaylistRemove { selected }
         | ActionPanelState::AudioSettings { selected }
         | ActionPanelState::AudioOutput { selected }
+        | ActionPanelState::OutputShaping { selected }
         | ActionPanelState::PlaybackSettings { selected }
         | ActionPanelState::OnlineDelaySettings { selected }
+        | ActionPanelState::Equalizer { selected }
         | ActionPanelState::ThemeSettings { selected }
         | ActionPanelState::OnlineNickname { selected, .. }
         | ActionPanelState::LyricsImportTxt { selected, .. }
         | ActionPanelState::MetadataEditor { selected, .. }
         | ActionPanelState::AudioQualityInspector { selected, .. }
+        | ActionPanelState::DuplicateClusters { selected, .. }
+        | ActionPanelState::DuplicateCluster { selected, .. }
         | ActionPanelState::AddDirectory { selected, .. }
-        | ActionPanelState::RemoveDirectory { selected } => advance(selected),
+        | ActionPanelState::RemoveDirectory { selected }
+        | ActionPanelState::StatsMerge { selected, .. }
+        | ActionPanelState::StatsExport { selected, .. }
+        | ActionPanelState::M3uExport { selected, .. }
+        | ActionPanelState::PlaylistImport { selected, .. }
+        | ActionPanelState::SmartPlaylistCreate { selected, .. }
+        | ActionPanelState::FolderEnter { selected, .. }
+        | ActionPanelState::QueueJump { selected, .. }
+        | ActionPanelState::SeekToTimestamp { selected, .. }
+        | ActionPanelState::SleepTimer { selected }
+        | ActionPanelState::PlayPath { selected, .. }
+        | ActionPanelState::FuzzyFind { selected, .. } => advance(selected),
         ActionPanelState::Closed => {}
     }
 }
@@ -6148,6 +9260,11 @@ fn handle_action_panel_input_with_recent(
     mut library_runtime: Option<&mut LibraryRuntime>,
     key: KeyCode,
 ) {
+    let key = if panel.takes_text_input() {
+        key
+    } else {
+        vim_navigation_key(key)
+    };
     if let ActionPanelState::Root { selected, query } = panel {
         match key {
             KeyCode::Char(ch) => {
@@ -6182,6 +9299,16 @@ fn handle_action_panel_input_with_recent(
         }
     }
 
+    if let ActionPanelState::RemoveDirectory { selected } = panel
+        && key == KeyCode::Char(' ')
+    {
+        let folders = sorted_folders(core);
+        if let Some(folder) = folders.get(*selected) {
+            toggle_folder_enabled(core, &*audio, library_runtime.as_deref_mut(), &folder.path);
+        }
+        return;
+    }
+
     if let ActionPanelState::PlaylistCreate { selected, input } = panel {
         match key {
             KeyCode::Char(ch) if *selected == 0 => {
@@ -6217,6 +9344,79 @@ fn handle_action_panel_input_with_recent(
         }
     }
 
+    if let ActionPanelState::FuzzyFind {
+        selected,
+        query,
+        results,
+    } = panel
+    {
+        let edited = match key {
+            KeyCode::Char(ch) if !ch.is_control() && query.chars().count() < 64 => {
+                query.push(ch);
+                true
+            }
+            KeyCode::Backspace if !query.is_empty() => {
+                query.pop();
+                true
+            }
+            _ => false,
+        };
+        if edited {
+            *results = core.fuzzy_find_tracks(query, FUZZY_FIND_LIMIT);
+            *selected = 0;
+            core.dirty = true;
+            return;
+        }
+    }
+
+    if let ActionPanelState::QueueJump { input, .. } = panel {
+        match key {
+            KeyCode::Char(ch) if ch.is_ascii_digit() && input.len() < 6 => {
+                input.push(ch);
+                core.dirty = true;
+                return;
+            }
+            KeyCode::Backspace if !input.is_empty() => {
+                input.pop();
+                core.dirty = true;
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    if let ActionPanelState::SeekToTimestamp { input, .. } = panel {
+        match key {
+            KeyCode::Char(ch) if (ch.is_ascii_digit() || ch == ':') && input.len() < 9 => {
+                input.push(ch);
+                core.dirty = true;
+                return;
+            }
+            KeyCode::Backspace if !input.is_empty() => {
+                input.pop();
+                core.dirty = true;
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    if let ActionPanelState::PlayPath { input, .. } = panel {
+        match key {
+            KeyCode::Char(ch) => {
+                input.push(ch);
+                core.dirty = true;
+                return;
+            }
+            KeyCode::Backspace if !input.is_empty() => {
+                input.pop();
+                core.dirty = true;
+                return;
+            }
+            _ => {}
+        }
+    }
+
     if let ActionPanelState::OnlineNickname { selected, input } = panel {
         match key {
             KeyCode::Char(ch) if *selected == 0 => {
@@ -6264,24 +9464,88 @@ fn handle_action_panel_input_with_recent(
         }
     }
 
-    if let ActionPanelState::MetadataEditor { selected, state } = panel
-        && state.selected_track_path.is_some()
+    if let ActionPanelState::StatsExport {
+        selected,
+        path_input,
+    }
+    | ActionPanelState::M3uExport {
+        selected,
+        path_input,
+    }
+    | ActionPanelState::PlaylistImport {
+        selected,
+        path_input,
+    } = panel
     {
+        match key {
+            KeyCode::Char(ch) if *selected == 0 => {
+                path_input.push(ch);
+                core.dirty = true;
+                return;
+            }
+            KeyCode::Backspace if *selected == 0 && !path_input.is_empty() => {
+                path_input.pop();
+                core.dirty = true;
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    if let ActionPanelState::SmartPlaylistCreate { selected, draft } = panel {
+        if *selected == 2 {
+            match key {
+                KeyCode::Char(ch) if ch.is_ascii_digit() && draft.limit_input.len() < 5 => {
+                    draft.limit_input.push(ch);
+                    core.dirty = true;
+                    return;
+                }
+                KeyCode::Backspace if !draft.limit_input.is_empty() => {
+                    draft.limit_input.pop();
+                    core.dirty = true;
+                    return;
+                }
+                _ => {}
+            }
+        } else if let Some(field) = draft.text_field(*selected) {
+            match key {
+                KeyCode::Char(ch) => {
+                    field.push(ch);
+                    core.dirty = true;
+                    return;
+                }
+                KeyCode::Backspace if !field.is_empty() => {
+                    field.pop();
+                    core.dirty = true;
+                    return;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if let ActionPanelState::MetadataEditor { selected, state } = panel {
+        let single_track = state.selected_track_path.is_some();
+        let year_row = single_track && *selected == 4;
         let target = match *selected {
-            0 => Some(&mut state.title_input),
+            0 if single_track => Some(&mut state.title_input),
             1 => Some(&mut state.artist_input),
             2 => Some(&mut state.album_input),
+            3 if single_track => Some(&mut state.genre_input),
+            4 if single_track => Some(&mut state.year_input),
             _ => None,
         };
         if let Some(target) = target {
             match key {
-                KeyCode::Char(ch) => {
+                KeyCode::Char(ch) if !year_row || (ch.is_ascii_digit() && target.len() < 4) => {
                     target.push(ch);
+                    state.confirm_tag_write = None;
                     core.dirty = true;
                     return;
                 }
                 KeyCode::Backspace if !target.is_empty() => {
                     target.pop();
+                    state.confirm_tag_write = None;
                     core.dirty = true;
                     return;
                 }
@@ -6298,21 +9562,38 @@ fn handle_action_panel_input_with_recent(
         ActionPanelState::PlaylistAdd { .. } | ActionPanelState::PlaylistAddNowPlaying { .. } => {
             playlist_picker_options(core).len()
         }
-        ActionPanelState::PlaylistRemove { .. } => sorted_playlist_names(core).len().max(1),
+        ActionPanelState::PlaylistRemove { .. } => all_playlist_names(core).len().max(1),
+        ActionPanelState::SmartPlaylistCreate { draft, .. } => draft.options().len(),
         ActionPanelState::PlaylistCreate { .. } | ActionPanelState::PlaylistCreateForAdd { .. } => {
             1
         }
-        ActionPanelState::AudioSettings { .. } => 3,
+        ActionPanelState::AudioSettings { .. } => 4,
+        ActionPanelState::OutputShaping { .. } => output_shaping_options(core).len(),
         ActionPanelState::AudioOutput { .. } => audio.available_outputs().len().saturating_add(1),
-        ActionPanelState::PlaybackSettings { .. } => 11,
-        ActionPanelState::OnlineDelaySettings { .. } => 6,
+        ActionPanelState::PlaybackSettings { .. } => 33,
+        ActionPanelState::OnlineDelaySettings { .. } => 7,
+        ActionPanelState::Equalizer { .. } => core.equalizer_bands.len() + 2,
         ActionPanelState::ThemeSettings { .. } => selectable_themes().len(),
         ActionPanelState::OnlineNickname { .. } => 1,
         ActionPanelState::LyricsImportTxt { .. } => 3,
         ActionPanelState::MetadataEditor { state, .. } => state.options().len(),
         ActionPanelState::AudioQualityInspector { state, .. } => state.options().len(),
+        ActionPanelState::DuplicateClusters { clusters, .. } => clusters.len().max(1),
+        ActionPanelState::DuplicateCluster { state, .. } => state.options().len(),
         ActionPanelState::AddDirectory { .. } => 2,
-        ActionPanelState::RemoveDirectory { .. } => sorted_folder_paths(core).len().max(1),
+        ActionPanelState::RemoveDirectory { .. } => sorted_folders(core).len().max(1),
+        ActionPanelState::StatsMerge { merges, .. } => stats_merge_options(merges).len(),
+        ActionPanelState::StatsExport { .. } => 3,
+        ActionPanelState::PlaylistImport { .. } => 2,
+        ActionPanelState::M3uExport { path_input, .. } => {
+            m3u_export_options(core, path_input).len()
+        }
+        ActionPanelState::FolderEnter { .. } => folder_enter_options().len(),
+        ActionPanelState::QueueJump { .. } => 1,
+        ActionPanelState::SeekToTimestamp { .. } => 1,
+        ActionPanelState::SleepTimer { .. } => SLEEP_TIMER_OPTIONS_MINUTES.len() + 3,
+        ActionPanelState::PlayPath { .. } => 1,
+        ActionPanelState::FuzzyFind { results, .. } => results.len().max(1),
     };
 
     if let ActionPanelState::Root { selected, query } = panel {
@@ -6381,10 +9662,16 @@ fn handle_action_panel_input_with_recent(
                 ActionPanelState::OnlineDelaySettings { .. } => {
                     ActionPanelState::PlaybackSettings { selected: 8 }
                 }
+                ActionPanelState::Equalizer { .. } => {
+                    ActionPanelState::PlaybackSettings { selected: 24 }
+                }
                 ActionPanelState::AddDirectory { .. } => ActionPanelState::Closed,
                 ActionPanelState::AudioOutput { .. } => {
                     ActionPanelState::AudioSettings { selected: 0 }
                 }
+                ActionPanelState::OutputShaping { .. } => {
+                    ActionPanelState::AudioSettings { selected: 2 }
+                }
                 ActionPanelState::ThemeSettings { .. } => ActionPanelState::Root {
                     selected: root_selected_for_action(RootActionId::Theme, recent_root_actions),
                     query: String::new(),
@@ -6420,9 +9707,71 @@ fn handle_action_panel_input_with_recent(
                     ),
                     query: String::new(),
                 },
-                ActionPanelState::Root { .. } | ActionPanelState::Closed => {
-                    ActionPanelState::Closed
+                ActionPanelState::StatsMerge { .. } => ActionPanelState::Root {
+                    selected: root_selected_for_action(
+                        RootActionId::MergeDuplicateStats,
+                        recent_root_actions,
+                    ),
+                    query: String::new(),
+                },
+                ActionPanelState::StatsExport { .. } => ActionPanelState::Root {
+                    selected: root_selected_for_action(
+                        RootActionId::ExportStats,
+                        recent_root_actions,
+                    ),
+                    query: String::new(),
+                },
+                ActionPanelState::SmartPlaylistCreate { .. } => ActionPanelState::Root {
+                    selected: root_selected_for_action(
+                        RootActionId::CreateSmartPlaylist,
+                        recent_root_actions,
+                    ),
+                    query: String::new(),
+                },
+                ActionPanelState::PlaylistImport { .. } => ActionPanelState::Root {
+                    selected: root_selected_for_action(
+                        RootActionId::ImportPlaylistFile,
+                        recent_root_actions,
+                    ),
+                    query: String::new(),
+                },
+                ActionPanelState::M3uExport { .. } => ActionPanelState::Root {
+                    selected: root_selected_for_action(
+                        RootActionId::ExportM3u,
+                        recent_root_actions,
+                    ),
+                    query: String::new(),
+                },
+                ActionPanelState::PlayPath { .. } => ActionPanelState::Root {
+                    selected: root_selected_for_action(RootActionId::PlayPath, recent_root_actions),
+                    query: String::new(),
+                },
+                ActionPanelState::DuplicateClusters { .. } => ActionPanelState::Root {
+                    selected: root_selected_for_action(
+                        RootActionId::FindDuplicates,
+                        recent_root_actions,
+                    ),
+                    query: String::new(),
+                },
+                ActionPanelState::DuplicateCluster { state, .. } => {
+                    ActionPanelState::DuplicateClusters {
+                        selected: state.cluster,
+                        clusters: std::mem::take(&mut state.clusters),
+                    }
                 }
+                ActionPanelState::SleepTimer { .. } => ActionPanelState::Root {
+                    selected: root_selected_for_action(
+                        RootActionId::SleepTimer,
+                        recent_root_actions,
+                    ),
+                    query: String::new(),
+                },
+                ActionPanelState::Root { .. }
+                | ActionPanelState::FolderEnter { .. }
+                | ActionPanelState::QueueJump { .. }
+                | ActionPanelState::SeekToTimestamp { .. }
+                | ActionPanelState::FuzzyFind { .. }
+                | ActionPanelState::Closed => ActionPanelState::Closed,
             };
             core.dirty = true;
         }
@@ -6442,21 +9791,13 @@ fn handle_action_panel_input_with_recent(
 
                 match selected_action {
                     RootActionId::RemoveSelectedFromQueue => {
-                        if core.viewing_shared_queue() {
-                            if let Some((index, expected_path)) =
-                                core.remove_selected_from_shared_queue()
-                                && let Some(network) = online_runtime
-                                    .as_deref()
-                                    .and_then(|runtime| runtime.network.as_ref())
-                            {
-                                network.send_local_action(NetworkLocalAction::QueueRemoveAt {
-                                    index,
-                                    expected_path: Some(expected_path),
-                                });
-                            }
-                        } else {
-                            core.remove_selected_from_local_queue();
-                        }
+                        remove_selected_queue_item(
+                            core,
+                            audio,
+                            online_runtime
+                                .as_deref()
+                                .and_then(|runtime| runtime.network.as_ref()),
+                        );
                         auto_save_state(core, &*audio);
                         panel.close();
                     }
@@ -6480,10 +9821,31 @@ fn handle_action_panel_input_with_recent(
                         auto_save_state(core, &*audio);
                         panel.close();
                     }
+                    RootActionId::CopyQueueAsText => {
+                        copy_queue_as_text(core);
+                        panel.close();
+                    }
+                    RootActionId::ImportQueueFromClipboard => {
+                        import_queue_from_clipboard(core);
+                        auto_save_state(core, &*audio);
+                        panel.close();
+                    }
+                    RootActionId::PlayPath => {
+                        *panel = ActionPanelState::PlayPath {
+                            selected: 0,
+                            input: String::new(),
+                        };
+                        core.status = String::from("Type or paste a path to play");
+                        core.dirty = true;
+                    }
                     RootActionId::PlaybackSettings => {
                         *panel = ActionPanelState::PlaybackSettings { selected: 0 };
                         core.dirty = true;
                     }
+                    RootActionId::SleepTimer => {
+                        *panel = ActionPanelState::SleepTimer { selected: 0 };
+                        core.dirty = true;
+                    }
                     RootActionId::RemoveSelectedFromPlaylist => {
                         core.remove_selected_from_current_playlist();
                         auto_save_state(core, &*audio);
@@ -6520,6 +9882,40 @@ fn handle_action_panel_input_with_recent(
                         core.dirty = true;
                         panel.close();
                     }
+                    RootActionId::MergeDuplicateStats => {
+                        core.stats_merge_preview_requested = true;
+                        core.status = String::from("Looking for duplicate stats...");
+                        core.dirty = true;
+                        panel.close();
+                    }
+                    RootActionId::ExportStats => {
+                        *panel = ActionPanelState::StatsExport {
+                            selected: 0,
+                            path_input: String::new(),
+                        };
+                        core.dirty = true;
+                    }
+                    RootActionId::CreateSmartPlaylist => {
+                        *panel = ActionPanelState::SmartPlaylistCreate {
+                            selected: 0,
+                            draft: SmartPlaylistDraft::default(),
+                        };
+                        core.dirty = true;
+                    }
+                    RootActionId::ImportPlaylistFile => {
+                        *panel = ActionPanelState::PlaylistImport {
+                            selected: 0,
+                            path_input: String::new(),
+                        };
+                        core.dirty = true;
+                    }
+                    RootActionId::ExportM3u => {
+                        *panel = ActionPanelState::M3uExport {
+                            selected: 0,
+                            path_input: String::new(),
+                        };
+                        core.dirty = true;
+                    }
                     RootActionId::MetadataEditor => {
                         let Some(state) = metadata_editor_state_for_selection(core) else {
                             core.status = String::from(
@@ -6547,6 +9943,16 @@ fn handle_action_panel_input_with_recent(
                         };
                         core.dirty = true;
                     }
+                    RootActionId::FindDuplicates => {
+                        core.duplicate_scan_requested = true;
+                        core.status = String::from("Scanning for duplicates...");
+                        core.dirty = true;
+                        panel.close();
+                    }
+                    RootActionId::CycleAllSongsOrder => {
+                        core.cycle_all_songs_order();
+                        panel.close();
+                    }
                     RootActionId::MinimizeToTray => {
                         request_minimize_to_tray(core);
                         panel.close();
@@ -6559,6 +9965,28 @@ fn handle_action_panel_input_with_recent(
                         };
                         core.dirty = true;
                     }
+                    RootActionId::ShuffleSharedQueue => {
+                        if let Some(order) = core.shuffle_shared_queue()
+                            && let Some(network) = online_runtime
+                                .as_deref()
+                                .and_then(|runtime| runtime.network.as_ref())
+                        {
+                            network.send_local_action(NetworkLocalAction::QueueShuffle { order });
+                        }
+                        panel.close();
+                    }
+                    RootActionId::StopLocalHomeServer => {
+                        if let Some(online_runtime) = online_runtime.as_deref_mut() {
+                            stop_local_home_server(core, online_runtime);
+                        }
+                        panel.close();
+                    }
+                    RootActionId::ReconnectLastRoom => {
+                        if let Some(online_runtime) = online_runtime.as_deref_mut() {
+                            reconnect_last_room(core, online_runtime);
+                        }
+                        panel.close();
+                    }
                     RootActionId::ClosePanel => {
                         panel.close();
                         core.dirty = true;
@@ -6650,7 +10078,7 @@ fn handle_action_panel_input_with_recent(
                 panel.close();
             }
             ActionPanelState::PlaylistRemove { selected } => {
-                let playlists = sorted_playlist_names(core);
+                let playlists = all_playlist_names(core);
                 if let Some(name) = playlists.get(selected) {
                     core.remove_playlist(name);
                     auto_save_state(core, &*audio);
@@ -6690,8 +10118,12 @@ fn handle_action_panel_input_with_recent(
                     *panel = ActionPanelState::AudioOutput { selected };
                     core.dirty = true;
                 }
-                _ => {
-                    *panel = ActionPanelState::Root {
+                2 => {
+                    *panel = ActionPanelState::OutputShaping { selected: 0 };
+                    core.dirty = true;
+                }
+                _ => {
+                    *panel = ActionPanelState::Root {
                         selected: root_selected_for_action(
                             RootActionId::AudioDriverSettings,
                             recent_root_actions,
@@ -6749,24 +10181,16 @@ fn handle_action_panel_input_with_recent(
                     core.dirty = true;
                     auto_save_state(core, &*audio);
                 }
-                2 => {
-                    core.loudness_normalization = !core.loudness_normalization;
-                    audio.set_loudness_normalization(core.loudness_normalization);
-                    core.status = format!(
-                        "Loudness normalization: {}",
-                        if core.loudness_normalization {
-                            "On"
-                        } else {
-                            "Off"
-                        }
-                    );
-                    core.dirty = true;
-                    auto_save_state(core, &*audio);
-                }
+                2 => toggle_loudness_normalization(core, audio),
                 3 => {
                     core.crossfade_seconds = next_crossfade_seconds(core.crossfade_seconds);
                     audio.set_crossfade_seconds(core.crossfade_seconds);
                     core.status = format!("Crossfade: {}", crossfade_label(core.crossfade_seconds));
+                    if core.crossfade_seconds > 0 && core.track_gap_seconds > 0 {
+                        core.track_gap_seconds = 0;
+                        core.pending_advance_at = None;
+                        core.status.push_str(" (gap between tracks off)");
+                    }
                     core.dirty = true;
                     auto_save_state(core, &*audio);
                 }
@@ -6816,6 +10240,84 @@ fn handle_action_panel_input_with_recent(
                     };
                     core.dirty = true;
                 }
+                10 => {
+                    core.cycle_end_of_queue_behavior();
+                    auto_save_state(core, &*audio);
+                }
+                11 => {
+                    core.cycle_keep_going_pool();
+                    auto_save_state(core, &*audio);
+                }
+                12 => {
+                    core.toggle_crossfade_only_when_shuffling();
+                    auto_save_state(core, &*audio);
+                }
+                13 => {
+                    core.cycle_sleep_on_silence_minutes();
+                    auto_save_state(core, &*audio);
+                }
+                14 => {
+                    core.cycle_folder_enter_behavior();
+                    auto_save_state(core, &*audio);
+                }
+                15 => {
+                    core.toggle_window_title_shows_track();
+                    auto_save_state(core, &*audio);
+                }
+                16 => {
+                    core.toggle_arrow_keys_seek();
+                    auto_save_state(core, &*audio);
+                }
+                17 => {
+                    core.cycle_track_gap_seconds();
+                    audio.set_crossfade_seconds(core.crossfade_seconds);
+                    auto_save_state(core, &*audio);
+                }
+                18 => {
+                    core.cycle_daily_listen_goal();
+                    auto_save_state(core, &*audio);
+                }
+                19 => {
+                    core.cycle_crossfade_lead();
+                    audio.set_crossfade_lead(Duration::from_millis(u64::from(
+                        core.crossfade_lead_ms,
+                    )));
+                    auto_save_state(core, &*audio);
+                }
+                20 => {
+                    core.cycle_stats_grace_seconds();
+                    auto_save_state(core, &*audio);
+                }
+                21 => {
+                    core.cycle_stats_completion_percent();
+                    auto_save_state(core, &*audio);
+                }
+                22 => toggle_prevent_clipping(core, audio),
+                23 => {
+                    core.cycle_compact_layout();
+                    auto_save_state(core, &*audio);
+                }
+                24 => {
+                    *panel = ActionPanelState::Equalizer { selected: 0 };
+                    core.dirty = true;
+                }
+                25 => toggle_gapless(core, audio),
+                26 => toggle_album_gain(core, audio),
+                27 => cycle_playback_speed(core, audio, true),
+                28 => toggle_preserve_pitch(core, audio),
+                29 => {
+                    core.cycle_crossfade_curve();
+                    audio.set_crossfade_curve(core.crossfade_curve);
+                    auto_save_state(core, &*audio);
+                }
+                30 => {
+                    core.cycle_shuffle_mode();
+                    auto_save_state(core, &*audio);
+                }
+                31 => {
+                    core.cycle_shuffle_recent_percent();
+                    auto_save_state(core, &*audio);
+                }
                 _ => {
                     *panel = ActionPanelState::Root {
                         selected: root_selected_for_action(
@@ -6874,11 +10376,48 @@ fn handle_action_panel_input_with_recent(
                     core.dirty = true;
                     auto_save_state(core, &*audio);
                 }
+                5 => {
+                    core.toggle_remember_online_password();
+                    auto_save_state(core, &*audio);
+                }
                 _ => {
                     *panel = ActionPanelState::PlaybackSettings { selected: 8 };
                     core.dirty = true;
                 }
             },
+            ActionPanelState::Equalizer { selected } => {
+                let band_count = core.equalizer_bands.len();
+                if selected < band_count {
+                    core.step_equalizer_band(selected);
+                    apply_equalizer(core, audio);
+                } else if selected == band_count {
+                    core.reset_equalizer();
+                    apply_equalizer(core, audio);
+                } else {
+                    *panel = ActionPanelState::PlaybackSettings { selected: 24 };
+                    core.dirty = true;
+                }
+            }
+            ActionPanelState::OutputShaping { selected } => match selected {
+                0 => {
+                    core.toggle_mono_output();
+                    audio.set_mono(core.mono_output);
+                    auto_save_state(core, &*audio);
+                }
+                1..=3 => {
+                    core.shift_balance(match selected {
+                        2 => -BALANCE_STEP,
+                        3 => BALANCE_STEP,
+                        _ => 0.0,
+                    });
+                    audio.set_balance(core.balance);
+                    auto_save_state(core, &*audio);
+                }
+                _ => {
+                    *panel = ActionPanelState::AudioSettings { selected: 2 };
+                    core.dirty = true;
+                }
+            },
             ActionPanelState::ThemeSettings { selected } => {
                 core.theme = selectable_themes()
                     .get(selected)
@@ -6907,6 +10446,94 @@ fn handle_action_panel_input_with_recent(
                 core.import_txt_to_lyrics(Path::new(trimmed_path), interval);
                 panel.close();
             }
+            ActionPanelState::StatsExport {
+                selected,
+                path_input,
+            } => {
+                let format = match selected {
+                    1 => crate::stats::StatsExportFormat::Csv,
+                    2 => crate::stats::StatsExportFormat::Json,
+                    _ => return,
+                };
+                if path_input.trim().is_empty() {
+                    core.status = String::from("Type a path to export stats to");
+                    core.dirty = true;
+                    return;
+                }
+                let mut path = parse_pasted_path(&path_input);
+                if path.extension().is_none() {
+                    path.set_extension(format.extension());
+                }
+                core.stats_export_requested = Some((path, format));
+                core.status = format!("Exporting stats as {}...", format.label());
+                core.dirty = true;
+                panel.close();
+            }
+            ActionPanelState::SmartPlaylistCreate {
+                selected,
+                mut draft,
+            } => match selected {
+                1 => {
+                    draft.sort = draft.sort.next();
+                    *panel = ActionPanelState::SmartPlaylistCreate { selected, draft };
+                    core.dirty = true;
+                }
+                SmartPlaylistDraft::CREATE_ROW => {
+                    let name = draft.name.trim();
+                    if name.is_empty() {
+                        core.status = String::from("Type a name for the smart playlist");
+                        core.dirty = true;
+                        return;
+                    }
+                    let Some(rule) = draft.rule() else {
+                        core.status = String::from("Limit must be at least 1");
+                        core.dirty = true;
+                        return;
+                    };
+                    core.create_smart_playlist(name, rule);
+                    auto_save_state(core, &*audio);
+                    panel.close();
+                }
+                _ => {}
+            },
+            ActionPanelState::PlaylistImport {
+                selected,
+                path_input,
+            } => {
+                if selected < 1 {
+                    return;
+                }
+                if path_input.trim().is_empty() {
+                    core.status = String::from("Provide M3U/PLS path to import");
+                    core.dirty = true;
+                    return;
+                }
+                core.import_playlist_file(&parse_pasted_path(&path_input));
+                auto_save_state(core, &*audio);
+                panel.close();
+            }
+            ActionPanelState::M3uExport {
+                selected,
+                path_input,
+            } => {
+                if selected == 0 {
+                    return;
+                }
+                if path_input.trim().is_empty() {
+                    core.status = String::from("Type a path to export the M3U to");
+                    core.dirty = true;
+                    return;
+                }
+                if selected == 1 {
+                    core.export_queue_m3u(&m3u_export_path(&path_input, "queue"));
+                } else {
+                    let Some(name) = all_playlist_names(core).get(selected - 2).cloned() else {
+                        return;
+                    };
+                    core.export_playlist_m3u(&name, &m3u_export_path(&path_input, &name));
+                }
+                panel.close();
+            }
             ActionPanelState::MetadataEditor { selected, state } => match selected {
                 0 if state.selected_track_path.is_none() => {
                     if state.confirm_all_songs_cover_copy {
@@ -6938,7 +10565,36 @@ fn handle_action_panel_input_with_recent(
                     );
                     panel.close();
                 }
-                1 if state.selected_track_path.is_none() => {
+                3 if state.selected_track_path.is_none() && state.confirm_tag_write != Some(3) => {
+                    if state.batch_metadata_edit().is_none() {
+                        core.status = String::from("Type an artist or album to apply");
+                        core.dirty = true;
+                        return;
+                    }
+                    core.status = format!(
+                        "Press Enter again to write artist/album to {} tracks in {}",
+                        state.copy_target_paths.len(),
+                        state.copy_target_label
+                    );
+                    core.dirty = true;
+                    if let ActionPanelState::MetadataEditor { state, .. } = panel {
+                        state.confirm_tag_write = Some(3);
+                    }
+                }
+                3 if state.selected_track_path.is_none() => {
+                    let Some(edit) = state.batch_metadata_edit() else {
+                        return;
+                    };
+                    write_metadata_to_paths(
+                        core,
+                        library_runtime.as_deref_mut(),
+                        &state.copy_target_paths,
+                        &edit,
+                        &state.copy_target_label,
+                    );
+                    panel.close();
+                }
+                4 if state.selected_track_path.is_none() => {
                     *panel = ActionPanelState::Root {
                         selected: root_selected_for_action(
                             RootActionId::MetadataEditor,
@@ -6948,11 +10604,30 @@ fn handle_action_panel_input_with_recent(
                     };
                     core.dirty = true;
                 }
-                3 => {
+                5 | 6 if state.confirm_tag_write != Some(selected) => {
+                    let Some(path) = state.selected_track_path.as_ref() else {
+                        return;
+                    };
+                    core.status = format!(
+                        "Press Enter again to {} tags in {}",
+                        if selected == 5 { "save" } else { "clear" },
+                        path.display()
+                    );
+                    core.dirty = true;
+                    if let ActionPanelState::MetadataEditor { state, .. } = panel {
+                        state.confirm_tag_write = Some(selected);
+                    }
+                }
+                5 => {
                     let Some(path) = state.selected_track_path.as_ref() else {
                         return;
                     };
-                    match library::write_embedded_metadata(path, &state.metadata_edit()) {
+                    let backup = core.tag_write_backup_path(path);
+                    match library::write_embedded_metadata(
+                        path,
+                        &state.metadata_edit(),
+                        backup.as_deref(),
+                    ) {
                         Ok(()) => {
                             core.reload_track_metadata(path);
                             if let Some(runtime) = library_runtime.as_mut() {
@@ -6969,11 +10644,12 @@ fn handle_action_panel_input_with_recent(
                     }
                     panel.close();
                 }
-                4 => {
+                6 => {
                     let Some(path) = state.selected_track_path.as_ref() else {
                         return;
                     };
-                    match library::clear_embedded_metadata(path) {
+                    let backup = core.tag_write_backup_path(path);
+                    match library::clear_embedded_metadata(path, backup.as_deref()) {
                         Ok(()) => {
                             core.reload_track_metadata(path);
                             if let Some(runtime) = library_runtime.as_mut() {
@@ -6990,7 +10666,7 @@ fn handle_action_panel_input_with_recent(
                     }
                     panel.close();
                 }
-                5 => {
+                7 => {
                     let Some(source_path) = now_playing_cover_source_path(core, &*audio) else {
                         core.status = String::from("No track is currently playing");
                         core.dirty = true;
@@ -7006,7 +10682,71 @@ fn handle_action_panel_input_with_recent(
                     );
                     panel.close();
                 }
-                6 => {
+                8 => {
+                    let Some(track_path) = state.selected_track_path.clone() else {
+                        return;
+                    };
+                    let artist = state.artist_input.trim();
+                    let album = state.album_input.trim();
+                    if artist.is_empty() || album.is_empty() {
+                        core.status = String::from("Cover art lookup needs an artist and album");
+                        core.dirty = true;
+                        return;
+                    }
+                    core.status = format!("Downloading cover art for {artist} - {album}...");
+                    core.cover_art_download_requested = Some(CoverArtDownloadRequest {
+                        track_path,
+                        artist: artist.to_string(),
+                        album: album.to_string(),
+                    });
+                    core.dirty = true;
+                    panel.close();
+                }
+                9 => {
+                    let Some(path) = state.selected_track_path.clone() else {
+                        return;
+                    };
+                    let crossfade_disabled = core.toggle_track_crossfade_disabled(&path);
+                    auto_save_state(core, &*audio);
+                    if let ActionPanelState::MetadataEditor { state, .. } = panel {
+                        state.crossfade_disabled = crossfade_disabled;
+                    }
+                }
+                10 => {
+                    let Some(path) = state.selected_track_path.as_ref() else {
+                        return;
+                    };
+                    let Some(backup) = core.tag_backup_path(path) else {
+                        core.status = String::from("Tag backup folder is unavailable");
+                        core.dirty = true;
+                        return;
+                    };
+                    match library::restore_track_backup(path, &backup) {
+                        Ok(()) => {
+                            core.reload_track_metadata(path);
+                            if let Some(runtime) = library_runtime.as_mut() {
+                                sync_library_index_track_from_core(core, runtime, path);
+                            }
+                            core.status = String::from("Restored original file from backup");
+                            core.dirty = true;
+                        }
+                        Err(err) => {
+                            core.status = format!("Restore failed: {err:#}");
+                            core.dirty = true;
+                            return;
+                        }
+                    }
+                    panel.close();
+                }
+                11 => {
+                    core.toggle_backup_before_tag_writes();
+                    auto_save_state(core, &*audio);
+                    if let ActionPanelState::MetadataEditor { state, .. } = panel {
+                        state.backup_before_tag_writes = core.backup_before_tag_writes;
+                        state.tag_backup_bytes = core.tag_backup_disk_usage();
+                    }
+                }
+                12 => {
                     *panel = ActionPanelState::Root {
                         selected: root_selected_for_action(
                             RootActionId::MetadataEditor,
@@ -7068,78 +10808,257 @@ fn handle_action_panel_input_with_recent(
                 }
             }
             ActionPanelState::RemoveDirectory { selected } => {
-                let folders = sorted_folder_paths(core);
-                if let Some(path) = folders.get(selected) {
-                    try_remove_folder_async(core, &*audio, library_runtime, path);
+                let folders = sorted_folders(core);
+                if let Some(folder) = folders.get(selected) {
+                    try_remove_folder_async(core, &*audio, library_runtime, &folder.path);
                 } else {
                     core.status = String::from("No folders available");
                     core.dirty = true;
                 }
                 panel.close();
             }
-            ActionPanelState::Closed => {}
-        },
-        _ => {}
-    }
-}
-
-#[cfg(windows)]
-fn choose_folder_externally() -> Result<Option<PathBuf>> {
-    let _ = disable_raw_mode();
-    struct RawModeRestore;
-    impl Drop for RawModeRestore {
-        fn drop(&mut self) {
-            let _ = enable_raw_mode();
-        }
-    }
-    let _restore = RawModeRestore;
-
-    let script = "Add-Type -AssemblyName System.Windows.Forms; $dlg = New-Object System.Windows.Forms.FolderBrowserDialog; $dlg.Description = 'Select music folder'; if ($dlg.ShowDialog() -eq [System.Windows.Forms.DialogResult]::OK) { [Console]::Out.WriteLine($dlg.SelectedPath) }";
-    let output = std::process::Command::new("powershell")
-        .args(["-NoProfile", "-Command", script])
-        .output()?;
-
-    if !output.status.success() {
-        return Err(anyhow::anyhow!("powerShell folder picker failed"));
-    }
-
-    let selected = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if selected.is_empty() {
-        Ok(None)
-    } else {
-        Ok(Some(PathBuf::from(selected)))
-    }
-}
-
-#[cfg(not(windows))]
-fn choose_folder_externally() -> Result<Option<PathBuf>> {
-    let home = std::env::var("HOME").unwrap_or_else(|_| String::from("/"));
-
-    let attempts: [(&str, Vec<&str>); 2] = [
-        (
-            "zenity",
-            vec![
-                "--file-selection",
-                "--directory",
-                "--title=Select music folder",
-            ],
-        ),
-        ("kdialog", vec!["--getexistingdirectory", home.as_str()]),
-    ];
-
-    let mut picker_available = false;
-    for (command, args) in attempts {
-        match try_external_folder_picker(command, &args)? {
-            FolderPickerResult::Unavailable => continue,
-            FolderPickerResult::Cancelled => {
-                picker_available = true;
-                break;
+            ActionPanelState::DuplicateClusters { selected, clusters } => {
+                if selected >= clusters.len() {
+                    panel.close();
+                    return;
+                }
+                *panel = ActionPanelState::DuplicateCluster {
+                    selected: 0,
+                    state: DuplicateClusterState {
+                        clusters,
+                        cluster: selected,
+                        keep: 0,
+                        confirm_delete: false,
+                    },
+                };
+                core.dirty = true;
             }
-            FolderPickerResult::Selected(path) => return Ok(Some(path)),
-        }
-    }
-
-    if picker_available {
+            ActionPanelState::DuplicateCluster {
+                selected,
+                mut state,
+            } => {
+                let copies = state.copies().len();
+                if selected < copies {
+                    state.keep = selected;
+                    state.confirm_delete = false;
+                    *panel = ActionPanelState::DuplicateCluster { selected, state };
+                    core.dirty = true;
+                } else if selected == copies {
+                    let removed = core.hide_library_tracks(&state.others());
+                    auto_save_state(core, &*audio);
+                    finish_duplicate_cluster(
+                        core,
+                        panel,
+                        state,
+                        format!("Removed {removed} track(s) from the library"),
+                    );
+                } else if !state.confirm_delete {
+                    core.status = format!(
+                        "Press Enter again to delete {} file(s) from disk",
+                        copies.saturating_sub(1)
+                    );
+                    state.confirm_delete = true;
+                    *panel = ActionPanelState::DuplicateCluster { selected, state };
+                    core.dirty = true;
+                } else {
+                    let (deleted, first_error) =
+                        delete_duplicate_files(core, library_runtime, &state.others());
+                    let done = match first_error {
+                        None => format!("Deleted {deleted} duplicate file(s)"),
+                        Some(err) => format!(
+                            "Deleted {deleted} duplicate file(s) ({} failed: {err})",
+                            copies.saturating_sub(1).saturating_sub(deleted)
+                        ),
+                    };
+                    finish_duplicate_cluster(core, panel, state, done);
+                }
+            }
+            ActionPanelState::StatsMerge { selected, merges } => {
+                if selected == 0 && !merges.is_empty() {
+                    core.coalesce_stats_requested = Some(merges);
+                    core.status = String::from("Merging duplicate stats...");
+                    core.dirty = true;
+                }
+                panel.close();
+            }
+            ActionPanelState::FuzzyFind {
+                selected, results, ..
+            } => {
+                if local_playback_locked_by_host_only(core) {
+                    core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
+                    core.dirty = true;
+                    panel.close();
+                    return;
+                }
+                let Some(path) = results
+                    .get(selected)
+                    .and_then(|track_idx| core.play_library_track(*track_idx))
+                else {
+                    core.status = String::from("No matching tracks");
+                    core.dirty = true;
+                    return;
+                };
+                if let Err(err) = audio.play(&path) {
+                    core.status = concise_audio_error(&err);
+                } else if let Some(runtime) = online_runtime.as_deref() {
+                    publish_current_playback_state(core, &*audio, runtime);
+                }
+                core.dirty = true;
+                panel.close();
+            }
+            ActionPanelState::QueueJump { input, .. } => {
+                if local_playback_locked_by_host_only(core) {
+                    core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
+                    core.dirty = true;
+                    panel.close();
+                    return;
+                }
+                let Some(path) = input
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|position| core.jump_to_queue_position(position))
+                else {
+                    if input.is_empty() {
+                        core.status = String::from("Type a queue position");
+                    }
+                    core.dirty = true;
+                    return;
+                };
+                if let Err(err) = audio.play(&path) {
+                    core.status = concise_audio_error(&err);
+                } else if let Some(runtime) = online_runtime.as_deref() {
+                    publish_current_playback_state(core, &*audio, runtime);
+                }
+                core.dirty = true;
+                panel.close();
+            }
+            ActionPanelState::SeekToTimestamp { input, .. } => {
+                if local_playback_locked_by_host_only(core) {
+                    core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
+                    core.dirty = true;
+                    panel.close();
+                    return;
+                }
+                if seek_to_timestamp_input(core, audio, &input) {
+                    if let Some(runtime) = online_runtime.as_deref() {
+                        publish_current_playback_state(core, &*audio, runtime);
+                    }
+                    panel.close();
+                }
+                core.dirty = true;
+            }
+            ActionPanelState::PlayPath { input, .. } => {
+                if local_playback_locked_by_host_only(core) {
+                    core.status = String::from(HOST_ONLY_LISTENER_LOCKED_STATUS);
+                    core.dirty = true;
+                    return;
+                }
+                if play_path_input(core, &input) {
+                    panel.close();
+                }
+                core.dirty = true;
+            }
+            ActionPanelState::SleepTimer { selected } => {
+                let timer_count = SLEEP_TIMER_OPTIONS_MINUTES.len();
+                if let Some(minutes) = SLEEP_TIMER_OPTIONS_MINUTES.get(selected) {
+                    core.set_sleep_timer(*minutes, Instant::now());
+                } else if selected == timer_count {
+                    core.set_sleep_after_current_track();
+                } else if selected == timer_count + 1 {
+                    core.cancel_sleep_timer();
+                } else {
+                    *panel = ActionPanelState::Root {
+                        selected: root_selected_for_action(
+                            RootActionId::SleepTimer,
+                            recent_root_actions,
+                        ),
+                        query: String::new(),
+                    };
+                    core.dirty = true;
+                    return;
+                }
+                panel.close();
+            }
+            ActionPanelState::FolderEnter { selected, folder } => {
+                match selected {
+                    0 => core.open_folder(folder),
+                    1 => {
+                        if let Some(path) = core.play_folder(&folder) {
+                            if let Err(err) = audio.play(&path) {
+                                core.status = concise_audio_error(&err);
+                            } else if let Some(runtime) = online_runtime.as_deref() {
+                                publish_current_playback_state(core, &*audio, runtime);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                core.dirty = true;
+                panel.close();
+            }
+            ActionPanelState::Closed => {}
+        },
+        _ => {}
+    }
+}
+
+#[cfg(windows)]
+fn choose_folder_externally() -> Result<Option<PathBuf>> {
+    let _ = disable_raw_mode();
+    struct RawModeRestore;
+    impl Drop for RawModeRestore {
+        fn drop(&mut self) {
+            let _ = enable_raw_mode();
+        }
+    }
+    let _restore = RawModeRestore;
+
+    let script = "Add-Type -AssemblyName System.Windows.Forms; $dlg = New-Object System.Windows.Forms.FolderBrowserDialog; $dlg.Description = 'Select music folder'; if ($dlg.ShowDialog() -eq [System.Windows.Forms.DialogResult]::OK) { [Console]::Out.WriteLine($dlg.SelectedPath) }";
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-Command", script])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("powerShell folder picker failed"));
+    }
+
+    let selected = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if selected.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(PathBuf::from(selected)))
+    }
+}
+
+#[cfg(not(windows))]
+fn choose_folder_externally() -> Result<Option<PathBuf>> {
+    let home = std::env::var("HOME").unwrap_or_else(|_| String::from("/"));
+
+    let attempts: [(&str, Vec<&str>); 2] = [
+        (
+            "zenity",
+            vec![
+                "--file-selection",
+                "--directory",
+                "--title=Select music folder",
+            ],
+        ),
+        ("kdialog", vec!["--getexistingdirectory", home.as_str()]),
+    ];
+
+    let mut picker_available = false;
+    for (command, args) in attempts {
+        match try_external_folder_picker(command, &args)? {
+            FolderPickerResult::Unavailable => continue,
+            FolderPickerResult::Cancelled => {
+                picker_available = true;
+                break;
+            }
+            FolderPickerResult::Selected(path) => return Ok(Some(path)),
+        }
+    }
+
+    if picker_available {
         Ok(None)
     } else {
         Err(anyhow::anyhow!(
@@ -7928,6 +11847,9 @@ mod tests {
     use crate::audio::AudioEngine;
     use crate::model::PersistedState;
     use crate::model::Track;
+    use crate::model::{CrossfadeCurve, ShuffleMode};
+    use crate::player::ActiveListenSession;
+    use crate::stats::ListenSessionRecord;
     use std::path::{Path, PathBuf};
     use std::time::{Duration, Instant};
 
@@ -7946,7 +11868,19 @@ mod tests {
         loudness_normalization: bool,
         crossfade_seconds: u16,
         volume: f32,
+        fade_out_gain: f32,
         fail_play: bool,
+        failing_outputs: Vec<String>,
+        level: Option<f32>,
+        equalizer: Vec<(f32, f32)>,
+        gapless: bool,
+        playback_speed: f32,
+        muted: bool,
+        ab_loop: Option<(Duration, Duration)>,
+        crossfade_curve: CrossfadeCurve,
+        device_lost: bool,
+        balance: f32,
+        mono: bool,
     }
 
     impl TestAudioEngine {
@@ -7966,7 +11900,19 @@ mod tests {
                 loudness_normalization: false,
                 crossfade_seconds: 0,
                 volume: 1.0,
+                fade_out_gain: 1.0,
                 fail_play: false,
+                failing_outputs: Vec::new(),
+                level: None,
+                equalizer: Vec::new(),
+                gapless: false,
+                playback_speed: 1.0,
+                muted: false,
+                ab_loop: None,
+                crossfade_curve: CrossfadeCurve::Linear,
+                device_lost: false,
+                balance: 0.0,
+                mono: false,
             }
         }
 
@@ -7986,7 +11932,19 @@ mod tests {
                 loudness_normalization: false,
                 crossfade_seconds: 0,
                 volume: 1.0,
+                fade_out_gain: 1.0,
                 fail_play: false,
+                failing_outputs: Vec::new(),
+                level: None,
+                equalizer: Vec::new(),
+                gapless: false,
+                playback_speed: 1.0,
+                muted: false,
+                ab_loop: None,
+                crossfade_curve: CrossfadeCurve::Linear,
+                device_lost: false,
+                balance: 0.0,
+                mono: false,
             }
         }
     }
@@ -7997,6 +11955,7 @@ mod tests {
             local_nickname: String::from("listener"),
             home_server_addr: String::from("127.0.0.1:7878"),
             home_server_connected: false,
+            local_home_server: None,
             nickname_configured: true,
             last_transport_seq: 0,
             join_prompt_active: false,
@@ -8023,10 +11982,16 @@ mod tests {
             pending_join_invite_code: String::new(),
             room_code_revealed: false,
             host_invite_modal_active: false,
+            host_invite_qr_only: false,
+            host_invite_qr_rows: None,
             host_invite_code: String::new(),
             host_invite_button: HostInviteModalButton::Copy,
+            chat_input_active: false,
+            chat_input: String::new(),
+            skip_requested: false,
             streamed_track_cache: HashMap::new(),
             pending_stream_path: None,
+            stream_progress: None,
             remote_logical_track: None,
             remote_track_title: None,
             remote_track_artist: None,
@@ -8034,7 +11999,9 @@ mod tests {
             remote_provider_track_id: None,
             last_remote_transport_origin: None,
             last_periodic_sync_at: Instant::now(),
+            last_drift_report_at: None,
             online_playback_source: OnlinePlaybackSource::LocalQueue,
+            sync_debug_log: None,
         }
     }
 
@@ -8097,10 +12064,16 @@ mod tests {
 
         assert_eq!(
             next_stream_quality_for_local_host(&session),
-            Some(crate::online::StreamQuality::Balanced)
+            Some(crate::online::StreamQuality::High)
         );
 
         session.quality = crate::online::StreamQuality::Balanced;
+        assert_eq!(
+            next_stream_quality_for_local_host(&session),
+            Some(crate::online::StreamQuality::Low)
+        );
+
+        session.quality = crate::online::StreamQuality::Low;
         assert_eq!(
             next_stream_quality_for_local_host(&session),
             Some(crate::online::StreamQuality::Lossless)
@@ -8167,6 +12140,7 @@ mod tests {
             ping_ms: 0,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
         session
     }
@@ -8220,6 +12194,7 @@ mod tests {
         fn stop(&mut self) {
             self.stopped = true;
             self.current = None;
+            self.queued = None;
             self.finished = false;
             self.position = None;
         }
@@ -8263,6 +12238,42 @@ mod tests {
             self.volume = volume.clamp(0.0, MAX_VOLUME);
         }
 
+        fn fade_out_gain(&self) -> f32 {
+            self.fade_out_gain
+        }
+
+        fn set_fade_out_gain(&mut self, gain: f32) {
+            self.fade_out_gain = gain.clamp(0.0, 1.0);
+        }
+
+        fn mute(&mut self) {
+            self.muted = true;
+        }
+
+        fn unmute(&mut self) {
+            self.muted = false;
+        }
+
+        fn is_muted(&self) -> bool {
+            self.muted
+        }
+
+        fn ab_loop(&self) -> Option<(Duration, Duration)> {
+            self.ab_loop
+        }
+
+        fn set_ab_loop(&mut self, bounds: Option<(Duration, Duration)>) {
+            self.ab_loop = bounds;
+        }
+
+        fn crossfade_curve(&self) -> CrossfadeCurve {
+            self.crossfade_curve
+        }
+
+        fn set_crossfade_curve(&mut self, curve: CrossfadeCurve) {
+            self.crossfade_curve = curve;
+        }
+
         fn output_name(&self) -> Option<String> {
             Some(
                 self.selected_output
@@ -8273,9 +12284,34 @@ mod tests {
 
         fn reload_driver(&mut self) -> Result<()> {
             self.reload_calls = self.reload_calls.saturating_add(1);
+            self.device_lost = false;
             Ok(())
         }
 
+        fn device_lost(&self) -> bool {
+            self.device_lost
+        }
+
+        fn announce_stream_duration(&mut self, duration: Duration) {
+            self.duration.get_or_insert(duration);
+        }
+
+        fn balance(&self) -> f32 {
+            self.balance
+        }
+
+        fn set_balance(&mut self, balance: f32) {
+            self.balance = balance;
+        }
+
+        fn mono(&self) -> bool {
+            self.mono
+        }
+
+        fn set_mono(&mut self, enabled: bool) {
+            self.mono = enabled;
+        }
+
         fn available_outputs(&self) -> Vec<String> {
             self.outputs.clone()
         }
@@ -8290,7 +12326,13 @@ mod tests {
             {
                 return Err(anyhow::anyhow!("audio output device not found: {name}"));
             }
+            if let Some(name) =
+                output.filter(|name| self.failing_outputs.iter().any(|entry| entry == *name))
+            {
+                return Err(anyhow::anyhow!("failed to open audio output: {name}"));
+            }
             self.selected_output = output.map(ToOwned::to_owned);
+            self.device_lost = false;
             Ok(())
         }
 
@@ -8317,6 +12359,39 @@ mod tests {
         fn is_finished(&self) -> bool {
             self.finished
         }
+
+        fn output_level(&self) -> Option<f32> {
+            self.level
+        }
+
+        fn set_equalizer(&mut self, bands: &[(f32, f32)]) {
+            self.equalizer = bands.to_vec();
+        }
+
+        fn equalizer(&self) -> Vec<(f32, f32)> {
+            self.equalizer.clone()
+        }
+
+        fn gapless(&self) -> bool {
+            self.gapless
+        }
+
+        fn set_gapless(&mut self, enabled: bool) {
+            self.gapless = enabled;
+        }
+
+        fn queue_gapless(&mut self, path: &Path) -> Result<()> {
+            self.queued = Some(path.to_path_buf());
+            Ok(())
+        }
+
+        fn playback_speed(&self) -> f32 {
+            self.playback_speed
+        }
+
+        fn set_playback_speed(&mut self, speed: f32) {
+            self.playback_speed = speed;
+        }
     }
 
     #[test]
@@ -8407,6 +12482,7 @@ mod tests {
             ping_ms: 0,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
         session
             .shared_queue
@@ -8570,6 +12646,21 @@ mod tests {
         assert!(matches!(panel, ActionPanelState::Closed));
     }
 
+    #[test]
+    fn tag_backup_row_reports_the_backup_folder_size() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(dir.path().join("a.mp3"), vec![0_u8; 1536]).expect("write a");
+        fs::write(dir.path().join("b.flac"), vec![0_u8; 512]).expect("write b");
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tag_backup_dir = Some(dir.path().to_path_buf());
+
+        assert!(!core.backup_before_tag_writes);
+        assert_eq!(core.tag_backup_disk_usage(), 2048);
+        assert_eq!(disk_usage_label(2048), "2.0 KB");
+        assert_eq!(disk_usage_label(300), "300 B");
+        assert_eq!(disk_usage_label(5 * 1024 * 1024 * 1024), "5.0 GB");
+    }
+
     #[test]
     fn metadata_editor_action_opens_for_selected_track() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -8593,8 +12684,10 @@ mod tests {
                 ref state,
             } => {
                 let options = state.options();
-                assert_eq!(options.len(), 7);
-                assert_eq!(options[5], "Copy now playing cover art to selected track");
+                assert_eq!(options.len(), 13);
+                assert_eq!(options[7], "Copy now playing cover art to selected track");
+                assert_eq!(options[8], "Download cover art and embed");
+                assert!(options[11].starts_with("Back up originals before writing: Off ("));
             }
             _ => panic!("expected metadata editor"),
         }
@@ -8660,7 +12753,13 @@ mod tests {
                 title_input: String::new(),
                 artist_input: String::new(),
                 album_input: String::new(),
+                genre_input: String::new(),
+                year_input: String::new(),
+                crossfade_disabled: false,
                 confirm_all_songs_cover_copy: true,
+                confirm_tag_write: None,
+                backup_before_tag_writes: true,
+                tag_backup_bytes: 0,
             },
         };
 
@@ -8683,31 +12782,388 @@ mod tests {
     }
 
     #[test]
-    fn quick_playlist_add_opens_picker_without_playlists() {
+    fn metadata_editor_batch_artist_write_confirms_and_counts_failures() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
         let mut audio = NullAudioEngine::new();
-        let mut panel = ActionPanelState::Closed;
+        let mut panel = ActionPanelState::MetadataEditor {
+            selected: 3,
+            state: MetadataEditorState {
+                selected_track_path: None,
+                copy_target_label: String::from("current folder"),
+                copy_target_paths: vec![
+                    PathBuf::from("missing-a.mp3"),
+                    PathBuf::from("missing-b.mp3"),
+                ],
+                title_input: String::new(),
+                artist_input: String::new(),
+                album_input: String::new(),
+                genre_input: String::new(),
+                year_input: String::new(),
+                crossfade_disabled: false,
+                confirm_all_songs_cover_copy: false,
+                confirm_tag_write: None,
+                backup_before_tag_writes: false,
+                tag_backup_bytes: 0,
+            },
+        };
 
-        apply_quick_action(
-            QuickActionId::SelectedToPlaylist,
-            &mut core,
-            &mut audio,
-            &mut panel,
-            None,
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.status, "Type an artist or album to apply");
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Up);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Up);
+        for ch in "Band".chars() {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char(ch));
+        }
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(
+            core.status,
+            "Press Enter again to write artist/album to 2 tracks in current folder"
+        );
+        let ActionPanelState::MetadataEditor { ref state, .. } = panel else {
+            panic!("expected metadata editor");
+        };
+        assert_eq!(state.options()[1], "Artist for all: Band");
+        assert_eq!(
+            state.options()[3],
+            "Confirm: write artist/album to 2 tracks in current folder"
+        );
+        assert_eq!(
+            state.batch_metadata_edit(),
+            Some(MetadataEdit {
+                artist: Some(String::from("Band")),
+                ..MetadataEdit::default()
+            })
         );
 
-        assert!(matches!(panel, ActionPanelState::PlaylistAdd { .. }));
-        let view = panel.to_view(&core, &audio, &[]).expect("picker view");
-        assert_eq!(view.options, vec![String::from("[+] Create new playlist")]);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(matches!(panel, ActionPanelState::Closed));
+        assert_eq!(
+            core.status,
+            "Wrote artist/album to 0 tracks in current folder (2 failed: track file not found)"
+        );
     }
 
     #[test]
-    fn quick_now_playing_add_opens_picker_without_playlists() {
+    fn metadata_editor_cover_download_runs_as_background_request() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
-        let mut audio = TestAudioEngine::new();
-        audio.current = Some(PathBuf::from("now.mp3"));
-        let mut panel = ActionPanelState::Closed;
-
+        let mut audio = NullAudioEngine::new();
+        let path = PathBuf::from("song.mp3");
+        let state = MetadataEditorState {
+            selected_track_path: Some(path.clone()),
+            copy_target_label: String::from("selected track"),
+            copy_target_paths: vec![path.clone()],
+            title_input: String::from("Song"),
+            artist_input: String::from("Band"),
+            album_input: String::new(),
+            genre_input: String::new(),
+            year_input: String::new(),
+            crossfade_disabled: false,
+            confirm_all_songs_cover_copy: false,
+            confirm_tag_write: None,
+            backup_before_tag_writes: true,
+            tag_backup_bytes: 0,
+        };
+        let mut panel = ActionPanelState::MetadataEditor {
+            selected: 8,
+            state: state.clone(),
+        };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.status, "Cover art lookup needs an artist and album");
+        assert!(core.cover_art_download_requested.is_none());
+
+        panel = ActionPanelState::MetadataEditor {
+            selected: 8,
+            state: MetadataEditorState {
+                album_input: String::from(" Debut "),
+                ..state
+            },
+        };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(matches!(panel, ActionPanelState::Closed));
+        assert_eq!(core.status, "Downloading cover art for Band - Debut...");
+        let request = core.cover_art_download_requested.take().expect("request");
+        assert_eq!(request.album, "Debut");
+
+        let (tx, rx) = mpsc::channel();
+        let mut runtime = CoverArtDownloadRuntime {
+            active: Some(CoverArtDownloadTask { request, rx }),
+        };
+        let mut library_runtime = LibraryRuntime {
+            active_scan: None,
+            next_scan_id: 1,
+            index: LibraryIndex::default(),
+            change_watch: TrackChangeWatch::default(),
+        };
+        poll_cover_art_download(&mut core, &mut runtime, &mut library_runtime);
+        assert!(runtime.active.is_some());
+
+        tx.send(Ok(None)).expect("send");
+        poll_cover_art_download(&mut core, &mut runtime, &mut library_runtime);
+        assert!(runtime.active.is_none());
+        assert_eq!(core.status, "No cover art found online for Band - Debut");
+    }
+
+    #[test]
+    fn missing_lyrics_prompt_fetches_online_and_saves_the_sidecar() {
+        let path = PathBuf::from("lyrics-fetch-test/missing-song.mp3");
+        let mut core = TuneCore::from_persisted_with_tracks(
+            PersistedState::default(),
+            vec![Track {
+                path: path.clone(),
+                title: String::from("Song"),
+                artist: Some(String::from("Band")),
+                album: None,
+            }],
+        );
+        let audio = NullAudioEngine::new();
+        core.header_section = HeaderSection::Lyrics;
+        core.sync_lyrics_for_track(Some(&path));
+        assert!(core.lyrics_missing_prompt);
+
+        let key = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE);
+        assert!(handle_lyrics_inline_input(&mut core, &audio, key));
+        assert_eq!(core.status, "Fetching lyrics for Band - Song...");
+        let request = core.lyrics_fetch_requested.take().expect("request");
+        assert_eq!(request.track_path, path);
+
+        let (tx, rx) = mpsc::channel();
+        let mut runtime = LyricsFetchRuntime {
+            active: Some(LyricsFetchTask { request, rx }),
+        };
+        poll_lyrics_fetch(&mut core, &mut runtime);
+        assert!(runtime.active.is_some());
+
+        tx.send(Ok(Some(lyrics::parse_lrc("[00:01.00]hello\n"))))
+            .expect("send");
+        poll_lyrics_fetch(&mut core, &mut runtime);
+        assert!(runtime.active.is_none());
+        assert!(core.status.starts_with("Saved synced lyrics to "));
+        assert!(!core.lyrics_missing_prompt);
+        assert_eq!(core.lyrics.as_ref().map(|doc| doc.lines.len()), Some(1));
+        let saved = lyrics::sidecar_lrc_path(&path).expect("sidecar path");
+        assert_eq!(
+            std::fs::read_to_string(&saved).expect("sidecar"),
+            "[00:01.00]hello\n"
+        );
+        let _ = std::fs::remove_file(saved);
+    }
+
+    #[test]
+    fn duplicate_cleanup_hides_or_deletes_the_copies_not_kept() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let tracks: Vec<Track> = ["a/song.mp3", "b/song.mp3", "a/x.mp3", "b/x.mp3"]
+            .into_iter()
+            .map(|name| {
+                let path = temp.path().join(name);
+                fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+                fs::write(&path, b"x").expect("write");
+                let stem = if name.ends_with("song.mp3") {
+                    "Song"
+                } else {
+                    "X"
+                };
+                Track {
+                    path,
+                    title: String::from(stem),
+                    artist: Some(String::from("Band")),
+                    album: None,
+                }
+            })
+            .collect();
+        let [song_a, song_b, x_a, x_b] = [0, 1, 2, 3].map(|idx| tracks[idx].path.clone());
+        let mut core =
+            TuneCore::from_persisted_with_tracks(PersistedState::default(), tracks.clone());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::Root {
+            selected: root_selected(RootActionId::FindDuplicates),
+            query: String::new(),
+        };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.status, "Scanning for duplicates...");
+        assert!(matches!(panel, ActionPanelState::Closed));
+        let mut runtime = DuplicateScanRuntime { active: None };
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let clusters = loop {
+            if let Some(clusters) = poll_duplicate_scan(&mut core, &mut runtime) {
+                break clusters;
+            }
+            assert!(Instant::now() < deadline, "duplicate scan did not finish");
+            std::thread::sleep(Duration::from_millis(5));
+        };
+        open_duplicate_clusters(&mut core, &mut panel, clusters);
+        assert_eq!(
+            core.status,
+            "Found 2 duplicated song(s); 2 extra file(s) can be removed"
+        );
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        let ActionPanelState::DuplicateCluster { ref state, .. } = panel else {
+            panic!("expected duplicate cluster");
+        };
+        assert_eq!(state.options()[1], format!("[KEEP] {}", song_b.display()));
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert_eq!(
+            core.status,
+            "Removed 1 track(s) from the library; 1 extra file(s) left"
+        );
+        assert_eq!(core.title_for_path(&song_a), None);
+        assert!(song_a.exists());
+        core.replace_library_tracks(tracks);
+        assert_eq!(core.title_for_path(&song_a), None);
+        assert_eq!(core.persisted_state().hidden_tracks, vec![song_a]);
+
+        assert!(matches!(
+            panel,
+            ActionPanelState::DuplicateClusters { selected: 0, .. }
+        ));
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        for _ in 0..3 {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        }
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(
+            core.status,
+            "Press Enter again to delete 1 file(s) from disk"
+        );
+        assert!(x_b.exists());
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(
+            core.status,
+            "Deleted 1 duplicate file(s); 0 extra file(s) left"
+        );
+        assert!(matches!(panel, ActionPanelState::Closed));
+        assert!(!x_b.exists());
+        assert!(x_a.exists());
+        assert_eq!(core.title_for_path(&x_b), None);
+    }
+
+    #[test]
+    fn metadata_editor_save_asks_for_confirmation_with_path() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = NullAudioEngine::new();
+        let path = PathBuf::from("missing-song.mp3");
+        let mut panel = ActionPanelState::MetadataEditor {
+            selected: 5,
+            state: MetadataEditorState {
+                selected_track_path: Some(path.clone()),
+                copy_target_label: String::from("selected track"),
+                copy_target_paths: vec![path.clone()],
+                title_input: String::from("Song"),
+                artist_input: String::new(),
+                album_input: String::new(),
+                genre_input: String::new(),
+                year_input: String::new(),
+                crossfade_disabled: false,
+                confirm_all_songs_cover_copy: false,
+                confirm_tag_write: None,
+                backup_before_tag_writes: true,
+                tag_backup_bytes: 0,
+            },
+        };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert_eq!(
+            core.status,
+            "Press Enter again to save tags in missing-song.mp3"
+        );
+        let ActionPanelState::MetadataEditor { ref state, .. } = panel else {
+            panic!("expected metadata editor");
+        };
+        assert_eq!(state.options()[5], "Confirm: save tags to missing-song.mp3");
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert!(core.status.starts_with("Metadata save failed"));
+    }
+
+    #[test]
+    fn metadata_editor_genre_and_year_rows_leave_blank_fields_alone() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = NullAudioEngine::new();
+        let path = PathBuf::from("missing-song.mp3");
+        let mut panel = ActionPanelState::MetadataEditor {
+            selected: 3,
+            state: MetadataEditorState {
+                selected_track_path: Some(path.clone()),
+                copy_target_label: String::from("selected track"),
+                copy_target_paths: vec![path],
+                title_input: String::from("Song"),
+                artist_input: String::new(),
+                album_input: String::from("  "),
+                genre_input: String::new(),
+                year_input: String::new(),
+                crossfade_disabled: false,
+                confirm_all_songs_cover_copy: false,
+                confirm_tag_write: None,
+                backup_before_tag_writes: true,
+                tag_backup_bytes: 0,
+            },
+        };
+
+        for ch in "Jazz".chars() {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char(ch));
+        }
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        for ch in "19x6-51".chars() {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char(ch));
+        }
+
+        let ActionPanelState::MetadataEditor {
+            selected,
+            ref state,
+        } = panel
+        else {
+            panic!("expected metadata editor");
+        };
+        assert_eq!(selected, 4);
+        assert_eq!(state.options()[3], "Genre: Jazz");
+        assert_eq!(state.options()[4], "Year: 1965");
+        assert_eq!(
+            state.metadata_edit(),
+            MetadataEdit {
+                title: Some(String::from("Song")),
+                artist: None,
+                album: None,
+                genre: Some(String::from("Jazz")),
+                year: Some(String::from("1965")),
+            }
+        );
+    }
+
+    #[test]
+    fn quick_playlist_add_opens_picker_without_playlists() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = NullAudioEngine::new();
+        let mut panel = ActionPanelState::Closed;
+
+        apply_quick_action(
+            QuickActionId::SelectedToPlaylist,
+            &mut core,
+            &mut audio,
+            &mut panel,
+            None,
+        );
+
+        assert!(matches!(panel, ActionPanelState::PlaylistAdd { .. }));
+        let view = panel.to_view(&core, &audio, &[]).expect("picker view");
+        assert_eq!(view.options, vec![String::from("[+] Create new playlist")]);
+    }
+
+    #[test]
+    fn quick_now_playing_add_opens_picker_without_playlists() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("now.mp3"));
+        let mut panel = ActionPanelState::Closed;
+
         apply_quick_action(
             QuickActionId::NowPlayingToPlaylist,
             &mut core,
@@ -8859,7 +13315,7 @@ mod tests {
         assert!(
             core.folders
                 .iter()
-                .any(|folder| crate::config::normalize_path(folder) == expected)
+                .any(|folder| crate::config::normalize_path(&folder.path) == expected)
         );
         assert!(matches!(panel, ActionPanelState::Closed));
     }
@@ -8867,7 +13323,11 @@ mod tests {
     #[test]
     fn action_panel_remove_directory_from_list() {
         let mut state = PersistedState::default();
-        state.folders.push(PathBuf::from(r"E:\LOCALMUSIC"));
+        state
+            .folders
+            .push(crate::model::LibraryFolder::new(PathBuf::from(
+                r"E:\LOCALMUSIC",
+            )));
         let mut core = TuneCore::from_persisted(state);
         let mut audio = NullAudioEngine::new();
         let mut panel = ActionPanelState::Root {
@@ -8883,6 +13343,80 @@ mod tests {
         assert!(matches!(panel, ActionPanelState::Closed));
     }
 
+    #[test]
+    fn library_file_change_reloads_metadata_after_debounce() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("new-title.mp3");
+        fs::write(&path, b"x").expect("write track");
+        let track = crate::model::Track {
+            path: path.clone(),
+            title: String::from("Old"),
+            artist: None,
+            album: None,
+        };
+        let mut core =
+            TuneCore::from_persisted_with_tracks(PersistedState::default(), vec![track.clone()]);
+        let mut library_runtime = LibraryRuntime {
+            active_scan: None,
+            next_scan_id: 1,
+            index: LibraryIndex {
+                tracks: vec![library::LibraryIndexEntry::from_track(&track)],
+            },
+            change_watch: TrackChangeWatch::default(),
+        };
+        let sweep = |core: &mut TuneCore, library_runtime: &mut LibraryRuntime, now: Instant| {
+            poll_library_file_changes(core, library_runtime, now);
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while library_runtime.change_watch.sweep.is_some() {
+                assert!(Instant::now() < deadline, "sweep did not finish");
+                std::thread::sleep(Duration::from_millis(5));
+                poll_library_file_changes(core, library_runtime, now);
+            }
+        };
+        let start = Instant::now();
+        sweep(&mut core, &mut library_runtime, start);
+        assert!(library_runtime.change_watch.pending.is_empty());
+
+        fs::write(&path, b"retagged").expect("rewrite track");
+        let detected = start + TRACK_CHANGE_POLL_INTERVAL;
+        sweep(&mut core, &mut library_runtime, detected);
+        assert_eq!(library_runtime.change_watch.pending.len(), 1);
+        assert_eq!(core.title_for_path(&path).as_deref(), Some("Old"));
+
+        sweep(
+            &mut core,
+            &mut library_runtime,
+            detected + TRACK_CHANGE_POLL_INTERVAL,
+        );
+        assert!(library_runtime.change_watch.pending.is_empty());
+        assert_eq!(core.title_for_path(&path).as_deref(), Some("new-title"));
+        assert_eq!(
+            library_runtime.index.tracks[0].fingerprint,
+            library::track_fingerprint(&path)
+        );
+    }
+
+    #[test]
+    fn action_panel_manage_directories_space_toggles_enabled() {
+        let mut state = PersistedState::default();
+        state
+            .folders
+            .push(crate::model::LibraryFolder::new(PathBuf::from(
+                r"E:\LOCALMUSIC",
+            )));
+        let mut core = TuneCore::from_persisted(state);
+        let mut audio = NullAudioEngine::new();
+        let mut panel = ActionPanelState::RemoveDirectory { selected: 0 };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char(' '));
+        assert!(!core.folders[0].enabled);
+        assert_eq!(core.status, "Folder disabled");
+        assert!(matches!(panel, ActionPanelState::RemoveDirectory { .. }));
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char(' '));
+        assert!(core.folders[0].enabled);
+    }
+
     #[test]
     fn action_panel_create_playlist_from_input() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -8986,72 +13520,579 @@ mod tests {
     }
 
     #[test]
-    fn playback_settings_toggle_loudness_and_crossfade() {
+    fn ctrl_s_is_not_the_output_shortcut() {
+        assert!(output_cycle_key(&KeyEvent::new(
+            KeyCode::Char('s'),
+            KeyModifiers::NONE
+        )));
+        assert!(output_cycle_key(&KeyEvent::new(
+            KeyCode::Char('S'),
+            KeyModifiers::SHIFT
+        )));
+        assert!(!output_cycle_key(&KeyEvent::new(
+            KeyCode::Char('s'),
+            KeyModifiers::CONTROL
+        )));
+    }
+
+    #[test]
+    fn output_shortcut_cycles_and_wraps_to_system_default() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
         let mut audio = TestAudioEngine::new();
-        let mut panel = ActionPanelState::Root {
-            selected: root_selected(RootActionId::PlaybackSettings),
-            query: String::new(),
-        };
+        let outputs = audio.available_outputs();
+        assert!(!outputs.is_empty());
 
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
-        assert!(matches!(panel, ActionPanelState::PlaybackSettings { .. }));
+        for output in &outputs {
+            cycle_output_device(&mut core, &mut audio);
+            assert_eq!(audio.selected_output_device().as_ref(), Some(output));
+        }
+        cycle_output_device(&mut core, &mut audio);
+        assert_eq!(audio.selected_output_device(), None);
+        assert!(core.status.starts_with("Output: "));
+    }
 
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+    #[test]
+    fn output_shortcut_skips_devices_that_fail_to_open() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.failing_outputs = vec![String::from("Headphones")];
+
+        cycle_output_device(&mut core, &mut audio);
+        assert_eq!(
+            audio.selected_output_device(),
+            Some(String::from("Speakers"))
+        );
+        assert_eq!(core.status, "Output: Speakers");
+    }
+
+    #[test]
+    fn loudness_shortcut_toggles_engine_and_reports_state() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+
+        toggle_loudness_normalization(&mut core, &mut audio);
         assert!(core.loudness_normalization);
         assert!(audio.loudness_normalization());
+        assert_eq!(core.status, "Loudness normalization: On");
 
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
-        assert_eq!(core.crossfade_seconds, 2);
-        assert_eq!(audio.crossfade_seconds(), 2);
-
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
-        assert_eq!(core.scrub_seconds, 10);
+        toggle_loudness_normalization(&mut core, &mut audio);
+        assert!(!audio.loudness_normalization());
+        assert_eq!(core.status, "Loudness normalization: Off");
+        assert!(online_tab_allows_global_shortcut(KeyCode::Char('g')));
+    }
 
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
-        assert!(!core.stats_enabled);
+    #[test]
+    fn playback_settings_toggles_crossfade_only_when_shuffling() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlaybackSettings { selected: 12 };
 
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
         handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
-        assert_eq!(core.stats_top_songs_count, 12);
+        assert!(core.crossfade_only_when_shuffling);
+        assert_eq!(core.status, "Crossfade only when shuffling: On");
+        assert!(core.persisted_state().crossfade_only_when_shuffling);
 
-        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
         handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
-        assert_eq!(core.fallback_cover_template, CoverArtTemplate::Aurora);
+        assert!(!core.crossfade_only_when_shuffling);
     }
 
     #[test]
-    fn online_delay_settings_cycles_sync_correction_threshold() {
+    fn folder_enter_prompt_plays_chosen_folder() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![Track {
+            path: PathBuf::from("/music/album/01.mp3"),
+            title: String::from("One"),
+            artist: None,
+            album: None,
+        }];
+        core.folder_enter_behavior = FolderEnterBehavior::Ask;
+        core.browser_entries = vec![crate::core::BrowserEntry {
+            kind: BrowserEntryKind::Folder,
+            path: PathBuf::from("/music/album"),
+            label: String::from("album"),
+        }];
+        core.selected_browser = 0;
         let mut audio = TestAudioEngine::new();
-        let mut panel = ActionPanelState::OnlineDelaySettings { selected: 4 };
+        let mut panel = ActionPanelState::Closed;
 
+        assert!(open_folder_enter_prompt(&core, &mut panel));
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
         handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
 
-        assert_eq!(core.online_sync_correction_threshold_ms, 400);
-        assert_eq!(core.status, "Online sync correction threshold: 400ms");
+        assert!(matches!(panel, ActionPanelState::Closed));
+        assert_eq!(audio.played, vec![PathBuf::from("/music/album/01.mp3")]);
+        assert_eq!(core.queue, vec![0]);
     }
 
     #[test]
-    fn playback_settings_cover_template_cycle_stays_music_note() {
+    fn window_title_shows_track_only_when_enabled() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
-        core.fallback_cover_template = CoverArtTemplate::Aurora;
+        core.tracks = vec![Track {
+            path: PathBuf::from("song.mp3"),
+            title: String::from("Song"),
+            artist: Some(String::from("Band")),
+            album: None,
+        }];
         let mut audio = TestAudioEngine::new();
-        let mut panel = ActionPanelState::PlaybackSettings { selected: 7 };
+        audio.current = Some(PathBuf::from("song.mp3"));
+        assert_eq!(window_title(&core, &audio), "TuneTUI");
 
+        let mut panel = ActionPanelState::PlaybackSettings { selected: 15 };
         handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(core.persisted_state().window_title_shows_track);
+        assert_eq!(window_title(&core, &audio), "TuneTUI — Band - Song");
 
-        assert_eq!(core.fallback_cover_template, CoverArtTemplate::Aurora);
-        assert_eq!(core.status, "Missing cover fallback: Music Note");
+        audio.current = None;
+        assert_eq!(window_title(&core, &audio), "TuneTUI");
     }
 
     #[test]
-    fn stats_left_on_range_cycles_back() {
+    fn queue_jump_plays_position_and_rejects_out_of_range() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = ["a.mp3", "b.mp3", "c.mp3"]
+            .into_iter()
+            .map(|path| Track {
+                path: PathBuf::from(path),
+                title: path.to_string(),
+                artist: None,
+                album: None,
+            })
+            .collect();
+        core.queue = vec![2, 0, 1];
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::QueueJump {
+            selected: 0,
+            input: String::new(),
+        };
+
+        for key in [KeyCode::Char('9'), KeyCode::Char('x'), KeyCode::Enter] {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, key);
+        }
+        assert_eq!(core.status, "Queue position must be 1-3");
+        assert!(audio.played.is_empty());
+
+        for key in [KeyCode::Backspace, KeyCode::Char('2'), KeyCode::Enter] {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, key);
+        }
+        assert!(matches!(panel, ActionPanelState::Closed));
+        assert_eq!(audio.played, vec![PathBuf::from("a.mp3")]);
+        assert_eq!(core.current_queue_index, Some(1));
+        assert_eq!(core.status, "Jumped to queue position 2/3");
+    }
+
+    #[test]
+    fn fuzzy_find_updates_live_queues_with_alt_enter_and_plays_with_enter() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = [("a.mp3", "Alpha"), ("h.mp3", "Hejira"), ("j.mp3", "Jolene")]
+            .into_iter()
+            .map(|(path, title)| Track {
+                path: PathBuf::from(path),
+                title: title.to_string(),
+                artist: None,
+                album: None,
+            })
+            .collect();
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::FuzzyFind {
+            selected: 0,
+            query: String::new(),
+            results: Vec::new(),
+        };
+
+        // hjkl are typed into the query rather than moving the selection.
+        for key in [KeyCode::Char('h'), KeyCode::Char('j')] {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, key);
+        }
+        let view = panel.to_view(&core, &audio, &[]).expect("view");
+        assert_eq!(view.search_query.as_deref(), Some("hj"));
+        assert_eq!(view.options, vec![String::from("Hejira")]);
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Backspace);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Backspace);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char('e'));
+        let view = panel.to_view(&core, &audio, &[]).expect("view");
+        assert_eq!(view.options, vec!["Hejira", "Jolene"]);
+
+        assert!(queue_fuzzy_find_selection(&mut core, &panel));
+        assert_eq!(core.queue, vec![1]);
+        assert_eq!(core.status, "Queued Hejira");
+        assert!(matches!(panel, ActionPanelState::FuzzyFind { .. }));
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(matches!(panel, ActionPanelState::Closed));
+        assert_eq!(audio.played, vec![PathBuf::from("j.mp3")]);
+        assert_eq!(core.queue, vec![0, 1, 2]);
+        assert_eq!(core.current_queue_index, Some(2));
+        assert!(!queue_fuzzy_find_selection(&mut core, &panel));
+    }
+
+    #[test]
+    fn seek_to_timestamp_parses_clamps_and_rejects_garbage() {
+        assert_eq!(parse_timestamp("1:05"), Some(Duration::from_secs(65)));
+        assert_eq!(parse_timestamp("75:30"), Some(Duration::from_secs(4_530)));
+        assert_eq!(parse_timestamp("1:02:03"), Some(Duration::from_secs(3_723)));
+        assert_eq!(parse_timestamp("1:60"), None);
+        assert_eq!(parse_timestamp("1:60:00"), None);
+        assert_eq!(parse_timestamp("::"), None);
+        assert_eq!(parse_timestamp("90"), None);
+
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("mix.mp3"));
+        audio.duration = Some(Duration::from_secs(3_900));
+        let mut panel = ActionPanelState::Closed;
+        open_seek_to_timestamp(&mut core, &audio, &mut panel);
+
+        for key in [KeyCode::Char('1'), KeyCode::Char(':'), KeyCode::Enter] {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, key);
+        }
+        assert_eq!(core.status, "Not a time: 1: (use mm:ss or h:mm:ss)");
+        assert!(matches!(panel, ActionPanelState::SeekToTimestamp { .. }));
+
+        for key in [
+            KeyCode::Char('0'),
+            KeyCode::Char('2'),
+            KeyCode::Char(':'),
+            KeyCode::Char('3'),
+            KeyCode::Char('0'),
+            KeyCode::Enter,
+        ] {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, key);
+        }
+        assert!(matches!(panel, ActionPanelState::Closed));
+        assert_eq!(audio.position, Some(Duration::from_secs(3_750)));
+        assert_eq!(core.status, "Seek 1:02:30");
+
+        assert!(seek_to_timestamp_input(&mut core, &mut audio, "99:00"));
+        assert_eq!(audio.position, Some(Duration::from_secs(3_900)));
+        assert_eq!(core.status, "Seek 1:05:00 (end of track)");
+    }
+
+    #[test]
+    fn ctrl_t_in_the_lyrics_editor_stamps_instead_of_seeking() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.position = Some(Duration::from_millis(4_200));
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        assert!(seek_to_timestamp_shortcut(&core, &ctrl_t));
+
+        core.header_section = HeaderSection::Lyrics;
+        core.lyrics_track_path = Some(PathBuf::from("song.mp3"));
+        core.lyrics = Some(lyrics::parse_plain_text("first line"));
+        assert!(seek_to_timestamp_shortcut(&core, &ctrl_t));
+
+        core.lyrics_mode = LyricsMode::Edit;
+        assert!(!seek_to_timestamp_shortcut(&core, &ctrl_t));
+        assert!(handle_lyrics_inline_input(&mut core, &audio, ctrl_t));
+        assert_eq!(
+            core.lyrics.as_ref().map(|doc| doc.lines[0].timestamp_ms),
+            Some(Some(4_200))
+        );
+    }
+
+    #[test]
+    fn playback_settings_cycles_sleep_on_silence() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlaybackSettings { selected: 13 };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.sleep_on_silence_minutes, 5);
+        assert_eq!(core.status, "Sleep on silence: 5 min");
+        assert_eq!(core.persisted_state().sleep_on_silence_minutes, 5);
+    }
+
+    #[test]
+    fn silence_watch_pauses_after_window_and_resets_on_sound_or_input() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("loop.mp3"));
+        audio.level = Some(0.0);
+        let mut watch = SilenceWatch::default();
+        let start = Instant::now();
+
+        assert!(!watch.tick(&mut core, &mut audio, start));
+        assert!(!watch.tick(&mut core, &mut audio, start + Duration::from_secs(3600)));
+
+        core.sleep_on_silence_minutes = 5;
+        assert!(!watch.tick(&mut core, &mut audio, start));
+        audio.level = Some(0.4);
+        assert!(!watch.tick(&mut core, &mut audio, start + Duration::from_secs(200)));
+        audio.level = Some(0.0);
+        assert!(!watch.tick(&mut core, &mut audio, start + Duration::from_secs(250)));
+        watch.reset();
+        assert!(!watch.tick(&mut core, &mut audio, start + Duration::from_secs(400)));
+        assert!(!audio.paused);
+
+        assert!(watch.tick(&mut core, &mut audio, start + Duration::from_secs(700)));
+        assert!(audio.paused);
+        assert_eq!(core.status, "Paused after 5 min of silence");
+    }
+
+    #[test]
+    fn sleep_timer_fades_out_and_pauses_without_touching_the_volume() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("lullaby.mp3"));
+        audio.volume = 0.8;
+        let mut timer = SleepTimer::default();
+        let start = Instant::now();
+        core.set_sleep_timer(15, start);
+        assert_eq!(core.status, "Sleep timer: pausing in 15 min");
+
+        let deadline = start + Duration::from_secs(15 * 60);
+        assert!(!timer.tick(&mut core, &mut audio, start + Duration::from_secs(60)));
+        assert_eq!(audio.fade_out_gain, 1.0);
+        assert!(!timer.tick(&mut core, &mut audio, deadline));
+        assert!(!timer.tick(
+            &mut core,
+            &mut audio,
+            deadline + Duration::from_millis(2_500)
+        ));
+        assert!((audio.fade_out_gain - 0.5).abs() < 0.01);
+        assert_eq!(audio.volume, 0.8);
+        assert_eq!(persisted_state_with_audio(&core, &audio).saved_volume, 0.8);
+        assert!(!audio.paused);
+
+        assert!(timer.tick(&mut core, &mut audio, deadline + Duration::from_secs(5)));
+        assert!(audio.paused);
+        assert_eq!(audio.fade_out_gain, 1.0);
+        assert_eq!(audio.volume, 0.8);
+        assert_eq!(core.sleep_deadline, None);
+        assert_eq!(core.status, "Sleep timer: paused");
+    }
+
+    #[test]
+    fn cancelling_the_sleep_timer_mid_fade_restores_full_gain() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("lullaby.mp3"));
+        audio.volume = 0.8;
+        let mut timer = SleepTimer::default();
+        let start = Instant::now();
+        core.sleep_deadline = Some(start);
+
+        assert!(!timer.tick(&mut core, &mut audio, start));
+        assert!(!timer.tick(&mut core, &mut audio, start + Duration::from_secs(4)));
+        assert!(audio.fade_out_gain < 1.0);
+        core.cancel_sleep_timer();
+        assert!(!timer.tick(&mut core, &mut audio, start + Duration::from_secs(5)));
+        assert_eq!(audio.fade_out_gain, 1.0);
+        assert_eq!(audio.volume, 0.8);
+        assert!(!audio.paused);
+    }
+
+    #[test]
+    fn sleep_timer_panel_sets_end_of_track_and_turns_off() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::SleepTimer { selected: 1 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(core.sleep_deadline.is_some());
+        assert!(matches!(panel, ActionPanelState::Closed));
+        assert_eq!(
+            sleep_timer_options(&core, Instant::now())[5],
+            "Turn off (pausing in 30 min)"
+        );
+
+        let mut panel = ActionPanelState::SleepTimer { selected: 4 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.sleep_deadline, None);
+        assert!(core.sleep_after_current_track);
+
+        let mut panel = ActionPanelState::SleepTimer { selected: 5 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(!core.sleep_after_current_track);
+        assert_eq!(core.status, "Sleep timer off");
+    }
+
+    #[test]
+    fn sleep_at_end_of_track_stops_instead_of_advancing() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = ["a.mp3", "b.mp3"]
+            .into_iter()
+            .map(|path| Track {
+                path: PathBuf::from(path),
+                title: path.to_string(),
+                artist: None,
+                album: None,
+            })
+            .collect();
+        core.queue = vec![0, 1];
+        core.current_queue_index = Some(0);
+        core.set_sleep_after_current_track();
+
+        let mut runtime = test_online_runtime();
+        let mut audio = TestAudioEngine::finished_with_current("a.mp3");
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert!(audio.played.is_empty());
+        assert!(audio.current_track().is_none());
+        assert!(!core.sleep_after_current_track);
+        assert_eq!(core.status, "Sleep timer: stopped at the end of the track");
+    }
+
+    #[test]
+    fn reinitialize_audio_reloads_and_restores_volume_and_position() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("song.mp3"));
+        audio.position = Some(Duration::from_secs(42));
+        audio.volume = 0.35;
+
+        reinitialize_audio(&mut core, &mut audio);
+
+        assert_eq!(audio.reload_calls, 1);
+        assert_eq!(audio.volume, 0.35);
+        assert_eq!(audio.position, Some(Duration::from_secs(42)));
+        assert_eq!(core.status, "Reinitialized display and audio");
+    }
+
+    #[test]
+    fn scrub_keys_follow_config_and_arrow_seek_mode() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        assert_eq!(
+            scrub_key_direction(&core, &audio, &key(KeyCode::Char('A'))),
+            Some(-1)
+        );
+        assert_eq!(
+            scrub_key_direction(&core, &audio, &key(KeyCode::Char('d'))),
+            Some(1)
+        );
+        assert_eq!(
+            scrub_key_direction(&core, &audio, &key(KeyCode::Left)),
+            None
+        );
+
+        core.scrub_back_key = ',';
+        core.scrub_forward_key = '.';
+        assert_eq!(
+            scrub_key_direction(&core, &audio, &key(KeyCode::Char('a'))),
+            None
+        );
+        assert_eq!(
+            scrub_key_direction(&core, &audio, &key(KeyCode::Char('.'))),
+            Some(1)
+        );
+
+        core.arrow_keys_seek = true;
+        assert_eq!(
+            scrub_key_direction(&core, &audio, &key(KeyCode::Right)),
+            None
+        );
+        audio.current = Some(PathBuf::from("song.mp3"));
+        assert_eq!(
+            scrub_key_direction(&core, &audio, &key(KeyCode::Left)),
+            Some(-1)
+        );
+        assert_eq!(
+            scrub_key_direction(&core, &audio, &key(KeyCode::Right)),
+            Some(1)
+        );
+        core.library_search_focused = true;
+        assert_eq!(
+            scrub_key_direction(&core, &audio, &key(KeyCode::Left)),
+            None
+        );
+    }
+
+    #[test]
+    fn previous_scrub_seconds_wraps_to_longest() {
+        assert_eq!(previous_scrub_seconds(10), 5);
+        assert_eq!(previous_scrub_seconds(5), 60);
+    }
+
+    #[test]
+    fn playback_settings_toggle_loudness_and_crossfade() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::Root {
+            selected: root_selected(RootActionId::PlaybackSettings),
+            query: String::new(),
+        };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(matches!(panel, ActionPanelState::PlaybackSettings { .. }));
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(core.loudness_normalization);
+        assert!(audio.loudness_normalization());
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.crossfade_seconds, 2);
+        assert_eq!(audio.crossfade_seconds(), 2);
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.scrub_seconds, 10);
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(!core.stats_enabled);
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.stats_top_songs_count, 12);
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.fallback_cover_template, CoverArtTemplate::Aurora);
+    }
+
+    #[test]
+    fn online_delay_settings_cycles_sync_correction_threshold() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::OnlineDelaySettings { selected: 4 };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert_eq!(core.online_sync_correction_threshold_ms, 400);
+        assert_eq!(core.status, "Online sync correction threshold: 400ms");
+    }
+
+    #[test]
+    fn online_settings_toggle_remembering_the_room_password() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::OnlineDelaySettings { selected: 5 };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert!(core.remember_online_password);
+        assert_eq!(core.status, "Remember room password: On");
+        assert_eq!(
+            online_delay_settings_options(&core)[5],
+            "Remember room password (encrypted): On"
+        );
+        assert!(
+            !playback_settings_options(&core)
+                .iter()
+                .any(|option| option.starts_with("Remember room password"))
+        );
+    }
+
+    #[test]
+    fn playback_settings_cover_template_cycle_stays_music_note() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.fallback_cover_template = CoverArtTemplate::Aurora;
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlaybackSettings { selected: 7 };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert_eq!(core.fallback_cover_template, CoverArtTemplate::Aurora);
+        assert_eq!(core.status, "Missing cover fallback: Music Note");
+    }
+
+    #[test]
+    fn stats_left_on_range_cycles_back() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
         core.header_section = crate::core::HeaderSection::Stats;
         core.stats_focus = crate::core::StatsFilterFocus::Range(3);
@@ -9059,7 +14100,8 @@ mod tests {
 
         assert!(handle_stats_inline_input(
             &mut core,
-            KeyEvent::new(KeyCode::Left, KeyModifiers::NONE)
+            KeyEvent::new(KeyCode::Left, KeyModifiers::NONE),
+            &[]
         ));
         assert_eq!(core.stats_range, crate::stats::StatsRange::Days7);
         assert!(matches!(
@@ -9077,7 +14119,8 @@ mod tests {
 
         assert!(handle_stats_inline_input(
             &mut core,
-            KeyEvent::new(KeyCode::Left, KeyModifiers::NONE)
+            KeyEvent::new(KeyCode::Left, KeyModifiers::NONE),
+            &[]
         ));
         assert_eq!(core.stats_scroll, 2);
         assert!(matches!(
@@ -9096,7 +14139,8 @@ mod tests {
 
         assert!(handle_stats_inline_input(
             &mut core,
-            KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT)
+            KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT),
+            &[]
         ));
         assert_eq!(core.stats_scroll, 0);
         assert!(matches!(
@@ -9293,23 +14337,21 @@ mod tests {
     }
 
     #[test]
-    fn mouse_left_click_on_volume_up_raises_volume() {
+    fn mouse_left_click_on_skips_sort_selects_skip_rate() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
         let mut audio = TestAudioEngine::new();
-        audio.set_volume(0.5);
         let mut panel = ActionPanelState::Closed;
         let mut recent_root_actions = Vec::new();
         let mut online_runtime = test_online_runtime();
-
         let mut hit_map = crate::ui::HitMap::default();
         hit_map.push(
             ratatui::prelude::Rect {
-                x: 30,
-                y: 10,
-                width: 10,
+                x: 20,
+                y: 5,
+                width: 7,
                 height: 1,
             },
-            crate::ui::HitTarget::VolumeUp,
+            crate::ui::HitTarget::StatsSort(2),
         );
 
         let mut mouse_state = MouseState::default();
@@ -9322,8 +14364,52 @@ mod tests {
             &mut online_runtime,
             MouseEvent {
                 kind: MouseEventKind::Down(MouseButton::Left),
-                column: 32,
-                row: 10,
+                column: 22,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            },
+            ratatui::prelude::Rect::default(),
+            &hit_map,
+            &mut mouse_state,
+            &mut pending_scrub_delta,
+        );
+
+        assert_eq!(core.stats_sort, crate::stats::StatsSort::SkipRate);
+        assert_eq!(core.stats_focus, crate::core::StatsFilterFocus::Sort(2));
+    }
+
+    #[test]
+    fn mouse_left_click_on_volume_up_raises_volume() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.set_volume(0.5);
+        let mut panel = ActionPanelState::Closed;
+        let mut recent_root_actions = Vec::new();
+        let mut online_runtime = test_online_runtime();
+
+        let mut hit_map = crate::ui::HitMap::default();
+        hit_map.push(
+            ratatui::prelude::Rect {
+                x: 30,
+                y: 10,
+                width: 10,
+                height: 1,
+            },
+            crate::ui::HitTarget::VolumeUp,
+        );
+
+        let mut mouse_state = MouseState::default();
+        let mut pending_scrub_delta: i64 = 0;
+        handle_mouse_with_panel(
+            &mut core,
+            &mut audio,
+            &mut panel,
+            &mut recent_root_actions,
+            &mut online_runtime,
+            MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 32,
+                row: 10,
                 modifiers: KeyModifiers::NONE,
             },
             ratatui::prelude::Rect::default(),
@@ -9510,7 +14596,8 @@ mod tests {
 
         assert!(!handle_stats_inline_input(
             &mut core,
-            KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)
+            KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            &[]
         ));
     }
 
@@ -9522,11 +14609,206 @@ mod tests {
 
         assert!(!handle_stats_inline_input(
             &mut core,
-            KeyEvent::new(KeyCode::Char('\u{3}'), KeyModifiers::NONE)
+            KeyEvent::new(KeyCode::Char('\u{3}'), KeyModifiers::NONE),
+            &[]
         ));
         assert!(core.stats_artist_filter.is_empty());
     }
 
+    #[test]
+    fn stats_forget_track_requires_confirmation() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.header_section = HeaderSection::Stats;
+        let row = |key: &str, title: &str| crate::stats::TrackStatsRow {
+            track_key: key.to_string(),
+            track_path: PathBuf::from(format!("{title}.mp3")),
+            title: title.to_string(),
+            artist: None,
+            album: None,
+            play_count: 1,
+            listen_seconds: 60,
+            last_played_epoch_seconds: 0,
+            starts: 0,
+            skips: 0,
+        };
+        let rows = vec![row("meta:a", "A"), row("meta:b", "B")];
+        let press = |core: &mut TuneCore, code| {
+            handle_stats_inline_input(core, KeyEvent::new(code, KeyModifiers::NONE), &rows)
+        };
+
+        assert!(press(&mut core, KeyCode::Tab));
+        assert!(press(&mut core, KeyCode::Down));
+        assert!(press(&mut core, KeyCode::Down));
+        assert_eq!(core.stats_focus, crate::core::StatsFilterFocus::Row(1));
+
+        assert!(press(&mut core, KeyCode::Delete));
+        assert!(core.forget_stats_track_requested.is_none());
+        assert!(press(&mut core, KeyCode::Esc));
+        assert!(press(&mut core, KeyCode::Delete));
+        assert!(core.forget_stats_track_requested.is_none());
+        assert!(press(&mut core, KeyCode::Delete));
+        assert_eq!(core.forget_stats_track_requested.as_deref(), Some("meta:b"));
+    }
+
+    #[test]
+    fn export_stats_panel_requests_the_chosen_format_and_adds_its_extension() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::StatsExport {
+            selected: 1,
+            path_input: String::new(),
+        };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.status, "Type a path to export stats to");
+        assert!(core.stats_export_requested.is_none());
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Up);
+        for ch in "'/tmp/my stats'".chars() {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char(ch));
+        }
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(matches!(panel, ActionPanelState::Closed));
+        assert_eq!(
+            core.stats_export_requested,
+            Some((
+                PathBuf::from("/tmp/my stats.json"),
+                crate::stats::StatsExportFormat::Json
+            ))
+        );
+        assert_eq!(core.status, "Exporting stats as JSON...");
+    }
+
+    #[test]
+    fn export_m3u_panel_writes_chosen_playlist_into_a_folder() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.playlists.insert(
+            String::from("Road: Trip"),
+            crate::model::Playlist {
+                tracks: vec![temp.path().join("a.mp3")],
+            },
+        );
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::M3uExport {
+            selected: 2,
+            path_input: String::new(),
+        };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.status, "Type a path to export the M3U to");
+
+        panel = ActionPanelState::M3uExport {
+            selected: 0,
+            path_input: temp.path().display().to_string(),
+        };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(matches!(panel, ActionPanelState::Closed));
+        let written = std::fs::read_to_string(temp.path().join("Road_ Trip.m3u")).expect("m3u");
+        assert!(written.starts_with("#EXTM3U\n#EXTINF:-1,a\n"));
+        assert_eq!(
+            m3u_export_path("/tmp/mix", "queue"),
+            PathBuf::from("/tmp/mix.m3u")
+        );
+    }
+
+    #[test]
+    fn smart_playlist_panel_builds_rule_and_creates_playlist() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::SmartPlaylistCreate {
+            selected: SmartPlaylistDraft::CREATE_ROW,
+            draft: SmartPlaylistDraft::default(),
+        };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.status, "Type a name for the smart playlist");
+
+        panel = ActionPanelState::SmartPlaylistCreate {
+            selected: 0,
+            draft: SmartPlaylistDraft::default(),
+        };
+        for ch in "Recent".chars() {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char(ch));
+        }
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        for key in [KeyCode::Backspace, KeyCode::Char('0')] {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, key);
+        }
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        for ch in "Band".chars() {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char(ch));
+        }
+        for _ in 0..2 {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Down);
+        }
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert!(matches!(panel, ActionPanelState::Closed));
+        let rule = core.smart_playlists.get("Recent").expect("smart playlist");
+        assert_eq!(rule.sort, SmartPlaylistSort::LastPlayed);
+        assert_eq!(rule.limit, 20);
+        assert_eq!(rule.artist.as_deref(), Some("Band"));
+        assert_eq!(rule.genre, None);
+        assert!(all_playlist_names(&core).contains(&String::from("Recent")));
+    }
+
+    #[test]
+    fn j_and_k_move_through_stats_rows_and_list_panels_but_type_into_inputs() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.header_section = HeaderSection::Stats;
+        core.stats_focus = crate::core::StatsFilterFocus::Row(0);
+        let row = crate::stats::TrackStatsRow {
+            track_key: String::from("meta:a"),
+            track_path: PathBuf::from("a.mp3"),
+            title: String::from("A"),
+            artist: None,
+            album: None,
+            play_count: 1,
+            listen_seconds: 60,
+            last_played_epoch_seconds: 0,
+            starts: 0,
+            skips: 0,
+        };
+        let rows = vec![row.clone(), row];
+        let press = |core: &mut TuneCore, ch| {
+            handle_stats_inline_input(
+                core,
+                KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE),
+                &rows,
+            )
+        };
+        assert!(press(&mut core, 'j'));
+        assert_eq!(core.stats_focus, crate::core::StatsFilterFocus::Row(1));
+        assert!(press(&mut core, 'k'));
+        assert_eq!(core.stats_focus, crate::core::StatsFilterFocus::Row(0));
+        assert!(!press(&mut core, 'l'), "l still switches pages");
+
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::ThemeSettings { selected: 0 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char('j'));
+        assert!(matches!(
+            panel,
+            ActionPanelState::ThemeSettings { selected: 1 }
+        ));
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char('h'));
+        assert!(matches!(panel, ActionPanelState::Root { .. }));
+
+        let mut panel = ActionPanelState::PlaylistCreate {
+            selected: 0,
+            input: String::new(),
+        };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char('j'));
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char('k'));
+        assert!(matches!(
+            panel,
+            ActionPanelState::PlaylistCreate { selected: 0, ref input } if input == "jk"
+        ));
+    }
+
     #[test]
     fn theme_settings_updates_core() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -9594,6 +14876,74 @@ mod tests {
         assert!((state.saved_volume - 1.75).abs() < f32::EPSILON);
     }
 
+    #[test]
+    fn mute_toggle_restores_previous_volume_and_persists() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.set_volume(0.65);
+
+        toggle_mute(&mut core, &mut audio);
+        assert!(audio.is_muted());
+        assert_eq!(core.status, "Muted");
+        let state = persisted_state_with_audio(&core, &audio);
+        assert!(state.saved_muted);
+        assert!((state.saved_volume - 0.65).abs() < f32::EPSILON);
+
+        toggle_mute(&mut core, &mut audio);
+        assert!(!audio.is_muted());
+        assert_eq!(core.status, "Unmuted at 65%");
+
+        let mut restored = TestAudioEngine::new();
+        apply_saved_volume(&mut restored, state.saved_volume);
+        apply_saved_mute(&mut core, &mut restored, state.saved_muted);
+        assert!(restored.is_muted());
+        assert_eq!(core.status, "Muted");
+    }
+
+    #[test]
+    fn ab_loop_key_sets_a_then_b_then_clears() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("song.mp3"));
+        audio.position = Some(Duration::from_secs(30));
+
+        cycle_ab_loop(&mut core, &mut audio);
+        assert_eq!(core.status, "Loop start A at 00:30; press o again to set B");
+
+        audio.position = Some(Duration::from_secs(20));
+        cycle_ab_loop(&mut core, &mut audio);
+        assert_eq!(core.status, "Loop end B must come after A (00:30)");
+        assert_eq!(audio.ab_loop(), None);
+
+        audio.position = Some(Duration::from_secs(45));
+        cycle_ab_loop(&mut core, &mut audio);
+        assert_eq!(core.status, "A-B loop 00:30-00:45");
+        assert_eq!(
+            audio.ab_loop(),
+            Some((Duration::from_secs(30), Duration::from_secs(45)))
+        );
+
+        cycle_ab_loop(&mut core, &mut audio);
+        assert_eq!(core.status, "A-B loop cleared");
+        assert_eq!(audio.ab_loop(), None);
+    }
+
+    #[test]
+    fn ab_loop_start_from_another_track_is_replaced() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("first.mp3"));
+        audio.position = Some(Duration::from_secs(10));
+        cycle_ab_loop(&mut core, &mut audio);
+
+        audio.current = Some(PathBuf::from("second.mp3"));
+        audio.position = Some(Duration::from_secs(50));
+        cycle_ab_loop(&mut core, &mut audio);
+
+        assert_eq!(core.status, "Loop start A at 00:50; press o again to set B");
+        assert_eq!(audio.ab_loop(), None);
+    }
+
     #[test]
     fn apply_saved_volume_clamps_into_supported_range() {
         let mut audio = TestAudioEngine::new();
@@ -9839,6 +15189,149 @@ mod tests {
         assert!(!event.counted_play);
     }
 
+    #[test]
+    fn listen_tracker_counts_gapless_hand_over_as_completed() {
+        let core = TuneCore::from_persisted(PersistedState::default());
+        let mut stats = StatsStore::default();
+        let mut tracker = ListenTracker::default();
+        let mut audio = TestAudioEngine::new();
+        audio.gapless = true;
+        audio.current = Some(PathBuf::from("a.mp3"));
+        audio.duration = Some(Duration::from_secs(20));
+        audio.position = Some(Duration::from_secs(12));
+
+        tracker.tick(&core, &audio, &mut stats, None);
+        let active = tracker.active.as_mut().expect("active session");
+        active.playing_started_at = Instant::now().checked_sub(Duration::from_secs(12));
+        audio.queue_gapless(Path::new("b.mp3")).expect("queue");
+        tracker.tick(&core, &audio, &mut stats, None);
+
+        // The sink never empties and the last sample of a.mp3 was well before
+        // its end; only the hand-over marks it as played through.
+        audio.finished = true;
+        audio.tick();
+        audio.finished = false;
+        tracker.tick(&core, &audio, &mut stats, None);
+
+        let event = stats
+            .events
+            .iter()
+            .rev()
+            .find(|event| event.track_path == Path::new("a.mp3"))
+            .expect("listen for a.mp3");
+        assert!(event.counted_play);
+        assert_eq!(
+            tracker
+                .active
+                .as_ref()
+                .map(|active| active.playback_path.clone()),
+            Some(PathBuf::from("b.mp3"))
+        );
+    }
+
+    #[test]
+    fn stop_playback_finalizes_listen_and_clears_now_playing() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![Track {
+            path: PathBuf::from("a.mp3"),
+            title: String::from("a"),
+            artist: None,
+            album: None,
+        }];
+        core.queue = vec![0];
+        core.current_queue_index = Some(0);
+        let mut stats = StatsStore::default();
+        let mut tracker = ListenTracker::default();
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("a.mp3"));
+        audio.duration = Some(Duration::from_secs(200));
+        let runtime = test_online_runtime();
+
+        tracker.tick(&core, &audio, &mut stats, None);
+        let active = tracker.active.as_mut().expect("active session");
+        active.playing_started_at = Instant::now().checked_sub(Duration::from_secs(15));
+
+        stop_playback(&mut core, &mut audio, &runtime, &mut tracker, &mut stats);
+
+        assert!(audio.stopped);
+        assert!(tracker.active.is_none());
+        assert!(!stats.events.is_empty());
+        assert_eq!(core.current_queue_index, None);
+        assert_eq!(core.status, "Stopped");
+    }
+
+    #[test]
+    fn stop_playback_mid_crossfade_drops_incoming_track() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut stats = StatsStore::default();
+        let mut tracker = ListenTracker::default();
+        let mut audio = TestAudioEngine::new();
+        audio.crossfade_seconds = 4;
+        audio.current = Some(PathBuf::from("outgoing.mp3"));
+        audio.queued = Some(PathBuf::from("incoming.mp3"));
+        let runtime = test_online_runtime();
+        tracker.tick(&core, &audio, &mut stats, None);
+
+        stop_playback(&mut core, &mut audio, &runtime, &mut tracker, &mut stats);
+        tracker.tick(&core, &audio, &mut stats, None);
+
+        assert_eq!(audio.current_track(), None);
+        assert_eq!(audio.crossfade_queued_track(), None);
+        assert!(tracker.active.is_none());
+        assert!(
+            stats
+                .events
+                .iter()
+                .all(|event| !event.track_path.ends_with("incoming.mp3"))
+        );
+    }
+
+    #[test]
+    fn stop_playback_without_track_is_noop() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut stats = StatsStore::default();
+        let mut tracker = ListenTracker::default();
+        let mut audio = TestAudioEngine::new();
+        let runtime = test_online_runtime();
+
+        stop_playback(&mut core, &mut audio, &runtime, &mut tracker, &mut stats);
+
+        assert!(!audio.stopped);
+        assert_eq!(core.status, "Nothing is playing");
+    }
+
+    #[test]
+    fn removing_the_playing_queue_entry_stops_playback() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = ["a", "b", "c"]
+            .into_iter()
+            .map(|name| Track {
+                path: PathBuf::from(format!("{name}.mp3")),
+                title: String::from(name),
+                artist: None,
+                album: None,
+            })
+            .collect();
+        core.queue = vec![0, 1, 2];
+        core.current_queue_index = Some(1);
+        core.open_local_queue_view();
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("b.mp3"));
+
+        core.selected_browser = 3;
+        remove_selected_queue_item(&mut core, &mut audio, None);
+        assert_eq!(core.queue, vec![0, 1]);
+        assert!(!audio.stopped);
+        assert_eq!(core.status, "Removed queue item");
+
+        core.selected_browser = 2;
+        remove_selected_queue_item(&mut core, &mut audio, None);
+        assert_eq!(core.queue, vec![0]);
+        assert!(audio.stopped);
+        assert_eq!(core.current_queue_index, None);
+        assert_eq!(core.status, "Removed the playing track. Stopped");
+    }
+
     #[test]
     fn finalize_after_partial_flush_retains_total_listen_and_play_count() {
         let mut stats = StatsStore::default();
@@ -9870,9 +15363,11 @@ mod tests {
                 persisted_listened_seconds: 30,
                 play_count_recorded: false,
                 pending_same_track_restart: false,
+                queued_next_track: None,
                 last_position: Some(Duration::from_secs(200)),
                 duration: Some(Duration::from_secs(200)),
             }),
+            ..ListenTracker::default()
         };
 
         assert!(tracker.finalize_active(&mut stats, false));
@@ -9891,11 +15386,117 @@ mod tests {
     }
 
     #[test]
-    fn listen_tracker_records_play_during_partial_flush_once() {
-        let core = TuneCore::from_persisted(PersistedState::default());
+    fn listen_tracker_scrobbles_counted_plays_with_an_artist() {
+        let queue = crate::scrobble::ScrobbleQueue::detached();
         let mut stats = StatsStore::default();
-        let mut tracker = ListenTracker::default();
-        let mut audio = TestAudioEngine::new();
+        let session = |artist: Option<&str>, listened_seconds: u64, play_count_recorded| {
+            Some(ActiveListenSession {
+                playback_path: PathBuf::from("a.mp3"),
+                track_path: PathBuf::from("a.mp3"),
+                title: String::from("Song"),
+                artist: artist.map(String::from),
+                album: Some(String::from("Record")),
+                provider_track_id: None,
+                started_at_epoch_seconds: 1_000,
+                playing_started_at: None,
+                listened: Duration::from_secs(listened_seconds),
+                persisted_listened_seconds: 0,
+                play_count_recorded,
+                pending_same_track_restart: false,
+                queued_next_track: None,
+                last_position: Some(Duration::from_secs(listened_seconds)),
+                duration: Some(Duration::from_secs(200)),
+            })
+        };
+        let mut tracker = ListenTracker {
+            scrobble_queue: Some(queue.clone()),
+            ..ListenTracker::default()
+        };
+
+        tracker.active = session(Some("Band"), 15, false);
+        tracker.finalize_active(&mut stats, false);
+        tracker.active = session(None, 90, false);
+        tracker.finalize_active(&mut stats, false);
+        assert!(queue.pending().is_empty());
+
+        // Counted by an earlier partial flush, scrobbled when the listen ends.
+        tracker.active = session(Some("Band"), 90, true);
+        tracker.finalize_active(&mut stats, false);
+        assert_eq!(
+            queue.pending(),
+            vec![crate::scrobble::Scrobble {
+                artist: String::from("Band"),
+                title: String::from("Song"),
+                album: Some(String::from("Record")),
+                duration_seconds: Some(200),
+                listened_at_epoch_seconds: 1_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn forgetting_the_playing_track_drops_its_listen_in_progress() {
+        let core = TuneCore::from_persisted(PersistedState::default());
+        let mut stats = StatsStore::default();
+        let mut tracker = ListenTracker::default();
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("a.mp3"));
+        audio.duration = Some(Duration::from_secs(200));
+
+        assert!(!tracker.tick(&core, &audio, &mut stats, None));
+        let active = tracker.active.as_mut().expect("active session");
+        active.playing_started_at = Instant::now().checked_sub(Duration::from_secs(31));
+        assert!(tracker.tick(&core, &audio, &mut stats, None));
+        let track_key = stats.track_totals.keys().next().expect("key").clone();
+
+        tracker.forget_track(&stats, "other-track");
+        assert!(tracker.active.is_some());
+        tracker.forget_track(&stats, &track_key);
+        stats.forget_track(&track_key);
+        assert!(tracker.active.is_none());
+        assert!(!tracker.finalize_active(&mut stats, false));
+        assert!(stats.events.is_empty());
+    }
+
+    #[test]
+    fn stats_rows_cache_requeries_only_when_history_or_filters_change() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut stats = StatsStore::default();
+        let mut cache = StatsRowsCache::default();
+        let listen = |title: &str| crate::stats::ListenSessionRecord {
+            track_path: PathBuf::from(format!("{title}.mp3")),
+            title: title.to_string(),
+            artist: Some(String::from("Band")),
+            album: None,
+            provider_track_id: None,
+            started_at_epoch_seconds: stats::now_epoch_seconds(),
+            listened_seconds: 60,
+            completed: true,
+            duration_seconds: Some(60),
+            counted_play_override: None,
+            allow_short_listen: false,
+        };
+        stats.record_listen(listen("Intro"));
+        assert_eq!(cache.rows(&core, &stats).len(), 1);
+        let key = cache.key.clone();
+        assert_eq!(cache.rows(&core, &stats).len(), 1);
+        assert_eq!(cache.key, key);
+
+        stats.record_listen(listen("Outro"));
+        assert_eq!(cache.rows(&core, &stats).len(), 2);
+
+        core.stats_search = String::from("outro");
+        let rows = cache.rows(&core, &stats);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].title, "Outro");
+    }
+
+    #[test]
+    fn listen_tracker_records_play_during_partial_flush_once() {
+        let core = TuneCore::from_persisted(PersistedState::default());
+        let mut stats = StatsStore::default();
+        let mut tracker = ListenTracker::default();
+        let mut audio = TestAudioEngine::new();
         audio.current = Some(PathBuf::from("a.mp3"));
         audio.duration = Some(Duration::from_secs(200));
 
@@ -9982,9 +15583,11 @@ mod tests {
                 persisted_listened_seconds: 0,
                 play_count_recorded: false,
                 pending_same_track_restart: false,
+                queued_next_track: None,
                 last_position: None,
                 duration: Some(Duration::from_secs(20)),
             }),
+            ..ListenTracker::default()
         };
 
         assert!(tracker.finalize_active(&mut stats, true));
@@ -10021,9 +15624,11 @@ mod tests {
                 persisted_listened_seconds: 186,
                 play_count_recorded: false,
                 pending_same_track_restart: true,
+                queued_next_track: None,
                 last_position: Some(Duration::from_secs(179)),
                 duration: Some(Duration::from_secs(180)),
             }),
+            ..ListenTracker::default()
         };
         let mut audio = TestAudioEngine::new();
         audio.current = Some(PathBuf::from("loop.mp3"));
@@ -10065,9 +15670,11 @@ mod tests {
                 persisted_listened_seconds: 42,
                 play_count_recorded: true,
                 pending_same_track_restart: false,
+                queued_next_track: None,
                 last_position: Some(Duration::from_secs(40)),
                 duration: Some(Duration::from_secs(180)),
             }),
+            ..ListenTracker::default()
         };
         let mut audio = TestAudioEngine::new();
         audio.current = Some(PathBuf::from("loop.mp3"));
@@ -10110,9 +15717,11 @@ mod tests {
                 persisted_listened_seconds: 140,
                 play_count_recorded: true,
                 pending_same_track_restart: false,
+                queued_next_track: None,
                 last_position: Some(Duration::from_secs(153)),
                 duration: Some(Duration::from_secs(153)),
             }),
+            ..ListenTracker::default()
         };
 
         assert!(tracker.finalize_active(&mut stats, false));
@@ -10150,9 +15759,11 @@ mod tests {
                 persisted_listened_seconds: 0,
                 play_count_recorded: false,
                 pending_same_track_restart: false,
+                queued_next_track: None,
                 last_position: Some(Duration::from_secs(40)),
                 duration: Some(Duration::from_secs(153)),
             }),
+            ..ListenTracker::default()
         };
 
         assert!(tracker.finalize_active(&mut stats, false));
@@ -10191,9 +15802,11 @@ mod tests {
                 persisted_listened_seconds: 0,
                 play_count_recorded: false,
                 pending_same_track_restart: false,
+                queued_next_track: None,
                 last_position: Some(Duration::from_secs(153)),
                 duration: Some(Duration::from_secs(153)),
             }),
+            ..ListenTracker::default()
         };
 
         assert!(tracker.finalize_active(&mut stats, false));
@@ -10229,9 +15842,11 @@ mod tests {
                 persisted_listened_seconds: 0,
                 play_count_recorded: false,
                 pending_same_track_restart: false,
+                queued_next_track: None,
                 last_position: Some(Duration::from_secs(2)),
                 duration: Some(Duration::from_secs(153)),
             }),
+            ..ListenTracker::default()
         };
 
         assert!(!tracker.finalize_active(&mut stats, false));
@@ -10338,12 +15953,301 @@ mod tests {
 
         let mut runtime = test_online_runtime();
         let mut audio = TestAudioEngine::finished_with_current("a.mp3");
-        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime);
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
 
         assert_eq!(audio.played, vec![PathBuf::from("b.mp3")]);
         assert_eq!(core.current_queue_index, Some(1));
     }
 
+    #[test]
+    fn auto_advance_waits_for_track_gap_before_next_track() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![
+            Track {
+                path: PathBuf::from("a.mp3"),
+                title: String::from("a"),
+                artist: None,
+                album: None,
+            },
+            Track {
+                path: PathBuf::from("b.mp3"),
+                title: String::from("b"),
+                artist: None,
+                album: None,
+            },
+        ];
+        core.queue = vec![0, 1];
+        core.current_queue_index = Some(0);
+        core.track_gap_seconds = 2;
+
+        let mut runtime = test_online_runtime();
+        let mut audio = TestAudioEngine::finished_with_current("a.mp3");
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert!(audio.played.is_empty());
+        assert!(core.pending_advance_at.is_some());
+
+        core.pending_advance_at = Some(Instant::now() - Duration::from_millis(1));
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert_eq!(audio.played, vec![PathBuf::from("b.mp3")]);
+        assert!(core.pending_advance_at.is_none());
+    }
+
+    #[test]
+    fn play_once_stops_instead_of_advancing_and_then_clears() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = ["a.mp3", "b.mp3"]
+            .into_iter()
+            .map(|path| Track {
+                path: PathBuf::from(path),
+                title: path.to_string(),
+                artist: None,
+                album: None,
+            })
+            .collect();
+        core.queue = vec![0, 1];
+        core.current_queue_index = Some(0);
+        set_play_once(&mut core, Some(PathBuf::from("a.mp3")));
+
+        let mut runtime = test_online_runtime();
+        let mut audio = TestAudioEngine::finished_with_current("a.mp3");
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert!(audio.played.is_empty());
+        assert!(audio.current_track().is_none());
+        assert!(core.play_once.is_none());
+        assert_eq!(core.status, "Played once; stopped");
+
+        set_play_once(&mut core, Some(PathBuf::from("b.mp3")));
+        let mut audio = TestAudioEngine::finished_with_current("a.mp3");
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert!(core.play_once.is_none());
+        assert_eq!(audio.played, vec![PathBuf::from("b.mp3")]);
+    }
+
+    #[test]
+    fn track_gap_and_crossfade_settings_turn_each_other_off() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlaybackSettings { selected: 3 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.crossfade_seconds, 2);
+
+        panel = ActionPanelState::PlaybackSettings { selected: 17 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.track_gap_seconds, 1);
+        assert_eq!(core.crossfade_seconds, 0);
+        assert_eq!(audio.crossfade_seconds(), 0);
+        assert_eq!(core.status, "Gap between tracks: 1s (crossfade off)");
+        assert_eq!(core.persisted_state().track_gap_seconds, 1);
+
+        panel = ActionPanelState::PlaybackSettings { selected: 3 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.crossfade_seconds, 2);
+        assert_eq!(core.track_gap_seconds, 0);
+    }
+
+    #[test]
+    fn normalization_gain_option_toggles_album_gain_and_persists() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlaybackSettings { selected: 26 };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert!(core.album_gain);
+        assert!(core.persisted_state().album_gain);
+        assert_eq!(core.status, "Normalization gain: Album");
+        assert_eq!(
+            playback_settings_options(&core)[26],
+            "Normalization gain: Album"
+        );
+    }
+
+    #[test]
+    fn output_shaping_sets_mono_and_balance_and_persists_them() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::AudioSettings { selected: 2 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(matches!(
+            panel,
+            ActionPanelState::OutputShaping { selected: 0 }
+        ));
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(core.mono_output && audio.mono);
+        assert_eq!(core.status, "Mono: On");
+
+        panel = ActionPanelState::OutputShaping { selected: 2 };
+        for _ in 0..3 {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        }
+        assert_eq!(core.balance, -0.3);
+        assert_eq!(audio.balance, -0.3);
+        assert_eq!(core.status, "Balance: 30% left");
+
+        let state = core.persisted_state();
+        assert!(state.mono_output);
+        assert_eq!(state.balance, -0.3);
+        let mut restored = TestAudioEngine::new();
+        apply_audio_preferences_from_core(&TuneCore::from_persisted(state), &mut restored);
+        assert!(restored.mono);
+        assert_eq!(restored.balance, -0.3);
+
+        panel = ActionPanelState::OutputShaping { selected: 1 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(audio.balance, 0.0);
+        assert_eq!(
+            output_shaping_options(&core)[1],
+            "Balance: Center (Enter to center)"
+        );
+    }
+
+    #[test]
+    fn crossfade_curve_option_cycles_applies_and_persists() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlaybackSettings { selected: 29 };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert_eq!(core.crossfade_curve, CrossfadeCurve::EqualPower);
+        assert_eq!(audio.crossfade_curve, CrossfadeCurve::EqualPower);
+        assert_eq!(core.status, "Crossfade curve: Equal power");
+        let state = core.persisted_state();
+        assert_eq!(state.crossfade_curve, CrossfadeCurve::EqualPower);
+
+        let mut restored_audio = TestAudioEngine::new();
+        let restored = TuneCore::from_persisted(state);
+        apply_audio_preferences_from_core(&restored, &mut restored_audio);
+        assert_eq!(restored_audio.crossfade_curve, CrossfadeCurve::EqualPower);
+        assert_eq!(
+            playback_settings_options(&restored)[29],
+            "Crossfade curve: Equal power"
+        );
+    }
+
+    #[test]
+    fn shuffle_options_cycle_mode_and_recency_window_and_persist() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlaybackSettings { selected: 30 };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.status, "Shuffle mode: Avoid recent, fewer plays first");
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.shuffle_mode, ShuffleMode::Random);
+
+        panel = ActionPanelState::PlaybackSettings { selected: 31 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.status, "Shuffle avoids the last 30% of the queue");
+
+        let restored = TuneCore::from_persisted(core.persisted_state());
+        assert_eq!(restored.shuffle_mode, ShuffleMode::Random);
+        let options = playback_settings_options(&restored);
+        assert_eq!(options[30], "Shuffle mode: Random");
+        assert_eq!(options[31], "Shuffle avoids last: 30% of queue");
+    }
+
+    #[test]
+    fn playback_speed_option_cycles_and_listen_together_keeps_normal_speed() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlaybackSettings { selected: 27 };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert_eq!(core.playback_speed, 1.25);
+        assert_eq!(audio.playback_speed, 1.25);
+        assert_eq!(core.persisted_state().playback_speed, 1.25);
+        assert_eq!(
+            playback_settings_options(&core)[27],
+            "Playback speed: 1.25x"
+        );
+
+        core.online.session = Some(host_only_listener_session());
+        sync_playback_speed(&core, &mut audio);
+        assert_eq!(audio.playback_speed, 1.0);
+        cycle_playback_speed(&mut core, &mut audio, true);
+        assert_eq!(core.playback_speed, 1.25);
+        assert_eq!(core.status, "Playback speed stays at 1x in Listen Together");
+
+        core.online.session = None;
+        sync_playback_speed(&core, &mut audio);
+        assert_eq!(audio.playback_speed, 1.25);
+    }
+
+    #[test]
+    fn equalizer_panel_steps_bands_applies_them_and_resets() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlaybackSettings { selected: 24 };
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(matches!(panel, ActionPanelState::Equalizer { selected: 0 }));
+
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(core.status, "Equalizer 60 Hz: +3 dB");
+        assert_eq!(audio.equalizer()[0], (60.0, 3.0));
+        assert_eq!(core.persisted_state().equalizer_bands[0], (60.0, 3.0));
+        assert_eq!(
+            playback_settings_options(&core)[24],
+            "Equalizer: Custom".to_string()
+        );
+
+        for _ in 0..4 {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        }
+        assert_eq!(core.equalizer_bands[0], (60.0, -12.0));
+
+        panel = ActionPanelState::Equalizer { selected: 5 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert_eq!(audio.equalizer(), crate::model::default_equalizer_bands());
+        assert_eq!(core.status, "Equalizer reset to flat");
+
+        panel = ActionPanelState::Equalizer { selected: 6 };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(matches!(
+            panel,
+            ActionPanelState::PlaybackSettings { selected: 24 }
+        ));
+    }
+
+    #[test]
+    fn gapless_auto_advance_appends_next_track_just_before_the_end() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = ["a.mp3", "b.mp3"]
+            .into_iter()
+            .map(|path| Track {
+                path: PathBuf::from(path),
+                title: path.to_string(),
+                artist: None,
+                album: None,
+            })
+            .collect();
+        core.queue = vec![0, 1];
+        core.current_queue_index = Some(0);
+
+        let mut audio = TestAudioEngine::new();
+        audio.gapless = true;
+        audio.current = Some(PathBuf::from("a.mp3"));
+        audio.duration = Some(Duration::from_secs(100));
+        audio.position = Some(Duration::from_secs(90));
+        let mut runtime = test_online_runtime();
+
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert_eq!(audio.crossfade_queued_track(), None);
+
+        audio.position = Some(Duration::from_secs(99));
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert_eq!(audio.crossfade_queued_track(), Some(Path::new("b.mp3")));
+        assert_eq!(audio.played, Vec::<PathBuf>::new());
+
+        audio.finished = true;
+        audio.tick();
+        assert_eq!(audio.played, vec![PathBuf::from("b.mp3")]);
+        assert_eq!(audio.position, Some(Duration::ZERO));
+    }
+
     #[test]
     fn auto_advance_starts_next_track_within_crossfade_window() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -10371,7 +16275,7 @@ mod tests {
         audio.crossfade_seconds = 6;
 
         let mut runtime = test_online_runtime();
-        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime);
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
 
         assert_eq!(audio.played, Vec::<PathBuf>::new());
         assert_eq!(audio.crossfade_queued_track(), Some(Path::new("b.mp3")));
@@ -10383,6 +16287,156 @@ mod tests {
         assert_eq!(audio.position, Some(Duration::from_secs(6)));
     }
 
+    #[test]
+    fn auto_advance_skips_crossfade_into_flagged_track() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![
+            Track {
+                path: PathBuf::from("a.mp3"),
+                title: String::from("a"),
+                artist: None,
+                album: None,
+            },
+            Track {
+                path: PathBuf::from("b.mp3"),
+                title: String::from("b"),
+                artist: None,
+                album: None,
+            },
+        ];
+        core.queue = vec![0, 1];
+        core.current_queue_index = Some(0);
+        core.toggle_track_crossfade_disabled(Path::new("b.mp3"));
+
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("a.mp3"));
+        audio.duration = Some(Duration::from_secs(100));
+        audio.position = Some(Duration::from_secs(95));
+        audio.crossfade_seconds = 6;
+
+        let mut runtime = test_online_runtime();
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
+
+        assert_eq!(audio.crossfade_queued_track(), None);
+        assert_eq!(core.current_queue_index, Some(0));
+
+        audio.finished = true;
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert_eq!(audio.played, vec![PathBuf::from("b.mp3")]);
+        assert_eq!(core.current_queue_index, Some(1));
+    }
+
+    #[test]
+    fn wall_clock_gap_detects_resume_only_for_forward_jumps() {
+        let before = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        assert!(!wall_clock_gap_suggests_resume(
+            before,
+            before + Duration::from_secs(1)
+        ));
+        assert!(wall_clock_gap_suggests_resume(
+            before,
+            before + Duration::from_secs(600)
+        ));
+        assert!(!wall_clock_gap_suggests_resume(
+            before,
+            before - Duration::from_secs(600)
+        ));
+    }
+
+    #[test]
+    fn resume_recovery_keeps_playback_position() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("a.mp3"));
+        audio.position = Some(Duration::from_secs(42));
+
+        recover_audio_output_after_resume(&mut core, &mut audio);
+
+        assert_eq!(audio.position, Some(Duration::from_secs(42)));
+        assert!(core.status.starts_with("Resumed from sleep"));
+    }
+
+    #[test]
+    fn resume_recovery_falls_back_when_saved_output_is_gone() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.selected_output = Some(String::from("USB DAC"));
+
+        recover_audio_output_after_resume(&mut core, &mut audio);
+
+        assert_eq!(audio.selected_output, None);
+        assert_eq!(
+            core.status,
+            "Resumed from sleep. 'USB DAC' unavailable, using default output"
+        );
+    }
+
+    #[test]
+    fn unplugged_output_falls_back_to_default_and_keeps_position() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.selected_output = Some(String::from("Speakers"));
+        audio.current = Some(PathBuf::from("a.mp3"));
+        audio.position = Some(Duration::from_secs(42));
+
+        audio.outputs.retain(|output| output != "Speakers");
+        recover_lost_output_device(&mut core, &mut audio);
+        assert_eq!(audio.selected_output.as_deref(), Some("Speakers"));
+        assert_eq!(audio.reload_calls, 0);
+
+        audio.device_lost = true;
+        recover_lost_output_device(&mut core, &mut audio);
+        assert!(!audio.device_lost());
+
+        assert_eq!(audio.selected_output, None);
+        assert_eq!(audio.position, Some(Duration::from_secs(42)));
+        assert_eq!(
+            core.status,
+            "'Speakers' disconnected. Using default output / -> Audio driver settings"
+        );
+    }
+
+    #[test]
+    fn lost_default_output_is_reloaded() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        audio.device_lost = true;
+
+        recover_lost_output_device(&mut core, &mut audio);
+
+        assert_eq!(audio.reload_calls, 1);
+        assert!(!audio.device_lost());
+        assert_eq!(core.status, "Audio output lost; reopened default output");
+    }
+
+    #[test]
+    fn null_engine_startup_warns_that_playback_is_silent() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = NullAudioEngine::new();
+        apply_saved_audio_output(&mut core, &mut audio, Some(String::from("USB DAC")));
+
+        assert!(audio.is_silent());
+        assert!(!TestAudioEngine::new().is_silent());
+        assert_eq!(audio.selected_output_device().as_deref(), Some("USB DAC"));
+
+        core.status = silent_audio_status(None);
+        recover_lost_output_device(&mut core, &mut audio);
+        recover_audio_output_after_resume(&mut core, &mut audio);
+        assert_eq!(audio.selected_output_device().as_deref(), Some("USB DAC"));
+        assert_eq!(
+            core.status,
+            "Running without audio output. Playback is silent"
+        );
+        assert_eq!(
+            silent_audio_status(Some(&anyhow::anyhow!("no default output stream"))),
+            "Running without audio output (no default output stream). Playback is silent"
+        );
+        assert_eq!(
+            silent_audio_status(None),
+            "Running without audio output. Playback is silent"
+        );
+    }
+
     #[test]
     fn auto_advance_stops_when_queue_ends() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -10397,7 +16451,7 @@ mod tests {
 
         let mut runtime = test_online_runtime();
         let mut audio = TestAudioEngine::finished_with_current("a.mp3");
-        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime);
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
 
         assert!(audio.stopped);
         assert_eq!(core.status, "Reached end of queue");
@@ -10431,6 +16485,7 @@ mod tests {
                 ping_ms: 0,
                 manual_extra_delay_ms: 0,
                 auto_ping_delay: true,
+                last_drift_ms: None,
             });
             session.last_transport = Some(TransportEnvelope {
                 seq: 1,
@@ -10441,7 +16496,7 @@ mod tests {
 
         let mut runtime = test_online_runtime();
         let mut audio = TestAudioEngine::finished_with_current("a.mp3");
-        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime);
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
 
         assert!(audio.played.is_empty());
         assert!(!audio.stopped);
@@ -10463,20 +16518,72 @@ mod tests {
         let mut runtime = test_online_runtime();
         runtime.local_nickname = String::from("host");
         let mut audio = TestAudioEngine::finished_with_current("a.mp3");
-        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime);
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
+
+        assert_eq!(audio.played, vec![PathBuf::from("shared.mp3")]);
+        assert_eq!(
+            runtime.online_playback_source,
+            OnlinePlaybackSource::SharedQueue
+        );
+        assert_eq!(
+            core.online
+                .session
+                .as_ref()
+                .map(|session| session.shared_queue.len()),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn online_skip_vote_majority_advances_the_authority_once() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.online.session = Some(crate::online::OnlineSession::host("host"));
+        if let Some(session) = core.online.session.as_mut() {
+            for nickname in ["a", "b"] {
+                session.participants.push(crate::online::Participant {
+                    nickname: nickname.to_string(),
+                    is_local: false,
+                    is_host: false,
+                    ping_ms: 0,
+                    manual_extra_delay_ms: 0,
+                    auto_ping_delay: true,
+                    last_drift_ms: None,
+                });
+            }
+            session.push_shared_track(Path::new("next.mp3"), String::from("next"), None);
+            session.last_transport = Some(TransportEnvelope {
+                seq: 1,
+                origin_nickname: String::from("host"),
+                command: TransportCommand::PlayTrack {
+                    path: PathBuf::from("a.mp3"),
+                    title: None,
+                    artist: None,
+                    album: None,
+                    provider_track_id: None,
+                },
+            });
+            session.skip_votes = vec![String::from("a")];
+        }
+        let mut runtime = test_online_runtime();
+        runtime.local_nickname = String::from("host");
+        let mut audio = TestAudioEngine::new();
+        audio.current = Some(PathBuf::from("a.mp3"));
+
+        maybe_skip_online_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert!(audio.played.is_empty());
+
+        if let Some(session) = core.online.session.as_mut() {
+            session.skip_votes.push(String::from("b"));
+        }
+        maybe_skip_online_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert_eq!(audio.played, vec![PathBuf::from("next.mp3")]);
+        assert_eq!(core.status, "Skip vote passed (2/3 skip votes)");
 
-        assert_eq!(audio.played, vec![PathBuf::from("shared.mp3")]);
-        assert_eq!(
-            runtime.online_playback_source,
-            OnlinePlaybackSource::SharedQueue
-        );
-        assert_eq!(
-            core.online
-                .session
-                .as_ref()
-                .map(|session| session.shared_queue.len()),
-            Some(0)
-        );
+        if let Some(session) = core.online.session.as_mut() {
+            session.skip_votes = vec![String::from("a"), String::from("b")];
+        }
+        maybe_skip_online_track(&mut core, &mut audio, &mut runtime, &[]);
+        assert_eq!(audio.played.len(), 1);
     }
 
     #[test]
@@ -10488,7 +16595,7 @@ mod tests {
         runtime.local_nickname = String::from("host");
         runtime.online_playback_source = OnlinePlaybackSource::SharedQueue;
         let mut audio = TestAudioEngine::finished_with_current("shared.mp3");
-        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime);
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
 
         assert!(audio.stopped);
         assert_eq!(core.status, "Reached end of shared queue");
@@ -10518,6 +16625,7 @@ mod tests {
             ping_ms: 0,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
         core.online.session = Some(session);
         let runtime = test_online_runtime();
@@ -10536,6 +16644,7 @@ mod tests {
             ping_ms: 0,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
         session.last_transport = Some(TransportEnvelope {
             seq: 7,
@@ -10567,6 +16676,7 @@ mod tests {
             ping_ms: 0,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
         session.last_transport = Some(TransportEnvelope {
             seq: 7,
@@ -10618,7 +16728,7 @@ mod tests {
         let mut runtime = test_online_runtime();
         runtime.local_nickname = String::from("host");
         let mut audio = TestAudioEngine::finished_with_current("a.mp3");
-        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime);
+        maybe_auto_advance_track(&mut core, &mut audio, &mut runtime, &[]);
 
         assert_eq!(audio.played, vec![PathBuf::from("b.mp3")]);
         assert_eq!(core.current_queue_index, Some(1));
@@ -10668,6 +16778,7 @@ mod tests {
                 ping_ms: 0,
                 manual_extra_delay_ms: 0,
                 auto_ping_delay: true,
+                last_drift_ms: None,
             });
             session.push_shared_track(
                 Path::new("shared.mp3"),
@@ -10885,6 +16996,18 @@ mod tests {
         assert!(!runtime.host_invite_modal_active);
     }
 
+    #[test]
+    fn clipboard_paste_status_distinguishes_missing_backend_from_empty_clipboard() {
+        assert_eq!(
+            clipboard_paste_status(ClipboardPaste::Pasted, "T1ABCDE"),
+            "Pasted input: T1ABCDE"
+        );
+        assert!(clipboard_paste_status(ClipboardPaste::Empty, "").contains("empty"));
+        let unavailable = clipboard_paste_status(ClipboardPaste::Unavailable, "");
+        assert!(unavailable.contains("No clipboard access"));
+        assert!(!unavailable.contains("failed"));
+    }
+
     #[test]
     fn host_invite_modal_escape_closes_dialog() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -10923,6 +17046,63 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn online_tab_uses_one_for_room_code_reveal_and_keeps_t_from_minimizing() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.header_section = HeaderSection::Online;
+        core.online.session = Some(crate::online::OnlineSession::host("host"));
+        let mut audio = NullAudioEngine::new();
+        let mut runtime = test_online_runtime();
+
+        assert!(handle_online_inline_input(
+            &mut core,
+            &mut audio,
+            KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE),
+            &mut runtime,
+        ));
+        assert!(!runtime.room_code_revealed);
+        assert_eq!(core.status, ONLINE_TRAY_KEY_HINT);
+
+        assert!(handle_online_inline_input(
+            &mut core,
+            &mut audio,
+            KeyEvent::new(KeyCode::Char('1'), KeyModifiers::NONE),
+            &mut runtime,
+        ));
+        assert!(runtime.room_code_revealed);
+    }
+
+    #[test]
+    fn online_tab_arrow_keys_scroll_shared_queue_within_bounds() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.header_section = HeaderSection::Online;
+        core.online.session = Some(crate::online::OnlineSession::host("host"));
+        if let Some(session) = core.online.session.as_mut() {
+            for name in ["a.mp3", "b.mp3", "c.mp3"] {
+                session.push_shared_track(Path::new(name), String::from(name), None);
+            }
+        }
+        let mut audio = NullAudioEngine::new();
+        let mut runtime = test_online_runtime();
+        let mut press = |core: &mut TuneCore, code| {
+            assert!(handle_online_inline_input(
+                core,
+                &mut audio,
+                KeyEvent::new(code, KeyModifiers::NONE),
+                &mut runtime,
+            ));
+        };
+
+        press(&mut core, KeyCode::Down);
+        assert_eq!(core.online_queue_scroll, 1);
+        press(&mut core, KeyCode::PageDown);
+        assert_eq!(core.online_queue_scroll, 2);
+        press(&mut core, KeyCode::Up);
+        assert_eq!(core.online_queue_scroll, 1);
+        press(&mut core, KeyCode::Home);
+        assert_eq!(core.online_queue_scroll, 0);
+    }
+
     #[test]
     fn online_tab_does_not_consume_ctrl_c() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -10939,6 +17119,47 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn online_chat_mode_captures_shortcut_keys_until_esc() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.header_section = HeaderSection::Online;
+        core.online.session = Some(crate::online::OnlineSession::host("host"));
+        let mut audio = NullAudioEngine::new();
+        let mut runtime = test_online_runtime();
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        assert!(!handle_online_chat_input(
+            &mut core,
+            press(KeyCode::Char('h')),
+            &mut runtime
+        ));
+        assert!(handle_online_inline_input(
+            &mut core,
+            &mut audio,
+            press(KeyCode::Char('c')),
+            &mut runtime,
+        ));
+        assert!(runtime.chat_input_active);
+        for ch in ['h', 'l', 'o', 'x'] {
+            assert!(handle_online_chat_input(
+                &mut core,
+                press(KeyCode::Char(ch)),
+                &mut runtime
+            ));
+        }
+        handle_online_chat_input(&mut core, press(KeyCode::Backspace), &mut runtime);
+        assert_eq!(runtime.chat_input, "hlo");
+        assert_eq!(core.header_section, HeaderSection::Online);
+        assert_eq!(
+            core.online.session.as_ref().map(|s| s.mode),
+            Some(crate::online::OnlineRoomMode::Collaborative)
+        );
+
+        handle_online_chat_input(&mut core, press(KeyCode::Esc), &mut runtime);
+        assert!(!runtime.chat_input_active);
+        assert!(runtime.chat_input.is_empty());
+    }
+
     #[test]
     fn online_tab_consumes_ctrl_s() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -11039,6 +17260,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn space_in_library_search_results_is_not_a_mark_key() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.header_section = HeaderSection::Library;
+        core.library_search_query = String::from("beat");
+        let space = KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE);
+        let ctrl_space = KeyEvent::new(KeyCode::Char(' '), KeyModifiers::CONTROL);
+        let insert = KeyEvent::new(KeyCode::Insert, KeyModifiers::NONE);
+
+        assert!(!library_search_mark_key(&core, &space));
+        assert!(library_search_mark_key(&core, &ctrl_space));
+        assert!(library_search_mark_key(&core, &insert));
+
+        core.library_search_query.clear();
+        assert!(!library_search_mark_key(&core, &insert));
+    }
+
     #[test]
     fn join_prompt_allows_typing_v_without_triggering_paste() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -11496,6 +17734,48 @@ mod tests {
         assert!(view.secret);
     }
 
+    #[test]
+    fn buffering_view_tracks_progress_for_pending_stream_only() {
+        let mut runtime = test_online_runtime();
+        assert!(runtime.buffering_view().is_none());
+
+        runtime.pending_stream_path = Some(PathBuf::from("shared.flac"));
+        let view = runtime.buffering_view().expect("buffering view");
+        assert_eq!(view.received_bytes, 0);
+        assert_eq!(view.total_bytes, None);
+
+        runtime.stream_progress = Some(StreamProgress {
+            requested_path: PathBuf::from("shared.flac"),
+            received_bytes: 512,
+            total_bytes: Some(2048),
+        });
+        let view = runtime.buffering_view().expect("buffering view");
+        assert_eq!(view.received_bytes, 512);
+        assert_eq!(view.total_bytes, Some(2048));
+
+        runtime.pending_stream_path = Some(PathBuf::from("other.flac"));
+        let view = runtime.buffering_view().expect("buffering view");
+        assert_eq!(view.received_bytes, 0);
+    }
+
+    #[test]
+    fn online_status_view_reports_role_connection_and_server() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut runtime = test_online_runtime();
+        runtime.home_server_addr = String::from("tunetui.online");
+        assert!(runtime.online_status_view(&core).is_none());
+
+        core.online.host_room("tester");
+        let view = runtime.online_status_view(&core).expect("status view");
+        assert_eq!(view.role, "Host");
+        assert_eq!(view.connection, "Disconnected");
+        assert_eq!(view.server_addr, "tunetui.online");
+
+        core.online.join_room("ROOM", "tester");
+        let view = runtime.online_status_view(&core).expect("status view");
+        assert_eq!(view.role, "Client");
+    }
+
     #[test]
     fn online_tab_ctrl_l_leaves_room() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -11514,6 +17794,162 @@ mod tests {
         assert!(core.online.session.is_none());
     }
 
+    #[test]
+    fn terminal_resize_clamps_selection_and_handles_one_row() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.selected_browser = core.browser_entries.len() + 5;
+        core.online_queue_scroll = 7;
+        core.dirty = false;
+
+        let rect = handle_terminal_resize(&mut core, 120, 1);
+        assert_eq!(
+            core.selected_browser,
+            core.browser_entries.len().saturating_sub(1)
+        );
+        assert_eq!(core.online_queue_scroll, 0);
+        assert!(core.dirty);
+        assert_eq!(rect.height, 0);
+
+        let rect = handle_terminal_resize(&mut core, 160, 48);
+        assert!(rect.width > 0 && rect.height > 0);
+    }
+
+    #[test]
+    fn leaving_room_keeps_local_home_server_until_stopped() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.header_section = HeaderSection::Online;
+        let mut audio = NullAudioEngine::new();
+        let mut runtime = test_online_runtime();
+        runtime.local_nickname = String::from("tester");
+        runtime.local_home_server = Some(
+            crate::online_net::start_home_server("127.0.0.1:0", None, RoomTransport::Sealed)
+                .expect("home server"),
+        );
+        core.online_host_room("tester");
+
+        assert!(handle_online_inline_input(
+            &mut core,
+            &mut audio,
+            KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL),
+            &mut runtime,
+        ));
+        assert!(core.online.session.is_none());
+        assert!(runtime.local_home_server.is_some());
+
+        stop_local_home_server(&mut core, &mut runtime);
+        assert!(runtime.local_home_server.is_none());
+        assert_eq!(core.status, "Local home server stopped");
+
+        stop_local_home_server(&mut core, &mut runtime);
+        assert_eq!(core.status, "No local home server running");
+    }
+
+    #[test]
+    fn online_tab_3_opens_invite_qr_with_code_hidden() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.header_section = HeaderSection::Online;
+        let mut audio = NullAudioEngine::new();
+        let mut runtime = test_online_runtime();
+        core.online_host_room("tester");
+        let room_code = core
+            .online
+            .session
+            .as_ref()
+            .expect("session")
+            .room_code
+            .clone();
+
+        assert!(handle_online_inline_input(
+            &mut core,
+            &mut audio,
+            KeyEvent::new(KeyCode::Char('3'), KeyModifiers::NONE),
+            &mut runtime,
+        ));
+        let view = runtime.host_invite_modal_view().expect("invite modal");
+        assert_eq!(view.invite_code, room_code);
+        assert!(view.qr_only);
+        let rows = view.qr_rows.expect("qr rows");
+        assert!(Arc::ptr_eq(
+            &rows,
+            &runtime
+                .host_invite_modal_view()
+                .and_then(|view| view.qr_rows)
+                .expect("cached rows")
+        ));
+        assert!(!view.code_revealed);
+        assert_eq!(runtime.invite_copied_status(), "Copied invite code");
+
+        assert!(handle_host_invite_modal_input(
+            &mut core,
+            KeyEvent::new(KeyCode::Char('1'), KeyModifiers::NONE),
+            &mut runtime,
+        ));
+        assert!(runtime.room_code_revealed);
+        assert!(runtime.invite_copied_status().ends_with(&room_code));
+    }
+
+    #[test]
+    fn now_playing_copy_text_formats_artist_and_title() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![
+            Track {
+                path: PathBuf::from("missing/a.flac"),
+                title: String::from("Song"),
+                artist: Some(String::from("Band")),
+                album: None,
+            },
+            Track {
+                path: PathBuf::from("missing/b.flac"),
+                title: String::from("Solo"),
+                artist: None,
+                album: None,
+            },
+        ];
+
+        let a = Path::new("missing/a.flac");
+        assert_eq!(now_playing_copy_text(&core, a, false), "Band - Song");
+        assert_eq!(now_playing_copy_text(&core, a, true), "missing/a.flac");
+        assert_eq!(
+            now_playing_copy_text(&core, Path::new("missing/b.flac"), false),
+            "Solo"
+        );
+
+        let audio = NullAudioEngine::new();
+        copy_now_playing(&mut core, &audio, false);
+        assert_eq!(core.status, "Nothing playing to copy");
+    }
+
+    #[test]
+    fn sync_decision_log_line_is_one_structured_line() {
+        let line = SyncDecision {
+            path: Path::new("music/a b.flac"),
+            remote_ms: 12_000,
+            delay_ms: 150,
+            target_ms: 12_150,
+            local_ms: 11_700,
+            drift_ms: 450,
+            threshold_ms: 300,
+            paused: false,
+            seeked: true,
+        }
+        .log_line();
+
+        assert_eq!(
+            line,
+            "event=set_playback_state path=\"music/a b.flac\" remote_ms=12000 delay_ms=150 target_ms=12150 local_ms=11700 drift_ms=450 threshold_ms=300 paused=false action=seek"
+        );
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn reconnect_without_previous_join_reports_status() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut runtime = test_online_runtime();
+
+        assert!(!reconnect_last_room(&mut core, &mut runtime));
+        assert_eq!(core.status, "No previous room to reconnect to");
+    }
+
     #[test]
     fn online_tab_l_is_page_shortcut() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -11599,11 +18035,87 @@ mod tests {
         assert_eq!(parsed, None);
     }
 
+    #[test]
+    fn parse_pasted_path_strips_quotes_and_file_uris() {
+        assert_eq!(
+            parse_pasted_path("  \"/home/tune/My Music\"  "),
+            PathBuf::from("/home/tune/My Music")
+        );
+        assert_eq!(
+            parse_pasted_path("'/home/tune/song.flac'"),
+            PathBuf::from("/home/tune/song.flac")
+        );
+        assert_eq!(
+            parse_pasted_path("file:///home/tune/My%20Music/song.flac"),
+            PathBuf::from("/home/tune/My Music/song.flac")
+        );
+        assert_eq!(
+            parse_pasted_path("file:///C:/Music/song.flac"),
+            PathBuf::from("C:/Music/song.flac")
+        );
+    }
+
+    #[test]
+    fn play_path_reads_the_folder_in_the_background_without_indexing_it() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        for name in ["one.mp3", "two.mp3"] {
+            fs::write(temp.path().join(name), b"x").expect("write");
+        }
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlayPath {
+            selected: 0,
+            input: temp.path().display().to_string(),
+        };
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+        assert!(matches!(panel, ActionPanelState::Closed));
+        assert!(core.play_path_requested.is_some());
+        assert!(audio.played.is_empty());
+
+        let mut library_runtime = LibraryRuntime::default();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let first = loop {
+            if let Some(first) = poll_library_scan(&mut core, &mut library_runtime) {
+                break first;
+            }
+            assert!(Instant::now() < deadline, "scan did not finish");
+            std::thread::sleep(Duration::from_millis(5));
+        };
+
+        assert_eq!(first.file_name(), Some(std::ffi::OsStr::new("one.mp3")));
+        assert_eq!(core.queue.len(), 2);
+        assert!(core.is_transient_track(&first));
+        assert!(library_runtime.index.tracks.is_empty());
+        assert!(library_runtime.active_scan.is_none());
+    }
+
+    #[test]
+    fn play_path_panel_reports_missing_paths_and_stays_open() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = TestAudioEngine::new();
+        let mut panel = ActionPanelState::PlayPath {
+            selected: 0,
+            input: String::new(),
+        };
+        for ch in "/definitely/missing/song.mp3".chars() {
+            handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Char(ch));
+        }
+        handle_action_panel_input(&mut core, &mut audio, &mut panel, KeyCode::Enter);
+
+        assert_eq!(core.status, "Path not found: /definitely/missing/song.mp3");
+        assert!(audio.played.is_empty());
+        assert!(matches!(panel, ActionPanelState::PlayPath { .. }));
+    }
+
     #[test]
     fn rewrite_room_server_addr_uses_home_server_host() {
         let rewritten = rewrite_room_server_addr_host("198.51.100.42:7878", "127.0.0.1:44623")
             .expect("rewritten address");
         assert_eq!(rewritten, "198.51.100.42:44623");
+
+        let sealed = rewrite_room_server_addr_host("198.51.100.42:7878", "tunes://127.0.0.1:44623")
+            .expect("rewritten address");
+        assert_eq!(sealed, "tunes://198.51.100.42:44623");
     }
 
     #[test]
@@ -11687,4 +18199,77 @@ mod tests {
         assert!(parse_home_link("").is_err());
         assert!(parse_home_link("   ").is_err());
     }
+
+    #[test]
+    fn startup_join_target_uses_default_server_for_bare_room() {
+        assert_eq!(
+            startup_join_target(" lounge ", "10.0.0.2:7878").expect("target"),
+            (String::from("10.0.0.2:7878"), String::from("lounge"))
+        );
+        assert_eq!(
+            startup_join_target("tune.example.com/room/lounge", "10.0.0.2:7878").expect("target"),
+            (
+                String::from("tune.example.com:7878"),
+                String::from("lounge")
+            )
+        );
+        assert!(startup_join_target("tune.example.com/", "10.0.0.2:7878").is_err());
+        assert!(startup_join_target("  ", "10.0.0.2:7878").is_err());
+    }
+
+    fn finish_startup_room(
+        core: &mut TuneCore,
+        runtime: &mut OnlineRuntime,
+        task: &mut Option<StartupRoomTask>,
+    ) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while task.is_some() && Instant::now() < deadline {
+            poll_startup_room(core, runtime, task);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(task.is_none(), "startup room check did not finish");
+    }
+
+    #[test]
+    fn startup_join_reports_unreachable_server_on_online_tab() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut runtime = test_online_runtime();
+        let mut task = start_startup_room(
+            &mut core,
+            &runtime,
+            &StartupJoin {
+                target: String::from("127.0.0.1:1/room/lounge"),
+                password: None,
+                host: false,
+            },
+        );
+
+        assert_eq!(core.header_section, HeaderSection::Online);
+        assert_eq!(core.status, "Joining room lounge...");
+        finish_startup_room(&mut core, &mut runtime, &mut task);
+        assert!(core.status.starts_with("Room not found or unavailable"));
+        assert!(!runtime.password_prompt_active);
+        assert!(core.online.session.is_none());
+    }
+
+    #[test]
+    fn startup_host_room_reports_unreachable_server() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut runtime = test_online_runtime();
+        runtime.home_server_addr = String::from("127.0.0.1:1");
+        let mut task = start_startup_room(
+            &mut core,
+            &runtime,
+            &StartupJoin {
+                target: String::from("lounge"),
+                password: Some(String::from("secret")),
+                host: true,
+            },
+        );
+
+        assert_eq!(core.status, "Creating room lounge...");
+        finish_startup_room(&mut core, &mut runtime, &mut task);
+        assert!(core.status.starts_with("Home server unavailable"));
+        assert!(core.online.session.is_none());
+    }
 }
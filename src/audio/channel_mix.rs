@@ -0,0 +1,198 @@
+use rodio::Source;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+
+/// -3 dB, so a mono downmix of two full-scale channels does not clip as badly
+/// as a plain sum.
+const MONO_SUM_GAIN: f32 = 0.707_945_8;
+const STEREO: rodio::ChannelCount = rodio::ChannelCount::new(2).unwrap();
+
+/// Mono and balance settings shared between the engine and every playing
+/// source, so changes apply without reopening the track.
+pub(crate) struct ChannelMixControl {
+    /// `f32` bits, -1.0 full left to 1.0 full right.
+    balance: AtomicU32,
+    mono: AtomicBool,
+}
+
+impl ChannelMixControl {
+    pub(crate) fn new() -> Self {
+        Self {
+            balance: AtomicU32::new(0.0_f32.to_bits()),
+            mono: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn set_balance(&self, balance: f32) {
+        self.balance.store(balance.to_bits(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn balance(&self) -> f32 {
+        f32::from_bits(self.balance.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn set_mono(&self, enabled: bool) {
+        self.mono.store(enabled, Ordering::Relaxed);
+    }
+
+    pub(crate) fn mono(&self) -> bool {
+        self.mono.load(Ordering::Relaxed)
+    }
+}
+
+/// One stereo frame after the mono downmix and balance. Balance only ever
+/// turns the opposite channel down, so the louder side stays at full level.
+fn mix_frame(left: f32, right: f32, balance: f32, mono: bool) -> (f32, f32) {
+    let (left, right) = if mono {
+        let sum = (left + right) * MONO_SUM_GAIN;
+        (sum, sum)
+    } else {
+        (left, right)
+    };
+    let balance = balance.clamp(-1.0, 1.0);
+    (
+        left * (1.0 - balance.max(0.0)),
+        right * (1.0 + balance.min(0.0)),
+    )
+}
+
+/// Applies mono and balance to stereo sources. Mono sources are spread to both
+/// channels first so balance can pan them. Other layouts pass through untouched.
+pub(crate) struct ChannelMix<S> {
+    inner: S,
+    control: Arc<ChannelMixControl>,
+    /// Right sample of the frame whose left sample was just returned.
+    pending_right: Option<f32>,
+}
+
+impl<S: Source> ChannelMix<S> {
+    pub(crate) fn new(inner: S, control: Arc<ChannelMixControl>) -> Self {
+        Self {
+            inner,
+            control,
+            pending_right: None,
+        }
+    }
+}
+
+impl<S: Source> Iterator for ChannelMix<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(right) = self.pending_right.take() {
+            return Some(right);
+        }
+        let left = self.inner.next()?;
+        let (left, right) = match self.inner.channels().get() {
+            1 => mix_frame(left, left, self.control.balance(), false),
+            2 => {
+                let Some(right) = self.inner.next() else {
+                    return Some(left);
+                };
+                mix_frame(left, right, self.control.balance(), self.control.mono())
+            }
+            _ => return Some(left),
+        };
+        self.pending_right = Some(right);
+        Some(left)
+    }
+}
+
+impl<S: Source> Source for ChannelMix<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        let upmix = if self.inner.channels().get() == 1 {
+            2
+        } else {
+            1
+        };
+        self.inner
+            .current_span_len()
+            .map(|len| len * upmix + usize::from(self.pending_right.is_some()))
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        match self.inner.channels().get() {
+            1 => STEREO,
+            _ => self.inner.channels(),
+        }
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+        self.inner.try_seek(pos)?;
+        self.pending_right = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChannelMix, ChannelMixControl, mix_frame};
+    use rodio::Source;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    struct MonoSamples(std::vec::IntoIter<f32>);
+
+    impl Iterator for MonoSamples {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            self.0.next()
+        }
+    }
+
+    impl Source for MonoSamples {
+        fn current_span_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> rodio::ChannelCount {
+            rodio::ChannelCount::new(1).unwrap()
+        }
+
+        fn sample_rate(&self) -> rodio::SampleRate {
+            rodio::SampleRate::new(44_100).unwrap()
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn mono_sources_are_spread_to_stereo_so_balance_pans_them() {
+        let control = Arc::new(ChannelMixControl::new());
+        control.set_balance(1.0);
+        let mix = ChannelMix::new(MonoSamples(vec![0.5, 0.25].into_iter()), control);
+        assert_eq!(mix.channels().get(), 2);
+        assert_eq!(mix.collect::<Vec<_>>(), vec![0.0, 0.5, 0.0, 0.25]);
+    }
+
+    #[test]
+    fn mono_sums_both_channels_at_minus_three_db() {
+        let (left, right) = mix_frame(0.5, 0.5, 0.0, true);
+        assert!((left - 0.708).abs() < 0.001);
+        assert_eq!(left, right);
+
+        assert_eq!(mix_frame(0.5, -0.25, 0.0, false), (0.5, -0.25));
+    }
+
+    #[test]
+    fn balance_turns_down_only_the_opposite_channel() {
+        assert_eq!(mix_frame(0.8, 0.8, 0.5, false), (0.4, 0.8));
+        assert_eq!(mix_frame(0.8, 0.8, -1.0, false), (0.8, 0.0));
+
+        let (left, right) = mix_frame(0.4, 0.0, 1.0, true);
+        assert_eq!(left, 0.0);
+        assert!(right > 0.28);
+    }
+}
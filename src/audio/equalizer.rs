@@ -0,0 +1,313 @@
+use rodio::Source;
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Bandwidth of every peaking band. The default bands sit about two octaves
+/// apart, so a fairly wide bell keeps neighbouring bands overlapping smoothly.
+const BAND_Q: f32 = 0.9;
+/// Bands at or above this fraction of the Nyquist frequency are skipped; the
+/// bilinear transform cramps them and they would only add noise.
+const MAX_BAND_NYQUIST_FRACTION: f32 = 0.95;
+
+/// Equalizer settings shared between the engine and every playing source, so a
+/// change applies to the current and crossfading tracks without reopening them.
+/// `generation` bumps on each change and sources rebuild their filters when it
+/// moves.
+pub(crate) struct EqualizerControl {
+    bands: Mutex<Vec<(f32, f32)>>,
+    generation: AtomicU32,
+}
+
+impl EqualizerControl {
+    pub(crate) fn new(bands: &[(f32, f32)]) -> Self {
+        Self {
+            bands: Mutex::new(bands.to_vec()),
+            generation: AtomicU32::new(0),
+        }
+    }
+
+    pub(crate) fn set_bands(&self, bands: &[(f32, f32)]) {
+        if let Ok(mut current) = self.bands.lock() {
+            *current = bands.to_vec();
+        }
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    pub(crate) fn bands(&self) -> Vec<(f32, f32)> {
+        self.bands
+            .lock()
+            .map(|bands| bands.clone())
+            .unwrap_or_default()
+    }
+
+    fn generation(&self) -> u32 {
+        self.generation.load(Ordering::Acquire)
+    }
+}
+
+/// Normalized peaking-filter coefficients (RBJ audio EQ cookbook).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Biquad {
+    /// `None` when the band would not change the signal or cannot be
+    /// represented at this sample rate.
+    fn peaking(frequency_hz: f32, gain_db: f32, sample_rate: f32) -> Option<Self> {
+        if gain_db == 0.0
+            || !gain_db.is_finite()
+            || !frequency_hz.is_finite()
+            || frequency_hz <= 0.0
+            || frequency_hz >= sample_rate * 0.5 * MAX_BAND_NYQUIST_FRACTION
+        {
+            return None;
+        }
+
+        let amplitude = 10_f32.powf(gain_db / 40.0);
+        let omega = 2.0 * PI * frequency_hz / sample_rate;
+        let alpha = omega.sin() / (2.0 * BAND_Q);
+        let cos_omega = omega.cos();
+        let a0 = 1.0 + alpha / amplitude;
+
+        Some(Self {
+            b0: (1.0 + alpha * amplitude) / a0,
+            b1: (-2.0 * cos_omega) / a0,
+            b2: (1.0 - alpha * amplitude) / a0,
+            a1: (-2.0 * cos_omega) / a0,
+            a2: (1.0 - alpha / amplitude) / a0,
+        })
+    }
+}
+
+/// Direct form I history for one band on one channel: `[x1, x2, y1, y2]`.
+type BiquadState = [f32; 4];
+
+/// Runs the decoded samples through one peaking filter per non-flat band.
+/// When every band is at 0 dB there are no filters and samples pass through
+/// untouched.
+pub(crate) struct Equalizer<S> {
+    inner: S,
+    control: Arc<EqualizerControl>,
+    seen_generation: u32,
+    sample_rate: u32,
+    channels: usize,
+    filters: Vec<Biquad>,
+    /// `filters.len() * channels` entries, band-major.
+    states: Vec<BiquadState>,
+    channel: usize,
+}
+
+impl<S: Source> Equalizer<S> {
+    pub(crate) fn new(inner: S, control: Arc<EqualizerControl>) -> Self {
+        let mut equalizer = Self {
+            seen_generation: control.generation(),
+            sample_rate: inner.sample_rate().get(),
+            channels: usize::from(inner.channels().get()),
+            inner,
+            control,
+            filters: Vec::new(),
+            states: Vec::new(),
+            channel: 0,
+        };
+        equalizer.rebuild_filters();
+        equalizer
+    }
+
+    fn rebuild_filters(&mut self) {
+        let sample_rate = self.sample_rate as f32;
+        let filters: Vec<Biquad> = self
+            .control
+            .bands()
+            .iter()
+            .filter_map(|&(frequency, gain)| Biquad::peaking(frequency, gain, sample_rate))
+            .collect();
+        let state_len = filters.len() * self.channels;
+        // Keep the history when the band layout is unchanged so dragging a gain
+        // does not click.
+        if self.states.len() != state_len {
+            self.states = vec![[0.0; 4]; state_len];
+        }
+        self.filters = filters;
+    }
+
+    /// Picks up equalizer changes and format changes between spans. Only called
+    /// on frame boundaries so every channel of a frame uses the same filters.
+    fn refresh_if_changed(&mut self) {
+        let generation = self.control.generation();
+        let sample_rate = self.inner.sample_rate().get();
+        let channels = usize::from(self.inner.channels().get());
+        if generation == self.seen_generation
+            && sample_rate == self.sample_rate
+            && channels == self.channels
+        {
+            return;
+        }
+
+        if channels != self.channels {
+            self.states.clear();
+        }
+        self.seen_generation = generation;
+        self.sample_rate = sample_rate;
+        self.channels = channels;
+        self.rebuild_filters();
+    }
+}
+
+impl<S: Source> Iterator for Equalizer<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel == 0 {
+            self.refresh_if_changed();
+        }
+        let sample = self.inner.next()?;
+        let channel = self.channel;
+        self.channel = (self.channel + 1) % self.channels.max(1);
+
+        let mut value = sample;
+        for (band, filter) in self.filters.iter().enumerate() {
+            let state = &mut self.states[band * self.channels + channel];
+            let [x1, x2, y1, y2] = *state;
+            let output = filter.b0 * value + filter.b1 * x1 + filter.b2 * x2
+                - filter.a1 * y1
+                - filter.a2 * y2;
+            *state = [value, x1, output, y1];
+            value = output;
+        }
+        Some(if value.is_finite() { value } else { sample })
+    }
+}
+
+impl<S: Source> Source for Equalizer<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.current_span_len()
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+        self.inner.try_seek(pos)?;
+        // History from before the jump would ring into the new position.
+        self.states.iter_mut().for_each(|state| *state = [0.0; 4]);
+        self.channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Biquad, Equalizer, EqualizerControl};
+    use rodio::Source;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Mono sine at `frequency` Hz, 48 kHz, `len` samples.
+    struct Sine {
+        frequency: f32,
+        index: usize,
+        len: usize,
+    }
+
+    impl Iterator for Sine {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            (self.index < self.len).then(|| {
+                let t = self.index as f32 / 48_000.0;
+                self.index += 1;
+                (2.0 * std::f32::consts::PI * self.frequency * t).sin() * 0.25
+            })
+        }
+    }
+
+    impl Source for Sine {
+        fn current_span_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> rodio::ChannelCount {
+            rodio::ChannelCount::new(1).expect("non-zero")
+        }
+
+        fn sample_rate(&self) -> rodio::SampleRate {
+            rodio::SampleRate::new(48_000).expect("non-zero")
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    fn sine(frequency: f32) -> Sine {
+        Sine {
+            frequency,
+            index: 0,
+            len: 48_000,
+        }
+    }
+
+    /// Peak over the second half, after the filters have settled.
+    fn settled_peak(samples: &[f32]) -> f32 {
+        samples[samples.len() / 2..]
+            .iter()
+            .fold(0.0_f32, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn flat_bands_pass_samples_through_unchanged() {
+        let control = Arc::new(EqualizerControl::new(&[(60.0, 0.0), (910.0, 0.0)]));
+        let expected: Vec<f32> = sine(440.0).collect();
+        let equalized: Vec<f32> = Equalizer::new(sine(440.0), control).collect();
+
+        assert_eq!(equalized, expected);
+    }
+
+    #[test]
+    fn peaking_band_boosts_its_frequency_and_leaves_distant_ones_alone() {
+        let control = Arc::new(EqualizerControl::new(&[(1_000.0, 6.0)]));
+        let boosted: Vec<f32> = Equalizer::new(sine(1_000.0), Arc::clone(&control)).collect();
+        let distant: Vec<f32> = Equalizer::new(sine(40.0), control).collect();
+
+        // +6 dB is about 2x amplitude at the centre frequency.
+        let gain = settled_peak(&boosted) / 0.25;
+        assert!((gain - 1.995).abs() < 0.05, "centre gain was {gain}");
+        let gain = settled_peak(&distant) / 0.25;
+        assert!((gain - 1.0).abs() < 0.05, "distant gain was {gain}");
+    }
+
+    #[test]
+    fn band_changes_apply_to_a_playing_source() {
+        let control = Arc::new(EqualizerControl::new(&[(1_000.0, 0.0)]));
+        let mut equalizer = Equalizer::new(sine(1_000.0), Arc::clone(&control));
+        let before: Vec<f32> = equalizer.by_ref().take(24_000).collect();
+        control.set_bands(&[(1_000.0, -12.0)]);
+        let after: Vec<f32> = equalizer.collect();
+
+        assert!(settled_peak(&before) > 0.24);
+        assert!(settled_peak(&after) < 0.07);
+    }
+
+    #[test]
+    fn bands_above_nyquist_are_skipped() {
+        assert_eq!(Biquad::peaking(30_000.0, 6.0, 48_000.0), None);
+        assert_eq!(Biquad::peaking(14_000.0, 0.0, 48_000.0), None);
+        assert!(Biquad::peaking(14_000.0, 6.0, 48_000.0).is_some());
+    }
+}
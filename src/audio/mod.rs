@@ -1,3 +1,4 @@
+use crate::model::CrossfadeCurve;
 use anyhow::{Context, Result};
 use rodio::Source;
 use rodio::cpal::Device;
@@ -8,14 +9,34 @@ use rodio::{Decoder, DeviceSinkBuilder, MixerDeviceSink, Player};
 #[cfg(unix)]
 use std::ffi::CString;
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::time::Duration;
 use std::time::Instant;
+use unicode_normalization::UnicodeNormalization;
+
+mod channel_mix;
+mod equalizer;
+mod opus;
+mod time_stretch;
+
+use channel_mix::{ChannelMix, ChannelMixControl};
+use equalizer::{Equalizer, EqualizerControl};
+use opus::OggOpusSource;
+use time_stretch::TimeStretch;
 
 const MAX_VOLUME: f32 = 2.5;
+pub const MIN_PLAYBACK_SPEED: f32 = 0.5;
+pub const MAX_PLAYBACK_SPEED: f32 = 3.0;
+/// Stop ramps audible sinks down over this long instead of cutting mid-waveform.
+const STOP_FADE: Duration = Duration::from_millis(40);
+const STOP_FADE_STEPS: u32 = 8;
 #[cfg(target_os = "linux")]
 const LINUX_PREFERRED_BUFFER_FRAMES: u32 = 2_048;
+/// How often a still-downloading stream cache file is checked for completion.
+const STREAM_COMPLETION_POLL: Duration = Duration::from_millis(500);
 
 pub trait AudioEngine {
     fn play(&mut self, path: &Path) -> Result<()>;
@@ -29,8 +50,20 @@ pub trait AudioEngine {
     fn position(&self) -> Option<Duration>;
     fn duration(&self) -> Option<Duration>;
     fn seek_to(&mut self, position: Duration) -> Result<()>;
+    /// The user's volume level. While muted this is the level `unmute` goes
+    /// back to, not the silent output.
     fn volume(&self) -> f32;
     fn set_volume(&mut self, volume: f32);
+    /// Extra 0.0-1.0 gain on top of `volume` for fade-outs like the sleep
+    /// timer's. Kept apart from `volume` so it is never saved.
+    fn fade_out_gain(&self) -> f32 {
+        1.0
+    }
+    fn set_fade_out_gain(&mut self, _gain: f32) {}
+    /// Silences output without touching `volume`.
+    fn mute(&mut self);
+    fn unmute(&mut self);
+    fn is_muted(&self) -> bool;
     fn output_name(&self) -> Option<String>;
     fn reload_driver(&mut self) -> Result<()>;
     fn available_outputs(&self) -> Vec<String>;
@@ -42,49 +75,358 @@ pub trait AudioEngine {
     fn set_crossfade_seconds(&mut self, seconds: u16);
     fn crossfade_queued_track(&self) -> Option<&Path>;
     fn is_finished(&self) -> bool;
+    /// True once the output stream reported that its device went away, such as
+    /// an unplugged USB DAC. Cleared when the output is reopened.
+    fn device_lost(&self) -> bool {
+        false
+    }
+    /// Peak output level (0.0 silent, 1.0 full scale) since the last call, after
+    /// volume. `None` when the engine cannot meter its output.
+    fn output_level(&self) -> Option<f32> {
+        None
+    }
+    /// True while the current track is a stream that is still downloading,
+    /// so its length is unknown and seeking has to wait.
+    fn seek_pending(&self) -> bool {
+        false
+    }
+    /// Length of the stream now playing as announced by whoever serves it.
+    /// Shown until the download finishes and the file reports its own.
+    fn announce_stream_duration(&mut self, _duration: Duration) {}
+    /// Extra time before the crossfade window at which the next track is
+    /// queued. It opens silently and the fade still starts on schedule.
+    fn crossfade_lead(&self) -> Duration {
+        Duration::ZERO
+    }
+    fn set_crossfade_lead(&mut self, _lead: Duration) {}
+    fn crossfade_curve(&self) -> CrossfadeCurve {
+        CrossfadeCurve::Linear
+    }
+    fn set_crossfade_curve(&mut self, _curve: CrossfadeCurve) {}
+    /// Whether loudness normalization may only boost as far as the track's
+    /// peak allows.
+    fn prevent_clipping(&self) -> bool {
+        true
+    }
+    fn set_prevent_clipping(&mut self, _enabled: bool) {}
+    /// Whether normalization prefers a file's album gain over its track gain,
+    /// keeping the loudness differences between songs of one album.
+    fn album_gain(&self) -> bool {
+        false
+    }
+    fn set_album_gain(&mut self, _enabled: bool) {}
+    /// Replaces the equalizer bands as `(frequency Hz, gain dB)` pairs. All
+    /// bands at 0 dB bypass the filters.
+    fn set_equalizer(&mut self, bands: &[(f32, f32)]);
+    fn equalizer(&self) -> Vec<(f32, f32)>;
+    /// Whether the next track may be queued to start the instant the current
+    /// one ends, with no gap, for albums whose tracks run together.
+    fn gapless(&self) -> bool {
+        false
+    }
+    fn set_gapless(&mut self, _enabled: bool) {}
+    /// Queues `path` to follow the current track without a gap. It shows as
+    /// `crossfade_queued_track` until it takes over, and is dropped like a
+    /// crossfade on seek.
+    fn queue_gapless(&mut self, path: &Path) -> Result<()>;
+    /// Playback rate, 1.0 for normal speed. `position` and `duration` stay in
+    /// track time; divide by this for wall-clock time.
+    fn playback_speed(&self) -> f32 {
+        1.0
+    }
+    /// Clamped to `MIN_PLAYBACK_SPEED..=MAX_PLAYBACK_SPEED`.
+    fn set_playback_speed(&mut self, _speed: f32) {}
+    /// Whether a speed other than 1.0 keeps the original pitch by time
+    /// stretching instead of resampling.
+    fn preserve_pitch(&self) -> bool {
+        true
+    }
+    fn set_preserve_pitch(&mut self, _enabled: bool) {}
+    /// Section `(A, B)` of the current track that repeats: passing B jumps back
+    /// to A. Cleared whenever another track starts.
+    fn ab_loop(&self) -> Option<(Duration, Duration)> {
+        None
+    }
+    fn set_ab_loop(&mut self, _bounds: Option<(Duration, Duration)>) {}
+    /// Left/right balance, -1.0 full left to 1.0 full right. Turns down the
+    /// opposite channel of stereo tracks; mono tracks are spread to both
+    /// channels first so they pan too.
+    fn balance(&self) -> f32 {
+        0.0
+    }
+    /// Clamped with `clamp_balance`.
+    fn set_balance(&mut self, _balance: f32) {}
+    /// Whether stereo tracks are downmixed so both speakers play both channels.
+    fn mono(&self) -> bool {
+        false
+    }
+    fn set_mono(&mut self, _enabled: bool) {}
+    /// True for engines that play nothing at all, like `NullAudioEngine` when
+    /// no sound device could be opened.
+    fn is_silent(&self) -> bool {
+        false
+    }
+}
+
+/// Volume multipliers `(outgoing, incoming)` at `progress` (0.0-1.0) through a
+/// crossfade. Every curve starts at `(1, 0)` and ends at `(0, 1)`.
+pub(crate) fn crossfade_gains(curve: CrossfadeCurve, progress: f32) -> (f32, f32) {
+    let progress = progress.clamp(0.0, 1.0);
+    match curve {
+        CrossfadeCurve::Linear => (1.0 - progress, progress),
+        CrossfadeCurve::EqualPower => {
+            let angle = progress * std::f32::consts::FRAC_PI_2;
+            (angle.cos(), angle.sin())
+        }
+        CrossfadeCurve::Logarithmic => {
+            let ramp = |amount: f32| (1.0 + 9.0 * amount).log10();
+            (ramp(1.0 - progress), ramp(progress))
+        }
+    }
+}
+
+/// Keeps a balance inside -1.0..=1.0; anything unusable centers it.
+pub fn clamp_balance(balance: f32) -> f32 {
+    if balance.is_finite() {
+        balance.clamp(-1.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Keeps a requested speed inside the supported range; anything unusable
+/// falls back to normal speed.
+pub fn clamp_playback_speed(speed: f32) -> f32 {
+    if speed.is_finite() {
+        speed.clamp(MIN_PLAYBACK_SPEED, MAX_PLAYBACK_SPEED)
+    } else {
+        1.0
+    }
+}
+
+/// A decoded track after speed, equalizer, and channel mixing, metered last
+/// so the level matches what reaches the speakers.
+type ProcessedSource<S> = LevelMeter<ChannelMix<Equalizer<TimeStretch<S>>>>;
+
+/// Volume, mute, fade and loudness normalization gains of the current and the
+/// queued track. Kept apart from the output stream so the level a track plays
+/// at can be worked out without a sound device.
+struct OutputLevels {
+    volume: f32,
+    fade_out_gain: f32,
+    muted: bool,
+    loudness_normalization: bool,
+    prevent_clipping: bool,
+    album_gain: bool,
+    track_gain: f32,
+    next_track_gain: f32,
+}
+
+impl Default for OutputLevels {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            fade_out_gain: 1.0,
+            muted: false,
+            loudness_normalization: false,
+            prevent_clipping: true,
+            album_gain: false,
+            track_gain: 1.0,
+            next_track_gain: 1.0,
+        }
+    }
+}
+
+impl OutputLevels {
+    /// Volume actually sent to the sinks; zero while muted so crossfade and
+    /// gain updates cannot bring the sound back.
+    fn output_volume(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume * self.fade_out_gain
+        }
+    }
+
+    fn effective_volume(&self) -> f32 {
+        (self.output_volume() * self.track_gain).clamp(0.0, MAX_VOLUME)
+    }
+
+    fn next_volume(&self, fade_gain: f32) -> f32 {
+        (self.output_volume() * self.next_track_gain * fade_gain).clamp(0.0, MAX_VOLUME)
+    }
+
+    /// Normalization gain for `path`, or 1.0 while normalization is off.
+    fn gain_for(&self, path: &Path) -> f32 {
+        if self.loudness_normalization {
+            self.loudness_gain(path)
+        } else {
+            1.0
+        }
+    }
+
+    /// Normalization gain for `path`: its ReplayGain or iTunNORM tags when it has
+    /// any, otherwise a scan of the opening seconds.
+    fn loudness_gain(&self, path: &Path) -> f32 {
+        let tags = crate::library::replay_gain_for_path(path);
+        replay_gain_multiplier(&tags, self.album_gain, self.prevent_clipping).unwrap_or_else(|| {
+            WasapiAudioEngine::estimate_track_gain(path, self.prevent_clipping).unwrap_or(1.0)
+        })
+    }
+
+    fn set_loudness_normalization(
+        &mut self,
+        enabled: bool,
+        current: Option<&Path>,
+        next: Option<&Path>,
+    ) {
+        self.loudness_normalization = enabled;
+        self.refresh_gains(current, next);
+    }
+
+    /// Recomputes the gains of the tracks already loaded, so toggling
+    /// normalization mid-song applies at once.
+    fn refresh_gains(&mut self, current: Option<&Path>, next: Option<&Path>) {
+        self.track_gain = current.map_or(1.0, |path| self.gain_for(path));
+        self.next_track_gain = next.map_or(1.0, |path| self.gain_for(path));
+    }
 }
 
 pub struct WasapiAudioEngine {
     stream: MixerDeviceSink,
     sink: Player,
     next_sink: Option<Player>,
+    /// Set by the stream's error callback when its device disappears. Each
+    /// stream gets its own flag so a late error from a replaced one is ignored.
+    device_lost: Arc<AtomicBool>,
     current: Option<PathBuf>,
     next_track: Option<PathBuf>,
     track_duration: Option<Duration>,
     next_track_duration: Option<Duration>,
     crossfade_started_at: Option<Instant>,
-    volume: f32,
+    crossfade_lead: Duration,
+    levels: OutputLevels,
     selected_output: Option<String>,
-    loudness_normalization: bool,
+    gapless: bool,
+    /// Set while the next track is appended behind the current one in `sink`;
+    /// storing `true` ends it before it plays.
+    gapless_cancel: Option<Arc<AtomicBool>>,
     crossfade_seconds: u16,
-    track_gain: f32,
-    next_track_gain: f32,
+    crossfade_curve: CrossfadeCurve,
+    level_peak: Arc<AtomicU32>,
+    equalizer: Arc<EqualizerControl>,
+    channel_mix: Arc<ChannelMixControl>,
+    playback_speed: f32,
+    preserve_pitch: bool,
+    ab_loop: Option<(Duration, Duration)>,
+    /// Time-stretch ratio shared by every playing source, as `f32` bits. It is
+    /// the playback speed while preserving pitch and 1.0 otherwise.
+    stretch_ratio: Arc<AtomicU32>,
+    /// Track position in microseconds, kept by the time stretcher of the
+    /// current and the queued track so speed changes cannot skew it.
+    track_clock: Arc<AtomicU64>,
+    next_track_clock: Arc<AtomicU64>,
+    seek_pending: bool,
+    reopen_before_seek: bool,
+    stream_polled_at: Option<Instant>,
 }
 
 impl WasapiAudioEngine {
     pub fn new() -> Result<Self> {
-        let (stream, sink) = Self::open_output_stream(None)?;
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let (stream, sink) = Self::open_output_stream(None, &device_lost)?;
 
         Ok(Self {
             stream,
             sink,
             next_sink: None,
+            device_lost,
             current: None,
             next_track: None,
             track_duration: None,
             next_track_duration: None,
             crossfade_started_at: None,
-            volume: 1.0,
+            crossfade_lead: Duration::ZERO,
+            levels: OutputLevels::default(),
             selected_output: None,
-            loudness_normalization: false,
+            gapless: false,
+            gapless_cancel: None,
             crossfade_seconds: 0,
-            track_gain: 1.0,
-            next_track_gain: 1.0,
+            crossfade_curve: CrossfadeCurve::default(),
+            level_peak: Arc::new(AtomicU32::new(0)),
+            equalizer: Arc::new(EqualizerControl::new(
+                &crate::model::default_equalizer_bands(),
+            )),
+            channel_mix: Arc::new(ChannelMixControl::new()),
+            playback_speed: 1.0,
+            preserve_pitch: true,
+            ab_loop: None,
+            stretch_ratio: Arc::new(AtomicU32::new(1.0_f32.to_bits())),
+            track_clock: Arc::new(AtomicU64::new(0)),
+            next_track_clock: Arc::new(AtomicU64::new(0)),
+            seek_pending: false,
+            reopen_before_seek: false,
+            stream_polled_at: None,
         })
     }
 
+    fn output_volume(&self) -> f32 {
+        self.levels.output_volume()
+    }
+
     fn effective_volume(&self) -> f32 {
-        (self.volume * self.track_gain).clamp(0.0, MAX_VOLUME)
+        self.levels.effective_volume()
+    }
+
+    fn next_volume(&self, fade_gain: f32) -> f32 {
+        self.levels.next_volume(fade_gain)
+    }
+
+    /// Pushes the current volume to both sinks, keeping any crossfade balance.
+    fn apply_volume(&self) {
+        let (outgoing, incoming) = crossfade_gains(self.crossfade_curve, self.crossfade_progress());
+        self.sink
+            .set_volume((self.effective_volume() * outgoing).clamp(0.0, MAX_VOLUME));
+        if let Some(next) = &self.next_sink {
+            next.set_volume(self.next_volume(incoming));
+        }
+    }
+
+    /// Rate the sinks resample at: the playback speed, unless pitch is kept and
+    /// the time stretcher does the work instead.
+    fn sink_speed(&self) -> f32 {
+        if self.preserve_pitch {
+            1.0
+        } else {
+            self.playback_speed
+        }
+    }
+
+    fn apply_speed(&self) {
+        let ratio = if self.preserve_pitch {
+            self.playback_speed
+        } else {
+            1.0
+        };
+        self.stretch_ratio.store(ratio.to_bits(), Ordering::Relaxed);
+        self.sink.set_speed(self.sink_speed());
+        if let Some(next) = &self.next_sink {
+            next.set_speed(self.sink_speed());
+        }
+    }
+
+    /// Wraps a decoded track in the shared processing chain and returns it with
+    /// the clock its time stretcher keeps.
+    fn processed_source<S: Source>(&self, source: S) -> (ProcessedSource<S>, Arc<AtomicU64>) {
+        let clock = Arc::new(AtomicU64::new(0));
+        let stretched =
+            TimeStretch::new(source, Arc::clone(&self.stretch_ratio), Arc::clone(&clock));
+        let equalized = Equalizer::new(stretched, Arc::clone(&self.equalizer));
+        let source = LevelMeter::new(
+            ChannelMix::new(equalized, Arc::clone(&self.channel_mix)),
+            Arc::clone(&self.level_peak),
+        );
+        (source, clock)
     }
 
     fn promote_next_if_ready(&mut self) {
@@ -97,37 +439,102 @@ impl WasapiAudioEngine {
         };
 
         self.sink = next_sink;
+        self.adopt_next_track();
+    }
+
+    /// True once a gapless track appended behind the current one is the one
+    /// playing. The sink drops finished sources, so its length tells.
+    fn gapless_handed_over(&self) -> bool {
+        self.gapless_cancel.is_some() && self.sink.len() <= 1
+    }
+
+    fn promote_gapless_if_started(&mut self) {
+        if !self.gapless_handed_over() {
+            return;
+        }
+        self.gapless_cancel = None;
+        self.adopt_next_track();
+        self.sink.set_volume(self.effective_volume());
+    }
+
+    fn adopt_next_track(&mut self) {
         self.current = self.next_track.take();
         self.track_duration = self.next_track_duration.take();
-        self.track_gain = self.next_track_gain;
-        self.next_track_gain = 1.0;
+        self.track_clock = Arc::clone(&self.next_track_clock);
+        self.ab_loop = None;
+        self.levels.track_gain = self.levels.next_track_gain;
+        self.levels.next_track_gain = 1.0;
         self.crossfade_started_at = None;
+        self.seek_pending = self.track_duration.is_none()
+            && self
+                .current
+                .as_deref()
+                .is_some_and(|path| stream_cache_wav_header(path).is_some());
+        self.reopen_before_seek = false;
+        self.stream_polled_at = None;
     }
 
     fn clear_next(&mut self) {
+        self.promote_gapless_if_started();
+        if let Some(cancel) = self.gapless_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
         if let Some(next) = self.next_sink.take() {
             next.stop();
         }
         self.next_track = None;
         self.next_track_duration = None;
-        self.next_track_gain = 1.0;
+        self.levels.next_track_gain = 1.0;
         self.crossfade_started_at = None;
     }
 
+    fn fade_out_for_stop(&self) {
+        if self.sink.is_paused() || self.sink.empty() {
+            return;
+        }
+        let start_current = self.sink.volume();
+        let start_next = self.next_sink.as_ref().map_or(0.0, Player::volume);
+        for step in 1..=STOP_FADE_STEPS {
+            let remaining = 1.0 - step as f32 / STOP_FADE_STEPS as f32;
+            self.sink.set_volume(start_current * remaining);
+            if let Some(next) = &self.next_sink {
+                next.set_volume(start_next * remaining);
+            }
+            std::thread::sleep(STOP_FADE / STOP_FADE_STEPS);
+        }
+    }
+
+    fn remaining(&self) -> Option<Duration> {
+        self.duration()
+            .zip(self.position())
+            .map(|(duration, position)| duration.saturating_sub(position))
+    }
+
+    /// False while a queued track is still inside its look-ahead lead.
+    fn crossfade_window_open(&self) -> bool {
+        self.crossfade_started_at
+            .is_none_or(|started| Instant::now() >= started)
+    }
+
     fn crossfade_progress(&self) -> f32 {
         let Some(started) = self.crossfade_started_at else {
             return 0.0;
         };
         let duration = self.crossfade_seconds.max(1) as f32;
-        (started.elapsed().as_secs_f32() / duration).clamp(0.0, 1.0)
+        let elapsed = Instant::now().saturating_duration_since(started);
+        (elapsed.as_secs_f32() / duration).clamp(0.0, 1.0)
     }
 
-    fn estimate_track_gain(path: &Path) -> Result<f32> {
-        let file = File::open(path).with_context(|| {
-            format!("failed to open track for loudness scan {}", path.display())
-        })?;
-        let source = Decoder::try_from(file)
-            .with_context(|| format!("failed to decode for loudness scan {}", path.display()))?;
+    /// Recomputes loudness gain for the tracks already loaded so toggling
+    /// normalization mid-song applies immediately instead of on the next `play`.
+    pub fn refresh_current_gain(&mut self) {
+        self.levels
+            .refresh_gains(self.current.as_deref(), self.next_track.as_deref());
+        self.apply_volume();
+    }
+
+    fn estimate_track_gain(path: &Path, prevent_clipping: bool) -> Result<f32> {
+        let source = open_track_source(path).context("loudness scan failed")?;
 
         let channels = usize::from(source.channels().get()).max(1);
         let sample_rate = usize::try_from(source.sample_rate().get())
@@ -136,10 +543,12 @@ impl WasapiAudioEngine {
         let max_samples = sample_rate.saturating_mul(channels).saturating_mul(10);
 
         let mut sum_sq = 0.0_f64;
+        let mut peak = 0.0_f64;
         let mut count = 0_u64;
         for sample in source.take(max_samples) {
             let v = f64::from(sample);
             sum_sq += v * v;
+            peak = peak.max(v.abs());
             count = count.saturating_add(1);
         }
 
@@ -148,41 +557,37 @@ impl WasapiAudioEngine {
         }
 
         let rms = (sum_sq / count as f64).sqrt();
-        if !(rms.is_finite()) || rms <= 0.000_01 {
-            return Ok(1.0);
-        }
-
-        let target_rms = 0.20_f64;
-        Ok((target_rms / rms).clamp(0.5, 1.8) as f32)
+        Ok(normalization_gain(rms, peak, prevent_clipping))
     }
 
     fn streamed_wav_has_unknown_duration(path: &Path) -> bool {
-        if !path
-            .to_string_lossy()
-            .to_ascii_lowercase()
-            .contains("tunetui_stream_cache")
-        {
-            return false;
+        stream_cache_wav_header(path).is_some_and(|(_, data_size)| data_size == u32::MAX)
+    }
+
+    /// Picks up a stream cache file that finished downloading while playing:
+    /// the duration comes from the finalized header, and the next seek reopens
+    /// the file because the running decoder still thinks it is unbounded.
+    fn poll_completed_stream(&mut self) {
+        if !self.seek_pending {
+            return;
         }
-        if !path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+        if self
+            .stream_polled_at
+            .is_some_and(|at| at.elapsed() < STREAM_COMPLETION_POLL)
         {
-            return false;
+            return;
         }
-        let mut file = match File::open(path) {
-            Ok(file) => file,
-            Err(_) => return false,
+        self.stream_polled_at = Some(Instant::now());
+        let Some(duration) = self
+            .current
+            .as_deref()
+            .and_then(completed_stream_wav_duration)
+        else {
+            return;
         };
-        if file.seek(SeekFrom::Start(40)).is_err() {
-            return false;
-        }
-        let mut data_size = [0_u8; 4];
-        if file.read_exact(&mut data_size).is_err() {
-            return false;
-        }
-        u32::from_le_bytes(data_size) == u32::MAX
+        self.track_duration = Some(duration);
+        self.seek_pending = false;
+        self.reopen_before_seek = true;
     }
 
     #[cfg(target_os = "linux")]
@@ -214,17 +619,23 @@ impl WasapiAudioEngine {
         Ok(builder)
     }
 
-    fn open_output_stream(output: Option<&str>) -> Result<(MixerDeviceSink, Player)> {
+    fn open_output_stream(
+        output: Option<&str>,
+        device_lost: &Arc<AtomicBool>,
+    ) -> Result<(MixerDeviceSink, Player)> {
         let mut stream = with_silenced_stderr(|| {
             let host = rodio::cpal::default_host();
             if let Some(requested) = output {
                 let device = host
                     .output_devices()
                     .context("failed to enumerate output devices")?
-                    .find(|candidate| audio_device_name(candidate).as_deref() == Some(requested))
+                    .find(|candidate| {
+                        audio_device_name(candidate)
+                            .is_some_and(|name| output_device_names_match(&name, requested))
+                    })
                     .with_context(|| format!("audio output device not found: {requested}"))?;
                 Self::output_stream_builder_for_device(device)?
-                    .with_error_callback(ignore_stream_error)
+                    .with_error_callback(flag_lost_device(device_lost))
                     .open_sink_or_fallback()
                     .context("failed to start selected output stream")
             } else {
@@ -233,7 +644,7 @@ impl WasapiAudioEngine {
                     .context("failed to open default system output stream")?;
                 match Self::output_stream_builder_for_device(default_device).and_then(|builder| {
                     builder
-                        .with_error_callback(ignore_stream_error)
+                        .with_error_callback(flag_lost_device(device_lost))
                         .open_sink_or_fallback()
                         .context("failed to start default output stream")
                 }) {
@@ -276,7 +687,7 @@ impl WasapiAudioEngine {
                             let opened = Self::output_stream_builder_for_device(device).and_then(
                                 |builder| {
                                     builder
-                                        .with_error_callback(ignore_stream_error)
+                                        .with_error_callback(flag_lost_device(device_lost))
                                         .open_sink_or_fallback()
                                         .context("failed to start fallback output stream")
                                 },
@@ -306,8 +717,10 @@ impl WasapiAudioEngine {
         let was_paused = self.sink.is_paused();
         let selected = self.selected_output.clone();
 
-        let (stream, sink) = Self::open_output_stream(selected.as_deref())?;
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let (stream, sink) = Self::open_output_stream(selected.as_deref(), &device_lost)?;
         self.stream = stream;
+        self.device_lost = device_lost;
         self.sink = sink;
         self.sink.set_volume(self.effective_volume());
         self.clear_next();
@@ -328,24 +741,25 @@ impl AudioEngine for WasapiAudioEngine {
         self.sink.stop();
         self.clear_next();
         self.sink = Player::connect_new(self.stream.mixer());
-        self.sink.set_volume(self.volume.clamp(0.0, MAX_VOLUME));
-
-        let file =
-            File::open(path).with_context(|| format!("failed to open track {}", path.display()))?;
-        let source = Decoder::try_from(file)
-            .with_context(|| format!("failed to decode {}", path.display()))?;
-        self.track_duration = if Self::streamed_wav_has_unknown_duration(path) {
+        self.sink
+            .set_volume(self.output_volume().clamp(0.0, MAX_VOLUME));
+        self.sink.set_speed(self.sink_speed());
+        self.ab_loop = None;
+
+        let source = open_track_source(path)?;
+        self.seek_pending = Self::streamed_wav_has_unknown_duration(path);
+        self.reopen_before_seek = false;
+        self.stream_polled_at = None;
+        self.track_duration = if self.seek_pending {
             None
         } else {
             source.total_duration()
         };
+        let (source, clock) = self.processed_source(source);
         self.sink.append(source);
+        self.track_clock = clock;
 
-        self.track_gain = if self.loudness_normalization {
-            Self::estimate_track_gain(path).unwrap_or(1.0)
-        } else {
-            1.0
-        };
+        self.levels.track_gain = self.levels.gain_for(path);
         self.sink.set_volume(self.effective_volume());
         self.current = Some(path.to_path_buf());
         Ok(())
@@ -363,46 +777,56 @@ impl AudioEngine for WasapiAudioEngine {
         self.clear_next();
         let next_sink = Player::connect_new(self.stream.mixer());
         next_sink.set_volume(0.0);
+        next_sink.set_speed(self.sink_speed());
 
-        let file =
-            File::open(path).with_context(|| format!("failed to open track {}", path.display()))?;
-        let source = Decoder::try_from(file)
-            .with_context(|| format!("failed to decode {}", path.display()))?;
+        let source = open_track_source(path)?;
         let next_duration = if Self::streamed_wav_has_unknown_duration(path) {
             None
         } else {
             source.total_duration()
         };
+        let (source, clock) = self.processed_source(source);
         next_sink.append(source);
+        self.next_track_clock = clock;
 
-        let next_gain = if self.loudness_normalization {
-            Self::estimate_track_gain(path).unwrap_or(1.0)
-        } else {
-            1.0
-        };
+        let next_gain = self.levels.gain_for(path);
 
-        if self.sink.is_paused() {
+        let lead_delay = crossfade_lead_delay(
+            self.remaining(),
+            self.crossfade_seconds,
+            self.crossfade_lead,
+        );
+        if self.sink.is_paused() || !lead_delay.is_zero() {
             next_sink.pause();
         }
 
         self.next_track = Some(path.to_path_buf());
         self.next_track_duration = next_duration;
-        self.next_track_gain = next_gain;
+        self.levels.next_track_gain = next_gain;
         self.next_sink = Some(next_sink);
-        self.crossfade_started_at = Some(Instant::now());
+        self.crossfade_started_at = Some(Instant::now() + lead_delay);
         Ok(())
     }
 
     fn tick(&mut self) {
+        self.poll_completed_stream();
+        self.promote_gapless_if_started();
+        if let Some((start, end)) = self.ab_loop
+            && self.position().is_some_and(|position| position >= end)
+        {
+            let _ = self.seek_to(start);
+        }
         let Some(next_sink) = self.next_sink.as_ref() else {
             return;
         };
+        if next_sink.is_paused() && !self.sink.is_paused() && self.crossfade_window_open() {
+            next_sink.play();
+        }
 
-        let progress = self.crossfade_progress();
+        let (outgoing, incoming) = crossfade_gains(self.crossfade_curve, self.crossfade_progress());
         self.sink
-            .set_volume((self.effective_volume() * (1.0 - progress)).clamp(0.0, MAX_VOLUME));
-        next_sink
-            .set_volume((self.volume * self.next_track_gain * progress).clamp(0.0, MAX_VOLUME));
+            .set_volume((self.effective_volume() * outgoing).clamp(0.0, MAX_VOLUME));
+        next_sink.set_volume(self.next_volume(incoming));
 
         if self.sink.empty() {
             self.promote_next_if_ready();
@@ -419,20 +843,29 @@ impl AudioEngine for WasapiAudioEngine {
 
     fn resume(&mut self) {
         self.sink.play();
-        if let Some(next) = &self.next_sink {
+        if let Some(next) = &self.next_sink
+            && self.crossfade_window_open()
+        {
             next.play();
         }
     }
 
+    /// Stops both the current track and any incoming crossfade track. Mid-fade
+    /// the incoming track is dropped too rather than committed, so stop always
+    /// means silence; both sinks get a short ramp down first to avoid a click.
     fn stop(&mut self) {
+        self.fade_out_for_stop();
         self.sink.stop();
         self.clear_next();
         self.current = None;
         self.next_track = None;
         self.track_duration = None;
         self.next_track_duration = None;
-        self.track_gain = 1.0;
-        self.next_track_gain = 1.0;
+        self.levels.track_gain = 1.0;
+        self.levels.next_track_gain = 1.0;
+        self.seek_pending = false;
+        self.reopen_before_seek = false;
+        self.ab_loop = None;
     }
 
     fn is_paused(&self) -> bool {
@@ -440,22 +873,48 @@ impl AudioEngine for WasapiAudioEngine {
     }
 
     fn current_track(&self) -> Option<&Path> {
+        if self.gapless_handed_over() {
+            return self.next_track.as_deref();
+        }
         self.current.as_deref()
     }
 
     fn position(&self) -> Option<Duration> {
         self.current.as_ref()?;
-        Some(self.sink.get_pos())
+        let clock = if self.gapless_handed_over() {
+            &self.next_track_clock
+        } else {
+            &self.track_clock
+        };
+        Some(Duration::from_micros(clock.load(Ordering::Relaxed)))
     }
 
     fn duration(&self) -> Option<Duration> {
+        if self.gapless_handed_over() {
+            return self.next_track_duration;
+        }
         self.track_duration
     }
 
     fn seek_to(&mut self, position: Duration) -> Result<()> {
+        self.promote_gapless_if_started();
         if self.current.is_none() {
             return Err(anyhow::anyhow!("no active track"));
         }
+        if self.seek_pending {
+            return Err(anyhow::anyhow!(
+                "stream still downloading; seeking unlocks once it finishes"
+            ));
+        }
+        if self.reopen_before_seek
+            && let Some(path) = self.current.clone()
+        {
+            let was_paused = self.sink.is_paused();
+            self.play(&path)?;
+            if was_paused {
+                self.pause();
+            }
+        }
 
         self.clear_next();
         self.sink
@@ -466,17 +925,39 @@ impl AudioEngine for WasapiAudioEngine {
     }
 
     fn volume(&self) -> f32 {
-        self.volume
+        self.levels.volume
     }
 
     fn set_volume(&mut self, volume: f32) {
-        self.volume = volume.clamp(0.0, MAX_VOLUME);
-        let progress = self.crossfade_progress();
-        self.sink
-            .set_volume((self.effective_volume() * (1.0 - progress)).clamp(0.0, MAX_VOLUME));
-        if let Some(next) = &self.next_sink {
-            next.set_volume((self.volume * self.next_track_gain * progress).clamp(0.0, MAX_VOLUME));
-        }
+        self.levels.volume = volume.clamp(0.0, MAX_VOLUME);
+        self.apply_volume();
+    }
+
+    fn fade_out_gain(&self) -> f32 {
+        self.levels.fade_out_gain
+    }
+
+    fn set_fade_out_gain(&mut self, gain: f32) {
+        self.levels.fade_out_gain = if gain.is_finite() {
+            gain.clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        self.apply_volume();
+    }
+
+    fn mute(&mut self) {
+        self.levels.muted = true;
+        self.apply_volume();
+    }
+
+    fn unmute(&mut self) {
+        self.levels.muted = false;
+        self.apply_volume();
+    }
+
+    fn is_muted(&self) -> bool {
+        self.levels.muted
     }
 
     fn output_name(&self) -> Option<String> {
@@ -522,23 +1003,16 @@ impl AudioEngine for WasapiAudioEngine {
     }
 
     fn loudness_normalization(&self) -> bool {
-        self.loudness_normalization
+        self.levels.loudness_normalization
     }
 
     fn set_loudness_normalization(&mut self, enabled: bool) {
-        self.loudness_normalization = enabled;
-        if !enabled || self.current.is_none() {
-            self.track_gain = 1.0;
-            self.next_track_gain = 1.0;
-            let progress = self.crossfade_progress();
-            self.sink
-                .set_volume((self.effective_volume() * (1.0 - progress)).clamp(0.0, MAX_VOLUME));
-            if let Some(next) = &self.next_sink {
-                next.set_volume(
-                    (self.volume * self.next_track_gain * progress).clamp(0.0, MAX_VOLUME),
-                );
-            }
-        }
+        self.levels.set_loudness_normalization(
+            enabled,
+            self.current.as_deref(),
+            self.next_track.as_deref(),
+        );
+        self.apply_volume();
     }
 
     fn crossfade_seconds(&self) -> u16 {
@@ -550,6 +1024,9 @@ impl AudioEngine for WasapiAudioEngine {
     }
 
     fn crossfade_queued_track(&self) -> Option<&Path> {
+        if self.gapless_handed_over() {
+            return None;
+        }
         self.next_track.as_deref()
     }
 
@@ -559,90 +1036,713 @@ impl AudioEngine for WasapiAudioEngine {
         }
         self.current.is_some() && !self.sink.is_paused() && self.sink.empty()
     }
-}
-
-fn ignore_stream_error(_: rodio::cpal::StreamError) {}
 
-fn audio_device_name(device: &Device) -> Option<String> {
-    device
-        .description()
-        .ok()
-        .map(|description| description.name().to_string())
-}
+    fn device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Acquire)
+    }
 
-#[cfg(unix)]
-fn with_silenced_stderr<T>(operation: impl FnOnce() -> T) -> T {
-    let saved = unsafe { libc::dup(libc::STDERR_FILENO) };
-    if saved < 0 {
-        return operation();
+    fn output_level(&self) -> Option<f32> {
+        let peak = f32::from_bits(self.level_peak.swap(0, Ordering::Relaxed));
+        Some((peak * self.effective_volume()).min(1.0))
     }
 
-    let devnull = CString::new("/dev/null")
-        .ok()
-        .map(|path| unsafe { libc::open(path.as_ptr(), libc::O_WRONLY) })
-        .unwrap_or(-1);
+    fn seek_pending(&self) -> bool {
+        self.seek_pending
+    }
 
-    if devnull >= 0 {
-        unsafe {
-            libc::dup2(devnull, libc::STDERR_FILENO);
-            libc::close(devnull);
+    fn announce_stream_duration(&mut self, duration: Duration) {
+        if self.seek_pending && self.track_duration.is_none() {
+            self.track_duration = Some(duration);
         }
     }
 
-    let result = operation();
+    fn crossfade_lead(&self) -> Duration {
+        self.crossfade_lead
+    }
 
-    unsafe {
-        libc::dup2(saved, libc::STDERR_FILENO);
-        libc::close(saved);
+    fn set_crossfade_lead(&mut self, lead: Duration) {
+        self.crossfade_lead = lead;
     }
 
-    result
-}
+    fn crossfade_curve(&self) -> CrossfadeCurve {
+        self.crossfade_curve
+    }
 
-#[cfg(not(unix))]
-fn with_silenced_stderr<T>(operation: impl FnOnce() -> T) -> T {
-    operation()
-}
+    fn set_crossfade_curve(&mut self, curve: CrossfadeCurve) {
+        self.crossfade_curve = curve;
+        self.apply_volume();
+    }
 
-pub struct NullAudioEngine {
-    paused: bool,
-    current: Option<PathBuf>,
-    volume: f32,
-    started_at: Option<Instant>,
-    position_offset: Duration,
-    track_duration: Option<Duration>,
-}
+    fn prevent_clipping(&self) -> bool {
+        self.levels.prevent_clipping
+    }
 
-impl NullAudioEngine {
-    pub fn new() -> Self {
-        Self {
-            paused: false,
-            current: None,
-            volume: 1.0,
-            started_at: None,
-            position_offset: Duration::ZERO,
-            track_duration: None,
+    fn set_prevent_clipping(&mut self, enabled: bool) {
+        if self.levels.prevent_clipping != enabled {
+            self.levels.prevent_clipping = enabled;
+            self.refresh_current_gain();
         }
     }
 
-    fn estimate_duration(path: &Path) -> Option<Duration> {
-        let file = File::open(path).ok()?;
-        let source = Decoder::try_from(file).ok()?;
-        source
-            .total_duration()
-            .filter(|duration| !duration.is_zero())
+    fn album_gain(&self) -> bool {
+        self.levels.album_gain
     }
 
-    fn current_position(&self) -> Duration {
-        let mut position = self.position_offset;
-        if !self.paused
-            && self.current.is_some()
-            && let Some(started_at) = self.started_at
-        {
-            position = position.saturating_add(started_at.elapsed());
-        }
-        if let Some(duration) = self.track_duration {
-            return position.min(duration);
+    fn set_album_gain(&mut self, enabled: bool) {
+        if self.levels.album_gain != enabled {
+            self.levels.album_gain = enabled;
+            self.refresh_current_gain();
+        }
+    }
+
+    fn set_equalizer(&mut self, bands: &[(f32, f32)]) {
+        self.equalizer.set_bands(bands);
+    }
+
+    fn equalizer(&self) -> Vec<(f32, f32)> {
+        self.equalizer.bands()
+    }
+
+    fn gapless(&self) -> bool {
+        self.gapless
+    }
+
+    fn set_gapless(&mut self, enabled: bool) {
+        self.gapless = enabled;
+    }
+
+    fn queue_gapless(&mut self, path: &Path) -> Result<()> {
+        if self.current.is_none() || self.sink.empty() {
+            return self.play(path);
+        }
+
+        self.clear_next();
+        let source = open_track_source(path)?;
+        let next_duration = if Self::streamed_wav_has_unknown_duration(path) {
+            None
+        } else {
+            source.total_duration()
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (source, clock) = self.processed_source(source);
+        self.sink
+            .append(Cancellable::new(source, Arc::clone(&cancel)));
+        self.next_track_clock = clock;
+
+        self.levels.next_track_gain = self.levels.gain_for(path);
+        self.next_track = Some(path.to_path_buf());
+        self.next_track_duration = next_duration;
+        self.gapless_cancel = Some(cancel);
+        Ok(())
+    }
+
+    fn playback_speed(&self) -> f32 {
+        self.playback_speed
+    }
+
+    fn set_playback_speed(&mut self, speed: f32) {
+        self.playback_speed = clamp_playback_speed(speed);
+        self.apply_speed();
+    }
+
+    fn preserve_pitch(&self) -> bool {
+        self.preserve_pitch
+    }
+
+    fn set_preserve_pitch(&mut self, enabled: bool) {
+        self.preserve_pitch = enabled;
+        self.apply_speed();
+    }
+
+    fn balance(&self) -> f32 {
+        self.channel_mix.balance()
+    }
+
+    fn set_balance(&mut self, balance: f32) {
+        self.channel_mix.set_balance(clamp_balance(balance));
+    }
+
+    fn mono(&self) -> bool {
+        self.channel_mix.mono()
+    }
+
+    fn set_mono(&mut self, enabled: bool) {
+        self.channel_mix.set_mono(enabled);
+    }
+
+    fn ab_loop(&self) -> Option<(Duration, Duration)> {
+        self.ab_loop
+    }
+
+    fn set_ab_loop(&mut self, bounds: Option<(Duration, Duration)>) {
+        self.ab_loop = bounds.filter(|(start, end)| start < end);
+    }
+}
+
+/// Headroom left below full scale when a boost is capped by the peak.
+const CLIP_PREVENTION_CEILING: f64 = 0.98;
+
+/// Gain that moves the scanned RMS toward the target level. With
+/// `prevent_clipping`, a boost stops where the scanned peak would reach the
+/// ceiling; cuts are left alone.
+fn normalization_gain(rms: f64, peak: f64, prevent_clipping: bool) -> f32 {
+    if !(rms.is_finite()) || rms <= 0.000_01 {
+        return 1.0;
+    }
+
+    let target_rms = 0.20_f64;
+    let mut gain = (target_rms / rms).clamp(0.5, 1.8);
+    if prevent_clipping && gain > 1.0 && peak.is_finite() && peak > 0.0 {
+        gain = gain.min((CLIP_PREVENTION_CEILING / peak).max(1.0));
+    }
+    gain as f32
+}
+
+/// Linear gain from loudness tags. Album mode uses the album gain when the file
+/// has one; either mode falls back to whichever gain is present. `None` when
+/// the file has no gain tags at all.
+fn replay_gain_multiplier(
+    tags: &crate::library::ReplayGainTags,
+    album_gain: bool,
+    prevent_clipping: bool,
+) -> Option<f32> {
+    let track = tags.track_gain_db.map(|gain| (gain, tags.track_peak));
+    let album = tags.album_gain_db.map(|gain| (gain, tags.album_peak));
+    let (gain_db, peak) = if album_gain {
+        album.or(track)?
+    } else {
+        track.or(album)?
+    };
+
+    let mut gain = 10_f64.powf(f64::from(gain_db) / 20.0);
+    if prevent_clipping
+        && gain > 1.0
+        && let Some(peak) = peak.filter(|peak| peak.is_finite() && *peak > 0.0)
+    {
+        gain = gain.min((CLIP_PREVENTION_CEILING / f64::from(peak)).max(1.0));
+    }
+    Some(gain as f32)
+}
+
+/// How long a freshly queued crossfade track waits before fading in: the time
+/// left until the crossfade window, never more than the look-ahead lead.
+fn crossfade_lead_delay(
+    remaining: Option<Duration>,
+    crossfade_seconds: u16,
+    lead: Duration,
+) -> Duration {
+    remaining
+        .map_or(Duration::ZERO, |remaining| {
+            remaining.saturating_sub(Duration::from_secs(u64::from(crossfade_seconds)))
+        })
+        .min(lead)
+}
+
+/// Decodes `path` for playback. A cue sheet track opens the sheet's audio file
+/// trimmed to that track's span, so it plays, seeks, and ends like a file of its own.
+fn open_track_source(path: &Path) -> Result<TrackSpan<TrackDecoder>> {
+    let segment = crate::library::cue_segment_for_path(path);
+    let file_path = segment
+        .as_ref()
+        .map_or(path, |segment| segment.file.as_path());
+    let file = File::open(file_path)
+        .with_context(|| format!("failed to open track {}", file_path.display()))?;
+    let source = match Decoder::try_from(file) {
+        Ok(decoder) => TrackDecoder::Default(decoder),
+        Err(err) => match OggOpusSource::open(file_path)
+            .with_context(|| format!("failed to decode {}", file_path.display()))?
+        {
+            Some(opus) => TrackDecoder::Opus(Box::new(opus)),
+            None => {
+                return Err(err)
+                    .with_context(|| format!("failed to decode {}", file_path.display()));
+            }
+        },
+    };
+    Ok(match segment {
+        Some(segment) => TrackSpan::new(source, segment.start, segment.end),
+        None => TrackSpan::whole(source),
+    })
+}
+
+/// Byte rate and data size from a WAV header in the stream cache, or `None`
+/// for any other file. The data size stays `u32::MAX` until the download ends.
+fn stream_cache_wav_header(path: &Path) -> Option<(u32, u32)> {
+    if !path
+        .to_string_lossy()
+        .to_ascii_lowercase()
+        .contains("tunetui_stream_cache")
+    {
+        return None;
+    }
+    if !path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+    {
+        return None;
+    }
+    let mut file = File::open(path).ok()?;
+    let mut header = [0_u8; 44];
+    file.read_exact(&mut header).ok()?;
+    let byte_rate = u32::from_le_bytes(header[28..32].try_into().ok()?);
+    let data_size = u32::from_le_bytes(header[40..44].try_into().ok()?);
+    Some((byte_rate, data_size))
+}
+
+/// Length of a stream cache WAV once its header has been finalized.
+fn completed_stream_wav_duration(path: &Path) -> Option<Duration> {
+    let (byte_rate, data_size) = stream_cache_wav_header(path)?;
+    if data_size == u32::MAX || byte_rate == 0 {
+        return None;
+    }
+    Some(Duration::from_secs_f64(
+        f64::from(data_size) / f64::from(byte_rate),
+    ))
+}
+
+/// Stream error callback. A vanished device is recorded for `device_lost`;
+/// other errors are transient glitches and are ignored.
+fn flag_lost_device(
+    device_lost: &Arc<AtomicBool>,
+) -> impl FnMut(rodio::cpal::StreamError) + Send + Clone + 'static {
+    let device_lost = Arc::clone(device_lost);
+    move |error| {
+        if matches!(error, rodio::cpal::StreamError::DeviceNotAvailable) {
+            device_lost.store(true, Ordering::Release);
+        }
+    }
+}
+
+/// Compares output device names trimmed and NFC-normalized, since driver
+/// updates can add trailing spaces or change how accented names are encoded.
+pub fn output_device_names_match(left: &str, right: &str) -> bool {
+    left.trim().nfc().eq(right.trim().nfc())
+}
+
+fn audio_device_name(device: &Device) -> Option<String> {
+    device
+        .description()
+        .ok()
+        .map(|description| description.name().to_string())
+}
+
+#[cfg(unix)]
+fn with_silenced_stderr<T>(operation: impl FnOnce() -> T) -> T {
+    let saved = unsafe { libc::dup(libc::STDERR_FILENO) };
+    if saved < 0 {
+        return operation();
+    }
+
+    let devnull = CString::new("/dev/null")
+        .ok()
+        .map(|path| unsafe { libc::open(path.as_ptr(), libc::O_WRONLY) })
+        .unwrap_or(-1);
+
+    if devnull >= 0 {
+        unsafe {
+            libc::dup2(devnull, libc::STDERR_FILENO);
+            libc::close(devnull);
+        }
+    }
+
+    let result = operation();
+
+    unsafe {
+        libc::dup2(saved, libc::STDERR_FILENO);
+        libc::close(saved);
+    }
+
+    result
+}
+
+/// Samples the level meter reads before publishing its running peak.
+const LEVEL_METER_BLOCK_SAMPLES: usize = 512;
+
+/// Passes samples through unchanged while recording the loudest one into a shared
+/// peak. The peak is kept locally and published once per block, so the audio
+/// thread does one atomic per block instead of one per sample. Samples are
+/// non-negative after `abs`, so their bit patterns order the same way as the
+/// values and `fetch_max` works on the raw bits.
+struct LevelMeter<S> {
+    inner: S,
+    peak: Arc<AtomicU32>,
+    block_peak: f32,
+    block_len: usize,
+}
+
+impl<S> LevelMeter<S> {
+    fn new(inner: S, peak: Arc<AtomicU32>) -> Self {
+        Self {
+            inner,
+            peak,
+            block_peak: 0.0,
+            block_len: 0,
+        }
+    }
+
+    fn publish_block(&mut self) {
+        if self.block_peak > 0.0 {
+            self.peak
+                .fetch_max(self.block_peak.to_bits(), Ordering::Relaxed);
+        }
+        self.block_peak = 0.0;
+        self.block_len = 0;
+    }
+}
+
+impl<S: Source> Iterator for LevelMeter<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Some(sample) = self.inner.next() else {
+            self.publish_block();
+            return None;
+        };
+        let level = sample.abs();
+        if level.is_finite() && level > self.block_peak {
+            self.block_peak = level;
+        }
+        self.block_len += 1;
+        if self.block_len >= LEVEL_METER_BLOCK_SAMPLES {
+            self.publish_block();
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for LevelMeter<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.current_span_len()
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+        self.inner.try_seek(pos)
+    }
+}
+
+/// Ends early once `cancelled` is set. A source appended to a sink cannot be
+/// taken back out, so a dropped gapless track is cancelled instead.
+struct Cancellable<S> {
+    inner: S,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<S> Cancellable<S> {
+    fn new(inner: S, cancelled: Arc<AtomicBool>) -> Self {
+        Self { inner, cancelled }
+    }
+}
+
+impl<S: Source> Iterator for Cancellable<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        self.inner.next()
+    }
+}
+
+impl<S: Source> Source for Cancellable<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.current_span_len()
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+        self.inner.try_seek(pos)
+    }
+}
+
+/// rodio's decoder, or libopus for Ogg Opus, which Symphonia can demux but not
+/// decode.
+enum TrackDecoder {
+    Default(Decoder<BufReader<File>>),
+    Opus(Box<OggOpusSource>),
+}
+
+impl Iterator for TrackDecoder {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Default(decoder) => decoder.next(),
+            Self::Opus(opus) => opus.next(),
+        }
+    }
+}
+
+impl Source for TrackDecoder {
+    fn current_span_len(&self) -> Option<usize> {
+        match self {
+            Self::Default(decoder) => decoder.current_span_len(),
+            Self::Opus(opus) => opus.current_span_len(),
+        }
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        match self {
+            Self::Default(decoder) => decoder.channels(),
+            Self::Opus(opus) => opus.channels(),
+        }
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        match self {
+            Self::Default(decoder) => decoder.sample_rate(),
+            Self::Opus(opus) => opus.sample_rate(),
+        }
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        match self {
+            Self::Default(decoder) => decoder.total_duration(),
+            Self::Opus(opus) => opus.total_duration(),
+        }
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+        match self {
+            Self::Default(decoder) => decoder.try_seek(pos),
+            Self::Opus(opus) => opus.try_seek(pos),
+        }
+    }
+}
+
+/// Plays `start..end` of a decoded file as one track. Positions, seeks, and the
+/// reported length are relative to `start`, and the source ends at `end` so the
+/// sink empties there and the queue advances as if the file had ended.
+struct TrackSpan<S> {
+    inner: S,
+    start: Duration,
+    length: Option<Duration>,
+    remaining_samples: Option<u64>,
+}
+
+impl<S: Source> TrackSpan<S> {
+    fn whole(inner: S) -> Self {
+        Self {
+            inner,
+            start: Duration::ZERO,
+            length: None,
+            remaining_samples: None,
+        }
+    }
+
+    fn new(mut inner: S, start: Duration, end: Option<Duration>) -> Self {
+        if !start.is_zero() && inner.try_seek(start).is_err() {
+            for _ in 0..span_samples(&inner, start) {
+                if inner.next().is_none() {
+                    break;
+                }
+            }
+        }
+        let length = end.map(|end| end.saturating_sub(start)).or_else(|| {
+            inner
+                .total_duration()
+                .map(|total| total.saturating_sub(start))
+        });
+        let remaining_samples = end.map(|end| span_samples(&inner, end.saturating_sub(start)));
+        Self {
+            inner,
+            start,
+            length,
+            remaining_samples,
+        }
+    }
+}
+
+fn span_samples<S: Source>(source: &S, span: Duration) -> u64 {
+    let frames = (span.as_secs_f64() * f64::from(source.sample_rate().get())).round() as u64;
+    frames.saturating_mul(u64::from(source.channels().get()))
+}
+
+impl<S: Source> Iterator for TrackSpan<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(remaining) = self.remaining_samples.as_mut() {
+            if *remaining == 0 {
+                return None;
+            }
+            *remaining -= 1;
+        }
+        self.inner.next()
+    }
+}
+
+impl<S: Source> Source for TrackSpan<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        let span = self.inner.current_span_len();
+        match self.remaining_samples {
+            Some(remaining) => {
+                let remaining = usize::try_from(remaining).unwrap_or(usize::MAX);
+                Some(span.map_or(remaining, |span| span.min(remaining)))
+            }
+            None => span,
+        }
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.length.or_else(|| self.inner.total_duration())
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+        self.inner.try_seek(self.start.saturating_add(pos))?;
+        if self.remaining_samples.is_some()
+            && let Some(length) = self.length
+        {
+            self.remaining_samples = Some(span_samples(&self.inner, length.saturating_sub(pos)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+fn with_silenced_stderr<T>(operation: impl FnOnce() -> T) -> T {
+    operation()
+}
+
+/// The one output `NullAudioEngine` lists, so the output picker shows that
+/// playback is silent instead of coming up empty.
+pub const NULL_OUTPUT_NAME: &str = "Null audio (no sound)";
+
+pub struct NullAudioEngine {
+    paused: bool,
+    current: Option<PathBuf>,
+    volume: f32,
+    muted: bool,
+    started_at: Option<Instant>,
+    position_offset: Duration,
+    track_duration: Option<Duration>,
+    crossfade_seconds: u16,
+    crossfade_lead: Duration,
+    crossfade_curve: CrossfadeCurve,
+    next: Option<NullQueuedTrack>,
+    equalizer: Vec<(f32, f32)>,
+    gapless: bool,
+    playback_speed: f32,
+    preserve_pitch: bool,
+    ab_loop: Option<(Duration, Duration)>,
+    balance: f32,
+    mono: bool,
+    /// Output the user chose, kept so a session without a sound card does not
+    /// forget their saved device.
+    selected_output: Option<String>,
+}
+
+/// Incoming crossfade track, clocked the same way as the current one so it can be
+/// promoted with its elapsed overlap as the starting position.
+struct NullQueuedTrack {
+    path: PathBuf,
+    duration: Option<Duration>,
+    started_at: Option<Instant>,
+    position_offset: Duration,
+}
+
+impl NullQueuedTrack {
+    fn position(&self) -> Duration {
+        self.started_at.map_or(self.position_offset, |started_at| {
+            self.position_offset
+                .saturating_add(Instant::now().saturating_duration_since(started_at))
+        })
+    }
+}
+
+impl NullAudioEngine {
+    pub fn new() -> Self {
+        Self {
+            paused: false,
+            current: None,
+            volume: 1.0,
+            muted: false,
+            started_at: None,
+            position_offset: Duration::ZERO,
+            track_duration: None,
+            crossfade_seconds: 0,
+            crossfade_lead: Duration::ZERO,
+            crossfade_curve: CrossfadeCurve::default(),
+            next: None,
+            equalizer: crate::model::default_equalizer_bands(),
+            gapless: false,
+            playback_speed: 1.0,
+            preserve_pitch: true,
+            ab_loop: None,
+            balance: 0.0,
+            mono: false,
+            selected_output: None,
+        }
+    }
+
+    fn promote_next_if_ready(&mut self) {
+        let Some(next) = self.next.as_ref() else {
+            return;
+        };
+        let current_done = match self.track_duration {
+            Some(duration) => self.current_position() >= duration,
+            None => next.position() >= Duration::from_secs(u64::from(self.crossfade_seconds)),
+        };
+        if !current_done {
+            return;
+        }
+        let next = self.next.take().expect("queued track checked above");
+        self.current = Some(next.path);
+        self.track_duration = next.duration;
+        self.started_at = next.started_at;
+        self.position_offset = next.position_offset;
+        self.ab_loop = None;
+    }
+
+    fn estimate_duration(path: &Path) -> Option<Duration> {
+        open_track_source(path)
+            .ok()?
+            .total_duration()
+            .filter(|duration| !duration.is_zero())
+    }
+
+    fn current_position(&self) -> Duration {
+        let mut position = self.position_offset;
+        if !self.paused
+            && self.current.is_some()
+            && let Some(started_at) = self.started_at
+        {
+            position = position.saturating_add(started_at.elapsed().mul_f32(self.playback_speed));
+        }
+        if let Some(duration) = self.track_duration {
+            return position.min(duration);
         }
         position
     }
@@ -656,39 +1756,72 @@ impl Default for NullAudioEngine {
 
 impl AudioEngine for NullAudioEngine {
     fn play(&mut self, path: &Path) -> Result<()> {
+        self.next = None;
         self.paused = false;
         self.current = Some(path.to_path_buf());
         self.started_at = Some(Instant::now());
         self.position_offset = Duration::ZERO;
         self.track_duration = Self::estimate_duration(path);
+        self.ab_loop = None;
         Ok(())
     }
 
     fn queue_crossfade(&mut self, path: &Path) -> Result<()> {
-        self.play(path)
+        if self.crossfade_seconds == 0 || self.current.is_none() || self.paused {
+            return self.play(path);
+        }
+        let remaining = self
+            .track_duration
+            .map(|duration| duration.saturating_sub(self.current_position()));
+        let lead_delay =
+            crossfade_lead_delay(remaining, self.crossfade_seconds, self.crossfade_lead);
+        self.next = Some(NullQueuedTrack {
+            path: path.to_path_buf(),
+            duration: Self::estimate_duration(path),
+            started_at: Some(Instant::now() + lead_delay),
+            position_offset: Duration::ZERO,
+        });
+        Ok(())
     }
 
-    fn tick(&mut self) {}
+    fn tick(&mut self) {
+        self.promote_next_if_ready();
+        if let Some((start, end)) = self.ab_loop
+            && self.current.is_some()
+            && self.current_position() >= end
+        {
+            let _ = self.seek_to(start);
+        }
+    }
 
     fn pause(&mut self) {
         self.position_offset = self.current_position();
         self.started_at = None;
         self.paused = true;
+        if let Some(next) = self.next.as_mut() {
+            next.position_offset = next.position();
+            next.started_at = None;
+        }
     }
 
     fn resume(&mut self) {
         if self.current.is_some() {
             self.started_at = Some(Instant::now());
         }
+        if let Some(next) = self.next.as_mut() {
+            next.started_at = Some(Instant::now());
+        }
         self.paused = false;
     }
 
     fn stop(&mut self) {
+        self.next = None;
         self.current = None;
         self.paused = false;
         self.started_at = None;
         self.position_offset = Duration::ZERO;
         self.track_duration = None;
+        self.ab_loop = None;
     }
 
     fn is_paused(&self) -> bool {
@@ -713,6 +1846,7 @@ impl AudioEngine for NullAudioEngine {
             return Err(anyhow::anyhow!("no active track"));
         }
 
+        self.next = None;
         self.position_offset = self
             .track_duration
             .map_or(position, |duration| position.min(duration));
@@ -732,8 +1866,20 @@ impl AudioEngine for NullAudioEngine {
         self.volume = volume.clamp(0.0, MAX_VOLUME);
     }
 
+    fn mute(&mut self) {
+        self.muted = true;
+    }
+
+    fn unmute(&mut self) {
+        self.muted = false;
+    }
+
+    fn is_muted(&self) -> bool {
+        self.muted
+    }
+
     fn output_name(&self) -> Option<String> {
-        Some("Null audio engine".to_string())
+        Some(NULL_OUTPUT_NAME.to_string())
     }
 
     fn reload_driver(&mut self) -> Result<()> {
@@ -741,17 +1887,24 @@ impl AudioEngine for NullAudioEngine {
     }
 
     fn available_outputs(&self) -> Vec<String> {
-        Vec::new()
+        vec![NULL_OUTPUT_NAME.to_string()]
     }
 
     fn selected_output_device(&self) -> Option<String> {
-        None
+        self.selected_output.clone()
     }
 
-    fn set_output_device(&mut self, _output: Option<&str>) -> Result<()> {
+    fn set_output_device(&mut self, output: Option<&str>) -> Result<()> {
+        self.selected_output = output
+            .filter(|name| !output_device_names_match(name, NULL_OUTPUT_NAME))
+            .map(ToOwned::to_owned);
         Ok(())
     }
 
+    fn is_silent(&self) -> bool {
+        true
+    }
+
     fn loudness_normalization(&self) -> bool {
         false
     }
@@ -759,16 +1912,117 @@ impl AudioEngine for NullAudioEngine {
     fn set_loudness_normalization(&mut self, _enabled: bool) {}
 
     fn crossfade_seconds(&self) -> u16 {
-        0
+        self.crossfade_seconds
     }
 
-    fn set_crossfade_seconds(&mut self, _seconds: u16) {}
+    fn set_crossfade_seconds(&mut self, seconds: u16) {
+        self.crossfade_seconds = seconds.min(10);
+    }
 
     fn crossfade_queued_track(&self) -> Option<&Path> {
-        None
+        self.next.as_ref().map(|next| next.path.as_path())
+    }
+
+    fn crossfade_lead(&self) -> Duration {
+        self.crossfade_lead
+    }
+
+    fn set_crossfade_lead(&mut self, lead: Duration) {
+        self.crossfade_lead = lead;
+    }
+
+    fn crossfade_curve(&self) -> CrossfadeCurve {
+        self.crossfade_curve
+    }
+
+    fn set_crossfade_curve(&mut self, curve: CrossfadeCurve) {
+        self.crossfade_curve = curve;
+    }
+
+    fn set_equalizer(&mut self, bands: &[(f32, f32)]) {
+        self.equalizer = bands.to_vec();
+    }
+
+    fn equalizer(&self) -> Vec<(f32, f32)> {
+        self.equalizer.clone()
+    }
+
+    fn gapless(&self) -> bool {
+        self.gapless
+    }
+
+    fn set_gapless(&mut self, enabled: bool) {
+        self.gapless = enabled;
+    }
+
+    fn queue_gapless(&mut self, path: &Path) -> Result<()> {
+        let Some(remaining) = self
+            .track_duration
+            .map(|duration| duration.saturating_sub(self.current_position()))
+        else {
+            return self.play(path);
+        };
+        if self.current.is_none() || self.paused {
+            return self.play(path);
+        }
+        self.next = Some(NullQueuedTrack {
+            path: path.to_path_buf(),
+            duration: Self::estimate_duration(path),
+            started_at: Some(Instant::now() + remaining.div_f32(self.playback_speed)),
+            position_offset: Duration::ZERO,
+        });
+        Ok(())
+    }
+
+    fn playback_speed(&self) -> f32 {
+        self.playback_speed
+    }
+
+    /// Rebases the clock so time already played keeps the old rate.
+    fn set_playback_speed(&mut self, speed: f32) {
+        self.position_offset = self.current_position();
+        if self.started_at.is_some() {
+            self.started_at = Some(Instant::now());
+        }
+        self.playback_speed = clamp_playback_speed(speed);
+    }
+
+    fn preserve_pitch(&self) -> bool {
+        self.preserve_pitch
+    }
+
+    fn set_preserve_pitch(&mut self, enabled: bool) {
+        self.preserve_pitch = enabled;
+    }
+
+    fn balance(&self) -> f32 {
+        self.balance
+    }
+
+    fn set_balance(&mut self, balance: f32) {
+        self.balance = clamp_balance(balance);
+    }
+
+    fn mono(&self) -> bool {
+        self.mono
+    }
+
+    fn set_mono(&mut self, enabled: bool) {
+        self.mono = enabled;
+    }
+
+    fn ab_loop(&self) -> Option<(Duration, Duration)> {
+        self.ab_loop
+    }
+
+    fn set_ab_loop(&mut self, bounds: Option<(Duration, Duration)>) {
+        self.ab_loop = bounds.filter(|(start, end)| start < end);
     }
 
     fn is_finished(&self) -> bool {
+        if self.next.is_some() {
+            return false;
+        }
         let Some(duration) = self.track_duration else {
             return false;
         };
@@ -778,17 +2032,255 @@ impl AudioEngine for NullAudioEngine {
 
 #[cfg(test)]
 mod tests {
-    #[cfg(target_os = "linux")]
     use super::WasapiAudioEngine;
-    use super::{AudioEngine, NullAudioEngine};
+    use super::{
+        AudioEngine, LEVEL_METER_BLOCK_SAMPLES, LevelMeter, MAX_PLAYBACK_SPEED, NULL_OUTPUT_NAME,
+        NullAudioEngine, OutputLevels, TrackSpan, clamp_playback_speed,
+        completed_stream_wav_duration, crossfade_gains, crossfade_lead_delay, normalization_gain,
+        output_device_names_match, replay_gain_multiplier,
+    };
+    use crate::model::CrossfadeCurve;
+    use rodio::Source;
     #[cfg(target_os = "linux")]
     use rodio::cpal::{BufferSize, SupportedBufferSize};
     use std::env;
     use std::fs;
     use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
     use std::thread;
     use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+    struct TestSamples(std::vec::IntoIter<f32>);
+
+    impl Iterator for TestSamples {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            self.0.next()
+        }
+    }
+
+    impl Source for TestSamples {
+        fn current_span_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> rodio::ChannelCount {
+            rodio::ChannelCount::new(1).expect("non-zero")
+        }
+
+        fn sample_rate(&self) -> rodio::SampleRate {
+            rodio::SampleRate::new(44_100).expect("non-zero")
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn output_device_names_match_ignores_trailing_whitespace_and_unicode_form() {
+        assert!(output_device_names_match(
+            "Speakers (Realtek Audio) ",
+            "Speakers (Realtek Audio)"
+        ));
+        assert!(output_device_names_match(
+            "Haut-parleurs (Int\u{e9}grés)",
+            "Haut-parleurs (Inte\u{301}grés)"
+        ));
+        assert!(!output_device_names_match("Speakers", "Headphones"));
+    }
+
+    /// Mono, 10 samples per second, each sample equal to its index.
+    struct Counter {
+        next: u32,
+        len: u32,
+    }
+
+    impl Iterator for Counter {
+        type Item = rodio::Sample;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            (self.next < self.len).then(|| {
+                self.next += 1;
+                (self.next - 1) as f32
+            })
+        }
+    }
+
+    impl Source for Counter {
+        fn current_span_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> rodio::ChannelCount {
+            rodio::ChannelCount::new(1).expect("nonzero")
+        }
+
+        fn sample_rate(&self) -> rodio::SampleRate {
+            rodio::SampleRate::new(10).expect("nonzero")
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            Some(Duration::from_millis(u64::from(self.len) * 100))
+        }
+
+        fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+            self.next = (pos.as_millis() / 100) as u32;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn replay_gain_multiplier_picks_album_or_track_gain_and_caps_by_peak() {
+        let tags = crate::library::ReplayGainTags {
+            track_gain_db: Some(-6.0),
+            track_peak: Some(1.0),
+            album_gain_db: Some(6.0),
+            album_peak: Some(0.7),
+        };
+
+        let track = replay_gain_multiplier(&tags, false, true).expect("track gain");
+        assert!((track - 0.501).abs() < 0.001);
+        // +6 dB would be ~2x, the 0.7 album peak allows only 1.4x.
+        let album = replay_gain_multiplier(&tags, true, true).expect("album gain");
+        assert!((album - 1.4).abs() < 0.001);
+        let unclamped = replay_gain_multiplier(&tags, true, false).expect("album gain");
+        assert!((unclamped - 1.995).abs() < 0.001);
+
+        let album_only = crate::library::ReplayGainTags {
+            album_gain_db: Some(-3.0),
+            ..Default::default()
+        };
+        assert!(replay_gain_multiplier(&album_only, false, true).is_some());
+        assert_eq!(
+            replay_gain_multiplier(&crate::library::ReplayGainTags::default(), true, true),
+            None
+        );
+    }
+
+    #[test]
+    fn normalization_boost_is_capped_by_peak_when_preventing_clipping() {
+        // Quiet but spiky: RMS asks for 1.8x, the 0.8 peak allows ~1.22x.
+        let capped = normalization_gain(0.1, 0.8, true);
+        assert!((capped - 0.98 / 0.8).abs() < 0.001);
+        assert!((normalization_gain(0.1, 0.8, false) - 1.8).abs() < 0.001);
+        // Cuts and sources already at full scale are left as they were.
+        assert!((normalization_gain(0.4, 1.0, true) - 0.5).abs() < 0.001);
+        assert!((normalization_gain(0.1, 1.2, true) - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn crossfade_curves_share_endpoints_and_equal_power_keeps_the_middle_loud() {
+        for curve in [
+            CrossfadeCurve::Linear,
+            CrossfadeCurve::EqualPower,
+            CrossfadeCurve::Logarithmic,
+        ] {
+            assert_eq!(crossfade_gains(curve, 0.0), (1.0, 0.0), "{curve:?}");
+            let (outgoing, incoming) = crossfade_gains(curve, 1.0);
+            assert!(outgoing.abs() < 1e-6 && (incoming - 1.0).abs() < 1e-6);
+        }
+
+        let (outgoing, incoming) = crossfade_gains(CrossfadeCurve::Linear, 0.5);
+        assert!((outgoing.powi(2) + incoming.powi(2) - 0.5).abs() < 1e-6);
+        let (outgoing, incoming) = crossfade_gains(CrossfadeCurve::EqualPower, 0.5);
+        assert!((outgoing.powi(2) + incoming.powi(2) - 1.0).abs() < 1e-6);
+        let (_, incoming) = crossfade_gains(CrossfadeCurve::Logarithmic, 0.25);
+        assert!(incoming > 0.5);
+    }
+
+    #[test]
+    fn crossfade_lead_holds_the_next_track_until_the_fade_window() {
+        let lead = Duration::from_millis(500);
+        assert_eq!(
+            crossfade_lead_delay(Some(Duration::from_millis(4_300)), 4, lead),
+            Duration::from_millis(300)
+        );
+        assert_eq!(
+            crossfade_lead_delay(Some(Duration::from_secs(10)), 4, lead),
+            lead
+        );
+        assert_eq!(
+            crossfade_lead_delay(Some(Duration::from_secs(3)), 4, lead),
+            Duration::ZERO
+        );
+        assert_eq!(crossfade_lead_delay(None, 4, lead), Duration::ZERO);
+    }
+
+    #[test]
+    fn track_span_plays_only_its_part_of_the_file() {
+        let mut span = TrackSpan::new(
+            Counter { next: 0, len: 100 },
+            Duration::from_secs(2),
+            Some(Duration::from_secs(5)),
+        );
+        assert_eq!(span.total_duration(), Some(Duration::from_secs(3)));
+        assert_eq!(span.next(), Some(20.0));
+
+        span.try_seek(Duration::from_secs(1)).expect("seek");
+        let rest: Vec<f32> = span.collect();
+        assert_eq!(rest.first(), Some(&30.0));
+        assert_eq!(rest.last(), Some(&49.0));
+
+        let last = TrackSpan::new(Counter { next: 0, len: 100 }, Duration::from_secs(8), None);
+        assert_eq!(last.total_duration(), Some(Duration::from_secs(2)));
+        assert_eq!(last.count(), 20);
+    }
+
+    #[test]
+    fn stream_cache_wav_duration_is_known_only_after_header_finalized() {
+        let dir = unique_test_dir("stream-seek").join("tunetui_stream_cache");
+        fs::create_dir_all(&dir).expect("cache dir");
+        let path = dir.join("track.wav");
+        let mut header = Vec::new();
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+        header.extend_from_slice(b"WAVEfmt ");
+        header.extend_from_slice(&16_u32.to_le_bytes());
+        header.extend_from_slice(&1_u16.to_le_bytes());
+        header.extend_from_slice(&2_u16.to_le_bytes());
+        header.extend_from_slice(&48_000_u32.to_le_bytes());
+        header.extend_from_slice(&192_000_u32.to_le_bytes());
+        header.extend_from_slice(&4_u16.to_le_bytes());
+        header.extend_from_slice(&16_u16.to_le_bytes());
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, &header).expect("write placeholder");
+        assert_eq!(completed_stream_wav_duration(&path), None);
+
+        header[40..44].copy_from_slice(&960_000_u32.to_le_bytes());
+        fs::write(&path, &header).expect("write finalized");
+        assert_eq!(
+            completed_stream_wav_duration(&path),
+            Some(Duration::from_secs(5))
+        );
+
+        let plain = dir.parent().expect("parent").join("track.wav");
+        fs::write(&plain, &header).expect("write plain");
+        assert_eq!(completed_stream_wav_duration(&plain), None);
+    }
+
+    #[test]
+    fn level_meter_passes_samples_through_and_records_peak() {
+        let peak = Arc::new(AtomicU32::new(0));
+        let source = TestSamples(vec![0.1, -0.6, 0.25].into_iter());
+        let mut meter = LevelMeter::new(source, Arc::clone(&peak));
+        let mut samples: Vec<f32> = meter.by_ref().take(2).collect();
+        assert_eq!(peak.load(Ordering::Relaxed), 0);
+        samples.extend(meter);
+
+        assert_eq!(samples, vec![0.1, -0.6, 0.25]);
+        assert_eq!(f32::from_bits(peak.load(Ordering::Relaxed)), 0.6);
+
+        peak.store(0, Ordering::Relaxed);
+        let loud = vec![0.5; LEVEL_METER_BLOCK_SAMPLES + 1];
+        let mut meter = LevelMeter::new(TestSamples(loud.into_iter()), Arc::clone(&peak));
+        assert_eq!(meter.by_ref().take(LEVEL_METER_BLOCK_SAMPLES).count(), 512);
+        assert_eq!(f32::from_bits(peak.load(Ordering::Relaxed)), 0.5);
+    }
+
     fn unique_test_dir(name: &str) -> PathBuf {
         let stamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -800,6 +2292,10 @@ mod tests {
     }
 
     fn write_test_wav(path: &Path, duration_ms: u32) {
+        write_test_wav_with_amplitude(path, duration_ms, 0);
+    }
+
+    fn write_test_wav_with_amplitude(path: &Path, duration_ms: u32, amplitude: i16) {
         let sample_rate: u32 = 44_100;
         let channels: u16 = 1;
         let bits_per_sample: u16 = 16;
@@ -824,7 +2320,9 @@ mod tests {
         bytes.extend_from_slice(&bits_per_sample.to_le_bytes());
         bytes.extend_from_slice(b"data");
         bytes.extend_from_slice(&data_size.to_le_bytes());
-        bytes.resize((44_u32 + data_size) as usize, 0_u8);
+        for _ in 0..total_samples {
+            bytes.extend_from_slice(&amplitude.to_le_bytes());
+        }
 
         fs::write(path, bytes).expect("wav fixture should be written");
     }
@@ -896,6 +2394,62 @@ mod tests {
         let _ = fs::remove_dir_all(dir);
     }
 
+    #[test]
+    fn null_engine_simulates_crossfade_queue_and_promotion() {
+        let dir = unique_test_dir("null-crossfade");
+        let first = dir.join("first.wav");
+        let second = dir.join("second.wav");
+        write_test_wav(&first, 150);
+        write_test_wav(&second, 2_000);
+
+        let mut engine = NullAudioEngine::new();
+        engine.set_crossfade_seconds(2);
+        assert_eq!(engine.crossfade_seconds(), 2);
+        engine.play(&first).expect("play first");
+        engine.queue_crossfade(&second).expect("queue second");
+        assert_eq!(engine.crossfade_queued_track(), Some(second.as_path()));
+        assert_eq!(engine.current_track(), Some(first.as_path()));
+        assert!(!engine.is_finished());
+
+        thread::sleep(Duration::from_millis(200));
+        engine.tick();
+        assert_eq!(engine.current_track(), Some(second.as_path()));
+        assert_eq!(engine.crossfade_queued_track(), None);
+        assert!(engine.position().expect("position") >= Duration::from_millis(150));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn null_engine_stop_mid_crossfade_stops_both_tracks() {
+        let mut engine = NullAudioEngine::new();
+        engine.set_crossfade_seconds(4);
+        engine.play(Path::new("first.mp3")).expect("play first");
+        engine
+            .queue_crossfade(Path::new("second.mp3"))
+            .expect("queue second");
+        assert_eq!(
+            engine.crossfade_queued_track(),
+            Some(Path::new("second.mp3"))
+        );
+
+        engine.stop();
+        engine.tick();
+
+        assert_eq!(engine.current_track(), None);
+        assert_eq!(engine.crossfade_queued_track(), None);
+    }
+
+    #[test]
+    fn null_engine_crossfade_off_plays_immediately() {
+        let mut engine = NullAudioEngine::new();
+        engine.play(Path::new("first.mp3")).expect("play first");
+        engine
+            .queue_crossfade(Path::new("second.mp3"))
+            .expect("queue second");
+        assert_eq!(engine.current_track(), Some(Path::new("second.mp3")));
+        assert_eq!(engine.crossfade_queued_track(), None);
+    }
+
     #[test]
     fn null_engine_unknown_duration_does_not_auto_finish() {
         let mut engine = NullAudioEngine::new();
@@ -936,6 +2490,92 @@ mod tests {
         let _ = fs::remove_dir_all(dir);
     }
 
+    #[test]
+    fn null_engine_ab_loop_jumps_back_and_clears_on_the_next_track() {
+        let mut engine = NullAudioEngine::new();
+        engine
+            .play(Path::new("nonexistent-track.flac"))
+            .expect("play should still work in null mode");
+        engine.pause();
+        engine.set_ab_loop(Some((Duration::from_secs(10), Duration::from_secs(5))));
+        assert_eq!(engine.ab_loop(), None, "B must come after A");
+
+        let bounds = (Duration::from_secs(10), Duration::from_secs(20));
+        engine.set_ab_loop(Some(bounds));
+        engine.seek_to(Duration::from_secs(21)).expect("seek");
+        engine.tick();
+        assert_eq!(engine.position(), Some(Duration::from_secs(10)));
+        assert_eq!(engine.ab_loop(), Some(bounds));
+
+        engine
+            .play(Path::new("other-track.flac"))
+            .expect("play should still work in null mode");
+        assert_eq!(engine.ab_loop(), None);
+    }
+
+    #[test]
+    fn null_engine_lists_one_silent_output_and_remembers_the_chosen_one() {
+        let mut engine = NullAudioEngine::new();
+        assert!(engine.is_silent());
+        assert_eq!(
+            engine.available_outputs(),
+            vec![NULL_OUTPUT_NAME.to_string()]
+        );
+        assert_eq!(engine.output_name().as_deref(), Some(NULL_OUTPUT_NAME));
+
+        engine
+            .set_output_device(Some("USB DAC"))
+            .expect("set output");
+        assert_eq!(engine.selected_output_device().as_deref(), Some("USB DAC"));
+        engine
+            .set_output_device(Some(NULL_OUTPUT_NAME))
+            .expect("set output");
+        assert_eq!(engine.selected_output_device(), None);
+    }
+
+    #[test]
+    fn null_engine_position_follows_playback_speed_in_track_time() {
+        let mut engine = NullAudioEngine::new();
+        engine
+            .play(Path::new("nonexistent-track.flac"))
+            .expect("play should still work in null mode");
+        engine.pause();
+        engine.set_playback_speed(8.0);
+        assert_eq!(engine.playback_speed(), MAX_PLAYBACK_SPEED);
+        engine.set_playback_speed(2.0);
+        let before = engine.position().expect("position");
+        engine.resume();
+
+        thread::sleep(Duration::from_millis(50));
+        let advanced = engine.position().expect("position") - before;
+        assert!(
+            advanced >= Duration::from_millis(100),
+            "two times speed should cover twice the wall-clock time, got {advanced:?}"
+        );
+        assert_eq!(clamp_playback_speed(f32::NAN), 1.0);
+    }
+
+    #[test]
+    fn toggling_loudness_mid_track_changes_the_effective_volume() {
+        let dir = unique_test_dir("loudness-refresh");
+        let path = dir.join("quiet.wav");
+        write_test_wav_with_amplitude(&path, 500, 1_000);
+        let mut levels = OutputLevels {
+            volume: 0.5,
+            prevent_clipping: false,
+            ..OutputLevels::default()
+        };
+        assert_eq!(levels.effective_volume(), 0.5);
+
+        levels.set_loudness_normalization(true, Some(&path), None);
+        assert!(levels.effective_volume() > 0.5);
+        assert_eq!(levels.next_track_gain, 1.0);
+
+        levels.set_loudness_normalization(false, Some(&path), None);
+        assert_eq!(levels.effective_volume(), 0.5);
+        let _ = fs::remove_dir_all(dir);
+    }
+
     #[cfg(target_os = "linux")]
     #[test]
     fn linux_buffer_size_prefers_stability_target_when_supported() {
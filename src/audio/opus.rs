@@ -0,0 +1,246 @@
+use anyhow::{Context, Result};
+use rodio::Source;
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+use symphonia::core::codecs::CODEC_TYPE_OPUS;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::{MediaSourceStream, MediaSourceStreamOptions};
+use symphonia::default::formats::OggReader;
+use unsafe_libopus::{
+    OPUS_OK, OpusDecoder as RawOpusDecoder, opus_decode, opus_decoder_create, opus_decoder_destroy,
+};
+
+/// Opus always decodes at 48 kHz whatever rate the original had.
+const OPUS_SAMPLE_RATE: u32 = 48_000;
+/// Longest packet Opus allows: 120 ms at 48 kHz.
+const MAX_PACKET_FRAMES: usize = 5_760;
+
+/// One libopus decoder for a mono or stereo stream.
+struct OpusPacketDecoder {
+    raw: *mut RawOpusDecoder,
+    channels: usize,
+}
+
+// The decoder state is only reached through `&mut self`, so moving it to the
+// audio thread with its source is sound.
+unsafe impl Send for OpusPacketDecoder {}
+
+impl OpusPacketDecoder {
+    fn new(channels: usize) -> Result<Self> {
+        let mut error = 0_i32;
+        let raw = unsafe {
+            opus_decoder_create(
+                OPUS_SAMPLE_RATE as i32,
+                i32::try_from(channels).unwrap_or(2),
+                &mut error,
+            )
+        };
+        if raw.is_null() || error != OPUS_OK {
+            anyhow::bail!("failed to initialize opus decoder (error code {error})");
+        }
+        Ok(Self { raw, channels })
+    }
+
+    /// Decodes one packet into `pcm`, returning the frames written.
+    fn decode(&mut self, packet: &[u8], pcm: &mut [i16]) -> Option<usize> {
+        let decoded = unsafe {
+            opus_decode(
+                self.raw,
+                packet.as_ptr(),
+                i32::try_from(packet.len()).unwrap_or(i32::MAX),
+                pcm.as_mut_ptr(),
+                i32::try_from(pcm.len() / self.channels).unwrap_or(i32::MAX),
+                0,
+            )
+        };
+        usize::try_from(decoded).ok()
+    }
+}
+
+impl Drop for OpusPacketDecoder {
+    fn drop(&mut self) {
+        if !self.raw.is_null() {
+            unsafe {
+                opus_decoder_destroy(self.raw);
+            }
+        }
+    }
+}
+
+/// Ogg Opus file decoded with libopus. Symphonia demuxes the pages and marks
+/// the encoder pre-skip and end padding, which are trimmed so tracks keep
+/// their exact length for gapless playback.
+pub(crate) struct OggOpusSource {
+    reader: OggReader,
+    track_id: u32,
+    decoder: OpusPacketDecoder,
+    channels: u16,
+    total_duration: Option<Duration>,
+    pcm: Vec<i16>,
+    buffer: Vec<f32>,
+    buffer_pos: usize,
+    /// Frames still to drop after a seek landed before the requested time.
+    skip_frames: u64,
+}
+
+impl OggOpusSource {
+    /// `Ok(None)` when the file is not an Ogg stream carrying Opus, so the caller
+    /// can report its own decoder's error instead.
+    pub(crate) fn open(path: &Path) -> Result<Option<Self>> {
+        let file =
+            File::open(path).with_context(|| format!("failed to open track {}", path.display()))?;
+        let stream = MediaSourceStream::new(Box::new(file), MediaSourceStreamOptions::default());
+        let options = FormatOptions {
+            enable_gapless: true,
+            ..FormatOptions::default()
+        };
+        let Ok(reader) = OggReader::try_new(stream, &options) else {
+            return Ok(None);
+        };
+        let Some(track) = reader
+            .tracks()
+            .iter()
+            .find(|track| track.codec_params.codec == CODEC_TYPE_OPUS)
+        else {
+            return Ok(None);
+        };
+
+        let channels = track
+            .codec_params
+            .channels
+            .map_or(0, |channels| channels.count());
+        if !(1..=2).contains(&channels) {
+            anyhow::bail!("Opus streams with {channels} channels are not supported");
+        }
+        let track_id = track.id;
+        let total_duration = track
+            .codec_params
+            .n_frames
+            .map(|frames| Duration::from_secs_f64(frames as f64 / f64::from(OPUS_SAMPLE_RATE)));
+
+        Ok(Some(Self {
+            reader,
+            track_id,
+            decoder: OpusPacketDecoder::new(channels)?,
+            channels: channels as u16,
+            total_duration,
+            pcm: vec![0; MAX_PACKET_FRAMES * channels],
+            buffer: Vec::new(),
+            buffer_pos: 0,
+            skip_frames: 0,
+        }))
+    }
+
+    /// Decodes packets until one yields samples. `false` at the end of the
+    /// stream; damaged packets are skipped rather than ending the track.
+    fn refill(&mut self) -> bool {
+        let channels = usize::from(self.channels);
+        loop {
+            let Ok(packet) = self.reader.next_packet() else {
+                return false;
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            let Some(frames) = self.decoder.decode(packet.buf(), &mut self.pcm) else {
+                continue;
+            };
+
+            let end = frames.saturating_sub(packet.trim_end() as usize);
+            let mut start = (packet.trim_start() as usize).min(end);
+            let skipped = self.skip_frames.min((end - start) as u64);
+            self.skip_frames -= skipped;
+            start += skipped as usize;
+
+            self.buffer.clear();
+            self.buffer.extend(
+                self.pcm[start * channels..end * channels]
+                    .iter()
+                    .map(|sample| f32::from(*sample) / 32_768.0),
+            );
+            self.buffer_pos = 0;
+            if !self.buffer.is_empty() {
+                return true;
+            }
+        }
+    }
+}
+
+impl Iterator for OggOpusSource {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer_pos >= self.buffer.len() && !self.refill() {
+            return None;
+        }
+        let sample = self.buffer[self.buffer_pos];
+        self.buffer_pos += 1;
+        Some(sample)
+    }
+}
+
+impl Source for OggOpusSource {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        rodio::ChannelCount::new(self.channels).unwrap_or(rodio::ChannelCount::MIN)
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        rodio::SampleRate::new(OPUS_SAMPLE_RATE).unwrap_or(rodio::SampleRate::MIN)
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+        let seeked = self
+            .reader
+            .seek(
+                SeekMode::Accurate,
+                SeekTo::Time {
+                    time: pos.into(),
+                    track_id: Some(self.track_id),
+                },
+            )
+            .map_err(|_| seek_unsupported())?;
+        // A fresh decoder keeps state from before the jump out of the new
+        // position.
+        self.decoder =
+            OpusPacketDecoder::new(usize::from(self.channels)).map_err(|_| seek_unsupported())?;
+        self.skip_frames = seeked.required_ts.saturating_sub(seeked.actual_ts);
+        self.buffer.clear();
+        self.buffer_pos = 0;
+        Ok(())
+    }
+}
+
+fn seek_unsupported() -> rodio::source::SeekError {
+    rodio::source::SeekError::NotSupported {
+        underlying_source: "ogg opus",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OggOpusSource;
+    use std::fs;
+
+    #[test]
+    fn files_that_are_not_ogg_opus_are_left_to_the_default_decoder() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("not-opus.opus");
+        fs::write(&path, b"RIFF\0\0\0\0WAVEjunk").expect("write fixture");
+
+        assert!(
+            OggOpusSource::open(&path)
+                .expect("opening a readable file")
+                .is_none()
+        );
+        assert!(OggOpusSource::open(&dir.path().join("missing.opus")).is_err());
+    }
+}
@@ -0,0 +1,440 @@
+use rodio::Source;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Length of each overlap-add window. Long enough to hold a couple of pitch
+/// periods of a low voice, short enough that the repeats are not heard as echo.
+const WINDOW: Duration = Duration::from_millis(40);
+/// How far either side of its natural spot a window may slide to line up with
+/// the previous one.
+const SEARCH: Duration = Duration::from_millis(8);
+/// Stride through the overlap when scoring a candidate position. The match only
+/// needs to be roughly right and full resolution costs several times more.
+const CORRELATION_STRIDE: usize = 4;
+
+/// Plays `inner` at the shared `ratio` without changing its pitch, using WSOLA:
+/// windows are read from the input `ratio` times faster than they are written,
+/// each nudged to the spot that best continues the previous window. At 1.0
+/// samples pass straight through.
+///
+/// `clock` always holds the track position in microseconds of what has been
+/// pulled, so the engine can report track time whatever the ratio.
+pub(crate) struct TimeStretch<S> {
+    inner: S,
+    ratio: Arc<AtomicU32>,
+    clock: Arc<AtomicU64>,
+    sample_rate: u32,
+    channels: usize,
+    /// Track position in frames of the next sample handed out.
+    track_frames: f64,
+    /// Interleaved output waiting to be handed out, each frame advancing the
+    /// track by `pending_step` frames.
+    pending: VecDeque<f32>,
+    pending_step: f64,
+    stretch: Option<StretchState>,
+    channel: usize,
+}
+
+/// Working buffers while the ratio is not 1.0. Frame numbers are absolute
+/// track frames so the position falls out of the analysis point.
+struct StretchState {
+    window: Vec<f32>,
+    hop: usize,
+    search: usize,
+    input: Vec<f32>,
+    input_start: u64,
+    input_done: bool,
+    natural: f64,
+    previous: Option<u64>,
+    accumulator: Vec<f32>,
+    /// Mono sums of `input`, reused every hop to score alignments.
+    mono: Vec<f32>,
+}
+
+impl StretchState {
+    fn new(start_frame: u64, sample_rate: u32, channels: usize) -> Self {
+        let rate = f64::from(sample_rate);
+        let hop = ((WINDOW.as_secs_f64() * rate) as usize / 2).max(16);
+        let window_len = hop * 2;
+        // Periodic Hann at 50% overlap sums to exactly one.
+        let window = (0..window_len)
+            .map(|index| {
+                let phase = std::f32::consts::TAU * index as f32 / window_len as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+        Self {
+            window,
+            hop,
+            search: ((SEARCH.as_secs_f64() * rate) as usize).max(1),
+            input: Vec::new(),
+            input_start: start_frame,
+            input_done: false,
+            natural: start_frame as f64,
+            previous: None,
+            accumulator: vec![0.0; window_len * channels],
+            mono: Vec::new(),
+        }
+    }
+
+    fn input_end(&self, channels: usize) -> u64 {
+        self.input_start + (self.input.len() / channels) as u64
+    }
+
+    fn fill_to<S: Source>(&mut self, inner: &mut S, end_frame: u64, channels: usize) {
+        while !self.input_done && self.input_end(channels) < end_frame {
+            for _ in 0..channels {
+                match inner.next() {
+                    Some(sample) => self.input.push(sample),
+                    None => {
+                        self.input_done = true;
+                        break;
+                    }
+                }
+            }
+        }
+        // A partial last frame would shift every channel after it.
+        let whole = self.input.len() - self.input.len() % channels;
+        self.input.truncate(whole);
+    }
+
+    fn sample(&self, frame: u64, channel: usize, channels: usize) -> f32 {
+        frame
+            .checked_sub(self.input_start)
+            .and_then(|offset| self.input.get(offset as usize * channels + channel))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Start frame within the search range whose opening half best matches the
+    /// continuation of the previous window.
+    fn best_start(&mut self, natural: u64, channels: usize) -> u64 {
+        let Some(previous) = self.previous else {
+            return natural;
+        };
+        let target = previous + self.hop as u64;
+        let low = natural
+            .saturating_sub(self.search as u64)
+            .max(self.input_start);
+        let high = natural + self.search as u64;
+
+        self.mono.clear();
+        self.mono.extend(
+            self.input
+                .chunks_exact(channels)
+                .map(|frame| frame.iter().sum::<f32>()),
+        );
+        let mono = &self.mono;
+        let at = |frame: u64| {
+            frame
+                .checked_sub(self.input_start)
+                .and_then(|offset| mono.get(offset as usize))
+                .copied()
+                .unwrap_or(0.0)
+        };
+
+        let mut best = (natural, f32::MIN);
+        let mut candidate = low;
+        while candidate <= high {
+            let score: f32 = (0..self.hop as u64)
+                .step_by(CORRELATION_STRIDE)
+                .map(|offset| at(candidate + offset) * at(target + offset))
+                .sum();
+            if score > best.1 {
+                best = (candidate, score);
+            }
+            candidate += 2;
+        }
+        best.0
+    }
+
+    /// Adds one window to the output and appends the finished hop to `out`.
+    /// `false` once the input has run out and the tail has been handed back.
+    fn next_hop<S: Source>(
+        &mut self,
+        inner: &mut S,
+        ratio: f64,
+        channels: usize,
+        out: &mut VecDeque<f32>,
+    ) -> bool {
+        let window_len = self.window.len();
+        let natural = self.natural.round() as u64;
+        self.fill_to(inner, natural + (self.search + window_len) as u64, channels);
+        if self.input_done && natural >= self.input_end(channels) {
+            if self.accumulator.iter().all(|sample| *sample == 0.0) {
+                return false;
+            }
+            out.extend(&self.accumulator[..self.hop * channels]);
+            self.accumulator.fill(0.0);
+            return true;
+        }
+
+        let start = self.best_start(natural, channels);
+        let first = self.previous.is_none();
+        for index in 0..window_len {
+            // The very first window has nothing to fade in against.
+            let gain = if first && index < self.hop {
+                1.0
+            } else {
+                self.window[index]
+            };
+            for channel in 0..channels {
+                self.accumulator[index * channels + channel] +=
+                    gain * self.sample(start + index as u64, channel, channels);
+            }
+        }
+
+        let hop_samples = self.hop * channels;
+        out.extend(&self.accumulator[..hop_samples]);
+        self.accumulator.copy_within(hop_samples.., 0);
+        let tail = self.accumulator.len() - hop_samples;
+        self.accumulator[tail..].fill(0.0);
+
+        self.previous = Some(start);
+        self.natural += self.hop as f64 * ratio;
+        let keep_from =
+            (start + self.hop as u64).min((self.natural as u64).saturating_sub(self.search as u64));
+        if keep_from > self.input_start {
+            let drop = ((keep_from - self.input_start) as usize * channels).min(self.input.len());
+            self.input.drain(..drop);
+            self.input_start += (drop / channels) as u64;
+        }
+        true
+    }
+
+    /// Raw input from where the previous window's second half begins. Added to
+    /// what is already in the accumulator it would rebuild exactly this input,
+    /// so handing it over unwindowed returns to 1.0 without a seam.
+    fn into_unstretched(self, channels: usize) -> (u64, Vec<f32>) {
+        let from = self
+            .previous
+            .map_or(self.natural as u64, |previous| previous + self.hop as u64);
+        let offset =
+            (from.saturating_sub(self.input_start) as usize * channels).min(self.input.len());
+        (from, self.input[offset..].to_vec())
+    }
+}
+
+impl<S: Source> TimeStretch<S> {
+    pub(crate) fn new(inner: S, ratio: Arc<AtomicU32>, clock: Arc<AtomicU64>) -> Self {
+        clock.store(0, Ordering::Relaxed);
+        Self {
+            sample_rate: inner.sample_rate().get(),
+            channels: usize::from(inner.channels().get()).max(1),
+            inner,
+            ratio,
+            clock,
+            track_frames: 0.0,
+            pending: VecDeque::new(),
+            pending_step: 1.0,
+            stretch: None,
+            channel: 0,
+        }
+    }
+
+    fn ratio(&self) -> f64 {
+        f64::from(f32::from_bits(self.ratio.load(Ordering::Relaxed)))
+    }
+
+    fn publish_clock(&self) {
+        let micros = self.track_frames / f64::from(self.sample_rate.max(1)) * 1_000_000.0;
+        self.clock.store(micros.max(0.0) as u64, Ordering::Relaxed);
+    }
+
+    /// Counts one handed-out sample toward the track position.
+    fn advance(&mut self, step: f64) {
+        self.channel += 1;
+        if self.channel >= self.channels {
+            self.channel = 0;
+            self.track_frames += step;
+            self.publish_clock();
+        }
+    }
+}
+
+impl<S: Source> Iterator for TimeStretch<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sample) = self.pending.pop_front() {
+                self.advance(self.pending_step);
+                return Some(sample);
+            }
+
+            let ratio = self.ratio();
+            let unity = (ratio - 1.0).abs() < 1e-3;
+            if self.stretch.is_none() {
+                if unity || self.channel != 0 {
+                    let sample = self.inner.next()?;
+                    self.advance(1.0);
+                    return Some(sample);
+                }
+                self.sample_rate = self.inner.sample_rate().get();
+                self.channels = usize::from(self.inner.channels().get()).max(1);
+                self.stretch = Some(StretchState::new(
+                    self.track_frames.round() as u64,
+                    self.sample_rate,
+                    self.channels,
+                ));
+            }
+
+            let channels = self.channels;
+            if unity {
+                let state = self.stretch.take().expect("stretch checked above");
+                let (from, samples) = state.into_unstretched(channels);
+                self.track_frames = from as f64;
+                self.pending.extend(samples);
+                self.pending_step = 1.0;
+                continue;
+            }
+
+            let state = self.stretch.as_mut().expect("stretch checked above");
+            if state.next_hop(&mut self.inner, ratio, channels, &mut self.pending) {
+                self.pending_step = ratio;
+            } else {
+                self.stretch = None;
+                return None;
+            }
+        }
+    }
+}
+
+impl<S: Source> Source for TimeStretch<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        if self.stretch.is_none() && self.pending.is_empty() {
+            self.inner.current_span_len()
+        } else {
+            None
+        }
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+        self.inner.try_seek(pos)?;
+        self.stretch = None;
+        self.pending.clear();
+        self.channel = 0;
+        self.track_frames = pos.as_secs_f64() * f64::from(self.sample_rate);
+        self.publish_clock();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeStretch;
+    use rodio::Source;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+    use std::time::Duration;
+
+    /// Mono sine, 8 kHz, `len` samples.
+    struct Sine {
+        frequency: f32,
+        index: usize,
+        len: usize,
+    }
+
+    impl Iterator for Sine {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            (self.index < self.len).then(|| {
+                let t = self.index as f32 / 8_000.0;
+                self.index += 1;
+                (std::f32::consts::TAU * self.frequency * t).sin() * 0.5
+            })
+        }
+    }
+
+    impl Source for Sine {
+        fn current_span_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> rodio::ChannelCount {
+            rodio::ChannelCount::new(1).expect("non-zero")
+        }
+
+        fn sample_rate(&self) -> rodio::SampleRate {
+            rodio::SampleRate::new(8_000).expect("non-zero")
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    fn stretched(ratio: f32, len: usize) -> (Vec<f32>, Arc<AtomicU64>) {
+        let source = Sine {
+            frequency: 200.0,
+            index: 0,
+            len,
+        };
+        let clock = Arc::new(AtomicU64::new(0));
+        let ratio = Arc::new(AtomicU32::new(ratio.to_bits()));
+        let samples = TimeStretch::new(source, ratio, Arc::clone(&clock)).collect();
+        (samples, clock)
+    }
+
+    /// Upward zero crossings per second over the middle of the output.
+    fn frequency(samples: &[f32]) -> f32 {
+        let middle = &samples[samples.len() / 4..samples.len() * 3 / 4];
+        let crossings = middle
+            .windows(2)
+            .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+            .count();
+        crossings as f32 * 8_000.0 / middle.len() as f32
+    }
+
+    #[test]
+    fn unity_ratio_passes_samples_through_and_clocks_track_time() {
+        let (samples, clock) = stretched(1.0, 8_000);
+        let expected: Vec<f32> = Sine {
+            frequency: 200.0,
+            index: 0,
+            len: 8_000,
+        }
+        .collect();
+
+        assert_eq!(samples, expected);
+        assert_eq!(clock.load(Ordering::Relaxed), 1_000_000);
+    }
+
+    #[test]
+    fn faster_ratio_shortens_output_but_keeps_pitch_and_track_time() {
+        let (samples, clock) = stretched(1.5, 24_000);
+
+        let expected_len = 24_000.0 / 1.5;
+        let ratio = samples.len() as f32 / expected_len;
+        assert!((ratio - 1.0).abs() < 0.05, "length ratio was {ratio}");
+        let pitch = frequency(&samples);
+        assert!((pitch - 200.0).abs() < 10.0, "pitch was {pitch}");
+        let clock = clock.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        assert!((clock - 3.0).abs() < 0.1, "clock was {clock}");
+    }
+
+    #[test]
+    fn slower_ratio_lengthens_output_and_keeps_pitch() {
+        let (samples, _) = stretched(0.5, 8_000);
+
+        let ratio = samples.len() as f32 / 16_000.0;
+        assert!((ratio - 1.0).abs() < 0.05, "length ratio was {ratio}");
+        let pitch = frequency(&samples);
+        assert!((pitch - 200.0).abs() < 10.0, "pitch was {pitch}");
+    }
+}
@@ -1,8 +1,11 @@
 use crate::library::LibraryIndex;
 use crate::model::PersistedState;
+use crate::online_secure::{open_at_rest, seal_at_rest};
 use anyhow::{Context, Result};
+use rand::RngExt;
 use std::env;
 use std::fs;
+use std::io::Write;
 use std::path::Component;
 use std::path::{Path, PathBuf};
 #[cfg(test)]
@@ -10,9 +13,14 @@ use std::sync::OnceLock;
 
 const APP_DIR: &str = "tunetui";
 const STATE_FILE: &str = "state.json";
+const STATE_KEY_FILE: &str = "state.key";
 const STATS_FILE: &str = "stats.json";
 const LIBRARY_INDEX_FILE: &str = "library_index.json";
 const LYRICS_DIR: &str = "lyrics";
+const TAG_BACKUPS_DIR: &str = "tag_backups";
+const SYNC_DEBUG_LOG_FILE: &str = "sync_debug.log";
+const SCROBBLE_QUEUE_FILE: &str = "scrobble_queue.json";
+const COVER_ART_CACHE_DIR: &str = "cover_art_cache";
 
 pub fn config_root() -> Result<PathBuf> {
     #[cfg(test)]
@@ -86,6 +94,26 @@ pub fn stats_path() -> Result<PathBuf> {
     Ok(config_root()?.join(STATS_FILE))
 }
 
+pub fn sync_debug_log_path() -> Result<PathBuf> {
+    Ok(config_root()?.join(SYNC_DEBUG_LOG_FILE))
+}
+
+pub fn scrobble_queue_path() -> Result<PathBuf> {
+    Ok(config_root()?.join(SCROBBLE_QUEUE_FILE))
+}
+
+/// Cache file for an album's downloaded cover, keyed case-insensitively by artist and album.
+pub fn cover_art_cache_path(artist: &str, album: &str) -> Result<PathBuf> {
+    let key = format!(
+        "{}\n{}",
+        artist.trim().to_lowercase(),
+        album.trim().to_lowercase()
+    );
+    Ok(config_root()?
+        .join(COVER_ART_CACHE_DIR)
+        .join(format!("{:016x}.img", stable_fnv1a_64(&key))))
+}
+
 pub fn library_index_path() -> Result<PathBuf> {
     Ok(config_root()?.join(LIBRARY_INDEX_FILE))
 }
@@ -101,6 +129,22 @@ pub fn ensure_lyrics_dir() -> Result<PathBuf> {
 }
 
 pub fn lyrics_path_for_track(track_path: &Path) -> Result<PathBuf> {
+    Ok(lyrics_root()?.join(format!("{}.lrc", track_file_key(track_path))))
+}
+
+pub fn tag_backup_root() -> Result<PathBuf> {
+    Ok(config_root()?.join(TAG_BACKUPS_DIR))
+}
+
+pub fn tag_backup_path_for_track(track_path: &Path, backup_dir: &Path) -> PathBuf {
+    let key = track_file_key(track_path);
+    match track_path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => backup_dir.join(format!("{key}.{ext}")),
+        None => backup_dir.join(key),
+    }
+}
+
+fn track_file_key(track_path: &Path) -> String {
     let normalized = normalize_path(track_path);
     let normalized_display = sanitize_display_text(&normalized.to_string_lossy());
     let hash = stable_fnv1a_64(&normalized_display);
@@ -112,7 +156,7 @@ pub fn lyrics_path_for_track(track_path: &Path) -> Result<PathBuf> {
         .filter(|name| !name.is_empty())
         .unwrap_or_else(|| String::from("track"));
 
-    Ok(lyrics_root()?.join(format!("{stem}-{hash:016x}.lrc")))
+    format!("{stem}-{hash:016x}")
 }
 
 pub fn load_state() -> Result<PersistedState> {
@@ -136,12 +180,11 @@ fn load_state_from_path(path: &Path) -> Result<PersistedState> {
     if !has_split_playback_settings {
         state.migrate_legacy_playback_mode();
     }
+    open_online_password(path, &mut state);
 
-    state.folders = state
-        .folders
-        .iter()
-        .map(|folder| recover_existing_path(folder))
-        .collect();
+    for folder in &mut state.folders {
+        folder.path = recover_existing_path(&folder.path);
+    }
     for playlist in state.playlists.values_mut() {
         playlist.tracks = playlist
             .tracks
@@ -192,11 +235,92 @@ fn save_state_to_path(path: &Path, state: &PersistedState) -> Result<()> {
         let backup = path.with_extension("json.bak");
         let _ = fs::copy(path, &backup);
     }
-    let json = serde_json::to_string_pretty(state)?;
+    let sealed = seal_online_password(path, state);
+    let json = serde_json::to_string_pretty(sealed.as_ref().unwrap_or(state))?;
     fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))?;
     Ok(())
 }
 
+/// A copy of the state with the remembered room password sealed, or `None`
+/// when there is no password to write.
+fn seal_online_password(path: &Path, state: &PersistedState) -> Option<PersistedState> {
+    let password = state.last_online_join.as_ref()?.password.as_deref()?;
+    let sealed = load_or_create_state_key(path).and_then(|key| seal_at_rest(&key, password));
+    let mut state = state.clone();
+    if let Some(join) = state.last_online_join.as_mut() {
+        join.sealed_password = sealed;
+    }
+    Some(state)
+}
+
+/// A missing or replaced key only forgets the password; the room is kept.
+fn open_online_password(path: &Path, state: &mut PersistedState) {
+    let Some(join) = state.last_online_join.as_mut() else {
+        return;
+    };
+    if let Some(sealed) = join.sealed_password.take() {
+        join.password = read_state_key(path).and_then(|key| open_at_rest(&key, &sealed));
+    }
+}
+
+fn read_state_key(state_path: &Path) -> Option<[u8; 32]> {
+    fs::read(state_path.with_file_name(STATE_KEY_FILE))
+        .ok()?
+        .try_into()
+        .ok()
+}
+
+/// The key lives beside the state file and, on Unix, only its owner can read it.
+fn load_or_create_state_key(state_path: &Path) -> Option<[u8; 32]> {
+    if let Some(key) = read_state_key(state_path) {
+        return Some(key);
+    }
+    let mut key = [0_u8; 32];
+    rand::rng().fill(&mut key);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(state_path.with_file_name(STATE_KEY_FILE))
+        .and_then(|mut file| file.write_all(&key))
+        .ok()?;
+    Some(key)
+}
+
+/// Decodes a `file://` URI, including `%20`-style escapes. Returns `None` for
+/// anything that is not a file URI.
+pub fn file_uri_path(text: &str) -> Option<PathBuf> {
+    let uri_path = text.strip_prefix("file://")?;
+    let uri_path = uri_path.strip_prefix("localhost").unwrap_or(uri_path);
+    let mut bytes = Vec::with_capacity(uri_path.len());
+    let raw = uri_path.as_bytes();
+    let mut idx = 0;
+    while idx < raw.len() {
+        let escaped = (raw[idx] == b'%')
+            .then(|| uri_path.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(byte) = escaped {
+            bytes.push(byte);
+            idx += 3;
+        } else {
+            bytes.push(raw[idx]);
+            idx += 1;
+        }
+    }
+    let decoded = String::from_utf8_lossy(&bytes).into_owned();
+    // `file:///C:/Music` carries a slash before the drive letter on Windows.
+    let decoded = match decoded.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => decoded[1..].to_string(),
+        _ => decoded,
+    };
+    Some(PathBuf::from(decoded))
+}
+
 pub fn normalize_path(path: &Path) -> PathBuf {
     let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
     strip_windows_verbatim_prefix(&canonical)
@@ -479,6 +603,40 @@ mod tests {
         assert_eq!(loaded.repeat_mode, crate::model::RepeatMode::All);
     }
 
+    #[test]
+    fn remembered_room_password_is_sealed_on_disk() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join(STATE_FILE);
+        let state = PersistedState {
+            last_online_join: Some(crate::model::LastOnlineJoin {
+                server_addr: String::from("10.0.0.2:7878"),
+                room_name: String::from("Friday"),
+                password: Some(String::from("hunter22")),
+                sealed_password: None,
+            }),
+            ..PersistedState::default()
+        };
+
+        save_state_to_path(&path, &state).expect("save");
+        let raw = fs::read_to_string(&path).expect("read state");
+        assert!(!raw.contains("hunter22"));
+        assert!(raw.contains("sealed_password"));
+        let join = load_state_from_path(&path)
+            .expect("load")
+            .last_online_join
+            .expect("last join");
+        assert_eq!(join.password.as_deref(), Some("hunter22"));
+        assert_eq!(join.sealed_password, None);
+
+        fs::remove_file(dir.path().join(STATE_KEY_FILE)).expect("remove key");
+        let join = load_state_from_path(&path)
+            .expect("load")
+            .last_online_join
+            .expect("last join");
+        assert_eq!(join.room_name, "Friday");
+        assert_eq!(join.password, None);
+    }
+
     #[test]
     fn load_migrates_legacy_playback_mode() {
         let dir = tempdir().expect("tempdir");
@@ -499,6 +657,26 @@ mod tests {
         assert_eq!(loaded.repeat_mode, crate::model::RepeatMode::All);
     }
 
+    #[test]
+    fn load_accepts_bare_folder_paths_and_enabled_flags() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join(STATE_FILE);
+        fs::write(
+            &path,
+            r#"{
+                "folders": ["/music/a", {"path": "/music/b", "enabled": false}],
+                "playlists": {}
+            }"#,
+        )
+        .expect("write state");
+
+        let loaded = load_state_from_path(&path).expect("load");
+
+        assert_eq!(loaded.folders.len(), 2);
+        assert!(loaded.folders[0].enabled);
+        assert!(!loaded.folders[1].enabled);
+    }
+
     #[test]
     fn save_and_load_library_index_round_trip() {
         let dir = tempdir().expect("tempdir");
@@ -513,6 +691,7 @@ mod tests {
                     file_size_bytes: 123,
                     modified_unix_seconds: 456,
                 }),
+                first_seen_unix_seconds: 789,
             }],
         };
 
@@ -650,4 +829,18 @@ mod tests {
             .expect("filename");
         assert!(filename.starts_with("song-"));
     }
+
+    #[test]
+    fn tag_backup_path_keeps_track_extension_in_backup_directory() {
+        let track_path = PathBuf::from("Music").join("Artist").join("song.flac");
+        let backup_dir = PathBuf::from("backups");
+        let path = tag_backup_path_for_track(&track_path, &backup_dir);
+        assert_eq!(path.parent(), Some(backup_dir.as_path()));
+        assert_eq!(path.extension().and_then(|ext| ext.to_str()), Some("flac"));
+        let filename = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .expect("filename");
+        assert!(filename.starts_with("song-"));
+    }
 }
@@ -1,19 +1,23 @@
 use crate::config;
 use crate::library;
 use crate::lyrics::{self, LyricLine, LyricsDocument, LyricsSource};
-use crate::model::{CoverArtTemplate, PersistedState, Playlist, RepeatMode, Theme, Track};
-use crate::online::OnlineState;
-use crate::stats::{StatsRange, StatsSort};
-use rand::SeedableRng;
+use crate::model::{
+    CoverArtTemplate, CrossfadeCurve, EndOfQueueBehavior, FolderEnterBehavior, KeepGoingPool,
+    LastOnlineJoin, LibraryFolder, PersistedState, Playlist, RepeatMode, ShuffleMode,
+    SmartPlaylist, SmartPlaylistSort, Theme, Track, default_equalizer_bands,
+};
+use crate::online::{OnlineState, TransportCommand};
+use crate::stats::{ListenEvent, StatsExportFormat, StatsMerge, StatsRange, StatsSort};
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
-use std::cell::RefCell;
-use std::collections::HashMap;
+use rand::{RngExt, SeedableRng};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BrowserEntryKind {
@@ -43,6 +47,7 @@ pub enum StatsFilterFocus {
     Artist,
     Album,
     Search,
+    Row(usize),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -51,6 +56,16 @@ pub enum LyricsMode {
     Edit,
 }
 
+/// What the user was doing when unsaved lyric edits stopped them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LyricsLeaveAction {
+    SwitchSection(HeaderSection),
+    ChangeTrack(PathBuf),
+    Quit,
+}
+
+const LYRICS_LEAVE_PROMPT: &str = "Unsaved lyric changes - save? (y/n/esc)";
+
 impl StatsFilterFocus {
     pub fn label(self) -> &'static str {
         match self {
@@ -59,6 +74,7 @@ impl StatsFilterFocus {
             Self::Artist => "Artist",
             Self::Album => "Album",
             Self::Search => "Search",
+            Self::Row(_) => "Track",
         }
     }
 }
@@ -83,6 +99,49 @@ impl HeaderSection {
     }
 }
 
+/// How the All Songs view lays out the library. Not persisted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AllSongsOrder {
+    #[default]
+    Title,
+    Year,
+    Genre,
+}
+
+impl AllSongsOrder {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Title => Self::Year,
+            Self::Year => Self::Genre,
+            Self::Genre => Self::Title,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Title => "sorted by title",
+            Self::Year => "sorted by year",
+            Self::Genre => "grouped by genre",
+        }
+    }
+}
+
+/// A track waiting for cover art to be looked up online and embedded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverArtDownloadRequest {
+    pub track_path: PathBuf,
+    pub artist: String,
+    pub album: String,
+}
+
+/// A track waiting for its lyrics to be looked up online and saved as a sidecar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LyricsFetchRequest {
+    pub track_path: PathBuf,
+    pub artist: String,
+    pub title: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct BrowserEntry {
     pub kind: BrowserEntryKind,
@@ -90,31 +149,126 @@ pub struct BrowserEntry {
     pub label: String,
 }
 
+const QUEUE_TEXT_HEADER: &str = "# TuneTUI queue (title<TAB>artist<TAB>album)";
+const RADIO_BATCH: usize = 5;
+pub const MAX_TRACK_GAP_SECONDS: u16 = 5;
+const DAILY_LISTEN_GOAL_OPTIONS: [u16; 7] = [0, 30, 60, 90, 120, 180, 240];
+const CROSSFADE_LEAD_OPTIONS_MS: [u16; 5] = [0, 250, 500, 1_000, 2_000];
+const STATS_GRACE_OPTIONS_SECONDS: [u16; 5] = [0, 5, 10, 15, 30];
+const STATS_COMPLETION_OPTIONS_PERCENT: [u8; 4] = [0, 80, 90, 95];
+/// Sleep timer lengths offered in the action panel.
+pub const SLEEP_TIMER_OPTIONS_MINUTES: [u16; 4] = [15, 30, 45, 60];
+const PLAYBACK_SPEED_OPTIONS: [f32; 7] = [0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+/// Each Enter on a band raises it by this much, wrapping from the top back to
+/// the bottom of the range.
+const EQUALIZER_STEP_DB: f32 = 3.0;
+const EQUALIZER_MAX_GAIN_DB: f32 = 12.0;
+const RADIO_RECENT_WINDOW: usize = 25;
+const SHUFFLE_RECENT_OPTIONS_PERCENT: [u8; 4] = [10, 20, 30, 50];
+/// Plays remembered for smart shuffle, enough to cover the largest window on
+/// a big library queue.
+const RECENT_PLAYS_LIMIT: usize = 2_000;
+const RADIO_SEED_LISTENS: usize = 10;
+
+/// Result of `TuneCore::import_queue_text`. `not_found` holds a
+/// `title - artist` marker for each line with no match in the library.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueueImport {
+    pub matched: usize,
+    pub not_found: Vec<String>,
+}
+
 #[derive(Debug)]
 pub struct TuneCore {
-    pub folders: Vec<PathBuf>,
+    pub folders: Vec<LibraryFolder>,
     pub tracks: Vec<Track>,
     track_lookup: HashMap<String, usize>,
     pub playlists: HashMap<String, Playlist>,
+    pub smart_playlists: HashMap<String, SmartPlaylist>,
     pub queue: Vec<usize>,
     pub selected_track: usize,
     pub current_queue_index: Option<usize>,
     pub shuffle_enabled: bool,
+    pub shuffle_mode: ShuffleMode,
+    pub shuffle_recent_percent: u8,
     pub repeat_mode: RepeatMode,
+    pub end_of_queue_behavior: EndOfQueueBehavior,
+    pub keep_going_pool: KeepGoingPool,
+    pub folder_enter_behavior: FolderEnterBehavior,
+    pub crossfade_only_when_shuffling: bool,
+    pub sleep_on_silence_minutes: u16,
+    pub window_title_shows_track: bool,
+    pub backup_before_tag_writes: bool,
+    pub tag_backup_dir: Option<PathBuf>,
+    pub scan_ignore_patterns: Vec<String>,
+    pub hidden_tracks: Vec<PathBuf>,
+    pub now_playing_template: String,
+    pub listenbrainz_token: Option<String>,
     pub loudness_normalization: bool,
+    /// Caps the normalization boost by the track's peak so it cannot clip.
+    pub prevent_clipping: bool,
+    /// Equalizer bands as `(frequency Hz, gain dB)`, lowest band first.
+    pub equalizer_bands: Vec<(f32, f32)>,
+    /// Queues the next track to start the instant the current one ends when
+    /// no crossfade applies.
+    pub gapless: bool,
+    /// Normalization prefers album gain tags so songs of one album keep their
+    /// relative loudness.
+    pub album_gain: bool,
+    /// Playback rate; positions stay in track time and the UI divides by it.
+    pub playback_speed: f32,
+    /// Speeds other than 1.0 time-stretch instead of shifting pitch.
+    pub preserve_pitch: bool,
+    /// Stereo tracks are downmixed so both speakers play both channels.
+    pub mono_output: bool,
+    /// -1.0 full left to 1.0 full right.
+    pub balance: f32,
     pub crossfade_seconds: u16,
+    pub crossfade_curve: CrossfadeCurve,
+    pub no_crossfade_tracks: Vec<PathBuf>,
     pub scrub_seconds: u16,
+    pub scrub_back_key: char,
+    pub scrub_forward_key: char,
+    pub arrow_keys_seek: bool,
+    /// Silence inserted between tracks on auto-advance; never set alongside crossfade.
+    pub track_gap_seconds: u16,
+    /// When the current gap ends and the next track should start.
+    pub pending_advance_at: Option<Instant>,
+    /// Track started with Alt+Enter: playback stops when it ends instead of
+    /// advancing. Dropped once it fires or another track starts.
+    pub play_once: Option<PathBuf>,
+    /// When the sleep timer fades playback out and pauses it.
+    pub sleep_deadline: Option<Instant>,
+    /// Sleep timer set to "end of track": playback stops when the current
+    /// track ends instead of advancing.
+    pub sleep_after_current_track: bool,
+    /// Listening target for the Stats Today progress line; 0 turns it off.
+    pub daily_listen_goal_minutes: u16,
+    /// How long before the crossfade window the next track is opened, so slow
+    /// storage has it decoding by the time the fade starts.
+    pub crossfade_lead_ms: u16,
+    /// Playing time a track needs before stats open a listen session for it,
+    /// so tracks skipped while sampling leave no record.
+    pub stats_grace_seconds: u16,
+    /// Share of a track's duration that counts as finishing it, alongside the
+    /// last-second window; 0 leaves only the window.
+    pub stats_completion_percent: u8,
+    /// Explicit compact layout choice; `None` switches by terminal size.
+    pub compact_layout: Option<bool>,
     pub theme: Theme,
     pub header_section: HeaderSection,
     pub browser_path: Option<PathBuf>,
     pub browser_playlist: Option<String>,
     pub browser_all_songs: bool,
+    pub all_songs_order: AllSongsOrder,
     pub browser_local_queue: bool,
     pub browser_shared_queue: bool,
     pub browser_entries: Vec<BrowserEntry>,
     pub selected_browser: usize,
     pub library_search_query: String,
     pub library_search_focused: bool,
+    /// Search results marked with Insert or Ctrl+Space for a bulk queue or playlist add.
+    pub library_search_marked: Vec<BrowserEntry>,
     pub dirty: bool,
     pub status: String,
     pub stats_enabled: bool,
@@ -129,54 +283,147 @@ pub struct TuneCore {
     pub stats_focus: StatsFilterFocus,
     pub stats_scroll: u16,
     pub clear_stats_requested: bool,
+    pub stats_forget_confirm: Option<String>,
+    pub forget_stats_track_requested: Option<String>,
+    pub stats_merge_preview_requested: bool,
+    /// Merges confirmed in the preview panel, applied exactly as shown.
+    pub coalesce_stats_requested: Option<Vec<StatsMerge>>,
+    pub stats_export_requested: Option<(PathBuf, StatsExportFormat)>,
+    pub cover_art_download_requested: Option<CoverArtDownloadRequest>,
+    pub lyrics_fetch_requested: Option<LyricsFetchRequest>,
+    /// File or folder to read in the background and play without adding it
+    /// to the library.
+    pub play_path_requested: Option<PathBuf>,
+    /// Set by Find duplicate tracks; the scan probes durations off the UI thread.
+    pub duplicate_scan_requested: bool,
     pub online_nickname: String,
+    /// Parameters of the last successful room join, kept for a manual
+    /// reconnect. The password stays in memory unless persisting it is on.
+    pub last_online_join: Option<LastOnlineJoin>,
+    pub remember_online_password: bool,
     pub lyrics: Option<LyricsDocument>,
     pub lyrics_track_path: Option<PathBuf>,
     pub lyrics_mode: LyricsMode,
     pub lyrics_selected_line: usize,
     pub lyrics_missing_prompt: bool,
     pub lyrics_creation_declined: bool,
+    /// Edit-mode changes not yet written to the sidecar.
+    pub lyrics_unsaved: bool,
+    pub lyrics_leave_prompt: Option<LyricsLeaveAction>,
+    /// Track the user chose to keep editing past, so it is not asked again.
+    lyrics_kept_for_track: Option<PathBuf>,
     pub online: OnlineState,
+    pub online_queue_scroll: usize,
+    /// Loop start A marked on a track while waiting for B. Ignored once a
+    /// different track plays.
+    pub ab_loop_start: Option<(PathBuf, Duration)>,
     duration_lookup: RefCell<HashMap<String, Option<u32>>>,
     cover_art_lookup: RefCell<HashMap<String, Option<Arc<[u8]>>>>,
     sorted_library_queue_cache: RefCell<Option<Vec<usize>>>,
+    /// Smart playlist contents, dropped on open and whenever the library changes.
+    smart_playlist_cache: RefCell<HashMap<String, Vec<PathBuf>>>,
+    /// Date added per normalized path, copied from the library index.
+    track_added_at: HashMap<String, u64>,
+    /// Normalized paths of tracks queued with Play path from outside the
+    /// library. They sit in `tracks` so the queue can address them but are left
+    /// out of library views, counts, and the saved index.
+    transient_tracks: HashSet<String>,
+    /// Genre and year per track, probed from the file the first time they are needed.
+    extra_tags_lookup: RefCell<HashMap<String, library::ExtraTags>>,
+    /// All Songs showed tracks whose genre and year are not probed yet.
+    extra_tags_probe_requested: Cell<bool>,
+    /// Track handed to the player last, kept independent of queue positions so
+    /// `current_queue_index` can be re-resolved after the queue is rebuilt.
+    pinned_track: Option<PathBuf>,
     shuffle_order: Vec<usize>,
     shuffle_cursor: usize,
     shuffle_rng: SmallRng,
+    /// Normalized paths of tracks handed to the player, newest last.
+    recent_plays: VecDeque<String>,
+    /// Counted plays per normalized path, for `ShuffleMode::FewerPlays` and
+    /// smart playlists.
+    play_counts: HashMap<String, u32>,
+    /// Start of the latest counted play per normalized path, in epoch seconds.
+    last_played_at: HashMap<String, i64>,
 }
 
 impl TuneCore {
     pub fn from_persisted(state: PersistedState) -> Self {
-        let tracks = library::scan_many(&state.folders);
+        let tracks = library::scan_many(
+            &crate::model::enabled_folder_paths(&state.folders),
+            &state.scan_ignore_patterns,
+        );
         Self::from_persisted_with_tracks(state, tracks)
     }
 
-    pub fn from_persisted_with_tracks(state: PersistedState, tracks: Vec<Track>) -> Self {
+    pub fn from_persisted_with_tracks(state: PersistedState, mut tracks: Vec<Track>) -> Self {
+        let hidden: HashSet<&Path> = state.hidden_tracks.iter().map(PathBuf::as_path).collect();
+        tracks.retain(|track| !hidden.contains(track.path.as_path()));
         let track_lookup = build_track_lookup(&tracks);
         let mut core = Self {
             folders: state.folders,
             tracks,
             track_lookup,
             playlists: state.playlists,
+            smart_playlists: state.smart_playlists,
             queue: Vec::new(),
             selected_track: 0,
             current_queue_index: None,
             shuffle_enabled: state.shuffle_enabled,
+            shuffle_mode: state.shuffle_mode,
+            shuffle_recent_percent: state.shuffle_recent_percent.clamp(1, 100),
             repeat_mode: state.repeat_mode,
+            end_of_queue_behavior: state.end_of_queue_behavior,
+            keep_going_pool: state.keep_going_pool,
+            folder_enter_behavior: state.folder_enter_behavior,
+            crossfade_only_when_shuffling: state.crossfade_only_when_shuffling,
+            sleep_on_silence_minutes: state.sleep_on_silence_minutes,
+            window_title_shows_track: state.window_title_shows_track,
+            backup_before_tag_writes: state.backup_before_tag_writes,
+            tag_backup_dir: state.tag_backup_dir,
+            scan_ignore_patterns: state.scan_ignore_patterns,
+            hidden_tracks: state.hidden_tracks,
+            now_playing_template: state.now_playing_template,
+            listenbrainz_token: state.listenbrainz_token,
             loudness_normalization: state.loudness_normalization,
+            prevent_clipping: state.prevent_clipping,
+            equalizer_bands: normalize_equalizer_bands(state.equalizer_bands),
+            gapless: state.gapless,
+            album_gain: state.album_gain,
+            playback_speed: crate::audio::clamp_playback_speed(state.playback_speed),
+            preserve_pitch: state.preserve_pitch,
+            mono_output: state.mono_output,
+            balance: crate::audio::clamp_balance(state.balance),
             crossfade_seconds: state.crossfade_seconds,
+            crossfade_curve: state.crossfade_curve,
+            no_crossfade_tracks: state.no_crossfade_tracks,
             scrub_seconds: normalize_scrub_seconds(state.scrub_seconds),
+            scrub_back_key: state.scrub_back_key,
+            scrub_forward_key: state.scrub_forward_key,
+            arrow_keys_seek: state.arrow_keys_seek,
+            track_gap_seconds: state.track_gap_seconds.min(MAX_TRACK_GAP_SECONDS),
+            pending_advance_at: None,
+            play_once: None,
+            sleep_deadline: None,
+            sleep_after_current_track: false,
+            daily_listen_goal_minutes: state.daily_listen_goal_minutes,
+            crossfade_lead_ms: state.crossfade_lead_ms,
+            stats_grace_seconds: state.stats_grace_seconds,
+            stats_completion_percent: state.stats_completion_percent.min(100),
+            compact_layout: state.compact_layout,
             theme: state.theme,
             header_section: HeaderSection::Library,
             browser_path: None,
             browser_playlist: None,
             browser_all_songs: false,
+            all_songs_order: AllSongsOrder::default(),
             browser_local_queue: false,
             browser_shared_queue: false,
             browser_entries: Vec::new(),
             selected_browser: 0,
             library_search_query: String::new(),
             library_search_focused: false,
+            library_search_marked: Vec::new(),
             dirty: true,
             status: String::from("Ready"),
             stats_enabled: state.stats_enabled,
@@ -193,20 +440,45 @@ impl TuneCore {
             stats_focus: StatsFilterFocus::Range(0),
             stats_scroll: 0,
             clear_stats_requested: false,
+            stats_forget_confirm: None,
+            forget_stats_track_requested: None,
+            stats_merge_preview_requested: false,
+            coalesce_stats_requested: None,
+            stats_export_requested: None,
+            cover_art_download_requested: None,
+            lyrics_fetch_requested: None,
+            play_path_requested: None,
+            duplicate_scan_requested: false,
             online_nickname: state.online_nickname.unwrap_or_default(),
+            last_online_join: state.last_online_join,
+            remember_online_password: state.remember_online_password,
             lyrics: None,
             lyrics_track_path: None,
             lyrics_mode: LyricsMode::View,
             lyrics_selected_line: 0,
             lyrics_missing_prompt: false,
             lyrics_creation_declined: false,
+            lyrics_unsaved: false,
+            lyrics_leave_prompt: None,
+            lyrics_kept_for_track: None,
             online: OnlineState::default(),
+            online_queue_scroll: 0,
+            ab_loop_start: None,
             duration_lookup: RefCell::new(HashMap::new()),
             cover_art_lookup: RefCell::new(HashMap::new()),
             sorted_library_queue_cache: RefCell::new(None),
+            smart_playlist_cache: RefCell::new(HashMap::new()),
+            track_added_at: HashMap::new(),
+            transient_tracks: HashSet::new(),
+            extra_tags_lookup: RefCell::new(HashMap::new()),
+            extra_tags_probe_requested: Cell::new(false),
+            pinned_track: None,
             shuffle_order: Vec::new(),
             shuffle_cursor: 0,
             shuffle_rng: SmallRng::from_rng(&mut rand::rng()),
+            recent_plays: VecDeque::new(),
+            play_counts: HashMap::new(),
+            last_played_at: HashMap::new(),
         };
         core.rebuild_main_queue();
         core.refresh_browser_entries();
@@ -214,14 +486,24 @@ impl TuneCore {
     }
 
     pub fn replace_library_tracks(&mut self, mut tracks: Vec<Track>) {
+        self.drop_hidden_tracks(&mut tracks);
         tracks.sort_by(|a, b| a.path.cmp(&b.path));
         tracks.dedup_by(|a, b| a.path == b.path);
         self.capture_library_update(|core| {
-            core.tracks = tracks;
+            let previous = std::mem::replace(&mut core.tracks, tracks);
+            let library_keys = build_track_lookup(&core.tracks);
+            core.transient_tracks
+                .retain(|key| !library_keys.contains_key(key));
+            // Play path tracks outside the library stay so their queue survives.
+            core.tracks.extend(previous.into_iter().filter(|track| {
+                core.transient_tracks
+                    .contains(&normalized_path_key(&track.path))
+            }));
         });
     }
 
-    pub fn upsert_library_tracks(&mut self, tracks: Vec<Track>) -> usize {
+    pub fn upsert_library_tracks(&mut self, mut tracks: Vec<Track>) -> usize {
+        self.drop_hidden_tracks(&mut tracks);
         if tracks.is_empty() {
             return 0;
         }
@@ -229,6 +511,8 @@ impl TuneCore {
         let mut changed = 0usize;
         self.capture_library_update(|core| {
             for track in tracks {
+                core.transient_tracks
+                    .remove(&normalized_path_key(&track.path));
                 match core.track_index(&track.path) {
                     Some(idx) if core.tracks.get(idx) != Some(&track) => {
                         core.tracks[idx] = track;
@@ -245,6 +529,52 @@ impl TuneCore {
         changed
     }
 
+    /// Library tracks and the durations already cached for them, for a
+    /// duplicate scan that probes the rest in the background.
+    pub fn duplicate_scan_input(&self) -> (Vec<Track>, HashMap<PathBuf, Option<u32>>) {
+        let durations = self
+            .tracks
+            .iter()
+            .filter(|track| self.has_cached_duration_for_path(&track.path))
+            .map(|track| {
+                let duration = self.cached_duration_seconds_for_path(&track.path);
+                (track.path.clone(), duration)
+            })
+            .collect();
+        (self.tracks.clone(), durations)
+    }
+
+    fn drop_hidden_tracks(&self, tracks: &mut Vec<Track>) {
+        if self.hidden_tracks.is_empty() {
+            return;
+        }
+        let hidden: HashSet<&Path> = self.hidden_tracks.iter().map(PathBuf::as_path).collect();
+        tracks.retain(|track| !hidden.contains(track.path.as_path()));
+    }
+
+    pub fn remove_library_tracks(&mut self, paths: &[PathBuf]) -> usize {
+        let before = self.tracks.len();
+        let removed: HashSet<&Path> = paths.iter().map(PathBuf::as_path).collect();
+        self.capture_library_update(|core| {
+            core.tracks
+                .retain(|track| !removed.contains(track.path.as_path()));
+        });
+        before.saturating_sub(self.tracks.len())
+    }
+
+    /// Removes tracks from the library and keeps later scans from adding them
+    /// back. The files stay on disk.
+    pub fn hide_library_tracks(&mut self, paths: &[PathBuf]) -> usize {
+        let mut hidden: HashSet<PathBuf> = self.hidden_tracks.iter().cloned().collect();
+        for path in paths {
+            if hidden.insert(path.clone()) {
+                self.hidden_tracks.push(path.clone());
+            }
+        }
+        self.dirty = true;
+        self.remove_library_tracks(paths)
+    }
+
     pub fn remove_tracks_in_folder(&mut self, root: &Path) -> usize {
         let root = config::normalize_path(root);
         let before = self.tracks.len();
@@ -276,7 +606,7 @@ impl TuneCore {
         }
 
         let normalized = config::normalize_path(&resolved);
-        if self.folders.iter().any(|folder| folder == &normalized) {
+        if self.folders.iter().any(|folder| folder.path == normalized) {
             return Err("Folder already added");
         }
 
@@ -284,12 +614,12 @@ impl TuneCore {
     }
 
     pub fn insert_folder_reference(&mut self, folder: PathBuf) {
-        if self.folders.iter().any(|existing| existing == &folder) {
+        if self.folders.iter().any(|existing| existing.path == folder) {
             return;
         }
-        self.folders.push(folder);
+        self.folders.push(LibraryFolder::new(folder));
         self.folders
-            .sort_by_cached_key(|path| path.to_string_lossy().to_ascii_lowercase());
+            .sort_by_cached_key(|folder| folder.path.to_string_lossy().to_ascii_lowercase());
         self.refresh_browser_entries();
         self.dirty = true;
     }
@@ -321,15 +651,16 @@ impl TuneCore {
             .find(|folder| {
                 candidates
                     .iter()
-                    .any(|candidate| path_eq(folder, candidate))
+                    .any(|candidate| path_eq(&folder.path, candidate))
             })
-            .cloned();
+            .map(|folder| folder.path.clone());
         let Some(removed) = removed else {
             self.set_status("Folder not found");
             return None;
         };
 
-        self.folders.retain(|folder| !path_eq(folder, &removed));
+        self.folders
+            .retain(|folder| !path_eq(&folder.path, &removed));
         self.browser_path = None;
         self.browser_all_songs = false;
         self.selected_browser = 0;
@@ -342,15 +673,37 @@ impl TuneCore {
         PersistedState {
             folders: self.folders.clone(),
             playlists: self.playlists.clone(),
+            smart_playlists: self.smart_playlists.clone(),
             shuffle_enabled: self.shuffle_enabled,
+            shuffle_mode: self.shuffle_mode,
+            shuffle_recent_percent: self.shuffle_recent_percent,
             repeat_mode: self.repeat_mode,
             playback_mode: None,
             loudness_normalization: self.loudness_normalization,
+            prevent_clipping: self.prevent_clipping,
+            equalizer_bands: self.equalizer_bands.clone(),
+            gapless: self.gapless,
+            album_gain: self.album_gain,
+            playback_speed: self.playback_speed,
+            preserve_pitch: self.preserve_pitch,
+            mono_output: self.mono_output,
+            balance: self.balance,
             crossfade_seconds: self.crossfade_seconds,
+            crossfade_curve: self.crossfade_curve,
             scrub_seconds: self.scrub_seconds,
+            scrub_back_key: self.scrub_back_key,
+            scrub_forward_key: self.scrub_forward_key,
+            arrow_keys_seek: self.arrow_keys_seek,
+            track_gap_seconds: self.track_gap_seconds,
+            daily_listen_goal_minutes: self.daily_listen_goal_minutes,
+            crossfade_lead_ms: self.crossfade_lead_ms,
+            stats_grace_seconds: self.stats_grace_seconds,
+            stats_completion_percent: self.stats_completion_percent,
+            compact_layout: self.compact_layout,
             theme: self.theme,
             selected_output_device: None,
             saved_volume: 1.0,
+            saved_muted: false,
             stats_enabled: self.stats_enabled,
             online_sync_correction_threshold_ms: self.online_sync_correction_threshold_ms,
             stats_top_songs_count: self.stats_top_songs_count,
@@ -360,6 +713,26 @@ impl TuneCore {
             } else {
                 Some(self.online_nickname.clone())
             },
+            last_online_join: self.last_online_join.clone().map(|mut join| {
+                if !self.remember_online_password {
+                    join.password = None;
+                }
+                join
+            }),
+            remember_online_password: self.remember_online_password,
+            no_crossfade_tracks: self.no_crossfade_tracks.clone(),
+            end_of_queue_behavior: self.end_of_queue_behavior,
+            keep_going_pool: self.keep_going_pool,
+            folder_enter_behavior: self.folder_enter_behavior,
+            crossfade_only_when_shuffling: self.crossfade_only_when_shuffling,
+            sleep_on_silence_minutes: self.sleep_on_silence_minutes,
+            window_title_shows_track: self.window_title_shows_track,
+            backup_before_tag_writes: self.backup_before_tag_writes,
+            tag_backup_dir: self.tag_backup_dir.clone(),
+            scan_ignore_patterns: self.scan_ignore_patterns.clone(),
+            hidden_tracks: self.hidden_tracks.clone(),
+            now_playing_template: self.now_playing_template.clone(),
+            listenbrainz_token: self.listenbrainz_token.clone(),
         }
     }
 
@@ -369,15 +742,29 @@ impl TuneCore {
         Ok(())
     }
 
+    /// True for a track queued with Play path that is not part of the library.
+    pub fn is_transient_track(&self, path: &Path) -> bool {
+        !self.transient_tracks.is_empty()
+            && self.transient_tracks.contains(&normalized_path_key(path))
+    }
+
+    fn library_track_count(&self) -> usize {
+        self.tracks
+            .len()
+            .saturating_sub(self.transient_tracks.len())
+    }
+
     fn invalidate_library_caches(&self) {
         *self.sorted_library_queue_cache.borrow_mut() = None;
+        self.smart_playlist_cache.borrow_mut().clear();
+        self.extra_tags_lookup.borrow_mut().clear();
     }
 
     pub fn add_folder(&mut self, input: &Path) {
         match self.resolve_folder_for_addition(input) {
             Ok(normalized) => {
                 self.insert_folder_reference(normalized.clone());
-                let mut found = library::scan_folder(&normalized);
+                let mut found = library::scan_folder(&normalized, &self.scan_ignore_patterns);
                 let count = found.len();
                 self.upsert_library_tracks(std::mem::take(&mut found));
                 self.set_status(&format!("Added folder with {count} tracks"));
@@ -394,13 +781,95 @@ impl TuneCore {
         self.set_status("Folder removed");
     }
 
+    pub fn enabled_folder_paths(&self) -> Vec<PathBuf> {
+        crate::model::enabled_folder_paths(&self.folders)
+    }
+
+    /// Flips a library root on or off and returns the new state. Disabled roots stay
+    /// in the list but are skipped by rescans and the root browser.
+    pub fn toggle_folder_enabled(&mut self, root: &Path) -> Option<bool> {
+        let folder = self
+            .folders
+            .iter_mut()
+            .find(|folder| path_eq(&folder.path, root))?;
+        folder.enabled = !folder.enabled;
+        let enabled = folder.enabled;
+        if !enabled {
+            self.remove_tracks_in_folder(root);
+            if self
+                .browser_path
+                .as_deref()
+                .is_some_and(|current| path_is_within(current, root))
+            {
+                self.browser_path = None;
+                self.selected_browser = 0;
+            }
+        }
+        self.refresh_browser_entries();
+        self.set_status(if enabled {
+            "Folder enabled"
+        } else {
+            "Folder disabled"
+        });
+        Some(enabled)
+    }
+
     pub fn rescan(&mut self) {
-        self.replace_library_tracks(library::scan_many(&self.folders));
-        self.set_status("Library rescanned");
+        self.replace_library_tracks(library::scan_many(
+            &self.enabled_folder_paths(),
+            &self.scan_ignore_patterns,
+        ));
+        self.set_status(&self.library_summary(None));
+    }
+
+    /// One-line collection summary shown after a scan. Play time only counts
+    /// durations that are already cached, so it can undercount on a cold cache.
+    pub fn library_summary(&self, total_bytes: Option<u64>) -> String {
+        let folders = self.enabled_folder_paths().len();
+        let mut details = Vec::with_capacity(2);
+        if let Some(bytes) = total_bytes.filter(|bytes| *bytes > 0) {
+            details.push(format_library_bytes(bytes));
+        }
+        let lookup = self.duration_lookup.borrow();
+        let mut known_seconds = 0u64;
+        let mut known_tracks = 0usize;
+        for track in &self.tracks {
+            let key = normalized_path_key(&track.path);
+            if self.transient_tracks.contains(&key) {
+                continue;
+            }
+            if let Some(Some(seconds)) = lookup.get(&key) {
+                known_seconds += u64::from(*seconds);
+                known_tracks += 1;
+            }
+        }
+        let track_count = self.library_track_count();
+        if known_tracks > 0 {
+            let prefix = if known_tracks < track_count {
+                "at least "
+            } else {
+                ""
+            };
+            details.push(format!(
+                "{prefix}{} total",
+                format_library_play_time(known_seconds)
+            ));
+        }
+
+        let mut summary = format!(
+            "Indexed {} track{} across {folders} folder{}",
+            format_thousands(track_count as u64),
+            if track_count == 1 { "" } else { "s" },
+            if folders == 1 { "" } else { "s" }
+        );
+        if !details.is_empty() {
+            summary.push_str(&format!(" ({})", details.join(", ")));
+        }
+        summary
     }
 
     pub fn create_playlist(&mut self, name: &str) {
-        if self.playlists.contains_key(name) {
+        if self.playlist_name_taken(name) {
             self.set_status("Playlist already exists");
             return;
         }
@@ -411,11 +880,12 @@ impl TuneCore {
     }
 
     pub fn remove_playlist(&mut self, name: &str) {
-        if self.playlists.remove(name).is_none() {
+        if self.playlists.remove(name).is_none() && self.smart_playlists.remove(name).is_none() {
             self.set_status("Playlist not found");
             return;
         }
 
+        self.smart_playlist_cache.borrow_mut().remove(name);
         if self.browser_playlist.as_deref() == Some(name) {
             self.browser_playlist = None;
             self.selected_browser = 0;
@@ -426,6 +896,211 @@ impl TuneCore {
         self.set_status("Playlist removed");
     }
 
+    pub fn create_smart_playlist(&mut self, name: &str, rule: SmartPlaylist) {
+        if self.playlist_name_taken(name) {
+            self.set_status("Playlist already exists");
+            return;
+        }
+
+        self.smart_playlists.insert(name.to_string(), rule);
+        self.refresh_browser_entries();
+        self.set_status("Smart playlist created");
+    }
+
+    fn playlist_name_taken(&self, name: &str) -> bool {
+        self.playlists.contains_key(name) || self.smart_playlists.contains_key(name)
+    }
+
+    pub fn is_smart_playlist(&self, name: &str) -> bool {
+        self.smart_playlists.contains_key(name)
+    }
+
+    /// Tracks of a manual playlist, or a smart playlist's current picks.
+    /// Takes the date-added times from the library index for `Date added`
+    /// smart playlists, so ranking never touches the files.
+    pub fn set_track_added_times(&mut self, index: &library::LibraryIndex) {
+        self.track_added_at = index
+            .tracks
+            .iter()
+            .map(|entry| (normalized_path_key(&entry.path), entry.added_unix_seconds()))
+            .collect();
+        self.smart_playlist_cache.borrow_mut().clear();
+    }
+
+    pub fn playlist_tracks(&self, name: &str) -> Option<Vec<PathBuf>> {
+        if let Some(playlist) = self.playlists.get(name) {
+            return Some(playlist.tracks.clone());
+        }
+        let rule = self.smart_playlists.get(name)?;
+        if let Some(cached) = self.smart_playlist_cache.borrow().get(name) {
+            return Some(cached.clone());
+        }
+        let tracks = self.evaluate_smart_playlist(rule);
+        self.smart_playlist_cache
+            .borrow_mut()
+            .insert(name.to_string(), tracks.clone());
+        Some(tracks)
+    }
+
+    /// Ranks the library by the rule's sort, using the history from
+    /// `set_listen_history`. Genre tags are read lazily, only for tracks that
+    /// could still make the limit.
+    fn evaluate_smart_playlist(&self, rule: &SmartPlaylist) -> Vec<PathBuf> {
+        let artist = smart_rule_filter(rule.artist.as_deref());
+        let genre = smart_rule_filter(rule.genre.as_deref());
+        let mut ranked = Vec::new();
+        for track in &self.tracks {
+            if let Some(artist) = artist.as_deref()
+                && !track
+                    .artist
+                    .as_deref()
+                    .is_some_and(|value| value.to_lowercase().contains(artist))
+            {
+                continue;
+            }
+            let key = normalized_path_key(&track.path);
+            let last_played = self.last_played_at.get(&key).copied();
+            let rank = match rule.sort {
+                SmartPlaylistSort::MostPlayed => {
+                    let Some(count) = self.play_counts.get(&key) else {
+                        continue;
+                    };
+                    (i64::from(*count), last_played.unwrap_or(i64::MIN))
+                }
+                SmartPlaylistSort::LastPlayed => {
+                    let Some(at) = last_played else {
+                        continue;
+                    };
+                    (at, 0)
+                }
+                SmartPlaylistSort::DateAdded => (
+                    self.track_added_at
+                        .get(&key)
+                        .map_or(0, |added| i64::try_from(*added).unwrap_or(i64::MAX)),
+                    0,
+                ),
+            };
+            ranked.push((rank, &track.path));
+        }
+        ranked.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+        ranked
+            .into_iter()
+            .filter(|(_, path)| {
+                genre
+                    .as_deref()
+                    .is_none_or(|genre| self.genre_matches(path, genre))
+            })
+            .take(rule.limit)
+            .map(|(_, path)| path.clone())
+            .collect()
+    }
+
+    fn genre_matches(&self, path: &Path, wanted: &str) -> bool {
+        self.extra_tags_for_path(path)
+            .genre
+            .is_some_and(|genre| genre.to_lowercase().contains(wanted))
+    }
+
+    fn extra_tags_for_path(&self, path: &Path) -> library::ExtraTags {
+        let key = normalized_path_key(path);
+        if let Some(cached) = self.extra_tags_lookup.borrow().get(&key) {
+            return cached.clone();
+        }
+        let tags = library::extra_tags_for_path(path);
+        self.extra_tags_lookup
+            .borrow_mut()
+            .insert(key, tags.clone());
+        tags
+    }
+
+    /// Tags probed so far; `None` while a track is still waiting for the worker.
+    fn cached_extra_tags(&self, path: &Path) -> Option<library::ExtraTags> {
+        let cached = self
+            .extra_tags_lookup
+            .borrow()
+            .get(&normalized_path_key(path))
+            .cloned();
+        if cached.is_none() {
+            self.extra_tags_probe_requested.set(true);
+        }
+        cached
+    }
+
+    /// Library tracks All Songs is waiting on, once per request.
+    pub fn take_extra_tags_probe_request(&self) -> Vec<PathBuf> {
+        if !self.extra_tags_probe_requested.replace(false) {
+            return Vec::new();
+        }
+        let lookup = self.extra_tags_lookup.borrow();
+        self.tracks
+            .iter()
+            .filter(|track| !lookup.contains_key(&normalized_path_key(&track.path)))
+            .map(|track| track.path.clone())
+            .collect()
+    }
+
+    pub fn cache_extra_tags(&mut self, probed: Vec<(PathBuf, library::ExtraTags)>) {
+        {
+            let mut lookup = self.extra_tags_lookup.borrow_mut();
+            for (path, tags) in probed {
+                lookup.insert(normalized_path_key(&path), tags);
+            }
+        }
+        if self.browser_all_songs && self.all_songs_order != AllSongsOrder::Title {
+            self.refresh_browser_entries();
+        }
+    }
+
+    pub fn cycle_all_songs_order(&mut self) {
+        self.all_songs_order = self.all_songs_order.next();
+        self.refresh_browser_entries();
+        self.set_status(&format!("All Songs {}", self.all_songs_order.label()));
+    }
+
+    /// Library order for the All Songs view. Year and genre orders keep title
+    /// order within a year or genre and put tracks without the tag, or not
+    /// probed yet, last.
+    fn all_songs_queue(&self) -> Vec<usize> {
+        let mut queue = self.metadata_sorted_library_queue();
+        match self.all_songs_order {
+            AllSongsOrder::Title => {}
+            AllSongsOrder::Year => queue.sort_by_cached_key(|idx| {
+                let year = self
+                    .cached_extra_tags(&self.tracks[*idx].path)
+                    .and_then(|tags| tags.year);
+                (year.is_none(), year)
+            }),
+            AllSongsOrder::Genre => queue.sort_by_cached_key(|idx| {
+                let genre = self
+                    .cached_extra_tags(&self.tracks[*idx].path)
+                    .and_then(|tags| tags.genre);
+                (genre.is_none(), genre.map(|genre| genre.to_lowercase()))
+            }),
+        }
+        queue
+    }
+
+    fn all_songs_label(&self, track: &Track) -> String {
+        let title = config::sanitize_display_text(&track.title);
+        if self.all_songs_order == AllSongsOrder::Title {
+            return title;
+        }
+        let Some(tags) = self.cached_extra_tags(&track.path) else {
+            return format!("[----] {title}");
+        };
+        match self.all_songs_order {
+            AllSongsOrder::Title => title,
+            AllSongsOrder::Year => match tags.year {
+                Some(year) => format!("[{year}] {title}"),
+                None => format!("[----] {title}"),
+            },
+            AllSongsOrder::Genre => match tags.genre {
+                Some(genre) => format!("[{}] {title}", config::sanitize_display_text(&genre)),
+                None => format!("[No genre] {title}"),
+            },
+        }
+    }
+
     pub fn add_selected_to_playlist(&mut self, name: &str) {
         let paths = self.selected_paths_for_playlist_action();
         self.add_paths_to_playlist(name, paths);
@@ -465,6 +1140,10 @@ impl TuneCore {
             return;
         }
 
+        if self.is_smart_playlist(&name) {
+            self.set_status("Smart playlists follow their rules; tracks cannot be removed");
+            return;
+        }
         let Some(playlist) = self.playlists.get_mut(&name) else {
             self.set_status("Playlist not found");
             return;
@@ -483,18 +1162,179 @@ impl TuneCore {
         }
     }
 
+    pub fn open_folder(&mut self, folder: PathBuf) {
+        self.library_search_query.clear();
+        self.library_search_focused = false;
+        self.browser_playlist = None;
+        self.browser_all_songs = false;
+        self.browser_local_queue = false;
+        self.browser_shared_queue = false;
+        self.browser_path = Some(folder);
+        self.selected_browser = 0;
+        self.refresh_browser_entries();
+        self.set_status("Opened folder");
+    }
+
+    /// Jumps playback to the 1-based `position` in the active queue, whatever the
+    /// Library is showing. Returns the track to play.
+    pub fn jump_to_queue_position(&mut self, position: usize) -> Option<PathBuf> {
+        let len = self.queue.len();
+        if len == 0 {
+            self.set_status("Queue is empty");
+            return None;
+        }
+        if position == 0 || position > len {
+            self.set_status(&format!("Queue position must be 1-{len}"));
+            return None;
+        }
+        let path = self
+            .queue
+            .get(position - 1)
+            .and_then(|idx| self.tracks.get(*idx))
+            .map(|track| track.path.clone())?;
+        self.current_queue_index = Some(position - 1);
+        self.pin_track(Some(path.clone()));
+        self.set_status(&format!("Jumped to queue position {position}/{len}"));
+        Some(path)
+    }
+
+    /// Library track indices whose title, artist, album or file name contain the
+    /// query's letters in order, best match first. Ties keep the title order of
+    /// the All Songs list.
+    pub fn fuzzy_find_tracks(&self, query: &str, limit: usize) -> Vec<usize> {
+        let needle: Vec<char> = query
+            .chars()
+            .filter(|ch| !ch.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect();
+        if needle.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<(i64, usize)> = self
+            .metadata_sorted_library_queue()
+            .into_iter()
+            .filter_map(|idx| {
+                let track = &self.tracks[idx];
+                let stem = track
+                    .path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy())
+                    .unwrap_or_default();
+                let haystack = format!(
+                    "{} {} {} {stem}",
+                    track.title,
+                    track.artist.as_deref().unwrap_or_default(),
+                    track.album.as_deref().unwrap_or_default(),
+                );
+                fuzzy_match_score(&needle, &haystack).map(|score| (score, idx))
+            })
+            .collect();
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        matches.truncate(limit);
+        matches.into_iter().map(|(_, idx)| idx).collect()
+    }
+
+    /// Plays one library track with the whole library, in All Songs order, as
+    /// the queue so next and previous carry on from it.
+    pub fn play_library_track(&mut self, track_idx: usize) -> Option<PathBuf> {
+        let track = self.tracks.get(track_idx)?;
+        let path = track.path.clone();
+        let title = track.title.clone();
+        self.queue = self.metadata_sorted_library_queue();
+        self.current_queue_index = self.queue.iter().position(|idx| *idx == track_idx);
+        self.pin_track(Some(path.clone()));
+        self.rebuild_shuffle_order();
+        if self.browser_local_queue {
+            self.refresh_browser_entries();
+        }
+        self.set_status(&format!("Playing {title}"));
+        Some(path)
+    }
+
+    pub fn queue_library_track(&mut self, track_idx: usize) {
+        let Some(title) = self.tracks.get(track_idx).map(|track| track.title.clone()) else {
+            return;
+        };
+        self.queue.push(track_idx);
+        self.rebuild_shuffle_order();
+        if self.browser_local_queue {
+            self.refresh_browser_entries();
+        }
+        self.set_status(&format!("Queued {title}"));
+    }
+
+    /// Loads every track under `folder` as the queue, in path order so subfolders
+    /// and numbered file names play as laid out on disk, and returns the first.
+    pub fn play_folder(&mut self, folder: &Path) -> Option<PathBuf> {
+        let mut queue: Vec<usize> = (0..self.tracks.len())
+            .filter(|idx| path_is_within(&self.tracks[*idx].path, folder))
+            .collect();
+        queue.sort_by(|a, b| self.tracks[*a].path.cmp(&self.tracks[*b].path));
+        let Some(first) = queue.first().and_then(|idx| self.tracks.get(*idx)) else {
+            self.set_status("No tracks in folder");
+            return None;
+        };
+        let path = first.path.clone();
+        self.queue = queue;
+        self.rebuild_shuffle_order();
+        self.current_queue_index = Some(0);
+        self.pin_track(Some(path.clone()));
+        let name = folder
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| folder.display().to_string());
+        self.set_status(&format!("Playing folder: {name}"));
+        Some(path)
+    }
+
+    /// Replaces the queue with `tracks`, which need not be in the library, and
+    /// returns the first one to play. Tracks from outside the library are kept
+    /// only for this queue and replace those of the previous Play path.
+    pub fn play_transient_tracks(&mut self, tracks: Vec<Track>, label: &str) -> Option<PathBuf> {
+        let first = tracks.first()?.path.clone();
+        let count = tracks.len();
+        let previous = std::mem::take(&mut self.transient_tracks);
+        if !previous.is_empty() {
+            self.tracks
+                .retain(|track| !previous.contains(&normalized_path_key(&track.path)));
+            self.track_lookup = build_track_lookup(&self.tracks);
+        }
+        let mut queue = Vec::with_capacity(count);
+        for track in tracks {
+            let idx = match self.track_index(&track.path) {
+                Some(idx) => idx,
+                None => {
+                    self.transient_tracks
+                        .insert(normalized_path_key(&track.path));
+                    self.tracks.push(track);
+                    self.tracks.len() - 1
+                }
+            };
+            queue.push(idx);
+        }
+        self.track_lookup = build_track_lookup(&self.tracks);
+        self.invalidate_library_caches();
+        self.queue = queue;
+        self.rebuild_shuffle_order();
+        self.current_queue_index = Some(0);
+        self.pin_track(Some(first.clone()));
+        self.dirty = true;
+        if count == 1 {
+            self.set_status(&format!("Playing {label}"));
+        } else {
+            self.set_status(&format!("Playing {label} ({count} tracks)"));
+        }
+        Some(first)
+    }
+
     pub fn load_playlist_queue(&mut self, name: &str) {
-        let Some(tracks) = self
-            .playlists
-            .get(name)
-            .map(|playlist| playlist.tracks.clone())
-        else {
+        let Some(tracks) = self.playlist_tracks(name) else {
             self.set_status("Playlist not found");
             return;
         };
 
         self.queue = self.queue_from_paths(&tracks);
-        self.current_queue_index = None;
+        self.current_queue_index = self.pinned_queue_index();
         self.rebuild_shuffle_order();
         self.set_status(&format!("Loaded playlist: {name}"));
         self.dirty = true;
@@ -502,7 +1342,7 @@ impl TuneCore {
 
     pub fn reset_main_queue(&mut self) {
         self.rebuild_main_queue();
-        self.current_queue_index = None;
+        self.current_queue_index = self.pinned_queue_index();
         self.set_status("Loaded main library queue");
     }
 
@@ -520,6 +1360,13 @@ impl TuneCore {
     }
 
     pub fn activate_selected(&mut self) -> Option<PathBuf> {
+        self.activate_selected_with(false)
+    }
+
+    /// Like `activate_selected`, but `alternate` (Shift+Enter) flips what Enter
+    /// does on a folder. With `FolderEnterBehavior::Ask` the caller is expected
+    /// to prompt first; unprompted, Enter opens and the alternate plays.
+    pub fn activate_selected_with(&mut self, alternate: bool) -> Option<PathBuf> {
         let Some(entry) = self.browser_entries.get(self.selected_browser).cloned() else {
             self.set_status("Nothing selected");
             return None;
@@ -535,22 +1382,24 @@ impl TuneCore {
                 None
             }
             BrowserEntryKind::Folder => {
-                self.browser_playlist = None;
-                self.browser_all_songs = false;
-                self.browser_local_queue = false;
-                self.browser_shared_queue = false;
-                self.browser_path = Some(entry.path);
-                self.selected_browser = 0;
-                self.refresh_browser_entries();
-                self.set_status("Opened folder");
+                let play = match self.folder_enter_behavior {
+                    FolderEnterBehavior::Play => !alternate,
+                    FolderEnterBehavior::Open | FolderEnterBehavior::Ask => alternate,
+                };
+                if play {
+                    return self.play_folder(&entry.path);
+                }
+                self.open_folder(entry.path);
                 None
             }
             BrowserEntryKind::Playlist => {
+                let name = entry.path.to_string_lossy().to_string();
+                self.smart_playlist_cache.borrow_mut().remove(&name);
                 self.browser_path = None;
                 self.browser_all_songs = false;
                 self.browser_local_queue = false;
                 self.browser_shared_queue = false;
-                self.browser_playlist = Some(entry.path.to_string_lossy().to_string());
+                self.browser_playlist = Some(name);
                 self.selected_browser = 0;
                 self.refresh_browser_entries();
                 self.set_status("Opened playlist");
@@ -597,24 +1446,19 @@ impl TuneCore {
                 if !self.library_search_query.is_empty() {
                     self.queue = self.queue_from_paths(&self.browser_track_paths());
                 } else if let Some(name) = &self.browser_playlist {
-                    if let Some(tracks) = self
-                        .playlists
-                        .get(name)
-                        .map(|playlist| playlist.tracks.clone())
-                    {
+                    if let Some(tracks) = self.playlist_tracks(name) {
                         self.queue = self.queue_from_paths(&tracks);
                     } else {
                         self.queue.clear();
                     }
                 } else if self.browser_all_songs {
-                    self.queue = self.metadata_sorted_library_queue();
+                    self.queue = self.all_songs_queue();
                 } else if self.browser_path.is_some() {
                     let tracks = self.browser_track_paths();
                     self.queue = self.queue_from_paths(&tracks);
                 } else {
                     self.queue = self.metadata_sorted_library_queue();
                 }
-                self.rebuild_shuffle_order();
                 self.current_queue_index = if !self.library_search_query.is_empty()
                     || self.browser_playlist.is_some()
                     || self.browser_all_songs
@@ -627,6 +1471,8 @@ impl TuneCore {
                         .position(|track_idx| path_eq(&self.tracks[*track_idx].path, &entry.path))
                 };
                 self.set_status("Playing selected track");
+                self.pin_track(Some(entry.path.clone()));
+                self.rebuild_shuffle_order();
                 Some(entry.path)
             }
         }
@@ -672,6 +1518,87 @@ impl TuneCore {
         queue_occurrence == entry_occurrence
     }
 
+    fn browser_folder_root(&self, current: &Path) -> Option<PathBuf> {
+        self.folders
+            .iter()
+            .map(|folder| &folder.path)
+            .filter(|root| path_is_within(current, root))
+            .max_by_key(|root| root.components().count())
+            .cloned()
+    }
+
+    /// Labels for each level of the current Library location, starting with
+    /// "Library" itself. Folder views list the root folder and each subfolder.
+    pub fn browser_breadcrumbs(&self) -> Vec<String> {
+        let mut crumbs = vec![String::from("Library")];
+        if let Some(name) = &self.browser_playlist {
+            if self.is_smart_playlist(name) {
+                crumbs.push(format!("Smart playlist: {name}"));
+            } else {
+                crumbs.push(format!("Playlist: {name}"));
+            }
+        } else if self.browser_all_songs {
+            crumbs.push(String::from("All Songs"));
+        } else if self.browser_local_queue {
+            crumbs.push(String::from("Local Queue"));
+        } else if self.browser_shared_queue {
+            crumbs.push(String::from("Shared Queue"));
+        } else if let Some(current) = &self.browser_path {
+            let Some(root) = self.browser_folder_root(current) else {
+                crumbs.push(current.display().to_string());
+                return crumbs;
+            };
+            crumbs.push(
+                root.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| root.display().to_string()),
+            );
+            let root_depth = config::normalize_path(&root).components().count();
+            crumbs.extend(
+                config::normalize_path(current)
+                    .components()
+                    .skip(root_depth)
+                    .map(|part| part.as_os_str().to_string_lossy().into_owned()),
+            );
+        }
+        crumbs
+    }
+
+    /// Jumps to the breadcrumb at `depth` (0 is the Library root), popping as
+    /// many levels as needed in one step.
+    pub fn navigate_to_breadcrumb(&mut self, depth: usize) {
+        let crumbs = self.browser_breadcrumbs();
+        if depth + 1 >= crumbs.len() {
+            return;
+        }
+
+        self.library_search_query.clear();
+        self.library_search_focused = false;
+        if depth == 0 {
+            self.browser_path = None;
+            self.browser_playlist = None;
+            self.browser_all_songs = false;
+            self.browser_local_queue = false;
+            self.browser_shared_queue = false;
+        } else if let Some(current) = self.browser_path.clone()
+            && let Some(root) = self.browser_folder_root(&current)
+        {
+            let root_depth = config::normalize_path(&root).components().count();
+            let mut target = root;
+            for part in config::normalize_path(&current)
+                .components()
+                .skip(root_depth)
+                .take(depth - 1)
+            {
+                target.push(part);
+            }
+            self.browser_path = Some(target);
+        }
+        self.selected_browser = 0;
+        self.refresh_browser_entries();
+        self.set_status(&format!("Jumped to {}", crumbs[depth]));
+    }
+
     pub fn navigate_back(&mut self) {
         self.library_search_query.clear();
         self.library_search_focused = false;
@@ -712,6 +1639,7 @@ impl TuneCore {
                 if let Some(root) = self
                     .folders
                     .iter()
+                    .map(|folder| &folder.path)
                     .filter(|root| path_is_within(current, root))
                     .max_by_key(|root| root.components().count())
                 {
@@ -751,8 +1679,42 @@ impl TuneCore {
         ));
     }
 
-    pub fn cycle_repeat_mode(&mut self) {
-        self.set_repeat_mode(self.repeat_mode.next());
+    pub fn cycle_shuffle_mode(&mut self) {
+        self.shuffle_mode = self.shuffle_mode.next();
+        if self.shuffle_enabled {
+            self.rebuild_shuffle_order();
+        }
+        self.set_status(&format!("Shuffle mode: {}", self.shuffle_mode.label()));
+    }
+
+    pub fn cycle_shuffle_recent_percent(&mut self) {
+        let next = SHUFFLE_RECENT_OPTIONS_PERCENT
+            .iter()
+            .copied()
+            .find(|percent| *percent > self.shuffle_recent_percent)
+            .unwrap_or(SHUFFLE_RECENT_OPTIONS_PERCENT[0]);
+        self.shuffle_recent_percent = next;
+        if self.shuffle_enabled {
+            self.rebuild_shuffle_order();
+        }
+        self.set_status(&format!("Shuffle avoids the last {next}% of the queue"));
+    }
+
+    /// Refreshes the play counts `ShuffleMode::FewerPlays` weighs against, from
+    /// the same listen log Radio uses.
+    pub fn set_listen_history(&mut self, history: &[ListenEvent]) {
+        self.play_counts.clear();
+        self.last_played_at.clear();
+        for event in history.iter().filter(|event| event.counted_play) {
+            let key = normalized_path_key(&event.track_path);
+            let last = self.last_played_at.entry(key.clone()).or_insert(i64::MIN);
+            *last = (*last).max(event.started_at_epoch_seconds);
+            *self.play_counts.entry(key).or_default() += 1;
+        }
+    }
+
+    pub fn cycle_repeat_mode(&mut self) {
+        self.set_repeat_mode(self.repeat_mode.next());
     }
 
     pub fn set_repeat_mode(&mut self, mode: RepeatMode) {
@@ -760,7 +1722,330 @@ impl TuneCore {
         self.set_status(&format!("Repeat: {}", self.repeat_mode.label()));
     }
 
+    pub fn cycle_end_of_queue_behavior(&mut self) {
+        self.end_of_queue_behavior = self.end_of_queue_behavior.next();
+        self.set_status(&format!(
+            "End of queue: {}",
+            self.end_of_queue_behavior.label()
+        ));
+    }
+
+    pub fn cycle_crossfade_curve(&mut self) {
+        self.crossfade_curve = self.crossfade_curve.next();
+        self.set_status(&format!(
+            "Crossfade curve: {}",
+            self.crossfade_curve.label()
+        ));
+    }
+
+    pub fn cycle_keep_going_pool(&mut self) {
+        self.keep_going_pool = self.keep_going_pool.next();
+        self.set_status(&format!(
+            "Keep going from: {}",
+            self.keep_going_pool.label()
+        ));
+    }
+
+    pub fn cycle_folder_enter_behavior(&mut self) {
+        self.folder_enter_behavior = self.folder_enter_behavior.next();
+        self.set_status(&format!(
+            "Enter on folder: {}",
+            self.folder_enter_behavior.label()
+        ));
+    }
+
+    pub fn set_sleep_timer(&mut self, minutes: u16, now: Instant) {
+        self.sleep_deadline = Some(now + Duration::from_secs(u64::from(minutes) * 60));
+        self.sleep_after_current_track = false;
+        self.set_status(&format!("Sleep timer: pausing in {minutes} min"));
+    }
+
+    pub fn set_sleep_after_current_track(&mut self) {
+        self.sleep_deadline = None;
+        self.sleep_after_current_track = true;
+        self.set_status("Sleep timer: stopping at the end of this track");
+    }
+
+    pub fn cancel_sleep_timer(&mut self) {
+        self.sleep_deadline = None;
+        self.sleep_after_current_track = false;
+        self.set_status("Sleep timer off");
+    }
+
+    pub fn toggle_crossfade_only_when_shuffling(&mut self) {
+        self.crossfade_only_when_shuffling = !self.crossfade_only_when_shuffling;
+        self.set_status(&format!(
+            "Crossfade only when shuffling: {}",
+            if self.crossfade_only_when_shuffling {
+                "On"
+            } else {
+                "Off"
+            }
+        ));
+    }
+
+    pub fn cycle_sleep_on_silence_minutes(&mut self) {
+        self.sleep_on_silence_minutes = match self.sleep_on_silence_minutes {
+            0 => 5,
+            5 => 10,
+            10 => 15,
+            15 => 30,
+            30 => 60,
+            _ => 0,
+        };
+        self.set_status(&format!(
+            "Sleep on silence: {}",
+            sleep_on_silence_label(self.sleep_on_silence_minutes)
+        ));
+    }
+
+    pub fn toggle_remember_online_password(&mut self) {
+        self.remember_online_password = !self.remember_online_password;
+        self.set_status(&format!(
+            "Remember room password: {}",
+            if self.remember_online_password {
+                "On"
+            } else {
+                "Off"
+            }
+        ));
+    }
+
+    pub fn toggle_window_title_shows_track(&mut self) {
+        self.window_title_shows_track = !self.window_title_shows_track;
+        self.set_status(&format!(
+            "Window title shows track: {}",
+            if self.window_title_shows_track {
+                "On"
+            } else {
+                "Off"
+            }
+        ));
+    }
+
+    /// Steps the gap through 0-5s. A gap turns crossfade off, since the two
+    /// transitions cannot both apply.
+    pub fn cycle_track_gap_seconds(&mut self) {
+        self.track_gap_seconds = if self.track_gap_seconds >= MAX_TRACK_GAP_SECONDS {
+            0
+        } else {
+            self.track_gap_seconds + 1
+        };
+        self.pending_advance_at = None;
+        let mut status = format!(
+            "Gap between tracks: {}",
+            track_gap_label(self.track_gap_seconds)
+        );
+        if self.track_gap_seconds > 0 && self.crossfade_seconds > 0 {
+            self.crossfade_seconds = 0;
+            status.push_str(" (crossfade off)");
+        }
+        self.set_status(&status);
+    }
+
+    pub fn cycle_daily_listen_goal(&mut self) {
+        let index = DAILY_LISTEN_GOAL_OPTIONS
+            .iter()
+            .position(|minutes| *minutes == self.daily_listen_goal_minutes)
+            .map_or(0, |index| (index + 1) % DAILY_LISTEN_GOAL_OPTIONS.len());
+        self.daily_listen_goal_minutes = DAILY_LISTEN_GOAL_OPTIONS[index];
+        self.set_status(&format!(
+            "Daily listening goal: {}",
+            daily_listen_goal_label(self.daily_listen_goal_minutes)
+        ));
+    }
+
+    pub fn cycle_crossfade_lead(&mut self) {
+        let index = CROSSFADE_LEAD_OPTIONS_MS
+            .iter()
+            .position(|ms| *ms == self.crossfade_lead_ms)
+            .map_or(0, |index| (index + 1) % CROSSFADE_LEAD_OPTIONS_MS.len());
+        self.crossfade_lead_ms = CROSSFADE_LEAD_OPTIONS_MS[index];
+        self.set_status(&format!(
+            "Crossfade look-ahead: {}",
+            crossfade_lead_label(self.crossfade_lead_ms)
+        ));
+    }
+
+    /// Steps through the common speeds; `faster` picks the next higher one and
+    /// wraps past either end. A custom speed moves to its nearest neighbour.
+    pub fn cycle_playback_speed(&mut self, faster: bool) {
+        let speed = if faster {
+            PLAYBACK_SPEED_OPTIONS
+                .iter()
+                .copied()
+                .find(|option| *option > self.playback_speed + 0.001)
+                .unwrap_or(PLAYBACK_SPEED_OPTIONS[0])
+        } else {
+            PLAYBACK_SPEED_OPTIONS
+                .iter()
+                .rev()
+                .copied()
+                .find(|option| *option < self.playback_speed - 0.001)
+                .unwrap_or(PLAYBACK_SPEED_OPTIONS[PLAYBACK_SPEED_OPTIONS.len() - 1])
+        };
+        self.playback_speed = speed;
+        self.set_status(&format!(
+            "Playback speed: {}",
+            playback_speed_label(self.playback_speed)
+        ));
+    }
+
+    pub fn toggle_preserve_pitch(&mut self) {
+        self.preserve_pitch = !self.preserve_pitch;
+        self.set_status(&format!(
+            "Preserve pitch: {}",
+            if self.preserve_pitch { "On" } else { "Off" }
+        ));
+    }
+
+    pub fn toggle_mono_output(&mut self) {
+        self.mono_output = !self.mono_output;
+        self.set_status(&format!(
+            "Mono: {}",
+            if self.mono_output { "On" } else { "Off" }
+        ));
+    }
+
+    /// Moves the balance by `delta` in steps of 10%; a `delta` of 0.0 centers it.
+    pub fn shift_balance(&mut self, delta: f32) {
+        self.balance = if delta == 0.0 {
+            0.0
+        } else {
+            (crate::audio::clamp_balance(self.balance + delta) * 10.0).round() / 10.0
+        };
+        self.set_status(&format!("Balance: {}", balance_label(self.balance)));
+    }
+
+    /// Flips the layout the user currently sees and remembers it as their choice.
+    pub fn toggle_compact_layout(&mut self, currently_compact: bool) {
+        self.compact_layout = Some(!currently_compact);
+        self.set_status(&format!(
+            "Layout: {}",
+            compact_layout_label(self.compact_layout)
+        ));
+    }
+
+    pub fn cycle_compact_layout(&mut self) {
+        self.compact_layout = match self.compact_layout {
+            None => Some(true),
+            Some(true) => Some(false),
+            Some(false) => None,
+        };
+        self.set_status(&format!(
+            "Layout: {}",
+            compact_layout_label(self.compact_layout)
+        ));
+    }
+
+    pub fn cycle_stats_completion_percent(&mut self) {
+        let index = STATS_COMPLETION_OPTIONS_PERCENT
+            .iter()
+            .position(|percent| *percent == self.stats_completion_percent)
+            .map_or(0, |index| {
+                (index + 1) % STATS_COMPLETION_OPTIONS_PERCENT.len()
+            });
+        self.stats_completion_percent = STATS_COMPLETION_OPTIONS_PERCENT[index];
+        self.set_status(&format!(
+            "Counts as completed: {}",
+            stats_completion_label(self.stats_completion_percent)
+        ));
+    }
+
+    pub fn step_equalizer_band(&mut self, band: usize) {
+        let Some((frequency, gain)) = self.equalizer_bands.get_mut(band) else {
+            return;
+        };
+        *gain = if *gain + EQUALIZER_STEP_DB > EQUALIZER_MAX_GAIN_DB {
+            -EQUALIZER_MAX_GAIN_DB
+        } else {
+            *gain + EQUALIZER_STEP_DB
+        };
+        let label = equalizer_band_label(*frequency, *gain);
+        self.set_status(&format!("Equalizer {label}"));
+    }
+
+    pub fn reset_equalizer(&mut self) {
+        self.equalizer_bands = default_equalizer_bands();
+        self.set_status("Equalizer reset to flat");
+    }
+
+    pub fn cycle_stats_grace_seconds(&mut self) {
+        let index = STATS_GRACE_OPTIONS_SECONDS
+            .iter()
+            .position(|seconds| *seconds == self.stats_grace_seconds)
+            .map_or(0, |index| (index + 1) % STATS_GRACE_OPTIONS_SECONDS.len());
+        self.stats_grace_seconds = STATS_GRACE_OPTIONS_SECONDS[index];
+        self.set_status(&format!(
+            "Stats grace period: {}",
+            stats_grace_label(self.stats_grace_seconds)
+        ));
+    }
+
+    pub fn toggle_arrow_keys_seek(&mut self) {
+        self.arrow_keys_seek = !self.arrow_keys_seek;
+        self.set_status(&format!(
+            "Arrow keys: {}",
+            arrow_keys_label(self.arrow_keys_seek)
+        ));
+    }
+
+    pub fn toggle_backup_before_tag_writes(&mut self) {
+        self.backup_before_tag_writes = !self.backup_before_tag_writes;
+        self.set_status(&format!(
+            "Back up originals before tag writes: {}",
+            if self.backup_before_tag_writes {
+                "On"
+            } else {
+                "Off"
+            }
+        ));
+    }
+
+    /// Where the original copy of `track` lives, whether or not backups are enabled.
+    pub fn tag_backup_path(&self, track: &Path) -> Option<PathBuf> {
+        let dir = match &self.tag_backup_dir {
+            Some(dir) => dir.clone(),
+            None => config::tag_backup_root().ok()?,
+        };
+        Some(config::tag_backup_path_for_track(track, &dir))
+    }
+
+    /// Bytes held in the tag backup folder. Reads the folder, so call it when a
+    /// panel opens rather than per frame.
+    pub fn tag_backup_disk_usage(&self) -> u64 {
+        let dir = match &self.tag_backup_dir {
+            Some(dir) => dir.clone(),
+            None => match config::tag_backup_root() {
+                Ok(dir) => dir,
+                Err(_) => return 0,
+            },
+        };
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return 0;
+        };
+        entries
+            .filter_map(|entry| entry.ok()?.metadata().ok())
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    /// Backup target for the next tag write, or `None` when backups are off.
+    pub fn tag_write_backup_path(&self, track: &Path) -> Option<PathBuf> {
+        if !self.backup_before_tag_writes {
+            return None;
+        }
+        self.tag_backup_path(track)
+    }
+
     pub fn set_header_section(&mut self, section: HeaderSection) {
+        if section != self.header_section
+            && self.header_section == HeaderSection::Lyrics
+            && self.request_lyrics_leave(LyricsLeaveAction::SwitchSection(section))
+        {
+            return;
+        }
         self.header_section = section;
         self.set_status(&format!("Section: {}", self.header_section.label()));
     }
@@ -770,8 +2055,8 @@ impl TuneCore {
         self.set_status(&format!("Stats range: {}", self.stats_range.label()));
     }
 
-    pub fn toggle_stats_sort(&mut self) {
-        self.stats_sort = self.stats_sort.toggle();
+    pub fn cycle_stats_sort(&mut self) {
+        self.stats_sort = self.stats_sort.next();
         self.set_status(&format!("Stats sort: {}", self.stats_sort.label()));
     }
 
@@ -801,6 +2086,7 @@ impl TuneCore {
     pub fn online_leave_room(&mut self) {
         if self.online.session.is_some() {
             self.online.leave_room();
+            self.online_queue_scroll = 0;
             if self.browser_shared_queue {
                 self.browser_shared_queue = false;
             }
@@ -940,14 +2226,45 @@ impl TuneCore {
             return Vec::new();
         };
 
-        if !session.can_local_control_playback() {
-            self.set_status("Room is host-only. Listener cannot edit queue");
-            return Vec::new();
-        }
-
         let owner_nickname = session
             .local_participant()
             .map(|entry| entry.nickname.clone());
+
+        if !session.can_local_control_playback() {
+            // Host-only listeners send requests instead. They show up once the
+            // host accepts them, so nothing is added locally.
+            let slots = owner_nickname
+                .as_deref()
+                .map_or(0, |nickname| session.request_slots_left(nickname));
+            if slots == 0 {
+                self.set_status(&format!(
+                    "You already have {} requests in the shared queue",
+                    crate::online::MAX_LISTENER_REQUESTS
+                ));
+                return Vec::new();
+            }
+            let requested: Vec<crate::online::SharedQueueItem> = queue_items
+                .into_iter()
+                .take(slots)
+                .map(|(path, title)| crate::online::SharedQueueItem {
+                    delivery: if path.exists() {
+                        crate::online::QueueDelivery::PreferLocalWithStreamFallback
+                    } else {
+                        crate::online::QueueDelivery::HostStreamOnly
+                    },
+                    path,
+                    title,
+                    owner_nickname: owner_nickname.clone(),
+                })
+                .collect();
+            self.set_status(&format!(
+                "Requested {} track{} from the host",
+                requested.len(),
+                if requested.len() == 1 { "" } else { "s" }
+            ));
+            return requested;
+        }
+
         let mut added = Vec::with_capacity(queue_items.len());
 
         for (path, title) in queue_items {
@@ -968,9 +2285,26 @@ impl TuneCore {
     }
 
     pub fn sync_lyrics_for_track(&mut self, track: Option<&Path>) {
+        if self.lyrics_unsaved
+            && self.lyrics_mode == LyricsMode::Edit
+            && self
+                .lyrics_track_path
+                .as_ref()
+                .is_some_and(|current| track.is_none_or(|next| !path_eq(current, next)))
+        {
+            let next = track.map(Path::to_path_buf).unwrap_or_default();
+            if self.lyrics_leave_prompt.is_none()
+                && self.lyrics_kept_for_track.as_ref() != Some(&next)
+            {
+                self.request_lyrics_leave(LyricsLeaveAction::ChangeTrack(next));
+            }
+            return;
+        }
+        self.lyrics_kept_for_track = None;
         let Some(path) = track else {
             self.lyrics = None;
             self.lyrics_track_path = None;
+            self.lyrics_unsaved = false;
             self.lyrics_mode = LyricsMode::View;
             self.lyrics_selected_line = 0;
             self.lyrics_missing_prompt = false;
@@ -987,6 +2321,7 @@ impl TuneCore {
         }
 
         self.lyrics_track_path = Some(path.to_path_buf());
+        self.lyrics_unsaved = false;
         self.lyrics_mode = LyricsMode::View;
         self.lyrics_selected_line = 0;
         self.lyrics_creation_declined = false;
@@ -1007,6 +2342,50 @@ impl TuneCore {
         }
     }
 
+    /// Holds `action` behind a save prompt when Edit mode has unsaved changes.
+    /// Returns false when there is nothing to lose and the caller can go ahead.
+    pub fn request_lyrics_leave(&mut self, action: LyricsLeaveAction) -> bool {
+        if !self.lyrics_unsaved || self.lyrics_mode != LyricsMode::Edit {
+            return false;
+        }
+        self.lyrics_leave_prompt = Some(action);
+        self.set_status(LYRICS_LEAVE_PROMPT);
+        true
+    }
+
+    /// Answers the save prompt with y (`save`) or n. Returns the held action
+    /// once it may proceed; a failed save keeps the edits and the prompt closes.
+    pub fn resolve_lyrics_leave_prompt(&mut self, save: bool) -> Option<LyricsLeaveAction> {
+        let action = self.lyrics_leave_prompt.take()?;
+        if save {
+            self.save_lyrics_sidecar();
+            if self.lyrics_unsaved {
+                return None;
+            }
+        } else {
+            self.lyrics_unsaved = false;
+            if let Some(path) = self.lyrics_track_path.take() {
+                // Reload the sidecar so the discarded edits do not linger.
+                if !matches!(action, LyricsLeaveAction::ChangeTrack(_)) {
+                    self.sync_lyrics_for_track(Some(&path));
+                }
+            }
+            self.set_status("Discarded lyric changes");
+        }
+        self.lyrics_mode = LyricsMode::View;
+        if let LyricsLeaveAction::SwitchSection(section) = action {
+            self.set_header_section(section);
+        }
+        Some(action)
+    }
+
+    pub fn cancel_lyrics_leave_prompt(&mut self) {
+        if let Some(LyricsLeaveAction::ChangeTrack(path)) = self.lyrics_leave_prompt.take() {
+            self.lyrics_kept_for_track = Some(path);
+        }
+        self.set_status("Still editing lyrics");
+    }
+
     pub fn decline_lyrics_creation(&mut self) {
         self.lyrics_missing_prompt = false;
         self.lyrics_creation_declined = true;
@@ -1040,6 +2419,85 @@ impl TuneCore {
         }
     }
 
+    pub fn request_lyrics_fetch(&mut self) {
+        let Some(path) = self.lyrics_track_path.clone() else {
+            self.set_status("No active track for lyrics");
+            return;
+        };
+        let track = self.track_index(&path).and_then(|idx| self.tracks.get(idx));
+        let artist = track
+            .and_then(|track| track.artist.as_deref())
+            .map(str::trim)
+            .unwrap_or_default()
+            .to_string();
+        let title = track
+            .map(|track| track.title.trim().to_string())
+            .unwrap_or_default();
+        if artist.is_empty() || title.is_empty() {
+            self.set_status("Lyrics lookup needs the track's artist and title");
+            return;
+        }
+        self.set_status(&format!("Fetching lyrics for {artist} - {title}..."));
+        self.lyrics_fetch_requested = Some(LyricsFetchRequest {
+            track_path: path,
+            artist,
+            title,
+        });
+    }
+
+    /// Saves lyrics found online as the track's sidecar and shows them if the
+    /// track is still waiting at the missing-lyrics prompt.
+    pub fn finish_lyrics_fetch(
+        &mut self,
+        request: &LyricsFetchRequest,
+        result: anyhow::Result<Option<LyricsDocument>>,
+    ) {
+        let label = format!("{} - {}", request.artist, request.title);
+        let path = &request.track_path;
+        let doc = match result {
+            Ok(Some(doc)) => doc,
+            Ok(None) => {
+                self.set_status(&format!("No lyrics found online for {label}"));
+                return;
+            }
+            Err(err) => {
+                self.set_status(&format!("Lyrics fetch failed: {err:#}"));
+                return;
+            }
+        };
+        if lyrics::sidecar_lrc_path(path).is_ok_and(|sidecar| sidecar.exists()) {
+            self.set_status(&format!(
+                "Kept the lyrics file created for {label} while fetching"
+            ));
+            return;
+        }
+        let synced = doc.precision != lyrics::LyricsTimingPrecision::None;
+        match lyrics::write_sidecar(path, &doc) {
+            Ok(saved) => {
+                let waiting = self
+                    .lyrics_track_path
+                    .as_ref()
+                    .is_some_and(|current| path_eq(current, path))
+                    && self.lyrics.is_none();
+                if waiting {
+                    self.lyrics = Some(doc);
+                    self.lyrics_selected_line = 0;
+                    self.lyrics_missing_prompt = false;
+                    self.lyrics_creation_declined = false;
+                }
+                if synced {
+                    self.set_status(&format!("Saved synced lyrics to {}", saved.display()));
+                } else {
+                    self.set_status(&format!(
+                        "Only plain lyrics found; saved to {}",
+                        saved.display()
+                    ));
+                }
+            }
+            Err(err) => self.set_status(&format!("Lyrics save failed: {err}")),
+        }
+    }
+
     pub fn toggle_lyrics_mode(&mut self) {
         self.lyrics_mode = match self.lyrics_mode {
             LyricsMode::View => LyricsMode::Edit,
@@ -1062,7 +2520,10 @@ impl TuneCore {
             return;
         };
         match lyrics::write_sidecar(&path, doc) {
-            Ok(saved) => self.set_status(&format!("Saved {}", saved.display())),
+            Ok(saved) => {
+                self.lyrics_unsaved = false;
+                self.set_status(&format!("Saved {}", saved.display()));
+            }
             Err(err) => self.set_status(&format!("Lyrics save failed: {err}")),
         }
     }
@@ -1140,6 +2601,7 @@ impl TuneCore {
         }
         if let Some(line) = doc.lines.get_mut(self.lyrics_selected_line) {
             line.text.push(ch);
+            self.lyrics_unsaved = true;
             self.dirty = true;
         }
     }
@@ -1153,6 +2615,7 @@ impl TuneCore {
         };
         if !line.text.is_empty() {
             line.text.pop();
+            self.lyrics_unsaved = true;
             self.dirty = true;
         }
     }
@@ -1177,6 +2640,7 @@ impl TuneCore {
             },
         );
         self.lyrics_selected_line = insert_at;
+        self.lyrics_unsaved = true;
         self.dirty = true;
     }
 
@@ -1195,6 +2659,7 @@ impl TuneCore {
         } else {
             self.lyrics_selected_line = self.lyrics_selected_line.min(doc.lines.len() - 1);
         }
+        self.lyrics_unsaved = true;
         self.dirty = true;
     }
 
@@ -1215,6 +2680,7 @@ impl TuneCore {
         self.lyrics_selected_line = self
             .active_lyric_line_for_position(Some(position))
             .unwrap_or(self.lyrics_selected_line);
+        self.lyrics_unsaved = true;
         self.dirty = true;
     }
 
@@ -1331,50 +2797,281 @@ impl TuneCore {
         self.set_status(&format!("Queued next {count} track(s)"));
     }
 
-    pub fn remove_selected_from_local_queue(&mut self) {
-        if !self.browser_local_queue {
-            self.set_status("Open local queue to remove item");
-            return;
+    /// Writes the local queue as one `title\tartist\talbum` line per track, so it
+    /// can be matched against someone else's library rather than their file paths.
+    pub fn export_queue_text(&self) -> String {
+        let mut text = String::from(QUEUE_TEXT_HEADER);
+        text.push('\n');
+        for track in self.queue.iter().filter_map(|idx| self.tracks.get(*idx)) {
+            let fields = [
+                track.title.as_str(),
+                track.artist.as_deref().unwrap_or_default(),
+                track.album.as_deref().unwrap_or_default(),
+            ];
+            let line = fields
+                .iter()
+                .map(|field| queue_text_field(field))
+                .collect::<Vec<_>>()
+                .join("\t");
+            text.push_str(line.trim_end());
+            text.push('\n');
         }
-        let Some(selected_pos) = self.selected_local_queue_position_in_browser() else {
-            self.set_status("Select a queue item to remove");
+        text
+    }
+
+    /// Writes a playlist as extended M3U so other players can open it.
+    pub fn export_playlist_m3u(&mut self, name: &str, path: &Path) {
+        let Some(tracks) = self.playlist_tracks(name) else {
+            self.set_status("Playlist not found");
             return;
         };
-        if selected_pos >= self.queue.len() {
-            self.set_status("Queue item not found");
+        self.write_m3u(&tracks, path);
+    }
+
+    pub fn export_queue_m3u(&mut self, path: &Path) {
+        let tracks: Vec<PathBuf> = self
+            .queue
+            .iter()
+            .filter_map(|idx| self.tracks.get(*idx))
+            .map(|track| track.path.clone())
+            .collect();
+        if tracks.is_empty() {
+            self.set_status("Queue is empty");
             return;
         }
-        self.queue.remove(selected_pos);
+        self.write_m3u(&tracks, path);
+    }
 
-        if let Some(current) = self.current_queue_index {
-            self.current_queue_index = if self.queue.is_empty() {
-                None
-            } else if selected_pos < current {
-                Some(current - 1)
-            } else if selected_pos == current {
-                Some(current.min(self.queue.len() - 1))
-            } else {
-                Some(current)
-            };
+    fn write_m3u(&mut self, tracks: &[PathBuf], path: &Path) {
+        let display = config::sanitize_display_text(&path.display().to_string());
+        match fs::write(path, self.m3u_text(tracks)) {
+            Ok(()) => self.set_status(&format!("Exported {} track(s) to {display}", tracks.len())),
+            Err(err) => self.set_status(&format!("M3U export failed: {err}")),
         }
-
-        self.rebuild_shuffle_order();
-        self.refresh_browser_entries();
-        self.set_status("Removed queue item");
     }
 
-    pub fn move_selected_local_queue_item_to_next(&mut self) {
-        if !self.browser_local_queue {
-            self.set_status("Open local queue to move item");
-            return;
+    /// `#EXTINF` carries the length in whole seconds, or `-1` when the file has
+    /// no readable duration, and `Artist - Title` from the library metadata.
+    fn m3u_text(&self, tracks: &[PathBuf]) -> String {
+        let mut text = String::from("#EXTM3U\n");
+        for path in tracks {
+            let duration = self
+                .duration_seconds_for_path(path)
+                .or_else(|| library::duration_seconds(path))
+                .map_or(-1, i64::from);
+            let title = self.title_for_path(path).unwrap_or_else(|| {
+                path.file_stem()
+                    .and_then(OsStr::to_str)
+                    .unwrap_or("unknown")
+                    .to_string()
+            });
+            let label = match self.artist_for_path(path) {
+                Some(artist) if !artist.trim().is_empty() => format!("{artist} - {title}"),
+                _ => title,
+            };
+            text.push_str(&format!(
+                "#EXTINF:{duration},{}\n{}\n",
+                queue_text_field(&label),
+                config::normalize_path(path).display()
+            ));
         }
-        if self.queue.len() < 2 {
-            self.set_status("Need at least 2 queue items");
+        text
+    }
+
+    /// Creates a playlist from an `.m3u`, `.m3u8` or `.pls` file, named after the
+    /// file. Entries already in the library reuse its path, so the same song is
+    /// not listed twice; entries whose file is missing are skipped and reported.
+    pub fn import_playlist_file(&mut self, path: &Path) {
+        if !library::is_playlist_file(path) {
+            self.set_status("Not an M3U or PLS playlist");
             return;
         }
-        let Some(from_index) = self.selected_local_queue_position_in_browser() else {
-            self.set_status("Select a queue item to move");
-            return;
+        let entries = match library::parse_playlist_file(path) {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.set_status(&format!("Playlist import failed: {err:#}"));
+                return;
+            }
+        };
+
+        let mut tracks = Vec::with_capacity(entries.len());
+        let mut missing = Vec::new();
+        for entry in entries {
+            let entry = config::normalize_path(&entry);
+            if let Some(idx) = self.track_index(&entry) {
+                tracks.push(self.tracks[idx].path.clone());
+            } else if entry.is_file() || library::cue_segment_for_path(&entry).is_some() {
+                tracks.push(entry);
+            } else {
+                missing.push(entry.file_name().map_or_else(
+                    || entry.display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                ));
+            }
+        }
+        if tracks.is_empty() {
+            self.set_status(&format!(
+                "No playlist tracks found on disk ({} missing)",
+                missing.len()
+            ));
+            return;
+        }
+
+        let stem = path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .map(str::trim)
+            .filter(|stem| !stem.is_empty())
+            .unwrap_or("Imported");
+        let mut name = stem.to_string();
+        let mut suffix = 2;
+        while self.playlist_name_taken(&name) {
+            name = format!("{stem} ({suffix})");
+            suffix += 1;
+        }
+        let count = tracks.len();
+        self.playlists.insert(name.clone(), Playlist { tracks });
+        self.refresh_browser_entries();
+        let mut status = format!("Imported {count} track(s) into playlist {name}");
+        if let Some(first) = missing.first() {
+            status.push_str(&format!("; {} missing: {first}", missing.len()));
+            if missing.len() > 1 {
+                status.push_str(", ...");
+            }
+        }
+        self.set_status(&status);
+    }
+
+    /// Replaces the local queue with library tracks matching the lines of an
+    /// `export_queue_text` blob. Lines that match nothing are reported, not queued.
+    pub fn import_queue_text(&mut self, text: &str) -> QueueImport {
+        let mut queue = Vec::new();
+        let mut not_found = Vec::new();
+        for line in text.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with("# ") {
+                continue;
+            }
+            let mut fields = line.split('\t').map(str::trim);
+            let title = fields.next().unwrap_or_default();
+            let artist = fields.next().unwrap_or_default();
+            let album = fields.next().unwrap_or_default();
+            if title.is_empty() {
+                continue;
+            }
+            match self.match_queue_text_track(title, artist, album) {
+                Some(idx) => queue.push(idx),
+                None if artist.is_empty() => not_found.push(title.to_string()),
+                None => not_found.push(format!("{title} - {artist}")),
+            }
+        }
+
+        let result = QueueImport {
+            matched: queue.len(),
+            not_found,
+        };
+        if queue.is_empty() {
+            self.set_status("No queue tracks matched your library");
+            return result;
+        }
+
+        self.queue = queue;
+        self.current_queue_index = self.pinned_queue_index();
+        self.rebuild_shuffle_order();
+        if self.browser_local_queue {
+            self.refresh_browser_entries();
+        }
+        let mut status = format!("Imported {} queue track(s)", result.matched);
+        if let Some(first) = result.not_found.first() {
+            status.push_str(&format!("; {} not found: {first}", result.not_found.len()));
+            if result.not_found.len() > 1 {
+                status.push_str(", ...");
+            }
+        }
+        self.set_status(&status);
+        result
+    }
+
+    /// Title must match; a given artist must match too. Among those, a matching
+    /// album wins, otherwise the first library hit is used.
+    fn match_queue_text_track(&self, title: &str, artist: &str, album: &str) -> Option<usize> {
+        let same = |value: Option<&str>, wanted: &str| {
+            value.is_some_and(|value| value.trim().eq_ignore_ascii_case(wanted))
+        };
+        let mut fallback = None;
+        for (idx, track) in self.tracks.iter().enumerate() {
+            if !track.title.trim().eq_ignore_ascii_case(title) {
+                continue;
+            }
+            if !artist.is_empty() && !same(track.artist.as_deref(), artist) {
+                continue;
+            }
+            if album.is_empty() || same(track.album.as_deref(), album) {
+                return Some(idx);
+            }
+            fallback.get_or_insert(idx);
+        }
+        fallback
+    }
+
+    /// Removes the queue entry at `index`, keeping `current_queue_index` on the
+    /// same track. Removing the current entry leaves the one after it current.
+    /// `None` when `index` is outside the queue, otherwise whether the current
+    /// entry was the one removed.
+    pub fn remove_from_queue(&mut self, index: usize) -> Option<bool> {
+        if index >= self.queue.len() {
+            return None;
+        }
+        self.queue.remove(index);
+
+        let removed_current = self.current_queue_index == Some(index);
+        if let Some(current) = self.current_queue_index {
+            self.current_queue_index = if self.queue.is_empty() {
+                None
+            } else if index < current {
+                Some(current - 1)
+            } else if index == current {
+                Some(current.min(self.queue.len() - 1))
+            } else {
+                Some(current)
+            };
+        }
+        self.rebuild_shuffle_order();
+        Some(removed_current)
+    }
+
+    /// Returns true when the removed entry was the current one.
+    pub fn remove_selected_from_local_queue(&mut self) -> bool {
+        if !self.browser_local_queue {
+            self.set_status("Open local queue to remove item");
+            return false;
+        }
+        let Some(selected_pos) = self.selected_local_queue_position_in_browser() else {
+            self.set_status("Select a queue item to remove");
+            return false;
+        };
+        let Some(removed_current) = self.remove_from_queue(selected_pos) else {
+            self.set_status("Queue item not found");
+            return false;
+        };
+
+        self.refresh_browser_entries();
+        self.set_status("Removed queue item");
+        removed_current
+    }
+
+    pub fn move_selected_local_queue_item_to_next(&mut self) {
+        if !self.browser_local_queue {
+            self.set_status("Open local queue to move item");
+            return;
+        }
+        if self.queue.len() < 2 {
+            self.set_status("Need at least 2 queue items");
+            return;
+        }
+        let Some(from_index) = self.selected_local_queue_position_in_browser() else {
+            self.set_status("Select a queue item to move");
+            return;
         };
         if from_index >= self.queue.len() {
             self.set_status("Queue item not found");
@@ -1425,6 +3122,68 @@ impl TuneCore {
         self.set_status("Moved queue item to next");
     }
 
+    /// Moves the queue entry at `from` to `to`, keeping `current_queue_index` on
+    /// the same track. `false` when either position is outside the queue.
+    pub fn move_queue_item(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.queue.len() || to >= self.queue.len() {
+            return false;
+        }
+        let item = self.queue.remove(from);
+        self.queue.insert(to, item);
+        self.current_queue_index = self.current_queue_index.map(|current| {
+            if current == from {
+                to
+            } else if from < current && current <= to {
+                current - 1
+            } else if to <= current && current < from {
+                current + 1
+            } else {
+                current
+            }
+        });
+        self.rebuild_shuffle_order();
+        true
+    }
+
+    /// Shifts the selected local queue item one place up (`-1`) or down (`1`)
+    /// and keeps it selected.
+    pub fn shift_selected_local_queue_item(&mut self, delta: isize) {
+        if !self.browser_local_queue {
+            self.set_status("Open local queue to move item");
+            return;
+        }
+        if self.shuffle_enabled {
+            self.set_status("Turn shuffle off to reorder the queue");
+            return;
+        }
+        let Some(from) = self.selected_local_queue_position_in_browser() else {
+            self.set_status("Select a queue item to move");
+            return;
+        };
+        let Some(to) = from
+            .checked_add_signed(delta)
+            .filter(|to| *to < self.queue.len())
+        else {
+            self.set_status(if delta < 0 {
+                "Queue item already first"
+            } else {
+                "Queue item already last"
+            });
+            return;
+        };
+        if !self.move_queue_item(from, to) {
+            self.set_status("Queue item not found");
+            return;
+        }
+        self.refresh_browser_entries();
+        self.selected_browser = self.selected_browser.saturating_add_signed(delta);
+        self.set_status(&format!(
+            "Moved queue item to {}/{}",
+            to + 1,
+            self.queue.len()
+        ));
+    }
+
     pub fn add_selected_to_shared_queue_end(&mut self) -> Vec<crate::online::SharedQueueItem> {
         let paths = self.selected_paths_for_browser_selection();
         let added = self.online_queue_paths(&paths);
@@ -1569,6 +3328,98 @@ impl TuneCore {
         Some((from_index, to_index, expected_path))
     }
 
+    /// Shared queue counterpart of `shift_selected_local_queue_item`. Returns
+    /// the move to broadcast to the room.
+    pub fn shift_selected_shared_queue_item(
+        &mut self,
+        delta: isize,
+    ) -> Option<(usize, usize, PathBuf)> {
+        if !self.browser_shared_queue {
+            self.set_status("Open shared queue to move item");
+            return None;
+        }
+        let Some(from_index) = self.selected_track_position_in_browser() else {
+            self.set_status("Select a shared queue item to move");
+            return None;
+        };
+        let Some(session) = self.online.session.as_mut() else {
+            self.set_status("Join or host a room first");
+            return None;
+        };
+        if !session.can_local_control_playback() {
+            self.set_status("Room is host-only. Listener cannot edit queue");
+            return None;
+        }
+        if from_index >= session.shared_queue.len() {
+            self.set_status("Shared queue item not found");
+            return None;
+        }
+        let Some(to_index) = from_index
+            .checked_add_signed(delta)
+            .filter(|to| *to < session.shared_queue.len())
+        else {
+            self.set_status(if delta < 0 {
+                "Shared queue item already first"
+            } else {
+                "Shared queue item already last"
+            });
+            return None;
+        };
+        let item = session
+            .shared_queue
+            .remove(from_index)
+            .expect("shared queue item should exist");
+        let expected_path = item.path.clone();
+        session.shared_queue.insert(to_index, item);
+        let len = session.shared_queue.len();
+        self.refresh_browser_entries();
+        self.selected_browser = self.selected_browser.saturating_add_signed(delta);
+        self.set_status(&format!(
+            "Moved shared queue item to {}/{len}",
+            to_index + 1
+        ));
+        Some((from_index, to_index, expected_path))
+    }
+
+    /// Host only: randomizes the shared queue, keeping the front item in place
+    /// when it is the track the room is playing. Returns the order to broadcast.
+    pub fn shuffle_shared_queue(&mut self) -> Option<Vec<usize>> {
+        let Some(session) = self.online.session.as_mut() else {
+            self.set_status("Join or host a room first");
+            return None;
+        };
+        if !session
+            .local_participant()
+            .is_some_and(|local| local.is_host)
+        {
+            self.set_status("Only host can shuffle the shared queue");
+            return None;
+        }
+        if session.shared_queue.len() < 2 {
+            self.set_status("Need at least 2 shared queue items");
+            return None;
+        }
+        let playing =
+            session
+                .last_transport
+                .as_ref()
+                .and_then(|transport| match &transport.command {
+                    TransportCommand::PlayTrack { path, .. }
+                    | TransportCommand::SetPlaybackState { path, .. } => Some(path.as_path()),
+                    TransportCommand::StopPlayback | TransportCommand::SetPaused { .. } => None,
+                });
+        let keep_front = session
+            .shared_queue
+            .front()
+            .is_some_and(|front| playing == Some(front.path.as_path()));
+        let mut order: Vec<usize> = (0..session.shared_queue.len()).collect();
+        order[usize::from(keep_front)..].shuffle(&mut self.shuffle_rng);
+        session.reorder_shared_queue(&order);
+        self.refresh_browser_entries();
+        self.set_status("Shuffled shared queue");
+        Some(order)
+    }
+
     pub fn queue_position_for_path(&self, path: &Path) -> Option<usize> {
         self.queue.iter().position(|idx| {
             self.tracks
@@ -1668,6 +3519,7 @@ impl TuneCore {
         }
 
         self.cover_art_lookup.borrow_mut().remove(&key);
+        self.extra_tags_lookup.borrow_mut().remove(&key);
         self.refresh_browser_entries();
         self.dirty = true;
     }
@@ -1690,6 +3542,76 @@ impl TuneCore {
         cover_art
     }
 
+    pub fn track_crossfade_disabled(&self, path: &Path) -> bool {
+        self.no_crossfade_tracks
+            .iter()
+            .any(|flagged| path_eq(flagged, path))
+    }
+
+    pub fn toggle_track_crossfade_disabled(&mut self, path: &Path) -> bool {
+        let before = self.no_crossfade_tracks.len();
+        self.no_crossfade_tracks
+            .retain(|flagged| !path_eq(flagged, path));
+        let disabled = self.no_crossfade_tracks.len() == before;
+        if disabled {
+            self.no_crossfade_tracks.push(config::normalize_path(path));
+        }
+        self.set_status(if disabled {
+            "Crossfade disabled for track"
+        } else {
+            "Crossfade enabled for track"
+        });
+        disabled
+    }
+
+    /// Path that `next_track_path`, or `end_of_queue_track_path` after it, would
+    /// return, without advancing the queue. Shuffle and Radio picks are drawn
+    /// from a copy of the shuffle RNG, so they match the real advance.
+    pub fn peek_next_track_path(&self, history: &[ListenEvent]) -> Option<PathBuf> {
+        let mut rng = self.shuffle_rng.clone();
+        let fresh_order;
+        let order = if self.shuffle_order_stale() {
+            fresh_order = self.shuffled_queue_order(self.current_queue_index, &mut rng);
+            &fresh_order
+        } else {
+            &self.shuffle_order
+        };
+        let idx = match self.following_queue_index(self.current_queue_index, order) {
+            Some(idx) => idx,
+            None => return self.peek_end_of_queue_track_path(history, &mut rng),
+        };
+        self.queue
+            .get(idx)
+            .and_then(|track_idx| self.tracks.get(*track_idx))
+            .map(|track| track.path.clone())
+    }
+
+    /// The track `end_of_queue_track_path` would pick, without queueing it.
+    fn peek_end_of_queue_track_path(
+        &self,
+        history: &[ListenEvent],
+        rng: &mut SmallRng,
+    ) -> Option<PathBuf> {
+        let idx = match self.end_of_queue_behavior {
+            EndOfQueueBehavior::Stop => return None,
+            EndOfQueueBehavior::RepeatQueue => {
+                let order = if self.shuffle_enabled {
+                    self.shuffled_queue_order(None, rng)
+                } else {
+                    Vec::new()
+                };
+                let pos = self.following_queue_index(None, &order)?;
+                *self.queue.get(pos)?
+            }
+            EndOfQueueBehavior::KeepGoing => {
+                let seed = self.current_path()?;
+                *self.keep_going_candidates(seed).first()?
+            }
+            EndOfQueueBehavior::Radio => *self.radio_candidates(history, rng).first()?,
+        };
+        self.tracks.get(idx).map(|track| track.path.clone())
+    }
+
     pub fn next_track_path(&mut self) -> Option<PathBuf> {
         if self.queue.is_empty() {
             self.set_status("Queue is empty");
@@ -1699,23 +3621,180 @@ impl TuneCore {
         let idx = match self.current_queue_index {
             Some(current) => self.next_index(current),
             None => {
-                if self.shuffle_enabled {
-                    if self.shuffle_order.len() != self.queue.len() {
-                        self.rebuild_shuffle_order();
-                    }
-                    self.shuffle_order.first().copied()
-                } else {
-                    Some(0)
+                if self.shuffle_order_stale() {
+                    self.rebuild_shuffle_order();
                 }
+                self.following_queue_index(None, &self.shuffle_order)
             }
         }?;
 
         self.current_queue_index = Some(idx);
         self.dirty = true;
-        self.queue
+        let path = self
+            .queue
             .get(idx)
             .and_then(|track_idx| self.tracks.get(*track_idx))
-            .map(|track| track.path.clone())
+            .map(|track| track.path.clone());
+        self.pin_track(path.clone());
+        path
+    }
+
+    /// Applies the end-of-queue behavior once `next_track_path` has run out of tracks.
+    /// `history` is the listen log that Radio picks from.
+    pub fn end_of_queue_track_path(&mut self, history: &[ListenEvent]) -> Option<PathBuf> {
+        match self.end_of_queue_behavior {
+            EndOfQueueBehavior::Stop => None,
+            EndOfQueueBehavior::RepeatQueue => {
+                if self.queue.is_empty() {
+                    return None;
+                }
+                self.current_queue_index = None;
+                if self.shuffle_enabled {
+                    self.rebuild_shuffle_order();
+                }
+                let path = self.next_track_path()?;
+                self.set_status("End of queue. Repeating queue");
+                Some(path)
+            }
+            EndOfQueueBehavior::KeepGoing => {
+                let seed = self.current_path()?.to_path_buf();
+                let additions = self.keep_going_candidates(&seed);
+                let first = self.queue.len();
+                let path = additions
+                    .first()
+                    .and_then(|idx| self.tracks.get(*idx))
+                    .map(|track| track.path.clone())?;
+                let count = additions.len();
+                self.queue.extend(additions);
+                self.rebuild_shuffle_order();
+                self.current_queue_index = Some(first);
+                self.pin_track(Some(path.clone()));
+                if self.browser_local_queue {
+                    self.refresh_browser_entries();
+                }
+                self.set_status(&format!(
+                    "End of queue. Added {count} track(s) from {}",
+                    self.keep_going_pool.label().to_ascii_lowercase()
+                ));
+                Some(path)
+            }
+            EndOfQueueBehavior::Radio => {
+                let mut rng = self.shuffle_rng.clone();
+                let additions = self.radio_candidates(history, &mut rng);
+                self.shuffle_rng = rng;
+                let first = self.queue.len();
+                let path = additions
+                    .first()
+                    .and_then(|idx| self.tracks.get(*idx))
+                    .map(|track| track.path.clone())?;
+                let titles = additions
+                    .iter()
+                    .filter_map(|idx| self.tracks.get(*idx))
+                    .map(|track| track.title.clone())
+                    .collect::<Vec<_>>();
+                self.queue.extend(additions);
+                self.rebuild_shuffle_order();
+                self.current_queue_index = Some(first);
+                self.pin_track(Some(path.clone()));
+                if self.browser_local_queue {
+                    self.refresh_browser_entries();
+                }
+                self.set_status(&format!(
+                    "Radio added {} track(s): {}",
+                    titles.len(),
+                    titles.join(", ")
+                ));
+                Some(path)
+            }
+        }
+    }
+
+    /// Picks up to `RADIO_BATCH` library tracks that sound like recent listening:
+    /// same artist or album as the last few plays, then most played. Tracks that
+    /// are queued or were heard in the last `RADIO_RECENT_WINDOW` listens are
+    /// skipped so the radio does not repeat itself right away.
+    fn radio_candidates(&self, history: &[ListenEvent], rng: &mut SmallRng) -> Vec<usize> {
+        let mut play_counts: HashMap<String, u32> = HashMap::new();
+        for event in history.iter().filter(|event| event.counted_play) {
+            *play_counts
+                .entry(normalized_path_key(&event.track_path))
+                .or_default() += 1;
+        }
+
+        let mut excluded: HashSet<String> = history
+            .iter()
+            .rev()
+            .take(RADIO_RECENT_WINDOW)
+            .map(|event| normalized_path_key(&event.track_path))
+            .collect();
+        excluded.extend(
+            self.queue
+                .iter()
+                .filter_map(|idx| self.tracks.get(*idx))
+                .map(|track| normalized_path_key(&track.path)),
+        );
+
+        let mut seed_artists = HashSet::new();
+        let mut seed_albums = HashSet::new();
+        if let Some(current) = self.current_path() {
+            seed_artists.extend(self.artist_for_path(current).map(str::to_ascii_lowercase));
+            seed_albums.extend(self.album_for_path(current).map(str::to_ascii_lowercase));
+        }
+        for event in history.iter().rev().take(RADIO_SEED_LISTENS) {
+            seed_artists.extend(event.artist.as_deref().map(str::to_ascii_lowercase));
+            seed_albums.extend(event.album.as_deref().map(str::to_ascii_lowercase));
+        }
+
+        let mut scored: Vec<(u32, usize)> = self
+            .tracks
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, track)| {
+                let key = normalized_path_key(&track.path);
+                if excluded.contains(&key) {
+                    return None;
+                }
+                let same_artist = track
+                    .artist
+                    .as_deref()
+                    .is_some_and(|artist| seed_artists.contains(&artist.to_ascii_lowercase()));
+                let same_album = track
+                    .album
+                    .as_deref()
+                    .is_some_and(|album| seed_albums.contains(&album.to_ascii_lowercase()));
+                let plays = play_counts.get(&key).copied().unwrap_or(0).min(5);
+                let score = u32::from(same_artist) * 3 + u32::from(same_album) * 2 + plays;
+                Some((score, idx))
+            })
+            .collect();
+
+        scored.shuffle(rng);
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored
+            .into_iter()
+            .take(RADIO_BATCH)
+            .map(|(_, idx)| idx)
+            .collect()
+    }
+
+    fn keep_going_candidates(&self, seed: &Path) -> Vec<usize> {
+        let seed_artist = self.artist_for_path(seed);
+        let seed_folder = seed.parent();
+        let queued: HashSet<usize> = self.queue.iter().copied().collect();
+        (0..self.tracks.len())
+            .filter(|idx| !queued.contains(idx))
+            .filter(|idx| {
+                let track = &self.tracks[*idx];
+                match self.keep_going_pool {
+                    KeepGoingPool::SameFolder => seed_folder
+                        .zip(track.path.parent())
+                        .is_some_and(|(seed, folder)| path_eq(seed, folder)),
+                    KeepGoingPool::SameArtist => seed_artist
+                        .zip(track.artist.as_deref())
+                        .is_some_and(|(seed, artist)| seed.eq_ignore_ascii_case(artist)),
+                }
+            })
+            .collect()
     }
 
     pub fn prev_track_path(&mut self) -> Option<PathBuf> {
@@ -1740,34 +3819,59 @@ impl TuneCore {
 
         self.current_queue_index = Some(idx);
         self.dirty = true;
-        self.queue
+        let path = self
+            .queue
             .get(idx)
             .and_then(|track_idx| self.tracks.get(*track_idx))
-            .map(|track| track.path.clone())
+            .map(|track| track.path.clone());
+        self.pin_track(path.clone());
+        path
     }
 
     fn next_index(&mut self, current: usize) -> Option<usize> {
         if self.repeat_mode == RepeatMode::One {
             return Some(current);
         }
-
-        if self.shuffle_enabled {
-            return self.next_shuffle_index(current);
+        if self.shuffle_order_stale() {
+            self.rebuild_shuffle_order();
+        }
+        let next = self.following_queue_index(Some(current), &self.shuffle_order)?;
+        if self.shuffle_enabled
+            && let Some(pos) = self.shuffle_order.iter().position(|idx| *idx == next)
+        {
+            self.shuffle_cursor = pos;
         }
+        Some(next)
+    }
 
-        match self.repeat_mode {
-            RepeatMode::Off => {
-                let next = current + 1;
-                (next < self.queue.len()).then_some(next)
-            }
-            RepeatMode::All => {
-                if self.queue.is_empty() {
-                    None
-                } else {
-                    Some((current + 1) % self.queue.len())
-                }
-            }
-            RepeatMode::One => unreachable!("repeat-one handled before queue order"),
+    /// Queue index that plays after `current`, or first when nothing is playing,
+    /// under the repeat and shuffle modes. `None` once the queue runs out.
+    /// Shuffled playback follows `order`, which callers keep in step with the queue.
+    fn following_queue_index(&self, current: Option<usize>, order: &[usize]) -> Option<usize> {
+        if self.queue.is_empty() {
+            return None;
+        }
+        let Some(current) = current else {
+            return if self.shuffle_enabled {
+                order.first().copied()
+            } else {
+                Some(0)
+            };
+        };
+        if self.repeat_mode == RepeatMode::One {
+            return Some(current);
+        }
+        if self.shuffle_enabled {
+            let pos = order.iter().position(|idx| *idx == current)?;
+            return order.get(pos + 1).copied().or_else(|| {
+                (self.repeat_mode == RepeatMode::All)
+                    .then(|| order.first().copied())
+                    .flatten()
+            });
+        }
+        match self.repeat_mode {
+            RepeatMode::All => Some((current + 1) % self.queue.len()),
+            _ => Some(current + 1).filter(|next| *next < self.queue.len()),
         }
     }
 
@@ -1795,29 +3899,6 @@ impl TuneCore {
         }
     }
 
-    fn next_shuffle_index(&mut self, current: usize) -> Option<usize> {
-        if self.shuffle_order.len() != self.queue.len() {
-            self.rebuild_shuffle_order();
-        }
-
-        if self.shuffle_order.is_empty() {
-            return None;
-        }
-
-        let pos = self.shuffle_order.iter().position(|idx| *idx == current)?;
-        if pos + 1 < self.shuffle_order.len() {
-            self.shuffle_cursor = pos + 1;
-            return self.shuffle_order.get(self.shuffle_cursor).copied();
-        }
-
-        if self.repeat_mode == RepeatMode::All {
-            self.shuffle_cursor = 0;
-            self.shuffle_order.first().copied()
-        } else {
-            None
-        }
-    }
-
     fn prev_shuffle_index(&mut self, current: usize) -> Option<usize> {
         if self.shuffle_order.len() != self.queue.len() {
             self.rebuild_shuffle_order();
@@ -1855,12 +3936,23 @@ impl TuneCore {
             .iter()
             .filter_map(|idx| self.tracks.get(*idx).map(|track| track.path.clone()))
             .collect();
-        let current_path = self.current_path().map(Path::to_path_buf);
+        let current_path = self
+            .current_path()
+            .map(Path::to_path_buf)
+            .or_else(|| self.pinned_track.clone());
+        let previous_shuffle_paths: Vec<PathBuf> = self
+            .shuffle_order
+            .iter()
+            .filter_map(|pos| self.queue.get(*pos))
+            .filter_map(|idx| self.tracks.get(*idx).map(|track| track.path.clone()))
+            .collect();
 
         apply(self);
 
         self.invalidate_library_caches();
         self.track_lookup = build_track_lookup(&self.tracks);
+        let lookup = &self.track_lookup;
+        self.transient_tracks.retain(|key| lookup.contains_key(key));
         if queue_was_main_library {
             self.queue = self.metadata_sorted_library_queue();
         } else {
@@ -1871,13 +3963,72 @@ impl TuneCore {
         }
         self.current_queue_index =
             current_path.and_then(|path| self.queue_position_for_path(&path));
-        self.rebuild_shuffle_order();
+        self.restore_shuffle_order(&previous_shuffle_paths);
         self.refresh_browser_entries();
         self.dirty = true;
     }
 
+    /// Marks `path` as the track handed to the player and remembers it as
+    /// recently played for smart shuffle.
+    fn pin_track(&mut self, path: Option<PathBuf>) {
+        if let Some(path) = &path {
+            self.recent_plays.push_back(normalized_path_key(path));
+            if self.recent_plays.len() > RECENT_PLAYS_LIMIT {
+                self.recent_plays.pop_front();
+            }
+        }
+        self.pinned_track = path;
+    }
+
+    pub fn clear_now_playing(&mut self) {
+        self.current_queue_index = None;
+        self.pinned_track = None;
+        self.dirty = true;
+    }
+
+    fn pinned_queue_index(&self) -> Option<usize> {
+        self.pinned_track
+            .as_deref()
+            .and_then(|path| self.queue_position_for_path(path))
+    }
+
+    /// Keeps the upcoming shuffle sequence stable across a queue rebuild; tracks
+    /// that are new to the queue are shuffled in after the surviving ones.
+    fn restore_shuffle_order(&mut self, previous_paths: &[PathBuf]) {
+        let mut positions: HashMap<String, Vec<usize>> = HashMap::new();
+        for (pos, idx) in self.queue.iter().enumerate().rev() {
+            if let Some(track) = self.tracks.get(*idx) {
+                positions
+                    .entry(normalized_path_key(&track.path))
+                    .or_default()
+                    .push(pos);
+            }
+        }
+
+        let mut used = vec![false; self.queue.len()];
+        let mut order = Vec::with_capacity(self.queue.len());
+        for path in previous_paths {
+            if let Some(pos) = positions
+                .get_mut(&normalized_path_key(path))
+                .and_then(Vec::pop)
+            {
+                used[pos] = true;
+                order.push(pos);
+            }
+        }
+        let mut fresh: Vec<usize> = (0..self.queue.len()).filter(|pos| !used[*pos]).collect();
+        fresh.shuffle(&mut self.shuffle_rng);
+        order.extend(fresh);
+
+        self.shuffle_cursor = self
+            .current_queue_index
+            .and_then(|current| order.iter().position(|pos| *pos == current))
+            .unwrap_or(0);
+        self.shuffle_order = order;
+    }
+
     fn queue_matches_main_library_order(&self) -> bool {
-        if self.queue.len() != self.tracks.len() {
+        if self.queue.len() != self.library_track_count() {
             return false;
         }
         self.queue == self.metadata_sorted_library_queue()
@@ -1886,18 +4037,29 @@ impl TuneCore {
     fn metadata_sorted_library_queue(&self) -> Vec<usize> {
         let cache = self.sorted_library_queue_cache.borrow();
         if let Some(ref cached) = *cache
-            && cached.len() == self.tracks.len()
+            && cached.len() == self.library_track_count()
         {
             return cached.clone();
         }
         drop(cache);
-        let mut queue: Vec<usize> = (0..self.tracks.len()).collect();
+        let mut queue: Vec<usize> = (0..self.tracks.len())
+            .filter(|idx| !self.is_transient_track(&self.tracks[*idx].path))
+            .collect();
         queue.sort_by_cached_key(|idx| self.tracks[*idx].title.to_ascii_lowercase());
         *self.sorted_library_queue_cache.borrow_mut() = Some(queue.clone());
         queue
     }
 
     fn selected_paths_for_playlist_action(&self) -> Vec<PathBuf> {
+        if !self.library_search_marked.is_empty() {
+            let mut seen = HashSet::new();
+            return self
+                .library_search_marked
+                .iter()
+                .flat_map(|entry| self.paths_for_browser_entry(entry))
+                .filter(|path| seen.insert(path.clone()))
+                .collect();
+        }
         let Some(entry) = self.browser_entries.get(self.selected_browser) else {
             return self
                 .tracks
@@ -1905,7 +4067,10 @@ impl TuneCore {
                 .map(|track| vec![track.path.clone()])
                 .unwrap_or_default();
         };
+        self.paths_for_browser_entry(entry)
+    }
 
+    fn paths_for_browser_entry(&self, entry: &BrowserEntry) -> Vec<PathBuf> {
         match entry.kind {
             BrowserEntryKind::Track => vec![entry.path.clone()],
             BrowserEntryKind::Folder => self
@@ -1915,9 +4080,7 @@ impl TuneCore {
                 .map(|track| track.path.clone())
                 .collect(),
             BrowserEntryKind::Playlist => self
-                .playlists
-                .get(entry.path.to_string_lossy().as_ref())
-                .map(|playlist| playlist.tracks.clone())
+                .playlist_tracks(entry.path.to_string_lossy().as_ref())
                 .unwrap_or_default(),
             BrowserEntryKind::AllSongs => self
                 .metadata_sorted_library_queue()
@@ -2003,8 +4166,36 @@ impl TuneCore {
     fn refresh_browser_entries(&mut self) {
         let mut entries = Vec::with_capacity(self.tracks.len().max(self.folders.len()));
 
+        if self.library_search_query.is_empty() {
+            self.library_search_marked.clear();
+        }
+
         if !self.library_search_query.is_empty() {
             let query_lower = self.library_search_query.to_ascii_lowercase();
+            let folders: BTreeSet<&Path> = self
+                .tracks
+                .iter()
+                .filter(|track| !self.is_transient_track(&track.path))
+                .filter_map(|track| track.path.parent())
+                .filter(|parent| {
+                    parent.file_name().is_some_and(|name| {
+                        name.to_string_lossy()
+                            .to_ascii_lowercase()
+                            .contains(&query_lower)
+                    })
+                })
+                .collect();
+            for folder in folders {
+                let label = folder
+                    .file_name()
+                    .map(|name| config::sanitize_display_text(&name.to_string_lossy()))
+                    .unwrap_or_default();
+                entries.push(BrowserEntry {
+                    kind: BrowserEntryKind::Folder,
+                    path: folder.to_path_buf(),
+                    label: format!("[DIR] {label}"),
+                });
+            }
             let queue = self.metadata_sorted_library_queue();
             entries.reserve_exact(queue.len());
             for idx in queue {
@@ -2032,9 +4223,9 @@ impl TuneCore {
                 label: String::from("[..] Back"),
             });
 
-            if let Some(playlist) = self.playlists.get(name) {
-                entries.reserve_exact(playlist.tracks.len());
-                for track in &playlist.tracks {
+            if let Some(tracks) = self.playlist_tracks(name) {
+                entries.reserve_exact(tracks.len());
+                for track in &tracks {
                     let cleaned = config::strip_windows_verbatim_prefix(track);
                     entries.push(BrowserEntry {
                         kind: BrowserEntryKind::Track,
@@ -2050,13 +4241,13 @@ impl TuneCore {
                 label: String::from("[..] Back"),
             });
 
-            let queue = self.metadata_sorted_library_queue();
+            let queue = self.all_songs_queue();
             entries.reserve_exact(queue.len());
             for idx in queue {
                 if let Some(track) = self.tracks.get(idx) {
                     entries.push(BrowserEntry {
                         kind: BrowserEntryKind::Track,
-                        label: config::sanitize_display_text(&track.title),
+                        label: self.all_songs_label(track),
                         path: track.path.clone(),
                     });
                 }
@@ -2116,6 +4307,7 @@ impl TuneCore {
             if let Ok(read_dir) = fs::read_dir(current) {
                 let mut folders = Vec::new();
                 let mut files = Vec::new();
+                let mut cue_sheets = Vec::new();
 
                 for entry in read_dir.filter_map(Result::ok) {
                     let path = config::strip_windows_verbatim_prefix(&entry.path());
@@ -2134,6 +4326,28 @@ impl TuneCore {
                             label: self.track_label_from_path(&path),
                             path,
                         });
+                    } else if library::is_cue_sheet(&path) {
+                        cue_sheets.push(path);
+                    }
+                }
+
+                let mut cue_entries = Vec::new();
+                for sheet_path in cue_sheets {
+                    let Ok(sheet) = library::parse_cue(&sheet_path) else {
+                        continue;
+                    };
+                    files.retain(|entry| {
+                        !sheet
+                            .tracks
+                            .iter()
+                            .any(|track| path_eq(&track.file, &entry.path))
+                    });
+                    for track in library::cue_sheet_tracks(&sheet_path, &sheet) {
+                        cue_entries.push(BrowserEntry {
+                            kind: BrowserEntryKind::Track,
+                            label: config::sanitize_display_text(&track.title),
+                            path: track.path,
+                        });
                     }
                 }
 
@@ -2141,11 +4355,12 @@ impl TuneCore {
                 files.sort_by_cached_key(|entry| entry.label.to_ascii_lowercase());
                 entries.extend(folders);
                 entries.extend(files);
+                entries.extend(cue_entries);
             }
         } else {
             entries.reserve_exact(self.folders.len() + self.playlists.len() + 3);
-            for folder in &self.folders {
-                let cleaned = config::strip_windows_verbatim_prefix(folder);
+            for folder in self.folders.iter().filter(|folder| folder.enabled) {
+                let cleaned = config::strip_windows_verbatim_prefix(&folder.path);
                 let label = cleaned
                     .file_name()
                     .map(|name| config::sanitize_display_text(&name.to_string_lossy()))
@@ -2184,6 +4399,13 @@ impl TuneCore {
                     label: format!("[PL] {}", config::sanitize_display_text(name)),
                 });
             }
+            for name in self.smart_playlists.keys() {
+                entries.push(BrowserEntry {
+                    kind: BrowserEntryKind::Playlist,
+                    path: PathBuf::from(name),
+                    label: format!("[SMART] {}", config::sanitize_display_text(name)),
+                });
+            }
 
             entries.sort_by_cached_key(|entry| entry.label.to_ascii_lowercase());
             entries.push(BrowserEntry {
@@ -2216,6 +4438,39 @@ impl TuneCore {
         self.refresh_browser_entries();
     }
 
+    /// Marks or unmarks the selected search result. Marks survive refining the
+    /// query and are dropped when the search closes.
+    pub fn toggle_library_search_mark(&mut self) {
+        let Some(entry) = self.browser_entries.get(self.selected_browser).cloned() else {
+            self.set_status("No selection");
+            return;
+        };
+        if !matches!(
+            entry.kind,
+            BrowserEntryKind::Track | BrowserEntryKind::Folder
+        ) {
+            self.set_status("Only tracks and folders can be marked");
+            return;
+        }
+        if let Some(pos) = self
+            .library_search_marked
+            .iter()
+            .position(|marked| marked.kind == entry.kind && marked.path == entry.path)
+        {
+            self.library_search_marked.remove(pos);
+        } else {
+            self.library_search_marked.push(entry);
+        }
+        let count = self.library_search_marked.len();
+        self.set_status(&format!("Marked {count} search result(s)"));
+    }
+
+    pub fn is_library_search_marked(&self, entry: &BrowserEntry) -> bool {
+        self.library_search_marked
+            .iter()
+            .any(|marked| marked.kind == entry.kind && marked.path == entry.path)
+    }
+
     fn track_label_from_path(&self, path: &Path) -> String {
         self.track_index(path)
             .and_then(|idx| self.tracks.get(idx))
@@ -2250,36 +4505,316 @@ impl TuneCore {
         }
 
         let cleaned = config::strip_windows_verbatim_prefix(path);
-        let title = cleaned
-            .file_stem()
-            .and_then(OsStr::to_str)
-            .unwrap_or("unknown")
-            .to_string();
-        let idx = self.tracks.len();
-        self.tracks.push(Track {
+        let track = library::cue_track_for_path(&cleaned).unwrap_or_else(|| Track {
+            title: cleaned
+                .file_stem()
+                .and_then(OsStr::to_str)
+                .unwrap_or("unknown")
+                .to_string(),
             path: cleaned,
-            title,
             artist: None,
             album: None,
         });
+        let idx = self.tracks.len();
+        self.tracks.push(track);
         self.track_lookup = build_track_lookup(&self.tracks);
         idx
     }
 
     fn rebuild_shuffle_order(&mut self) {
-        self.shuffle_order = (0..self.queue.len()).collect();
-        self.shuffle_order.shuffle(&mut self.shuffle_rng);
+        let mut rng = self.shuffle_rng.clone();
+        self.shuffle_order = self.shuffled_queue_order(self.current_queue_index, &mut rng);
+        self.shuffle_rng = rng;
         self.shuffle_cursor = 0;
     }
 
+    fn shuffle_order_stale(&self) -> bool {
+        self.shuffle_enabled && self.shuffle_order.len() != self.queue.len()
+    }
+
+    /// A fresh shuffle of the queue led by `current`. Drawing from a copy of
+    /// the shuffle RNG predicts the order a rebuild would make.
+    fn shuffled_queue_order(&self, current: Option<usize>, rng: &mut SmallRng) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.queue.len()).collect();
+        order.shuffle(rng);
+        if self.shuffle_mode == ShuffleMode::Random {
+            return order;
+        }
+
+        let keys: Vec<Option<String>> = self
+            .queue
+            .iter()
+            .map(|idx| {
+                self.tracks
+                    .get(*idx)
+                    .map(|track| normalized_path_key(&track.path))
+            })
+            .collect();
+        if self.shuffle_mode == ShuffleMode::FewerPlays {
+            // Weighted shuffle: a key of u^(plays + 1) makes each extra play
+            // less likely to land near the front without ever ruling it out.
+            let mut weighted: Vec<(f64, usize)> = Vec::with_capacity(order.len());
+            for pos in &order {
+                let plays = keys[*pos]
+                    .as_ref()
+                    .and_then(|key| self.play_counts.get(key))
+                    .copied()
+                    .unwrap_or(0);
+                let draw = rng.random::<f64>();
+                weighted.push((draw.powf(f64::from(plays) + 1.0), *pos));
+            }
+            weighted.sort_by(|a, b| b.0.total_cmp(&a.0));
+            order = weighted.into_iter().map(|(_, pos)| pos).collect();
+        }
+
+        // Age 0 is the newest play inside the recency window.
+        let window = (self.queue.len() * usize::from(self.shuffle_recent_percent)).div_ceil(100);
+        let mut ages: HashMap<&str, usize> = HashMap::new();
+        for (age, key) in self.recent_plays.iter().rev().take(window).enumerate() {
+            ages.entry(key.as_str()).or_insert(age);
+        }
+        let age_of = |pos: usize| keys[pos].as_deref().and_then(|key| ages.get(key).copied());
+
+        // The playing entry leads so the order continues from it; recent tracks
+        // trail, the longest ago first.
+        let current = current.filter(|idx| *idx < self.queue.len());
+        let (mut recent, fresh): (Vec<usize>, Vec<usize>) = order
+            .iter()
+            .copied()
+            .filter(|pos| Some(*pos) != current)
+            .partition(|pos| age_of(*pos).is_some());
+        recent.sort_by_key(|pos| std::cmp::Reverse(age_of(*pos)));
+        current.into_iter().chain(fresh).chain(recent).collect()
+    }
+
     fn set_status(&mut self, message: &str) {
         self.status = message.to_string();
         self.dirty = true;
     }
 }
 
+/// Case-insensitive subsequence match of `needle` (already lowercased) in
+/// `haystack`, tried from every place the first letter occurs. Runs of
+/// consecutive letters and letters at the start of a word score higher, and
+/// matches that begin late score a little lower. `None` when some letter of the
+/// needle is missing.
+fn fuzzy_match_score(needle: &[char], haystack: &str) -> Option<i64> {
+    let first = *needle.first()?;
+    let haystack: Vec<char> = haystack.chars().flat_map(char::to_lowercase).collect();
+    (0..haystack.len())
+        .filter(|start| haystack[*start] == first)
+        .filter_map(|start| fuzzy_match_score_from(needle, &haystack, start))
+        .max()
+}
+
+fn fuzzy_match_score_from(needle: &[char], haystack: &[char], start: usize) -> Option<i64> {
+    let mut wanted = needle.iter().peekable();
+    let mut score = -(start.min(10) as i64);
+    let mut previous_matched = false;
+    let mut previous_char = start
+        .checked_sub(1)
+        .map_or(' ', |previous| haystack[previous]);
+    for &ch in &haystack[start..] {
+        let Some(&&next) = wanted.peek() else {
+            break;
+        };
+        let matched = next == ch;
+        if matched {
+            wanted.next();
+            score += 1;
+            if previous_matched {
+                score += 5;
+            }
+            if !previous_char.is_alphanumeric() {
+                score += 8;
+            }
+        }
+        previous_matched = matched;
+        previous_char = ch;
+    }
+    wanted.peek().is_none().then_some(score)
+}
+
+/// Lowercased rule text, or `None` when the rule leaves it blank.
+fn smart_rule_filter(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_lowercase)
+}
+
+fn queue_text_field(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| if ch.is_control() { ' ' } else { ch })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn format_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, ch) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn format_library_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    if unit == "B" || value >= 10.0 {
+        format!("{value:.0} {unit}")
+    } else {
+        format!("{value:.1} {unit}")
+    }
+}
+
+fn format_library_play_time(seconds: u64) -> String {
+    let days = seconds / 86_400;
+    let hours = (seconds % 86_400) / 3600;
+    let mins = (seconds % 3600) / 60;
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else {
+        format!("{mins}m")
+    }
+}
+
+pub fn arrow_keys_label(seek: bool) -> &'static str {
+    if seek {
+        "Seek while playing"
+    } else {
+        "Navigate"
+    }
+}
+
+pub fn compact_layout_label(setting: Option<bool>) -> &'static str {
+    match setting {
+        None => "Auto (compact on small terminals)",
+        Some(true) => "Compact",
+        Some(false) => "Full",
+    }
+}
+
+/// `60 Hz: +3 dB`, `3.6 kHz: 0 dB`.
+pub fn equalizer_band_label(frequency_hz: f32, gain_db: f32) -> String {
+    let frequency = if frequency_hz >= 1_000.0 {
+        let khz = format!("{:.1}", frequency_hz / 1_000.0);
+        format!("{} kHz", khz.trim_end_matches(".0"))
+    } else {
+        format!("{frequency_hz:.0} Hz")
+    };
+    if gain_db == 0.0 {
+        format!("{frequency}: 0 dB")
+    } else {
+        format!("{frequency}: {gain_db:+.0} dB")
+    }
+}
+
+/// Falls back to the flat default bands when none were saved and keeps gains
+/// inside the range the settings panel can reach.
+fn normalize_equalizer_bands(bands: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    if bands.is_empty() {
+        return default_equalizer_bands();
+    }
+    bands
+        .into_iter()
+        .map(|(frequency, gain)| {
+            let gain = if gain.is_finite() {
+                gain.clamp(-EQUALIZER_MAX_GAIN_DB, EQUALIZER_MAX_GAIN_DB)
+            } else {
+                0.0
+            };
+            (frequency, gain)
+        })
+        .collect()
+}
+
+pub fn stats_completion_label(percent: u8) -> String {
+    if percent == 0 {
+        String::from("Last second")
+    } else {
+        format!("{percent}% or last second")
+    }
+}
+
+pub fn stats_grace_label(seconds: u16) -> String {
+    if seconds == 0 {
+        String::from("Off")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// "Center", "30% left", "100% right".
+pub fn balance_label(balance: f32) -> String {
+    let percent = (balance.abs() * 100.0).round() as u8;
+    match percent {
+        0 => String::from("Center"),
+        _ if balance < 0.0 => format!("{percent}% left"),
+        _ => format!("{percent}% right"),
+    }
+}
+
+/// "1x", "1.25x", "0.5x".
+pub fn playback_speed_label(speed: f32) -> String {
+    let label = format!("{speed:.2}");
+    let label = label.trim_end_matches('0').trim_end_matches('.');
+    format!("{label}x")
+}
+
+pub fn crossfade_lead_label(ms: u16) -> String {
+    match ms {
+        0 => String::from("Off"),
+        ms if ms % 1_000 == 0 => format!("{}s", ms / 1_000),
+        ms => format!("{:.2}s", f32::from(ms) / 1_000.0)
+            .trim_end_matches('0')
+            .to_string(),
+    }
+}
+
+pub fn daily_listen_goal_label(minutes: u16) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => String::from("Off"),
+        (0, mins) => format!("{mins}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, mins) => format!("{hours}h {mins}m"),
+    }
+}
+
+pub fn track_gap_label(seconds: u16) -> String {
+    if seconds == 0 {
+        String::from("Off")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+pub fn sleep_on_silence_label(minutes: u16) -> String {
+    if minutes == 0 {
+        String::from("Off")
+    } else {
+        format!("{minutes} min")
+    }
+}
+
 fn is_audio_file(path: &Path) -> bool {
-    const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "wav", "ogg", "m4a", "aac", "opus"];
+    const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "wav", "ogg", "m4a", "aac", "opus", "wv"];
     let ext = path.extension().and_then(OsStr::to_str).unwrap_or_default();
     AUDIO_EXTENSIONS
         .iter()
@@ -2371,6 +4906,50 @@ mod tests {
     use crate::model::Playlist;
     use proptest::prop_assert;
 
+    #[test]
+    fn equalizer_bands_restore_clamped_and_default_when_missing() {
+        let state = PersistedState {
+            equalizer_bands: vec![(60.0, 30.0), (3_600.0, f32::NAN)],
+            ..PersistedState::default()
+        };
+        let core = TuneCore::from_persisted(state);
+        assert_eq!(core.equalizer_bands, vec![(60.0, 12.0), (3_600.0, 0.0)]);
+
+        let state = PersistedState {
+            equalizer_bands: Vec::new(),
+            ..PersistedState::default()
+        };
+        let core = TuneCore::from_persisted(state);
+        assert_eq!(core.equalizer_bands, default_equalizer_bands());
+        assert_eq!(equalizer_band_label(3_600.0, -6.0), "3.6 kHz: -6 dB");
+        assert_eq!(equalizer_band_label(14_000.0, 0.0), "14 kHz: 0 dB");
+    }
+
+    #[test]
+    fn playback_speed_cycles_common_values_and_wraps() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        assert_eq!(core.playback_speed, 1.0);
+        core.cycle_playback_speed(true);
+        assert_eq!(core.playback_speed, 1.25);
+        assert_eq!(core.status, "Playback speed: 1.25x");
+        core.playback_speed = 2.0;
+        core.cycle_playback_speed(true);
+        assert_eq!(core.playback_speed, 0.5);
+        core.cycle_playback_speed(false);
+        assert_eq!(core.playback_speed, 2.0);
+        core.playback_speed = 1.1;
+        core.cycle_playback_speed(false);
+        assert_eq!(core.playback_speed, 1.0);
+        assert_eq!(playback_speed_label(1.0), "1x");
+        assert_eq!(playback_speed_label(0.75), "0.75x");
+
+        let state = PersistedState {
+            playback_speed: 9.0,
+            ..PersistedState::default()
+        };
+        assert_eq!(TuneCore::from_persisted(state).playback_speed, 3.0);
+    }
+
     #[test]
     fn loop_mode_wraps() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -2398,23 +4977,226 @@ mod tests {
     }
 
     #[test]
-    fn set_header_section_updates_status() {
+    fn m3u_export_writes_extinf_with_artist_title_and_unknown_duration() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let song = temp.path().join("song.flac");
+        fs::write(&song, b"not audio").expect("write song");
         let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![
+            Track {
+                path: song.clone(),
+                title: String::from("Song"),
+                artist: Some(String::from("Band")),
+                album: None,
+            },
+            Track {
+                path: temp.path().join("bare.mp3"),
+                title: String::from("Bare"),
+                artist: None,
+                album: None,
+            },
+        ];
+        core.track_lookup = build_track_lookup(&core.tracks);
+        core.cache_duration_seconds_for_path(&core.tracks[1].path.clone(), Some(215));
+        core.queue = vec![0, 1];
 
-        core.set_header_section(HeaderSection::Online);
+        let out = temp.path().join("queue.m3u");
+        core.export_queue_m3u(&out);
+        let text = fs::read_to_string(&out).expect("read m3u");
+        assert_eq!(
+            text,
+            format!(
+                "#EXTM3U\n#EXTINF:-1,Band - Song\n{}\n#EXTINF:215,Bare\n{}\n",
+                config::normalize_path(&song).display(),
+                temp.path().join("bare.mp3").display()
+            )
+        );
+        assert!(core.status.starts_with("Exported 2 track(s) to "));
 
-        assert_eq!(core.header_section, HeaderSection::Online);
-        assert_eq!(core.status, "Section: Online");
+        core.playlists.insert(
+            String::from("mix"),
+            Playlist {
+                tracks: vec![song.clone()],
+            },
+        );
+        let out = temp.path().join("mix.m3u");
+        core.export_playlist_m3u("mix", &out);
+        assert!(
+            fs::read_to_string(&out)
+                .expect("read playlist m3u")
+                .contains("#EXTINF:-1,Band - Song\n")
+        );
+        core.export_playlist_m3u("missing", &out);
+        assert_eq!(core.status, "Playlist not found");
     }
 
     #[test]
-    fn reload_track_metadata_falls_back_to_file_stem_for_missing_tags() {
+    fn playlist_import_reuses_library_paths_and_reports_missing_files() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let music = config::normalize_path(temp.path());
+        fs::write(music.join("known.flac"), b"x").expect("write known");
+        fs::write(music.join("extra.mp3"), b"x").expect("write extra");
         let mut core = TuneCore::from_persisted(PersistedState::default());
         core.tracks = vec![Track {
-            path: PathBuf::from("new-title.mp3"),
-            title: String::from("Old"),
-            artist: Some(String::from("Artist")),
-            album: Some(String::from("Album")),
+            path: music.join("known.flac"),
+            title: String::from("Known"),
+            artist: None,
+            album: None,
+        }];
+        core.track_lookup = build_track_lookup(&core.tracks);
+        core.playlists
+            .insert(String::from("Party"), Playlist::default());
+        let list = music.join("Party.m3u");
+        fs::write(
+            &list,
+            "#EXTM3U\n./known.flac\nextra.mp3\ngone.mp3\nlost.ogg\n",
+        )
+        .expect("write m3u");
+
+        core.import_playlist_file(&list);
+        let playlist = core.playlists.get("Party (2)").expect("imported playlist");
+        assert_eq!(
+            playlist.tracks,
+            vec![music.join("known.flac"), music.join("extra.mp3")]
+        );
+        assert_eq!(core.tracks.len(), 1);
+        assert_eq!(
+            core.status,
+            "Imported 2 track(s) into playlist Party (2); 2 missing: gone.mp3, ..."
+        );
+
+        core.import_playlist_file(&music.join("known.flac"));
+        assert_eq!(core.status, "Not an M3U or PLS playlist");
+    }
+
+    #[test]
+    fn queue_text_round_trips_by_metadata_and_marks_missing_tracks() {
+        let track = |path: &str, title: &str, artist: &str, album: &str| Track {
+            path: PathBuf::from(path),
+            title: String::from(title),
+            artist: Some(String::from(artist)),
+            album: Some(String::from(album)),
+        };
+        let mut sender = TuneCore::from_persisted(PersistedState::default());
+        sender.tracks = vec![
+            track("/them/one.flac", "One", "Band", "Live"),
+            track("/them/two.flac", "Two", "Band", "Studio"),
+            track("/them/rare.flac", "Rare", "Other", "B-Sides"),
+        ];
+        sender.queue = vec![1, 0, 2];
+        let text = sender.export_queue_text();
+        assert!(text.starts_with("# TuneTUI queue"));
+        assert!(text.contains("Two\tBand\tStudio\n"));
+
+        let mut receiver = TuneCore::from_persisted(PersistedState::default());
+        receiver.tracks = vec![
+            track("/me/one-studio.mp3", "One", "Band", "Studio"),
+            track("/me/one-live.mp3", "one", "band", "live"),
+            track("/me/two.mp3", "Two", "Band", "Studio"),
+        ];
+        receiver.track_lookup = build_track_lookup(&receiver.tracks);
+
+        let result = receiver.import_queue_text(&text);
+
+        assert_eq!(result.matched, 2);
+        assert_eq!(result.not_found, vec![String::from("Rare - Other")]);
+        assert_eq!(receiver.queue, vec![2, 1]);
+        assert_eq!(
+            receiver.status,
+            "Imported 2 queue track(s); 1 not found: Rare - Other"
+        );
+    }
+
+    #[test]
+    fn folder_enter_behavior_plays_folder_and_shift_flips_it() {
+        let track = |path: &str| Track {
+            path: PathBuf::from(path),
+            title: String::from("t"),
+            artist: None,
+            album: None,
+        };
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![
+            track("/music/album/02.mp3"),
+            track("/music/other/01.mp3"),
+            track("/music/album/01.mp3"),
+        ];
+        core.track_lookup = build_track_lookup(&core.tracks);
+        let folder_entry = BrowserEntry {
+            kind: BrowserEntryKind::Folder,
+            path: PathBuf::from("/music/album"),
+            label: String::from("album"),
+        };
+        core.browser_entries = vec![folder_entry.clone()];
+        core.selected_browser = 0;
+        core.folder_enter_behavior = FolderEnterBehavior::Play;
+
+        let played = core.activate_selected();
+
+        assert_eq!(played, Some(PathBuf::from("/music/album/01.mp3")));
+        assert_eq!(core.queue, vec![2, 0]);
+        assert_eq!(core.current_queue_index, Some(0));
+        assert_eq!(core.status, "Playing folder: album");
+
+        core.browser_entries = vec![folder_entry];
+        core.selected_browser = 0;
+        assert_eq!(core.activate_selected_with(true), None);
+        assert_eq!(core.browser_path, Some(PathBuf::from("/music/album")));
+    }
+
+    #[test]
+    fn shuffle_shared_queue_keeps_playing_front_item_and_is_host_only() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut session = crate::online::OnlineSession::host("host");
+        for idx in 0..8 {
+            session.push_shared_track(Path::new(&format!("{idx}.flac")), idx.to_string(), None);
+        }
+        session.last_transport = Some(crate::online::TransportEnvelope {
+            seq: 1,
+            origin_nickname: String::from("host"),
+            command: TransportCommand::PlayTrack {
+                path: PathBuf::from("0.flac"),
+                title: None,
+                artist: None,
+                album: None,
+                provider_track_id: None,
+            },
+        });
+        core.online.session = Some(session);
+
+        let order = core.shuffle_shared_queue().expect("host can shuffle");
+
+        assert_eq!(order[0], 0);
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..8).collect::<Vec<_>>());
+        let session = core.online.session.as_ref().expect("session");
+        assert_eq!(session.shared_queue[0].title, "0");
+        assert_eq!(core.status, "Shuffled shared queue");
+
+        core.online.session.as_mut().expect("session").participants[0].is_host = false;
+        assert!(core.shuffle_shared_queue().is_none());
+        assert_eq!(core.status, "Only host can shuffle the shared queue");
+    }
+
+    #[test]
+    fn set_header_section_updates_status() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+
+        core.set_header_section(HeaderSection::Online);
+
+        assert_eq!(core.header_section, HeaderSection::Online);
+        assert_eq!(core.status, "Section: Online");
+    }
+
+    #[test]
+    fn reload_track_metadata_falls_back_to_file_stem_for_missing_tags() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![Track {
+            path: PathBuf::from("new-title.mp3"),
+            title: String::from("Old"),
+            artist: Some(String::from("Artist")),
+            album: Some(String::from("Album")),
         }];
         core.track_lookup = build_track_lookup(&core.tracks);
         core.queue = vec![0];
@@ -2444,6 +5226,257 @@ mod tests {
         assert_eq!(core.cached_duration_seconds_for_path(unknown), None);
     }
 
+    #[test]
+    fn library_summary_counts_tracks_bytes_and_cached_play_time() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.folders = vec![LibraryFolder::new(PathBuf::from("music"))];
+        core.tracks = (0..1200)
+            .map(|index| Track {
+                path: PathBuf::from(format!("music/{index}.mp3")),
+                title: format!("{index}"),
+                artist: None,
+                album: None,
+            })
+            .collect();
+        assert_eq!(
+            core.library_summary(None),
+            "Indexed 1,200 tracks across 1 folder"
+        );
+
+        core.cache_duration_seconds_for_path(Path::new("music/0.mp3"), Some(90_000));
+        assert_eq!(
+            core.library_summary(Some(5 * 1024 * 1024 * 1024)),
+            "Indexed 1,200 tracks across 1 folder (5.0 GB, at least 1d 1h total)"
+        );
+    }
+
+    #[test]
+    fn no_crossfade_flag_toggles_and_persists() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let path = Path::new("album/track.flac");
+
+        assert!(core.toggle_track_crossfade_disabled(path));
+        assert!(core.track_crossfade_disabled(path));
+        let restored = TuneCore::from_persisted(core.persisted_state());
+        assert!(restored.track_crossfade_disabled(path));
+
+        assert!(!core.toggle_track_crossfade_disabled(path));
+        assert!(!core.track_crossfade_disabled(path));
+    }
+
+    #[test]
+    fn last_online_join_persists_password_only_when_opted_in() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.last_online_join = Some(LastOnlineJoin {
+            server_addr: String::from("10.0.0.2:7878"),
+            room_name: String::from("Friday"),
+            password: Some(String::from("secret")),
+            sealed_password: None,
+        });
+
+        let saved = core.persisted_state().last_online_join.expect("last join");
+        assert_eq!(saved.room_name, "Friday");
+        assert_eq!(saved.password, None);
+
+        core.toggle_remember_online_password();
+        let saved = core.persisted_state().last_online_join.expect("last join");
+        assert_eq!(saved.password.as_deref(), Some("secret"));
+    }
+
+    fn track_at(path: &str, artist: Option<&str>) -> Track {
+        Track {
+            path: PathBuf::from(path),
+            title: path.to_string(),
+            artist: artist.map(str::to_string),
+            album: None,
+        }
+    }
+
+    #[test]
+    fn end_of_queue_stop_returns_nothing() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![track_at("a/1.mp3", None)];
+        core.queue = vec![0];
+        core.current_queue_index = Some(0);
+
+        assert_eq!(core.end_of_queue_track_path(&[]), None);
+    }
+
+    #[test]
+    fn end_of_queue_repeat_restarts_from_first_track() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![track_at("a/1.mp3", None), track_at("a/2.mp3", None)];
+        core.queue = vec![0, 1];
+        core.current_queue_index = Some(1);
+        core.end_of_queue_behavior = EndOfQueueBehavior::RepeatQueue;
+
+        assert_eq!(core.next_track_path(), None);
+        assert_eq!(
+            core.end_of_queue_track_path(&[]),
+            Some(PathBuf::from("a/1.mp3"))
+        );
+        assert_eq!(core.current_queue_index, Some(0));
+    }
+
+    #[test]
+    fn end_of_queue_keep_going_appends_from_selected_pool() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![
+            track_at("a/1.mp3", Some("Band")),
+            track_at("a/2.mp3", Some("Other")),
+            track_at("b/3.mp3", Some("band")),
+        ];
+        core.queue = vec![0];
+        core.current_queue_index = Some(0);
+        core.end_of_queue_behavior = EndOfQueueBehavior::KeepGoing;
+
+        assert_eq!(
+            core.end_of_queue_track_path(&[]),
+            Some(PathBuf::from("a/2.mp3"))
+        );
+        assert_eq!(core.queue, vec![0, 1]);
+        assert_eq!(core.current_queue_index, Some(1));
+
+        core.queue = vec![0];
+        core.current_queue_index = Some(0);
+        core.keep_going_pool = KeepGoingPool::SameArtist;
+        assert_eq!(
+            core.end_of_queue_track_path(&[]),
+            Some(PathBuf::from("b/3.mp3"))
+        );
+        assert_eq!(core.queue, vec![0, 2]);
+    }
+
+    #[test]
+    fn end_of_queue_radio_prefers_recent_artist_and_skips_recent_plays() {
+        let listen = |path: &str, artist: &str| ListenEvent {
+            track_path: PathBuf::from(path),
+            title: String::from(path),
+            artist: Some(String::from(artist)),
+            album: None,
+            provider_track_id: None,
+            started_at_epoch_seconds: 0,
+            listened_seconds: 200,
+            counted_play: true,
+            completed: None,
+            duration_seconds: None,
+        };
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![
+            track_at("a/1.mp3", Some("Band")),
+            track_at("a/2.mp3", Some("Band")),
+            track_at("b/3.mp3", Some("Other")),
+            track_at("b/4.mp3", Some("band")),
+        ];
+        core.queue = vec![0];
+        core.current_queue_index = Some(0);
+        core.end_of_queue_behavior = EndOfQueueBehavior::Radio;
+        let history = vec![listen("a/2.mp3", "Band")];
+
+        assert_eq!(
+            core.end_of_queue_track_path(&history),
+            Some(PathBuf::from("b/4.mp3"))
+        );
+        assert_eq!(core.queue, vec![0, 3, 2]);
+        assert_eq!(core.current_queue_index, Some(1));
+        assert!(core.status.starts_with("Radio added 2 track(s)"));
+    }
+
+    #[test]
+    fn rescan_during_playback_keeps_same_next_track() {
+        for shuffle in [false, true] {
+            let mut core = TuneCore::from_persisted(PersistedState::default());
+            let tracks: Vec<Track> = (0..8)
+                .map(|n| track_at(&format!("lib/{n}.mp3"), None))
+                .collect();
+            core.replace_library_tracks(tracks.clone());
+            core.set_shuffle_enabled(shuffle);
+            core.reset_main_queue();
+            let playing = core.next_track_path().expect("first track");
+            let expected_next = core.peek_next_track_path(&[]);
+
+            core.replace_library_tracks(tracks);
+
+            assert_eq!(core.current_path(), Some(playing.as_path()));
+            assert_eq!(core.next_track_path(), expected_next);
+        }
+    }
+
+    #[test]
+    fn reset_main_queue_re_resolves_pinned_track() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.replace_library_tracks(vec![
+            track_at("lib/a.mp3", None),
+            track_at("lib/b.mp3", None),
+        ]);
+        core.reset_main_queue();
+        core.next_track_path();
+        let playing = core.next_track_path().expect("second track");
+
+        core.reset_main_queue();
+
+        assert_eq!(core.current_path(), Some(playing.as_path()));
+    }
+
+    #[test]
+    fn peek_next_track_path_does_not_advance_queue() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![
+            Track {
+                path: PathBuf::from("a.mp3"),
+                title: String::from("a"),
+                artist: None,
+                album: None,
+            },
+            Track {
+                path: PathBuf::from("b.mp3"),
+                title: String::from("b"),
+                artist: None,
+                album: None,
+            },
+        ];
+        core.queue = vec![0, 1];
+        core.current_queue_index = Some(0);
+
+        assert_eq!(core.peek_next_track_path(&[]), Some(PathBuf::from("b.mp3")));
+        assert_eq!(core.current_queue_index, Some(0));
+
+        core.current_queue_index = Some(1);
+        assert_eq!(core.peek_next_track_path(&[]), None);
+        core.repeat_mode = RepeatMode::All;
+        assert_eq!(core.peek_next_track_path(&[]), Some(PathBuf::from("a.mp3")));
+    }
+
+    #[test]
+    fn peek_next_track_path_matches_stale_shuffle_and_end_of_queue_picks() {
+        let tracks: Vec<Track> = (0..12)
+            .map(|n| track_at(&format!("lib/{n}.mp3"), Some("Band")))
+            .collect();
+        for behavior in [
+            EndOfQueueBehavior::Stop,
+            EndOfQueueBehavior::RepeatQueue,
+            EndOfQueueBehavior::KeepGoing,
+            EndOfQueueBehavior::Radio,
+        ] {
+            for shuffle in [false, true] {
+                let mut core = TuneCore::from_persisted(PersistedState::default());
+                core.tracks = tracks.clone();
+                core.queue = vec![0, 1, 2];
+                core.current_queue_index = Some(0);
+                core.shuffle_enabled = shuffle;
+                core.end_of_queue_behavior = behavior;
+
+                for _ in 0..8 {
+                    let peeked = core.peek_next_track_path(&[]);
+                    let next = core
+                        .next_track_path()
+                        .or_else(|| core.end_of_queue_track_path(&[]));
+                    assert_eq!(peeked, next, "{behavior:?}, shuffle {shuffle}");
+                }
+            }
+        }
+    }
+
     #[test]
     fn invalid_stats_top_songs_count_defaults_to_ten() {
         let state = PersistedState {
@@ -2458,7 +5491,9 @@ mod tests {
     #[test]
     fn root_browser_uses_folders() {
         let mut state = PersistedState::default();
-        state.folders.push(PathBuf::from(r"E:\LOCALMUSIC"));
+        state
+            .folders
+            .push(LibraryFolder::new(PathBuf::from(r"E:\LOCALMUSIC")));
         let core = TuneCore::from_persisted(state);
         assert!(
             core.browser_entries
@@ -2467,6 +5502,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn disabled_folder_is_hidden_and_skipped_by_rescan() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let root = temp.path().join("music");
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::write(root.join("song.mp3"), b"not really audio").expect("write track");
+        let mut state = PersistedState::default();
+        state
+            .folders
+            .push(LibraryFolder::new(config::normalize_path(&root)));
+        let mut core = TuneCore::from_persisted(state);
+        let root = core.folders[0].path.clone();
+        assert_eq!(core.tracks.len(), 1);
+
+        assert_eq!(core.toggle_folder_enabled(&root), Some(false));
+        assert!(core.tracks.is_empty());
+        assert!(
+            !core
+                .browser_entries
+                .iter()
+                .any(|entry| entry.kind == BrowserEntryKind::Folder)
+        );
+        core.rescan();
+        assert!(core.tracks.is_empty());
+        assert_eq!(core.folders.len(), 1);
+
+        assert_eq!(core.toggle_folder_enabled(&root), Some(true));
+        core.rescan();
+        assert_eq!(core.tracks.len(), 1);
+    }
+
     #[test]
     fn add_folder_sanitizes_leading_bullet_character() {
         let temp = tempfile::tempdir().expect("tempdir");
@@ -2477,7 +5543,11 @@ mod tests {
         let mut core = TuneCore::from_persisted(PersistedState::default());
         core.add_folder(&copied);
 
-        assert!(core.folders.iter().any(|folder| path_eq(folder, &real)));
+        assert!(
+            core.folders
+                .iter()
+                .any(|folder| path_eq(&folder.path, &real))
+        );
     }
 
     #[test]
@@ -2490,7 +5560,11 @@ mod tests {
         let mut core = TuneCore::from_persisted(PersistedState::default());
         core.add_folder(&copied);
 
-        assert!(core.folders.iter().any(|folder| path_eq(folder, &real)));
+        assert!(
+            core.folders
+                .iter()
+                .any(|folder| path_eq(&folder.path, &real))
+        );
     }
 
     #[test]
@@ -2509,7 +5583,11 @@ mod tests {
         let mut core = TuneCore::from_persisted(PersistedState::default());
         core.add_folder(&copied);
 
-        assert!(core.folders.iter().any(|folder| path_eq(folder, &real)));
+        assert!(
+            core.folders
+                .iter()
+                .any(|folder| path_eq(&folder.path, &real))
+        );
     }
 
     #[test]
@@ -2521,7 +5599,11 @@ mod tests {
         let mut core = TuneCore::from_persisted(PersistedState::default());
         core.add_folder(&real);
 
-        assert!(core.folders.iter().any(|folder| path_eq(folder, &real)));
+        assert!(
+            core.folders
+                .iter()
+                .any(|folder| path_eq(&folder.path, &real))
+        );
     }
 
     #[test]
@@ -2534,13 +5616,19 @@ mod tests {
         let mut core = TuneCore::from_persisted(PersistedState::default());
         core.add_folder(&copied);
 
-        assert!(core.folders.iter().any(|folder| path_eq(folder, &real)));
+        assert!(
+            core.folders
+                .iter()
+                .any(|folder| path_eq(&folder.path, &real))
+        );
     }
 
     #[test]
     fn remove_folder_removes_matching_entry() {
         let mut state = PersistedState::default();
-        state.folders.push(PathBuf::from(r"E:\LOCALMUSIC"));
+        state
+            .folders
+            .push(LibraryFolder::new(PathBuf::from(r"E:\LOCALMUSIC")));
         let mut core = TuneCore::from_persisted(state);
 
         core.remove_folder(Path::new(r"E:\LOCALMUSIC"));
@@ -2590,30 +5678,187 @@ mod tests {
                 .any(|entry| entry.kind == BrowserEntryKind::QueueShared)
         );
 
-        core.online_host_room("dj");
-
-        assert!(
-            core.browser_entries
-                .iter()
-                .any(|entry| entry.kind == BrowserEntryKind::QueueShared)
+        core.online_host_room("dj");
+
+        assert!(
+            core.browser_entries
+                .iter()
+                .any(|entry| entry.kind == BrowserEntryKind::QueueShared)
+        );
+    }
+
+    #[test]
+    fn root_browser_includes_playlists() {
+        let mut state = PersistedState::default();
+        state.playlists.insert(
+            String::from("mix"),
+            Playlist {
+                tracks: vec![PathBuf::from("song.mp3")],
+            },
+        );
+        let core = TuneCore::from_persisted(state);
+        assert!(
+            core.browser_entries
+                .iter()
+                .any(|entry| entry.kind == BrowserEntryKind::Playlist && entry.label == "[PL] mix")
+        );
+    }
+
+    #[test]
+    fn smart_playlist_ranks_plays_filters_artist_and_refreshes_on_open() {
+        let track = |path: &str, artist: &str| Track {
+            path: PathBuf::from(path),
+            title: String::from(path),
+            artist: Some(String::from(artist)),
+            album: None,
+        };
+        let play = |path: &str, at: i64| ListenEvent {
+            track_path: PathBuf::from(path),
+            title: String::from(path),
+            artist: None,
+            album: None,
+            provider_track_id: None,
+            started_at_epoch_seconds: at,
+            listened_seconds: 200,
+            counted_play: true,
+            completed: None,
+            duration_seconds: None,
+        };
+        let mut state = PersistedState::default();
+        state.smart_playlists.insert(
+            String::from("top"),
+            SmartPlaylist {
+                sort: SmartPlaylistSort::MostPlayed,
+                limit: 2,
+                artist: Some(String::from(" band ")),
+                genre: None,
+            },
+        );
+        state.smart_playlists.insert(
+            String::from("recent"),
+            SmartPlaylist {
+                sort: SmartPlaylistSort::LastPlayed,
+                limit: 10,
+                artist: None,
+                genre: None,
+            },
+        );
+        let mut core = TuneCore::from_persisted_with_tracks(
+            state,
+            vec![
+                track("a.mp3", "The Band"),
+                track("b.mp3", "Band"),
+                track("c.mp3", "Band"),
+                track("solo.mp3", "Solo"),
+            ],
+        );
+        core.set_listen_history(&[
+            play("a.mp3", 10),
+            play("b.mp3", 20),
+            play("b.mp3", 30),
+            play("c.mp3", 5),
+            play("solo.mp3", 40),
+            play("solo.mp3", 50),
+            play("solo.mp3", 60),
+        ]);
+
+        assert_eq!(
+            core.playlist_tracks("top"),
+            Some(vec![PathBuf::from("b.mp3"), PathBuf::from("a.mp3")])
+        );
+        assert_eq!(
+            core.playlist_tracks("recent").expect("recent")[..2],
+            [PathBuf::from("solo.mp3"), PathBuf::from("b.mp3")]
+        );
+        assert!(
+            core.browser_entries
+                .iter()
+                .any(|entry| entry.kind == BrowserEntryKind::Playlist
+                    && entry.label == "[SMART] top")
+        );
+
+        core.set_listen_history(&[play("c.mp3", 70), play("c.mp3", 80), play("c.mp3", 90)]);
+        assert_eq!(
+            core.playlist_tracks("top"),
+            Some(vec![PathBuf::from("b.mp3"), PathBuf::from("a.mp3")])
+        );
+        core.selected_browser = core
+            .browser_entries
+            .iter()
+            .position(|entry| entry.label == "[SMART] top")
+            .expect("smart entry");
+        core.activate_selected();
+        assert_eq!(core.browser_playlist.as_deref(), Some("top"));
+        assert_eq!(
+            core.playlist_tracks("top"),
+            Some(vec![PathBuf::from("c.mp3")])
+        );
+        assert_eq!(
+            core.browser_breadcrumbs(),
+            vec![String::from("Library"), String::from("Smart playlist: top")]
+        );
+
+        core.selected_browser = 1;
+        core.remove_selected_from_current_playlist();
+        assert_eq!(
+            core.status,
+            "Smart playlists follow their rules; tracks cannot be removed"
         );
+        core.create_playlist("top");
+        assert_eq!(core.status, "Playlist already exists");
+        core.remove_playlist("top");
+        assert!(!core.smart_playlists.contains_key("top"));
+        assert!(!core.smart_playlist_cache.borrow().contains_key("top"));
     }
 
     #[test]
-    fn root_browser_includes_playlists() {
+    fn date_added_smart_playlist_uses_the_library_index() {
+        let track = |path: &str| Track {
+            path: PathBuf::from(path),
+            title: String::from(path),
+            artist: None,
+            album: None,
+        };
         let mut state = PersistedState::default();
-        state.playlists.insert(
-            String::from("mix"),
-            Playlist {
-                tracks: vec![PathBuf::from("song.mp3")],
+        state.smart_playlists.insert(
+            String::from("new"),
+            SmartPlaylist {
+                sort: SmartPlaylistSort::DateAdded,
+                limit: 10,
+                artist: None,
+                genre: None,
             },
         );
-        let core = TuneCore::from_persisted(state);
-        assert!(
-            core.browser_entries
+        let tracks = vec![track("old.mp3"), track("new.mp3"), track("unknown.mp3")];
+        let mut index = library::LibraryIndex {
+            tracks: tracks
                 .iter()
-                .any(|entry| entry.kind == BrowserEntryKind::Playlist && entry.label == "[PL] mix")
+                .map(library::LibraryIndexEntry::from_track)
+                .collect(),
+        };
+        index.tracks[0].first_seen_unix_seconds = 100;
+        index.tracks[1].first_seen_unix_seconds = 200;
+        index.tracks.pop();
+        let mut core = TuneCore::from_persisted_with_tracks(state, tracks);
+        core.set_track_added_times(&index);
+        assert_eq!(
+            core.playlist_tracks("new"),
+            Some(vec![
+                PathBuf::from("new.mp3"),
+                PathBuf::from("old.mp3"),
+                PathBuf::from("unknown.mp3"),
+            ])
+        );
+
+        index.tracks[0].first_seen_unix_seconds = 300;
+        core.set_track_added_times(&index);
+        assert_eq!(
+            core.playlist_tracks("new").expect("new")[0],
+            PathBuf::from("old.mp3")
         );
+
+        core.remove_playlist("new");
+        assert!(core.smart_playlist_cache.borrow().is_empty());
     }
 
     #[test]
@@ -2693,7 +5938,7 @@ mod tests {
     fn navigate_back_stops_at_added_root() {
         let library_root = PathBuf::from("localmusic");
         let mut state = PersistedState::default();
-        state.folders.push(library_root.clone());
+        state.folders.push(LibraryFolder::new(library_root.clone()));
         let mut core = TuneCore::from_persisted(state);
 
         core.browser_path = Some(library_root);
@@ -2707,7 +5952,7 @@ mod tests {
         let library_root = PathBuf::from("localmusic");
         let albums_path = library_root.join("Albums");
         let mut state = PersistedState::default();
-        state.folders.push(library_root.clone());
+        state.folders.push(LibraryFolder::new(library_root.clone()));
         let mut core = TuneCore::from_persisted(state);
 
         core.browser_path = Some(albums_path);
@@ -2718,6 +5963,73 @@ mod tests {
         assert_eq!(core.browser_path, None);
     }
 
+    #[test]
+    fn breadcrumbs_follow_folder_depth_and_jump_multiple_levels() {
+        let library_root = PathBuf::from("localmusic");
+        let album_path = library_root.join("Artist").join("Album");
+        let mut state = PersistedState::default();
+        state.folders.push(LibraryFolder::new(library_root.clone()));
+        let mut core = TuneCore::from_persisted(state);
+
+        core.browser_path = Some(album_path);
+        assert_eq!(
+            core.browser_breadcrumbs(),
+            vec!["Library", "localmusic", "Artist", "Album"]
+        );
+
+        core.navigate_to_breadcrumb(1);
+        assert_eq!(core.browser_path, Some(library_root.clone()));
+        assert_eq!(core.status, "Jumped to localmusic");
+
+        core.browser_path = Some(library_root.join("Artist").join("Album"));
+        core.navigate_to_breadcrumb(0);
+        assert_eq!(core.browser_path, None);
+        assert_eq!(core.browser_breadcrumbs(), vec!["Library"]);
+    }
+
+    #[test]
+    fn fuzzy_find_ranks_word_starts_and_runs_above_scattered_letters() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![
+            Track {
+                path: PathBuf::from("01 - intro.mp3"),
+                title: String::from("Blue Monday"),
+                artist: Some(String::from("New Order")),
+                album: None,
+            },
+            Track {
+                path: PathBuf::from("bm.mp3"),
+                title: String::from("Submarine"),
+                artist: None,
+                album: Some(String::from("Yellow")),
+            },
+            Track {
+                path: PathBuf::from("rain-song.flac"),
+                title: String::from("The Rain Song"),
+                artist: Some(String::from("Led Zeppelin")),
+                album: Some(String::from("Houses of the Holy")),
+            },
+        ];
+
+        assert_eq!(core.fuzzy_find_tracks("blue mon", 10), vec![0]);
+        assert_eq!(core.fuzzy_find_tracks("bm", 10), vec![0, 1]);
+        assert_eq!(core.fuzzy_find_tracks("intro", 10), vec![0, 2]);
+        assert_eq!(core.fuzzy_find_tracks("ZEP", 10), vec![2]);
+        assert_eq!(core.fuzzy_find_tracks("bm", 1), vec![0]);
+        assert!(core.fuzzy_find_tracks("xyz", 10).is_empty());
+        assert!(core.fuzzy_find_tracks("  ", 10).is_empty());
+
+        let path = core.play_library_track(2).expect("track");
+        assert_eq!(path, PathBuf::from("rain-song.flac"));
+        assert_eq!(core.queue, vec![0, 1, 2]);
+        assert_eq!(core.current_queue_index, Some(2));
+        assert_eq!(core.status, "Playing The Rain Song");
+
+        core.queue_library_track(0);
+        assert_eq!(core.queue, vec![0, 1, 2, 0]);
+        assert_eq!(core.status, "Queued Blue Monday");
+    }
+
     #[test]
     fn shuffle_visits_each_track_before_repeat() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -2761,6 +6073,55 @@ mod tests {
         assert_eq!(core.next_track_path(), None);
     }
 
+    #[test]
+    fn smart_shuffle_puts_recent_plays_last_and_can_favor_fewer_plays() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = (0..10)
+            .map(|n| Track {
+                path: PathBuf::from(format!("{n}.mp3")),
+                title: n.to_string(),
+                artist: None,
+                album: None,
+            })
+            .collect();
+        core.queue = (0..10).collect();
+        for n in [3, 7] {
+            core.pin_track(Some(PathBuf::from(format!("{n}.mp3"))));
+        }
+        core.current_queue_index = Some(5);
+
+        for _ in 0..20 {
+            core.rebuild_shuffle_order();
+            assert_eq!(core.shuffle_order[0], 5);
+            assert_eq!(&core.shuffle_order[8..], &[3, 7]);
+        }
+
+        core.shuffle_mode = ShuffleMode::FewerPlays;
+        core.current_queue_index = None;
+        let history: Vec<ListenEvent> = (0..50)
+            .map(|_| ListenEvent {
+                track_path: PathBuf::from("0.mp3"),
+                title: String::from("0"),
+                artist: None,
+                album: None,
+                provider_track_id: None,
+                started_at_epoch_seconds: 0,
+                listened_seconds: 200,
+                counted_play: true,
+                completed: None,
+                duration_seconds: None,
+            })
+            .collect();
+        core.set_listen_history(&history);
+        let mut heavy_in_front_half = 0;
+        for _ in 0..200 {
+            core.rebuild_shuffle_order();
+            let pos = core.shuffle_order.iter().position(|pos| *pos == 0);
+            heavy_in_front_half += usize::from(pos.is_some_and(|pos| pos < 4));
+        }
+        assert!(heavy_in_front_half < 20, "{heavy_in_front_half}");
+    }
+
     #[test]
     fn enabling_shuffle_rebuilds_existing_order() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -3105,6 +6466,41 @@ mod tests {
         assert_eq!(core.status, "added to queue");
     }
 
+    #[test]
+    fn host_only_listener_queue_add_becomes_a_capped_request() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut session = crate::online::OnlineSession::join("ROOM22", "fan");
+        session.mode = crate::online::OnlineRoomMode::HostOnly;
+        session.push_shared_track(
+            Path::new("old.mp3"),
+            String::from("old"),
+            Some("fan".into()),
+        );
+        core.online.session = Some(session);
+
+        let paths: Vec<PathBuf> = ["a.mp3", "b.mp3", "c.mp3"].map(PathBuf::from).to_vec();
+        let requested = core.online_queue_paths(&paths);
+
+        assert_eq!(requested.len(), 2);
+        assert!(
+            requested
+                .iter()
+                .all(|item| item.owner_nickname.as_deref() == Some("fan"))
+        );
+        assert_eq!(core.status, "Requested 2 tracks from the host");
+        let session = core.online.session.as_mut().expect("online session");
+        assert_eq!(session.shared_queue.len(), 1);
+
+        for item in requested {
+            session.shared_queue.push_back(item);
+        }
+        assert!(core.online_queue_paths(&paths).is_empty());
+        assert_eq!(
+            core.status,
+            "You already have 3 requests in the shared queue"
+        );
+    }
+
     #[test]
     fn remove_selected_from_current_playlist_removes_track() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -3174,6 +6570,108 @@ mod tests {
         assert_eq!(core.queue, vec![0, 0]);
     }
 
+    #[test]
+    fn remove_from_queue_shifts_current_index_and_reports_removing_it() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.queue = vec![10, 11, 12];
+        core.current_queue_index = Some(1);
+
+        assert_eq!(core.remove_from_queue(0), Some(false));
+        assert_eq!(core.current_queue_index, Some(0));
+        assert_eq!(core.remove_from_queue(1), Some(false));
+        assert_eq!(core.current_queue_index, Some(0));
+        assert_eq!(core.remove_from_queue(1), None);
+        assert_eq!(core.remove_from_queue(0), Some(true));
+        assert!(core.queue.is_empty());
+        assert_eq!(core.current_queue_index, None);
+    }
+
+    #[test]
+    fn move_queue_item_keeps_current_index_on_the_playing_track() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.queue = vec![10, 11, 12, 13];
+        core.current_queue_index = Some(2);
+
+        assert!(core.move_queue_item(0, 3));
+        assert_eq!(core.queue, vec![11, 12, 13, 10]);
+        assert_eq!(core.current_queue_index, Some(1));
+
+        assert!(core.move_queue_item(3, 0));
+        assert_eq!(core.current_queue_index, Some(2));
+
+        assert!(core.move_queue_item(2, 1));
+        assert_eq!(core.queue, vec![10, 12, 11, 13]);
+        assert_eq!(core.current_queue_index, Some(1));
+
+        assert!(!core.move_queue_item(1, 4));
+    }
+
+    #[test]
+    fn shift_selected_local_queue_item_moves_it_and_keeps_it_selected() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = ["a", "b", "c"]
+            .into_iter()
+            .map(|name| Track {
+                path: PathBuf::from(format!("{name}.mp3")),
+                title: String::from(name),
+                artist: None,
+                album: None,
+            })
+            .collect();
+        core.track_lookup = build_track_lookup(&core.tracks);
+        core.queue = vec![0, 1, 2];
+        core.current_queue_index = Some(0);
+        core.open_local_queue_view();
+        core.selected_browser = 1;
+
+        core.shift_selected_local_queue_item(1);
+
+        assert_eq!(core.queue, vec![1, 0, 2]);
+        assert_eq!(core.current_queue_index, Some(1));
+        assert_eq!(core.selected_browser, 2);
+        assert_eq!(core.browser_entries[2].label, "a");
+        assert_eq!(core.status, "Moved queue item to 2/3");
+
+        core.selected_browser = 1;
+        core.shift_selected_local_queue_item(-1);
+        assert_eq!(core.queue, vec![1, 0, 2]);
+        assert_eq!(core.status, "Queue item already first");
+    }
+
+    #[test]
+    fn shift_selected_shared_queue_item_returns_the_move_to_broadcast() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.online_host_room("host");
+        if let Some(session) = core.online.session.as_mut() {
+            for name in ["a", "b"] {
+                session
+                    .shared_queue
+                    .push_back(crate::online::SharedQueueItem {
+                        path: PathBuf::from(format!("{name}.mp3")),
+                        title: String::from(name),
+                        delivery: crate::online::QueueDelivery::HostStreamOnly,
+                        owner_nickname: None,
+                    });
+            }
+        }
+        core.open_shared_queue_view();
+        core.selected_browser = 2;
+
+        assert_eq!(
+            core.shift_selected_shared_queue_item(-1),
+            Some((1, 0, PathBuf::from("b.mp3")))
+        );
+        assert_eq!(core.selected_browser, 1);
+        assert_eq!(
+            core.online
+                .session
+                .as_ref()
+                .map(|session| session.shared_queue[0].title.clone()),
+            Some(String::from("b"))
+        );
+        assert_eq!(core.shift_selected_shared_queue_item(-1), None);
+    }
+
     #[test]
     fn shared_queue_view_includes_owner_in_label() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -3490,6 +6988,148 @@ mod tests {
         assert_eq!(core.browser_entries[0].label, "Two");
     }
 
+    #[test]
+    fn unsaved_lyric_edits_hold_track_and_section_changes_behind_a_prompt() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.header_section = HeaderSection::Lyrics;
+        core.lyrics_track_path = Some(PathBuf::from("missing-dir/a.mp3"));
+        core.lyrics = Some(LyricsDocument {
+            lines: vec![LyricLine {
+                timestamp_ms: None,
+                text: String::new(),
+            }],
+            source: LyricsSource::Created,
+            precision: lyrics::LyricsTimingPrecision::None,
+        });
+        core.lyrics_mode = LyricsMode::Edit;
+        core.lyrics_insert_char('x');
+        assert!(core.lyrics_unsaved);
+
+        let next = PathBuf::from("missing-dir/b.mp3");
+        core.sync_lyrics_for_track(Some(&next));
+        assert_eq!(
+            core.lyrics_leave_prompt,
+            Some(LyricsLeaveAction::ChangeTrack(next.clone()))
+        );
+        assert_eq!(core.status, LYRICS_LEAVE_PROMPT);
+        assert_eq!(
+            core.lyrics_track_path,
+            Some(PathBuf::from("missing-dir/a.mp3"))
+        );
+
+        core.cancel_lyrics_leave_prompt();
+        core.sync_lyrics_for_track(Some(&next));
+        assert!(core.lyrics_leave_prompt.is_none());
+        assert!(core.lyrics_unsaved);
+
+        core.set_header_section(HeaderSection::Stats);
+        assert_eq!(core.header_section, HeaderSection::Lyrics);
+        assert_eq!(
+            core.resolve_lyrics_leave_prompt(false),
+            Some(LyricsLeaveAction::SwitchSection(HeaderSection::Stats))
+        );
+        assert_eq!(core.header_section, HeaderSection::Stats);
+        assert!(!core.lyrics_unsaved);
+        assert_eq!(core.lyrics_mode, LyricsMode::View);
+    }
+
+    #[test]
+    fn play_transient_tracks_queues_tracks_outside_the_library() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = vec![Track {
+            path: PathBuf::from("library/a.mp3"),
+            title: String::from("A"),
+            artist: None,
+            album: None,
+        }];
+        core.track_lookup = build_track_lookup(&core.tracks);
+        let dropped = |name: &str| Track {
+            path: PathBuf::from("downloads").join(name),
+            title: name.to_string(),
+            artist: None,
+            album: None,
+        };
+
+        let first =
+            core.play_transient_tracks(vec![dropped("x.mp3"), dropped("y.mp3")], "downloads");
+
+        assert_eq!(first, Some(PathBuf::from("downloads").join("x.mp3")));
+        assert_eq!(core.queue, vec![1, 2]);
+        assert_eq!(core.current_queue_index, Some(0));
+        assert_eq!(core.status, "Playing downloads (2 tracks)");
+        assert!(core.is_transient_track(&PathBuf::from("downloads").join("y.mp3")));
+        assert_eq!(core.metadata_sorted_library_queue(), vec![0]);
+        assert!(core.library_summary(None).starts_with("Indexed 1 track "));
+
+        core.replace_library_tracks(vec![
+            dropped("y.mp3"),
+            Track {
+                path: PathBuf::from("library/a.mp3"),
+                title: String::from("A"),
+                artist: None,
+                album: None,
+            },
+        ]);
+        assert_eq!(core.tracks.len(), 3);
+        assert!(core.is_transient_track(&PathBuf::from("downloads").join("x.mp3")));
+        assert!(!core.is_transient_track(&PathBuf::from("downloads").join("y.mp3")));
+        assert_eq!(core.queue.len(), 2);
+
+        core.play_transient_tracks(vec![dropped("z.mp3")], "z.mp3");
+        assert!(!core.is_transient_track(&PathBuf::from("downloads").join("x.mp3")));
+        assert_eq!(core.tracks.len(), 3);
+        assert_eq!(core.status, "Playing z.mp3");
+    }
+
+    #[test]
+    fn marked_search_results_expand_folders_for_bulk_queue_add() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let folder = PathBuf::from("music").join("rock");
+        core.tracks = vec![
+            Track {
+                path: folder.join("one.mp3"),
+                title: String::from("One Rock"),
+                artist: None,
+                album: None,
+            },
+            Track {
+                path: folder.join("two.mp3"),
+                title: String::from("Two"),
+                artist: None,
+                album: None,
+            },
+            Track {
+                path: PathBuf::from("music").join("three.mp3"),
+                title: String::from("Three"),
+                artist: None,
+                album: None,
+            },
+        ];
+        core.track_lookup = build_track_lookup(&core.tracks);
+
+        core.library_search_query = String::from("rock");
+        core.refresh_browser_entries();
+        assert_eq!(core.browser_entries.len(), 2);
+        assert_eq!(core.browser_entries[0].kind, BrowserEntryKind::Folder);
+        core.selected_browser = 0;
+        core.toggle_library_search_mark();
+        core.selected_browser = 1;
+        core.toggle_library_search_mark();
+
+        core.library_search_query = String::from("three");
+        core.refresh_browser_entries();
+        core.selected_browser = 0;
+        core.toggle_library_search_mark();
+        assert_eq!(core.library_search_marked.len(), 3);
+
+        core.add_selected_to_local_queue_end();
+        assert_eq!(core.queue, vec![0, 1, 2]);
+        assert_eq!(core.status, "Queued 3 track(s)");
+
+        core.clear_library_search();
+        assert!(core.library_search_marked.is_empty());
+    }
+
     #[test]
     fn library_clear_search_restores_previous_view() {
         let mut core = TuneCore::from_persisted(PersistedState::default());
@@ -3520,4 +7160,98 @@ mod tests {
         assert!(core.library_search_query.is_empty());
         assert_eq!(core.browser_entries.len(), 3); // Back + A + B
     }
+
+    #[test]
+    fn all_songs_orders_by_year_and_genre_with_untagged_last() {
+        let track = |path: &str| Track {
+            path: PathBuf::from(path),
+            title: path.trim_end_matches(".mp3").to_uppercase(),
+            artist: None,
+            album: None,
+        };
+        let mut core = TuneCore::from_persisted_with_tracks(
+            PersistedState::default(),
+            vec![track("a.mp3"), track("b.mp3"), track("c.mp3")],
+        );
+        for (path, genre, year) in [
+            ("a.mp3", None, None),
+            ("b.mp3", Some("rock"), Some(2001)),
+            ("c.mp3", Some("Jazz"), Some(1965)),
+        ] {
+            core.extra_tags_lookup.borrow_mut().insert(
+                normalized_path_key(Path::new(path)),
+                library::ExtraTags {
+                    genre: genre.map(String::from),
+                    year,
+                },
+            );
+        }
+        core.browser_all_songs = true;
+        let labels = |core: &TuneCore| {
+            core.browser_entries
+                .iter()
+                .skip(1)
+                .map(|entry| entry.label.clone())
+                .collect::<Vec<_>>()
+        };
+
+        core.refresh_browser_entries();
+        assert_eq!(labels(&core), vec!["A", "B", "C"]);
+
+        core.cycle_all_songs_order();
+        assert_eq!(core.status, "All Songs sorted by year");
+        assert_eq!(labels(&core), vec!["[1965] C", "[2001] B", "[----] A"]);
+
+        core.cycle_all_songs_order();
+        assert_eq!(core.status, "All Songs grouped by genre");
+        assert_eq!(labels(&core), vec!["[Jazz] C", "[rock] B", "[No genre] A"]);
+
+        core.cycle_all_songs_order();
+        assert_eq!(core.all_songs_order, AllSongsOrder::Title);
+    }
+
+    #[test]
+    fn all_songs_waits_for_probed_tags_instead_of_reading_files() {
+        let track = |path: &str| Track {
+            path: PathBuf::from(path),
+            title: path.trim_end_matches(".mp3").to_uppercase(),
+            artist: None,
+            album: None,
+        };
+        let mut core = TuneCore::from_persisted_with_tracks(
+            PersistedState::default(),
+            vec![track("a.mp3"), track("b.mp3")],
+        );
+        core.browser_all_songs = true;
+        core.cycle_all_songs_order();
+        let labels = |core: &TuneCore| {
+            core.browser_entries
+                .iter()
+                .skip(1)
+                .map(|entry| entry.label.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(labels(&core), vec!["[----] A", "[----] B"]);
+        assert_eq!(
+            core.take_extra_tags_probe_request(),
+            vec![PathBuf::from("a.mp3"), PathBuf::from("b.mp3")]
+        );
+        assert!(core.take_extra_tags_probe_request().is_empty());
+
+        core.cache_extra_tags(vec![(
+            PathBuf::from("b.mp3"),
+            library::ExtraTags {
+                genre: None,
+                year: Some(1999),
+            },
+        )]);
+        assert_eq!(labels(&core), vec!["[1999] B", "[----] A"]);
+        assert_eq!(
+            core.take_extra_tags_probe_request(),
+            vec![PathBuf::from("a.mp3")]
+        );
+
+        core.invalidate_library_caches();
+        assert!(core.extra_tags_lookup.borrow().is_empty());
+    }
 }
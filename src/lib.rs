@@ -7,5 +7,8 @@ pub mod lyrics;
 pub mod model;
 pub mod online;
 pub mod online_net;
+pub mod online_secure;
+pub mod player;
+pub mod scrobble;
 pub mod stats;
 pub mod ui;
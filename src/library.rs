@@ -8,22 +8,43 @@ use lofty::probe::Probe;
 use lofty::tag::{Tag, TagType};
 use rodio::{Decoder, Source};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
 use std::thread;
-use std::time::UNIX_EPOCH;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use symphonia::core::formats::FormatOptions;
 use symphonia::core::io::{MediaSourceStream, MediaSourceStreamOptions};
 use symphonia::core::meta::{MetadataOptions, StandardTagKey};
-use symphonia::core::probe::Hint;
+use symphonia::core::probe::{Hint, ProbeResult};
 use symphonia::default::get_probe;
 use walkdir::WalkDir;
 
-const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "wav", "ogg", "m4a", "aac", "opus"];
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "wav", "ogg", "m4a", "aac", "opus", "wv"];
+/// Cue sheet tracks are addressed as `<sheet>.cue#<number>`, so queues,
+/// playlists, and stats can key them by path like any other file.
+const CUE_TRACK_MARKER: char = '#';
+const CUE_FRAMES_PER_SECOND: u64 = 75;
+/// Copies of a song whose lengths differ by at most this much count as duplicates.
+const DUPLICATE_DURATION_TOLERANCE_SECONDS: u32 = 2;
+const MUSICBRAINZ_RELEASE_SEARCH_URL: &str = "https://musicbrainz.org/ws/2/release/";
+const COVER_ART_ARCHIVE_URL: &str = "https://coverartarchive.org/release";
+/// MusicBrainz asks every client to identify itself.
+const COVER_ART_USER_AGENT: &str = concat!(
+    "TuneTUI/",
+    env!("CARGO_PKG_VERSION"),
+    " ( https://github.com/Sqble/tui_music )"
+);
+const COVER_ART_REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// Search hits below this MusicBrainz score are too loose to trust for a cover.
+const MIN_RELEASE_MATCH_SCORE: u64 = 90;
+const MAX_RELEASE_CANDIDATES: usize = 5;
+/// A cached miss is retried after this long, in case art was uploaded since.
+const COVER_ART_MISS_RETRY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
 
 #[derive(Default)]
 struct TrackMetadata {
@@ -32,11 +53,14 @@ struct TrackMetadata {
     album: Option<String>,
 }
 
+/// Tag fields to write. `None` leaves a field as it is; an empty string clears it.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct MetadataEdit {
     pub title: Option<String>,
     pub artist: Option<String>,
     pub album: Option<String>,
+    pub genre: Option<String>,
+    pub year: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -44,6 +68,15 @@ pub struct MetadataSnapshot {
     pub title: Option<String>,
     pub artist: Option<String>,
     pub album: Option<String>,
+    pub genre: Option<String>,
+    pub year: Option<String>,
+}
+
+/// Tags the library scan does not keep, read straight from the file on demand.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtraTags {
+    pub genre: Option<String>,
+    pub year: Option<u16>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -59,6 +92,10 @@ pub struct LibraryIndexEntry {
     pub artist: Option<String>,
     pub album: Option<String>,
     pub fingerprint: Option<LibraryTrackFingerprint>,
+    /// When a scan first indexed the file, kept across rescans and tag edits.
+    /// Zero for entries indexed before this was recorded.
+    #[serde(default)]
+    pub first_seen_unix_seconds: u64,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -67,10 +104,50 @@ pub struct LibraryIndex {
     pub tracks: Vec<LibraryIndexEntry>,
 }
 
+/// One `TRACK` from a cue sheet: where it starts inside `file`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CueTrack {
+    pub number: u32,
+    pub title: String,
+    pub performer: Option<String>,
+    pub file: PathBuf,
+    pub start: Duration,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CueSheet {
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    pub tracks: Vec<CueTrack>,
+}
+
+/// A parsed cue sheet and the file size and mtime it was parsed from.
+struct CachedCueSheet {
+    len: u64,
+    modified: Option<SystemTime>,
+    sheet: Arc<CueSheet>,
+}
+
+/// Cue tracks are looked up by path on every play, duration probe and metadata
+/// read, so each sheet is parsed once and reused until the file changes.
+static CUE_SHEET_CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedCueSheet>>> = OnceLock::new();
+
+/// The span of `file` a cue sheet track covers. `end` is the next track's
+/// start, or `None` when the track runs to the end of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CueSegment {
+    pub file: PathBuf,
+    pub start: Duration,
+    pub end: Option<Duration>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LibraryScanKind {
     FullRefresh,
     AddFolder,
+    /// Reads a file or folder given to Play path. Its tracks are queued but
+    /// never merged into the library or its index.
+    PlayPath,
 }
 
 impl LibraryScanKind {
@@ -78,6 +155,7 @@ impl LibraryScanKind {
         match self {
             Self::FullRefresh => "Library refresh",
             Self::AddFolder => "Folder import",
+            Self::PlayPath => "Reading path",
         }
     }
 }
@@ -140,20 +218,31 @@ pub struct AudioQualitySnapshot {
     pub spectrograph_rows: Vec<String>,
 }
 
-pub fn scan_folder(root: &Path) -> Vec<Track> {
-    let mut tracks = Vec::new();
+pub fn scan_folder(root: &Path, ignore_patterns: &[String]) -> Vec<Track> {
+    let mut paths = Vec::new();
+    let mut cue_sheets = Vec::new();
 
     for entry in WalkDir::new(root)
         .follow_links(true)
         .into_iter()
+        .filter_entry(|entry| !is_scan_ignored(entry, ignore_patterns))
         .filter_map(Result::ok)
     {
         let path = entry.path();
-        if !entry.file_type().is_file() || !is_audio(path) {
+        if !entry.file_type().is_file() {
             continue;
         }
+        if is_audio(path) {
+            paths.push(path.to_path_buf());
+        } else if is_cue_sheet(path) {
+            cue_sheets.push(path.to_path_buf());
+        }
+    }
+    expand_cue_sheets(&mut paths, &cue_sheets);
 
-        let metadata = metadata_for(path);
+    let mut tracks = Vec::with_capacity(paths.len());
+    for path in paths {
+        let metadata = metadata_for(&path);
         let title = metadata
             .title
             .filter(|title| !title.trim().is_empty())
@@ -165,7 +254,7 @@ pub fn scan_folder(root: &Path) -> Vec<Track> {
             });
 
         tracks.push(Track {
-            path: PathBuf::from(path),
+            path,
             title,
             artist: metadata.artist,
             album: metadata.album,
@@ -181,9 +270,65 @@ pub fn spawn_library_scan(
     kind: LibraryScanKind,
     roots: Vec<PathBuf>,
     existing_index: LibraryIndex,
+    ignore_patterns: Vec<String>,
     tx: Sender<LibraryScanEvent>,
 ) {
-    thread::spawn(move || run_library_scan(scan_id, kind, roots, existing_index, tx));
+    thread::spawn(move || {
+        run_library_scan(scan_id, kind, roots, existing_index, &ignore_patterns, tx)
+    });
+}
+
+/// Groups tracks that share a normalized artist and title and whose durations
+/// are within a couple of seconds. Tracks without an artist are never grouped.
+/// `duration_of` is only asked about tracks that already share a name, so a
+/// slow lookup stays cheap on large libraries. Each cluster is sorted by path.
+pub fn find_duplicates(
+    tracks: &[Track],
+    mut duration_of: impl FnMut(&Path) -> Option<u32>,
+) -> Vec<Vec<PathBuf>> {
+    let mut by_name: HashMap<String, Vec<&Path>> = HashMap::new();
+    for track in tracks {
+        if let Some(key) = crate::stats::metadata_track_key(track.artist.as_deref(), &track.title) {
+            by_name.entry(key).or_default().push(&track.path);
+        }
+    }
+
+    let mut clusters = Vec::new();
+    for paths in by_name.into_values().filter(|paths| paths.len() > 1) {
+        let mut timed: Vec<(Option<u32>, &Path)> = paths
+            .into_iter()
+            .map(|path| (duration_of(path), path))
+            .collect();
+        timed.sort();
+        let mut cluster: Vec<PathBuf> = Vec::new();
+        let mut cluster_start = None;
+        for (duration, path) in timed {
+            let fits = !cluster.is_empty()
+                && match (cluster_start, duration) {
+                    (Some(start), Some(duration)) => {
+                        duration - start <= DUPLICATE_DURATION_TOLERANCE_SECONDS
+                    }
+                    (None, None) => true,
+                    _ => false,
+                };
+            if !fits {
+                if cluster.len() > 1 {
+                    clusters.push(std::mem::take(&mut cluster));
+                }
+                cluster.clear();
+                cluster_start = duration;
+            }
+            cluster.push(path.to_path_buf());
+        }
+        if cluster.len() > 1 {
+            clusters.push(cluster);
+        }
+    }
+    for cluster in &mut clusters {
+        cluster.sort();
+    }
+    clusters.sort();
+    clusters
 }
 
 pub fn tracks_from_index(index: &LibraryIndex, roots: &[PathBuf]) -> Vec<Track> {
@@ -199,12 +344,13 @@ pub fn tracks_from_index(index: &LibraryIndex, roots: &[PathBuf]) -> Vec<Track>
 }
 
 pub fn upsert_index_entry(index: &mut LibraryIndex, track: &Track) {
-    let entry = LibraryIndexEntry::from_track(track);
+    let mut entry = LibraryIndexEntry::from_track(track);
     if let Some(existing) = index
         .tracks
         .iter_mut()
         .find(|candidate| candidate.path == entry.path)
     {
+        entry.first_seen_unix_seconds = existing.added_unix_seconds();
         *existing = entry;
     } else {
         index.tracks.push(entry);
@@ -225,6 +371,7 @@ fn run_library_scan(
     kind: LibraryScanKind,
     roots: Vec<PathBuf>,
     existing_index: LibraryIndex,
+    ignore_patterns: &[String],
     tx: Sender<LibraryScanEvent>,
 ) {
     const DISCOVERY_BATCH_SIZE: usize = 64;
@@ -242,7 +389,7 @@ fn run_library_scan(
     let mut refreshed_metadata_tracks = 0usize;
 
     for root in roots {
-        for path in audio_file_paths(&root) {
+        for path in audio_file_paths(&root, ignore_patterns) {
             discovered_tracks = discovered_tracks.saturating_add(1);
             let key = normalized_path_key(&path);
             let fingerprint = track_fingerprint(&path);
@@ -275,10 +422,11 @@ fn run_library_scan(
             let track = track_for_path(&path);
             refreshed_metadata_tracks = refreshed_metadata_tracks.saturating_add(1);
             metadata_batch.push(track.clone());
-            next_index.push(LibraryIndexEntry::from_track_with_fingerprint(
-                &track,
-                fingerprint,
-            ));
+            let mut entry = LibraryIndexEntry::from_track_with_fingerprint(&track, fingerprint);
+            if let Some(cached_entry) = cached {
+                entry.first_seen_unix_seconds = cached_entry.added_unix_seconds();
+            }
+            next_index.push(entry);
 
             if metadata_batch.len() >= METADATA_BATCH_SIZE {
                 let tracks = std::mem::take(&mut metadata_batch);
@@ -331,18 +479,26 @@ fn run_library_scan(
     });
 }
 
-fn audio_file_paths(root: &Path) -> Vec<PathBuf> {
+fn audio_file_paths(root: &Path, ignore_patterns: &[String]) -> Vec<PathBuf> {
     let mut paths = Vec::new();
+    let mut cue_sheets = Vec::new();
     for entry in WalkDir::new(root)
         .follow_links(true)
         .into_iter()
+        .filter_entry(|entry| !is_scan_ignored(entry, ignore_patterns))
         .filter_map(Result::ok)
     {
         let path = crate::config::normalize_path(entry.path());
-        if entry.file_type().is_file() && is_audio(&path) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if is_audio(&path) {
             paths.push(path);
+        } else if is_cue_sheet(&path) {
+            cue_sheets.push(path);
         }
     }
+    expand_cue_sheets(&mut paths, &cue_sheets);
     paths.sort();
     paths.dedup();
     paths
@@ -384,8 +540,13 @@ fn track_for_path(path: &Path) -> Track {
     }
 }
 
+/// Cue sheet tracks take the sheet's fingerprint, so editing the sheet
+/// refreshes their titles on the next scan.
 pub fn track_fingerprint(path: &Path) -> Option<LibraryTrackFingerprint> {
-    let metadata = std::fs::metadata(path).ok()?;
+    let metadata = match split_cue_track_path(path) {
+        Some((sheet, _)) => std::fs::metadata(sheet).ok()?,
+        None => std::fs::metadata(path).ok()?,
+    };
     let modified = metadata.modified().ok()?;
     let modified_unix_seconds = modified.duration_since(UNIX_EPOCH).ok()?.as_secs();
     Some(LibraryTrackFingerprint {
@@ -437,9 +598,23 @@ impl LibraryIndexEntry {
             artist: track.artist.clone(),
             album: track.album.clone(),
             fingerprint,
+            first_seen_unix_seconds: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs()),
         }
     }
 
+    /// Date added for smart playlists: the first-seen time, or the file's
+    /// modified time from the fingerprint for entries older than that field.
+    pub fn added_unix_seconds(&self) -> u64 {
+        if self.first_seen_unix_seconds > 0 {
+            return self.first_seen_unix_seconds;
+        }
+        self.fingerprint
+            .as_ref()
+            .map_or(0, |fingerprint| fingerprint.modified_unix_seconds)
+    }
+
     pub fn to_track(&self) -> Track {
         Track {
             path: self.path.clone(),
@@ -451,6 +626,13 @@ impl LibraryIndexEntry {
 }
 
 fn metadata_for(path: &Path) -> TrackMetadata {
+    if let Some(track) = cue_track_for_path(path) {
+        return TrackMetadata {
+            title: Some(track.title),
+            artist: track.artist,
+            album: track.album,
+        };
+    }
     let stripped = crate::config::strip_windows_verbatim_prefix(path);
     let symphonia_meta = symphonia_metadata(&stripped);
     if symphonia_meta.title.is_some()
@@ -464,19 +646,22 @@ fn metadata_for(path: &Path) -> TrackMetadata {
 
 pub fn metadata_snapshot_for_path(path: &Path) -> MetadataSnapshot {
     let metadata = metadata_for(path);
+    let extra = extra_tags_for_path(path);
     MetadataSnapshot {
         title: metadata.title,
         artist: metadata.artist,
         album: metadata.album,
+        genre: extra.genre,
+        year: extra.year.map(|year| year.to_string()),
     }
 }
 
 pub fn audio_quality_snapshot(path: &Path) -> AudioQualitySnapshot {
-    let stripped = crate::config::strip_windows_verbatim_prefix(path);
+    let duration_seconds = duration_seconds(path);
+    let stripped = crate::config::strip_windows_verbatim_prefix(&media_file_path(path));
     let (bitrate_kbps, sample_rate_hz, channels) = reported_audio_properties(&stripped);
     let format_label = format_label_for_path(&stripped);
     let rating = classify_audio_quality(bitrate_kbps, is_lossless_path(&stripped));
-    let duration_seconds = duration_seconds(&stripped);
     let spectrograph_rows = build_static_spectrograph(&stripped, duration_seconds);
 
     AudioQualitySnapshot {
@@ -490,9 +675,16 @@ pub fn audio_quality_snapshot(path: &Path) -> AudioQualitySnapshot {
     }
 }
 
-pub fn write_embedded_metadata(path: &Path, edit: &MetadataEdit) -> Result<()> {
+pub fn write_embedded_metadata(
+    path: &Path,
+    edit: &MetadataEdit,
+    backup_path: Option<&Path>,
+) -> Result<()> {
     validate_tag_edit_target(path)?;
     let stripped = crate::config::strip_windows_verbatim_prefix(path);
+    if let Some(backup_path) = backup_path {
+        backup_original_track(&stripped, backup_path)?;
+    }
 
     let mut tagged_file = Probe::open(&stripped)
         .with_context(|| format!("failed to open {}", stripped.display()))?
@@ -516,13 +708,27 @@ pub fn write_embedded_metadata(path: &Path, edit: &MetadataEdit) -> Result<()> {
         .with_context(|| format!("failed to write metadata for {}", stripped.display()))
 }
 
-pub fn clear_embedded_metadata(path: &Path) -> Result<()> {
-    write_embedded_metadata(path, &MetadataEdit::default())
+pub fn clear_embedded_metadata(path: &Path, backup_path: Option<&Path>) -> Result<()> {
+    let cleared = MetadataEdit {
+        title: Some(String::new()),
+        artist: Some(String::new()),
+        album: Some(String::new()),
+        genre: None,
+        year: None,
+    };
+    write_embedded_metadata(path, &cleared, backup_path)
 }
 
-pub fn write_embedded_cover_art(path: &Path, image_data: &[u8]) -> Result<()> {
+pub fn write_embedded_cover_art(
+    path: &Path,
+    image_data: &[u8],
+    backup_path: Option<&Path>,
+) -> Result<()> {
     validate_tag_edit_target(path)?;
     let stripped = crate::config::strip_windows_verbatim_prefix(path);
+    if let Some(backup_path) = backup_path {
+        backup_original_track(&stripped, backup_path)?;
+    }
 
     let mut tagged_file = Probe::open(&stripped)
         .with_context(|| format!("failed to open {}", stripped.display()))?
@@ -545,6 +751,31 @@ pub fn write_embedded_cover_art(path: &Path, image_data: &[u8]) -> Result<()> {
         .with_context(|| format!("failed to write cover art for {}", stripped.display()))
 }
 
+/// Copies the untouched track to `backup_path` before the first tag write.
+/// An existing backup is kept so it always holds the original file.
+fn backup_original_track(path: &Path, backup_path: &Path) -> Result<()> {
+    if backup_path.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = backup_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::copy(path, backup_path).with_context(|| format!("failed to back up {}", path.display()))?;
+    Ok(())
+}
+
+pub fn restore_track_backup(path: &Path, backup_path: &Path) -> Result<()> {
+    validate_tag_edit_target(path)?;
+    if !backup_path.is_file() {
+        anyhow::bail!("no backup found for this track")
+    }
+    let stripped = crate::config::strip_windows_verbatim_prefix(path);
+    fs::copy(backup_path, &stripped)
+        .with_context(|| format!("failed to restore {}", stripped.display()))?;
+    Ok(())
+}
+
 fn replace_cover_picture(tag: &mut Tag, image_data: &[u8]) -> Result<()> {
     let mut cursor = std::io::Cursor::new(image_data);
     let mut picture = Picture::from_reader(&mut cursor)
@@ -559,13 +790,21 @@ fn replace_cover_picture(tag: &mut Tag, image_data: &[u8]) -> Result<()> {
 }
 
 fn apply_metadata_edit_to_tag(tag: &mut Tag, edit: &MetadataEdit) {
-    set_tag_text(tag, ItemKey::TrackTitle, edit.title.as_deref());
-    set_tag_text(tag, ItemKey::TrackArtist, edit.artist.as_deref());
-    set_tag_text(tag, ItemKey::AlbumTitle, edit.album.as_deref());
+    for (key, value) in [
+        (ItemKey::TrackTitle, &edit.title),
+        (ItemKey::TrackArtist, &edit.artist),
+        (ItemKey::AlbumTitle, &edit.album),
+        (ItemKey::Genre, &edit.genre),
+        (ItemKey::RecordingDate, &edit.year),
+    ] {
+        if let Some(value) = value {
+            set_tag_text(tag, key, value);
+        }
+    }
 }
 
-fn set_tag_text(tag: &mut Tag, key: ItemKey, value: Option<&str>) {
-    let cleaned = value.and_then(clean_metadata_value);
+fn set_tag_text(tag: &mut Tag, key: ItemKey, value: &str) {
+    let cleaned = clean_metadata_value(value);
     tag.remove_key(key);
     if let Some(text) = cleaned {
         tag.insert_text(key, text);
@@ -608,11 +847,111 @@ fn preferred_tag_type_for_path(path: &Path) -> Option<TagType> {
     if ext.eq_ignore_ascii_case("m4a") {
         return Some(TagType::Mp4Ilst);
     }
+    if ext.eq_ignore_ascii_case("wv") {
+        return Some(TagType::Ape);
+    }
     None
 }
 
+/// Looks up a front cover for `album` on MusicBrainz and the Cover Art Archive.
+/// Hits and misses are cached on disk by artist and album. This blocks on the
+/// network, so call it off the UI thread.
+pub fn fetch_cover_art(artist: &str, album: &str) -> Result<Option<Vec<u8>>> {
+    let cache_path = crate::config::cover_art_cache_path(artist, album)?;
+    if let Some(cached) = cached_cover_art(&cache_path) {
+        return Ok(cached);
+    }
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(COVER_ART_REQUEST_TIMEOUT))
+        .build()
+        .into();
+    let image = download_cover_art(&agent, artist, album)?;
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(&cache_path, image.as_deref().unwrap_or_default())
+        .with_context(|| format!("failed to write {}", cache_path.display()))?;
+    Ok(image)
+}
+
+/// An empty cache file records a recent miss.
+fn cached_cover_art(cache_path: &Path) -> Option<Option<Vec<u8>>> {
+    let bytes = fs::read(cache_path).ok()?;
+    if !bytes.is_empty() {
+        return Some(Some(bytes));
+    }
+    let age = fs::metadata(cache_path)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .ok()?;
+    (age < COVER_ART_MISS_RETRY).then_some(None)
+}
+
+fn download_cover_art(agent: &ureq::Agent, artist: &str, album: &str) -> Result<Option<Vec<u8>>> {
+    let search: serde_json::Value = agent
+        .get(MUSICBRAINZ_RELEASE_SEARCH_URL)
+        .query("query", musicbrainz_release_query(artist, album))
+        .query("fmt", "json")
+        .query("limit", MAX_RELEASE_CANDIDATES.to_string())
+        .header("User-Agent", COVER_ART_USER_AGENT)
+        .call()
+        .context("MusicBrainz search failed")?
+        .body_mut()
+        .read_json()
+        .context("MusicBrainz sent an unreadable search result")?;
+
+    for release_id in matching_release_ids(&search) {
+        let response = agent
+            .get(format!("{COVER_ART_ARCHIVE_URL}/{release_id}/front-500"))
+            .header("User-Agent", COVER_ART_USER_AGENT)
+            .call();
+        match response {
+            Ok(mut response) => {
+                let image = response
+                    .body_mut()
+                    .read_to_vec()
+                    .context("failed to read cover art download")?;
+                return Ok(Some(image));
+            }
+            Err(ureq::Error::StatusCode(404)) => {}
+            Err(err) => {
+                return Err(anyhow::Error::new(err).context("Cover Art Archive request failed"));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn musicbrainz_release_query(artist: &str, album: &str) -> String {
+    let quoted = |value: &str| value.trim().replace('\\', "\\\\").replace('"', "\\\"");
+    format!(
+        "release:\"{}\" AND artist:\"{}\"",
+        quoted(album),
+        quoted(artist)
+    )
+}
+
+fn matching_release_ids(search: &serde_json::Value) -> Vec<String> {
+    search["releases"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|release| {
+            release["score"]
+                .as_u64()
+                .is_none_or(|score| score >= MIN_RELEASE_MATCH_SCORE)
+        })
+        .filter_map(|release| release["id"].as_str().map(str::to_string))
+        .take(MAX_RELEASE_CANDIDATES)
+        .collect()
+}
+
 pub fn embedded_cover_art(path: &Path) -> Option<Vec<u8>> {
-    let stripped = crate::config::strip_windows_verbatim_prefix(path);
+    let stripped = crate::config::strip_windows_verbatim_prefix(&media_file_path(path));
     symphonia_embedded_cover_art(&stripped).or_else(|| id3v2_cover_art(&stripped))
 }
 
@@ -645,10 +984,8 @@ fn symphonia_embedded_cover_art(path: &Path) -> Option<Vec<u8>> {
     Some(visual.data.as_ref().to_vec())
 }
 
-fn symphonia_metadata(path: &Path) -> TrackMetadata {
-    let Ok(file) = File::open(path) else {
-        return TrackMetadata::default();
-    };
+fn probe_media(path: &Path) -> Option<ProbeResult> {
+    let file = File::open(path).ok()?;
     let source = MediaSourceStream::new(Box::new(file), MediaSourceStreamOptions::default());
 
     let mut hint = Hint::new();
@@ -656,12 +993,18 @@ fn symphonia_metadata(path: &Path) -> TrackMetadata {
         hint.with_extension(extension);
     }
 
-    let Ok(mut probed) = get_probe().format(
-        &hint,
-        source,
-        &FormatOptions::default(),
-        &MetadataOptions::default(),
-    ) else {
+    get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .ok()
+}
+
+fn symphonia_metadata(path: &Path) -> TrackMetadata {
+    let Some(mut probed) = probe_media(path) else {
         return TrackMetadata::default();
     };
 
@@ -687,7 +1030,141 @@ fn symphonia_metadata(path: &Path) -> TrackMetadata {
     }
 }
 
+/// Loudness tags in dB, with their peaks as linear sample values. A field is
+/// `None` when the file does not carry it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReplayGainTags {
+    pub track_gain_db: Option<f32>,
+    pub track_peak: Option<f32>,
+    pub album_gain_db: Option<f32>,
+    pub album_peak: Option<f32>,
+}
+
+/// Reads `REPLAYGAIN_*` tags, or an iTunes `iTunNORM` comment as the track gain,
+/// through the same probe as the title and artist. Tags ahead of the container
+/// (ID3v2 on MP3s) are included.
+pub fn replay_gain_for_path(path: &Path) -> ReplayGainTags {
+    let stripped = crate::config::strip_windows_verbatim_prefix(&media_file_path(path));
+    let Some(mut probed) = probe_media(&stripped) else {
+        return ReplayGainTags::default();
+    };
+
+    let mut tags = probed
+        .metadata
+        .get()
+        .and_then(|metadata| metadata.current().map(|revision| revision.tags().to_vec()))
+        .unwrap_or_default();
+    if let Some(revision) = probed.format.metadata().current() {
+        tags.extend_from_slice(revision.tags());
+    }
+    replay_gain_from_tags(&tags)
+}
+
+/// Genre and year, read through the same probe as the ReplayGain tags. Not
+/// part of the scan, so callers should cache them.
+pub fn extra_tags_for_path(path: &Path) -> ExtraTags {
+    let stripped = crate::config::strip_windows_verbatim_prefix(&media_file_path(path));
+    let Some(mut probed) = probe_media(&stripped) else {
+        return ExtraTags::default();
+    };
+
+    let mut tags = probed
+        .metadata
+        .get()
+        .and_then(|metadata| metadata.current().map(|revision| revision.tags().to_vec()))
+        .unwrap_or_default();
+    if let Some(revision) = probed.format.metadata().current() {
+        tags.extend_from_slice(revision.tags());
+    }
+    let year = [
+        StandardTagKey::Date,
+        StandardTagKey::ReleaseDate,
+        StandardTagKey::OriginalDate,
+    ]
+    .into_iter()
+    .find_map(|key| tag_value(&tags, key, &["date", "year", "tdrc", "tyer"]))
+    .and_then(|text| parse_tag_year(&text));
+    ExtraTags {
+        genre: tag_value(&tags, StandardTagKey::Genre, &["genre"]),
+        year,
+    }
+}
+
+/// The first four-digit run in a date tag, so `1997`, `1997-05-12` and
+/// `12/05/1997` all read as 1997.
+fn parse_tag_year(text: &str) -> Option<u16> {
+    text.split(|ch: char| !ch.is_ascii_digit())
+        .find(|run| run.len() == 4)
+        .and_then(|run| run.parse().ok())
+        .filter(|year| *year >= 1000)
+}
+
+fn replay_gain_from_tags(tags: &[symphonia::core::meta::Tag]) -> ReplayGainTags {
+    let lookup = |standard_key: StandardTagKey, name: &str| {
+        tags.iter()
+            .find(|tag| {
+                tag.std_key == Some(standard_key) || tag.key.to_ascii_uppercase().ends_with(name)
+            })
+            .and_then(|tag| parse_replay_gain_value(&tag.value.to_string()))
+    };
+
+    let track_gain_db = lookup(StandardTagKey::ReplayGainTrackGain, "REPLAYGAIN_TRACK_GAIN")
+        .or_else(|| {
+            tags.iter()
+                .filter(|tag| {
+                    tag.key.to_ascii_lowercase().contains("itunnorm")
+                        || tag.key.to_ascii_uppercase().starts_with("COMM")
+                })
+                .find_map(|tag| itunnorm_gain_db(&tag.value.to_string()))
+        });
+
+    ReplayGainTags {
+        track_gain_db,
+        track_peak: lookup(StandardTagKey::ReplayGainTrackPeak, "REPLAYGAIN_TRACK_PEAK"),
+        album_gain_db: lookup(StandardTagKey::ReplayGainAlbumGain, "REPLAYGAIN_ALBUM_GAIN"),
+        album_peak: lookup(StandardTagKey::ReplayGainAlbumPeak, "REPLAYGAIN_ALBUM_PEAK"),
+    }
+}
+
+/// `-6.48 dB`, `+1.2 dB`, or a bare number as written for peaks.
+fn parse_replay_gain_value(value: &str) -> Option<f32> {
+    let trimmed = value.trim();
+    let number = trimmed
+        .get(trimmed.len().saturating_sub(2)..)
+        .filter(|suffix| suffix.eq_ignore_ascii_case("db"))
+        .map_or(trimmed, |_| &trimmed[..trimmed.len() - 2]);
+    number
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// iTunes Sound Check stores ten hex words; the first two are the left and
+/// right loudness as 1/1000 of a reference level, so the louder channel gives
+/// the gain. Anything else in a comment is ignored.
+fn itunnorm_gain_db(value: &str) -> Option<f32> {
+    let words: Vec<&str> = value.split_whitespace().collect();
+    if words.len() < 10 || !words.iter().all(|word| word.len() == 8) {
+        return None;
+    }
+    let left = u32::from_str_radix(words[0], 16).ok()?;
+    let right = u32::from_str_radix(words[1], 16).ok()?;
+    let loudness = left.max(right);
+    if loudness == 0 {
+        return None;
+    }
+    Some((-10.0 * (f64::from(loudness) / 1000.0).log10()) as f32)
+}
+
 pub fn duration_seconds(path: &Path) -> Option<u32> {
+    if let Some(segment) = cue_segment_for_path(path) {
+        let end = match segment.end {
+            Some(end) => end.as_secs() as u32,
+            None => duration_seconds(&segment.file)?,
+        };
+        return Some(end.saturating_sub(segment.start.as_secs() as u32));
+    }
     let stripped = crate::config::strip_windows_verbatim_prefix(path);
 
     let Ok(file) = File::open(&stripped) else {
@@ -1319,16 +1796,55 @@ fn tag_value(
     })
 }
 
-pub fn scan_many(roots: &[PathBuf]) -> Vec<Track> {
+pub fn scan_many(roots: &[PathBuf], ignore_patterns: &[String]) -> Vec<Track> {
     let mut all = Vec::new();
     for root in roots {
-        all.extend(scan_folder(root));
+        all.extend(scan_folder(root, ignore_patterns));
     }
     all.sort_by(|a, b| a.path.cmp(&b.path));
     all.dedup_by(|a, b| a.path == b.path);
     all
 }
 
+/// Library roots themselves are never ignored; patterns apply to the files and
+/// folders beneath them.
+fn is_scan_ignored(entry: &walkdir::DirEntry, ignore_patterns: &[String]) -> bool {
+    if entry.depth() == 0 {
+        return false;
+    }
+    let name = entry.file_name().to_string_lossy();
+    ignore_patterns
+        .iter()
+        .any(|pattern| ignore_pattern_matches(pattern.trim(), &name))
+}
+
+/// Case-insensitive glob match supporting `*` and `?` against a single file or folder name.
+fn ignore_pattern_matches(pattern: &str, name: &str) -> bool {
+    if pattern.is_empty() {
+        return false;
+    }
+    let pattern: Vec<char> = pattern.chars().map(|ch| ch.to_ascii_lowercase()).collect();
+    let name: Vec<char> = name.chars().map(|ch| ch.to_ascii_lowercase()).collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            p = star_p + 1;
+            n = star_n + 1;
+            backtrack = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|ch| *ch == '*')
+}
+
 fn is_audio(path: &Path) -> bool {
     let ext = path.extension().and_then(OsStr::to_str).unwrap_or_default();
     AUDIO_EXTENSIONS
@@ -1336,19 +1852,495 @@ fn is_audio(path: &Path) -> bool {
         .any(|supported| ext.eq_ignore_ascii_case(supported))
 }
 
+pub fn is_playlist_file(path: &Path) -> bool {
+    path.extension().and_then(OsStr::to_str).is_some_and(|ext| {
+        ["m3u", "m3u8", "pls"]
+            .iter()
+            .any(|supported| ext.eq_ignore_ascii_case(supported))
+    })
+}
+
+/// Entries of an `.m3u`/`.m3u8` or `.pls` playlist in order, with relative
+/// entries resolved against the playlist's folder. Stream URLs are dropped.
+pub fn parse_playlist_file(path: &Path) -> Result<Vec<PathBuf>> {
+    let bytes =
+        fs::read(path).with_context(|| format!("failed to read playlist {}", path.display()))?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let text = String::from_utf8_lossy(&bytes);
+    let is_pls = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pls"));
+    Ok(if is_pls {
+        parse_pls_text(&text, dir)
+    } else {
+        parse_m3u_text(&text, dir)
+    })
+}
+
+fn parse_m3u_text(text: &str, dir: &Path) -> Vec<PathBuf> {
+    text.lines()
+        .map(|line| line.trim_start_matches('\u{feff}').trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|entry| playlist_entry_path(entry, dir))
+        .collect()
+}
+
+/// `FileN=` entries ordered by N, since PLS does not require them in order.
+fn parse_pls_text(text: &str, dir: &Path) -> Vec<PathBuf> {
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let Some((key, value)) = line.trim_start_matches('\u{feff}').split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let number = key
+            .get(..4)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("file"))
+            .and_then(|_| key[4..].parse::<u32>().ok());
+        if let Some(number) = number
+            && let Some(path) = playlist_entry_path(value.trim(), dir)
+        {
+            entries.push((number, path));
+        }
+    }
+    entries.sort_by_key(|(number, _)| *number);
+    entries.into_iter().map(|(_, path)| path).collect()
+}
+
+fn playlist_entry_path(entry: &str, dir: &Path) -> Option<PathBuf> {
+    if let Some(path) = crate::config::file_uri_path(entry) {
+        return Some(path);
+    }
+    if entry.is_empty() || entry.contains("://") {
+        return None;
+    }
+    let path = PathBuf::from(entry);
+    Some(if path.is_absolute() {
+        path
+    } else {
+        dir.join(path)
+    })
+}
+
+pub fn parse_cue(path: &Path) -> Result<CueSheet> {
+    let bytes =
+        fs::read(path).with_context(|| format!("failed to read cue sheet {}", path.display()))?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    Ok(parse_cue_text(&String::from_utf8_lossy(&bytes), dir))
+}
+
+/// Tracks without an `INDEX 01` are dropped; sheet-level `PERFORMER` fills in
+/// for tracks that do not name their own.
+fn parse_cue_text(text: &str, dir: &Path) -> CueSheet {
+    let mut sheet = CueSheet::default();
+    let mut file: Option<PathBuf> = None;
+    let mut pending: Option<(u32, PathBuf)> = None;
+    let mut title = None;
+    let mut performer = None;
+    let mut start = None;
+
+    for line in text.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim();
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        match command.to_ascii_uppercase().as_str() {
+            "FILE" => {
+                push_cue_track(
+                    &mut sheet,
+                    pending.take(),
+                    title.take(),
+                    performer.take(),
+                    start.take(),
+                );
+                file = Some(resolve_cue_file(dir, &cue_file_name(rest)));
+            }
+            "TRACK" => {
+                push_cue_track(
+                    &mut sheet,
+                    pending.take(),
+                    title.take(),
+                    performer.take(),
+                    start.take(),
+                );
+                let number = rest
+                    .split_whitespace()
+                    .next()
+                    .and_then(|number| number.parse().ok());
+                pending = number.zip(file.clone());
+            }
+            "TITLE" if pending.is_some() => title = Some(cue_value(rest)),
+            "TITLE" => sheet.title = Some(cue_value(rest)),
+            "PERFORMER" if pending.is_some() => performer = Some(cue_value(rest)),
+            "PERFORMER" => sheet.performer = Some(cue_value(rest)),
+            "INDEX" if pending.is_some() => {
+                if let Some(("01", time)) = rest.split_once(char::is_whitespace) {
+                    start = parse_cue_time(time.trim());
+                }
+            }
+            _ => {}
+        }
+    }
+    push_cue_track(&mut sheet, pending, title, performer, start);
+
+    for track in &mut sheet.tracks {
+        if track.performer.is_none() {
+            track.performer = sheet.performer.clone();
+        }
+    }
+    sheet
+}
+
+fn push_cue_track(
+    sheet: &mut CueSheet,
+    pending: Option<(u32, PathBuf)>,
+    title: Option<String>,
+    performer: Option<String>,
+    start: Option<Duration>,
+) {
+    if let (Some((number, file)), Some(start)) = (pending, start) {
+        sheet.tracks.push(CueTrack {
+            number,
+            title: title.unwrap_or_else(|| format!("Track {number:02}")),
+            performer,
+            file,
+            start,
+        });
+    }
+}
+
+fn cue_value(rest: &str) -> String {
+    match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next().unwrap_or_default().to_string(),
+        None => rest.to_string(),
+    }
+}
+
+/// `FILE "name" WAVE`: the name may be quoted or bare, followed by a file type.
+fn cue_file_name(rest: &str) -> String {
+    if rest.starts_with('"') {
+        return cue_value(rest);
+    }
+    rest.rsplit_once(char::is_whitespace)
+        .map_or(rest, |(name, _)| name.trim())
+        .to_string()
+}
+
+/// Rippers often write the sheet against a `.wav` that was later encoded to
+/// FLAC, so a missing file falls back to an audio file with the same stem.
+fn resolve_cue_file(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    if path.is_file() {
+        return path;
+    }
+    AUDIO_EXTENSIONS
+        .iter()
+        .map(|ext| path.with_extension(ext))
+        .find(|candidate| candidate.is_file())
+        .unwrap_or(path)
+}
+
+/// `MM:SS:FF`, with 75 frames per second.
+fn parse_cue_time(value: &str) -> Option<Duration> {
+    let mut parts = value.split(':').map(|part| part.parse::<u64>().ok());
+    let (minutes, seconds, frames) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || frames >= CUE_FRAMES_PER_SECOND {
+        return None;
+    }
+    Some(
+        Duration::from_secs(minutes * 60 + seconds)
+            + Duration::from_nanos(frames * 1_000_000_000 / CUE_FRAMES_PER_SECOND),
+    )
+}
+
+pub fn cue_track_path(sheet: &Path, number: u32) -> PathBuf {
+    let mut raw = sheet.as_os_str().to_os_string();
+    raw.push(format!("{CUE_TRACK_MARKER}{number:02}"));
+    PathBuf::from(raw)
+}
+
+/// Splits a `cue_track_path` back into the sheet path and track number.
+pub fn split_cue_track_path(path: &Path) -> Option<(PathBuf, u32)> {
+    let (sheet, number) = path.to_str()?.rsplit_once(CUE_TRACK_MARKER)?;
+    let sheet = PathBuf::from(sheet);
+    if !is_cue_sheet(&sheet) {
+        return None;
+    }
+    Some((sheet, number.parse().ok()?))
+}
+
+fn cached_cue_sheet(sheet_path: &Path) -> Option<Arc<CueSheet>> {
+    let metadata = fs::metadata(sheet_path).ok()?;
+    let (len, modified) = (metadata.len(), metadata.modified().ok());
+    let cache = CUE_SHEET_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(cached) = cache
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(sheet_path)
+        .filter(|cached| cached.len == len && cached.modified == modified)
+    {
+        return Some(Arc::clone(&cached.sheet));
+    }
+
+    let sheet = Arc::new(parse_cue(sheet_path).ok()?);
+    cache.lock().unwrap_or_else(PoisonError::into_inner).insert(
+        sheet_path.to_path_buf(),
+        CachedCueSheet {
+            len,
+            modified,
+            sheet: Arc::clone(&sheet),
+        },
+    );
+    Some(sheet)
+}
+
+pub fn cue_segment_for_path(path: &Path) -> Option<CueSegment> {
+    let (sheet_path, number) = split_cue_track_path(path)?;
+    let sheet = cached_cue_sheet(&sheet_path)?;
+    let position = sheet
+        .tracks
+        .iter()
+        .position(|track| track.number == number)?;
+    let track = &sheet.tracks[position];
+    let end = sheet
+        .tracks
+        .get(position + 1)
+        .filter(|next| next.file == track.file)
+        .map(|next| next.start);
+    Some(CueSegment {
+        file: track.file.clone(),
+        start: track.start,
+        end,
+    })
+}
+
+pub fn cue_sheet_tracks(sheet_path: &Path, sheet: &CueSheet) -> Vec<Track> {
+    sheet
+        .tracks
+        .iter()
+        .map(|track| Track {
+            path: cue_track_path(sheet_path, track.number),
+            title: track.title.clone(),
+            artist: track.performer.clone(),
+            album: sheet.title.clone(),
+        })
+        .collect()
+}
+
+pub fn cue_track_for_path(path: &Path) -> Option<Track> {
+    let (sheet_path, _) = split_cue_track_path(path)?;
+    let sheet = cached_cue_sheet(&sheet_path)?;
+    cue_sheet_tracks(&sheet_path, &sheet)
+        .into_iter()
+        .find(|track| track.path == path)
+}
+
+/// The file on disk behind a library path: the sheet's audio file for a cue
+/// track, otherwise the path itself.
+pub fn media_file_path(path: &Path) -> PathBuf {
+    cue_segment_for_path(path).map_or_else(|| path.to_path_buf(), |segment| segment.file)
+}
+
+/// Swaps audio files that a cue sheet splits up for the sheet's tracks. Sheets
+/// whose audio file is missing are ignored and the file, if any, stays whole.
+fn expand_cue_sheets(paths: &mut Vec<PathBuf>, cue_sheets: &[PathBuf]) {
+    let mut split_files = HashSet::new();
+    for sheet_path in cue_sheets {
+        let Ok(sheet) = parse_cue(sheet_path) else {
+            continue;
+        };
+        for track in sheet.tracks.iter().filter(|track| track.file.is_file()) {
+            split_files.insert(normalized_path_key(&track.file));
+            paths.push(cue_track_path(sheet_path, track.number));
+        }
+    }
+    paths.retain(|path| !split_files.contains(&normalized_path_key(path)));
+}
+
+pub fn is_cue_sheet(path: &Path) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("cue"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use symphonia::core::meta::{Tag as SymphoniaTag, Value};
     use tempfile::tempdir;
 
+    #[test]
+    fn replay_gain_tags_parse_gains_peaks_and_itunnorm() {
+        let tags = vec![
+            SymphoniaTag::new(
+                Some(StandardTagKey::ReplayGainTrackGain),
+                "TXXX:REPLAYGAIN_TRACK_GAIN",
+                Value::from("-6.50 dB"),
+            ),
+            SymphoniaTag::new(None, "REPLAYGAIN_TRACK_PEAK", Value::from("0.988")),
+            SymphoniaTag::new(None, "replaygain_album_gain", Value::from("+1.25 dB")),
+        ];
+        let gains = replay_gain_from_tags(&tags);
+        assert_eq!(gains.track_gain_db, Some(-6.5));
+        assert_eq!(gains.track_peak, Some(0.988));
+        assert_eq!(gains.album_gain_db, Some(1.25));
+        assert_eq!(gains.album_peak, None);
+
+        let itunes = vec![SymphoniaTag::new(
+            None,
+            "COMM!eng",
+            Value::from(
+                " 00000FA0 000007D0 00004C9B 00004C9B 00024CA8 00024CA8 00007FFF 00007FFF 00024CA8 00024CA8",
+            ),
+        )];
+        let gain = replay_gain_from_tags(&itunes)
+            .track_gain_db
+            .expect("itunnorm");
+        assert!((gain + 6.02).abs() < 0.01, "gain was {gain}");
+
+        let plain = vec![SymphoniaTag::new(
+            None,
+            "COMM!eng",
+            Value::from("great song"),
+        )];
+        assert_eq!(replay_gain_from_tags(&plain), ReplayGainTags::default());
+    }
+
+    #[test]
+    fn upsert_index_entry_keeps_the_date_added() {
+        let track = Track {
+            path: PathBuf::from("/music/a.mp3"),
+            title: String::from("Old"),
+            artist: None,
+            album: None,
+        };
+        let mut index = LibraryIndex {
+            tracks: vec![LibraryIndexEntry {
+                first_seen_unix_seconds: 0,
+                ..LibraryIndexEntry::from_track_with_fingerprint(
+                    &track,
+                    Some(LibraryTrackFingerprint {
+                        file_size_bytes: 1,
+                        modified_unix_seconds: 500,
+                    }),
+                )
+            }],
+        };
+        assert_eq!(index.tracks[0].added_unix_seconds(), 500);
+
+        upsert_index_entry(
+            &mut index,
+            &Track {
+                title: String::from("New"),
+                ..track
+            },
+        );
+        assert_eq!(index.tracks[0].title, "New");
+        assert_eq!(index.tracks[0].first_seen_unix_seconds, 500);
+    }
+
+    #[test]
+    fn parse_cue_reads_tracks_and_offsets() {
+        let sheet = parse_cue_text(
+            "\u{feff}PERFORMER \"Band\"
+TITLE \"Live\"
+FILE \"Live.flac\" WAVE
+  TRACK 01 AUDIO
+    TITLE \"Intro\"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE \"Song\"
+    PERFORMER \"Guest\"
+    INDEX 00 03:58:00
+    INDEX 01 04:00:37
+",
+            Path::new("rips"),
+        );
+
+        assert_eq!(sheet.title.as_deref(), Some("Live"));
+        assert_eq!(sheet.tracks.len(), 2);
+        assert_eq!(sheet.tracks[0].title, "Intro");
+        assert_eq!(sheet.tracks[0].performer.as_deref(), Some("Band"));
+        assert_eq!(sheet.tracks[1].performer.as_deref(), Some("Guest"));
+        assert_eq!(sheet.tracks[1].file, Path::new("rips").join("Live.flac"));
+        assert_eq!(
+            sheet.tracks[1].start,
+            Duration::from_secs(240) + Duration::from_nanos(37 * 1_000_000_000 / 75)
+        );
+    }
+
+    #[test]
+    fn playlist_files_resolve_relative_entries_and_skip_streams() {
+        let m3u = parse_m3u_text(
+            "\u{feff}#EXTM3U\n#EXTINF:215,Band - Song\nsub/song.mp3\n\nhttp://radio.example/live\n/abs/b.flac\nfile:///abs/My%20Song.ogg\n",
+            Path::new("lists"),
+        );
+        assert_eq!(
+            m3u,
+            vec![
+                Path::new("lists").join("sub/song.mp3"),
+                PathBuf::from("/abs/b.flac"),
+                PathBuf::from("/abs/My Song.ogg"),
+            ]
+        );
+
+        let pls = parse_pls_text(
+            "[playlist]\nFile2=two.mp3\nTitle2=Two\nfile1=one.mp3\nFile3=https://radio.example\nNumberOfEntries=3\nVersion=2\n",
+            Path::new("lists"),
+        );
+        assert_eq!(
+            pls,
+            vec![
+                Path::new("lists").join("one.mp3"),
+                Path::new("lists").join("two.mp3"),
+            ]
+        );
+        assert!(is_playlist_file(Path::new("mix.M3U8")));
+        assert!(!is_playlist_file(Path::new("mix.cue")));
+    }
+
+    #[test]
+    fn scan_splits_cue_sheet_files_into_tracks() {
+        let dir = tempdir().expect("tempdir");
+        fs::write(dir.path().join("Album.flac"), b"x").expect("write flac");
+        fs::write(dir.path().join("other.mp3"), b"x").expect("write mp3");
+        let sheet = dir.path().join("Album.cue");
+        fs::write(
+            &sheet,
+            "TITLE \"Album\"\nFILE \"Album.wav\" WAVE\nTRACK 01 AUDIO\nTITLE \"One\"\nINDEX 01 00:00:00\nTRACK 02 AUDIO\nTITLE \"Two\"\nINDEX 01 03:00:00\n",
+        )
+        .expect("write cue");
+
+        let tracks = scan_folder(dir.path(), &[]);
+        let titles: Vec<&str> = tracks.iter().map(|track| track.title.as_str()).collect();
+        assert_eq!(titles, vec!["One", "Two", "other"]);
+        assert_eq!(tracks[1].path, cue_track_path(&sheet, 2));
+        assert_eq!(tracks[1].album.as_deref(), Some("Album"));
+
+        let first = cue_segment_for_path(&tracks[0].path).expect("first segment");
+        assert_eq!(first.file, dir.path().join("Album.flac"));
+        assert_eq!(first.end, Some(Duration::from_secs(180)));
+        let last = cue_segment_for_path(&tracks[1].path).expect("last segment");
+        assert_eq!(last.start, Duration::from_secs(180));
+        assert_eq!(last.end, None);
+
+        // An edited sheet is parsed again instead of served from the cache.
+        fs::write(
+            &sheet,
+            "TITLE \"Album\"\nFILE \"Album.wav\" WAVE\nTRACK 01 AUDIO\nTITLE \"One\"\nINDEX 01 00:00:00\nTRACK 02 AUDIO\nTITLE \"Two (edit)\"\nINDEX 01 04:00:00\n",
+        )
+        .expect("rewrite cue");
+        let last = cue_segment_for_path(&tracks[1].path).expect("edited segment");
+        assert_eq!(last.start, Duration::from_secs(240));
+    }
+
     #[test]
     fn scan_filters_non_audio_files() {
         let dir = tempdir().expect("tempdir");
         fs::write(dir.path().join("a.mp3"), b"x").expect("write mp3");
         fs::write(dir.path().join("b.txt"), b"x").expect("write txt");
 
-        let tracks = scan_folder(dir.path());
+        let tracks = scan_folder(dir.path(), &[]);
         assert_eq!(tracks.len(), 1);
         assert!(tracks[0].path.ends_with("a.mp3"));
         assert_eq!(tracks[0].title, "a");
@@ -1356,6 +2348,35 @@ mod tests {
         assert_eq!(tracks[0].album, None);
     }
 
+    #[test]
+    fn scan_skips_ignored_files_and_folders() {
+        let dir = tempdir().expect("tempdir");
+        fs::create_dir_all(dir.path().join(".git")).expect("create hidden dir");
+        fs::create_dir_all(dir.path().join("Samples")).expect("create samples dir");
+        fs::write(dir.path().join("keep.mp3"), b"x").expect("write kept");
+        fs::write(dir.path().join(".hidden.mp3"), b"x").expect("write hidden");
+        fs::write(dir.path().join(".git").join("a.mp3"), b"x").expect("write git");
+        fs::write(dir.path().join("Samples").join("kick.wav"), b"x").expect("write sample");
+
+        let patterns = vec![String::from(".*"), String::from("sample?")];
+        let tracks = scan_folder(dir.path(), &patterns);
+        assert_eq!(tracks.len(), 1);
+        assert!(tracks[0].path.ends_with("keep.mp3"));
+        assert_eq!(
+            audio_file_paths(dir.path(), &patterns),
+            vec![crate::config::normalize_path(&dir.path().join("keep.mp3"))]
+        );
+    }
+
+    #[test]
+    fn ignore_pattern_matches_globs_case_insensitively() {
+        assert!(ignore_pattern_matches("*.tmp", "Track.TMP"));
+        assert!(ignore_pattern_matches("__MACOSX", "__macosx"));
+        assert!(ignore_pattern_matches("a*b*c", "axxbyyc"));
+        assert!(!ignore_pattern_matches("a*b", "axxbc"));
+        assert!(!ignore_pattern_matches("", "anything"));
+    }
+
     #[test]
     fn metadata_value_cleaning_trims_and_drops_empty() {
         assert_eq!(
@@ -1371,7 +2392,8 @@ mod tests {
         let file = dir.path().join("note.txt");
         fs::write(&file, b"x").expect("write text");
 
-        let err = write_embedded_metadata(&file, &MetadataEdit::default()).expect_err("error");
+        let err =
+            write_embedded_metadata(&file, &MetadataEdit::default(), None).expect_err("error");
         assert!(
             err.to_string().contains("unsupported audio format"),
             "unexpected error: {err:#}"
@@ -1384,13 +2406,43 @@ mod tests {
         let file = dir.path().join("note.txt");
         fs::write(&file, b"x").expect("write text");
 
-        let err = write_embedded_cover_art(&file, b"not-image").expect_err("error");
+        let err = write_embedded_cover_art(&file, b"not-image", None).expect_err("error");
         assert!(
             err.to_string().contains("unsupported audio format"),
             "unexpected error: {err:#}"
         );
     }
 
+    #[test]
+    fn track_backup_keeps_first_original_and_restores_it() {
+        let dir = tempdir().expect("tempdir");
+        let track = dir.path().join("song.mp3");
+        let backup = dir.path().join("backups").join("song.mp3");
+        fs::write(&track, b"original").expect("write track");
+
+        backup_original_track(&track, &backup).expect("first backup");
+        fs::write(&track, b"edited once").expect("edit track");
+        backup_original_track(&track, &backup).expect("second backup");
+        assert_eq!(fs::read(&backup).expect("read backup"), b"original");
+
+        restore_track_backup(&track, &backup).expect("restore");
+        assert_eq!(fs::read(&track).expect("read track"), b"original");
+    }
+
+    #[test]
+    fn restore_track_backup_requires_existing_backup() {
+        let dir = tempdir().expect("tempdir");
+        let track = dir.path().join("song.mp3");
+        fs::write(&track, b"x").expect("write track");
+
+        let err = restore_track_backup(&track, &dir.path().join("missing.mp3"))
+            .expect_err("missing backup");
+        assert!(
+            err.to_string().contains("no backup found"),
+            "unexpected error: {err:#}"
+        );
+    }
+
     #[test]
     fn quality_rating_thresholds_match_issue_rules() {
         assert_eq!(
@@ -1427,6 +2479,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn find_duplicates_matches_normalized_names_within_duration_tolerance() {
+        let track = |path: &str, title: &str, artist: Option<&str>| Track {
+            path: PathBuf::from(path),
+            title: String::from(title),
+            artist: artist.map(String::from),
+            album: None,
+        };
+        let tracks = vec![
+            track("a/song.mp3", "Song", Some("Band")),
+            track("b/song.flac", "song ", Some("BAND")),
+            track("c/song-live.mp3", "Song", Some("Band")),
+            track("d/song.mp3", "Song", None),
+            track("e/x.mp3", "X", Some("Y")),
+            track("f/x.mp3", "X", Some("Y")),
+            track("g/solo.mp3", "Solo", Some("Y")),
+        ];
+        let durations = HashMap::from([
+            (PathBuf::from("a/song.mp3"), 200),
+            (PathBuf::from("b/song.flac"), 202),
+            (PathBuf::from("c/song-live.mp3"), 230),
+        ]);
+
+        let clusters = find_duplicates(&tracks, |path| durations.get(path).copied());
+
+        assert_eq!(
+            clusters,
+            vec![
+                vec![PathBuf::from("a/song.mp3"), PathBuf::from("b/song.flac")],
+                vec![PathBuf::from("e/x.mp3"), PathBuf::from("f/x.mp3")],
+            ]
+        );
+    }
+
     #[test]
     fn tracks_from_index_filters_to_requested_roots() {
         let index = LibraryIndex {
@@ -1437,6 +2523,7 @@ mod tests {
                     artist: Some(String::from("artist")),
                     album: None,
                     fingerprint: None,
+                    first_seen_unix_seconds: 0,
                 },
                 LibraryIndexEntry {
                     path: PathBuf::from("/other/song2.flac"),
@@ -1444,6 +2531,7 @@ mod tests {
                     artist: None,
                     album: None,
                     fingerprint: None,
+                    first_seen_unix_seconds: 0,
                 },
             ],
         };
@@ -1464,6 +2552,7 @@ mod tests {
                     artist: None,
                     album: None,
                     fingerprint: None,
+                    first_seen_unix_seconds: 0,
                 },
                 LibraryIndexEntry {
                     path: PathBuf::from("/music/B/song2.flac"),
@@ -1471,6 +2560,7 @@ mod tests {
                     artist: None,
                     album: None,
                     fingerprint: None,
+                    first_seen_unix_seconds: 0,
                 },
             ],
         };
@@ -1480,4 +2570,38 @@ mod tests {
         assert_eq!(index.tracks.len(), 1);
         assert_eq!(index.tracks[0].path, PathBuf::from("/music/B/song2.flac"));
     }
+
+    #[test]
+    fn cover_art_lookup_quotes_query_skips_loose_matches_and_reads_cache() {
+        assert_eq!(
+            musicbrainz_release_query(" AC/DC ", "Back \"in\" Black"),
+            "release:\"Back \\\"in\\\" Black\" AND artist:\"AC/DC\""
+        );
+        let search = serde_json::json!({
+            "releases": [
+                { "id": "loose", "score": 40 },
+                { "id": "exact", "score": 100 },
+                { "id": "unscored" },
+            ]
+        });
+        assert_eq!(matching_release_ids(&search), vec!["exact", "unscored"]);
+        assert!(matching_release_ids(&serde_json::json!({})).is_empty());
+
+        let temp = tempdir().expect("tempdir");
+        let cache = temp.path().join("cover.img");
+        assert_eq!(cached_cover_art(&cache), None);
+        fs::write(&cache, b"").expect("write miss");
+        assert_eq!(cached_cover_art(&cache), Some(None));
+        fs::write(&cache, b"jpeg").expect("write hit");
+        assert_eq!(cached_cover_art(&cache), Some(Some(b"jpeg".to_vec())));
+    }
+
+    #[test]
+    fn tag_year_reads_first_four_digit_run() {
+        assert_eq!(parse_tag_year("1997"), Some(1997));
+        assert_eq!(parse_tag_year("1997-05-12"), Some(1997));
+        assert_eq!(parse_tag_year("12/05/1997"), Some(1997));
+        assert_eq!(parse_tag_year("0042"), None);
+        assert_eq!(parse_tag_year("abc"), None);
+    }
 }
@@ -1,7 +1,20 @@
 use anyhow::{Context, Result};
+use serde::Deserialize;
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const LRCLIB_SEARCH_URL: &str = "https://lrclib.net/api/search";
+/// LRCLIB asks clients to identify themselves.
+const LYRICS_USER_AGENT: &str = concat!(
+    "TuneTUI/",
+    env!("CARGO_PKG_VERSION"),
+    " ( https://github.com/Sqble/tui_music )"
+);
+const LYRICS_REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// Search hits whose length is further off than this are a different recording.
+const LYRICS_DURATION_TOLERANCE_SECONDS: f64 = 2.0;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LyricsTimingPrecision {
@@ -30,6 +43,17 @@ pub struct LyricsDocument {
     pub precision: LyricsTimingPrecision,
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LrclibRecord {
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    synced_lyrics: Option<String>,
+    #[serde(default)]
+    plain_lyrics: Option<String>,
+}
+
 pub fn sidecar_lrc_path(track_path: &Path) -> Result<PathBuf> {
     crate::config::lyrics_path_for_track(track_path)
 }
@@ -153,6 +177,82 @@ pub fn write_sidecar(track_path: &Path, doc: &LyricsDocument) -> Result<PathBuf>
     Ok(target)
 }
 
+/// Searches LRCLIB for lyrics to `title` by `artist`, ready to save with
+/// [`write_sidecar`]. Synced lyrics win when their timestamps are in order;
+/// otherwise plain lyrics come back without timing. This blocks on the
+/// network, so call it off the UI thread.
+pub fn fetch_synced(
+    artist: &str,
+    title: &str,
+    duration_seconds: Option<u32>,
+) -> Result<Option<LyricsDocument>> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(LYRICS_REQUEST_TIMEOUT))
+        .build()
+        .into();
+    let records: Vec<LrclibRecord> = agent
+        .get(LRCLIB_SEARCH_URL)
+        .query("artist_name", artist.trim())
+        .query("track_name", title.trim())
+        .header("User-Agent", LYRICS_USER_AGENT)
+        .call()
+        .context("LRCLIB search failed")?
+        .body_mut()
+        .read_json()
+        .context("LRCLIB sent an unreadable search result")?;
+    Ok(pick_fetched_lyrics(&records, duration_seconds))
+}
+
+fn pick_fetched_lyrics(
+    records: &[LrclibRecord],
+    duration_seconds: Option<u32>,
+) -> Option<LyricsDocument> {
+    let candidates: Vec<&LrclibRecord> = records
+        .iter()
+        .filter(|record| match (duration_seconds, record.duration) {
+            (Some(wanted), Some(found)) => {
+                (found - f64::from(wanted)).abs() <= LYRICS_DURATION_TOLERANCE_SECONDS
+            }
+            _ => true,
+        })
+        .collect();
+
+    let synced = candidates
+        .iter()
+        .filter_map(|record| record.synced_lyrics.as_deref())
+        .find(|raw| lrc_timestamps_are_monotonic(raw))
+        .map(parse_lrc);
+    let mut doc = synced.or_else(|| {
+        candidates
+            .iter()
+            .filter_map(|record| record.plain_lyrics.as_deref())
+            .find(|raw| !raw.trim().is_empty())
+            .map(parse_plain_text)
+    })?;
+    doc.source = LyricsSource::Sidecar;
+    Some(doc)
+}
+
+/// True when every timed line starts at or after the one before it. Only the
+/// first stamp of a line counts, since a repeated chorus may list later ones.
+fn lrc_timestamps_are_monotonic(raw: &str) -> bool {
+    let mut previous = None;
+    for line in raw.lines().map(str::trim) {
+        if is_metadata_lrc_line(line) {
+            continue;
+        }
+        let (timestamps, _) = parse_line_timestamps(line);
+        let Some(&first) = timestamps.first() else {
+            continue;
+        };
+        if previous.is_some_and(|previous| first < previous) {
+            return false;
+        }
+        previous = Some(first);
+    }
+    previous.is_some()
+}
+
 pub fn read_txt_for_import(path: &Path) -> Result<Vec<String>> {
     let raw = fs::read_to_string(path)
         .with_context(|| format!("failed to read txt file {}", path.display()))?;
@@ -383,4 +483,38 @@ mod tests {
         assert_eq!(doc.lines[1].timestamp_ms, Some(3000));
         assert_eq!(doc.lines[2].timestamp_ms, Some(6000));
     }
+
+    #[test]
+    fn fetched_lyrics_need_ordered_stamps_and_fall_back_to_plain() {
+        let record = |duration: f64, synced: Option<&str>, plain: Option<&str>| LrclibRecord {
+            duration: Some(duration),
+            synced_lyrics: synced.map(String::from),
+            plain_lyrics: plain.map(String::from),
+        };
+        assert!(lrc_timestamps_are_monotonic(
+            "[ar:Band]\n[00:01.00][00:30.00]chorus\n[00:05.00]verse\n"
+        ));
+        assert!(!lrc_timestamps_are_monotonic("plain words only"));
+
+        let records = vec![
+            record(300.0, Some("[00:01.00]wrong song\n"), None),
+            record(
+                181.5,
+                Some("[00:09.00]late\n[00:02.00]early\n"),
+                Some("first line\nsecond line\n"),
+            ),
+        ];
+        let doc = pick_fetched_lyrics(&records, Some(180)).expect("plain fallback");
+        assert_eq!(doc.precision, LyricsTimingPrecision::None);
+        assert_eq!(doc.source, LyricsSource::Sidecar);
+        assert_eq!(doc.lines.len(), 2);
+        assert_eq!(doc.lines[0].text, "first line");
+
+        let records = vec![record(179.0, Some("[00:01.00]one\n[00:02.50]two\n"), None)];
+        let doc = pick_fetched_lyrics(&records, Some(180)).expect("synced lyrics");
+        assert_eq!(doc.precision, LyricsTimingPrecision::Line);
+        assert_eq!(doc.lines[1].timestamp_ms, Some(2500));
+
+        assert!(pick_fetched_lyrics(&records, Some(240)).is_none());
+    }
 }
@@ -1,3 +1,6 @@
+use tune::online_net::HomeServerStatus;
+use tune::online_secure::RoomTransport;
+
 const DEFAULT_HOME_SERVER_PORT: u16 = 7878;
 const DEFAULT_ROOM_PORT_RANGE: (u16, u16) = (9000, 9100);
 
@@ -8,11 +11,22 @@ struct CliArgs {
     ip: Option<String>,
     host_ip: Option<String>,
     room_port_range: Option<(u16, u16)>,
+    multi_instance: bool,
+    debug_sync: bool,
+    plain_rooms: bool,
+    status: bool,
+    nickname: Option<String>,
+    join: Option<String>,
+    host_room: Option<String>,
+    room_password: Option<String>,
+    scan: Vec<std::path::PathBuf>,
 }
 
 fn main() -> anyhow::Result<()> {
     let args = parse_args(std::env::args().skip(1).collect())?;
     let ip_provided = args.ip.is_some();
+    let allow_multiple_instances = args.multi_instance || multi_instance_env_enabled();
+    let debug_sync = args.debug_sync || env_flag_enabled("TUNETUI_DEBUG_SYNC");
     let host_addr = args
         .host_ip
         .clone()
@@ -23,26 +37,83 @@ fn main() -> anyhow::Result<()> {
     } else {
         None
     };
+    let online_nickname = args.nickname.clone();
+    let startup_join = args
+        .join
+        .clone()
+        .map(|target| (target, false))
+        .or_else(|| args.host_room.clone().map(|target| (target, true)))
+        .map(|(target, host)| tune::app::StartupJoin {
+            target,
+            password: args
+                .room_password
+                .clone()
+                .or_else(|| std::env::var("TUNETUI_ROOM_PASSWORD").ok())
+                .filter(|value| !value.is_empty()),
+            host,
+        });
+    let room_transport = if args.plain_rooms {
+        RoomTransport::Plain
+    } else {
+        RoomTransport::Sealed
+    };
+
+    if args.status {
+        let target = args
+            .ip
+            .unwrap_or_else(|| format!("127.0.0.1:{DEFAULT_HOME_SERVER_PORT}"));
+        let status = tune::online_net::home_server_status(&target)?;
+        print!("{}", home_status_report(&target, &status));
+        return Ok(());
+    }
+
+    if !args.scan.is_empty() {
+        println!("{}", tune::app::scan_library_headless(&args.scan)?);
+        return Ok(());
+    }
 
     if args.host && !args.app {
-        return tune::online_net::run_home_server_forever_with_ports(&host_addr, room_port_range);
+        return tune::online_net::run_home_server_forever_with_ports(
+            &host_addr,
+            room_port_range,
+            room_transport,
+        );
     }
 
     if args.host && args.app {
-        let _server = tune::online_net::start_home_server(&host_addr, room_port_range)?;
+        let server =
+            tune::online_net::start_home_server(&host_addr, room_port_range, room_transport)?;
         let app_target = local_home_target_from_bind_addr(&host_addr);
         return tune::app::run_with_startup(tune::app::AppStartupOptions {
             default_home_server_addr: Some(app_target),
             home_server_connected: true,
+            local_home_server: Some(server),
+            allow_multiple_instances,
+            debug_sync,
+            online_nickname,
+            startup_join,
         });
     }
 
     tune::app::run_with_startup(tune::app::AppStartupOptions {
         default_home_server_addr: args.ip,
         home_server_connected: ip_provided,
+        local_home_server: None,
+        allow_multiple_instances,
+        debug_sync,
+        online_nickname,
+        startup_join,
     })
 }
 
+fn multi_instance_env_enabled() -> bool {
+    env_flag_enabled("TUNETUI_MULTI_INSTANCE")
+}
+
+fn env_flag_enabled(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|value| matches!(value.trim(), "1" | "true" | "yes"))
+}
+
 fn local_home_target_from_bind_addr(bind_addr: &str) -> String {
     match bind_addr.parse::<std::net::SocketAddr>() {
         Ok(std::net::SocketAddr::V4(addr)) if addr.ip().is_unspecified() => {
@@ -55,6 +126,34 @@ fn local_home_target_from_bind_addr(bind_addr: &str) -> String {
     }
 }
 
+/// One `key=value` line for the server, then one per room, so scripts can
+/// grep and split without a JSON parser.
+fn home_status_report(server_addr: &str, status: &HomeServerStatus) -> String {
+    let connections: u32 = status
+        .rooms
+        .iter()
+        .map(|room| u32::from(room.current_connections))
+        .sum();
+    let mut report = format!(
+        "server={server_addr} uptime_seconds={} room_ports={} rooms={} connections={connections}\n",
+        status.uptime_seconds,
+        status.room_ports,
+        status.rooms.len()
+    );
+    for room in &status.rooms {
+        report.push_str(&format!(
+            "room={:?} port={} connections={}/{} locked={} encrypted={}\n",
+            room.room_name,
+            room.port,
+            room.current_connections,
+            room.max_connections,
+            room.locked,
+            room.encrypted
+        ));
+    }
+    report
+}
+
 fn parse_args(args: Vec<String>) -> anyhow::Result<CliArgs> {
     let mut out = CliArgs::default();
     let mut index = 0;
@@ -62,6 +161,10 @@ fn parse_args(args: Vec<String>) -> anyhow::Result<CliArgs> {
         match args[index].as_str() {
             "--host" => out.host = true,
             "--app" => out.app = true,
+            "--multi-instance" => out.multi_instance = true,
+            "--debug-sync" => out.debug_sync = true,
+            "--plain-rooms" => out.plain_rooms = true,
+            "--status" => out.status = true,
             "--ip" => {
                 index += 1;
                 let Some(value) = args.get(index) else {
@@ -89,6 +192,56 @@ fn parse_args(args: Vec<String>) -> anyhow::Result<CliArgs> {
                 };
                 out.room_port_range = Some(parse_port_range(value)?);
             }
+            "--scan" => {
+                index += 1;
+                let Some(value) = args.get(index) else {
+                    anyhow::bail!("--scan requires a folder path");
+                };
+                if value.trim().is_empty() {
+                    anyhow::bail!("--scan cannot be empty");
+                }
+                out.scan.push(std::path::PathBuf::from(value.trim()));
+            }
+            "--nickname" => {
+                index += 1;
+                let Some(value) = args.get(index) else {
+                    anyhow::bail!("--nickname requires a name");
+                };
+                if value.trim().is_empty() {
+                    anyhow::bail!("--nickname cannot be empty");
+                }
+                out.nickname = Some(value.trim().to_string());
+            }
+            "--join" => {
+                index += 1;
+                let Some(value) = args.get(index) else {
+                    anyhow::bail!("--join requires a room name or host[:port]/room/NAME link");
+                };
+                if value.trim().is_empty() {
+                    anyhow::bail!("--join cannot be empty");
+                }
+                out.join = Some(value.trim().to_string());
+            }
+            "--host-room" => {
+                index += 1;
+                let Some(value) = args.get(index) else {
+                    anyhow::bail!("--host-room requires a room name");
+                };
+                if value.trim().is_empty() {
+                    anyhow::bail!("--host-room cannot be empty");
+                }
+                out.host_room = Some(value.trim().to_string());
+            }
+            "--room-password" => {
+                index += 1;
+                let Some(value) = args.get(index) else {
+                    anyhow::bail!("--room-password requires a value");
+                };
+                if value.is_empty() {
+                    anyhow::bail!("--room-password cannot be empty");
+                }
+                out.room_password = Some(value.clone());
+            }
             "-h" | "--help" => {
                 print_help();
                 std::process::exit(0);
@@ -103,6 +256,37 @@ fn parse_args(args: Vec<String>) -> anyhow::Result<CliArgs> {
     if out.room_port_range.is_some() && !out.host {
         anyhow::bail!("--room-port-range requires --host");
     }
+    if out.plain_rooms && !out.host {
+        anyhow::bail!("--plain-rooms requires --host");
+    }
+    if out.status && (out.host || out.app) {
+        anyhow::bail!("--status queries a running server; it cannot be combined with --host");
+    }
+    if out.join.is_some() && out.host {
+        anyhow::bail!("--join connects to an existing room; it cannot be combined with --host");
+    }
+    if out.host_room.is_some() && out.join.is_some() {
+        anyhow::bail!("--host-room and --join cannot be combined");
+    }
+    if out.host_room.is_some() && out.host && !out.app {
+        anyhow::bail!("--host-room needs the app; use it without --host or with --host --app");
+    }
+    if out.room_password.is_some() && out.join.is_none() && out.host_room.is_none() {
+        anyhow::bail!("--room-password requires --join or --host-room");
+    }
+    if out.nickname.is_some() && out.host && !out.app {
+        anyhow::bail!("--nickname needs the app; use it without --host or with --host --app");
+    }
+    if out.status && (out.nickname.is_some() || out.join.is_some() || out.host_room.is_some()) {
+        anyhow::bail!("--status cannot be combined with --nickname, --join or --host-room");
+    }
+    if !out.scan.is_empty()
+        && (out.host || out.app || out.status || out.join.is_some() || out.host_room.is_some())
+    {
+        anyhow::bail!(
+            "--scan indexes and exits; it cannot be combined with --host, --status, --join or --host-room"
+        );
+    }
     if out.host && out.host_ip.is_some() && out.ip.is_some() {
         anyhow::bail!(
             "use --host-ip for host bind address or --ip as the legacy host alias, not both"
@@ -115,6 +299,8 @@ fn print_help() {
     println!("TuneTUI");
     println!("  --host            Run home server mode");
     println!("  --app             With --host, also run TUI app");
+    println!("  --multi-instance  Allow another TUI instance to run alongside this one (Windows)");
+    println!("  --debug-sync      Log every Listen Together sync decision to sync_debug.log");
     println!(
         "  --host-ip host[:port]  Bind address for --host (default 0.0.0.0:{})",
         DEFAULT_HOME_SERVER_PORT
@@ -127,6 +313,24 @@ fn print_help() {
         "  --room-port-range start-end   Room port range for host mode (default {}-{})",
         DEFAULT_ROOM_PORT_RANGE.0, DEFAULT_ROOM_PORT_RANGE.1
     );
+    println!(
+        "  --plain-rooms     With --host, leave password rooms unencrypted (trusted LAN only)"
+    );
+    println!("  --scan DIR        Add DIR to the library, rescan, save, and exit (repeatable)");
+    println!("  --nickname NAME   Online nickname, saved as if typed into the prompt");
+    println!(
+        "  --join ROOM       On launch, join ROOM on the --ip server or a host[:port]/room/NAME link"
+    );
+    println!(
+        "  --host-room ROOM  On launch, create and host ROOM on the --ip server (or --host --app)"
+    );
+    println!(
+        "  --room-password PASS  Password for --join or --host-room (TUNETUI_ROOM_PASSWORD keeps it out of ps)"
+    );
+    println!(
+        "  --status          Print the rooms of the home server at --ip (default 127.0.0.1:{}) and exit",
+        DEFAULT_HOME_SERVER_PORT
+    );
 }
 
 fn normalize_home_server_addr(raw: &str) -> String {
@@ -180,8 +384,10 @@ fn parse_port_range(raw: &str) -> anyhow::Result<(u16, u16)> {
 #[cfg(test)]
 mod tests {
     use super::{
-        local_home_target_from_bind_addr, normalize_home_server_addr, parse_args, parse_port_range,
+        home_status_report, local_home_target_from_bind_addr, normalize_home_server_addr,
+        parse_args, parse_port_range,
     };
+    use tune::online_net::{HomeRoomStatus, HomeServerStatus};
 
     fn args(values: &[&str]) -> Vec<String> {
         values.iter().map(|value| (*value).to_string()).collect()
@@ -265,6 +471,135 @@ mod tests {
         assert_eq!(parsed.host_ip, None);
     }
 
+    #[test]
+    fn parse_args_accepts_plain_rooms_only_with_host() {
+        let parsed = parse_args(args(&["--host", "--plain-rooms"])).expect("args");
+        assert!(parsed.plain_rooms);
+
+        let err = parse_args(args(&["--plain-rooms"])).expect_err("needs --host");
+        assert!(err.to_string().contains("requires --host"));
+    }
+
+    #[test]
+    fn parse_args_accepts_status_only_without_host() {
+        let parsed = parse_args(args(&["--status", "--ip", "203.0.113.5"])).expect("args");
+        assert!(parsed.status);
+        assert_eq!(parsed.ip.as_deref(), Some("203.0.113.5:7878"));
+
+        let err = parse_args(args(&["--host", "--status"])).expect_err("status with host");
+        assert!(err.to_string().contains("cannot be combined"));
+    }
+
+    #[test]
+    fn parse_args_accepts_join_with_nickname_and_password() {
+        let parsed = parse_args(args(&[
+            "--ip",
+            "203.0.113.5",
+            "--nickname",
+            " kiosk ",
+            "--join",
+            "lounge",
+            "--room-password",
+            " secret ",
+        ]))
+        .expect("args");
+        assert_eq!(parsed.nickname.as_deref(), Some("kiosk"));
+        assert_eq!(parsed.join.as_deref(), Some("lounge"));
+        assert_eq!(parsed.room_password.as_deref(), Some(" secret "));
+
+        let parsed = parse_args(args(&["--host", "--app", "--nickname", "dj"])).expect("args");
+        assert_eq!(parsed.nickname.as_deref(), Some("dj"));
+    }
+
+    #[test]
+    fn parse_args_accepts_host_room_with_optional_password() {
+        let parsed = parse_args(args(&[
+            "--host",
+            "--app",
+            "--host-room",
+            " Friday Mix ",
+            "--room-password",
+            "secret",
+        ]))
+        .expect("args");
+        assert_eq!(parsed.host_room.as_deref(), Some("Friday Mix"));
+        assert_eq!(parsed.room_password.as_deref(), Some("secret"));
+
+        let parsed =
+            parse_args(args(&["--ip", "203.0.113.5", "--host-room", "lounge"])).expect("args");
+        assert_eq!(parsed.host_room.as_deref(), Some("lounge"));
+        assert_eq!(parsed.room_password, None);
+
+        let err = parse_args(args(&["--host", "--host-room", "lounge"])).expect_err("no app");
+        assert!(err.to_string().contains("needs the app"));
+        let err = parse_args(args(&["--join", "a", "--host-room", "b"])).expect_err("both");
+        assert!(err.to_string().contains("cannot be combined"));
+        assert!(parse_args(args(&["--host-room", " "])).is_err());
+    }
+
+    #[test]
+    fn parse_args_collects_repeated_scan_folders() {
+        let parsed =
+            parse_args(args(&["--scan", "/mnt/nas/music", "--scan", " /srv/flac "])).expect("args");
+        assert_eq!(
+            parsed.scan,
+            vec![
+                std::path::PathBuf::from("/mnt/nas/music"),
+                std::path::PathBuf::from("/srv/flac")
+            ]
+        );
+
+        let err = parse_args(args(&["--host", "--scan", "/srv/flac"])).expect_err("scan with host");
+        assert!(err.to_string().contains("cannot be combined"));
+        assert!(parse_args(args(&["--scan"])).is_err());
+    }
+
+    #[test]
+    fn parse_args_rejects_join_with_host_and_stray_password() {
+        let err = parse_args(args(&["--host", "--app", "--join", "lounge"])).expect_err("host");
+        assert!(err.to_string().contains("cannot be combined with --host"));
+
+        let err = parse_args(args(&["--room-password", "secret"])).expect_err("no join");
+        assert!(err.to_string().contains("requires --join"));
+
+        assert!(parse_args(args(&["--host", "--nickname", "dj"])).is_err());
+        assert!(parse_args(args(&["--join", "  "])).is_err());
+        assert!(parse_args(args(&["--nickname"])).is_err());
+    }
+
+    #[test]
+    fn home_status_report_has_a_line_per_room() {
+        let status = HomeServerStatus {
+            uptime_seconds: 3_600,
+            room_ports: String::from("9000-9100"),
+            rooms: vec![
+                HomeRoomStatus {
+                    room_name: String::from("Friday Mix"),
+                    port: 9003,
+                    current_connections: 3,
+                    max_connections: 8,
+                    locked: true,
+                    encrypted: true,
+                },
+                HomeRoomStatus {
+                    room_name: String::from("lobby"),
+                    port: 9004,
+                    current_connections: 1,
+                    max_connections: 4,
+                    locked: false,
+                    encrypted: false,
+                },
+            ],
+        };
+
+        assert_eq!(
+            home_status_report("127.0.0.1:7878", &status),
+            "server=127.0.0.1:7878 uptime_seconds=3600 room_ports=9000-9100 rooms=2 connections=4\n\
+             room=\"Friday Mix\" port=9003 connections=3/8 locked=true encrypted=true\n\
+             room=\"lobby\" port=9004 connections=1/4 locked=false encrypted=false\n"
+        );
+    }
+
     #[test]
     fn parse_args_rejects_ambiguous_host_ip_and_ip() {
         let err = parse_args(args(&[
@@ -278,6 +613,20 @@ mod tests {
         assert!(err.to_string().contains("not both"));
     }
 
+    #[test]
+    fn parse_args_accepts_multi_instance_flag() {
+        let parsed = parse_args(args(&["--multi-instance"])).expect("args");
+        assert!(parsed.multi_instance);
+        assert!(!parse_args(args(&[])).expect("args").multi_instance);
+    }
+
+    #[test]
+    fn parse_args_accepts_debug_sync_flag() {
+        let parsed = parse_args(args(&["--debug-sync"])).expect("args");
+        assert!(parsed.debug_sync);
+        assert!(!parse_args(args(&[])).expect("args").debug_sync);
+    }
+
     #[test]
     fn parse_args_rejects_host_ip_without_host() {
         let err = parse_args(args(&["--host-ip", "0.0.0.0"]))
@@ -18,6 +18,53 @@ pub enum RepeatMode {
     One,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum EndOfQueueBehavior {
+    #[default]
+    Stop,
+    RepeatQueue,
+    KeepGoing,
+    Radio,
+}
+
+/// Shape of the volume ramps while two tracks overlap in a crossfade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CrossfadeCurve {
+    #[default]
+    Linear,
+    /// Keeps the combined power steady, so there is no dip in loudness mid-fade.
+    EqualPower,
+    /// Brings the incoming track up quickly and lets the outgoing one trail off.
+    Logarithmic,
+}
+
+/// How a new shuffle order is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ShuffleMode {
+    /// Every order equally likely, recently heard tracks included.
+    Random,
+    /// Recently played tracks go to the back of the order.
+    #[default]
+    AvoidRecent,
+    /// Like `AvoidRecent`, and tracks with fewer plays tend to come up first.
+    FewerPlays,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum KeepGoingPool {
+    #[default]
+    SameFolder,
+    SameArtist,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum FolderEnterBehavior {
+    #[default]
+    Open,
+    Play,
+    Ask,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum Theme {
     #[default]
@@ -86,6 +133,114 @@ impl RepeatMode {
     }
 }
 
+impl EndOfQueueBehavior {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Stop => Self::RepeatQueue,
+            Self::RepeatQueue => Self::KeepGoing,
+            Self::KeepGoing => Self::Radio,
+            Self::Radio => Self::Stop,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Stop => "Stop",
+            Self::RepeatQueue => "Repeat queue",
+            Self::KeepGoing => "Keep going",
+            Self::Radio => "Radio",
+        }
+    }
+}
+
+impl FolderEnterBehavior {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Open => Self::Play,
+            Self::Play => Self::Ask,
+            Self::Ask => Self::Open,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Open => "Open folder",
+            Self::Play => "Play folder",
+            Self::Ask => "Ask",
+        }
+    }
+}
+
+impl CrossfadeCurve {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Linear => Self::EqualPower,
+            Self::EqualPower => Self::Logarithmic,
+            Self::Logarithmic => Self::Linear,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::EqualPower => "Equal power",
+            Self::Logarithmic => "Logarithmic",
+        }
+    }
+}
+
+impl SmartPlaylistSort {
+    pub fn next(self) -> Self {
+        match self {
+            Self::MostPlayed => Self::LastPlayed,
+            Self::LastPlayed => Self::DateAdded,
+            Self::DateAdded => Self::MostPlayed,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MostPlayed => "Most played",
+            Self::LastPlayed => "Last played",
+            Self::DateAdded => "Date added",
+        }
+    }
+}
+
+impl ShuffleMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Random => Self::AvoidRecent,
+            Self::AvoidRecent => Self::FewerPlays,
+            Self::FewerPlays => Self::Random,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Random => "Random",
+            Self::AvoidRecent => "Avoid recent",
+            Self::FewerPlays => "Avoid recent, fewer plays first",
+        }
+    }
+}
+
+impl KeepGoingPool {
+    pub fn next(self) -> Self {
+        match self {
+            Self::SameFolder => Self::SameArtist,
+            Self::SameArtist => Self::SameFolder,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::SameFolder => "Same folder",
+            Self::SameArtist => "Same artist",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Track {
     pub path: PathBuf,
@@ -99,28 +254,166 @@ pub struct Playlist {
     pub tracks: Vec<PathBuf>,
 }
 
+/// What a smart playlist ranks the library by before taking its limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum SmartPlaylistSort {
+    #[default]
+    MostPlayed,
+    LastPlayed,
+    /// Newest file modification time first, the closest thing to an added
+    /// date that the library keeps.
+    DateAdded,
+}
+
+/// Rules for a playlist that is rebuilt from listening stats and library
+/// metadata when opened, instead of storing a track list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmartPlaylist {
+    pub sort: SmartPlaylistSort,
+    pub limit: usize,
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub genre: Option<String>,
+}
+
+/// Server and room of the last successful Listen Together join. The password
+/// only reaches the state file when the user opts in, and then only sealed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastOnlineJoin {
+    pub server_addr: String,
+    pub room_name: String,
+    /// Never written out; older state files may still carry it in the clear.
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    /// `password` sealed with the local state key when the state is saved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sealed_password: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "LibraryFolderRecord")]
+pub struct LibraryFolder {
+    pub path: PathBuf,
+    pub enabled: bool,
+}
+
+impl LibraryFolder {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            enabled: true,
+        }
+    }
+}
+
+/// Older state files stored library roots as bare paths.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LibraryFolderRecord {
+    Path(PathBuf),
+    Entry {
+        path: PathBuf,
+        #[serde(default = "default_folder_enabled")]
+        enabled: bool,
+    },
+}
+
+impl From<LibraryFolderRecord> for LibraryFolder {
+    fn from(record: LibraryFolderRecord) -> Self {
+        match record {
+            LibraryFolderRecord::Path(path) => Self::new(path),
+            LibraryFolderRecord::Entry { path, enabled } => Self { path, enabled },
+        }
+    }
+}
+
+pub fn enabled_folder_paths(folders: &[LibraryFolder]) -> Vec<PathBuf> {
+    folders
+        .iter()
+        .filter(|folder| folder.enabled)
+        .map(|folder| folder.path.clone())
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistedState {
-    pub folders: Vec<PathBuf>,
+    pub folders: Vec<LibraryFolder>,
     pub playlists: HashMap<String, Playlist>,
     #[serde(default)]
+    pub smart_playlists: HashMap<String, SmartPlaylist>,
+    #[serde(default)]
     pub shuffle_enabled: bool,
     #[serde(default)]
+    pub shuffle_mode: ShuffleMode,
+    /// Share of the queue, in percent, that smart shuffle treats as recently
+    /// played.
+    #[serde(default = "default_shuffle_recent_percent")]
+    pub shuffle_recent_percent: u8,
+    #[serde(default)]
     pub repeat_mode: RepeatMode,
     #[serde(default, skip_serializing)]
     pub playback_mode: Option<LegacyPlaybackMode>,
     #[serde(default)]
     pub loudness_normalization: bool,
+    #[serde(default = "default_prevent_clipping")]
+    pub prevent_clipping: bool,
+    #[serde(default = "default_equalizer_bands")]
+    pub equalizer_bands: Vec<(f32, f32)>,
+    #[serde(default)]
+    pub gapless: bool,
+    #[serde(default)]
+    pub album_gain: bool,
+    #[serde(default = "default_playback_speed")]
+    pub playback_speed: f32,
+    #[serde(default = "default_preserve_pitch")]
+    pub preserve_pitch: bool,
+    #[serde(default)]
+    pub mono_output: bool,
+    #[serde(default)]
+    pub balance: f32,
     #[serde(default)]
     pub crossfade_seconds: u16,
+    #[serde(default)]
+    pub crossfade_curve: CrossfadeCurve,
+    #[serde(default)]
+    pub crossfade_only_when_shuffling: bool,
+    #[serde(default)]
+    pub sleep_on_silence_minutes: u16,
+    #[serde(default)]
+    pub window_title_shows_track: bool,
+    #[serde(default = "default_backup_before_tag_writes")]
+    pub backup_before_tag_writes: bool,
+    #[serde(default)]
+    pub tag_backup_dir: Option<PathBuf>,
     #[serde(default = "default_scrub_seconds")]
     pub scrub_seconds: u16,
+    #[serde(default = "default_scrub_back_key")]
+    pub scrub_back_key: char,
+    #[serde(default = "default_scrub_forward_key")]
+    pub scrub_forward_key: char,
+    #[serde(default)]
+    pub arrow_keys_seek: bool,
+    #[serde(default)]
+    pub track_gap_seconds: u16,
+    #[serde(default)]
+    pub daily_listen_goal_minutes: u16,
+    #[serde(default = "default_crossfade_lead_ms")]
+    pub crossfade_lead_ms: u16,
+    #[serde(default)]
+    pub stats_grace_seconds: u16,
+    #[serde(default)]
+    pub stats_completion_percent: u8,
+    #[serde(default)]
+    pub compact_layout: Option<bool>,
     #[serde(default)]
     pub theme: Theme,
     #[serde(default)]
     pub selected_output_device: Option<String>,
     #[serde(default = "default_saved_volume")]
     pub saved_volume: f32,
+    #[serde(default)]
+    pub saved_muted: bool,
     #[serde(default = "default_stats_enabled")]
     pub stats_enabled: bool,
     #[serde(default = "default_online_sync_correction_threshold_ms")]
@@ -131,6 +424,74 @@ pub struct PersistedState {
     pub fallback_cover_template: CoverArtTemplate,
     #[serde(default)]
     pub online_nickname: Option<String>,
+    #[serde(default)]
+    pub last_online_join: Option<LastOnlineJoin>,
+    #[serde(default)]
+    pub remember_online_password: bool,
+    #[serde(default)]
+    pub no_crossfade_tracks: Vec<PathBuf>,
+    #[serde(default)]
+    pub end_of_queue_behavior: EndOfQueueBehavior,
+    #[serde(default)]
+    pub keep_going_pool: KeepGoingPool,
+    #[serde(default)]
+    pub folder_enter_behavior: FolderEnterBehavior,
+    #[serde(default = "default_scan_ignore_patterns")]
+    pub scan_ignore_patterns: Vec<String>,
+    /// Files taken out of the library by duplicate cleanup; scans skip them.
+    #[serde(default)]
+    pub hidden_tracks: Vec<PathBuf>,
+    #[serde(default = "default_now_playing_template")]
+    pub now_playing_template: String,
+    /// User token for ListenBrainz scrobbling; scrobbling is off without one.
+    #[serde(default)]
+    pub listenbrainz_token: Option<String>,
+}
+
+/// Centre frequencies of the equalizer bands, lowest first.
+pub const EQUALIZER_BAND_FREQUENCIES_HZ: [f32; 5] = [60.0, 230.0, 910.0, 3_600.0, 14_000.0];
+
+/// Flat equalizer: every band at 0 dB.
+pub fn default_equalizer_bands() -> Vec<(f32, f32)> {
+    EQUALIZER_BAND_FREQUENCIES_HZ
+        .into_iter()
+        .map(|frequency| (frequency, 0.0))
+        .collect()
+}
+
+pub fn default_scan_ignore_patterns() -> Vec<String> {
+    [".*", "__MACOSX", "@eaDir"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+pub fn default_now_playing_template() -> String {
+    String::from("{title} - {artist}")
+}
+
+fn default_folder_enabled() -> bool {
+    true
+}
+
+fn default_backup_before_tag_writes() -> bool {
+    false
+}
+
+fn default_prevent_clipping() -> bool {
+    true
+}
+
+fn default_shuffle_recent_percent() -> u8 {
+    20
+}
+
+fn default_playback_speed() -> f32 {
+    1.0
+}
+
+fn default_preserve_pitch() -> bool {
+    true
 }
 
 fn default_stats_enabled() -> bool {
@@ -141,10 +502,22 @@ fn default_saved_volume() -> f32 {
     1.0
 }
 
+fn default_scrub_back_key() -> char {
+    'a'
+}
+
+fn default_scrub_forward_key() -> char {
+    'd'
+}
+
 fn default_scrub_seconds() -> u16 {
     5
 }
 
+fn default_crossfade_lead_ms() -> u16 {
+    500
+}
+
 fn default_online_sync_correction_threshold_ms() -> u16 {
     300
 }
@@ -158,20 +531,57 @@ impl Default for PersistedState {
         Self {
             folders: Vec::new(),
             playlists: HashMap::new(),
+            smart_playlists: HashMap::new(),
             shuffle_enabled: false,
+            shuffle_mode: ShuffleMode::default(),
+            shuffle_recent_percent: default_shuffle_recent_percent(),
             repeat_mode: RepeatMode::Off,
             playback_mode: None,
             loudness_normalization: false,
+            prevent_clipping: default_prevent_clipping(),
+            equalizer_bands: default_equalizer_bands(),
+            gapless: false,
+            album_gain: false,
+            playback_speed: default_playback_speed(),
+            preserve_pitch: default_preserve_pitch(),
+            mono_output: false,
+            balance: 0.0,
             crossfade_seconds: 0,
+            crossfade_curve: CrossfadeCurve::default(),
+            crossfade_only_when_shuffling: false,
+            sleep_on_silence_minutes: 0,
+            window_title_shows_track: false,
+            backup_before_tag_writes: default_backup_before_tag_writes(),
+            tag_backup_dir: None,
             scrub_seconds: default_scrub_seconds(),
+            scrub_back_key: default_scrub_back_key(),
+            scrub_forward_key: default_scrub_forward_key(),
+            arrow_keys_seek: false,
+            track_gap_seconds: 0,
+            daily_listen_goal_minutes: 0,
+            crossfade_lead_ms: default_crossfade_lead_ms(),
+            stats_grace_seconds: 0,
+            stats_completion_percent: 0,
+            compact_layout: None,
             theme: Theme::default(),
             selected_output_device: None,
             saved_volume: default_saved_volume(),
+            saved_muted: false,
             stats_enabled: default_stats_enabled(),
             online_sync_correction_threshold_ms: default_online_sync_correction_threshold_ms(),
             stats_top_songs_count: default_stats_top_songs_count(),
             fallback_cover_template: CoverArtTemplate::default(),
             online_nickname: None,
+            last_online_join: None,
+            remember_online_password: false,
+            no_crossfade_tracks: Vec::new(),
+            end_of_queue_behavior: EndOfQueueBehavior::default(),
+            keep_going_pool: KeepGoingPool::default(),
+            folder_enter_behavior: FolderEnterBehavior::default(),
+            scan_ignore_patterns: default_scan_ignore_patterns(),
+            hidden_tracks: Vec::new(),
+            now_playing_template: default_now_playing_template(),
+            listenbrainz_token: None,
         }
     }
 }
@@ -5,6 +5,10 @@ use std::path::{Path, PathBuf};
 
 const ROOM_CODE_LEN: usize = 6;
 pub(crate) const MAX_SHARED_QUEUE_ITEMS: usize = 512;
+/// Tracks one listener may have waiting in a host-only room's shared queue.
+pub(crate) const MAX_LISTENER_REQUESTS: usize = 3;
+pub(crate) const MAX_CHAT_LOG_ITEMS: usize = 200;
+pub(crate) const MAX_CHAT_MESSAGE_CHARS: usize = 300;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OnlineRoomMode {
@@ -31,21 +35,40 @@ impl OnlineRoomMode {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StreamQuality {
     Lossless,
+    /// Opus at 320 kbps.
+    High,
+    /// Opus at 160 kbps.
     Balanced,
+    /// Opus at 96 kbps, for listeners on slow or metered connections.
+    Low,
 }
 
 impl StreamQuality {
     pub fn next(self) -> Self {
         match self {
-            Self::Lossless => Self::Balanced,
-            Self::Balanced => Self::Lossless,
+            Self::Lossless => Self::High,
+            Self::High => Self::Balanced,
+            Self::Balanced => Self::Low,
+            Self::Low => Self::Lossless,
         }
     }
 
     pub fn label(self) -> &'static str {
         match self {
             Self::Lossless => "Lossless",
+            Self::High => "High",
             Self::Balanced => "Balanced",
+            Self::Low => "Low",
+        }
+    }
+
+    /// Target Opus bitrate, or `None` when the original file is sent as is.
+    pub fn opus_bitrate_kbps(self) -> Option<u32> {
+        match self {
+            Self::Lossless => None,
+            Self::High => Some(320),
+            Self::Balanced => Some(160),
+            Self::Low => Some(96),
         }
     }
 }
@@ -74,6 +97,12 @@ pub struct SharedQueueItem {
     pub owner_nickname: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub from: String,
+    pub message: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Participant {
     pub nickname: String,
@@ -82,6 +111,9 @@ pub struct Participant {
     pub ping_ms: u16,
     pub manual_extra_delay_ms: u16,
     pub auto_ping_delay: bool,
+    /// Drift this participant last reported, host position minus theirs.
+    #[serde(default)]
+    pub last_drift_ms: Option<i32>,
 }
 
 impl Participant {
@@ -140,8 +172,17 @@ pub struct OnlineSession {
     pub quality: StreamQuality,
     pub participants: Vec<Participant>,
     pub shared_queue: VecDeque<SharedQueueItem>,
+    /// Host position minus local position at the last sync, so positive means
+    /// local playback was behind.
     pub last_sync_drift_ms: i32,
     pub last_transport: Option<TransportEnvelope>,
+    /// Oldest first, capped at `MAX_CHAT_LOG_ITEMS`.
+    #[serde(default)]
+    pub chat_log: VecDeque<ChatMessage>,
+    /// Nicknames that voted to skip the current track. Cleared whenever the
+    /// room's track changes.
+    #[serde(default)]
+    pub skip_votes: Vec<String>,
 }
 
 impl OnlineSession {
@@ -157,10 +198,13 @@ impl OnlineSession {
                 ping_ms: 0,
                 manual_extra_delay_ms: 0,
                 auto_ping_delay: true,
+                last_drift_ms: None,
             }],
             shared_queue: VecDeque::new(),
             last_sync_drift_ms: 0,
             last_transport: None,
+            chat_log: VecDeque::new(),
+            skip_votes: Vec::new(),
         }
     }
 
@@ -180,10 +224,13 @@ impl OnlineSession {
                 ping_ms: 0,
                 manual_extra_delay_ms: 0,
                 auto_ping_delay: true,
+                last_drift_ms: None,
             }],
             shared_queue: VecDeque::new(),
             last_sync_drift_ms: 0,
             last_transport: None,
+            chat_log: VecDeque::new(),
+            skip_votes: Vec::new(),
         }
     }
 
@@ -235,6 +282,25 @@ impl OnlineSession {
         }
     }
 
+    /// Rearranges the shared queue so that position `i` holds the item that was
+    /// at `order[i]`. Ignored unless `order` is a permutation of the whole queue,
+    /// which keeps a stale order from an out-of-date client from dropping items.
+    pub fn reorder_shared_queue(&mut self, order: &[usize]) -> bool {
+        let len = self.shared_queue.len();
+        if order.len() != len {
+            return false;
+        }
+        let mut seen = vec![false; len];
+        for &index in order {
+            if index >= len || std::mem::replace(&mut seen[index], true) {
+                return false;
+            }
+        }
+        let previous: Vec<SharedQueueItem> = self.shared_queue.drain(..).collect();
+        self.shared_queue = order.iter().map(|&index| previous[index].clone()).collect();
+        true
+    }
+
     pub fn push_shared_track(
         &mut self,
         path: &Path,
@@ -262,6 +328,76 @@ impl OnlineSession {
             }
         }
     }
+
+    /// How many more tracks `nickname` may request in a host-only room, where
+    /// listeners can only add to the shared queue under their own name.
+    pub fn request_slots_left(&self, nickname: &str) -> usize {
+        let pending = self
+            .shared_queue
+            .iter()
+            .filter(|item| {
+                item.owner_nickname
+                    .as_deref()
+                    .is_some_and(|owner| owner.eq_ignore_ascii_case(nickname))
+            })
+            .count();
+        MAX_LISTENER_REQUESTS.saturating_sub(pending)
+    }
+
+    /// The track the room is playing, from the last transport command.
+    pub fn current_transport_path(&self) -> Option<&Path> {
+        match &self.last_transport.as_ref()?.command {
+            TransportCommand::PlayTrack { path, .. }
+            | TransportCommand::SetPlaybackState { path, .. } => Some(path.as_path()),
+            TransportCommand::StopPlayback | TransportCommand::SetPaused { .. } => None,
+        }
+    }
+
+    /// Votes from people still in the room; a vote leaves with its voter.
+    pub fn skip_vote_count(&self) -> usize {
+        self.skip_votes
+            .iter()
+            .filter(|voter| {
+                self.participants
+                    .iter()
+                    .any(|participant| participant.nickname.eq_ignore_ascii_case(voter))
+            })
+            .count()
+    }
+
+    /// A strict majority of everyone in the room, the authority included.
+    pub fn skip_vote_passed(&self) -> bool {
+        self.skip_vote_count() * 2 > self.participants.len()
+    }
+
+    pub fn skip_vote_label(&self) -> String {
+        format!(
+            "{}/{} skip votes",
+            self.skip_vote_count(),
+            self.participants.len()
+        )
+    }
+
+    /// Appends a chat line with control and bidi characters stripped and long
+    /// messages cut short. Returns `false` when nothing printable was left.
+    pub fn push_chat_message(&mut self, from: &str, message: &str) -> bool {
+        let message: String = crate::config::sanitize_display_text(message)
+            .trim()
+            .chars()
+            .take(MAX_CHAT_MESSAGE_CHARS)
+            .collect();
+        if message.is_empty() {
+            return false;
+        }
+        self.chat_log.push_back(ChatMessage {
+            from: crate::config::sanitize_display_text(from.trim()),
+            message,
+        });
+        while self.chat_log.len() > MAX_CHAT_LOG_ITEMS {
+            self.chat_log.pop_front();
+        }
+        true
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -325,6 +461,26 @@ mod tests {
         assert!(session.is_local_listener_locked());
     }
 
+    #[test]
+    fn reorder_shared_queue_rejects_orders_that_are_not_permutations() {
+        let mut session = OnlineSession::host("dj");
+        for name in ["a.flac", "b.flac", "c.flac"] {
+            session.push_shared_track(Path::new(name), name.to_string(), None);
+        }
+
+        assert!(!session.reorder_shared_queue(&[0, 1]));
+        assert!(!session.reorder_shared_queue(&[0, 0, 1]));
+        assert!(!session.reorder_shared_queue(&[0, 1, 3]));
+        assert!(session.reorder_shared_queue(&[1, 2, 0]));
+
+        let titles: Vec<&str> = session
+            .shared_queue
+            .iter()
+            .map(|item| item.title.as_str())
+            .collect();
+        assert_eq!(titles, ["b.flac", "c.flac", "a.flac"]);
+    }
+
     #[test]
     fn join_session_preserves_room_name_casing() {
         let session = OnlineSession::join("  My Room  ", "listener");
@@ -341,6 +497,7 @@ mod tests {
             ping_ms: 35,
             manual_extra_delay_ms: 40,
             auto_ping_delay: true,
+            last_drift_ms: None,
         };
         assert_eq!(participant.effective_delay_ms(), 75);
     }
@@ -355,4 +512,55 @@ mod tests {
         let item: SharedQueueItem = serde_json::from_value(value).expect("deserializes");
         assert_eq!(item.owner_nickname, None);
     }
+
+    #[test]
+    fn skip_vote_needs_a_majority_of_current_participants() {
+        let mut session = OnlineSession::host("dj");
+        for nickname in ["a", "b", "c"] {
+            session.participants.push(Participant {
+                nickname: nickname.to_string(),
+                is_local: false,
+                is_host: false,
+                ping_ms: 0,
+                manual_extra_delay_ms: 0,
+                auto_ping_delay: true,
+                last_drift_ms: None,
+            });
+        }
+
+        session.skip_votes = vec![String::from("a"), String::from("B")];
+        assert_eq!(session.skip_vote_count(), 2);
+        assert!(!session.skip_vote_passed());
+
+        session.skip_votes.push(String::from("gone"));
+        assert!(!session.skip_vote_passed());
+        session.skip_votes.push(String::from("c"));
+        assert!(session.skip_vote_passed());
+    }
+
+    #[test]
+    fn chat_messages_are_sanitized_and_the_log_is_capped() {
+        let mut session = OnlineSession::host("dj");
+        assert!(!session.push_chat_message("dj", "  \u{202E}\n "));
+        assert!(session.push_chat_message("dj\u{200B}", " hi\u{202E} there\u{7} "));
+        assert_eq!(
+            session.chat_log[0],
+            ChatMessage {
+                from: String::from("dj"),
+                message: String::from("hi there"),
+            }
+        );
+
+        session.push_chat_message("dj", &"x".repeat(MAX_CHAT_MESSAGE_CHARS + 50));
+        assert_eq!(
+            session.chat_log[1].message.chars().count(),
+            MAX_CHAT_MESSAGE_CHARS
+        );
+
+        for n in 0..MAX_CHAT_LOG_ITEMS {
+            session.push_chat_message("dj", &n.to_string());
+        }
+        assert_eq!(session.chat_log.len(), MAX_CHAT_LOG_ITEMS);
+        assert_eq!(session.chat_log[0].message, "0");
+    }
 }
@@ -1,6 +1,9 @@
 use crate::online::{
     MAX_SHARED_QUEUE_ITEMS, OnlineSession, SharedQueueItem, StreamQuality, TransportEnvelope,
 };
+use crate::online_secure::{
+    HANDSHAKE_NONCE_BYTES, RoomTransport, WireReader, WireWriter, derive_transport_keys,
+};
 use anyhow::Context;
 use base64::Engine;
 use rand::RngExt;
@@ -10,11 +13,9 @@ use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
-use std::net::{
-    IpAddr, Ipv4Addr, Shutdown as NetShutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs,
-    UdpSocket,
-};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -43,15 +44,18 @@ const BALANCED_STREAM_SAMPLE_RATE: u32 = 48_000;
 const BALANCED_STREAM_CHANNELS: u16 = 2;
 const BALANCED_STREAM_BITS_PER_SAMPLE: u16 = 16;
 const BALANCED_OPUS_FRAME_MS: u32 = 20;
-const BALANCED_OPUS_BITRATE_BPS: i32 = 160_000;
 const BALANCED_OPUS_MAX_PACKET_BYTES: usize = 4_000;
 const BALANCED_PAYLOAD_MAGIC: &[u8; 5] = b"TTOP1";
 const BALANCED_FALLBACK_READY_PCM_BYTES: u64 = 192_000;
+const STREAM_PROGRESS_MIN_BYTES: u64 = 256 * 1024;
+const STREAM_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
 const PING_INTERVAL: Duration = Duration::from_millis(1_500);
 const PING_TIMEOUT: Duration = Duration::from_millis(5_000);
 const HOME_ROOM_EMPTY_GRACE_PERIOD: Duration = Duration::from_secs(3);
 const HOME_ROOM_MAX_CONNECTIONS_MIN: u16 = 2;
 const HOME_ROOM_MAX_CONNECTIONS_MAX: u16 = 32;
+/// How long a stopping home server waits for its rooms to say goodbye.
+const HOME_SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Clone, Copy)]
 enum HostLogLevel {
@@ -107,6 +111,25 @@ pub struct HomeRoomResolved {
     pub max_connections: u16,
 }
 
+/// A running home server as reported to `tune --status`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HomeServerStatus {
+    pub uptime_seconds: u64,
+    pub room_ports: String,
+    pub rooms: Vec<HomeRoomStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HomeRoomStatus {
+    pub room_name: String,
+    pub port: u16,
+    pub current_connections: u16,
+    pub max_connections: u16,
+    pub locked: bool,
+    pub encrypted: bool,
+}
+
+#[derive(Debug)]
 pub struct HomeServerHandle {
     shutdown_tx: Sender<()>,
     join_handle: Option<thread::JoinHandle<()>>,
@@ -148,6 +171,14 @@ pub enum NetworkEvent {
         requested_path: PathBuf,
         local_temp_path: PathBuf,
         format: StreamTrackFormat,
+        /// Length announced by the sender, known before an Opus stream has
+        /// finished downloading.
+        duration: Option<Duration>,
+    },
+    StreamTrackProgress {
+        requested_path: PathBuf,
+        received_bytes: u64,
+        total_bytes: Option<u64>,
     },
     Status(String),
 }
@@ -155,7 +186,7 @@ pub enum NetworkEvent {
 #[derive(Debug, Clone, Copy)]
 pub enum StreamTrackFormat {
     LosslessOriginal,
-    BalancedOpus160kVbrStereo,
+    OpusVbrStereo { bitrate_kbps: u32 },
 }
 
 #[derive(Debug, Clone)]
@@ -179,6 +210,9 @@ pub enum LocalAction {
         to_index: usize,
         expected_path: Option<PathBuf>,
     },
+    QueueShuffle {
+        order: Vec<usize>,
+    },
     QueueConsume {
         expected_path: Option<PathBuf>,
     },
@@ -187,6 +221,13 @@ pub enum LocalAction {
         auto_ping_delay: bool,
     },
     Transport(TransportEnvelope),
+    Chat {
+        message: String,
+    },
+    VoteSkip {
+        expected_path: Option<PathBuf>,
+    },
+    ReportDrift(i32),
 }
 
 #[derive(Debug)]
@@ -233,6 +274,7 @@ impl OnlineNetwork {
             bind_addr,
             session,
             expected_password,
+            RoomTransport::Sealed,
             max_peers,
             false,
         )
@@ -242,11 +284,11 @@ impl OnlineNetwork {
         bind_addr: &str,
         mut session: OnlineSession,
         expected_password: Option<String>,
+        transport: RoomTransport,
         max_peers: usize,
         log_events: bool,
     ) -> anyhow::Result<Self> {
-        let listener = TcpListener::bind(bind_addr)
-            .with_context(|| format!("failed to bind online host at {bind_addr}"))?;
+        let listener = bind_listener(bind_addr, "online host", "try --room-port-range")?;
         let bound_addr = listener
             .local_addr()
             .context("failed to read host listener addr")?;
@@ -260,21 +302,26 @@ impl OnlineNetwork {
             log_events,
             HostLogLevel::Info,
             format_args!(
-                "room host listening room={} bind={} max_peers={} locked={}",
+                "room host listening room={} bind={} max_peers={} locked={} transport={:?}",
                 session.room_code,
                 bound_addr,
                 max_peers,
                 expected_password
                     .as_deref()
-                    .is_some_and(|value| !value.trim().is_empty())
+                    .is_some_and(|value| !value.trim().is_empty()),
+                RoomTransport::for_room(transport, expected_password.as_deref())
             ),
         );
 
+        let access = RoomAccess {
+            transport: RoomTransport::for_room(transport, expected_password.as_deref()),
+            password: expected_password,
+        };
         thread::spawn(move || {
             host_loop(
                 listener,
                 &mut session,
-                expected_password,
+                access,
                 max_peers,
                 cmd_rx,
                 event_tx,
@@ -290,17 +337,34 @@ impl OnlineNetwork {
         })
     }
 
+    /// `server_addr` is a room address as the home server advertises it; a
+    /// `tunes://` room is sealed with `password` before the hello is sent.
     pub fn start_client(
         server_addr: &str,
         room_code: &str,
         nickname: &str,
         password: Option<String>,
     ) -> anyhow::Result<Self> {
-        let mut stream = TcpStream::connect(server_addr)
-            .with_context(|| format!("failed to connect to {server_addr}"))?;
+        let (transport, connect_addr) = RoomTransport::parse_room_addr(server_addr);
+        let stream = TcpStream::connect(connect_addr)
+            .with_context(|| format!("failed to connect to {connect_addr}"))?;
         stream
             .set_nodelay(true)
             .context("failed to enable TCP_NODELAY")?;
+        let mut reader = WireReader::plain(
+            stream
+                .try_clone()
+                .context("failed to clone client stream")?,
+        );
+        let mut stream = WireWriter::plain(stream);
+
+        if transport == RoomTransport::Sealed {
+            let Some(room_password) = password.as_deref().filter(|value| !value.trim().is_empty())
+            else {
+                anyhow::bail!("this room is encrypted and needs its password");
+            };
+            seal_client_connection(&mut reader, &mut stream, room_password)?;
+        }
 
         send_json_line(
             &mut stream,
@@ -312,16 +376,17 @@ impl OnlineNetwork {
         )
         .context("failed to send hello")?;
 
-        let mut reader = BufReader::new(
-            stream
-                .try_clone()
-                .context("failed to clone client stream")?,
-        );
         let mut line = String::new();
-        let read = reader
-            .read_line(&mut line)
-            .context("failed to read hello ack")?;
+        let read = match reader.read_line(&mut line) {
+            Err(err) if stream.is_sealed() && err.kind() == std::io::ErrorKind::InvalidData => {
+                anyhow::bail!("invalid room password")
+            }
+            result => result.context("failed to read hello ack")?,
+        };
         if read == 0 {
+            if stream.is_sealed() {
+                anyhow::bail!("host closed the encrypted connection; check the room password");
+            }
             anyhow::bail!("server closed connection during handshake");
         }
 
@@ -386,6 +451,19 @@ impl OnlineNetwork {
     pub fn shutdown(&self) {
         let _ = self.cmd_tx.send(NetworkCommand::Shutdown);
     }
+
+    /// Waits until the network thread has finished after `shutdown`, or
+    /// until `deadline`. `false` when the deadline passed first.
+    fn wait_closed(&self, deadline: Instant) -> bool {
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.event_rx.recv_timeout(remaining) {
+                Ok(_) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => return true,
+                Err(mpsc::RecvTimeoutError::Timeout) => return false,
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -403,6 +481,7 @@ enum HomeRequest {
     ResolveRoom {
         room_name: String,
     },
+    Status,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -410,6 +489,7 @@ enum HomeResponse {
     Ok,
     Rooms { rooms: Vec<HomeRoomDirectoryEntry> },
     RoomResolved { room: HomeRoomResolvedWire },
+    Status { status: HomeServerStatus },
     Error { message: String },
 }
 
@@ -430,24 +510,56 @@ struct HostedRoom {
     network: OnlineNetwork,
     max_connections: u16,
     locked: bool,
+    transport: RoomTransport,
     current_connections: u16,
     empty_since: Option<Instant>,
 }
 
+fn bind_listener(bind_addr: &str, what: &str, port_hint: &str) -> anyhow::Result<TcpListener> {
+    TcpListener::bind(bind_addr).map_err(|err| {
+        let message = bind_error_message(&err, bind_addr, what, port_hint);
+        anyhow::Error::new(err).context(message)
+    })
+}
+
+/// Turns common bind failures into a message that says what to change.
+fn bind_error_message(
+    err: &std::io::Error,
+    bind_addr: &str,
+    what: &str,
+    port_hint: &str,
+) -> String {
+    let (host, port) = bind_addr.rsplit_once(':').unwrap_or((bind_addr, ""));
+    match err.kind() {
+        std::io::ErrorKind::AddrInUse => {
+            format!("port {port} is already in use; {port_hint} or a different port")
+        }
+        std::io::ErrorKind::PermissionDenied => format!(
+            "permission denied for port {port}; use a port above 1024 or allow TuneTUI through the firewall"
+        ),
+        std::io::ErrorKind::AddrNotAvailable => format!(
+            "address {host} is not on this machine; use 0.0.0.0 or one of this machine's IPs"
+        ),
+        _ => format!("failed to bind {what} at {bind_addr}: {err}"),
+    }
+}
+
+/// `room_transport` is what password rooms use; `Plain` is the LAN opt-out.
 pub fn start_home_server(
     bind_addr: &str,
     room_port_range: Option<(u16, u16)>,
+    room_transport: RoomTransport,
 ) -> anyhow::Result<HomeServerHandle> {
-    start_home_server_with_logging(bind_addr, room_port_range, false)
+    start_home_server_with_logging(bind_addr, room_port_range, room_transport, false)
 }
 
 fn start_home_server_with_logging(
     bind_addr: &str,
     room_port_range: Option<(u16, u16)>,
+    room_transport: RoomTransport,
     log_events: bool,
 ) -> anyhow::Result<HomeServerHandle> {
-    let listener = TcpListener::bind(bind_addr)
-        .with_context(|| format!("failed to bind home server at {bind_addr}"))?;
+    let listener = bind_listener(bind_addr, "home server", "try --host-ip host:port")?;
     listener
         .set_nonblocking(true)
         .context("failed to set nonblocking home listener")?;
@@ -459,12 +571,13 @@ fn start_home_server_with_logging(
         log_events,
         HostLogLevel::Info,
         format_args!(
-            "home server listening bind={bind_addr} local={bind} room_ports={}",
+            "home server listening bind={bind_addr} local={bind} room_ports={} transport={room_transport:?}",
             room_port_range_label(room_port_range)
         ),
     );
     let bind_addr_for_closure = bind_addr.to_string();
     let join_handle = thread::spawn(move || {
+        let started_at = Instant::now();
         let mut rooms: HashMap<String, HostedRoom> = HashMap::new();
         loop {
             if shutdown_rx.try_recv().is_ok() {
@@ -609,6 +722,20 @@ fn start_home_server_with_logging(
                                 },
                             }
                         }
+                        Ok(HomeRequest::Status) => {
+                            host_log(
+                                log_events,
+                                HostLogLevel::Info,
+                                format_args!("home status peer={peer_addr} rooms={}", rooms.len()),
+                            );
+                            HomeResponse::Status {
+                                status: home_server_status_snapshot(
+                                    &rooms,
+                                    started_at,
+                                    room_port_range,
+                                ),
+                            }
+                        }
                         Ok(HomeRequest::CreateRoom {
                             room_name,
                             owner_nickname,
@@ -670,15 +797,19 @@ fn start_home_server_with_logging(
                                 let mut session = OnlineSession::host(&owner_nickname);
                                 session.room_code = name.to_string();
                                 session.participants.clear();
+                                let password = password
+                                    .as_deref()
+                                    .map(str::trim)
+                                    .filter(|value| !value.is_empty())
+                                    .map(str::to_string);
+                                let transport =
+                                    RoomTransport::for_room(room_transport, password.as_deref());
                                 match start_room_host_for_home_server(
                                     bind,
                                     room_port_range,
                                     session,
-                                    password
-                                        .as_deref()
-                                        .map(str::trim)
-                                        .filter(|value| !value.is_empty())
-                                        .map(str::to_string),
+                                    password,
+                                    transport,
                                     usize::from(max_connections),
                                     log_events,
                                 ) {
@@ -696,9 +827,8 @@ fn start_home_server_with_logging(
                                                 room_server_port: room_port,
                                                 network,
                                                 max_connections,
-                                                locked: password
-                                                    .as_deref()
-                                                    .is_some_and(|value| !value.trim().is_empty()),
+                                                locked,
+                                                transport,
                                                 current_connections: 0,
                                                 empty_since: None,
                                             },
@@ -707,7 +837,7 @@ fn start_home_server_with_logging(
                                             log_events,
                                             HostLogLevel::Info,
                                             format_args!(
-                                                "room created room={name} port={room_port} max={max_connections} locked={locked}"
+                                                "room created room={name} port={room_port} max={max_connections} locked={locked} transport={transport:?}"
                                             ),
                                         );
                                         match room_by_name(&rooms, name) {
@@ -766,14 +896,27 @@ fn start_home_server_with_logging(
                 }
             }
         }
-        for (_, room) in rooms {
+        drop(listener);
+        for room in rooms.values() {
+            room.network.shutdown();
+        }
+        let deadline = Instant::now() + HOME_SHUTDOWN_DRAIN_TIMEOUT;
+        for room in rooms.values() {
+            let drained = room.network.wait_closed(deadline);
             host_log(
                 log_events,
-                HostLogLevel::Info,
-                format_args!("room closed room={} reason=server_shutdown", room.room_name),
+                if drained {
+                    HostLogLevel::Info
+                } else {
+                    HostLogLevel::Warn
+                },
+                format_args!(
+                    "room closed room={} reason=server_shutdown drained={drained}",
+                    room.room_name
+                ),
             );
-            room.network.shutdown();
         }
+        host_log(log_events, HostLogLevel::Info, "home server stopped");
     });
 
     Ok(HomeServerHandle {
@@ -783,16 +926,83 @@ fn start_home_server_with_logging(
 }
 
 pub fn run_home_server_forever(bind_addr: &str) -> anyhow::Result<()> {
-    run_home_server_forever_with_ports(bind_addr, None)
+    run_home_server_forever_with_ports(bind_addr, None, RoomTransport::Sealed)
 }
 
+/// Runs until SIGINT or SIGTERM (Ctrl+C or console close on Windows), then
+/// tells every room's listeners the session ended and closes the ports.
 pub fn run_home_server_forever_with_ports(
     bind_addr: &str,
     room_port_range: Option<(u16, u16)>,
+    room_transport: RoomTransport,
 ) -> anyhow::Result<()> {
-    let _handle = start_home_server_with_logging(bind_addr, room_port_range, true)?;
-    loop {
-        thread::sleep(Duration::from_millis(1000));
+    let handle = start_home_server_with_logging(bind_addr, room_port_range, room_transport, true)?;
+    install_shutdown_signal_handlers();
+    while !SHUTDOWN_SIGNALLED.load(Ordering::SeqCst) {
+        thread::sleep(Duration::from_millis(200));
+    }
+    host_log(
+        true,
+        HostLogLevel::Info,
+        "home server stopping reason=signal",
+    );
+    handle.shutdown();
+    Ok(())
+}
+
+static SHUTDOWN_SIGNALLED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn on_shutdown_signal(_signal: libc::c_int) {
+    SHUTDOWN_SIGNALLED.store(true, Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+fn install_shutdown_signal_handlers() {
+    let handler = on_shutdown_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn on_console_ctrl(_ctrl_type: u32) -> windows_sys::core::BOOL {
+    SHUTDOWN_SIGNALLED.store(true, Ordering::SeqCst);
+    1
+}
+
+#[cfg(windows)]
+fn install_shutdown_signal_handlers() {
+    unsafe {
+        windows_sys::Win32::System::Console::SetConsoleCtrlHandler(Some(on_console_ctrl), 1);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn install_shutdown_signal_handlers() {}
+
+fn home_server_status_snapshot(
+    rooms: &HashMap<String, HostedRoom>,
+    started_at: Instant,
+    room_port_range: Option<(u16, u16)>,
+) -> HomeServerStatus {
+    let mut rooms: Vec<HomeRoomStatus> = rooms
+        .values()
+        .map(|room| HomeRoomStatus {
+            room_name: room.room_name.clone(),
+            port: room.room_server_port,
+            current_connections: room.current_connections,
+            max_connections: room.max_connections,
+            locked: room.locked,
+            encrypted: room.transport == RoomTransport::Sealed,
+        })
+        .collect();
+    rooms.sort_by(|a, b| a.room_name.cmp(&b.room_name));
+    HomeServerStatus {
+        uptime_seconds: started_at.elapsed().as_secs(),
+        room_ports: room_port_range_label(room_port_range),
+        rooms,
     }
 }
 
@@ -841,6 +1051,14 @@ pub fn create_home_room(
     )?)
 }
 
+pub fn home_server_status(server_addr: &str) -> anyhow::Result<HomeServerStatus> {
+    match send_home_request(server_addr, &HomeRequest::Status)? {
+        HomeResponse::Status { status } => Ok(status),
+        HomeResponse::Error { message } => anyhow::bail!(message),
+        _ => anyhow::bail!("unexpected response from home server"),
+    }
+}
+
 pub fn resolve_home_room(server_addr: &str, room_name: &str) -> anyhow::Result<HomeRoomResolved> {
     resolve_from_response(send_home_request(
         server_addr,
@@ -925,6 +1143,7 @@ fn start_room_host_for_home_server(
     room_port_range: Option<(u16, u16)>,
     session: OnlineSession,
     password: Option<String>,
+    transport: RoomTransport,
     max_connections: usize,
     log_events: bool,
 ) -> anyhow::Result<OnlineNetwork> {
@@ -936,6 +1155,7 @@ fn start_room_host_for_home_server(
                 &room_bind,
                 session.clone(),
                 password.clone(),
+                transport,
                 max_connections,
                 log_events,
             ) {
@@ -965,6 +1185,7 @@ fn start_room_host_for_home_server(
         &room_bind,
         session,
         password,
+        transport,
         max_connections,
         log_events,
     )
@@ -995,38 +1216,38 @@ fn home_room_resolved_wire(
         _ => false,
     };
 
-    let final_addr = if use_local_addr {
-        SocketAddr::new(local_ip, room.room_server_port).to_string()
+    let room_server_addr = if use_local_addr {
+        room.transport
+            .room_addr(&SocketAddr::new(local_ip, room.room_server_port).to_string())
     } else {
-        let advertise_ip = resolve_advertise_addr(bind_addr)
-            .ok()
-            .and_then(|addr| addr.rsplit_once(':').map(|(ip, _)| ip.to_string()))
+        bind_addr
+            .rsplit_once(':')
+            .and_then(|(ip, _)| {
+                resolve_advertise_addr(&format!("{ip}:{}", room.room_server_port), room.transport)
+                    .ok()
+            })
             .unwrap_or_else(|| {
-                bind_addr
-                    .rsplit_once(':')
-                    .map(|(ip, _)| ip.to_string())
-                    .unwrap_or_else(|| String::from("127.0.0.1"))
-            });
-        format!("{advertise_ip}:{}", room.room_server_port)
+                let fallback = SocketAddr::new(
+                    IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+                    room.room_server_port,
+                );
+                room.transport.room_addr(&fallback.to_string())
+            })
     };
 
-    let (ip_str, port_str) = final_addr.rsplit_once(':').unwrap_or(("127.0.0.1", "0"));
-    let ip: IpAddr = ip_str
-        .parse()
-        .unwrap_or(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
-    let port: u16 = port_str.parse().unwrap_or(0);
-
     HomeRoomResolvedWire {
         room_name: room.room_name.clone(),
         room_code: room.room_code.clone(),
-        room_server_addr: SocketAddr::new(ip, port).to_string(),
+        room_server_addr,
         locked: room.locked,
         current_connections: room.current_connections,
         max_connections: room.max_connections,
     }
 }
 
-pub fn resolve_advertise_addr(bind_addr: &str) -> anyhow::Result<String> {
+/// The address peers should dial for a listener on `bind_addr`, with the
+/// `tunes://` scheme when the room is sealed.
+pub fn resolve_advertise_addr(bind_addr: &str, transport: RoomTransport) -> anyhow::Result<String> {
     let bind = parse_socket_addr_v4(bind_addr)?;
     let port = bind.port();
     let bind_ip = *bind.ip();
@@ -1037,7 +1258,7 @@ pub fn resolve_advertise_addr(bind_addr: &str) -> anyhow::Result<String> {
     } else {
         bind_ip
     };
-    Ok(format!("{ip}:{port}"))
+    Ok(transport.room_addr(&format!("{ip}:{port}")))
 }
 
 pub fn build_invite_code(server_addr: &str, password: &str) -> anyhow::Result<String> {
@@ -1354,8 +1575,8 @@ fn base32_decode_no_padding(value: &str) -> anyhow::Result<Vec<u8>> {
 }
 
 fn client_loop(
-    stream: TcpStream,
-    handshake_reader: BufReader<TcpStream>,
+    stream: WireWriter,
+    handshake_reader: WireReader,
     local_nickname: String,
     initial_session: Option<OnlineSession>,
     cmd_rx: Receiver<NetworkCommand>,
@@ -1485,8 +1706,14 @@ fn client_loop(
                             path,
                             total_bytes,
                             payload_format,
+                            duration_ms,
                         }) => {
-                            match InboundStreamDownload::new(&path, total_bytes, payload_format) {
+                            match InboundStreamDownload::new(
+                                &path,
+                                total_bytes,
+                                payload_format,
+                                duration_ms,
+                            ) {
                                 Ok(state) => {
                                     inbound_streams.insert(request_id, state);
                                 }
@@ -1519,7 +1746,9 @@ fn client_loop(
                                             }
                                             false
                                         }
-                                        StreamPayloadFormat::BalancedOpus160kVbr => {
+                                        StreamPayloadFormat::BalancedOpus160kVbr
+                                        | StreamPayloadFormat::HighOpus320kVbr
+                                        | StreamPayloadFormat::LowOpus96kVbr => {
                                             match ingest_balanced_stream_bytes(state, &bytes) {
                                                 Ok(ready) => ready,
                                                 Err(err) => {
@@ -1535,13 +1764,14 @@ fn client_loop(
                                     };
                                     state.received_bytes =
                                         state.received_bytes.saturating_add(bytes.len() as u64);
+                                    if !ready_now
+                                        && !state.ready_emitted
+                                        && state.progress_update_due(Instant::now())
+                                    {
+                                        let _ = read_event_tx.send(stream_progress_event(state));
+                                    }
                                     if ready_now {
-                                        let _ =
-                                            read_event_tx.send(NetworkEvent::StreamTrackReady {
-                                                requested_path: state.requested_path.clone(),
-                                                local_temp_path: state.local_temp_path.clone(),
-                                                format: stream_track_format(state.payload_format),
-                                            });
+                                        let _ = read_event_tx.send(stream_ready_event(state));
                                     }
                                 }
                                 Err(err) => {
@@ -1595,17 +1825,16 @@ fn client_loop(
                                 continue;
                             }
                             if !state.ready_emitted {
-                                let _ = read_event_tx.send(NetworkEvent::StreamTrackReady {
-                                    requested_path: state.requested_path,
-                                    local_temp_path: state.local_temp_path,
-                                    format: stream_track_format(state.payload_format),
-                                });
+                                let _ = read_event_tx.send(stream_ready_event(&state));
                             }
                         }
                         Ok(WireServerMessage::Status(message)) => {
                             let _ = read_event_tx.send(NetworkEvent::Status(message));
                         }
-                        Ok(WireServerMessage::HelloAck { .. }) => {}
+                        Ok(
+                            WireServerMessage::HelloAck { .. }
+                            | WireServerMessage::SecureHelloAck { .. },
+                        ) => {}
                         Err(err) => {
                             let _ = read_event_tx.send(NetworkEvent::Status(format!(
                                 "Online message parse error: {err}"
@@ -1627,7 +1856,7 @@ fn client_loop(
         match cmd_rx.recv() {
             Ok(NetworkCommand::Shutdown) => {
                 if let Ok(stream) = writer.lock() {
-                    let _ = stream.shutdown(NetShutdown::Both);
+                    let _ = stream.shutdown();
                 }
                 break;
             }
@@ -1659,14 +1888,102 @@ fn client_loop(
     }
 
     if let Ok(stream) = writer.lock() {
-        let _ = stream.shutdown(NetShutdown::Both);
+        let _ = stream.shutdown();
+    }
+}
+
+/// Swaps nonces with the host and seals both halves with keys derived from
+/// the room password. The host's refusal comes back as a plain hello ack.
+fn seal_client_connection(
+    reader: &mut WireReader,
+    writer: &mut WireWriter,
+    password: &str,
+) -> anyhow::Result<()> {
+    let mut client_nonce = [0_u8; HANDSHAKE_NONCE_BYTES];
+    rand::rng().fill(&mut client_nonce);
+    send_json_line(
+        writer,
+        &WireClientMessage::SecureHello {
+            client_nonce: base64::engine::general_purpose::STANDARD.encode(client_nonce),
+        },
+    )
+    .context("failed to send secure hello")?;
+
+    let mut line = String::new();
+    if reader
+        .read_line(&mut line)
+        .context("failed to read secure hello ack")?
+        == 0
+    {
+        anyhow::bail!("server closed connection during handshake");
     }
+    let host_nonce = match serde_json::from_str::<WireServerMessage>(line.trim_end())
+        .context("failed to parse secure hello ack")?
+    {
+        WireServerMessage::SecureHelloAck { host_nonce } => {
+            decode_handshake_nonce(&host_nonce).context("host sent an invalid handshake nonce")?
+        }
+        WireServerMessage::HelloAck {
+            accepted: false,
+            reason,
+            session: _,
+        } => anyhow::bail!(reason.unwrap_or_else(|| String::from("server rejected connection"))),
+        _ => anyhow::bail!("invalid handshake response from server"),
+    };
+
+    let keys = derive_transport_keys(password, &client_nonce, &host_nonce);
+    writer.seal(&keys.client_to_host);
+    reader.seal(&keys.host_to_client);
+    Ok(())
+}
+
+/// The host side of `seal_client_connection`. Rooms without a password have
+/// nothing to derive a key from, so the client is turned away.
+fn seal_host_connection(
+    reader: &mut WireReader,
+    writer: &mut WireWriter,
+    password: Option<&str>,
+    client_nonce: &str,
+) -> anyhow::Result<()> {
+    let Some(password) = password.filter(|value| !value.trim().is_empty()) else {
+        let _ = send_json_line(
+            writer,
+            &WireServerMessage::HelloAck {
+                accepted: false,
+                reason: Some(String::from("this room has no password to encrypt with")),
+                session: None,
+            },
+        );
+        anyhow::bail!("encrypted hello for a room without a password");
+    };
+    let client_nonce =
+        decode_handshake_nonce(client_nonce).context("client sent an invalid handshake nonce")?;
+    let mut host_nonce = [0_u8; HANDSHAKE_NONCE_BYTES];
+    rand::rng().fill(&mut host_nonce);
+    send_json_line(
+        writer,
+        &WireServerMessage::SecureHelloAck {
+            host_nonce: base64::engine::general_purpose::STANDARD.encode(host_nonce),
+        },
+    )?;
+
+    let keys = derive_transport_keys(password, &client_nonce, &host_nonce);
+    writer.seal(&keys.host_to_client);
+    reader.seal(&keys.client_to_host);
+    Ok(())
+}
+
+fn decode_handshake_nonce(value: &str) -> anyhow::Result<[u8; HANDSHAKE_NONCE_BYTES]> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(value)?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("handshake nonce has the wrong length"))
 }
 
 fn host_loop(
     listener: TcpListener,
     session: &mut OnlineSession,
-    expected_password: Option<String>,
+    access: RoomAccess,
     max_peers: usize,
     cmd_rx: Receiver<NetworkCommand>,
     event_tx: Sender<NetworkEvent>,
@@ -1709,7 +2026,10 @@ fn host_loop(
                         ),
                     );
                     let inbound_tx_clone = inbound_tx.clone();
-                    thread::spawn(move || host_peer_reader(peer_id, stream, inbound_tx_clone));
+                    let peer_access = access.clone();
+                    thread::spawn(move || {
+                        host_peer_reader(peer_id, stream, &peer_access, inbound_tx_clone)
+                    });
                 }
                 Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => break,
                 Err(err) => {
@@ -1730,7 +2050,7 @@ fn host_loop(
                 Ok(inbound) => handle_inbound(
                     inbound,
                     session,
-                    expected_password.as_deref(),
+                    access.password.as_deref(),
                     max_peers,
                     InboundState {
                         peers: &mut peers,
@@ -2049,6 +2369,7 @@ fn handle_inbound(
                     ping_ms: 35,
                     manual_extra_delay_ms: 0,
                     auto_ping_delay: true,
+                    last_drift_ms: None,
                 });
             }
 
@@ -2230,6 +2551,7 @@ fn handle_inbound(
             path,
             total_bytes,
             payload_format,
+            duration_ms,
         } => {
             if let Some(relay) = pending_relay_requests.get(&(peer_id, request_id)) {
                 let Some(requester_peer) = peers.get(&relay.requester_peer_id) else {
@@ -2257,6 +2579,7 @@ fn handle_inbound(
                         path,
                         total_bytes,
                         payload_format,
+                        duration_ms,
                     },
                 )
                 .is_err()
@@ -2275,7 +2598,7 @@ fn handle_inbound(
                 inbound_streams.remove(&key);
                 return;
             }
-            match InboundStreamDownload::new(&path, total_bytes, payload_format) {
+            match InboundStreamDownload::new(&path, total_bytes, payload_format, duration_ms) {
                 Ok(state) => {
                     inbound_streams.insert(key, state);
                 }
@@ -2328,7 +2651,9 @@ fn handle_inbound(
                             }
                             false
                         }
-                        StreamPayloadFormat::BalancedOpus160kVbr => {
+                        StreamPayloadFormat::BalancedOpus160kVbr
+                        | StreamPayloadFormat::HighOpus320kVbr
+                        | StreamPayloadFormat::LowOpus96kVbr => {
                             match ingest_balanced_stream_bytes(state, &bytes) {
                                 Ok(ready) => ready,
                                 Err(err) => {
@@ -2343,12 +2668,14 @@ fn handle_inbound(
                         }
                     };
                     state.received_bytes = state.received_bytes.saturating_add(bytes.len() as u64);
+                    if !ready_now
+                        && !state.ready_emitted
+                        && state.progress_update_due(Instant::now())
+                    {
+                        let _ = event_tx.send(stream_progress_event(state));
+                    }
                     if ready_now {
-                        let _ = event_tx.send(NetworkEvent::StreamTrackReady {
-                            requested_path: state.requested_path.clone(),
-                            local_temp_path: state.local_temp_path.clone(),
-                            format: stream_track_format(state.payload_format),
-                        });
+                        let _ = event_tx.send(stream_ready_event(state));
                     }
                 }
                 Err(err) => {
@@ -2426,11 +2753,7 @@ fn handle_inbound(
                 return;
             }
             if !state.ready_emitted {
-                let _ = event_tx.send(NetworkEvent::StreamTrackReady {
-                    requested_path: state.requested_path,
-                    local_temp_path: state.local_temp_path,
-                    format: stream_track_format(state.payload_format),
-                });
+                let _ = event_tx.send(stream_ready_event(&state));
             }
         }
         Inbound::Disconnected { peer_id } => {
@@ -2711,6 +3034,14 @@ fn log_local_action(enabled: bool, room_code: &str, origin: &str, action: &Local
                 "room action room={room_code} origin={origin} type=queue_move from={from_index} to={to_index}"
             ),
         ),
+        LocalAction::QueueShuffle { order } => host_log(
+            true,
+            HostLogLevel::Info,
+            format_args!(
+                "room action room={room_code} origin={origin} type=queue_shuffle items={}",
+                order.len()
+            ),
+        ),
         LocalAction::QueueConsume { .. } => host_log(
             true,
             HostLogLevel::Info,
@@ -2734,6 +3065,26 @@ fn log_local_action(enabled: bool, room_code: &str, origin: &str, action: &Local
                 transport_command_label(&envelope.command)
             ),
         ),
+        LocalAction::Chat { message } => host_log(
+            true,
+            HostLogLevel::Info,
+            format_args!(
+                "room action room={room_code} origin={origin} type=chat chars={}",
+                message.chars().count()
+            ),
+        ),
+        LocalAction::VoteSkip { .. } => host_log(
+            true,
+            HostLogLevel::Info,
+            format_args!("room action room={room_code} origin={origin} type=vote_skip"),
+        ),
+        LocalAction::ReportDrift(drift_ms) => host_log(
+            true,
+            HostLogLevel::Info,
+            format_args!(
+                "room action room={room_code} origin={origin} type=report_drift drift_ms={drift_ms}"
+            ),
+        ),
     }
 }
 
@@ -2799,6 +3150,11 @@ fn apply_action_to_session(
                 {
                     last_transport.origin_nickname = participant.nickname.clone();
                 }
+                for voter in &mut session.skip_votes {
+                    if voter.eq_ignore_ascii_case(&previous) {
+                        *voter = participant.nickname.clone();
+                    }
+                }
             }
         }
         LocalAction::QueueAdd(item) => {
@@ -2855,6 +3211,9 @@ fn apply_action_to_session(
             let insert_at = to_index.min(session.shared_queue.len());
             session.shared_queue.insert(insert_at, item);
         }
+        LocalAction::QueueShuffle { order } => {
+            session.reorder_shared_queue(&order);
+        }
         LocalAction::QueueConsume { expected_path } => {
             let can_consume = match (session.shared_queue.front(), expected_path.as_ref()) {
                 (Some(_), None) => true,
@@ -2899,8 +3258,47 @@ fn apply_action_to_session(
                 .unwrap_or(1);
             envelope.seq = next_seq;
             envelope.origin_nickname = origin_nickname.to_string();
+            let track_changed = matches!(
+                envelope.command,
+                crate::online::TransportCommand::PlayTrack { .. }
+            ) || transport_path(&envelope.command)
+                != session.current_transport_path();
+            if track_changed {
+                session.skip_votes.clear();
+            }
             session.last_transport = Some(envelope);
         }
+        LocalAction::Chat { message } => {
+            session.push_chat_message(origin_nickname, &message);
+        }
+        LocalAction::VoteSkip { expected_path } => {
+            let Some(current) = session.current_transport_path() else {
+                return;
+            };
+            let stale = expected_path
+                .as_deref()
+                .is_some_and(|expected| expected != current);
+            let is_participant = session
+                .participants
+                .iter()
+                .any(|participant| participant.nickname.eq_ignore_ascii_case(origin_nickname));
+            let already_voted = session
+                .skip_votes
+                .iter()
+                .any(|voter| voter.eq_ignore_ascii_case(origin_nickname));
+            if !stale && is_participant && !already_voted {
+                session.skip_votes.push(origin_nickname.to_string());
+            }
+        }
+        LocalAction::ReportDrift(drift_ms) => {
+            if let Some(participant) = session
+                .participants
+                .iter_mut()
+                .find(|participant| participant.nickname.eq_ignore_ascii_case(origin_nickname))
+            {
+                participant.last_drift_ms = Some(drift_ms);
+            }
+        }
     }
 }
 
@@ -2909,15 +3307,29 @@ fn action_allowed_for_origin(
     action: &LocalAction,
     origin_nickname: &str,
 ) -> bool {
+    if matches!(action, LocalAction::QueueShuffle { .. }) {
+        return origin_is_host(session, origin_nickname);
+    }
     if session.mode != crate::online::OnlineRoomMode::HostOnly {
         return true;
     }
     if origin_is_host(session, origin_nickname) {
         return true;
     }
+    if let LocalAction::QueueAdd(item) = action {
+        return item
+            .owner_nickname
+            .as_deref()
+            .is_some_and(|owner| owner.eq_ignore_ascii_case(origin_nickname))
+            && session.request_slots_left(origin_nickname) > 0;
+    }
     matches!(
         action,
-        LocalAction::DelayUpdate { .. } | LocalAction::SetNickname { .. }
+        LocalAction::DelayUpdate { .. }
+            | LocalAction::SetNickname { .. }
+            | LocalAction::Chat { .. }
+            | LocalAction::VoteSkip { .. }
+            | LocalAction::ReportDrift(_)
     )
 }
 
@@ -3037,7 +3449,11 @@ fn apply_action_to_client_session(
                 guard.allowed_paths.remove(&path);
             }
         }
-        LocalAction::QueueMove { .. } => {}
+        LocalAction::QueueMove { .. }
+        | LocalAction::QueueShuffle { .. }
+        | LocalAction::Chat { .. }
+        | LocalAction::VoteSkip { .. }
+        | LocalAction::ReportDrift(_) => {}
         LocalAction::Transport(_) => {
             if let Some(path) = previous_local_transport_path
                 && !path_allowed_for_client(session, &guard.local_nickname, &path)
@@ -3096,8 +3512,13 @@ fn broadcast(peers: &mut HashMap<u32, PeerConnection>, message: &WireServerMessa
     }
 }
 
-fn host_peer_reader(peer_id: u32, stream: TcpStream, inbound_tx: Sender<Inbound>) {
-    let mut reader = BufReader::new(match stream.try_clone() {
+fn host_peer_reader(
+    peer_id: u32,
+    stream: TcpStream,
+    access: &RoomAccess,
+    inbound_tx: Sender<Inbound>,
+) {
+    let mut reader = WireReader::plain(match stream.try_clone() {
         Ok(clone) => clone,
         Err(err) => {
             let _ = inbound_tx.send(Inbound::ReadError {
@@ -3107,21 +3528,30 @@ fn host_peer_reader(peer_id: u32, stream: TcpStream, inbound_tx: Sender<Inbound>
             return;
         }
     });
+    let mut stream = WireWriter::plain(stream);
 
-    let mut first_line = String::new();
-    match reader.read_line(&mut first_line) {
-        Ok(0) => {
-            let _ = inbound_tx.send(Inbound::Disconnected { peer_id });
-            return;
-        }
-        Ok(_) => {}
-        Err(err) => {
+    let Some(mut first_line) = read_handshake_line(&mut reader, peer_id, &inbound_tx) else {
+        return;
+    };
+    if let Ok(WireClientMessage::SecureHello { client_nonce }) =
+        serde_json::from_str::<WireClientMessage>(first_line.trim_end())
+    {
+        if let Err(err) = seal_host_connection(
+            &mut reader,
+            &mut stream,
+            access.password.as_deref(),
+            &client_nonce,
+        ) {
             let _ = inbound_tx.send(Inbound::ReadError {
                 peer_id,
-                error: err.to_string(),
+                error: format!("{err:#}"),
             });
             return;
         }
+        let Some(sealed_line) = read_handshake_line(&mut reader, peer_id, &inbound_tx) else {
+            return;
+        };
+        first_line = sealed_line;
     }
 
     let hello = serde_json::from_str::<WireClientMessage>(first_line.trim_end());
@@ -3136,6 +3566,23 @@ fn host_peer_reader(peer_id: u32, stream: TcpStream, inbound_tx: Sender<Inbound>
             return;
         }
     };
+    if access.transport == RoomTransport::Sealed && !stream.is_sealed() {
+        let _ = send_json_line(
+            &mut stream,
+            &WireServerMessage::HelloAck {
+                accepted: false,
+                reason: Some(String::from(
+                    "this room is encrypted; update TuneTUI to join",
+                )),
+                session: None,
+            },
+        );
+        let _ = inbound_tx.send(Inbound::ReadError {
+            peer_id,
+            error: String::from("unencrypted hello for an encrypted room"),
+        });
+        return;
+    }
 
     let _ = inbound_tx.send(Inbound::Hello {
         peer_id,
@@ -3179,6 +3626,7 @@ fn host_peer_reader(peer_id: u32, stream: TcpStream, inbound_tx: Sender<Inbound>
                         path,
                         total_bytes,
                         payload_format,
+                        duration_ms,
                     }) => {
                         let _ = inbound_tx.send(Inbound::StreamStart {
                             peer_id,
@@ -3186,6 +3634,7 @@ fn host_peer_reader(peer_id: u32, stream: TcpStream, inbound_tx: Sender<Inbound>
                             path,
                             total_bytes,
                             payload_format,
+                            duration_ms,
                         });
                     }
                     Ok(WireClientMessage::StreamChunk {
@@ -3212,7 +3661,8 @@ fn host_peer_reader(peer_id: u32, stream: TcpStream, inbound_tx: Sender<Inbound>
                             error,
                         });
                     }
-                    Ok(WireClientMessage::Hello { .. }) => {}
+                    Ok(WireClientMessage::Hello { .. } | WireClientMessage::SecureHello { .. }) => {
+                    }
                     Err(err) => {
                         let _ = inbound_tx.send(Inbound::ReadError {
                             peer_id,
@@ -3232,7 +3682,31 @@ fn host_peer_reader(peer_id: u32, stream: TcpStream, inbound_tx: Sender<Inbound>
     }
 }
 
-fn send_json_line<T: Serialize>(stream: &mut TcpStream, value: &T) -> anyhow::Result<()> {
+/// Reads one line before the peer has joined. `None` once the host has been
+/// told the connection is gone.
+fn read_handshake_line(
+    reader: &mut WireReader,
+    peer_id: u32,
+    inbound_tx: &Sender<Inbound>,
+) -> Option<String> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) => {
+            let _ = inbound_tx.send(Inbound::Disconnected { peer_id });
+            None
+        }
+        Ok(_) => Some(line),
+        Err(err) => {
+            let _ = inbound_tx.send(Inbound::ReadError {
+                peer_id,
+                error: err.to_string(),
+            });
+            None
+        }
+    }
+}
+
+fn send_json_line<W: Write, T: Serialize>(stream: &mut W, value: &T) -> anyhow::Result<()> {
     let mut bytes = serde_json::to_vec(value).context("serialize failed")?;
     bytes.push(b'\n');
     stream.write_all(&bytes).context("write failed")?;
@@ -3241,69 +3715,42 @@ fn send_json_line<T: Serialize>(stream: &mut TcpStream, value: &T) -> anyhow::Re
 }
 
 fn send_json_line_shared<T: Serialize>(
-    stream: &Arc<Mutex<TcpStream>>,
+    stream: &Arc<Mutex<WireWriter>>,
     value: &T,
 ) -> anyhow::Result<()> {
     let mut locked = stream
         .lock()
         .map_err(|_| anyhow::anyhow!("peer socket lock poisoned"))?;
-    send_json_line(&mut locked, value)
+    send_json_line(&mut *locked, value)
 }
 
 fn stream_file_to_client(
-    writer: &Arc<Mutex<TcpStream>>,
+    writer: &Arc<Mutex<WireWriter>>,
     path: &Path,
     request_id: u64,
     quality: StreamQuality,
 ) -> anyhow::Result<()> {
-    validate_stream_source(path)?;
-    match quality {
-        StreamQuality::Lossless => {
-            let file_size = fs::metadata(path)
-                .with_context(|| format!("failed to read stream metadata for {}", path.display()))?
-                .len();
-            send_json_line_shared(
-                writer,
-                &WireServerMessage::StreamStart {
-                    request_id,
-                    path: path.to_path_buf(),
-                    total_bytes: file_size,
-                    payload_format: StreamPayloadFormat::OriginalFile,
-                },
-            )?;
-            stream_lossless_chunks(path, |chunk| {
-                let encoded = base64::engine::general_purpose::STANDARD.encode(chunk);
-                send_json_line_shared(
-                    writer,
-                    &WireServerMessage::StreamChunk {
-                        request_id,
-                        data_base64: encoded,
-                    },
-                )
-            })?;
-        }
-        StreamQuality::Balanced => {
-            send_json_line_shared(
-                writer,
-                &WireServerMessage::StreamStart {
-                    request_id,
-                    path: path.to_path_buf(),
-                    total_bytes: 0,
-                    payload_format: StreamPayloadFormat::BalancedOpus160kVbr,
-                },
-            )?;
-            stream_balanced_opus_chunks(path, |chunk| {
-                let encoded = base64::engine::general_purpose::STANDARD.encode(chunk);
-                send_json_line_shared(
-                    writer,
-                    &WireServerMessage::StreamChunk {
-                        request_id,
-                        data_base64: encoded,
-                    },
-                )
-            })?;
-        }
-    }
+    let outbound = OutboundStream::prepare(path, quality)?;
+    send_json_line_shared(
+        writer,
+        &WireServerMessage::StreamStart {
+            request_id,
+            path: path.to_path_buf(),
+            total_bytes: outbound.total_bytes,
+            payload_format: outbound.payload_format,
+            duration_ms: outbound.duration_ms,
+        },
+    )?;
+    outbound.send_chunks(path, |chunk| {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(chunk);
+        send_json_line_shared(
+            writer,
+            &WireServerMessage::StreamChunk {
+                request_id,
+                data_base64: encoded,
+            },
+        )
+    })?;
 
     send_json_line_shared(
         writer,
@@ -3317,59 +3764,32 @@ fn stream_file_to_client(
 }
 
 fn stream_file_to_host(
-    writer: &Arc<Mutex<TcpStream>>,
+    writer: &Arc<Mutex<WireWriter>>,
     path: &Path,
     request_id: u64,
     quality: StreamQuality,
 ) -> anyhow::Result<()> {
-    validate_stream_source(path)?;
-    match quality {
-        StreamQuality::Lossless => {
-            let file_size = fs::metadata(path)
-                .with_context(|| format!("failed to read stream metadata for {}", path.display()))?
-                .len();
-            send_json_line_shared(
-                writer,
-                &WireClientMessage::StreamStart {
-                    request_id,
-                    path: path.to_path_buf(),
-                    total_bytes: file_size,
-                    payload_format: StreamPayloadFormat::OriginalFile,
-                },
-            )?;
-            stream_lossless_chunks(path, |chunk| {
-                let encoded = base64::engine::general_purpose::STANDARD.encode(chunk);
-                send_json_line_shared(
-                    writer,
-                    &WireClientMessage::StreamChunk {
-                        request_id,
-                        data_base64: encoded,
-                    },
-                )
-            })?;
-        }
-        StreamQuality::Balanced => {
-            send_json_line_shared(
-                writer,
-                &WireClientMessage::StreamStart {
-                    request_id,
-                    path: path.to_path_buf(),
-                    total_bytes: 0,
-                    payload_format: StreamPayloadFormat::BalancedOpus160kVbr,
-                },
-            )?;
-            stream_balanced_opus_chunks(path, |chunk| {
-                let encoded = base64::engine::general_purpose::STANDARD.encode(chunk);
-                send_json_line_shared(
-                    writer,
-                    &WireClientMessage::StreamChunk {
-                        request_id,
-                        data_base64: encoded,
-                    },
-                )
-            })?;
-        }
-    }
+    let outbound = OutboundStream::prepare(path, quality)?;
+    send_json_line_shared(
+        writer,
+        &WireClientMessage::StreamStart {
+            request_id,
+            path: path.to_path_buf(),
+            total_bytes: outbound.total_bytes,
+            payload_format: outbound.payload_format,
+            duration_ms: outbound.duration_ms,
+        },
+    )?;
+    outbound.send_chunks(path, |chunk| {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(chunk);
+        send_json_line_shared(
+            writer,
+            &WireClientMessage::StreamChunk {
+                request_id,
+                data_base64: encoded,
+            },
+        )
+    })?;
 
     send_json_line_shared(
         writer,
@@ -3382,6 +3802,131 @@ fn stream_file_to_host(
     )
 }
 
+/// What a stream request sends, worked out before `StreamStart` so the
+/// receiver learns the size and length up front.
+struct OutboundStream {
+    payload_format: StreamPayloadFormat,
+    /// 0 while an Opus payload still has to be encoded.
+    total_bytes: u64,
+    duration_ms: Option<u64>,
+    /// Where the Opus payload for this source and bitrate is cached.
+    payload_cache: Option<PathBuf>,
+}
+
+impl OutboundStream {
+    fn prepare(path: &Path, quality: StreamQuality) -> anyhow::Result<Self> {
+        validate_stream_source(path)?;
+        let payload_format = StreamPayloadFormat::for_quality(quality);
+        let duration_ms = stream_source_duration_ms(path);
+        let Some(bitrate_kbps) = payload_format.opus_bitrate_kbps() else {
+            let total_bytes = fs::metadata(path)
+                .with_context(|| format!("failed to read stream metadata for {}", path.display()))?
+                .len();
+            return Ok(Self {
+                payload_format,
+                total_bytes,
+                duration_ms,
+                payload_cache: None,
+            });
+        };
+        let payload_cache = opus_payload_cache_path(path, bitrate_kbps)?;
+        let total_bytes = fs::metadata(&payload_cache).map_or(0, |metadata| metadata.len());
+        Ok(Self {
+            payload_format,
+            total_bytes,
+            duration_ms,
+            payload_cache: Some(payload_cache),
+        })
+    }
+
+    fn send_chunks<F>(&self, source_path: &Path, mut send_chunk: F) -> anyhow::Result<()>
+    where
+        F: FnMut(&[u8]) -> anyhow::Result<()>,
+    {
+        let (Some(cache_path), Some(bitrate_kbps)) = (
+            self.payload_cache.as_deref(),
+            self.payload_format.opus_bitrate_kbps(),
+        ) else {
+            return stream_lossless_chunks(source_path, send_chunk);
+        };
+        if self.total_bytes > 0 {
+            return stream_lossless_chunks(cache_path, send_chunk);
+        }
+
+        // Written under a name of its own and renamed once complete, so a
+        // cancelled or concurrent encode never leaves a truncated payload.
+        let partial_path = cache_path.with_extension(format!("{}.part", next_request_id()));
+        let mut partial = File::create(&partial_path)
+            .with_context(|| format!("failed to create stream cache {}", partial_path.display()))?;
+        let encoded = stream_opus_chunks(source_path, bitrate_kbps, |chunk| {
+            partial.write_all(chunk)?;
+            send_chunk(chunk)
+        })
+        .and_then(|()| Ok(partial.flush()?));
+        drop(partial);
+        match encoded {
+            Ok(()) => {
+                if fs::rename(&partial_path, cache_path).is_err() {
+                    let _ = fs::remove_file(&partial_path);
+                }
+                Ok(())
+            }
+            Err(err) => {
+                let _ = fs::remove_file(&partial_path);
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Cache file for the Opus payload of `source` at `bitrate_kbps`. The key
+/// covers the file's size and modification time, so an edited file is
+/// encoded again.
+fn opus_payload_cache_path(source: &Path, bitrate_kbps: u32) -> anyhow::Result<PathBuf> {
+    let metadata = fs::metadata(source)
+        .with_context(|| format!("failed to read stream metadata for {}", source.display()))?;
+    let modified_nanos = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let mut hasher = Sha256::new();
+    hasher.update(source.to_string_lossy().as_bytes());
+    hasher.update(metadata.len().to_le_bytes());
+    hasher.update(modified_nanos.to_le_bytes());
+    let digest = hasher.finalize();
+    let key: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    let mut dir = std::env::temp_dir();
+    dir.push("tunetui_stream_cache");
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("failed to create stream cache dir {}", dir.display()))?;
+    let stem = source
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .map(sanitize_cache_name)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| String::from("track"));
+    dir.push(format!("{stem}_{key}_{bitrate_kbps}k.ttop"));
+    Ok(dir)
+}
+
+fn stream_source_duration_ms(path: &Path) -> Option<u64> {
+    File::open(path)
+        .ok()
+        .and_then(|file| Decoder::try_from(file).ok())
+        .and_then(|decoder| decoder.total_duration())
+        .or_else(|| {
+            crate::library::duration_seconds(path)
+                .map(|seconds| Duration::from_secs(u64::from(seconds)))
+        })
+        .and_then(|duration| u64::try_from(duration.as_millis()).ok())
+        .filter(|millis| *millis > 0)
+}
+
 fn stream_lossless_chunks<F>(path: &Path, mut send_chunk: F) -> anyhow::Result<()>
 where
     F: FnMut(&[u8]) -> anyhow::Result<()>,
@@ -3399,7 +3944,11 @@ where
     Ok(())
 }
 
-fn stream_balanced_opus_chunks<F>(source_path: &Path, mut send_chunk: F) -> anyhow::Result<()>
+fn stream_opus_chunks<F>(
+    source_path: &Path,
+    bitrate_kbps: u32,
+    mut send_chunk: F,
+) -> anyhow::Result<()>
 where
     F: FnMut(&[u8]) -> anyhow::Result<()>,
 {
@@ -3431,7 +3980,7 @@ where
         BALANCED_STREAM_SAMPLE_RATE,
         i32::from(BALANCED_STREAM_CHANNELS),
     )?;
-    encoder.set_bitrate(BALANCED_OPUS_BITRATE_BPS)?;
+    encoder.set_bitrate(i32::try_from(bitrate_kbps.saturating_mul(1_000)).unwrap_or(i32::MAX))?;
     encoder.set_vbr(true)?;
 
     for sample in decoder {
@@ -3551,7 +4100,7 @@ fn transcode_balanced_stream_to_opus_payload(source_path: &Path) -> anyhow::Resu
 
     let mut output = File::create(&output_path)
         .with_context(|| format!("failed to create balanced stream {}", output_path.display()))?;
-    stream_balanced_opus_chunks(source_path, |chunk| {
+    stream_opus_chunks(source_path, 160, |chunk| {
         output.write_all(chunk).with_context(|| {
             format!(
                 "failed writing balanced stream chunk to {}",
@@ -3854,14 +4403,32 @@ fn validate_stream_source(path: &Path) -> anyhow::Result<()> {
 fn stream_size_matches(expected: u64, received: u64, payload_format: StreamPayloadFormat) -> bool {
     match payload_format {
         StreamPayloadFormat::OriginalFile => expected == received,
-        StreamPayloadFormat::BalancedOpus160kVbr => expected == 0 || expected == received,
+        StreamPayloadFormat::BalancedOpus160kVbr
+        | StreamPayloadFormat::HighOpus320kVbr
+        | StreamPayloadFormat::LowOpus96kVbr => expected == 0 || expected == received,
     }
 }
 
-fn stream_track_format(payload_format: StreamPayloadFormat) -> StreamTrackFormat {
-    match payload_format {
-        StreamPayloadFormat::OriginalFile => StreamTrackFormat::LosslessOriginal,
-        StreamPayloadFormat::BalancedOpus160kVbr => StreamTrackFormat::BalancedOpus160kVbrStereo,
+/// Opus payloads served from the sender's cache come with their size; ones
+/// encoded on the fly report 0 and show a spinner instead.
+fn stream_progress_event(state: &InboundStreamDownload) -> NetworkEvent {
+    NetworkEvent::StreamTrackProgress {
+        requested_path: state.requested_path.clone(),
+        received_bytes: state.received_bytes,
+        total_bytes: (state.total_bytes > 0).then_some(state.total_bytes),
+    }
+}
+
+fn stream_ready_event(state: &InboundStreamDownload) -> NetworkEvent {
+    let format = match state.payload_format.opus_bitrate_kbps() {
+        Some(bitrate_kbps) => StreamTrackFormat::OpusVbrStereo { bitrate_kbps },
+        None => StreamTrackFormat::LosslessOriginal,
+    };
+    NetworkEvent::StreamTrackReady {
+        requested_path: state.requested_path.clone(),
+        local_temp_path: state.local_temp_path.clone(),
+        format,
+        duration: state.duration,
     }
 }
 
@@ -3880,7 +4447,14 @@ fn smooth_ping(previous: u16, sample: u16) -> u16 {
 #[derive(Debug)]
 struct PeerConnection {
     nickname: String,
-    writer: Arc<Mutex<TcpStream>>,
+    writer: Arc<Mutex<WireWriter>>,
+}
+
+/// What a peer needs to join a hosted room, and how its connection is carried.
+#[derive(Debug, Clone)]
+struct RoomAccess {
+    password: Option<String>,
+    transport: RoomTransport,
 }
 
 #[derive(Debug)]
@@ -3911,7 +4485,7 @@ enum Inbound {
         room_code: String,
         nickname: String,
         password: Option<String>,
-        stream: TcpStream,
+        stream: WireWriter,
     },
     Action {
         peer_id: u32,
@@ -3933,6 +4507,7 @@ enum Inbound {
         path: PathBuf,
         total_bytes: u64,
         payload_format: StreamPayloadFormat,
+        duration_ms: Option<u64>,
     },
     StreamChunk {
         peer_id: u32,
@@ -3959,6 +4534,28 @@ enum Inbound {
 enum StreamPayloadFormat {
     OriginalFile,
     BalancedOpus160kVbr,
+    HighOpus320kVbr,
+    LowOpus96kVbr,
+}
+
+impl StreamPayloadFormat {
+    fn for_quality(quality: StreamQuality) -> Self {
+        match quality {
+            StreamQuality::Lossless => Self::OriginalFile,
+            StreamQuality::High => Self::HighOpus320kVbr,
+            StreamQuality::Balanced => Self::BalancedOpus160kVbr,
+            StreamQuality::Low => Self::LowOpus96kVbr,
+        }
+    }
+
+    fn opus_bitrate_kbps(self) -> Option<u32> {
+        match self {
+            Self::OriginalFile => None,
+            Self::HighOpus320kVbr => Some(320),
+            Self::BalancedOpus160kVbr => Some(160),
+            Self::LowOpus96kVbr => Some(96),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -3968,6 +4565,11 @@ enum WireClientMessage {
         nickname: String,
         password: Option<String>,
     },
+    /// Opens a sealed connection; everything after the host's ack is
+    /// encrypted, including the `Hello` with the password.
+    SecureHello {
+        client_nonce: String,
+    },
     Action(WireAction),
     Pong {
         nonce: u64,
@@ -3983,6 +4585,8 @@ enum WireClientMessage {
         path: PathBuf,
         total_bytes: u64,
         payload_format: StreamPayloadFormat,
+        #[serde(default)]
+        duration_ms: Option<u64>,
     },
     StreamChunk {
         request_id: u64,
@@ -4003,6 +4607,9 @@ enum WireServerMessage {
         reason: Option<String>,
         session: Option<OnlineSession>,
     },
+    SecureHelloAck {
+        host_nonce: String,
+    },
     Session(OnlineSession),
     ActionSync {
         action: WireAction,
@@ -4020,6 +4627,8 @@ enum WireServerMessage {
         path: PathBuf,
         total_bytes: u64,
         payload_format: StreamPayloadFormat,
+        #[serde(default)]
+        duration_ms: Option<u64>,
     },
     StreamChunk {
         request_id: u64,
@@ -4041,12 +4650,15 @@ struct InboundStreamDownload {
     received_bytes: u64,
     total_bytes: u64,
     payload_format: StreamPayloadFormat,
+    duration: Option<Duration>,
     header_parsed: bool,
     packet_buffer: Vec<u8>,
     decoder: Option<ManagedOpusDecoder>,
     pcm_buffer: Vec<i16>,
     wav_data_bytes: u64,
     ready_emitted: bool,
+    progress_reported_bytes: u64,
+    progress_reported_at: Option<Instant>,
 }
 
 #[derive(Debug)]
@@ -4060,6 +4672,7 @@ impl InboundStreamDownload {
         requested_path: &Path,
         total_bytes: u64,
         payload_format: StreamPayloadFormat,
+        duration_ms: Option<u64>,
     ) -> anyhow::Result<Self> {
         let local_temp_path = create_stream_cache_path(requested_path, payload_format)?;
         let file = File::create(&local_temp_path).with_context(|| {
@@ -4075,18 +4688,66 @@ impl InboundStreamDownload {
             received_bytes: 0,
             total_bytes,
             payload_format,
+            duration: duration_ms.map(Duration::from_millis),
             header_parsed: false,
             packet_buffer: Vec::new(),
             decoder: None,
             pcm_buffer: Vec::new(),
             wav_data_bytes: 0,
             ready_emitted: false,
+            progress_reported_bytes: 0,
+            progress_reported_at: None,
         };
         if payload_format == StreamPayloadFormat::OriginalFile {
             state.header_parsed = true;
         }
         Ok(state)
     }
+
+    /// Whether a chunk moved the download far enough to be worth a progress
+    /// event, recording it as reported when it did.
+    fn progress_update_due(&mut self, now: Instant) -> bool {
+        let elapsed = self
+            .progress_reported_at
+            .map(|reported_at| now.saturating_duration_since(reported_at));
+        if !stream_progress_due(
+            self.progress_reported_bytes,
+            self.received_bytes,
+            self.total_bytes,
+            elapsed,
+        ) {
+            return false;
+        }
+        self.progress_reported_bytes = self.received_bytes;
+        self.progress_reported_at = Some(now);
+        true
+    }
+}
+
+/// Chunks arrive every 24 KB, so progress is reported only once it moved by
+/// 1% or 256 KB, or 100 ms passed. The first and the final update always go out.
+fn stream_progress_due(
+    reported_bytes: u64,
+    received_bytes: u64,
+    total_bytes: u64,
+    since_last_report: Option<Duration>,
+) -> bool {
+    let Some(since_last_report) = since_last_report else {
+        return true;
+    };
+    if received_bytes <= reported_bytes {
+        return false;
+    }
+    if total_bytes > 0 && received_bytes >= total_bytes {
+        return true;
+    }
+    let moved = received_bytes - reported_bytes;
+    let step = if total_bytes > 0 {
+        (total_bytes / 100).min(STREAM_PROGRESS_MIN_BYTES)
+    } else {
+        STREAM_PROGRESS_MIN_BYTES
+    };
+    moved >= step || since_last_report >= STREAM_PROGRESS_INTERVAL
 }
 
 fn ingest_balanced_stream_bytes(
@@ -4183,7 +4844,9 @@ fn finalize_inbound_stream(state: &mut InboundStreamDownload) -> anyhow::Result<
             state.file.flush()?;
             Ok(())
         }
-        StreamPayloadFormat::BalancedOpus160kVbr => {
+        StreamPayloadFormat::BalancedOpus160kVbr
+        | StreamPayloadFormat::HighOpus320kVbr
+        | StreamPayloadFormat::LowOpus96kVbr => {
             let _ = ingest_balanced_stream_bytes(state, &[])?;
             if !state.header_parsed {
                 anyhow::bail!("missing balanced stream header");
@@ -4220,7 +4883,9 @@ fn create_stream_cache_path(
             .map(sanitize_cache_name)
             .filter(|value| !value.is_empty())
             .unwrap_or_else(|| String::from("bin")),
-        StreamPayloadFormat::BalancedOpus160kVbr => String::from("wav"),
+        StreamPayloadFormat::BalancedOpus160kVbr
+        | StreamPayloadFormat::HighOpus320kVbr
+        | StreamPayloadFormat::LowOpus96kVbr => String::from("wav"),
     };
     let micros = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -4265,6 +4930,9 @@ enum WireAction {
         to_index: usize,
         expected_path: Option<PathBuf>,
     },
+    QueueShuffle {
+        order: Vec<usize>,
+    },
     QueueConsume {
         expected_path: Option<PathBuf>,
     },
@@ -4273,6 +4941,13 @@ enum WireAction {
         auto_ping_delay: bool,
     },
     Transport(TransportEnvelope),
+    Chat {
+        message: String,
+    },
+    VoteSkip {
+        expected_path: Option<PathBuf>,
+    },
+    ReportDrift(i32),
 }
 
 fn action_to_wire(action: LocalAction) -> WireAction {
@@ -4298,6 +4973,7 @@ fn action_to_wire(action: LocalAction) -> WireAction {
             to_index,
             expected_path,
         },
+        LocalAction::QueueShuffle { order } => WireAction::QueueShuffle { order },
         LocalAction::QueueConsume { expected_path } => WireAction::QueueConsume { expected_path },
         LocalAction::DelayUpdate {
             manual_extra_delay_ms,
@@ -4307,6 +4983,9 @@ fn action_to_wire(action: LocalAction) -> WireAction {
             auto_ping_delay,
         },
         LocalAction::Transport(envelope) => WireAction::Transport(envelope),
+        LocalAction::Chat { message } => WireAction::Chat { message },
+        LocalAction::VoteSkip { expected_path } => WireAction::VoteSkip { expected_path },
+        LocalAction::ReportDrift(drift_ms) => WireAction::ReportDrift(drift_ms),
     }
 }
 
@@ -4333,6 +5012,7 @@ fn wire_to_action(action: WireAction) -> LocalAction {
             to_index,
             expected_path,
         },
+        WireAction::QueueShuffle { order } => LocalAction::QueueShuffle { order },
         WireAction::QueueConsume { expected_path } => LocalAction::QueueConsume { expected_path },
         WireAction::DelayUpdate {
             manual_extra_delay_ms,
@@ -4342,6 +5022,9 @@ fn wire_to_action(action: WireAction) -> LocalAction {
             auto_ping_delay,
         },
         WireAction::Transport(envelope) => LocalAction::Transport(envelope),
+        WireAction::Chat { message } => LocalAction::Chat { message },
+        WireAction::VoteSkip { expected_path } => LocalAction::VoteSkip { expected_path },
+        WireAction::ReportDrift(drift_ms) => LocalAction::ReportDrift(drift_ms),
     }
 }
 
@@ -4466,7 +5149,8 @@ mod tests {
             request_id: 7,
             path: PathBuf::from("track.flac"),
             total_bytes: 123,
-            payload_format: StreamPayloadFormat::BalancedOpus160kVbr,
+            payload_format: StreamPayloadFormat::LowOpus96kVbr,
+            duration_ms: Some(215_000),
         };
         let encoded = serde_json::to_string(&msg).expect("serialize");
         let decoded: WireServerMessage = serde_json::from_str(&encoded).expect("deserialize");
@@ -4476,11 +5160,13 @@ mod tests {
                 path,
                 total_bytes,
                 payload_format,
+                duration_ms,
             } => {
                 assert_eq!(request_id, 7);
                 assert_eq!(path, PathBuf::from("track.flac"));
                 assert_eq!(total_bytes, 123);
-                assert_eq!(payload_format, StreamPayloadFormat::BalancedOpus160kVbr);
+                assert_eq!(payload_format, StreamPayloadFormat::LowOpus96kVbr);
+                assert_eq!(duration_ms, Some(215_000));
             }
             other => panic!("unexpected message: {other:?}"),
         }
@@ -4593,6 +5279,45 @@ mod tests {
         assert_eq!(path.extension().and_then(|ext| ext.to_str()), Some("wav"));
     }
 
+    #[test]
+    fn opus_payloads_are_cached_per_bitrate_and_served_with_their_size() {
+        let source_path = unique_temp_file("cached_source", "flac");
+        fs::write(&source_path, b"not really flac").expect("write source");
+        let balanced = opus_payload_cache_path(&source_path, 160).expect("balanced cache");
+        let low = opus_payload_cache_path(&source_path, 96).expect("low cache");
+        assert_ne!(balanced, low);
+        assert_eq!(
+            opus_payload_cache_path(&source_path, 96).expect("low cache again"),
+            low
+        );
+
+        let fresh = OutboundStream::prepare(&source_path, StreamQuality::Low).expect("prepare");
+        assert_eq!(fresh.payload_format, StreamPayloadFormat::LowOpus96kVbr);
+        assert_eq!(fresh.total_bytes, 0);
+
+        let payload = b"TTOP1-cached-payload".repeat(4);
+        fs::write(&low, &payload).expect("write cached payload");
+        let cached = OutboundStream::prepare(&source_path, StreamQuality::Low).expect("prepare");
+        assert_eq!(cached.total_bytes, payload.len() as u64);
+        let mut sent = Vec::new();
+        cached
+            .send_chunks(&source_path, |chunk| {
+                sent.extend_from_slice(chunk);
+                Ok(())
+            })
+            .expect("send cached payload");
+        assert_eq!(sent, payload);
+
+        fs::write(&source_path, b"edited, longer than before").expect("edit source");
+        assert_ne!(
+            opus_payload_cache_path(&source_path, 96).expect("cache after edit"),
+            low
+        );
+
+        let _ = fs::remove_file(source_path);
+        let _ = fs::remove_file(low);
+    }
+
     #[test]
     fn balanced_opus_encode_decode_round_trip_accepts_stereo_payload() {
         let source_path = unique_temp_file("balanced_source", "wav");
@@ -4671,6 +5396,33 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn stream_progress_is_throttled_but_reports_first_and_final_chunks() {
+        let chunk = STREAM_CHUNK_BYTES as u64;
+        let total = 100 * 1024 * 1024;
+        let soon = Some(Duration::from_millis(5));
+
+        assert!(stream_progress_due(0, chunk, total, None));
+        assert!(!stream_progress_due(chunk, 2 * chunk, total, soon));
+        assert!(stream_progress_due(
+            chunk,
+            chunk + STREAM_PROGRESS_MIN_BYTES,
+            total,
+            soon
+        ));
+        assert!(stream_progress_due(
+            chunk,
+            2 * chunk,
+            total,
+            Some(STREAM_PROGRESS_INTERVAL)
+        ));
+        assert!(stream_progress_due(total - chunk, total, total, soon));
+
+        let small_total = 1_000 * chunk;
+        assert!(stream_progress_due(0, small_total / 100, small_total, soon));
+        assert!(!stream_progress_due(0, chunk, 0, soon));
+    }
+
     #[test]
     fn queue_consume_removes_front_when_expected_matches() {
         let mut session = OnlineSession::host("host");
@@ -4844,9 +5596,8 @@ mod tests {
     }
 
     #[test]
-    fn host_only_blocks_listener_queue_add_network_action() {
+    fn queue_shuffle_applies_only_from_host() {
         let mut session = OnlineSession::host("host");
-        session.mode = crate::online::OnlineRoomMode::HostOnly;
         session.participants.push(crate::online::Participant {
             nickname: String::from("listener"),
             is_local: false,
@@ -4854,20 +5605,40 @@ mod tests {
             ping_ms: 0,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
+        for name in ["a.flac", "b.flac", "c.flac"] {
+            session
+                .shared_queue
+                .push_back(crate::online::SharedQueueItem {
+                    path: PathBuf::from(name),
+                    title: name.to_string(),
+                    delivery: crate::online::QueueDelivery::HostStreamOnly,
+                    owner_nickname: None,
+                });
+        }
+        let paths = |session: &OnlineSession| {
+            session
+                .shared_queue
+                .iter()
+                .map(|item| item.path.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
 
         apply_action_to_session(
             &mut session,
-            LocalAction::QueueAdd(crate::online::SharedQueueItem {
-                path: PathBuf::from("a.flac"),
-                title: String::from("a"),
-                delivery: crate::online::QueueDelivery::HostStreamOnly,
-                owner_nickname: Some(String::from("listener")),
-            }),
+            LocalAction::QueueShuffle {
+                order: vec![2, 1, 0],
+            },
             "listener",
         );
+        assert_eq!(paths(&session), ["a.flac", "b.flac", "c.flac"]);
 
-        assert!(session.shared_queue.is_empty());
+        let wire = action_to_wire(LocalAction::QueueShuffle {
+            order: vec![0, 2, 1],
+        });
+        apply_action_to_session(&mut session, wire_to_action(wire), "host");
+        assert_eq!(paths(&session), ["a.flac", "c.flac", "b.flac"]);
     }
 
     #[test]
@@ -4881,6 +5652,7 @@ mod tests {
             ping_ms: 12,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
 
         apply_action_to_session(
@@ -4901,6 +5673,151 @@ mod tests {
         assert!(!listener.auto_ping_delay);
     }
 
+    #[test]
+    fn host_only_accepts_listener_requests_under_their_own_name_only() {
+        let mut session = OnlineSession::host("host");
+        session.mode = crate::online::OnlineRoomMode::HostOnly;
+        session.participants.push(crate::online::Participant {
+            nickname: String::from("listener"),
+            is_local: false,
+            is_host: false,
+            ping_ms: 12,
+            manual_extra_delay_ms: 0,
+            auto_ping_delay: true,
+            last_drift_ms: None,
+        });
+        let request = |owner: &str, name: &str| {
+            LocalAction::QueueAdd(crate::online::SharedQueueItem {
+                path: PathBuf::from(name),
+                title: name.to_string(),
+                delivery: crate::online::QueueDelivery::PreferLocalWithStreamFallback,
+                owner_nickname: Some(owner.to_string()),
+            })
+        };
+
+        apply_action_to_session(&mut session, request("host", "spoof.flac"), "listener");
+        assert!(session.shared_queue.is_empty());
+
+        for name in ["a.flac", "b.flac", "c.flac", "d.flac"] {
+            apply_action_to_session(&mut session, request("listener", name), "listener");
+        }
+        assert_eq!(
+            session.shared_queue.len(),
+            crate::online::MAX_LISTENER_REQUESTS
+        );
+
+        apply_action_to_session(
+            &mut session,
+            LocalAction::QueueRemoveAt {
+                index: 0,
+                expected_path: None,
+            },
+            "listener",
+        );
+        assert_eq!(
+            session.shared_queue.len(),
+            crate::online::MAX_LISTENER_REQUESTS
+        );
+    }
+
+    #[test]
+    fn drift_reports_land_on_the_reporting_participant() {
+        let mut session = OnlineSession::host("host");
+        session.mode = crate::online::OnlineRoomMode::HostOnly;
+        session.participants.push(crate::online::Participant {
+            nickname: String::from("listener"),
+            is_local: false,
+            is_host: false,
+            ping_ms: 12,
+            manual_extra_delay_ms: 0,
+            auto_ping_delay: true,
+            last_drift_ms: None,
+        });
+
+        let wire = action_to_wire(LocalAction::ReportDrift(-42));
+        apply_action_to_session(&mut session, wire_to_action(wire), "Listener");
+
+        assert_eq!(session.participants[0].last_drift_ms, None);
+        assert_eq!(session.participants[1].last_drift_ms, Some(-42));
+    }
+
+    #[test]
+    fn skip_votes_tally_once_per_listener_and_reset_on_track_change() {
+        let mut session = OnlineSession::host("host");
+        session.mode = crate::online::OnlineRoomMode::HostOnly;
+        session.participants.push(crate::online::Participant {
+            nickname: String::from("listener"),
+            is_local: false,
+            is_host: false,
+            ping_ms: 12,
+            manual_extra_delay_ms: 0,
+            auto_ping_delay: true,
+            last_drift_ms: None,
+        });
+        let playing = |path: &str| {
+            LocalAction::Transport(TransportEnvelope {
+                seq: 0,
+                origin_nickname: String::new(),
+                command: crate::online::TransportCommand::SetPlaybackState {
+                    path: PathBuf::from(path),
+                    title: None,
+                    artist: None,
+                    album: None,
+                    provider_track_id: None,
+                    position_ms: 0,
+                    paused: false,
+                },
+            })
+        };
+        let vote = |path: &str| LocalAction::VoteSkip {
+            expected_path: Some(PathBuf::from(path)),
+        };
+
+        apply_action_to_session(&mut session, vote("a.flac"), "listener");
+        assert!(session.skip_votes.is_empty());
+
+        apply_action_to_session(&mut session, playing("a.flac"), "host");
+        apply_action_to_session(&mut session, vote("old.flac"), "listener");
+        assert!(session.skip_votes.is_empty());
+        let wire = action_to_wire(vote("a.flac"));
+        apply_action_to_session(&mut session, wire_to_action(wire), "listener");
+        apply_action_to_session(&mut session, vote("a.flac"), "listener");
+        assert_eq!(session.skip_votes, ["listener"]);
+
+        apply_action_to_session(&mut session, playing("a.flac"), "host");
+        assert_eq!(session.skip_votes, ["listener"]);
+        apply_action_to_session(&mut session, playing("b.flac"), "host");
+        assert!(session.skip_votes.is_empty());
+    }
+
+    #[test]
+    fn host_only_allows_listener_chat_under_the_sender_nickname() {
+        let mut session = OnlineSession::host("host");
+        session.mode = crate::online::OnlineRoomMode::HostOnly;
+        session.participants.push(crate::online::Participant {
+            nickname: String::from("listener"),
+            is_local: false,
+            is_host: false,
+            ping_ms: 12,
+            manual_extra_delay_ms: 0,
+            auto_ping_delay: true,
+            last_drift_ms: None,
+        });
+
+        let wire = action_to_wire(LocalAction::Chat {
+            message: String::from("great track\u{202E}"),
+        });
+        apply_action_to_session(&mut session, wire_to_action(wire), "listener");
+
+        assert_eq!(
+            session.chat_log.back(),
+            Some(&crate::online::ChatMessage {
+                from: String::from("listener"),
+                message: String::from("great track"),
+            })
+        );
+    }
+
     #[test]
     fn nickname_update_renames_participant_and_owned_queue_items() {
         let mut session = OnlineSession::host("host");
@@ -4939,6 +5856,7 @@ mod tests {
             ping_ms: 12,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
 
         apply_action_to_session(
@@ -5000,6 +5918,7 @@ mod tests {
             ping_ms: 25,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
 
         let listener = TcpListener::bind("127.0.0.1:0").expect("bind listener");
@@ -5012,7 +5931,7 @@ mod tests {
             9,
             PeerConnection {
                 nickname: String::from("listenera"),
-                writer: Arc::new(Mutex::new(server_stream)),
+                writer: Arc::new(Mutex::new(WireWriter::plain(server_stream))),
             },
         );
         drop(client_stream);
@@ -5078,6 +5997,7 @@ mod tests {
             ping_ms: 25,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
         session
             .shared_queue
@@ -5106,7 +6026,7 @@ mod tests {
             9,
             PeerConnection {
                 nickname: String::from("ListenerA"),
-                writer: Arc::new(Mutex::new(server_stream)),
+                writer: Arc::new(Mutex::new(WireWriter::plain(server_stream))),
             },
         );
         drop(client_stream);
@@ -5157,6 +6077,7 @@ mod tests {
             ping_ms: 20,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
         session.participants.push(crate::online::Participant {
             nickname: String::from("beta"),
@@ -5165,6 +6086,7 @@ mod tests {
             ping_ms: 22,
             manual_extra_delay_ms: 0,
             auto_ping_delay: true,
+            last_drift_ms: None,
         });
 
         let listener = TcpListener::bind("127.0.0.1:0").expect("bind listener");
@@ -5177,7 +6099,7 @@ mod tests {
             1,
             PeerConnection {
                 nickname: String::from("HOST"),
-                writer: Arc::new(Mutex::new(server_stream)),
+                writer: Arc::new(Mutex::new(WireWriter::plain(server_stream))),
             },
         );
         drop(client_stream);
@@ -5229,7 +6151,8 @@ mod tests {
         drop(probe);
 
         let home_addr = format!("127.0.0.1:{port}");
-        let handle = start_home_server(&home_addr, None).expect("start home server");
+        let handle =
+            start_home_server(&home_addr, None, RoomTransport::Sealed).expect("start home server");
 
         verify_home_server(&home_addr).expect("verify home server");
         let room =
@@ -5244,6 +6167,93 @@ mod tests {
         handle.shutdown();
     }
 
+    #[test]
+    fn home_server_status_lists_rooms_and_shutdown_tells_listeners() {
+        let probe = TcpListener::bind("127.0.0.1:0").expect("bind probe port");
+        let port = probe.local_addr().expect("probe addr").port();
+        drop(probe);
+
+        let home_addr = format!("127.0.0.1:{port}");
+        let handle =
+            start_home_server(&home_addr, None, RoomTransport::Sealed).expect("start home server");
+        let room = create_home_room(&home_addr, "Den", "hoster", Some("party123"), 4)
+            .expect("create room");
+        let client = OnlineNetwork::start_client(
+            &room.room_server_addr,
+            &room.room_code,
+            "hoster",
+            Some(String::from("party123")),
+        )
+        .expect("join created room");
+
+        let deadline = Instant::now() + Duration::from_secs(3);
+        let status = loop {
+            let status = home_server_status(&home_addr).expect("query status");
+            if status.rooms.first().map(|room| room.current_connections) == Some(1)
+                || Instant::now() >= deadline
+            {
+                break status;
+            }
+            thread::sleep(Duration::from_millis(50));
+        };
+        assert_eq!(status.room_ports, "dynamic");
+        assert_eq!(
+            status.rooms,
+            vec![HomeRoomStatus {
+                room_name: String::from("Den"),
+                port: RoomTransport::parse_room_addr(&room.room_server_addr)
+                    .1
+                    .rsplit_once(':')
+                    .and_then(|(_, port)| port.parse().ok())
+                    .expect("room port"),
+                current_connections: 1,
+                max_connections: 4,
+                locked: true,
+                encrypted: true,
+            }]
+        );
+
+        handle.shutdown();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let mut told = false;
+        while !told && Instant::now() < deadline {
+            match client.try_recv_event() {
+                Some(NetworkEvent::Status(message)) => {
+                    told = message.contains("Host ended session");
+                }
+                Some(_) => {}
+                None => thread::sleep(Duration::from_millis(20)),
+            }
+        }
+        assert!(told, "listeners should hear the room end");
+        assert!(home_server_status(&home_addr).is_err());
+        client.shutdown();
+    }
+
+    #[test]
+    fn home_server_bind_failure_explains_port_in_use() {
+        let probe = TcpListener::bind("127.0.0.1:0").expect("bind probe port");
+        let port = probe.local_addr().expect("probe addr").port();
+
+        let err = match start_home_server(&format!("127.0.0.1:{port}"), None, RoomTransport::Sealed)
+        {
+            Ok(_) => panic!("port should already be in use"),
+            Err(err) => err,
+        };
+        assert_eq!(
+            err.to_string(),
+            format!("port {port} is already in use; try --host-ip host:port or a different port")
+        );
+    }
+
+    #[test]
+    fn bind_error_message_suggests_high_port_on_permission_denied() {
+        let err = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let message =
+            bind_error_message(&err, "0.0.0.0:80", "online host", "try --room-port-range");
+        assert!(message.starts_with("permission denied for port 80; use a port above 1024"));
+    }
+
     #[test]
     fn home_server_created_room_client_stays_connected_briefly() {
         let probe = TcpListener::bind("127.0.0.1:0").expect("bind probe port");
@@ -5251,7 +6261,8 @@ mod tests {
         drop(probe);
 
         let home_addr = format!("127.0.0.1:{port}");
-        let handle = start_home_server(&home_addr, None).expect("start home server");
+        let handle =
+            start_home_server(&home_addr, None, RoomTransport::Sealed).expect("start home server");
         verify_home_server(&home_addr).expect("verify home server");
         let room =
             create_home_room(&home_addr, "roomname", "hoster", None, 8).expect("create room");
@@ -5318,4 +6329,65 @@ mod tests {
         client.shutdown();
         host.shutdown();
     }
+
+    #[test]
+    fn password_rooms_seal_the_connection_and_refuse_plain_or_wrong_keys() {
+        let mut session = OnlineSession::host("hoster");
+        session.room_code = String::from("ROOM");
+        session.participants.clear();
+        let host = OnlineNetwork::start_host_with_max(
+            "127.0.0.1:0",
+            session,
+            Some(String::from("party123")),
+            8,
+        )
+        .expect("start sealed host");
+        let bare_addr = host.bind_addr().expect("host addr").to_string();
+        let sealed_addr = RoomTransport::Sealed.room_addr(&bare_addr);
+
+        let plain = OnlineNetwork::start_client(
+            &bare_addr,
+            "ROOM",
+            "plain",
+            Some(String::from("party123")),
+        );
+        let plain_err = plain.err().expect("plain hello refused").to_string();
+        assert!(plain_err.contains("encrypted"), "{plain_err}");
+
+        let wrong = OnlineNetwork::start_client(
+            &sealed_addr,
+            "ROOM",
+            "wrong",
+            Some(String::from("party124")),
+        );
+        assert!(wrong.is_err(), "wrong password must not open the room");
+
+        let client = OnlineNetwork::start_client(
+            &sealed_addr,
+            "ROOM",
+            "hoster",
+            Some(String::from("party123")),
+        )
+        .expect("join sealed host");
+        client.send_local_action(LocalAction::Chat {
+            message: String::from("over the wire"),
+        });
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut delivered = false;
+        while Instant::now() < deadline && !delivered {
+            while let Some(event) = client.try_recv_event() {
+                if let NetworkEvent::SessionSync(session) = event {
+                    delivered |= session
+                        .chat_log
+                        .iter()
+                        .any(|chat| chat.message == "over the wire");
+                }
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(delivered, "chat never came back over the sealed connection");
+
+        client.shutdown();
+        host.shutdown();
+    }
 }
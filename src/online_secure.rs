@@ -0,0 +1,386 @@
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngExt;
+use sha2::Sha256;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream};
+
+pub(crate) const HANDSHAKE_NONCE_BYTES: usize = 16;
+const SEALED_ADDR_SCHEME: &str = "tunes://";
+/// Every guess at a captured handshake costs this many HMAC rounds.
+const KEY_DERIVATION_ROUNDS: u32 = 20_000;
+/// Far above any session snapshot; a corrupt length cannot allocate gigabytes.
+const MAX_FRAME_BYTES: usize = 16 * 1024 * 1024;
+
+/// How a room's connections are carried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoomTransport {
+    /// JSON lines as they are. Rooms without a password, or every room on a
+    /// home server started with `--plain-rooms`.
+    #[default]
+    Plain,
+    /// Every line encrypted with a key derived from the room password.
+    Sealed,
+}
+
+impl RoomTransport {
+    /// Sealing needs the room password, so open rooms always stay plain.
+    pub fn for_room(preferred: RoomTransport, password: Option<&str>) -> Self {
+        if password.is_some_and(|value| !value.trim().is_empty()) {
+            preferred
+        } else {
+            RoomTransport::Plain
+        }
+    }
+
+    /// Adds the scheme to a bare `host:port`. Plain addresses stay bare so
+    /// they read the same as before sealed rooms existed.
+    pub fn room_addr(self, addr: &str) -> String {
+        match self {
+            RoomTransport::Plain => addr.to_string(),
+            RoomTransport::Sealed => format!("{SEALED_ADDR_SCHEME}{addr}"),
+        }
+    }
+
+    /// Splits a room address back into its transport and bare `host:port`.
+    pub fn parse_room_addr(addr: &str) -> (Self, &str) {
+        let addr = addr.trim();
+        match addr.strip_prefix(SEALED_ADDR_SCHEME) {
+            Some(rest) => (RoomTransport::Sealed, rest),
+            None => (RoomTransport::Plain, addr),
+        }
+    }
+}
+
+/// One key per direction, so the two frame counters never share a nonce.
+pub(crate) struct TransportKeys {
+    pub(crate) client_to_host: [u8; 32],
+    pub(crate) host_to_client: [u8; 32],
+}
+
+/// Both nonces go into the salt, so every connection gets fresh keys even
+/// though the password stays the same.
+pub(crate) fn derive_transport_keys(
+    password: &str,
+    client_nonce: &[u8],
+    host_nonce: &[u8],
+) -> TransportKeys {
+    let mut salt = Vec::with_capacity(32 + client_nonce.len() + host_nonce.len());
+    salt.extend_from_slice(b"tunetui-transport-v1");
+    salt.extend_from_slice(client_nonce);
+    salt.extend_from_slice(host_nonce);
+    let okm = pbkdf2::pbkdf2_hmac_array::<Sha256, 64>(
+        password.trim().as_bytes(),
+        &salt,
+        KEY_DERIVATION_ROUNDS,
+    );
+    let mut keys = TransportKeys {
+        client_to_host: [0; 32],
+        host_to_client: [0; 32],
+    };
+    keys.client_to_host.copy_from_slice(&okm[..32]);
+    keys.host_to_client.copy_from_slice(&okm[32..]);
+    keys
+}
+
+/// Seals a secret kept on disk, such as a remembered room password, with a
+/// key that stays on this machine. The random nonce goes in front.
+pub(crate) fn seal_at_rest(key: &[u8; 32], secret: &str) -> Option<String> {
+    let mut nonce = [0_u8; 12];
+    rand::rng().fill(&mut nonce);
+    let sealed = ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(Nonce::from_slice(&nonce), secret.as_bytes())
+        .ok()?;
+    let mut bytes = Vec::with_capacity(nonce.len() + sealed.len());
+    bytes.extend_from_slice(&nonce);
+    bytes.extend_from_slice(&sealed);
+    Some(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// `None` when the text was sealed with another key or was changed.
+pub(crate) fn open_at_rest(key: &[u8; 32], sealed: &str) -> Option<String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(sealed.trim())
+        .ok()?;
+    if bytes.len() < 12 {
+        return None;
+    }
+    let (nonce, sealed) = bytes.split_at(12);
+    let clear = ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), sealed)
+        .ok()?;
+    String::from_utf8(clear).ok()
+}
+
+struct FrameCipher {
+    cipher: ChaCha20Poly1305,
+    counter: u64,
+}
+
+impl FrameCipher {
+    fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(key)),
+            counter: 0,
+        }
+    }
+
+    /// Frames are numbered, so a dropped, replayed or reordered frame fails
+    /// to open.
+    fn next_nonce(&mut self) -> Nonce {
+        let mut nonce = [0_u8; 12];
+        nonce[4..].copy_from_slice(&self.counter.to_be_bytes());
+        self.counter = self.counter.wrapping_add(1);
+        *Nonce::from_slice(&nonce)
+    }
+}
+
+/// Write half of a room connection. Once sealed, everything written between
+/// two flushes goes out as one encrypted frame.
+pub(crate) struct WireWriter {
+    stream: TcpStream,
+    sealer: Option<FrameCipher>,
+    pending: Vec<u8>,
+}
+
+impl WireWriter {
+    pub(crate) fn plain(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            sealer: None,
+            pending: Vec::new(),
+        }
+    }
+
+    pub(crate) fn seal(&mut self, key: &[u8; 32]) {
+        self.sealer = Some(FrameCipher::new(key));
+    }
+
+    pub(crate) fn is_sealed(&self) -> bool {
+        self.sealer.is_some()
+    }
+
+    pub(crate) fn shutdown(&self) -> io::Result<()> {
+        self.stream.shutdown(Shutdown::Both)
+    }
+}
+
+impl std::fmt::Debug for WireWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WireWriter")
+            .field("stream", &self.stream)
+            .field("sealed", &self.is_sealed())
+            .finish()
+    }
+}
+
+impl Write for WireWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.sealer.is_none() {
+            return self.stream.write(buf);
+        }
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(sealer) = self.sealer.as_mut()
+            && !self.pending.is_empty()
+        {
+            let nonce = sealer.next_nonce();
+            let sealed = sealer
+                .cipher
+                .encrypt(&nonce, self.pending.as_slice())
+                .map_err(|_| io::Error::other("failed to encrypt frame"))?;
+            self.pending.clear();
+            let len = u32::try_from(sealed.len())
+                .ok()
+                .filter(|len| *len as usize <= MAX_FRAME_BYTES)
+                .ok_or_else(|| io::Error::other("frame too large to encrypt"))?;
+            let mut frame = Vec::with_capacity(4 + sealed.len());
+            frame.extend_from_slice(&len.to_be_bytes());
+            frame.extend_from_slice(&sealed);
+            self.stream.write_all(&frame)?;
+        }
+        self.stream.flush()
+    }
+}
+
+/// Read half of a room connection. Once sealed, each frame is opened and
+/// checked before any of it reaches the line reader.
+pub(crate) struct WireReader {
+    reader: BufReader<TcpStream>,
+    opener: Option<FrameCipher>,
+    clear: Vec<u8>,
+    clear_pos: usize,
+}
+
+impl WireReader {
+    pub(crate) fn plain(stream: TcpStream) -> Self {
+        Self {
+            reader: BufReader::new(stream),
+            opener: None,
+            clear: Vec::new(),
+            clear_pos: 0,
+        }
+    }
+
+    /// Bytes after the handshake line may already be buffered; they are
+    /// read as frames from here on.
+    pub(crate) fn seal(&mut self, key: &[u8; 32]) {
+        self.opener = Some(FrameCipher::new(key));
+    }
+
+    /// `Ok(false)` when the stream ended cleanly between frames.
+    fn open_next_frame(&mut self) -> io::Result<bool> {
+        let Some(opener) = self.opener.as_mut() else {
+            return Ok(false);
+        };
+        let mut len = [0_u8; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(err) => return Err(err),
+        }
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_FRAME_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "encrypted frame too large",
+            ));
+        }
+        let mut sealed = vec![0_u8; len];
+        self.reader.read_exact(&mut sealed)?;
+        let nonce = opener.next_nonce();
+        self.clear = opener
+            .cipher
+            .decrypt(&nonce, sealed.as_slice())
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "encrypted frame failed to verify",
+                )
+            })?;
+        self.clear_pos = 0;
+        Ok(true)
+    }
+}
+
+impl Read for WireReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let read = available.len().min(buf.len());
+        buf[..read].copy_from_slice(&available[..read]);
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl BufRead for WireReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.opener.is_none() {
+            return self.reader.fill_buf();
+        }
+        while self.clear_pos >= self.clear.len() {
+            if !self.open_next_frame()? {
+                return Ok(&[]);
+            }
+        }
+        Ok(&self.clear[self.clear_pos..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        if self.opener.is_none() {
+            self.reader.consume(amount);
+        } else {
+            self.clear_pos = (self.clear_pos + amount).min(self.clear.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn connected_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let client = TcpStream::connect(listener.local_addr().expect("addr")).expect("connect");
+        let (server, _) = listener.accept().expect("accept");
+        (client, server)
+    }
+
+    #[test]
+    fn room_addresses_carry_the_transport_scheme() {
+        assert_eq!(
+            RoomTransport::Sealed.room_addr("203.0.113.9:9001"),
+            "tunes://203.0.113.9:9001"
+        );
+        assert_eq!(
+            RoomTransport::parse_room_addr(" tunes://203.0.113.9:9001"),
+            (RoomTransport::Sealed, "203.0.113.9:9001")
+        );
+        assert_eq!(
+            RoomTransport::parse_room_addr("10.0.0.4:9001"),
+            (RoomTransport::Plain, "10.0.0.4:9001")
+        );
+        assert_eq!(
+            RoomTransport::for_room(RoomTransport::Sealed, Some("  ")),
+            RoomTransport::Plain
+        );
+        assert_eq!(
+            RoomTransport::for_room(RoomTransport::Sealed, Some("party")),
+            RoomTransport::Sealed
+        );
+    }
+
+    #[test]
+    fn sealed_lines_round_trip_and_hide_their_contents() {
+        let (client, server) = connected_pair();
+        let keys = derive_transport_keys("party123", &[1; 16], &[2; 16]);
+        let mut writer = WireWriter::plain(client.try_clone().expect("clone"));
+        writer.seal(&keys.client_to_host);
+        let mut reader = WireReader::plain(server.try_clone().expect("clone"));
+        reader.seal(&keys.client_to_host);
+
+        for message in ["{\"hello\":\"room\"}\n", "{\"second\":true}\n"] {
+            writer.write_all(message.as_bytes()).expect("write");
+            writer.flush().expect("flush");
+        }
+        let mut line = String::new();
+        reader.read_line(&mut line).expect("first line");
+        assert_eq!(line, "{\"hello\":\"room\"}\n");
+        line.clear();
+        reader.read_line(&mut line).expect("second line");
+        assert_eq!(line, "{\"second\":true}\n");
+
+        writer.write_all(b"secret-track.flac\n").expect("write");
+        writer.flush().expect("flush");
+        let mut raw = WireReader::plain(server);
+        let mut bytes = vec![0_u8; 64];
+        let read = raw.read(&mut bytes).expect("raw read");
+        assert!(
+            !String::from_utf8_lossy(&bytes[..read]).contains("secret-track"),
+            "sealed frames must not carry plaintext"
+        );
+    }
+
+    #[test]
+    fn frames_sealed_with_another_password_are_refused() {
+        let (client, server) = connected_pair();
+        let right = derive_transport_keys("party123", &[1; 16], &[2; 16]);
+        let wrong = derive_transport_keys("party124", &[1; 16], &[2; 16]);
+        let mut writer = WireWriter::plain(client);
+        writer.seal(&wrong.client_to_host);
+        let mut reader = WireReader::plain(server);
+        reader.seal(&right.client_to_host);
+
+        writer.write_all(b"{\"hello\":1}\n").expect("write");
+        writer.flush().expect("flush");
+        let err = reader
+            .read_line(&mut String::new())
+            .expect_err("wrong key must not open");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
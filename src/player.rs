@@ -0,0 +1,1033 @@
+use crate::audio::AudioEngine;
+use crate::core::TuneCore;
+use crate::scrobble::{Scrobble, ScrobbleQueue};
+use crate::stats::{self, ListenSessionRecord, StatsStore};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const PARTIAL_LISTEN_FLUSH_SECONDS: u32 = 10;
+const LOOP_RESTART_END_WINDOW_SECONDS: u64 = 2;
+const LOOP_RESTART_START_WINDOW_SECONDS: u64 = 5;
+const LOOP_RESTART_FALLBACK_MIN_PREVIOUS_SECONDS: u64 = 20;
+/// Roughly -50 dBFS; quieter than any real music passage, louder than dither noise.
+const SILENCE_LEVEL_THRESHOLD: f32 = 0.003;
+/// How long before the current track ends the next one is decoded and appended
+/// for gapless playback.
+const GAPLESS_QUEUE_LEAD: Duration = Duration::from_secs(2);
+/// How long the sleep timer takes to fade playback out before pausing.
+const SLEEP_FADE: Duration = Duration::from_secs(5);
+
+/// Playback changes reported to `Player::subscribe` listeners. `Finished` is
+/// sent when a track ends on its own (or is replaced by the queue advancing),
+/// not when the user skips or stops it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlaybackEvent {
+    TrackStarted(PathBuf),
+    Paused,
+    Resumed,
+    Finished(PathBuf),
+    Seeked(Duration),
+    VolumeChanged(f32),
+    Stopped,
+}
+
+type PlaybackListener = Box<dyn FnMut(&PlaybackEvent)>;
+
+/// Headless playback: a `TuneCore` queue driving an `AudioEngine`, with listen
+/// stats recorded as tracks play. Nothing here touches the terminal, so it can
+/// be embedded in other programs. Call `tick` regularly (every 100ms or so).
+pub struct Player {
+    pub core: TuneCore,
+    pub audio: Box<dyn AudioEngine>,
+    pub stats: StatsStore,
+    listen_tracker: ListenTracker,
+    silence_watch: SilenceWatch,
+    sleep_timer: SleepTimer,
+    listeners: Vec<PlaybackListener>,
+    last_track: Option<PathBuf>,
+}
+
+impl Player {
+    /// Wraps an existing core, engine, and stats store. Use
+    /// `TuneCore::from_persisted` with `config::load_state` to start from the
+    /// saved TuneTUI state, or `PersistedState::default()` for a blank one.
+    pub fn new(core: TuneCore, audio: Box<dyn AudioEngine>, stats: StatsStore) -> Self {
+        Self {
+            core,
+            audio,
+            stats,
+            listen_tracker: ListenTracker::default(),
+            silence_watch: SilenceWatch::default(),
+            sleep_timer: SleepTimer::default(),
+            listeners: Vec::new(),
+            last_track: None,
+        }
+    }
+
+    /// Registers a listener that is called with every `PlaybackEvent`, in order,
+    /// from whichever `Player` method caused it.
+    pub fn subscribe(&mut self, listener: impl FnMut(&PlaybackEvent) + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    fn emit(&mut self, event: PlaybackEvent) {
+        for listener in &mut self.listeners {
+            listener(&event);
+        }
+    }
+
+    fn start_track(&mut self, path: &Path) -> Result<()> {
+        self.audio.play(path)?;
+        self.last_track = self.audio.current_track().map(Path::to_path_buf);
+        self.emit(PlaybackEvent::TrackStarted(path.to_path_buf()));
+        Ok(())
+    }
+
+    /// Plays `path` directly, outside the queue order.
+    pub fn play(&mut self, path: &Path) -> Result<()> {
+        self.start_track(path)
+    }
+
+    /// Plays the next queued track. Returns `false` when the queue has nothing next.
+    pub fn next_track(&mut self) -> Result<bool> {
+        match self.core.next_track_path() {
+            Some(path) => self.start_track(&path).map(|()| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Plays the previous queued track. Returns `false` when there is none.
+    pub fn previous_track(&mut self) -> Result<bool> {
+        match self.core.prev_track_path() {
+            Some(path) => self.start_track(&path).map(|()| true),
+            None => Ok(false),
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.audio.current_track().is_none() || self.audio.is_paused() {
+            return;
+        }
+        self.audio.pause();
+        self.emit(PlaybackEvent::Paused);
+    }
+
+    pub fn resume(&mut self) {
+        if self.audio.current_track().is_none() || !self.audio.is_paused() {
+            return;
+        }
+        self.audio.resume();
+        self.emit(PlaybackEvent::Resumed);
+    }
+
+    pub fn seek_to(&mut self, position: Duration) -> Result<()> {
+        self.audio.seek_to(position)?;
+        self.emit(PlaybackEvent::Seeked(position));
+        Ok(())
+    }
+
+    /// Sets the engine volume and reports the clamped value it settled on.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.audio.set_volume(volume);
+        let volume = self.audio.volume();
+        self.emit(PlaybackEvent::VolumeChanged(volume));
+    }
+
+    /// Stops playback after recording the listen so far.
+    pub fn stop(&mut self) {
+        self.listen_tracker.finalize_active(&mut self.stats, false);
+        self.audio.stop();
+        self.core.clear_now_playing();
+        self.last_track = None;
+        self.emit(PlaybackEvent::Stopped);
+    }
+
+    /// Advances the engine and queue. Returns `true` when listen stats changed
+    /// and should be saved with `stats::save_stats`.
+    pub fn tick(&mut self) -> bool {
+        self.audio.tick();
+        if self
+            .silence_watch
+            .tick(&mut self.core, &mut *self.audio, Instant::now())
+        {
+            self.emit(PlaybackEvent::Paused);
+        }
+        if self
+            .sleep_timer
+            .tick(&mut self.core, &mut *self.audio, Instant::now())
+        {
+            self.emit(PlaybackEvent::Paused);
+        }
+        let stats_changed =
+            self.listen_tracker
+                .tick(&self.core, &*self.audio, &mut self.stats, None);
+        advance_local_queue(&mut self.core, &mut *self.audio, &self.stats.events);
+        self.sync_track_events();
+        stats_changed
+    }
+
+    /// Reports track changes the engine or queue made on its own during `tick`,
+    /// such as a track ending or a crossfade handing over to the next one.
+    fn sync_track_events(&mut self) {
+        let current = self.audio.current_track().map(Path::to_path_buf);
+        if current == self.last_track {
+            return;
+        }
+        if let Some(finished) = self.last_track.take() {
+            self.emit(PlaybackEvent::Finished(finished));
+        }
+        if let Some(started) = current.clone() {
+            self.emit(PlaybackEvent::TrackStarted(started));
+        }
+        self.last_track = current;
+    }
+
+    /// Restarts the sleep-on-silence timer. Call on any user input.
+    pub fn note_user_input(&mut self) {
+        self.silence_watch.reset();
+    }
+}
+
+/// Sleep-on-silence: pauses playback once the engine has metered nothing above
+/// `SILENCE_LEVEL_THRESHOLD` for `core.sleep_on_silence_minutes`, so listen time
+/// stops accruing when nobody is around. Off when the setting is 0 or the engine
+/// cannot meter its output.
+#[derive(Debug, Default)]
+pub struct SilenceWatch {
+    silent_since: Option<Instant>,
+}
+
+impl SilenceWatch {
+    pub fn reset(&mut self) {
+        self.silent_since = None;
+    }
+
+    /// Samples the output level once. Returns `true` when playback was paused.
+    pub fn tick(&mut self, core: &mut TuneCore, audio: &mut dyn AudioEngine, now: Instant) -> bool {
+        let minutes = core.sleep_on_silence_minutes;
+        if minutes == 0 || audio.current_track().is_none() || audio.is_paused() {
+            self.silent_since = None;
+            return false;
+        }
+        let Some(level) = audio.output_level() else {
+            self.silent_since = None;
+            return false;
+        };
+        if level > SILENCE_LEVEL_THRESHOLD {
+            self.silent_since = None;
+            return false;
+        }
+
+        let silent_since = *self.silent_since.get_or_insert(now);
+        if now.saturating_duration_since(silent_since)
+            < Duration::from_secs(u64::from(minutes) * 60)
+        {
+            return false;
+        }
+
+        audio.pause();
+        self.silent_since = None;
+        core.status = format!("Paused after {minutes} min of silence");
+        core.dirty = true;
+        true
+    }
+}
+
+/// Sleep timer: once `core.sleep_deadline` passes, fades the output down over
+/// `SLEEP_FADE` with the engine's fade-out gain and pauses. The user's volume is
+/// never changed, so a save mid-fade keeps the usual level. Cancelling or
+/// pausing mid-fade restores full gain at once.
+#[derive(Debug, Default)]
+pub struct SleepTimer {
+    /// When the fade began.
+    fade: Option<Instant>,
+}
+
+impl SleepTimer {
+    fn restore_volume(&mut self, audio: &mut dyn AudioEngine) {
+        if self.fade.take().is_some() {
+            audio.set_fade_out_gain(1.0);
+        }
+    }
+
+    /// Steps the fade once. Returns `true` when playback was paused.
+    pub fn tick(&mut self, core: &mut TuneCore, audio: &mut dyn AudioEngine, now: Instant) -> bool {
+        if core.sleep_deadline.is_none_or(|deadline| now < deadline) {
+            self.restore_volume(audio);
+            return false;
+        }
+        if audio.current_track().is_none() || audio.is_paused() {
+            self.restore_volume(audio);
+            core.sleep_deadline = None;
+            core.status = String::from("Sleep timer ended");
+            core.dirty = true;
+            return false;
+        }
+
+        let started = *self.fade.get_or_insert(now);
+        let progress =
+            now.saturating_duration_since(started).as_secs_f32() / SLEEP_FADE.as_secs_f32();
+        if progress < 1.0 {
+            audio.set_fade_out_gain(1.0 - progress);
+            return false;
+        }
+
+        audio.pause();
+        self.restore_volume(audio);
+        core.sleep_deadline = None;
+        core.status = String::from("Sleep timer: paused");
+        core.dirty = true;
+        true
+    }
+}
+
+/// Starts the next track when the current one ends, or queues a crossfade (or a
+/// gapless hand-over) into it once playback is close enough to the end. Does
+/// nothing while paused.
+pub fn advance_local_queue(
+    core: &mut TuneCore,
+    audio: &mut dyn AudioEngine,
+    history: &[stats::ListenEvent],
+) {
+    if audio.current_track().is_none() || audio.is_paused() {
+        return;
+    }
+
+    if let Some(once) = core.play_once.as_deref() {
+        if audio.current_track() != Some(once) {
+            core.play_once = None;
+        } else {
+            if audio.is_finished() {
+                audio.stop();
+                core.play_once = None;
+                core.status = String::from("Played once; stopped");
+                core.dirty = true;
+            }
+            return;
+        }
+    }
+
+    if core.sleep_after_current_track {
+        if audio.is_finished() {
+            audio.stop();
+            core.sleep_after_current_track = false;
+            core.status = String::from("Sleep timer: stopped at the end of the track");
+            core.dirty = true;
+        }
+        return;
+    }
+
+    let crossfade_triggered = should_trigger_crossfade_advance(audio)
+        && crossfade_allowed_for_next_transition(core, audio, history);
+    let gapless_triggered = !crossfade_triggered
+        && core.track_gap_seconds == 0
+        && should_trigger_gapless_advance(audio);
+    if (crossfade_triggered || gapless_triggered) && audio.crossfade_queued_track().is_some() {
+        return;
+    }
+
+    if !audio.is_finished() && !crossfade_triggered && !gapless_triggered {
+        core.pending_advance_at = None;
+        return;
+    }
+
+    if !crossfade_triggered && core.track_gap_seconds > 0 {
+        let now = Instant::now();
+        let resume_at = *core
+            .pending_advance_at
+            .get_or_insert_with(|| now + Duration::from_secs(u64::from(core.track_gap_seconds)));
+        if now < resume_at {
+            return;
+        }
+        core.pending_advance_at = None;
+    }
+
+    if let Some(path) = core
+        .next_track_path()
+        .or_else(|| core.end_of_queue_track_path(history))
+    {
+        let result = if crossfade_triggered {
+            audio.queue_crossfade(&path)
+        } else if gapless_triggered {
+            audio.queue_gapless(&path)
+        } else {
+            audio.play(&path)
+        };
+        if let Err(err) = result {
+            core.status = concise_audio_error(&err);
+            core.dirty = true;
+        }
+    } else if audio.is_finished() {
+        audio.stop();
+        core.status = String::from("Reached end of queue");
+        core.dirty = true;
+    }
+}
+
+/// With crossfade at 0 this never fires and gapless playback, when enabled,
+/// handles the transition instead.
+pub fn should_trigger_crossfade_advance(audio: &dyn AudioEngine) -> bool {
+    let crossfade_seconds = audio.crossfade_seconds();
+    // A loop ending near the outro would otherwise start fading into the next
+    // track on every pass.
+    if crossfade_seconds == 0 || audio.ab_loop().is_some() {
+        return false;
+    }
+
+    let Some(position) = audio.position() else {
+        return false;
+    };
+    let Some(duration) = audio.duration() else {
+        return false;
+    };
+    if duration <= position {
+        return false;
+    }
+
+    let remaining = duration.saturating_sub(position);
+    remaining <= Duration::from_secs(u64::from(crossfade_seconds)) + audio.crossfade_lead()
+}
+
+/// True in the last `GAPLESS_QUEUE_LEAD` of a track when gapless playback is on.
+/// Callers only use it when no crossfade was triggered for the transition.
+pub fn should_trigger_gapless_advance(audio: &dyn AudioEngine) -> bool {
+    if !audio.gapless() || audio.ab_loop().is_some() {
+        return false;
+    }
+    let Some((position, duration)) = audio.position().zip(audio.duration()) else {
+        return false;
+    };
+    position < duration && duration.saturating_sub(position) <= GAPLESS_QUEUE_LEAD
+}
+
+/// Flagged tracks always get a hard transition, whether they are outgoing or incoming.
+/// The incoming track may be an end-of-queue pick, so `history` is what Radio
+/// would choose from. With `crossfade_only_when_shuffling`, in-order playback
+/// is never crossfaded.
+pub fn crossfade_allowed_for_next_transition(
+    core: &TuneCore,
+    audio: &dyn AudioEngine,
+    history: &[stats::ListenEvent],
+) -> bool {
+    if core.crossfade_only_when_shuffling && !core.shuffle_enabled {
+        return false;
+    }
+    if core.no_crossfade_tracks.is_empty() {
+        return true;
+    }
+    if audio
+        .current_track()
+        .is_some_and(|path| core.track_crossfade_disabled(path))
+    {
+        return false;
+    }
+    !core
+        .peek_next_track_path(history)
+        .is_some_and(|path| core.track_crossfade_disabled(&path))
+}
+
+pub fn concise_audio_error(err: &anyhow::Error) -> String {
+    let message = err.to_string();
+    let lower = message.to_ascii_lowercase();
+    if lower.contains("device") && (lower.contains("no longer") || lower.contains("unavailable")) {
+        return String::from("Audio device unavailable. Use / -> Audio driver settings -> Reload");
+    }
+    if let Some(path) = message.strip_prefix("failed to decode ") {
+        let path = Path::new(path);
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        return format!(
+            "Can't decode {name}: {} audio is unsupported or the file is damaged",
+            container_label(path)
+        );
+    }
+    format!("Playback failed: {message}")
+}
+
+/// Human name of the audio container for a file extension.
+fn container_label(path: &Path) -> String {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "opus" => String::from("Opus"),
+        "wv" => String::from("WavPack"),
+        "ogg" | "oga" => String::from("Ogg"),
+        "m4a" | "mp4" => String::from("MP4/AAC"),
+        "" => String::from("This"),
+        other => other.to_ascii_uppercase(),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ActiveListenSession {
+    pub(crate) playback_path: PathBuf,
+    pub(crate) track_path: PathBuf,
+    pub(crate) title: String,
+    pub(crate) artist: Option<String>,
+    pub(crate) album: Option<String>,
+    pub(crate) provider_track_id: Option<String>,
+    pub(crate) started_at_epoch_seconds: i64,
+    pub(crate) playing_started_at: Option<Instant>,
+    /// Wall-clock time spent playing, so a sped-up track counts the seconds
+    /// actually listened rather than the track time covered.
+    pub(crate) listened: Duration,
+    pub(crate) persisted_listened_seconds: u32,
+    pub(crate) play_count_recorded: bool,
+    pub(crate) pending_same_track_restart: bool,
+    /// The track queued to follow this one as of the last tick. Handing over to
+    /// it means this one played out, even without the engine reporting an end.
+    pub(crate) queued_next_track: Option<PathBuf>,
+    pub(crate) last_position: Option<Duration>,
+    pub(crate) duration: Option<Duration>,
+}
+
+/// Turns engine playback into listen events for a `StatsStore`, flushing long
+/// sessions in slices so a crash loses at most a few seconds.
+#[derive(Debug, Default)]
+pub struct ListenTracker {
+    pub(crate) active: Option<ActiveListenSession>,
+    pub(crate) pending: Option<PendingListen>,
+    /// Copied from `core.stats_completion_percent` on each tick.
+    pub(crate) completion_percent: u8,
+    /// Receives each counted play when scrobbling is set up.
+    pub scrobble_queue: Option<ScrobbleQueue>,
+}
+
+/// A track still inside `core.stats_grace_seconds`, not yet a session.
+#[derive(Debug)]
+pub(crate) struct PendingListen {
+    playback_path: PathBuf,
+    started_at_epoch_seconds: i64,
+    played: Duration,
+    playing_since: Option<Instant>,
+}
+
+/// Overrides the identity recorded for a listen, used when the playing file is a
+/// temporary stream of some other logical track.
+#[derive(Debug, Clone)]
+pub struct StatsIdentityHint {
+    pub logical_path: PathBuf,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub provider_track_id: Option<String>,
+}
+
+impl ListenTracker {
+    pub fn reset(&mut self) {
+        self.active = None;
+        self.pending = None;
+    }
+
+    /// Drops the session in progress when its track's history is forgotten, so
+    /// the rest of that listen is not written back under the track. Call before
+    /// `StatsStore::forget_track`, which drops the provider mapping.
+    pub fn forget_track(&mut self, stats: &StatsStore, track_key: &str) {
+        let forgotten = self.active.as_ref().is_some_and(|active| {
+            stats.resolve_track_key(
+                &active.title,
+                active.artist.as_deref(),
+                &active.track_path,
+                active.provider_track_id.as_deref(),
+            ) == track_key
+        });
+        if forgotten {
+            self.reset();
+        }
+    }
+
+    /// Samples the engine once. Returns `true` when `stats` changed and should be saved.
+    pub fn tick(
+        &mut self,
+        core: &TuneCore,
+        audio: &dyn AudioEngine,
+        stats: &mut StatsStore,
+        identity_hint: Option<&StatsIdentityHint>,
+    ) -> bool {
+        let mut wrote_event = false;
+        self.completion_percent = core.stats_completion_percent;
+        let current_track = audio.current_track().map(Path::to_path_buf);
+        let current_position = audio.position();
+        let crossfade_seconds = audio.crossfade_seconds();
+        let paused = audio.is_paused();
+
+        let finished = audio.is_finished();
+        let mut force_completed = finished;
+        let should_finalize = self.active.as_ref().is_some_and(|active| {
+            let track_changed = current_track.as_ref() != Some(&active.playback_path);
+            if track_changed && current_track.is_some() && current_track == active.queued_next_track
+            {
+                force_completed = true;
+            }
+            let restarted_same_track = !track_changed
+                && !finished
+                && same_track_restarted(active, current_position, paused, crossfade_seconds);
+            if restarted_same_track {
+                force_completed = true;
+            }
+            track_changed || finished || restarted_same_track
+        });
+        if should_finalize {
+            wrote_event = self.finalize_active(stats, force_completed) || wrote_event;
+        }
+
+        if current_track.is_none() || finished {
+            self.pending = None;
+            return wrote_event;
+        }
+
+        if self.active.is_none() {
+            let path = current_track.expect("checked some");
+            let grace = Duration::from_secs(u64::from(core.stats_grace_seconds));
+            let Some((started_at_epoch_seconds, already_listened)) =
+                self.grace_period_elapsed(&path, paused, grace)
+            else {
+                return wrote_event;
+            };
+            let (logical_path, provider_track_id, hint_title, hint_artist, hint_album) =
+                if let Some(hint) = identity_hint {
+                    (
+                        hint.logical_path.clone(),
+                        hint.provider_track_id.clone(),
+                        hint.title.clone(),
+                        hint.artist.clone(),
+                        hint.album.clone(),
+                    )
+                } else {
+                    (path.clone(), None, None, None, None)
+                };
+            let now = Instant::now();
+            self.active = Some(ActiveListenSession {
+                title: hint_title
+                    .or_else(|| core.title_for_path(&logical_path))
+                    .unwrap_or_else(|| {
+                        logical_path
+                            .file_stem()
+                            .and_then(|name| name.to_str())
+                            .unwrap_or("-")
+                            .to_string()
+                    }),
+                artist: hint_artist
+                    .or_else(|| core.artist_for_path(&logical_path).map(ToOwned::to_owned)),
+                album: hint_album
+                    .or_else(|| core.album_for_path(&logical_path).map(ToOwned::to_owned)),
+                playback_path: path,
+                track_path: logical_path,
+                provider_track_id,
+                started_at_epoch_seconds,
+                playing_started_at: (!paused).then_some(now),
+                listened: already_listened,
+                persisted_listened_seconds: 0,
+                play_count_recorded: false,
+                pending_same_track_restart: false,
+                queued_next_track: None,
+                last_position: current_position,
+                duration: audio.duration(),
+            });
+            return wrote_event;
+        }
+
+        if let Some(active) = self.active.as_mut() {
+            let queued_same_track = audio
+                .crossfade_queued_track()
+                .is_some_and(|queued| queued == active.playback_path.as_path());
+            active.pending_same_track_restart |= queued_same_track;
+            active.queued_next_track = audio.crossfade_queued_track().map(Path::to_path_buf);
+            active.last_position = current_position.or(active.last_position);
+            active.duration = audio.duration().or(active.duration);
+            if paused {
+                if let Some(started) = active.playing_started_at.take() {
+                    active.listened = active.listened.saturating_add(started.elapsed());
+                }
+            } else if active.playing_started_at.is_none() {
+                active.playing_started_at = Some(Instant::now());
+            }
+        }
+
+        wrote_event = self.flush_partial(stats) || wrote_event;
+
+        wrote_event
+    }
+
+    /// Holds off a new session until `grace` of playing time has passed on the
+    /// same track. Returns when the listen started and how much was already
+    /// played, so the grace period still counts once the session opens.
+    fn grace_period_elapsed(
+        &mut self,
+        path: &Path,
+        paused: bool,
+        grace: Duration,
+    ) -> Option<(i64, Duration)> {
+        if grace.is_zero() {
+            self.pending = None;
+            return Some((stats::now_epoch_seconds(), Duration::ZERO));
+        }
+        let now = Instant::now();
+        if self
+            .pending
+            .as_ref()
+            .is_none_or(|pending| pending.playback_path != path)
+        {
+            self.pending = Some(PendingListen {
+                playback_path: path.to_path_buf(),
+                started_at_epoch_seconds: stats::now_epoch_seconds(),
+                played: Duration::ZERO,
+                playing_since: None,
+            });
+        }
+        let pending = self.pending.as_mut()?;
+        if let Some(since) = pending.playing_since.take() {
+            pending.played = pending
+                .played
+                .saturating_add(now.saturating_duration_since(since));
+        }
+        if !paused {
+            pending.playing_since = Some(now);
+        }
+        if pending.played < grace {
+            return None;
+        }
+        let pending = self.pending.take()?;
+        Some((pending.started_at_epoch_seconds, pending.played))
+    }
+
+    /// Records whatever is left of the active session, e.g. before stopping playback.
+    pub fn finalize_active(&mut self, stats: &mut StatsStore, force_completed: bool) -> bool {
+        let mut active = match self.active.take() {
+            Some(active) => active,
+            None => return false,
+        };
+
+        if let Some(started) = active.playing_started_at.take() {
+            active.listened = active.listened.saturating_add(started.elapsed());
+        }
+
+        let total_listened_seconds = duration_to_recorded_seconds(active.listened);
+        let listened_seconds =
+            total_listened_seconds.saturating_sub(active.persisted_listened_seconds);
+        let completed = force_completed
+            || active
+                .duration
+                .zip(active.last_position)
+                .is_some_and(|(duration, position)| {
+                    reached_completion(position, duration, self.completion_percent)
+                });
+        let counted_play = crate::stats::should_count_as_play(
+            total_listened_seconds,
+            completed,
+            active.duration.map(|duration| duration.as_secs() as u32),
+        ) && !active.play_count_recorded;
+        let allow_short_listen = active.persisted_listened_seconds > 0 || counted_play;
+        // Scrobble once the listen ends, whether the play was counted now or
+        // by an earlier partial flush.
+        if (counted_play || active.play_count_recorded)
+            && let Some(queue) = self.scrobble_queue.as_ref()
+            && let Some(artist) = active.artist.clone()
+        {
+            queue.push(Scrobble {
+                artist,
+                title: active.title.clone(),
+                album: active.album.clone(),
+                duration_seconds: active.duration.map(|duration| duration.as_secs() as u32),
+                listened_at_epoch_seconds: active.started_at_epoch_seconds,
+            });
+        }
+
+        stats.record_listen(ListenSessionRecord {
+            track_path: active.track_path,
+            title: active.title,
+            artist: active.artist,
+            album: active.album,
+            provider_track_id: active.provider_track_id,
+            started_at_epoch_seconds: active.started_at_epoch_seconds,
+            listened_seconds,
+            completed,
+            duration_seconds: active.duration.map(|duration| duration.as_secs() as u32),
+            counted_play_override: Some(counted_play),
+            allow_short_listen,
+        });
+        listened_seconds >= PARTIAL_LISTEN_FLUSH_SECONDS || counted_play || allow_short_listen
+    }
+
+    fn flush_partial(&mut self, stats: &mut StatsStore) -> bool {
+        let Some(active) = self.active.as_mut() else {
+            return false;
+        };
+
+        let mut listened = active.listened;
+        if let Some(started) = active.playing_started_at {
+            listened = listened.saturating_add(started.elapsed());
+        }
+
+        let total_seconds = duration_to_recorded_seconds(listened);
+        let delta = total_seconds.saturating_sub(active.persisted_listened_seconds);
+        let should_record_play = crate::stats::should_count_as_play(
+            total_seconds,
+            false,
+            active.duration.map(|duration| duration.as_secs() as u32),
+        ) && !active.play_count_recorded;
+
+        if delta < PARTIAL_LISTEN_FLUSH_SECONDS && !should_record_play {
+            return false;
+        }
+
+        stats.record_listen(ListenSessionRecord {
+            track_path: active.track_path.clone(),
+            title: active.title.clone(),
+            artist: active.artist.clone(),
+            album: active.album.clone(),
+            provider_track_id: active.provider_track_id.clone(),
+            started_at_epoch_seconds: active.started_at_epoch_seconds,
+            listened_seconds: delta,
+            completed: false,
+            duration_seconds: active.duration.map(|duration| duration.as_secs() as u32),
+            counted_play_override: Some(should_record_play),
+            allow_short_listen: false,
+        });
+        active.persisted_listened_seconds = active.persisted_listened_seconds.saturating_add(delta);
+        if should_record_play {
+            active.play_count_recorded = true;
+        }
+        true
+    }
+}
+
+/// Either the last second of the track or, when set, `percent` of its duration.
+fn reached_completion(position: Duration, duration: Duration, percent: u8) -> bool {
+    position >= duration
+        || duration.saturating_sub(position) <= Duration::from_secs(1)
+        || (percent > 0 && position >= duration.mul_f64(f64::from(percent) / 100.0))
+}
+
+fn same_track_restarted(
+    active: &ActiveListenSession,
+    current_position: Option<Duration>,
+    paused: bool,
+    crossfade_seconds: u16,
+) -> bool {
+    if paused || !active.pending_same_track_restart {
+        return false;
+    }
+
+    let Some(current) = current_position else {
+        return false;
+    };
+    let Some(previous) = active.last_position else {
+        return false;
+    };
+    if current >= previous {
+        return false;
+    }
+
+    let start_window = Duration::from_secs(
+        LOOP_RESTART_START_WINDOW_SECONDS.max(u64::from(crossfade_seconds).saturating_add(2)),
+    );
+    let was_near_end = active.duration.is_some_and(|duration| {
+        let end_window = Duration::from_secs(LOOP_RESTART_END_WINDOW_SECONDS);
+        previous >= duration.saturating_sub(end_window)
+    }) || previous
+        >= Duration::from_secs(LOOP_RESTART_FALLBACK_MIN_PREVIOUS_SECONDS);
+    let now_near_start = current <= start_window;
+    was_near_end && now_near_start
+}
+
+fn duration_to_recorded_seconds(duration: Duration) -> u32 {
+    if duration.is_zero() {
+        return 0;
+    }
+    let secs = duration.as_secs();
+    let has_subsec = duration.subsec_nanos() > 0;
+    let rounded = if has_subsec {
+        secs.saturating_add(1)
+    } else {
+        secs
+    };
+    rounded.min(u64::from(u32::MAX)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::NullAudioEngine;
+    use crate::model::{PersistedState, Track};
+
+    #[test]
+    fn player_walks_the_queue_without_a_terminal() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.tracks = ["a.mp3", "b.mp3"]
+            .into_iter()
+            .map(|path| Track {
+                path: PathBuf::from(path),
+                title: path.to_string(),
+                artist: None,
+                album: None,
+            })
+            .collect();
+        core.queue = vec![0, 1];
+        let mut player = Player::new(
+            core,
+            Box::new(NullAudioEngine::new()),
+            StatsStore::default(),
+        );
+
+        assert!(player.next_track().expect("next"));
+        let first = player.audio.current_track().map(Path::to_path_buf);
+        assert!(player.next_track().expect("next"));
+        let second = player.audio.current_track().map(Path::to_path_buf);
+        assert_ne!(first, second);
+        player.tick();
+
+        player.stop();
+        assert!(player.audio.current_track().is_none());
+    }
+
+    #[test]
+    fn player_reports_playback_events_to_subscribers() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut player = Player::new(
+            TuneCore::from_persisted(PersistedState::default()),
+            Box::new(NullAudioEngine::new()),
+            StatsStore::default(),
+        );
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&events);
+        player.subscribe(move |event| sink.borrow_mut().push(event.clone()));
+
+        player.play(Path::new("a.mp3")).expect("play");
+        player.pause();
+        player.pause();
+        player.resume();
+        player.seek_to(Duration::from_secs(30)).expect("seek");
+        player.set_volume(0.5);
+        player.tick();
+        player
+            .audio
+            .play(Path::new("b.mp3"))
+            .expect("engine advance");
+        player.tick();
+        player.stop();
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                PlaybackEvent::TrackStarted(PathBuf::from("a.mp3")),
+                PlaybackEvent::Paused,
+                PlaybackEvent::Resumed,
+                PlaybackEvent::Seeked(Duration::from_secs(30)),
+                PlaybackEvent::VolumeChanged(0.5),
+                PlaybackEvent::Finished(PathBuf::from("a.mp3")),
+                PlaybackEvent::TrackStarted(PathBuf::from("b.mp3")),
+                PlaybackEvent::Stopped,
+            ]
+        );
+    }
+
+    #[test]
+    fn listen_session_waits_for_stats_grace_period() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        core.stats_grace_seconds = 5;
+        let mut audio = NullAudioEngine::new();
+        audio.play(Path::new("a.mp3")).expect("play");
+        let mut stats = StatsStore::default();
+        let mut tracker = ListenTracker::default();
+
+        tracker.tick(&core, &audio, &mut stats, None);
+        assert!(tracker.active.is_none());
+        tracker.pending.as_mut().expect("pending listen").played = Duration::from_secs(5);
+
+        tracker.tick(&core, &audio, &mut stats, None);
+        let active = tracker.active.as_ref().expect("session after grace");
+        assert!(active.listened >= Duration::from_secs(5));
+        assert!(tracker.pending.is_none());
+
+        audio.play(Path::new("b.mp3")).expect("play");
+        tracker.tick(&core, &audio, &mut stats, None);
+        assert!(tracker.active.is_none());
+        assert_eq!(
+            tracker
+                .pending
+                .as_ref()
+                .map(|pending| pending.playback_path.clone()),
+            Some(PathBuf::from("b.mp3"))
+        );
+    }
+
+    #[test]
+    fn listen_time_counts_wall_clock_seconds_at_faster_speeds() {
+        let core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = NullAudioEngine::new();
+        audio.set_playback_speed(3.0);
+        audio.play(Path::new("a.mp3")).expect("play");
+        let mut stats = StatsStore::default();
+        let mut tracker = ListenTracker::default();
+
+        tracker.tick(&core, &audio, &mut stats, None);
+        std::thread::sleep(Duration::from_millis(60));
+        audio.pause();
+        tracker.tick(&core, &audio, &mut stats, None);
+
+        let active = tracker.active.as_ref().expect("listen session");
+        let track_time = audio.position().expect("position");
+        assert!(active.listened >= Duration::from_millis(60));
+        assert!(
+            active.listened * 2 < track_time,
+            "listened {:?} should be wall-clock time, track moved {track_time:?}",
+            active.listened
+        );
+    }
+
+    #[test]
+    fn decode_failures_name_the_container_instead_of_a_generic_error() {
+        let err =
+            anyhow::anyhow!("invalid data").context("failed to decode /music/Live/01 Intro.wv");
+        assert_eq!(
+            concise_audio_error(&err),
+            "Can't decode 01 Intro.wv: WavPack audio is unsupported or the file is damaged"
+        );
+
+        let err = anyhow::anyhow!("not found").context("failed to open track /music/a.opus");
+        assert_eq!(
+            concise_audio_error(&err),
+            "Playback failed: failed to open track /music/a.opus"
+        );
+    }
+
+    #[test]
+    fn completion_percent_counts_skipped_outros_as_completed() {
+        let duration = Duration::from_secs(300);
+        let outro = Duration::from_secs(290);
+
+        assert!(!reached_completion(outro, duration, 0));
+        assert!(reached_completion(
+            Duration::from_millis(299_500),
+            duration,
+            0
+        ));
+        assert!(reached_completion(Duration::from_secs(285), duration, 95));
+        assert!(!reached_completion(Duration::from_secs(280), duration, 95));
+    }
+
+    #[test]
+    fn crossfade_only_when_shuffling_blocks_in_order_transitions() {
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let mut audio = NullAudioEngine::new();
+        audio.play(Path::new("a.mp3")).expect("play");
+
+        assert!(crossfade_allowed_for_next_transition(&core, &audio, &[]));
+        core.toggle_crossfade_only_when_shuffling();
+        assert!(!crossfade_allowed_for_next_transition(&core, &audio, &[]));
+        core.set_shuffle_enabled(true);
+        assert!(crossfade_allowed_for_next_transition(&core, &audio, &[]));
+        core.toggle_crossfade_only_when_shuffling();
+        core.set_shuffle_enabled(false);
+        assert!(crossfade_allowed_for_next_transition(&core, &audio, &[]));
+    }
+}
@@ -0,0 +1,417 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const LISTENBRAINZ_SUBMIT_URL: &str = "https://api.listenbrainz.org/1/submit-listens";
+/// Keeps each request small; a long offline backlog goes up in several.
+const MAX_SCROBBLES_PER_REQUEST: usize = 100;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(30);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(15 * 60);
+
+/// One counted play, ready to submit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scrobble {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    pub duration_seconds: Option<u32>,
+    pub listened_at_epoch_seconds: i64,
+}
+
+#[derive(Debug)]
+pub enum ScrobbleError {
+    /// Network trouble or a server-side failure; the batch is retried later.
+    Temporary(anyhow::Error),
+    /// The service refused the batch itself, so retrying cannot help.
+    Rejected(anyhow::Error),
+    /// The token was refused. Nothing more is sent until it is fixed and the
+    /// app restarts; queued scrobbles are kept for then.
+    Unauthorized(anyhow::Error),
+}
+
+/// A scrobbling service. Submissions run on the queue's background thread.
+pub trait Scrobbler: Send {
+    fn submit(&self, scrobbles: &[Scrobble]) -> Result<(), ScrobbleError>;
+}
+
+/// ListenBrainz with a user token from the account settings page.
+pub struct ListenBrainz {
+    agent: ureq::Agent,
+    token: String,
+}
+
+impl ListenBrainz {
+    pub fn new(token: &str) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .build()
+            .into();
+        Self {
+            agent,
+            token: token.trim().to_string(),
+        }
+    }
+}
+
+impl Scrobbler for ListenBrainz {
+    fn submit(&self, scrobbles: &[Scrobble]) -> Result<(), ScrobbleError> {
+        let result = self
+            .agent
+            .post(LISTENBRAINZ_SUBMIT_URL)
+            .header("Authorization", &format!("Token {}", self.token))
+            .send_json(listenbrainz_payload(scrobbles));
+        match result {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::StatusCode(400)) => Err(ScrobbleError::Rejected(anyhow::anyhow!(
+                "ListenBrainz rejected {} listens",
+                scrobbles.len()
+            ))),
+            Err(ureq::Error::StatusCode(code @ (401 | 403))) => Err(ScrobbleError::Unauthorized(
+                anyhow::anyhow!("ListenBrainz refused the token (HTTP {code})"),
+            )),
+            Err(err) => Err(ScrobbleError::Temporary(
+                anyhow::Error::new(err).context("ListenBrainz submission failed"),
+            )),
+        }
+    }
+}
+
+fn listenbrainz_payload(scrobbles: &[Scrobble]) -> serde_json::Value {
+    let listens: Vec<serde_json::Value> = scrobbles
+        .iter()
+        .map(|scrobble| {
+            let mut metadata = json!({
+                "artist_name": scrobble.artist,
+                "track_name": scrobble.title,
+            });
+            if let Some(album) = scrobble.album.as_deref() {
+                metadata["release_name"] = json!(album);
+            }
+            if let Some(seconds) = scrobble.duration_seconds {
+                metadata["additional_info"] = json!({ "duration_ms": u64::from(seconds) * 1_000 });
+            }
+            json!({
+                "listened_at": scrobble.listened_at_epoch_seconds,
+                "track_metadata": metadata,
+            })
+        })
+        .collect();
+    json!({
+        "listen_type": if listens.len() == 1 { "single" } else { "import" },
+        "payload": listens,
+    })
+}
+
+/// Hands scrobbles to a background thread that submits them, retrying with
+/// backoff while offline. Unsent scrobbles are written to `queue_path` as soon
+/// as they are pushed, so a play finished right before exit survives a restart.
+#[derive(Debug, Clone)]
+pub struct ScrobbleQueue {
+    pending: Arc<PendingScrobbles>,
+    wake: Sender<()>,
+}
+
+/// Unsent scrobbles shared by the app and the worker. Every change is saved to
+/// `queue_path` before the lock is released.
+#[derive(Debug, Default)]
+struct PendingScrobbles {
+    scrobbles: Mutex<Vec<Scrobble>>,
+    queue_path: Option<PathBuf>,
+    auth_error: Mutex<Option<String>>,
+}
+
+impl PendingScrobbles {
+    fn lock(&self) -> MutexGuard<'_, Vec<Scrobble>> {
+        self.scrobbles
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn save(&self, scrobbles: &[Scrobble]) {
+        if let Some(path) = self.queue_path.as_deref() {
+            let _ = save_pending_scrobbles(path, scrobbles);
+        }
+    }
+}
+
+/// How a round of submissions ended.
+#[derive(Debug, PartialEq, Eq)]
+enum SubmitOutcome {
+    Sent,
+    RetryLater,
+    Unauthorized(String),
+}
+
+impl ScrobbleQueue {
+    pub fn start(scrobbler: Box<dyn Scrobbler>, queue_path: PathBuf) -> Self {
+        let scrobbles = load_pending_scrobbles(&queue_path).unwrap_or_default();
+        let pending = Arc::new(PendingScrobbles {
+            scrobbles: Mutex::new(scrobbles),
+            queue_path: Some(queue_path),
+            auth_error: Mutex::new(None),
+        });
+        let (wake, receiver) = mpsc::channel();
+        let worker_pending = Arc::clone(&pending);
+        thread::spawn(move || run_scrobble_worker(scrobbler.as_ref(), &worker_pending, &receiver));
+        Self { pending, wake }
+    }
+
+    pub fn push(&self, scrobble: Scrobble) {
+        {
+            let mut scrobbles = self.pending.lock();
+            scrobbles.push(scrobble);
+            self.pending.save(&scrobbles);
+        }
+        let _ = self.wake.send(());
+    }
+
+    /// Why the service refused the token, reported once.
+    pub fn take_auth_error(&self) -> Option<String> {
+        self.pending
+            .auth_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+
+    /// A queue without a worker or queue file.
+    #[cfg(test)]
+    pub(crate) fn detached() -> Self {
+        let (wake, _) = mpsc::channel();
+        Self {
+            pending: Arc::default(),
+            wake,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn pending(&self) -> Vec<Scrobble> {
+        self.pending.lock().clone()
+    }
+}
+
+fn run_scrobble_worker(scrobbler: &dyn Scrobbler, pending: &PendingScrobbles, wake: &Receiver<()>) {
+    let mut retry_delay = FIRST_RETRY_DELAY;
+    let mut next_attempt = Instant::now();
+    let mut unauthorized = false;
+    loop {
+        if !unauthorized && !pending.lock().is_empty() && Instant::now() >= next_attempt {
+            match submit_pending(scrobbler, pending) {
+                SubmitOutcome::Sent => retry_delay = FIRST_RETRY_DELAY,
+                SubmitOutcome::RetryLater => {
+                    next_attempt = Instant::now() + retry_delay;
+                    retry_delay = retry_delay.saturating_mul(2).min(MAX_RETRY_DELAY);
+                }
+                SubmitOutcome::Unauthorized(message) => {
+                    unauthorized = true;
+                    *pending
+                        .auth_error
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner) = Some(message);
+                }
+            }
+        }
+
+        let received = if unauthorized || pending.lock().is_empty() {
+            wake.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            wake.recv_timeout(next_attempt.saturating_duration_since(Instant::now()))
+        };
+        if received == Err(RecvTimeoutError::Disconnected) {
+            return;
+        }
+    }
+}
+
+/// Submits the oldest scrobbles first, in batches, without holding the lock
+/// during the request. A rejected batch is halved until the listen the server
+/// refuses is alone, so only that one is dropped; on any other failure the
+/// rest stay queued.
+fn submit_pending(scrobbler: &dyn Scrobbler, pending: &PendingScrobbles) -> SubmitOutcome {
+    let mut batch_limit = MAX_SCROBBLES_PER_REQUEST;
+    loop {
+        let batch: Vec<Scrobble> = {
+            let scrobbles = pending.lock();
+            let len = scrobbles.len().min(batch_limit);
+            scrobbles[..len].to_vec()
+        };
+        if batch.is_empty() {
+            return SubmitOutcome::Sent;
+        }
+        match scrobbler.submit(&batch) {
+            Err(ScrobbleError::Rejected(_)) if batch.len() > 1 => {
+                batch_limit = batch.len() / 2;
+            }
+            result @ (Ok(()) | Err(ScrobbleError::Rejected(_))) => {
+                if result.is_err() {
+                    batch_limit = MAX_SCROBBLES_PER_REQUEST;
+                }
+                // New scrobbles are only ever appended, so the batch is still
+                // at the front.
+                let mut scrobbles = pending.lock();
+                scrobbles.drain(..batch.len());
+                pending.save(&scrobbles);
+            }
+            Err(ScrobbleError::Temporary(_)) => return SubmitOutcome::RetryLater,
+            Err(ScrobbleError::Unauthorized(err)) => {
+                return SubmitOutcome::Unauthorized(format!("{err:#}"));
+            }
+        }
+    }
+}
+
+fn load_pending_scrobbles(path: &Path) -> Result<Vec<Scrobble>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+fn save_pending_scrobbles(path: &Path, pending: &[Scrobble]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(pending)?;
+    fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn scrobble(title: &str) -> Scrobble {
+        Scrobble {
+            artist: String::from("Band"),
+            title: title.to_string(),
+            album: None,
+            duration_seconds: None,
+            listened_at_epoch_seconds: 1_700_000_000,
+        }
+    }
+
+    /// Fails a batch starting with a `temporary_failures` title once, rejects
+    /// batches holding a `rejected` title, refuses the token for batches
+    /// starting with `Locked`, and records the rest.
+    struct FakeScrobbler {
+        temporary_failures: RefCell<Vec<&'static str>>,
+        rejected: &'static str,
+        submitted: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl Scrobbler for FakeScrobbler {
+        fn submit(&self, scrobbles: &[Scrobble]) -> Result<(), ScrobbleError> {
+            let first = scrobbles[0].title.as_str();
+            let mut failures = self.temporary_failures.borrow_mut();
+            if let Some(index) = failures.iter().position(|title| *title == first) {
+                failures.remove(index);
+                return Err(ScrobbleError::Temporary(anyhow::anyhow!("offline")));
+            }
+            if scrobbles.iter().any(|s| s.title == self.rejected) {
+                return Err(ScrobbleError::Rejected(anyhow::anyhow!("bad request")));
+            }
+            if first == "Locked" {
+                return Err(ScrobbleError::Unauthorized(anyhow::anyhow!("bad token")));
+            }
+            self.submitted
+                .borrow_mut()
+                .push(scrobbles.iter().map(|s| s.title.clone()).collect());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn listenbrainz_payload_includes_album_and_duration_when_known() {
+        let mut first = scrobble("One");
+        first.album = Some(String::from("Record"));
+        first.duration_seconds = Some(215);
+
+        let single = listenbrainz_payload(std::slice::from_ref(&first));
+        assert_eq!(single["listen_type"], "single");
+        let metadata = &single["payload"][0]["track_metadata"];
+        assert_eq!(metadata["artist_name"], "Band");
+        assert_eq!(metadata["track_name"], "One");
+        assert_eq!(metadata["release_name"], "Record");
+        assert_eq!(metadata["additional_info"]["duration_ms"], 215_000);
+        assert_eq!(single["payload"][0]["listened_at"], 1_700_000_000);
+
+        let batch = listenbrainz_payload(&[first, scrobble("Two")]);
+        assert_eq!(batch["listen_type"], "import");
+        assert!(batch["payload"][1]["track_metadata"]["release_name"].is_null());
+    }
+
+    #[test]
+    fn failed_submissions_stay_queued_and_rejected_ones_are_dropped() {
+        let scrobbler = FakeScrobbler {
+            temporary_failures: RefCell::new(vec!["One"]),
+            rejected: "Bad",
+            submitted: RefCell::new(Vec::new()),
+        };
+        let pending = PendingScrobbles::default();
+        *pending.lock() = vec![scrobble("One"), scrobble("Two")];
+
+        assert_eq!(
+            submit_pending(&scrobbler, &pending),
+            SubmitOutcome::RetryLater
+        );
+        assert_eq!(pending.lock().len(), 2);
+        assert_eq!(submit_pending(&scrobbler, &pending), SubmitOutcome::Sent);
+        assert!(pending.lock().is_empty());
+        assert_eq!(*scrobbler.submitted.borrow(), vec![vec!["One", "Two"]]);
+
+        *pending.lock() = (0..MAX_SCROBBLES_PER_REQUEST + 1)
+            .map(|n| scrobble(if n == 40 { "Bad" } else { "Fine" }))
+            .collect();
+        assert_eq!(submit_pending(&scrobbler, &pending), SubmitOutcome::Sent);
+        assert!(pending.lock().is_empty());
+        let sent: Vec<String> = scrobbler.submitted.borrow()[1..].concat();
+        assert_eq!(sent.len(), MAX_SCROBBLES_PER_REQUEST);
+        assert!(sent.iter().all(|title| title == "Fine"));
+
+        *pending.lock() = vec![scrobble("Locked")];
+        assert_eq!(
+            submit_pending(&scrobbler, &pending),
+            SubmitOutcome::Unauthorized(String::from("bad token"))
+        );
+        assert_eq!(pending.lock().len(), 1);
+    }
+
+    #[test]
+    fn pending_scrobbles_round_trip_through_the_queue_file() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("scrobble_queue.json");
+        assert!(
+            load_pending_scrobbles(&path)
+                .expect("missing file")
+                .is_empty()
+        );
+
+        let pending = vec![scrobble("One"), scrobble("Two")];
+        save_pending_scrobbles(&path, &pending).expect("save");
+        assert_eq!(load_pending_scrobbles(&path).expect("load"), pending);
+
+        // A push is on disk before any worker sees it.
+        let (wake, _) = mpsc::channel();
+        let queue = ScrobbleQueue {
+            pending: Arc::new(PendingScrobbles {
+                scrobbles: Mutex::new(pending),
+                queue_path: Some(path.clone()),
+                auth_error: Mutex::new(None),
+            }),
+            wake,
+        };
+        queue.push(scrobble("Three"));
+        assert_eq!(load_pending_scrobbles(&path).expect("load").len(), 3);
+    }
+}
@@ -3,16 +3,21 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::{SystemTime, UNIX_EPOCH};
+use time::UtcOffset;
 use unicode_normalization::UnicodeNormalization;
 
 const MAX_EVENTS: usize = 20_000;
 const MIN_TRACKED_LISTEN_SECONDS: u32 = 10;
 const MINUTE_TREND_END_ADVANCE_SECONDS: i64 = 20;
 const STATS_SCHEMA_VERSION: u32 = 3;
+/// Tracks started fewer times than this get no skip rate, so a single skip
+/// does not read as 100%.
+pub const MIN_STARTS_FOR_SKIP_RATE: u64 = 3;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum StatsRange {
@@ -46,6 +51,7 @@ impl StatsRange {
 pub enum StatsSort {
     Plays,
     ListenTime,
+    SkipRate,
 }
 
 impl StatsSort {
@@ -53,18 +59,20 @@ impl StatsSort {
         match self {
             Self::Plays => "plays",
             Self::ListenTime => "listen",
+            Self::SkipRate => "skip rate",
         }
     }
 
-    pub fn toggle(self) -> Self {
+    pub fn next(self) -> Self {
         match self {
-            Self::Plays => Self::ListenTime,
             Self::ListenTime => Self::Plays,
+            Self::Plays => Self::SkipRate,
+            Self::SkipRate => Self::ListenTime,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StatsQuery {
     pub range: StatsRange,
     pub sort: StatsSort,
@@ -111,6 +119,14 @@ pub struct ListenEvent {
     pub started_at_epoch_seconds: i64,
     pub listened_seconds: u32,
     pub counted_play: bool,
+    /// Whether the listen reached the end of the track. `None` for listens
+    /// recorded before this was kept.
+    #[serde(default)]
+    pub completed: Option<bool>,
+    /// Track length when the listen was recorded, so a different recording
+    /// saved over the same path is not mistaken for a duplicate.
+    #[serde(default)]
+    pub duration_seconds: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -119,6 +135,16 @@ pub struct TrackTotals {
     pub listen_seconds: u64,
 }
 
+/// One proposed merge of duplicate history found by `StatsStore::coalesce_preview`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsMerge {
+    pub from_key: String,
+    pub into_key: String,
+    pub title: String,
+    pub artist: Option<String>,
+    pub listens: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatsStore {
     #[serde(default = "default_stats_schema_version")]
@@ -164,6 +190,9 @@ struct StatsQueryCacheKey {
     album_filter: String,
     search: String,
     time_bucket: i64,
+    /// Streak and calendar totals move at local midnight even when the range
+    /// bucket does not.
+    local_day: i64,
 }
 
 impl StatsQueryCache {
@@ -200,23 +229,88 @@ fn time_bucket_for_range(range: StatsRange, now_epoch_seconds: i64) -> i64 {
 
 #[derive(Debug, Clone)]
 pub struct TrackStatsRow {
+    pub track_key: String,
     pub track_path: PathBuf,
     pub title: String,
     pub artist: Option<String>,
     pub album: Option<String>,
     pub play_count: u64,
     pub listen_seconds: u64,
+    pub last_played_epoch_seconds: i64,
+    /// Listens with a known outcome, and how many of them stopped before the
+    /// end.
+    pub starts: u64,
+    pub skips: u64,
+}
+
+impl TrackStatsRow {
+    /// Share of starts that were skipped, once there are enough starts.
+    pub fn skip_rate(&self) -> Option<f64> {
+        (self.starts >= MIN_STARTS_FOR_SKIP_RATE).then(|| self.skips as f64 / self.starts as f64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsExportFormat {
+    Csv,
+    Json,
+}
+
+impl StatsExportFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Json => "JSON",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// One line of an export: the aggregates of a Stats row, with the time of the
+/// latest listen as ISO-8601 UTC.
+#[derive(Debug, Serialize)]
+struct StatsExportRow {
+    title: String,
+    artist: Option<String>,
+    album: Option<String>,
+    path: String,
+    play_count: u64,
+    total_listen_seconds: u64,
+    last_played: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct StatsSnapshot {
     pub total_plays: u64,
     pub total_listen_seconds: u64,
+    /// Everything listened to in the Today window, ignoring the query filters.
+    pub today_listen_seconds: u64,
+    /// Local calendar days in the query with at least one counted play.
+    pub days_listened: u32,
+    pub streak: ListeningStreak,
     pub rows: Vec<TrackStatsRow>,
     pub recent: Vec<ListenEvent>,
     pub trend: TrendSeries,
 }
 
+/// Listening habits by local calendar day, ignoring the query filters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ListeningStreak {
+    /// Consecutive days with a counted play, ending today, or yesterday while
+    /// today has none yet.
+    pub current_days: u32,
+    pub longest_days: u32,
+    pub today_seconds: u64,
+    /// Since Monday.
+    pub week_seconds: u64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TrendUnit {
     Minutes,
@@ -254,6 +348,13 @@ pub fn now_epoch_seconds() -> i64 {
         .unwrap_or(0)
 }
 
+/// The machine's UTC offset, read once; the stats day boundaries and the Stats
+/// view's clock labels both use it.
+pub fn local_utc_offset() -> UtcOffset {
+    static LOCAL_OFFSET: OnceLock<UtcOffset> = OnceLock::new();
+    *LOCAL_OFFSET.get_or_init(|| UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC))
+}
+
 pub fn load_stats() -> Result<StatsStore> {
     let path = config::stats_path()?;
     load_stats_from_path(&path)
@@ -295,6 +396,196 @@ impl StatsStore {
         self.cache.borrow_mut().invalidate();
     }
 
+    /// Drops every listen event and aggregate stored under `track_key`,
+    /// returning how many events were removed.
+    pub fn forget_track(&mut self, track_key: &str) -> usize {
+        let mut keys = self.event_track_keys().into_iter();
+        let before = self.events.len();
+        self.events
+            .retain(|_| keys.next().is_none_or(|key| key != track_key));
+        let removed = before - self.events.len();
+
+        self.track_totals.remove(track_key);
+        self.provider_track_key_map
+            .retain(|_, key| key != track_key);
+        self.cache.borrow_mut().invalidate();
+        removed
+    }
+
+    /// Finds history stored under more than one key for the same file: path-keyed
+    /// or provider-keyed listens that share a normalized path and duration with
+    /// exactly one metadata key, or path-keyed listens that share them with
+    /// exactly one provider key. Listens recorded before durations were kept
+    /// count as that duration when their path has only one. Nothing is changed.
+    pub fn coalesce_preview(&self) -> Vec<StatsMerge> {
+        let keys = self.event_track_keys();
+        let mut durations_by_path: HashMap<String, Vec<u32>> = HashMap::new();
+        for event in &self.events {
+            if let Some(duration) = event.duration_seconds {
+                let durations = durations_by_path
+                    .entry(legacy_path_key(&event.track_path))
+                    .or_default();
+                if !durations.contains(&duration) {
+                    durations.push(duration);
+                }
+            }
+        }
+        let mut keys_by_path: HashMap<(String, Option<u32>), Vec<&str>> = HashMap::new();
+        for (event, key) in self.events.iter().zip(&keys) {
+            let path = legacy_path_key(&event.track_path);
+            let duration = event.duration_seconds.or_else(|| {
+                match durations_by_path.get(&path).map(Vec::as_slice) {
+                    Some([only]) => Some(*only),
+                    _ => None,
+                }
+            });
+            let group = keys_by_path.entry((path, duration)).or_default();
+            if !group.contains(&key.as_str()) {
+                group.push(key);
+            }
+        }
+
+        // A key heard at two lengths cannot be moved without splitting its totals.
+        let mut groups_per_key: HashMap<&str, usize> = HashMap::new();
+        for group in keys_by_path.values() {
+            for key in group {
+                *groups_per_key.entry(key).or_default() += 1;
+            }
+        }
+
+        let mut paths: Vec<&(String, Option<u32>)> = keys_by_path.keys().collect();
+        paths.sort();
+        let mut into_by_key: HashMap<&str, &str> = HashMap::new();
+        let mut targets: Vec<&str> = Vec::new();
+        for path in paths {
+            let group = &keys_by_path[path];
+            let meta: Vec<&str> = group
+                .iter()
+                .copied()
+                .filter(|key| is_metadata_track_key(key))
+                .collect();
+            let provider: Vec<&str> = group
+                .iter()
+                .copied()
+                .filter(|key| key.starts_with("provider:"))
+                .collect();
+            let target = match (meta.as_slice(), provider.as_slice()) {
+                ([only], _) => *only,
+                ([], [only]) => *only,
+                _ => continue,
+            };
+            if into_by_key.contains_key(target) {
+                continue;
+            }
+            for key in group.iter().copied() {
+                if key == target
+                    || is_metadata_track_key(key)
+                    || groups_per_key[key] > 1
+                    || targets.contains(&key)
+                    || into_by_key.contains_key(key)
+                {
+                    continue;
+                }
+                into_by_key.insert(key, target);
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+        }
+
+        let mut merges: Vec<StatsMerge> = into_by_key
+            .into_iter()
+            .map(|(from_key, into_key)| {
+                let representative = self
+                    .events
+                    .iter()
+                    .zip(&keys)
+                    .rev()
+                    .find(|(_, key)| key.as_str() == into_key)
+                    .map(|(event, _)| event);
+                StatsMerge {
+                    from_key: from_key.to_string(),
+                    into_key: into_key.to_string(),
+                    title: representative
+                        .map(|event| event.title.clone())
+                        .unwrap_or_default(),
+                    artist: representative.and_then(|event| event.artist.clone()),
+                    listens: keys.iter().filter(|key| key.as_str() == from_key).count(),
+                }
+            })
+            .collect();
+        merges.sort_by(|a, b| {
+            a.into_key
+                .cmp(&b.into_key)
+                .then(a.from_key.cmp(&b.from_key))
+        });
+        merges
+    }
+
+    /// Applies merges from `coalesce_preview`, the ones the user was shown,
+    /// rewriting merged listens to the surviving track identity and summing
+    /// their totals. Returns how many were applied.
+    pub fn coalesce(&mut self, merges: &[StatsMerge]) -> usize {
+        if merges.is_empty() {
+            return 0;
+        }
+
+        let keys = self.event_track_keys();
+        let into_by_key: HashMap<&str, &str> = merges
+            .iter()
+            .map(|merge| (merge.from_key.as_str(), merge.into_key.as_str()))
+            .collect();
+        let mut representatives: HashMap<String, ListenEvent> = HashMap::new();
+        for (event, key) in self.events.iter().zip(&keys) {
+            if merges.iter().any(|merge| &merge.into_key == key) {
+                representatives.insert(key.clone(), event.clone());
+            }
+        }
+        for (event, key) in self.events.iter_mut().zip(&keys) {
+            let Some(representative) = into_by_key
+                .get(key.as_str())
+                .and_then(|into| representatives.get(*into))
+            else {
+                continue;
+            };
+            event.title = representative.title.clone();
+            event.artist = representative.artist.clone();
+            event.provider_track_id = representative.provider_track_id.clone();
+            if event.album.is_none() {
+                event.album = representative.album.clone();
+            }
+        }
+
+        for merge in merges {
+            if let Some(totals) = self.track_totals.remove(&merge.from_key) {
+                let bucket = self.track_totals.entry(merge.into_key.clone()).or_default();
+                bucket.play_count = bucket.play_count.saturating_add(totals.play_count);
+                bucket.listen_seconds = bucket.listen_seconds.saturating_add(totals.listen_seconds);
+            }
+        }
+        for key in self.provider_track_key_map.values_mut() {
+            if let Some(into) = into_by_key.get(key.as_str()) {
+                *key = (*into).to_string();
+            }
+        }
+        self.cache.borrow_mut().invalidate();
+        merges.len()
+    }
+
+    fn event_track_keys(&self) -> Vec<String> {
+        self.events
+            .iter()
+            .map(|event| {
+                self.resolve_track_key(
+                    &event.title,
+                    event.artist.as_deref(),
+                    &event.track_path,
+                    event.provider_track_id.as_deref(),
+                )
+            })
+            .collect()
+    }
+
     pub fn record_listen(&mut self, record: ListenSessionRecord) {
         let counted_play = record.counted_play_override.unwrap_or_else(|| {
             should_count_as_play(
@@ -343,6 +634,8 @@ impl StatsStore {
             started_at_epoch_seconds: record.started_at_epoch_seconds,
             listened_seconds: record.listened_seconds,
             counted_play,
+            completed: Some(record.completed),
+            duration_seconds: record.duration_seconds,
         });
 
         if self.events.len() > MAX_EVENTS {
@@ -353,6 +646,12 @@ impl StatsStore {
         self.cache.borrow_mut().invalidate();
     }
 
+    /// Bumped on every change to the history, including merges that keep the
+    /// event count.
+    pub fn generation(&self) -> u64 {
+        self.cache.borrow().generation
+    }
+
     pub fn query(&self, query: &StatsQuery, now_epoch_seconds: i64) -> StatsSnapshot {
         let time_bucket = time_bucket_for_range(query.range, now_epoch_seconds);
         let cache_key = StatsQueryCacheKey {
@@ -362,6 +661,7 @@ impl StatsStore {
             album_filter: query.album_filter.clone(),
             search: query.search.clone(),
             time_bucket,
+            local_day: local_day(now_epoch_seconds, local_utc_offset().whole_seconds()),
         };
 
         if let Some(cached) = self.cache.borrow().get(&cache_key) {
@@ -374,6 +674,7 @@ impl StatsStore {
     }
 
     fn compute_query(&self, query: &StatsQuery, now_epoch_seconds: i64) -> StatsSnapshot {
+        let utc_offset_seconds = local_utc_offset().whole_seconds();
         let range_start = range_start_epoch(query.range, now_epoch_seconds);
         let artist_filter = query.artist_filter.trim().to_ascii_lowercase();
         let album_filter = query.album_filter.trim().to_ascii_lowercase();
@@ -387,6 +688,10 @@ impl StatsStore {
         let mut total_plays = 0_u64;
         let mut total_listen_seconds = 0_u64;
         let mut recent: HashMap<String, ListenEvent> = HashMap::new();
+        let mut played_days = BTreeSet::new();
+        // A listen can be saved in several slices; it counts as finished when
+        // any of them reached the end.
+        let mut sessions: HashMap<(String, i64), bool> = HashMap::new();
 
         for event in &self.events {
             if matches!(
@@ -438,12 +743,16 @@ impl StatsStore {
             let row = by_track
                 .entry(key.clone())
                 .or_insert_with(|| TrackStatsRow {
+                    track_key: key.clone(),
                     track_path: event.track_path.clone(),
                     title: event.title.clone(),
                     artist: event.artist.clone(),
                     album: event.album.clone(),
                     play_count: 0,
                     listen_seconds: 0,
+                    last_played_epoch_seconds: event.started_at_epoch_seconds,
+                    starts: 0,
+                    skips: 0,
                 });
             if metadata_track_key(event.artist.as_deref(), &event.title).is_some() {
                 row.title = event.title.clone();
@@ -455,13 +764,26 @@ impl StatsStore {
             row.listen_seconds = row
                 .listen_seconds
                 .saturating_add(u64::from(event.listened_seconds));
+            row.last_played_epoch_seconds = row
+                .last_played_epoch_seconds
+                .max(event.started_at_epoch_seconds);
             if event.counted_play {
                 row.play_count = row.play_count.saturating_add(1);
                 total_plays = total_plays.saturating_add(1);
+                played_days.insert(local_day(
+                    event.started_at_epoch_seconds,
+                    utc_offset_seconds,
+                ));
             }
             total_listen_seconds =
                 total_listen_seconds.saturating_add(u64::from(event.listened_seconds));
 
+            if let Some(completed) = event.completed {
+                *sessions
+                    .entry((key.clone(), event.started_at_epoch_seconds))
+                    .or_default() |= completed;
+            }
+
             let recent_key = format!("{}|{}", key, event.started_at_epoch_seconds);
             match recent.get_mut(&recent_key) {
                 Some(aggregate) => {
@@ -476,23 +798,157 @@ impl StatsStore {
             }
         }
 
+        for ((key, _), completed) in sessions {
+            if let Some(row) = by_track.get_mut(&key) {
+                row.starts += 1;
+                row.skips += u64::from(!completed);
+            }
+        }
+
         let mut rows: Vec<TrackStatsRow> = by_track.into_values().collect();
+        if query.sort == StatsSort::SkipRate {
+            rows.retain(|row| row.skip_rate().is_some());
+        }
         rows.sort_by(|a, b| compare_rows(a, b, query.sort));
 
         let mut recent: Vec<ListenEvent> = recent.into_values().collect();
         recent.sort_by_key(|event| std::cmp::Reverse(event.started_at_epoch_seconds));
         let trend = build_trend_series(query.range, query.sort, now_epoch_seconds, &recent);
+        let today_listen_seconds = self.today_listen_seconds(now_epoch_seconds);
+        let streak = self.listening_streak(now_epoch_seconds, utc_offset_seconds);
 
         StatsSnapshot {
             total_plays,
             total_listen_seconds,
+            today_listen_seconds,
+            days_listened: u32::try_from(played_days.len()).unwrap_or(u32::MAX),
+            streak,
             rows,
             recent,
             trend,
         }
     }
 
-    fn resolve_track_key(
+    /// Writes the rows `query` selects, in its sort order, as CSV with a header
+    /// line. Returns the number of tracks written.
+    pub fn export_csv(
+        &self,
+        path: &Path,
+        query: &StatsQuery,
+        now_epoch_seconds: i64,
+    ) -> Result<usize> {
+        let rows = self.export_rows(query, now_epoch_seconds);
+        let mut csv =
+            String::from("title,artist,album,path,play_count,total_listen_seconds,last_played\n");
+        for row in &rows {
+            let fields = [
+                csv_field(&row.title),
+                csv_field(row.artist.as_deref().unwrap_or_default()),
+                csv_field(row.album.as_deref().unwrap_or_default()),
+                csv_field(&row.path),
+                row.play_count.to_string(),
+                row.total_listen_seconds.to_string(),
+                row.last_played.clone(),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        fs::write(path, csv).with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(rows.len())
+    }
+
+    /// Same rows as `export_csv`, as a pretty-printed JSON array.
+    pub fn export_json(
+        &self,
+        path: &Path,
+        query: &StatsQuery,
+        now_epoch_seconds: i64,
+    ) -> Result<usize> {
+        let rows = self.export_rows(query, now_epoch_seconds);
+        let json = serde_json::to_string_pretty(&rows)?;
+        fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(rows.len())
+    }
+
+    fn export_rows(&self, query: &StatsQuery, now_epoch_seconds: i64) -> Vec<StatsExportRow> {
+        self.query(query, now_epoch_seconds)
+            .rows
+            .into_iter()
+            .map(|row| StatsExportRow {
+                path: row.track_path.to_string_lossy().into_owned(),
+                title: row.title,
+                artist: row.artist,
+                album: row.album,
+                play_count: row.play_count,
+                total_listen_seconds: row.listen_seconds,
+                last_played: iso8601_utc(row.last_played_epoch_seconds),
+            })
+            .collect()
+    }
+
+    /// Listen time in the same rolling window as the Today range.
+    pub fn today_listen_seconds(&self, now_epoch_seconds: i64) -> u64 {
+        let start = range_start_epoch(StatsRange::Today, now_epoch_seconds).unwrap_or(i64::MIN);
+        self.events
+            .iter()
+            .filter(|event| event.started_at_epoch_seconds >= start)
+            .map(|event| u64::from(event.listened_seconds))
+            .sum()
+    }
+
+    /// Streaks count days with a counted play; today and this week add up all
+    /// listening. Days start at local midnight for `utc_offset_seconds`.
+    pub fn listening_streak(
+        &self,
+        now_epoch_seconds: i64,
+        utc_offset_seconds: i32,
+    ) -> ListeningStreak {
+        let today = local_day(now_epoch_seconds, utc_offset_seconds);
+        // 1970-01-01 was a Thursday, three days after a Monday.
+        let week_start = today - (today + 3).rem_euclid(7);
+        let mut streak = ListeningStreak::default();
+        let mut played_days = BTreeSet::new();
+        for event in &self.events {
+            let day = local_day(event.started_at_epoch_seconds, utc_offset_seconds);
+            if day > today {
+                continue;
+            }
+            let seconds = u64::from(event.listened_seconds);
+            if day == today {
+                streak.today_seconds = streak.today_seconds.saturating_add(seconds);
+            }
+            if day >= week_start {
+                streak.week_seconds = streak.week_seconds.saturating_add(seconds);
+            }
+            if event.counted_play {
+                played_days.insert(day);
+            }
+        }
+
+        let mut run = 0_u32;
+        let mut previous = None;
+        for day in &played_days {
+            run = if previous == Some(day - 1) {
+                run + 1
+            } else {
+                1
+            };
+            streak.longest_days = streak.longest_days.max(run);
+            previous = Some(*day);
+        }
+        let mut day = if played_days.contains(&today) {
+            today
+        } else {
+            today - 1
+        };
+        while played_days.contains(&day) {
+            streak.current_days += 1;
+            day -= 1;
+        }
+        streak
+    }
+
+    pub(crate) fn resolve_track_key(
         &self,
         title: &str,
         artist: Option<&str>,
@@ -579,7 +1035,7 @@ fn build_trend_series(
     let mut buckets = vec![0_u64; bucket_len];
     for event in events {
         match sort {
-            StatsSort::Plays => {
+            StatsSort::Plays | StatsSort::SkipRate => {
                 let index = ((event.started_at_epoch_seconds.saturating_sub(start)) / step_seconds)
                     .clamp(0, (bucket_len as i64) - 1) as usize;
                 buckets[index] = buckets[index].saturating_add(u64::from(event.counted_play));
@@ -653,10 +1109,38 @@ fn add_listen_time_to_buckets(
     }
 }
 
+fn iso8601_utc(epoch_seconds: i64) -> String {
+    let dt = time::OffsetDateTime::from_unix_timestamp(epoch_seconds)
+        .unwrap_or(time::OffsetDateTime::UNIX_EPOCH);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        dt.year(),
+        dt.month() as u8,
+        dt.day(),
+        dt.hour(),
+        dt.minute(),
+        dt.second()
+    )
+}
+
+/// Quotes a CSV field when it holds a separator, quote or line break, doubling
+/// any quotes inside (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 fn compare_rows(a: &TrackStatsRow, b: &TrackStatsRow, sort: StatsSort) -> Ordering {
     let primary = match sort {
         StatsSort::Plays => b.play_count.cmp(&a.play_count),
         StatsSort::ListenTime => b.listen_seconds.cmp(&a.listen_seconds),
+        StatsSort::SkipRate => {
+            let rate = |row: &TrackStatsRow| row.skip_rate().unwrap_or_default();
+            rate(b).total_cmp(&rate(a)).then(b.skips.cmp(&a.skips))
+        }
     };
     if primary != Ordering::Equal {
         return primary;
@@ -672,6 +1156,13 @@ fn compare_rows(a: &TrackStatsRow, b: &TrackStatsRow, sort: StatsSort) -> Orderi
         })
 }
 
+/// Days since 1970-01-01 in local time.
+fn local_day(epoch_seconds: i64, utc_offset_seconds: i32) -> i64 {
+    epoch_seconds
+        .saturating_add(i64::from(utc_offset_seconds))
+        .div_euclid(86_400)
+}
+
 fn range_start_epoch(range: StatsRange, now_epoch_seconds: i64) -> Option<i64> {
     let day = 86_400_i64;
     match range {
@@ -696,7 +1187,7 @@ fn normalize_provider_track_id(value: Option<&str>) -> Option<String> {
     }
 }
 
-fn metadata_track_key(artist: Option<&str>, title: &str) -> Option<String> {
+pub(crate) fn metadata_track_key(artist: Option<&str>, title: &str) -> Option<String> {
     let normalized_artist = normalize_artist_for_match(artist.unwrap_or_default());
     let normalized_title = normalize_text_for_match(title);
     if normalized_artist.is_empty() || normalized_title.is_empty() {
@@ -906,6 +1397,382 @@ mod tests {
         assert!(store.track_totals.is_empty());
     }
 
+    #[test]
+    fn export_writes_filtered_rows_with_escaped_csv_and_iso_timestamps() {
+        let mut store = StatsStore::default();
+        for (path, title, artist, started) in [
+            (
+                "/music/Hello, \"World\".mp3",
+                "Hello, \"World\"",
+                "Neon",
+                1_000,
+            ),
+            (
+                "/music/Hello, \"World\".mp3",
+                "Hello, \"World\"",
+                "Neon",
+                90_061,
+            ),
+            ("/music/Other.mp3", "Other", "Someone Else", 2_000),
+        ] {
+            store.record_listen(ListenSessionRecord {
+                track_path: PathBuf::from(path),
+                title: title.to_string(),
+                artist: Some(artist.to_string()),
+                album: None,
+                provider_track_id: None,
+                started_at_epoch_seconds: started,
+                listened_seconds: 60,
+                completed: false,
+                duration_seconds: Some(180),
+                counted_play_override: None,
+                allow_short_listen: false,
+            });
+        }
+        let query = StatsQuery {
+            artist_filter: String::from("neon"),
+            ..StatsQuery::default()
+        };
+        let dir = tempfile::tempdir().expect("temp dir");
+
+        let csv_path = dir.path().join("stats.csv");
+        assert_eq!(
+            store.export_csv(&csv_path, &query, 100_000).expect("csv"),
+            1
+        );
+        assert_eq!(
+            fs::read_to_string(&csv_path).expect("read csv"),
+            "title,artist,album,path,play_count,total_listen_seconds,last_played\n\
+             \"Hello, \"\"World\"\"\",Neon,,\"/music/Hello, \"\"World\"\".mp3\",2,120,\
+             1970-01-02T01:01:01Z\n"
+        );
+
+        let json_path = dir.path().join("stats.json");
+        assert_eq!(
+            store
+                .export_json(&json_path, &query, 100_000)
+                .expect("json"),
+            1
+        );
+        let rows: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).expect("read json"))
+                .expect("parse json");
+        assert_eq!(rows[0]["title"], "Hello, \"World\"");
+        assert_eq!(rows[0]["album"], serde_json::Value::Null);
+        assert_eq!(rows[0]["total_listen_seconds"], 120);
+        assert_eq!(rows[0]["last_played"], "1970-01-02T01:01:01Z");
+        assert_eq!(rows.as_array().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn forget_track_leaves_other_tracks_untouched() {
+        let mut store = StatsStore::default();
+        for (path, title, started) in [
+            ("C:/music/A.mp3", "Night Drive", 1_000),
+            ("C:/music/B.mp3", "Ocean Room", 1_200),
+            ("C:/music/A.mp3", "Night Drive", 1_400),
+        ] {
+            store.record_listen(ListenSessionRecord {
+                track_path: PathBuf::from(path),
+                title: title.to_string(),
+                artist: Some("Neon".to_string()),
+                album: None,
+                provider_track_id: None,
+                started_at_epoch_seconds: started,
+                listened_seconds: 60,
+                completed: false,
+                duration_seconds: Some(180),
+                counted_play_override: None,
+                allow_short_listen: false,
+            });
+        }
+        let query = StatsQuery {
+            range: StatsRange::Lifetime,
+            sort: StatsSort::Plays,
+            artist_filter: String::new(),
+            album_filter: String::new(),
+            search: String::new(),
+        };
+        let before = store.query(&query, 2_000);
+        let forgotten = before
+            .rows
+            .iter()
+            .find(|row| row.title == "Night Drive")
+            .expect("night drive row")
+            .track_key
+            .clone();
+
+        assert_eq!(store.forget_track(&forgotten), 2);
+
+        let after = store.query(&query, 2_000);
+        assert_eq!(after.rows.len(), 1);
+        assert_eq!(after.rows[0].title, "Ocean Room");
+        assert_eq!(after.rows[0].play_count, 1);
+        assert_eq!(after.rows[0].listen_seconds, 60);
+        assert_eq!(after.total_plays, 1);
+        assert_eq!(after.recent.len(), 1);
+        assert!(!store.track_totals.contains_key(&forgotten));
+        assert_eq!(store.track_totals.len(), 1);
+    }
+
+    #[test]
+    fn coalesce_merges_path_keyed_history_into_provider_key() {
+        let mut store = StatsStore::default();
+        for (path, title, provider, started) in [
+            ("C:/music/intro.mp3", "Intro", Some("yt:abc"), 1_000),
+            ("C:/music/Intro.mp3", "intro", None, 1_200),
+            ("C:/music/other.mp3", "Other", None, 1_400),
+        ] {
+            store.record_listen(ListenSessionRecord {
+                track_path: PathBuf::from(path),
+                title: title.to_string(),
+                artist: None,
+                album: None,
+                provider_track_id: provider.map(str::to_string),
+                started_at_epoch_seconds: started,
+                listened_seconds: 60,
+                completed: false,
+                duration_seconds: Some(180),
+                counted_play_override: None,
+                allow_short_listen: false,
+            });
+        }
+        let query = StatsQuery::default();
+        assert_eq!(store.query(&query, 2_000).rows.len(), 3);
+
+        let preview = store.coalesce_preview();
+        assert_eq!(preview.len(), 1);
+        assert_eq!(preview[0].into_key, "provider:yt:abc");
+        assert_eq!(preview[0].listens, 1);
+        assert_eq!(store.query(&query, 2_000).rows.len(), 3);
+
+        assert_eq!(store.coalesce(&preview), 1);
+        let snapshot = store.query(&query, 2_000);
+        assert_eq!(snapshot.rows.len(), 2);
+        let merged = snapshot
+            .rows
+            .iter()
+            .find(|row| row.track_key == "provider:yt:abc")
+            .expect("merged row");
+        assert_eq!(merged.play_count, 2);
+        assert_eq!(merged.listen_seconds, 120);
+        assert_eq!(store.track_totals["provider:yt:abc"].play_count, 2);
+        assert!(store.coalesce_preview().is_empty());
+    }
+
+    #[test]
+    fn coalesce_leaves_differently_tagged_tracks_alone() {
+        let mut store = StatsStore::default();
+        for title in ["First", "Second"] {
+            store.record_listen(ListenSessionRecord {
+                track_path: PathBuf::from("C:/music/track01.mp3"),
+                title: title.to_string(),
+                artist: Some("Band".to_string()),
+                album: None,
+                provider_track_id: None,
+                started_at_epoch_seconds: 1_000,
+                listened_seconds: 60,
+                completed: false,
+                duration_seconds: Some(180),
+                counted_play_override: None,
+                allow_short_listen: false,
+            });
+        }
+
+        assert!(store.coalesce_preview().is_empty());
+        assert_eq!(store.track_totals.len(), 2);
+    }
+
+    #[test]
+    fn coalesce_keeps_a_different_recording_at_the_same_path_apart() {
+        let mut store = StatsStore::default();
+        for (title, provider, duration) in
+            [("Intro", Some("yt:abc"), 180), ("intro (live)", None, 420)]
+        {
+            store.record_listen(ListenSessionRecord {
+                track_path: PathBuf::from("C:/music/intro.mp3"),
+                title: title.to_string(),
+                artist: None,
+                album: None,
+                provider_track_id: provider.map(str::to_string),
+                started_at_epoch_seconds: 1_000,
+                listened_seconds: 60,
+                completed: false,
+                duration_seconds: Some(duration),
+                counted_play_override: None,
+                allow_short_listen: false,
+            });
+        }
+
+        assert!(store.coalesce_preview().is_empty());
+    }
+
+    #[test]
+    fn coalesce_applies_only_the_previewed_merges() {
+        let mut store = StatsStore::default();
+        let listen = |path: &str, title: &str, provider: Option<&str>| ListenSessionRecord {
+            track_path: PathBuf::from(path),
+            title: title.to_string(),
+            artist: None,
+            album: None,
+            provider_track_id: provider.map(str::to_string),
+            started_at_epoch_seconds: 1_000,
+            listened_seconds: 60,
+            completed: false,
+            duration_seconds: Some(180),
+            counted_play_override: None,
+            allow_short_listen: false,
+        };
+        store.record_listen(listen("C:/music/intro.mp3", "Intro", Some("yt:abc")));
+        store.record_listen(listen("C:/music/Intro.mp3", "intro", None));
+        let shown = store.coalesce_preview();
+        assert_eq!(shown.len(), 1);
+
+        store.record_listen(listen("C:/music/outro.mp3", "Outro", Some("yt:def")));
+        store.record_listen(listen("C:/music/Outro.mp3", "outro", None));
+        assert_eq!(store.coalesce(&shown), 1);
+        let left = store.coalesce_preview();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].into_key, "provider:yt:def");
+    }
+
+    #[test]
+    fn today_listen_seconds_uses_today_window_and_ignores_filters() {
+        let mut store = StatsStore::default();
+        for (title, started_at) in [("Old", 1_000), ("Recent", 90_000), ("Latest", 95_000)] {
+            store.record_listen(ListenSessionRecord {
+                track_path: PathBuf::from(format!("C:/music/{title}.mp3")),
+                title: title.to_string(),
+                artist: Some("Band".to_string()),
+                album: None,
+                provider_track_id: None,
+                started_at_epoch_seconds: started_at,
+                listened_seconds: 120,
+                completed: false,
+                duration_seconds: Some(180),
+                counted_play_override: None,
+                allow_short_listen: false,
+            });
+        }
+
+        let now = 100_000;
+        assert_eq!(store.today_listen_seconds(now), 240);
+        let snapshot = store.query(
+            &StatsQuery {
+                search: String::from("latest"),
+                ..StatsQuery::default()
+            },
+            now,
+        );
+        assert_eq!(snapshot.total_listen_seconds, 120);
+        assert_eq!(snapshot.today_listen_seconds, 240);
+    }
+
+    #[test]
+    fn listening_streak_counts_local_days_with_counted_plays() {
+        let day = 86_400_i64;
+        let mut store = StatsStore::default();
+        let mut listen = |started_at: i64, listened_seconds: u32| {
+            store.record_listen(ListenSessionRecord {
+                track_path: PathBuf::from("C:/music/Song.mp3"),
+                title: "Song".to_string(),
+                artist: Some("Band".to_string()),
+                album: None,
+                provider_track_id: None,
+                started_at_epoch_seconds: started_at,
+                listened_seconds,
+                completed: false,
+                duration_seconds: Some(180),
+                counted_play_override: None,
+                allow_short_listen: false,
+            });
+        };
+        for n in 10..14 {
+            listen(n * day + 43_200, 120);
+        }
+        listen(18 * day + 82_800, 120);
+        listen(19 * day + 82_800, 120);
+        // Too short to count as a play, so it does not extend the streak.
+        listen(20 * day + 43_200, 12);
+
+        // Day 20 is a Wednesday; the week began on day 18.
+        let now = 20 * day + 46_800;
+        assert_eq!(
+            store.listening_streak(now, 0),
+            ListeningStreak {
+                current_days: 2,
+                longest_days: 4,
+                today_seconds: 12,
+                week_seconds: 252,
+            }
+        );
+
+        // Two hours ahead of UTC, the 23:00 listens land a day later.
+        let shifted = store.listening_streak(now, 7_200);
+        assert_eq!(shifted.current_days, 2);
+        assert_eq!(shifted.today_seconds, 132);
+
+        assert_eq!(store.listening_streak(30 * day, 0).current_days, 0);
+        assert_eq!(store.query(&StatsQuery::default(), now).days_listened, 6);
+    }
+
+    #[test]
+    fn skip_rate_sort_lists_tracks_with_enough_starts_most_skipped_first() {
+        let mut store = StatsStore::default();
+        let mut listen = |title: &str, started_at: i64, listened_seconds: u32, completed| {
+            store.record_listen(ListenSessionRecord {
+                track_path: PathBuf::from(format!("C:/music/{title}.mp3")),
+                title: title.to_string(),
+                artist: Some("Band".to_string()),
+                album: None,
+                provider_track_id: None,
+                started_at_epoch_seconds: started_at,
+                listened_seconds,
+                completed,
+                duration_seconds: Some(180),
+                counted_play_override: None,
+                allow_short_listen: true,
+            });
+        };
+        // Saved in two slices; the second reached the end, so not a skip.
+        listen("Keeper", 100, 60, false);
+        listen("Keeper", 100, 120, true);
+        listen("Keeper", 400, 20, false);
+        listen("Keeper", 700, 180, true);
+        for started_at in [1_000, 1_300, 1_600, 1_900] {
+            listen("Skipper", started_at, 15, started_at == 1_900);
+        }
+        listen("Once", 2_000, 12, false);
+
+        let snapshot = store.query(
+            &StatsQuery {
+                sort: StatsSort::SkipRate,
+                ..StatsQuery::default()
+            },
+            5_000,
+        );
+        let rows: Vec<(&str, u64, u64)> = snapshot
+            .rows
+            .iter()
+            .map(|row| (row.title.as_str(), row.skips, row.starts))
+            .collect();
+        assert_eq!(rows, vec![("Skipper", 3, 4), ("Keeper", 1, 3)]);
+        assert_eq!(snapshot.rows[0].skip_rate(), Some(0.75));
+
+        // Listens saved before completion was kept have no outcome.
+        store.events[0].completed = None;
+        store.events[1].completed = None;
+        store.cache.borrow_mut().invalidate();
+        let snapshot = store.query(&StatsQuery::default(), 5_000);
+        let keeper = snapshot
+            .rows
+            .iter()
+            .find(|row| row.title == "Keeper")
+            .expect("keeper row");
+        assert_eq!((keeper.skips, keeper.starts), (1, 2));
+        assert_eq!(keeper.skip_rate(), None);
+    }
+
     #[test]
     fn query_applies_search_filters_and_sort() {
         let mut store = StatsStore::default();
@@ -1069,6 +1936,8 @@ mod tests {
             started_at_epoch_seconds: 0,
             listened_seconds: 30,
             counted_play: true,
+            completed: None,
+            duration_seconds: None,
         }];
 
         let trend = build_trend_series(StatsRange::Lifetime, StatsSort::ListenTime, 95, &events);
@@ -1088,6 +1957,8 @@ mod tests {
             started_at_epoch_seconds: 0,
             listened_seconds: 30,
             counted_play: true,
+            completed: None,
+            duration_seconds: None,
         }];
 
         let trend = build_trend_series(StatsRange::Lifetime, StatsSort::ListenTime, 70, &events);
@@ -1107,6 +1978,8 @@ mod tests {
             started_at_epoch_seconds: 0,
             listened_seconds: 4_740,
             counted_play: true,
+            completed: None,
+            duration_seconds: None,
         }];
 
         let trend = build_trend_series(StatsRange::Lifetime, StatsSort::ListenTime, 4_740, &events);
@@ -1129,6 +2002,8 @@ mod tests {
                 started_at_epoch_seconds: 16_200 + (index as i64) * 180,
                 listened_seconds: 180,
                 counted_play: true,
+                completed: None,
+                duration_seconds: None,
             });
         }
 
@@ -1151,6 +2026,8 @@ mod tests {
             started_at_epoch_seconds: now - 600,
             listened_seconds: 120,
             counted_play: true,
+            completed: None,
+            duration_seconds: None,
         }];
 
         let trend = build_trend_series(StatsRange::Today, StatsSort::ListenTime, now, &events);
@@ -1169,6 +2046,8 @@ mod tests {
             started_at_epoch_seconds: now - 1_200,
             listened_seconds: 90,
             counted_play: true,
+            completed: None,
+            duration_seconds: None,
         }];
 
         let trend_7d = build_trend_series(StatsRange::Days7, StatsSort::ListenTime, now, &events);
@@ -1191,6 +2070,8 @@ mod tests {
                 started_at_epoch_seconds: now - 50_000,
                 listened_seconds: 90,
                 counted_play: true,
+                completed: None,
+                duration_seconds: None,
             },
             ListenEvent {
                 track_path: PathBuf::from("C:/music/B.mp3"),
@@ -1201,6 +2082,8 @@ mod tests {
                 started_at_epoch_seconds: now - 400,
                 listened_seconds: 120,
                 counted_play: true,
+                completed: None,
+                duration_seconds: None,
             },
         ];
 
@@ -4,9 +4,14 @@ use crate::core::HeaderSection;
 use crate::core::LyricsMode;
 use crate::core::StatsFilterFocus;
 use crate::core::TuneCore;
+use crate::core::daily_listen_goal_label;
+use crate::core::playback_speed_label;
 use crate::model::{CoverArtTemplate, RepeatMode, Theme};
-use crate::online::OnlineSession;
-use crate::stats::{ListenEvent, StatsRange, StatsSnapshot, StatsSort, TrendSeries};
+use crate::online::{ChatMessage, OnlineSession};
+use crate::stats::{
+    ListenEvent, ListeningStreak, StatsRange, StatsSnapshot, StatsSort, TrendSeries,
+    local_utc_offset,
+};
 use image::imageops::FilterType;
 use image::{ImageBuffer, ImageFormat, Rgba};
 use ratatui::prelude::*;
@@ -16,9 +21,10 @@ use ratatui::widgets::{
 };
 use std::collections::HashMap;
 use std::path::Path;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
-use time::{OffsetDateTime, UtcOffset};
+use time::OffsetDateTime;
 
 const APP_TITLE: &str = "TuneTUI";
 const APP_VERSION: &str = "v1.0.0-alpha-3";
@@ -35,6 +41,7 @@ pub enum HitTarget {
     QuickAddSelectedToQueueNext,
     LibrarySearchBar,
     LibraryRow(usize),
+    LibraryCrumb(usize),
     Prev,
     Next,
     ScrubBack,
@@ -67,6 +74,7 @@ pub enum HitTarget {
     CycleStreamQuality,
     ToggleRoomCodeReveal,
     CopyRoomCode,
+    ShowRoomQr,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -135,15 +143,33 @@ pub struct ActionPanelView {
     pub title: String,
     pub hint: String,
     pub search_query: Option<String>,
+    pub drift_meter: Option<SyncDriftMeter>,
     pub options: Vec<String>,
     pub selected: usize,
 }
 
+/// Live drift readout for the Online delay settings panel, redrawn as syncs arrive.
+pub struct SyncDriftMeter {
+    pub drift_ms: i32,
+    pub threshold_ms: u16,
+}
+
 pub struct HostInviteModalView {
     pub invite_code: String,
     pub copy_selected: bool,
+    /// Show the invite as a QR code, with the raw text only when revealed.
+    pub qr_only: bool,
+    /// QR rows from `invite_qr_rows`, `None` when the code is too long for one.
+    pub qr_rows: Option<Arc<[String]>>,
+    pub code_revealed: bool,
 }
 
+/// Largest QR (version 4) that still scans cleanly from a terminal cell grid.
+const MAX_INVITE_QR_MODULES: usize = 33;
+const INVITE_QR_QUIET_ZONE: usize = 2;
+/// Rows the QR invite modal needs besides the code itself.
+const INVITE_QR_MODAL_CHROME_ROWS: u16 = 14;
+
 pub struct OnlinePasswordPromptView {
     pub title: String,
     pub subtitle: String,
@@ -175,13 +201,28 @@ pub struct OnlineRoomDirectoryModalView {
     pub rooms: Vec<String>,
 }
 
+pub struct OnlineStatusView {
+    pub role: String,
+    pub connection: String,
+    pub server_addr: String,
+}
+
+pub struct BufferingView {
+    pub received_bytes: u64,
+    pub total_bytes: Option<u64>,
+}
+
 pub struct OverlayViews<'a> {
     pub join_prompt_modal: Option<&'a JoinPromptModalView>,
     pub room_directory_view: Option<&'a OnlineRoomDirectoryModalView>,
     pub online_password_prompt: Option<&'a OnlinePasswordPromptView>,
     pub host_invite_modal: Option<&'a HostInviteModalView>,
     pub online_room_field: Option<&'a OnlineRoomFieldView>,
+    pub online_status: Option<&'a OnlineStatusView>,
+    pub buffering: Option<&'a BufferingView>,
     pub room_code_revealed: bool,
+    /// The chat draft while chat input mode is on.
+    pub chat_input: Option<&'a str>,
 }
 
 #[derive(Clone, Copy)]
@@ -322,7 +363,56 @@ fn palette(theme: Theme) -> ThemePalette {
     }
 }
 
-pub fn library_rect(area: Rect) -> Rect {
+/// Below either size the compact layout is used unless the user picked one.
+const COMPACT_LAYOUT_BELOW_WIDTH: u16 = 100;
+const COMPACT_LAYOUT_BELOW_HEIGHT: u16 = 30;
+/// Shorter than this, the compact layout drops the timeline too.
+const COMPACT_TIMELINE_MIN_HEIGHT: u16 = 8;
+
+/// `setting` is the persisted choice; `None` follows the terminal size.
+pub fn compact_layout_active(area: Rect, setting: Option<bool>) -> bool {
+    setting.unwrap_or(
+        area.width < COMPACT_LAYOUT_BELOW_WIDTH || area.height < COMPACT_LAYOUT_BELOW_HEIGHT,
+    )
+}
+
+/// Top-level regions of the screen. Panes the compact layout drops are
+/// zero-sized, so drawing into them is a no-op.
+struct ScreenLayout {
+    header: Rect,
+    body: Rect,
+    timeline: Rect,
+    control: Rect,
+    selection: Rect,
+    footer: Rect,
+}
+
+fn screen_layout(area: Rect, compact: bool) -> ScreenLayout {
+    if compact {
+        let timeline_height = if area.height >= COMPACT_TIMELINE_MIN_HEIGHT {
+            3
+        } else {
+            0
+        };
+        let vertical = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(timeline_height),
+                Constraint::Length(1),
+            ])
+            .split(area);
+        return ScreenLayout {
+            header: vertical[0],
+            body: vertical[1],
+            timeline: vertical[2],
+            control: Rect::default(),
+            selection: Rect::default(),
+            footer: vertical[3],
+        };
+    }
+
     let vertical = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -334,13 +424,34 @@ pub fn library_rect(area: Rect) -> Rect {
             Constraint::Length(3),
         ])
         .split(area);
+    ScreenLayout {
+        header: vertical[0],
+        body: vertical[1],
+        timeline: vertical[2],
+        control: vertical[3],
+        selection: vertical[4],
+        footer: vertical[5],
+    }
+}
 
-    let body = Layout::default()
+/// The compact Library gives the whole body to the browser and drops Song Info.
+fn body_columns(body: Rect, compact: bool, section: HeaderSection) -> Rc<[Rect]> {
+    let right = if compact && section == HeaderSection::Library {
+        0
+    } else {
+        34
+    };
+    Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(66), Constraint::Percentage(34)])
-        .split(vertical[1]);
+        .constraints([
+            Constraint::Percentage(100 - right),
+            Constraint::Percentage(right),
+        ])
+        .split(body)
+}
 
-    body[0]
+pub fn library_rect(area: Rect, compact: bool, section: HeaderSection) -> Rect {
+    body_columns(screen_layout(area, compact).body, compact, section)[0]
 }
 
 pub fn draw(
@@ -358,41 +469,43 @@ pub fn draw(
         frame.area(),
     );
 
-    let vertical = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),
-            Constraint::Min(8),
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Length(3),
-        ])
-        .split(frame.area());
+    let compact = compact_layout_active(frame.area(), core.compact_layout);
+    let layout = screen_layout(frame.area(), compact);
 
-    frame.render_widget(status_panel_block(core, &colors), vertical[0]);
-    register_status_pill_hits(vertical[0], core);
-
-    let header_inner = vertical[0].inner(Margin {
-        vertical: 0,
-        horizontal: 1,
-    });
+    let header_inner = if compact {
+        frame.render_widget(
+            Block::default().style(Style::default().bg(colors.panel_bg)),
+            layout.header,
+        );
+        layout.header
+    } else {
+        frame.render_widget(status_panel_block(core, &colors), layout.header);
+        register_status_pill_hits(layout.header, core);
+        layout.header.inner(Margin {
+            vertical: 0,
+            horizontal: 1,
+        })
+    };
     let tabs_width = header_tabs_width().min(header_inner.width.saturating_sub(1));
     let header_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Min(0), Constraint::Length(tabs_width)])
         .split(header_inner);
 
-    let header_left = Paragraph::new(Line::from(vec![
-        Span::styled(
-            APP_TITLE,
-            Style::default()
-                .fg(colors.accent)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled("  ", Style::default().fg(colors.muted)),
-        Span::styled(APP_VERSION, Style::default().fg(colors.muted)),
-    ]));
+    let header_left = if compact {
+        Paragraph::new(header_status_line(core, &colors))
+    } else {
+        Paragraph::new(Line::from(vec![
+            Span::styled(
+                APP_TITLE,
+                Style::default()
+                    .fg(colors.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("  ", Style::default().fg(colors.muted)),
+            Span::styled(APP_VERSION, Style::default().fg(colors.muted)),
+        ]))
+    };
     frame.render_widget(header_left, header_chunks[0]);
 
     let header_right =
@@ -400,10 +513,7 @@ pub fn draw(
     frame.render_widget(header_right, header_chunks[1]);
     register_header_tab_hits(header_chunks[1]);
 
-    let body = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(66), Constraint::Percentage(34)])
-        .split(vertical[1]);
+    let body = body_columns(layout.body, compact, core.header_section);
 
     frame.render_widget(Clear, body[0]);
     frame.render_widget(Clear, body[1]);
@@ -414,10 +524,14 @@ pub fn draw(
             .iter()
             .enumerate()
             .map(|(i, entry)| {
-                let marker = if core.is_browser_entry_playing(i) {
-                    "  > "
-                } else {
-                    "    "
+                let marker = match (
+                    core.is_library_search_marked(entry),
+                    core.is_browser_entry_playing(i),
+                ) {
+                    (true, true) => " *> ",
+                    (true, false) => " *  ",
+                    (false, true) => "  > ",
+                    (false, false) => "    ",
                 };
                 let kind_style = match entry.kind {
                     BrowserEntryKind::Back => Style::default().fg(colors.alert),
@@ -442,19 +556,30 @@ pub fn draw(
             .collect();
 
         let library_title = if !core.library_search_query.is_empty() {
-            String::from("Library / Search")
-        } else if let Some(name) = &core.browser_playlist {
-            format!("Library / Playlist / {name}")
-        } else if core.browser_all_songs {
-            String::from("Library / All Songs")
-        } else if core.browser_local_queue {
-            String::from("Library / Local Queue")
-        } else if core.browser_shared_queue {
-            String::from("Library / Shared Queue")
-        } else if let Some(path) = &core.browser_path {
-            format!("Library / {}", path.display())
+            match core.library_search_marked.len() {
+                0 => String::from("Library / Search"),
+                marked => format!("Library / Search ({marked} marked)"),
+            }
         } else {
-            String::from("Library")
+            let segments = breadcrumb_segments(
+                &core.browser_breadcrumbs(),
+                usize::from(body[0].width.saturating_sub(4)),
+            );
+            // Titles start one cell in from the corner, after a padding space.
+            let mut x = body[0].x.saturating_add(2);
+            let mut title = String::new();
+            for (depth, text) in &segments {
+                let width = text.chars().count() as u16;
+                if let Some(depth) = depth {
+                    hit_map_push(
+                        Rect::new(x, body[0].y, width, 1),
+                        HitTarget::LibraryCrumb(*depth),
+                    );
+                }
+                x = x.saturating_add(width);
+                title.push_str(text);
+            }
+            title
         };
 
         let block = panel_block(
@@ -576,10 +701,14 @@ pub fn draw(
             .map(|seconds| format_duration(Duration::from_secs(u64::from(seconds))))
             .unwrap_or_else(|| String::from("--:--"));
 
-        let queue_position = now_playing
-            .and_then(|path| core.queue_position_for_path(path))
-            .map(|idx| format!("{}/{}", idx + 1, core.queue.len()))
-            .unwrap_or_else(|| format!("-/{}", core.queue.len()));
+        let queue_position = if now_playing.is_some() && now_playing == core.play_once.as_deref() {
+            String::from("Stops after this track")
+        } else {
+            now_playing
+                .and_then(|path| core.queue_position_for_path(path))
+                .map(|idx| format!("{}/{}", idx + 1, core.queue.len()))
+                .unwrap_or_else(|| format!("-/{}", core.queue.len()))
+        };
 
         let info_text = vec![
             Line::from(vec![
@@ -647,7 +776,7 @@ pub fn draw(
             vertical: 1,
             horizontal: 1,
         });
-        if info_inner.width > 0 && info_inner.height > 0 {
+        if body[1].width > 0 && info_inner.width > 0 && info_inner.height > 0 {
             let details_height = info_inner.height.min(9);
             let cover_height = info_inner.height.saturating_sub(details_height);
             let chunks = Layout::default()
@@ -692,52 +821,72 @@ pub fn draw(
         }
     }
 
-    draw_timeline_panel(frame, vertical[2], core, audio, &colors);
+    if layout.timeline.height > 0 {
+        draw_timeline_panel(
+            frame,
+            layout.timeline,
+            core,
+            audio,
+            overlays.buffering,
+            &colors,
+        );
+    }
 
-    let control_block = Paragraph::new(control_line(audio, 16, &colors))
-        .block(panel_block(
-            "Control",
-            colors.panel_bg,
-            colors.text,
-            colors.border,
-        ))
-        .wrap(Wrap { trim: true });
-    frame.render_widget(control_block, vertical[3]);
-    register_control_line_hits(vertical[3], 16);
+    if compact {
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                core.status.as_str(),
+                Style::default().fg(colors.text),
+            )))
+            .style(Style::default().bg(colors.panel_bg)),
+            layout.footer,
+        );
+    } else {
+        let control_block = Paragraph::new(control_line(audio, 16, &colors))
+            .block(panel_block(
+                "Control",
+                colors.panel_bg,
+                colors.text,
+                colors.border,
+            ))
+            .wrap(Wrap { trim: true });
+        frame.render_widget(control_block, layout.control);
+        register_control_line_hits(layout.control, 16);
 
-    let selection_block = Paragraph::new(selection_actions_line(&colors))
+        let selection_block = Paragraph::new(selection_actions_line(&colors))
+            .block(panel_block(
+                "Selection",
+                colors.panel_bg,
+                colors.text,
+                colors.border,
+            ))
+            .alignment(Alignment::Left)
+            .wrap(Wrap { trim: true });
+        frame.render_widget(selection_block, layout.selection);
+        register_selection_action_hits(layout.selection);
+
+        let key_hint = if core.header_section == HeaderSection::Stats {
+            "Keys: Left/Right Focus, Enter Cycle, Type filters, Backspace Edit, Shift+Up Top"
+        } else if core.header_section == HeaderSection::Lyrics {
+            "Keys: Ctrl+E Edit/view, Up/Down Line, Enter New line, Ctrl+T Timestamp, / Actions"
+        } else if core.header_section == HeaderSection::Online {
+            "Keys: Enter Select/join, Ctrl+N Shared now, Ctrl+L Leave room"
+        } else {
+            "Keys: Enter Play, Backspace Back, Ctrl+F Search, / Actions, T Tray, Ctrl+C Quit"
+        };
+        let footer = Paragraph::new(Line::from(vec![
+            Span::styled(key_hint, Style::default().fg(colors.muted)),
+            Span::styled("  |  ", Style::default().fg(colors.muted)),
+            Span::styled(core.status.as_str(), Style::default().fg(colors.text)),
+        ]))
         .block(panel_block(
-            "Selection",
+            "Message",
             colors.panel_bg,
             colors.text,
             colors.border,
-        ))
-        .alignment(Alignment::Left)
-        .wrap(Wrap { trim: true });
-    frame.render_widget(selection_block, vertical[4]);
-    register_selection_action_hits(vertical[4]);
-
-    let key_hint = if core.header_section == HeaderSection::Stats {
-        "Keys: Left/Right Focus, Enter Cycle, Type filters, Backspace Edit, Shift+Up Top"
-    } else if core.header_section == HeaderSection::Lyrics {
-        "Keys: Ctrl+E Edit/view, Up/Down Line, Enter New line, Ctrl+T Timestamp, / Actions"
-    } else if core.header_section == HeaderSection::Online {
-        "Keys: Enter Select/join, Ctrl+N Shared now, Ctrl+L Leave room"
-    } else {
-        "Keys: Enter Play, Backspace Back, Ctrl+F Search, / Actions, T Tray, Ctrl+C Quit"
-    };
-    let footer = Paragraph::new(Line::from(vec![
-        Span::styled(key_hint, Style::default().fg(colors.muted)),
-        Span::styled("  |  ", Style::default().fg(colors.muted)),
-        Span::styled(core.status.as_str(), Style::default().fg(colors.text)),
-    ]))
-    .block(panel_block(
-        "Message",
-        colors.panel_bg,
-        colors.text,
-        colors.border,
-    ));
-    frame.render_widget(footer, vertical[5]);
+        ));
+        frame.render_widget(footer, layout.footer);
+    }
 
     if let Some(panel) = action_panel {
         draw_action_panel(frame, panel, &colors);
@@ -1362,8 +1511,53 @@ fn draw_join_prompt(frame: &mut Frame, modal: &JoinPromptModalView, colors: &The
     );
 }
 
+/// Renders `code` as QR rows of half blocks, two modules per cell, with a
+/// quiet zone. Returns `None` when the code needs a QR too dense to scan.
+pub(crate) fn invite_qr_rows(code: &str) -> Option<Vec<String>> {
+    let qr = qrcode::QrCode::with_error_correction_level(code, qrcode::EcLevel::L).ok()?;
+    let modules = qr.width();
+    if modules > MAX_INVITE_QR_MODULES {
+        return None;
+    }
+    let size = modules + INVITE_QR_QUIET_ZONE * 2;
+    let dark = |x: usize, y: usize| {
+        let (Some(x), Some(y)) = (
+            x.checked_sub(INVITE_QR_QUIET_ZONE),
+            y.checked_sub(INVITE_QR_QUIET_ZONE),
+        ) else {
+            return false;
+        };
+        x < modules && y < modules && qr[(x, y)] == qrcode::Color::Dark
+    };
+    let rows = (0..size)
+        .step_by(2)
+        .map(|y| {
+            (0..size)
+                .map(|x| match (dark(x, y), dark(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                })
+                .collect()
+        })
+        .collect();
+    Some(rows)
+}
+
 fn draw_host_invite_modal(frame: &mut Frame, modal: &HostInviteModalView, colors: &ThemePalette) {
-    let popup = centered_rect(frame.area(), 54, 36);
+    let area = frame.area();
+    let qr_rows = modal
+        .qr_rows
+        .as_deref()
+        .filter(|_| modal.qr_only)
+        .filter(|rows| rows.len() as u16 + INVITE_QR_MODAL_CHROME_ROWS <= area.height);
+    if let Some(qr_rows) = qr_rows {
+        draw_host_invite_qr_modal(frame, modal, qr_rows, colors);
+        return;
+    }
+
+    let popup = centered_rect(area, 54, 36);
     frame.render_widget(Clear, popup);
     frame.render_widget(
         panel_block("Room Ready", colors.popup_bg, colors.text, colors.border),
@@ -1442,6 +1636,111 @@ fn draw_host_invite_modal(frame: &mut Frame, modal: &HostInviteModalView, colors
     );
 }
 
+fn draw_host_invite_qr_modal(
+    frame: &mut Frame,
+    modal: &HostInviteModalView,
+    qr_rows: &[String],
+    colors: &ThemePalette,
+) {
+    let area = frame.area();
+    let qr_width = qr_rows.first().map_or(0, |row| row.chars().count()) as u16;
+    let width = qr_width.saturating_add(6).max(58).min(area.width);
+    let height = (qr_rows.len() as u16 + INVITE_QR_MODAL_CHROME_ROWS - 2).min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        panel_block("Room Ready", colors.popup_bg, colors.text, colors.border),
+        popup,
+    );
+
+    let inner = popup.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    let button_style = |selected: bool| {
+        if selected {
+            Style::default()
+                .fg(colors.text)
+                .bg(colors.popup_selected_bg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(colors.muted)
+        }
+    };
+    // Dark modules on a light background, whatever the theme, so phone
+    // cameras read it.
+    let qr_style = Style::default().fg(Color::Black).bg(Color::White);
+    let code_line = if modal.code_revealed {
+        Line::from(Span::styled(
+            modal.invite_code.as_str(),
+            Style::default()
+                .fg(colors.accent)
+                .add_modifier(Modifier::BOLD),
+        ))
+    } else {
+        Line::from(Span::styled(
+            "Code hidden. 1 shows it",
+            Style::default().fg(colors.muted),
+        ))
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Scan to join",
+            Style::default().fg(colors.muted),
+        )),
+        Line::from(""),
+    ];
+    lines.extend(
+        qr_rows
+            .iter()
+            .map(|row| Line::from(Span::styled(row.as_str(), qr_style))),
+    );
+    lines.extend([
+        Line::from(""),
+        code_line,
+        Line::from(""),
+        Line::from(Span::styled(
+            "[ Copy to clipboard ]",
+            button_style(modal.copy_selected),
+        )),
+        Line::from(""),
+        Line::from(Span::styled("[ OK ]", button_style(!modal.copy_selected))),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Use Up/Down or Tab. Enter activates selected button.",
+            Style::default().fg(colors.muted),
+        )),
+    ]);
+    // No wrapping: trimming would eat the quiet zone.
+    frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), inner);
+
+    let copy_y = inner.y + qr_rows.len() as u16 + 5;
+    hit_map_push(
+        Rect {
+            x: inner.x,
+            y: copy_y,
+            width: inner.width,
+            height: 1,
+        },
+        HitTarget::HostInviteCopy,
+    );
+    hit_map_push(
+        Rect {
+            x: inner.x,
+            y: copy_y + 2,
+            width: inner.width,
+            height: 1,
+        },
+        HitTarget::HostInviteOk,
+    );
+}
+
 fn header_tabs_line(selected: HeaderSection, colors: &ThemePalette) -> Line<'static> {
     let mut spans = Vec::new();
 
@@ -1616,12 +1915,13 @@ fn draw_online_section(
     let quality_badge = format!(" Q Stream Quality: {} ", session.quality.label());
     let toggle_badge = room_field_secret.then(|| {
         if overlays.room_code_revealed {
-            " T Hide ".to_string()
+            " 1 Hide ".to_string()
         } else {
-            " T Show ".to_string()
+            " 1 Show ".to_string()
         }
     });
     let copy_badge = " 2 Copy ".to_string();
+    let qr_badge = " 3 QR ".to_string();
     let room_field_label = format!("{room_field_label} ");
 
     let mut left_lines = vec![Line::from(vec![
@@ -1662,12 +1962,25 @@ fn draw_online_section(
             copy_badge.clone(),
             Style::default().fg(colors.text).bg(copy_bg),
         ));
+        line.spans
+            .push(Span::styled("  ", Style::default().fg(colors.muted)));
+        line.spans.push(Span::styled(
+            qr_badge.clone(),
+            Style::default().fg(colors.text).bg(copy_bg),
+        ));
     }
 
+    if let Some(status) = overlays.online_status {
+        left_lines.push(online_status_line(status, session, &colors));
+    }
+    let skip_votes = if session.current_transport_path().is_some() {
+        format!("  {} (s)", session.skip_vote_label())
+    } else {
+        String::new()
+    };
     left_lines.push(Line::from(Span::styled(
         format!(
-            "Peers {}  Shared queue {}  Drift {}ms",
-            session.participants.len(),
+            "Shared queue {}  Drift {}ms{skip_votes}",
             session.shared_queue.len(),
             session.last_sync_drift_ms
         ),
@@ -1676,7 +1989,7 @@ fn draw_online_section(
 
     left_lines.push(Line::from(""));
     left_lines.push(Line::from(Span::styled(
-        "Participants",
+        participant_table_row("Participants", "Ping", "Delay man/eff", "Drift"),
         Style::default()
             .fg(colors.text)
             .add_modifier(Modifier::BOLD),
@@ -1688,6 +2001,13 @@ fn draw_online_section(
         )));
     }
 
+    let left_split = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((left_lines.len() as u16).saturating_add(2)),
+            Constraint::Min(ONLINE_CHAT_MIN_HEIGHT),
+        ])
+        .split(horizontal[0]);
     let left = Paragraph::new(left_lines)
         .block(panel_block(
             "Online Session",
@@ -1696,12 +2016,13 @@ fn draw_online_section(
             colors.border,
         ))
         .wrap(Wrap { trim: true });
-    frame.render_widget(left, horizontal[0]);
+    frame.render_widget(left, left_split[0]);
+    draw_online_chat(frame, left_split[1], colors, session, overlays.chat_input);
 
     // Register mouse hit targets for online session controls.
-    let inner_x = horizontal[0].x.saturating_add(1);
-    let inner_y = horizontal[0].y.saturating_add(1);
-    let inner_width = horizontal[0].width.saturating_sub(2);
+    let inner_x = left_split[0].x.saturating_add(1);
+    let inner_y = left_split[0].y.saturating_add(1);
+    let inner_width = left_split[0].width.saturating_sub(2);
 
     let mode_badge_width = mode_badge.chars().count() as u16;
     let sep_width = 5u16; // "  |  "
@@ -1737,6 +2058,7 @@ fn draw_online_section(
         .map(|badge| badge.chars().count() as u16)
         .unwrap_or(0);
     let copy_width = copy_badge.chars().count() as u16;
+    let qr_width = qr_badge.chars().count() as u16;
     let line1_total = label_width
         + code_width
         + if toggle_badge.is_some() {
@@ -1745,7 +2067,9 @@ fn draw_online_section(
             0
         }
         + 2 // "  "
-        + copy_width;
+        + copy_width
+        + 2
+        + qr_width;
     if line1_total <= inner_width {
         let mut x = inner_x;
         x = x.saturating_add(label_width);
@@ -1782,6 +2106,16 @@ fn draw_online_section(
             },
             HitTarget::CopyRoomCode,
         );
+        x = x.saturating_add(copy_width + 2);
+        hit_map_push(
+            Rect {
+                x,
+                y: inner_y.saturating_add(1),
+                width: qr_width,
+                height: 1,
+            },
+            HitTarget::ShowRoomQr,
+        );
     }
 
     let mut right_lines = Vec::new();
@@ -1812,26 +2146,63 @@ fn draw_online_section(
     }
 
     right_lines.push(Line::from(Span::styled(
-        "Shared Queue",
+        format!("Shared Queue ({})", session.shared_queue.len()),
         Style::default()
             .fg(colors.text)
             .add_modifier(Modifier::BOLD),
     )));
-    for (index, item) in session.shared_queue.iter().rev().take(10).enumerate() {
+    // Keep the networking notes below the queue visible; the queue gets the rest.
+    let reserved_rows = right_lines.len() + 8;
+    let queue_rows = usize::from(horizontal[1].height)
+        .saturating_sub(reserved_rows)
+        .max(3);
+    let (start, end) = shared_queue_window(
+        session.shared_queue.len(),
+        core.online_queue_scroll,
+        queue_rows,
+    );
+    let playing_path = online_current_transport_path(session);
+    for (index, item) in session
+        .shared_queue
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(end - start)
+    {
         let owner_suffix = item
             .owner_nickname
             .as_deref()
             .filter(|owner| !owner.is_empty())
             .map(|owner| format!(" @{}", truncate_for_line(owner, 12)))
             .unwrap_or_default();
+        let playing = playing_path == Some(item.path.as_path());
+        let style = if playing {
+            Style::default()
+                .fg(colors.accent)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(colors.muted)
+        };
         right_lines.push(Line::from(Span::styled(
             format!(
-                "{:>2}. {}{} [{}]",
+                "{}{:>2}. {}{} [{}]",
+                if playing { "> " } else { "  " },
                 index + 1,
                 truncate_for_line(&item.title, 28),
                 owner_suffix,
                 item.delivery.label()
             ),
+            style,
+        )));
+    }
+    if end - start < session.shared_queue.len() {
+        right_lines.push(Line::from(Span::styled(
+            format!(
+                "{}-{} of {}  Up/Down to scroll",
+                start + 1,
+                end,
+                session.shared_queue.len()
+            ),
             Style::default().fg(colors.muted),
         )));
     }
@@ -1872,6 +2243,127 @@ fn draw_online_section(
     frame.render_widget(right, horizontal[1]);
 }
 
+/// Chat panel rows including its border: a few messages plus the input line.
+const ONLINE_CHAT_MIN_HEIGHT: u16 = 6;
+
+fn draw_online_chat(
+    frame: &mut Frame,
+    area: Rect,
+    colors: ThemePalette,
+    session: &OnlineSession,
+    chat_input: Option<&str>,
+) {
+    let width = usize::from(area.width.saturating_sub(2)).max(1);
+    let message_rows = usize::from(area.height.saturating_sub(3));
+    let mut lines = chat_lines(&session.chat_log, width, &colors);
+    if session.chat_log.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No messages yet.",
+            Style::default().fg(colors.muted),
+        )));
+    }
+    let mut lines = lines.split_off(lines.len().saturating_sub(message_rows));
+    lines.resize(message_rows, Line::from(""));
+
+    lines.push(match chat_input {
+        Some(draft) => {
+            let visible = width.saturating_sub(3);
+            let skip = draft.chars().count().saturating_sub(visible);
+            Line::from(vec![
+                Span::styled("> ", Style::default().fg(colors.accent)),
+                Span::styled(
+                    format!("{}_", draft.chars().skip(skip).collect::<String>()),
+                    Style::default().fg(colors.text),
+                ),
+            ])
+        }
+        None => Line::from(Span::styled(
+            "Press c to chat",
+            Style::default().fg(colors.muted),
+        )),
+    });
+
+    let chat = Paragraph::new(lines).block(panel_block(
+        "Chat",
+        colors.content_panel_bg,
+        colors.text,
+        colors.border,
+    ));
+    frame.render_widget(chat, area);
+}
+
+/// Chat log wrapped by hand to `width` columns so the newest messages can be
+/// pinned to the bottom of the pane. Names and text are sanitized again here
+/// because the log arrives from other peers.
+fn chat_lines(
+    log: &std::collections::VecDeque<ChatMessage>,
+    width: usize,
+    colors: &ThemePalette,
+) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for entry in log {
+        let from = truncate_for_line(&crate::config::sanitize_display_text(&entry.from), 16);
+        let prefix = format!("{from}: ");
+        let message: Vec<char> = crate::config::sanitize_display_text(&entry.message)
+            .chars()
+            .collect();
+        let first_len = width.saturating_sub(prefix.chars().count()).max(1);
+        let first: String = message.iter().take(first_len).collect();
+        lines.push(Line::from(vec![
+            Span::styled(
+                prefix,
+                Style::default()
+                    .fg(colors.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(first, Style::default().fg(colors.text)),
+        ]));
+        for chunk in message.get(first_len..).unwrap_or_default().chunks(width) {
+            lines.push(Line::from(Span::styled(
+                chunk.iter().collect::<String>(),
+                Style::default().fg(colors.text),
+            )));
+        }
+    }
+    lines
+}
+
+fn online_status_line(
+    status: &OnlineStatusView,
+    session: &OnlineSession,
+    colors: &ThemePalette,
+) -> Line<'static> {
+    let connection_color = if status.connection == "Connected" {
+        colors.accent
+    } else {
+        colors.alert
+    };
+    let peers = session.participants.len();
+    Line::from(vec![
+        Span::styled(
+            status.role.clone(),
+            Style::default()
+                .fg(colors.text)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("  ", Style::default().fg(colors.muted)),
+        Span::styled(
+            status.connection.clone(),
+            Style::default()
+                .fg(connection_color)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(
+                "  {}  {peers} {}",
+                status.server_addr,
+                if peers == 1 { "peer" } else { "peers" }
+            ),
+            Style::default().fg(colors.muted),
+        ),
+    ])
+}
+
 fn participant_line(participant: &crate::online::Participant, session: &OnlineSession) -> String {
     let mut parts = Vec::with_capacity(5);
     if participant.is_local {
@@ -1888,24 +2380,53 @@ fn participant_line(participant: &crate::online::Participant, session: &OnlineSe
     } else {
         format!(" ({})", parts.join(", "))
     };
+    let drift = participant
+        .last_drift_ms
+        .map_or_else(|| String::from("-"), |drift| format!("{drift:+}ms"));
+    participant_table_row(
+        &format!("{}{}", participant.nickname, tags),
+        &format!("{}ms", participant.ping_ms),
+        &format!(
+            "{}/{}ms",
+            participant.manual_extra_delay_ms,
+            participant.effective_delay_ms()
+        ),
+        &drift,
+    )
+}
+
+/// Fixed-width columns so the participant rows line up under the heading.
+/// The heading and rows start with text because the panel trims leading
+/// spaces.
+fn participant_table_row(name: &str, ping: &str, delay: &str, drift: &str) -> String {
     format!(
-        "- {}{}  ping {}ms",
-        participant.nickname, tags, participant.ping_ms
+        "{:<24}{ping:>7}{delay:>15}{drift:>9}",
+        truncate_for_line(name, 23)
     )
 }
 
+/// Visible `start..end` range of a queue of `len` items scrolled to `scroll`.
+fn shared_queue_window(len: usize, scroll: usize, rows: usize) -> (usize, usize) {
+    let start = scroll.min(len.saturating_sub(rows));
+    (start, (start + rows).min(len))
+}
+
+fn online_current_transport_path(session: &OnlineSession) -> Option<&Path> {
+    match &session.last_transport.as_ref()?.command {
+        crate::online::TransportCommand::PlayTrack { path, .. }
+        | crate::online::TransportCommand::SetPlaybackState { path, .. } => Some(path.as_path()),
+        crate::online::TransportCommand::StopPlayback
+        | crate::online::TransportCommand::SetPaused { .. } => None,
+    }
+}
+
 fn shared_queue_waiting_message(session: &OnlineSession) -> Option<String> {
     let next_shared_path = session
         .shared_queue
         .front()
         .map(|item| item.path.as_path())?;
     let last_transport = session.last_transport.as_ref()?;
-    let current_path = match &last_transport.command {
-        crate::online::TransportCommand::PlayTrack { path, .. }
-        | crate::online::TransportCommand::SetPlaybackState { path, .. } => path.as_path(),
-        crate::online::TransportCommand::StopPlayback
-        | crate::online::TransportCommand::SetPaused { .. } => return None,
-    };
+    let current_path = online_current_transport_path(session)?;
 
     if current_path == next_shared_path {
         return None;
@@ -1957,7 +2478,7 @@ fn draw_lyrics_section(
 
     let Some(doc) = core.lyrics.as_ref() else {
         let message = if core.lyrics_missing_prompt {
-            "No lyrics found for this track. Enter creates an empty .lrc, Backspace skips."
+            "No lyrics found for this track. Enter creates an empty .lrc, F fetches online, Backspace skips."
         } else {
             "No lyrics loaded. Play a track, import TXT via /, or create a sidecar in this tab."
         };
@@ -2010,9 +2531,10 @@ fn draw_lyrics_section(
     let mut right_lines = vec![Line::from(Span::styled(
         format!(
             "Mode {}  Source {:?}  Timing {:?}",
-            match core.lyrics_mode {
-                LyricsMode::View => "View",
-                LyricsMode::Edit => "Edit",
+            match (core.lyrics_mode, core.lyrics_unsaved) {
+                (LyricsMode::View, _) => "View",
+                (LyricsMode::Edit, false) => "Edit",
+                (LyricsMode::Edit, true) => "Edit (unsaved)",
             },
             doc.source,
             doc.precision
@@ -2180,6 +2702,13 @@ fn draw_stats_section(
             matches!(core.stats_focus, StatsFilterFocus::Sort(1)),
             &colors,
         ),
+        Span::raw(" "),
+        stats_choice_box(
+            "Skips",
+            core.stats_sort == StatsSort::SkipRate,
+            matches!(core.stats_focus, StatsFilterFocus::Sort(2)),
+            &colors,
+        ),
     ]));
 
     left_lines.push(Line::from(vec![
@@ -2207,14 +2736,26 @@ fn draw_stats_section(
 
     left_lines.push(Line::from(Span::styled(
         format!(
-            "Total plays {}  Total listen {}",
+            "Total plays {}  Total listen {}  Days listened {}",
             snapshot.total_plays,
-            format_seconds(snapshot.total_listen_seconds)
+            format_seconds(snapshot.total_listen_seconds),
+            snapshot.days_listened
         ),
         Style::default()
             .fg(colors.accent)
             .add_modifier(Modifier::BOLD),
     )));
+    left_lines.push(Line::from(Span::styled(
+        streak_line(&snapshot.streak),
+        Style::default().fg(colors.text),
+    )));
+    if core.daily_listen_goal_minutes > 0 {
+        left_lines.push(daily_goal_line(
+            snapshot.today_listen_seconds,
+            core.daily_listen_goal_minutes,
+            &colors,
+        ));
+    }
     left_lines.push(Line::from(""));
 
     left_lines.push(Line::from(Span::styled(
@@ -2232,12 +2773,16 @@ fn draw_stats_section(
     }
     left_lines.push(Line::from(""));
 
-    let metric_label = match core.stats_sort {
-        StatsSort::Plays => "plays",
-        StatsSort::ListenTime => "listen",
+    let heading = match core.stats_sort {
+        StatsSort::Plays => String::from("Top songs by plays"),
+        StatsSort::ListenTime => String::from("Top songs by listen"),
+        StatsSort::SkipRate => format!(
+            "Most skipped ({}+ starts)",
+            crate::stats::MIN_STARTS_FOR_SKIP_RATE
+        ),
     };
     left_lines.push(Line::from(Span::styled(
-        format!("Top songs by {metric_label}"),
+        heading,
         Style::default()
             .fg(colors.text)
             .add_modifier(Modifier::BOLD),
@@ -2245,9 +2790,13 @@ fn draw_stats_section(
 
     let top_songs_limit = usize::from(core.stats_top_songs_count.max(1));
     for (index, row) in snapshot.rows.iter().take(top_songs_limit).enumerate() {
+        let skip_percent = |row: &crate::stats::TrackStatsRow| {
+            (row.skip_rate().unwrap_or_default() * 100.0).round() as u64
+        };
         let value = match core.stats_sort {
             StatsSort::Plays => row.play_count,
             StatsSort::ListenTime => row.listen_seconds,
+            StatsSort::SkipRate => skip_percent(row),
         };
         let top_value = snapshot
             .rows
@@ -2255,21 +2804,45 @@ fn draw_stats_section(
             .map(|first| match core.stats_sort {
                 StatsSort::Plays => first.play_count,
                 StatsSort::ListenTime => first.listen_seconds,
+                // Rates share one scale so the bars compare across rows.
+                StatsSort::SkipRate => 100,
             })
             .unwrap_or(0)
             .max(1);
         let title = truncate_for_line(&row.title, 22);
         let bar = unicode_bar(value, top_value, 14);
-        let details = format!("{}P {}", row.play_count, format_seconds(row.listen_seconds));
+        let details = match core.stats_sort {
+            StatsSort::SkipRate => format!(
+                "{}% skipped ({}/{})",
+                skip_percent(row),
+                row.skips,
+                row.starts
+            ),
+            StatsSort::Plays | StatsSort::ListenTime => {
+                format!("{}P {}", row.play_count, format_seconds(row.listen_seconds))
+            }
+        };
+        let style = if core.stats_focus == StatsFilterFocus::Row(index) {
+            Style::default()
+                .fg(colors.text)
+                .bg(colors.selected_bg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(colors.text)
+        };
         left_lines.push(Line::from(Span::styled(
             format!("{:>2}. {:<22} {} {}", index + 1, title, bar, details),
-            Style::default().fg(colors.text),
+            style,
         )));
     }
 
     if snapshot.rows.is_empty() {
         left_lines.push(Line::from(Span::styled(
-            "No stats for current filters.",
+            if core.stats_sort == StatsSort::SkipRate {
+                "No tracks started often enough for current filters."
+            } else {
+                "No stats for current filters."
+            },
             Style::default().fg(colors.muted),
         )));
     }
@@ -2320,7 +2893,7 @@ fn draw_stats_section(
     if line1 >= scroll {
         let y = inner_y + (line1 - scroll) as u16;
         let mut x = inner_x + 6; // "Sort  "
-        for (idx, label) in [(0, "Listen"), (1, "Plays")] {
+        for (idx, label) in [(0, "Listen"), (1, "Plays"), (2, "Skips")] {
             let w = stats_choice_width(label) as u16;
             hit_map_push(
                 Rect {
@@ -2784,14 +3357,9 @@ fn weekday_short(day: time::Weekday) -> &'static str {
     }
 }
 
-fn local_utc_offset() -> UtcOffset {
-    static LOCAL_OFFSET: OnceLock<UtcOffset> = OnceLock::new();
-    *LOCAL_OFFSET.get_or_init(|| UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC))
-}
-
 fn short_metric_label(value: u64, sort: StatsSort) -> String {
     match sort {
-        StatsSort::Plays => format!("{value}p"),
+        StatsSort::Plays | StatsSort::SkipRate => format!("{value}p"),
         StatsSort::ListenTime => {
             if value >= 7_200 {
                 format!("{:.1}h", value as f64 / 3_600.0)
@@ -2851,6 +3419,44 @@ fn truncate_for_line(input: &str, max_chars: usize) -> String {
     out
 }
 
+fn streak_line(streak: &ListeningStreak) -> String {
+    let days = |count: u32| {
+        if count == 1 {
+            String::from("1 day")
+        } else {
+            format!("{count} days")
+        }
+    };
+    format!(
+        "Streak {} (best {})  Today {}  This week {}",
+        days(streak.current_days),
+        days(streak.longest_days),
+        format_seconds(streak.today_seconds),
+        format_seconds(streak.week_seconds)
+    )
+}
+
+fn daily_goal_line(today_seconds: u64, goal_minutes: u16, colors: &ThemePalette) -> Line<'static> {
+    let goal_seconds = u64::from(goal_minutes) * 60;
+    let ratio = today_seconds as f64 / goal_seconds.max(1) as f64;
+    let mut spans = vec![Span::styled(
+        format!(
+            "Today {} / {} {}",
+            format_seconds(today_seconds),
+            daily_listen_goal_label(goal_minutes),
+            progress_bar(Some(ratio), 16)
+        ),
+        Style::default().fg(colors.text),
+    )];
+    if today_seconds >= goal_seconds {
+        spans.push(Span::styled(
+            "  goal met",
+            Style::default().fg(colors.muted),
+        ));
+    }
+    Line::from(spans)
+}
+
 fn format_seconds(seconds: u64) -> String {
     let hours = seconds / 3600;
     let mins = (seconds % 3600) / 60;
@@ -2881,6 +3487,45 @@ fn format_recent_event(event: &ListenEvent, now_epoch_seconds: i64) -> String {
     )
 }
 
+/// Splits breadcrumbs into clickable crumbs (with their depth) and separators.
+/// When too wide, crumbs after "Library" are replaced by "…" from the middle out,
+/// always keeping the current location visible.
+fn breadcrumb_segments(crumbs: &[String], max_chars: usize) -> Vec<(Option<usize>, String)> {
+    const SEPARATOR: &str = " > ";
+    let width = |keep: &[usize], elided: bool| -> usize {
+        keep.iter()
+            .map(|&index| crumbs[index].chars().count())
+            .sum::<usize>()
+            + SEPARATOR.len() * keep.len().saturating_sub(1)
+            + if elided { 1 + SEPARATOR.len() } else { 0 }
+    };
+
+    let mut keep: Vec<usize> = (0..crumbs.len()).collect();
+    let mut elided = false;
+    while keep.len() > 2 && width(&keep, elided) > max_chars {
+        keep.remove(keep.len() / 2);
+        elided = true;
+    }
+
+    let mut segments = Vec::new();
+    for (position, &index) in keep.iter().enumerate() {
+        if position > 0 {
+            if elided && index != keep[position - 1] + 1 {
+                segments.push((None, format!("{SEPARATOR}…")));
+            }
+            segments.push((None, String::from(SEPARATOR)));
+        }
+        let text = if index + 1 == crumbs.len() && position > 0 {
+            let used = width(&keep[..position], elided) + SEPARATOR.len();
+            truncate_for_line(&crumbs[index], max_chars.saturating_sub(used).max(4))
+        } else {
+            crumbs[index].clone()
+        };
+        segments.push((Some(index), text));
+    }
+    segments
+}
+
 fn panel_block(title: &str, bg: Color, text: Color, border: Color) -> Block<'_> {
     Block::default()
         .borders(Borders::ALL)
@@ -2892,15 +3537,101 @@ fn panel_block(title: &str, bg: Color, text: Color, border: Color) -> Block<'_>
         .style(Style::default().bg(bg))
 }
 
+/// The Timeline panel is drawn in every header section, so its title doubles as a
+/// compact now-playing bar.
+fn mini_bar_title(core: &TuneCore, audio: &dyn AudioEngine, max_chars: usize) -> String {
+    let Some(path) = audio.current_track() else {
+        return String::from("Timeline");
+    };
+    let state = if audio.is_paused() {
+        "Paused"
+    } else {
+        "Playing"
+    };
+    let title = core.title_for_path(path).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("-"))
+    });
+    let artist = core.artist_for_path(path);
+    let album = core.album_for_path(path);
+    let info = render_now_playing_template(&core.now_playing_template, &title, artist, album);
+    truncate_for_line(&format!("{state}: {info}"), max_chars.max(8))
+}
+
+/// Expands `{title}`, `{artist}`, and `{album}`. Each placeholder owns the
+/// literal text before it and any closing brackets right after it, so a
+/// missing field drops its separators too. Unknown placeholders stay literal.
+fn render_now_playing_template(
+    template: &str,
+    title: &str,
+    artist: Option<&str>,
+    album: Option<&str>,
+) -> String {
+    const CLOSING: &[char] = &[')', ']', '}', '>'];
+    let mut literals = Vec::new();
+    let mut literal = String::new();
+    let mut fields: Vec<Option<&str>> = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let end = start + len;
+        let value = match &rest[start + 1..end] {
+            "title" => Some(title),
+            "artist" => artist,
+            "album" => album,
+            _ => {
+                literal.push_str(&rest[..=end]);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        };
+        literal.push_str(&rest[..start]);
+        literals.push(std::mem::take(&mut literal));
+        fields.push(value.filter(|value| !value.trim().is_empty()));
+        rest = &rest[end + 1..];
+    }
+    literal.push_str(rest);
+    literals.push(literal);
+
+    let mut out = literals[0].clone();
+    let mut emitted = false;
+    for (index, value) in fields.iter().enumerate() {
+        let Some(value) = value else {
+            continue;
+        };
+        if emitted {
+            out.push_str(literals[index].trim_start_matches(CLOSING));
+        }
+        out.push_str(value);
+        let after = &literals[index + 1];
+        if index + 1 == fields.len() {
+            out.push_str(after);
+        } else {
+            out.push_str(&after[..after.len() - after.trim_start_matches(CLOSING).len()]);
+        }
+        emitted = true;
+    }
+    if emitted {
+        out.trim().to_string()
+    } else {
+        title.to_string()
+    }
+}
+
 fn draw_timeline_panel(
     frame: &mut Frame,
     area: Rect,
     core: &TuneCore,
     audio: &dyn AudioEngine,
+    buffering: Option<&BufferingView>,
     colors: &ThemePalette,
 ) {
+    let title = mini_bar_title(core, audio, usize::from(area.width.saturating_sub(4)));
     frame.render_widget(
-        panel_block("Timeline", colors.panel_bg, colors.text, colors.border),
+        panel_block(&title, colors.panel_bg, colors.text, colors.border),
         area,
     );
 
@@ -2925,11 +3656,12 @@ fn draw_timeline_panel(
             width: timeline_width,
             height: inner.height,
         };
+        let (text, color) = match buffering {
+            Some(view) => (buffering_line(view, timeline_bar_width), colors.alert),
+            None => (timeline_line(audio, timeline_bar_width), colors.text),
+        };
         frame.render_widget(
-            Paragraph::new(Span::styled(
-                timeline_line(audio, timeline_bar_width),
-                Style::default().fg(colors.text),
-            )),
+            Paragraph::new(Span::styled(text, Style::default().fg(color))),
             timeline_area,
         );
     }
@@ -3367,7 +4099,7 @@ fn draw_action_panel(frame: &mut Frame, panel: &ActionPanelView, colors: &ThemeP
     }
 
     let has_search = panel.search_query.is_some();
-    let search_height = u16::from(has_search);
+    let search_height = u16::from(has_search) + u16::from(panel.drift_meter.is_some());
     let hint_height = 1;
     if inner.height <= search_height.saturating_add(hint_height) {
         return;
@@ -3392,6 +4124,15 @@ fn draw_action_panel(frame: &mut Frame, panel: &ActionPanelView, colors: &ThemeP
             search_area,
         );
     }
+    if let Some(meter) = &panel.drift_meter {
+        let meter_area = Rect {
+            x: inner.x,
+            y: inner.y.saturating_add(u16::from(has_search)),
+            width: inner.width,
+            height: 1,
+        };
+        frame.render_widget(Paragraph::new(sync_drift_line(meter, colors)), meter_area);
+    }
 
     let list_y = inner.y.saturating_add(search_height);
     let show_scrollbar = list_overflows(panel.options.len(), list_height_usize) && inner.width > 1;
@@ -3487,6 +4228,49 @@ fn draw_action_panel(frame: &mut Frame, panel: &ActionPanelView, colors: &ThemeP
     );
 }
 
+/// `Drift +42ms behind host [────────│──█──────]`: the marker sits right of
+/// center when local playback trails the host, and the scale spans twice the
+/// sync correction threshold each way.
+fn sync_drift_line(meter: &SyncDriftMeter, colors: &ThemePalette) -> Line<'static> {
+    const HALF_WIDTH: i64 = 10;
+    let drift = i64::from(meter.drift_ms);
+    let scale = (i64::from(meter.threshold_ms) * 2).max(1);
+    let offset = (drift * HALF_WIDTH / scale).clamp(-HALF_WIDTH, HALF_WIDTH);
+    let marker = (HALF_WIDTH + offset) as usize;
+    let bar: String = (0..=(HALF_WIDTH * 2) as usize)
+        .map(|idx| {
+            if idx == marker {
+                '█'
+            } else if idx == HALF_WIDTH as usize {
+                '│'
+            } else {
+                '─'
+            }
+        })
+        .collect();
+    let direction = match drift {
+        -10..=10 => "in sync",
+        drift if drift > 0 => "behind host",
+        _ => "ahead of host",
+    };
+    let value_color = if drift.abs() < i64::from(meter.threshold_ms) {
+        colors.accent
+    } else {
+        colors.alert
+    };
+    Line::from(vec![
+        Span::styled("Drift ", Style::default().fg(colors.muted)),
+        Span::styled(
+            format!("{:+}ms", meter.drift_ms),
+            Style::default()
+                .fg(value_color)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(format!(" {direction} "), Style::default().fg(colors.text)),
+        Span::styled(format!("[{bar}]"), Style::default().fg(value_color)),
+    ])
+}
+
 fn action_panel_option_item(
     panel: &ActionPanelView,
     item: &str,
@@ -3533,6 +4317,7 @@ fn action_panel_section_name(item: &str) -> Option<&'static str> {
         "Stats" => Some("Stats"),
         "Window" => Some("Window"),
         "Lyrics" => Some("Lyrics"),
+        "Online" => Some("Online"),
         "Actions" => Some("Actions"),
         _ => None,
     }
@@ -3549,6 +4334,7 @@ fn action_panel_section_bg(section: &str) -> Option<Color> {
         "Stats" => Some(Color::Rgb(105, 76, 37)),
         "Window" => Some(Color::Rgb(76, 69, 58)),
         "Lyrics" => Some(Color::Rgb(90, 55, 55)),
+        "Online" => Some(Color::Rgb(37, 105, 75)),
         "Actions" => Some(Color::Rgb(80, 60, 112)),
         _ => None,
     }
@@ -3983,22 +4769,70 @@ fn progress_bar(ratio: Option<f64>, width: usize) -> String {
     bar
 }
 
+fn buffering_line(view: &BufferingView, bar_width: usize) -> String {
+    const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+    match view.total_bytes.filter(|total| *total > 0) {
+        Some(total) => {
+            let ratio = (view.received_bytes as f64 / total as f64).clamp(0.0, 1.0);
+            format!(
+                "Buffering… {:>3}% {}",
+                (ratio * 100.0).floor() as u8,
+                progress_bar(Some(ratio), bar_width)
+            )
+        }
+        None => {
+            let frame = SPINNER[((view.received_bytes / 16_384) % 4) as usize];
+            format!(
+                "Buffering… {frame} {} KB",
+                view.received_bytes.div_ceil(1024)
+            )
+        }
+    }
+}
+
+/// Times are shown as wall-clock listening time, so at 1.5x a 30 minute episode
+/// reads 20:00 and the speed is tagged after the total.
 fn timeline_line(audio: &dyn AudioEngine, timeline_bar_width: usize) -> String {
-    let elapsed = audio.position().unwrap_or(Duration::from_secs(0));
-    let total = audio.duration();
+    let speed = audio.playback_speed();
+    let wall_clock = |time: Duration| time.div_f32(speed.max(f32::EPSILON));
+    let elapsed = wall_clock(audio.position().unwrap_or(Duration::from_secs(0)));
+    let total = audio.duration().map(wall_clock);
     let ratio = total.and_then(|duration| {
         let total_secs = duration.as_secs_f64();
         (total_secs > 0.0).then_some((elapsed.as_secs_f64() / total_secs).clamp(0.0, 1.0))
     });
 
-    format!(
-        "{} / {} {}",
-        format_duration(elapsed),
-        total
-            .map(format_duration)
-            .unwrap_or_else(|| String::from("--:--")),
-        progress_bar(ratio, timeline_bar_width),
-    )
+    let mut total_label = match total {
+        Some(duration) => format_duration(duration),
+        None if audio.seek_pending() => String::from("--:-- (seek pending)"),
+        None => String::from("--:--"),
+    };
+    let mut bar_width = timeline_bar_width;
+    if speed != 1.0 {
+        let tag = format!(" {}", playback_speed_label(speed));
+        bar_width = bar_width.saturating_sub(tag.len()).max(4);
+        total_label.push_str(&tag);
+    }
+    let mut bar = progress_bar(ratio, bar_width);
+    if let Some((start, end)) = audio.ab_loop()
+        && let Some(duration) = audio.duration().filter(|duration| !duration.is_zero())
+    {
+        let at = |time: Duration| time.as_secs_f64() / duration.as_secs_f64();
+        bar = mark_progress_bar(&bar, bar_width, &[at(start), at(end)]);
+    }
+    format!("{} / {} {}", format_duration(elapsed), total_label, bar)
+}
+
+/// Replaces the cells of a `progress_bar` at each ratio with a thin marker.
+fn mark_progress_bar(bar: &str, width: usize, ratios: &[f64]) -> String {
+    let mut cells: Vec<char> = bar.chars().collect();
+    for ratio in ratios {
+        let cell = ((ratio.clamp(0.0, 1.0) * width as f64) as usize).min(width.saturating_sub(1));
+        if let Some(slot) = cells.get_mut(cell + 1) {
+            *slot = '│';
+        }
+    }
+    cells.into_iter().collect()
 }
 
 fn register_control_line_hits(area: Rect, volume_bar_width: u16) {
@@ -4063,15 +4897,19 @@ fn control_line(
     volume_bar_width: usize,
     colors: &ThemePalette,
 ) -> Line<'static> {
-    let volume_percent = (audio.volume() * 100.0).round() as u16;
+    // "Mute" keeps the width of "100%" so the badge hit areas stay put.
+    let volume_label = if audio.is_muted() {
+        String::from("Mute")
+    } else {
+        format!("{:>3}%", (audio.volume() * 100.0).round() as u16)
+    };
     let volume_ratio = audio.volume().clamp(0.0, 1.0) as f64;
     let mut spans = Vec::with_capacity(10);
 
     spans.push(Span::styled(
         format!(
-            "Vol {} {:>3}%  ",
+            "Vol {} {volume_label}  ",
             progress_bar(Some(volume_ratio), volume_bar_width),
-            volume_percent
         ),
         Style::default().fg(colors.text),
     ));
@@ -4305,6 +5143,107 @@ mod tests {
         assert!(!list_overflows(5, 5));
     }
 
+    #[test]
+    fn compact_layout_follows_size_unless_chosen() {
+        assert!(compact_layout_active(Rect::new(0, 0, 80, 24), None));
+        assert!(!compact_layout_active(Rect::new(0, 0, 160, 48), None));
+        assert!(compact_layout_active(Rect::new(0, 0, 160, 48), Some(true)));
+        assert!(!compact_layout_active(Rect::new(0, 0, 80, 24), Some(false)));
+
+        let area = Rect::new(0, 0, 80, 24);
+        assert_eq!(
+            library_rect(area, true, HeaderSection::Library),
+            Rect::new(0, 1, 80, 19)
+        );
+    }
+
+    #[test]
+    fn draw_survives_tiny_terminals_in_both_layouts() {
+        use crate::audio::NullAudioEngine;
+        use crate::model::PersistedState;
+        use ratatui::Terminal;
+        use ratatui::backend::TestBackend;
+
+        let mut core = TuneCore::from_persisted(PersistedState::default());
+        let audio = NullAudioEngine::new();
+        for (compact, section) in [Some(true), Some(false)].into_iter().flat_map(|compact| {
+            [
+                HeaderSection::Library,
+                HeaderSection::Lyrics,
+                HeaderSection::Stats,
+                HeaderSection::Online,
+            ]
+            .map(|section| (compact, section))
+        }) {
+            core.compact_layout = compact;
+            core.header_section = section;
+            for (width, height) in [(80, 24), (20, 6), (1, 1), (0, 0)] {
+                let mut terminal = Terminal::new(TestBackend::new(width, height)).expect("term");
+                terminal
+                    .draw(|frame| {
+                        draw(
+                            frame,
+                            &core,
+                            &audio,
+                            None,
+                            None,
+                            OverlayViews {
+                                join_prompt_modal: None,
+                                room_directory_view: None,
+                                online_password_prompt: None,
+                                host_invite_modal: None,
+                                online_room_field: None,
+                                online_status: None,
+                                buffering: None,
+                                room_code_revealed: false,
+                                chat_input: None,
+                            },
+                        )
+                    })
+                    .expect("draw");
+            }
+        }
+    }
+
+    #[test]
+    fn invite_qr_rows_fit_short_codes_and_reject_long_ones() {
+        let rows = invite_qr_rows("T1ABCDEFGHIJKLMNOPQRSTUVWXYZ234567").expect("qr");
+        let width = rows[0].chars().count();
+        assert!(rows.iter().all(|row| row.chars().count() == width));
+        assert_eq!(rows.len(), width.div_ceil(2));
+        assert!(rows[0].chars().all(|ch| ch == ' '));
+
+        assert!(invite_qr_rows(&"X".repeat(200)).is_none());
+    }
+
+    #[test]
+    fn sync_drift_line_places_marker_by_direction() {
+        let colors = palette(Theme::Dark);
+        let text = |drift_ms| {
+            sync_drift_line(
+                &SyncDriftMeter {
+                    drift_ms,
+                    threshold_ms: 100,
+                },
+                &colors,
+            )
+            .spans
+            .iter()
+            .map(|span| span.content.to_string())
+            .collect::<String>()
+        };
+
+        assert_eq!(text(0), "Drift +0ms in sync [──────────█──────────]");
+        assert_eq!(
+            text(100),
+            "Drift +100ms behind host [──────────│────█─────]"
+        );
+        assert_eq!(
+            text(-5_000),
+            "Drift -5000ms ahead of host [█─────────│──────────]"
+        );
+    }
+
     #[test]
     fn action_panel_sections_have_distinct_backgrounds() {
         let sections = [
@@ -4317,6 +5256,7 @@ mod tests {
             "Stats",
             "Window",
             "Lyrics",
+            "Online",
             "Actions",
         ];
         let mut backgrounds = Vec::with_capacity(sections.len());
@@ -4335,6 +5275,91 @@ mod tests {
         assert_eq!(action_panel_section_name("(no matching actions)"), None);
     }
 
+    #[test]
+    fn mini_bar_title_shows_play_state_and_track() {
+        let core = TuneCore::from_persisted(crate::model::PersistedState::default());
+        let mut audio = crate::audio::NullAudioEngine::new();
+        assert_eq!(mini_bar_title(&core, &audio, 40), "Timeline");
+
+        audio
+            .play(std::path::Path::new("Some Song.mp3"))
+            .expect("play");
+        assert_eq!(mini_bar_title(&core, &audio, 40), "Playing: Some Song");
+        audio.pause();
+        assert_eq!(mini_bar_title(&core, &audio, 12), "Paused: Som~");
+    }
+
+    #[test]
+    fn now_playing_template_drops_missing_fields_with_separators() {
+        let template = "{artist} - {title} [{album}]";
+        assert_eq!(
+            render_now_playing_template(template, "Song", Some("Band"), Some("LP")),
+            "Band - Song [LP]"
+        );
+        assert_eq!(
+            render_now_playing_template(template, "Song", Some("Band"), None),
+            "Band - Song"
+        );
+        assert_eq!(
+            render_now_playing_template(template, "Song", None, Some("LP")),
+            "Song [LP]"
+        );
+        assert_eq!(
+            render_now_playing_template("{title} - {artist}", "Song", None, None),
+            "Song"
+        );
+        assert_eq!(
+            render_now_playing_template("{title} {year} {", "Song", None, None),
+            "Song {year} {"
+        );
+        assert_eq!(
+            render_now_playing_template("{title} ({album}) - {artist}", "Song", Some("Band"), None),
+            "Song - Band"
+        );
+        assert_eq!(
+            render_now_playing_template("{album}", "Song", None, None),
+            "Song"
+        );
+    }
+
+    #[test]
+    fn breadcrumb_segments_elide_the_middle_when_narrow() {
+        let crumbs: Vec<String> = ["Library", "Music", "Artist", "Album", "Disc 1"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let joined = |segments: Vec<(Option<usize>, String)>| {
+            segments
+                .into_iter()
+                .map(|(_, text)| text)
+                .collect::<String>()
+        };
+
+        assert_eq!(
+            joined(breadcrumb_segments(&crumbs, 80)),
+            "Library > Music > Artist > Album > Disc 1"
+        );
+        let narrow = breadcrumb_segments(&crumbs, 30);
+        assert_eq!(joined(narrow.clone()), "Library > Music > … > Disc 1");
+        let depths: Vec<usize> = narrow.iter().filter_map(|(depth, _)| *depth).collect();
+        assert_eq!(depths, vec![0, 1, 4]);
+    }
+
+    #[test]
+    fn buffering_line_shows_percent_or_spinner() {
+        let known = BufferingView {
+            received_bytes: 42,
+            total_bytes: Some(100),
+        };
+        assert!(buffering_line(&known, 10).starts_with("Buffering…  42% ["));
+
+        let unknown = BufferingView {
+            received_bytes: 20_000,
+            total_bytes: None,
+        };
+        assert_eq!(buffering_line(&unknown, 10), "Buffering… / 20 KB");
+    }
+
     #[test]
     fn timeline_line_only_shows_timeline_data() {
         let mut audio = crate::audio::NullAudioEngine::new();
@@ -4344,6 +5369,44 @@ mod tests {
         assert!(!line.contains("Vol"));
     }
 
+    #[test]
+    fn ab_loop_markers_land_on_the_matching_progress_cells() {
+        assert_eq!(mark_progress_bar("[░░░░]", 4, &[0.0, 0.5]), "[│░│░]");
+        assert_eq!(mark_progress_bar("[████]", 4, &[1.0]), "[███│]");
+    }
+
+    #[test]
+    fn timeline_line_shows_wall_clock_time_and_speed_when_sped_up() {
+        let mut audio = crate::audio::NullAudioEngine::new();
+        audio
+            .play(std::path::Path::new("missing.mp3"))
+            .expect("play");
+        audio.pause();
+        audio.seek_to(Duration::from_secs(90)).expect("seek");
+        audio.set_playback_speed(1.5);
+
+        let line = timeline_line(&audio, 10);
+        assert!(line.starts_with("01:00 / --:-- 1.5x"), "{line}");
+    }
+
+    #[test]
+    fn control_line_shows_mute_in_place_of_the_percentage() {
+        let mut audio = crate::audio::NullAudioEngine::new();
+        audio.set_volume(0.65);
+        let colors = palette(Theme::Dark);
+        let unmuted = control_line(&audio, 10, &colors).spans[0]
+            .content
+            .to_string();
+        audio.mute();
+        let muted = control_line(&audio, 10, &colors).spans[0]
+            .content
+            .to_string();
+
+        assert!(unmuted.contains(" 65%"));
+        assert!(muted.contains("Mute"));
+        assert_eq!(muted.chars().count(), unmuted.chars().count());
+    }
+
     #[test]
     fn control_line_shows_volume_hint_without_scrub() {
         let mut audio = crate::audio::NullAudioEngine::new();
@@ -4529,6 +5592,54 @@ mod tests {
         assert_eq!(message.as_deref(), Some("Now playing @host local queue."));
     }
 
+    #[test]
+    fn participant_rows_show_ping_delay_and_reported_drift() {
+        let mut session = OnlineSession::host("host");
+        session.participants.push(crate::online::Participant {
+            nickname: String::from("listener"),
+            is_local: false,
+            is_host: false,
+            ping_ms: 35,
+            manual_extra_delay_ms: 40,
+            auto_ping_delay: true,
+            last_drift_ms: Some(-12),
+        });
+
+        assert_eq!(
+            participant_line(&session.participants[1], &session),
+            "listener                   35ms        40/75ms    -12ms"
+        );
+        assert!(participant_line(&session.participants[0], &session).ends_with("  -"));
+    }
+
+    #[test]
+    fn chat_lines_wrap_to_width_and_strip_control_characters() {
+        let log = std::collections::VecDeque::from([ChatMessage {
+            from: String::from("dj\u{202E}"),
+            message: String::from("hello\u{7} there world"),
+        }]);
+        let rows: Vec<String> = chat_lines(&log, 10, &palette(Theme::Dark))
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(rows, ["dj: hello ", "there worl", "d"]);
+    }
+
+    #[test]
+    fn shared_queue_window_clamps_scroll_to_last_page() {
+        assert_eq!(shared_queue_window(20, 0, 5), (0, 5));
+        assert_eq!(shared_queue_window(20, 7, 5), (7, 12));
+        assert_eq!(shared_queue_window(20, 19, 5), (15, 20));
+        assert_eq!(shared_queue_window(3, 2, 5), (0, 3));
+        assert_eq!(shared_queue_window(0, 4, 5), (0, 0));
+    }
+
     #[test]
     fn shared_queue_waiting_message_hidden_when_current_is_shared_head() {
         let mut session = OnlineSession::host("host");